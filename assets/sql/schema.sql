@@ -16,6 +16,22 @@ CREATE TABLE workflow_templates (
     created_at DATETIME NOT NULL,
     updated_at DATETIME NOT NULL
 );
+CREATE TABLE workflow_template_revisions (
+    template_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    dsl_definition TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    published_at DATETIME,
+    PRIMARY KEY (template_id, revision)
+);
+CREATE TABLE workflow_template_aliases (
+    template_id TEXT NOT NULL,
+    alias TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    updated_at DATETIME NOT NULL,
+    PRIMARY KEY (template_id, alias)
+);
 CREATE TABLE workflow_executions (
     run_id TEXT PRIMARY KEY,
     workflow_id TEXT NOT NULL,
@@ -36,8 +52,9 @@ CREATE TABLE workflow_executions (
     search_attrs TEXT,
     context_snapshot TEXT,
     version INTEGER NOT NULL
-);
+, parent_run_id TEXT, template_revision INTEGER);
 CREATE INDEX idx_workflow_executions_shard_status ON workflow_executions (shard_id, status);
+CREATE INDEX idx_workflow_executions_parent ON workflow_executions (parent_run_id);
 CREATE TABLE workflow_events (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     run_id TEXT NOT NULL,
@@ -54,7 +71,6 @@ CREATE TABLE workflow_events (
     timestamp DATETIME NOT NULL,
     archived BOOLEAN NOT NULL DEFAULT FALSE
 );
-CREATE TABLE sqlite_sequence(name,seq);
 CREATE INDEX idx_wf_events_run_event ON workflow_events(run_id, event_id);
 CREATE INDEX idx_wf_events_shard_run_event ON workflow_events(shard_id, run_id, event_id);
 CREATE TABLE workflow_states (
@@ -73,7 +89,7 @@ CREATE TABLE workflow_states (
     created_at DATETIME NOT NULL,
     updated_at DATETIME NOT NULL,
     version INTEGER NOT NULL
-);
+, attempts INTEGER NOT NULL DEFAULT 0, mapping_trace TEXT);
 CREATE INDEX idx_workflow_states_run_id ON workflow_states (run_id);
 CREATE INDEX idx_workflow_states_status ON workflow_states (status);
 CREATE TABLE activity_tasks (
@@ -107,8 +123,11 @@ CREATE TABLE timers (
     fire_at DATETIME NOT NULL,
     status TEXT NOT NULL,
     version INTEGER NOT NULL,
-    state_name TEXT NOT NULL
-, payload TEXT, created_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00', updated_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00');
+    state_name TEXT,
+    payload TEXT,
+    created_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00',
+    updated_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00'
+);
 CREATE INDEX idx_timers_run ON timers(run_id, fire_at);
 CREATE TABLE workflow_visibility (
     run_id TEXT PRIMARY KEY,
@@ -145,11 +164,35 @@ CREATE TABLE queue_tasks (
     
     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-, resource TEXT NOT NULL DEFAULT '', priority INTEGER, timeout_seconds INTEGER);
+, resource TEXT NOT NULL DEFAULT '', priority INTEGER, timeout_seconds INTEGER, heartbeat_seconds INTEGER, last_heartbeat_at DATETIME, task_queue TEXT NOT NULL DEFAULT '', root_run_id TEXT NOT NULL DEFAULT '', template_id TEXT, worker_id TEXT);
 CREATE INDEX idx_queue_tasks_status ON queue_tasks(status);
 CREATE INDEX idx_queue_tasks_run_id ON queue_tasks(run_id);
 CREATE INDEX idx_queue_tasks_next_retry_at ON queue_tasks(next_retry_at);
 CREATE INDEX idx_queue_tasks_updated_at ON queue_tasks(updated_at);
+CREATE INDEX idx_queue_tasks_queue_status ON queue_tasks(task_queue, status);
+CREATE INDEX idx_queue_tasks_root_run_id ON queue_tasks(root_run_id);
+CREATE INDEX idx_queue_tasks_worker_status ON queue_tasks(worker_id, status);
+CREATE TABLE outbox_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    published_at DATETIME
+);
+CREATE INDEX idx_outbox_unpublished ON outbox_messages(published_at, created_at);
+CREATE TABLE workers (
+    worker_id TEXT PRIMARY KEY,
+    capabilities TEXT NOT NULL DEFAULT '[]',
+    task_queues TEXT NOT NULL DEFAULT '[]',
+    version TEXT,
+    concurrency INTEGER NOT NULL DEFAULT 1,
+    in_flight INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'active',
+    registered_at DATETIME NOT NULL,
+    last_seen_at DATETIME NOT NULL
+);
+CREATE INDEX idx_workers_status_last_seen ON workers(status, last_seen_at);
 
 
 INSERT INTO workflow_templates (
@@ -221,4 +264,8 @@ INSERT INTO workflow_templates (
     1,
     CURRENT_TIMESTAMP,
     CURRENT_TIMESTAMP
-);
\ No newline at end of file
+);
+
+INSERT INTO workflow_template_revisions (template_id, revision, dsl_definition, status, created_at, published_at)
+SELECT template_id, version, dsl_definition, 'PUBLISHED', created_at, updated_at
+FROM workflow_templates;
@@ -10,7 +10,8 @@ pub async fn resolve_token(
 ) -> Result<TokenResult, AuthError> {
     let input = ctx.to_json();
     let value = MappingEngine::apply(spec.fields.clone(), &input)
-        .map_err(|e| AuthError::MappingError(e.to_string()))?;
+        .map_err(|e| AuthError::MappingError(e.to_string()))?
+        .output;
 
         let field_map: HashMap<String, Value> = value.as_object()
         .cloned()
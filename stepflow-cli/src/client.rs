@@ -0,0 +1,85 @@
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::config::Profile;
+
+/// 网关 `/v1/*` 接口的 HTTP 客户端
+pub struct GatewayClient {
+    http: Client,
+    base_url: String,
+    profile: Profile,
+}
+
+impl GatewayClient {
+    pub fn new(profile: Profile) -> Result<Self> {
+        let http = Client::builder()
+            .build()
+            .context("Failed to build HTTP client")?;
+        let base_url = format!("{}/v1", profile.gateway_url.trim_end_matches('/'));
+        Ok(Self { http, base_url, profile })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let res = self.send(self.request(Method::GET, path).query(query)).await?;
+        decode(res).await
+    }
+
+    pub async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        let res = self.send(self.request(Method::POST, path).json(body)).await?;
+        decode(res).await
+    }
+
+    pub async fn put<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        let res = self.send(self.request(Method::PUT, path).json(body)).await?;
+        decode(res).await
+    }
+
+    /// 不关心响应体的 PUT（如 `/queue_tasks/:id` 只返回 200）
+    pub async fn put_empty<B: Serialize>(&self, path: &str, body: &B) -> Result<()> {
+        self.send(self.request(Method::PUT, path).json(body)).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, path)).await?;
+        Ok(())
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self.http.request(method, format!("{}{}", self.base_url, path));
+        match (&self.profile.token, &self.profile.username) {
+            (Some(token), _) => req.bearer_auth(token),
+            (None, Some(user)) => req.basic_auth(user, self.profile.password.as_ref()),
+            (None, None) => req,
+        }
+    }
+
+    /// 发送请求；非 2xx 时把网关返回的错误正文带进错误信息
+    async fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let res = req
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach gateway at {}", self.base_url))?;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            if body.trim().is_empty() {
+                bail!("Gateway returned {}", status);
+            }
+            bail!("Gateway returned {}: {}", status, body.trim());
+        }
+        Ok(res)
+    }
+}
+
+async fn decode<T: DeserializeOwned>(res: Response) -> Result<T> {
+    let url = res.url().path().to_string();
+    res.json()
+        .await
+        .with_context(|| format!("Invalid response from {}", url))
+}
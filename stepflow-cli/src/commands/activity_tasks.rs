@@ -0,0 +1,59 @@
+use anyhow::Result;
+use clap::Subcommand;
+use stepflow_dto::dto::activity_task::ActivityTaskDto;
+
+use super::Context;
+use crate::output::{cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum ActivityTaskCommand {
+    /// 分页列出活动任务
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+        #[arg(long, default_value_t = 0)]
+        offset: i64,
+    },
+    /// 查看活动任务详情
+    Get { task_token: String },
+    /// 列出某个执行的活动任务
+    ByRun { run_id: String },
+}
+
+pub async fn run(ctx: &Context, cmd: ActivityTaskCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        ActivityTaskCommand::List { limit, offset } => {
+            let query = [("limit", limit.to_string()), ("offset", offset.to_string())];
+            let list: Vec<ActivityTaskDto> = client.get("/activity_tasks", &query).await?;
+            print_list(ctx.output, &list)
+        }
+        ActivityTaskCommand::Get { task_token } => {
+            let task: ActivityTaskDto = client.get(&format!("/activity_tasks/{task_token}"), &[]).await?;
+            print_one(ctx.output, &task)
+        }
+        ActivityTaskCommand::ByRun { run_id } => {
+            let list: Vec<ActivityTaskDto> =
+                client.get(&format!("/activity_tasks/by-run/{run_id}"), &[]).await?;
+            print_list(ctx.output, &list)
+        }
+    }
+}
+
+impl TableRow for ActivityTaskDto {
+    fn headers() -> &'static [&'static str] {
+        &["TASK TOKEN", "RUN ID", "ACTIVITY", "STATUS", "ATTEMPT", "SCHEDULED AT", "ERROR"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.task_token.clone(),
+            self.run_id.clone(),
+            self.activity_type.clone(),
+            self.status.clone(),
+            format!("{}/{}", self.attempt, self.max_attempts),
+            time_cell(Some(&self.scheduled_at)),
+            cell(self.error.as_ref()),
+        ]
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use std::path::Path;
+
+use crate::{
+    config::{CliConfig, Profile, DEFAULT_PROFILE},
+    output::{cell, print_json, render_table, OutputFormat},
+};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// 列出配置文件中的 profile
+    List,
+    /// 新增或修改 profile
+    Set {
+        name: String,
+        /// 网关根地址，如 http://127.0.0.1:3000
+        #[arg(long)]
+        gateway_url: Option<String>,
+        /// Bearer token
+        #[arg(long)]
+        token: Option<String>,
+        /// Basic 认证用户名
+        #[arg(long)]
+        username: Option<String>,
+        /// Basic 认证密码
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// 设为默认 profile
+    Use { name: String },
+    /// 删除 profile
+    Remove { name: String },
+}
+
+/// 配置命令只读写本地文件，不访问网关
+pub fn run(path: &Path, output: OutputFormat, cmd: ConfigCommand) -> Result<()> {
+    let mut config = CliConfig::load(path)?;
+    match cmd {
+        ConfigCommand::List => {
+            if output == OutputFormat::Json {
+                return print_json(&config);
+            }
+            let current = config.current_profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            let rows = config.profiles.iter().map(|(name, p)| {
+                vec![
+                    if name == current { format!("* {name}") } else { name.clone() },
+                    p.gateway_url.clone(),
+                    auth_kind(p).into(),
+                    cell(p.username.as_ref()),
+                ]
+            });
+            println!("{}", render_table(&["PROFILE", "GATEWAY URL", "AUTH", "USERNAME"], rows));
+            return Ok(());
+        }
+        ConfigCommand::Set { name, gateway_url, token, username, password } => {
+            let profile = config.profiles.entry(name.clone()).or_default();
+            if let Some(url) = gateway_url {
+                profile.gateway_url = url;
+            }
+            if token.is_some() {
+                profile.token = token;
+            }
+            if username.is_some() {
+                profile.username = username;
+            }
+            if password.is_some() {
+                profile.password = password;
+            }
+            println!("Saved profile '{name}' to {}", path.display());
+        }
+        ConfigCommand::Use { name } => {
+            if !config.profiles.contains_key(&name) {
+                bail!("Profile '{}' not found in config", name);
+            }
+            println!("Switched to profile '{name}'");
+            config.current_profile = Some(name);
+        }
+        ConfigCommand::Remove { name } => {
+            if config.profiles.remove(&name).is_none() {
+                bail!("Profile '{}' not found in config", name);
+            }
+            if config.current_profile.as_deref() == Some(name.as_str()) {
+                config.current_profile = None;
+            }
+            println!("Removed profile '{name}'");
+        }
+    }
+    config.save(path)
+}
+
+fn auth_kind(profile: &Profile) -> &'static str {
+    match (&profile.token, &profile.username) {
+        (Some(_), _) => "token",
+        (None, Some(_)) => "basic",
+        (None, None) => "none",
+    }
+}
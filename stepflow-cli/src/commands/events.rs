@@ -0,0 +1,124 @@
+use anyhow::Result;
+use clap::Subcommand;
+use std::time::Duration;
+use stepflow_dto::dto::{
+    execution::{self, ExecDto},
+    workflow_event::WorkflowEventDto,
+};
+
+use super::Context;
+use crate::output::{cell, print_list, OutputFormat, TableRow};
+
+/// tail 时每次拉取的事件数
+const TAIL_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Subcommand)]
+pub enum EventCommand {
+    /// 分页列出执行的事件
+    List {
+        run_id: String,
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+        #[arg(long, default_value_t = 0)]
+        offset: i64,
+    },
+    /// 按顺序输出执行的全部事件；`--follow` 时持续输出新事件直到执行结束
+    Tail {
+        run_id: String,
+        #[arg(short, long)]
+        follow: bool,
+        /// 轮询间隔（毫秒）
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+}
+
+pub async fn run(ctx: &Context, cmd: EventCommand) -> Result<()> {
+    match cmd {
+        EventCommand::List { run_id, limit, offset } => {
+            let list = fetch_page(ctx, &run_id, limit, offset).await?;
+            print_list(ctx.output, &list)
+        }
+        EventCommand::Tail { run_id, follow, interval_ms } => {
+            tail(ctx, &run_id, follow, Duration::from_millis(interval_ms)).await
+        }
+    }
+}
+
+async fn fetch_page(ctx: &Context, run_id: &str, limit: i64, offset: i64) -> Result<Vec<WorkflowEventDto>> {
+    ctx.client
+        .get(
+            &format!("/workflow_events/run/{run_id}"),
+            &[("limit", limit.to_string()), ("offset", offset.to_string())],
+        )
+        .await
+}
+
+/// 事件按 event_id 升序返回，用已输出的条数作为下一页的 offset
+async fn tail(ctx: &Context, run_id: &str, follow: bool, interval: Duration) -> Result<()> {
+    let mut offset = 0;
+    loop {
+        // 先取执行状态再拉事件：执行已结束时，本轮拉完即为全部事件；暂停的执行恢复后还会继续产生事件
+        let finished = !follow || {
+            let exec: ExecDto = ctx.client.get(&format!("/executions/{run_id}"), &[]).await?;
+            execution::is_closed(&exec.status)
+        };
+
+        loop {
+            let page = fetch_page(ctx, run_id, TAIL_PAGE_SIZE, offset).await?;
+            for event in &page {
+                println!("{}", format_event(ctx.output, event)?);
+            }
+            offset += page.len() as i64;
+            if (page.len() as i64) < TAIL_PAGE_SIZE {
+                break;
+            }
+        }
+
+        if finished {
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// tail 的单行输出：表格模式为对齐的文本行，JSON 模式为每行一个事件（NDJSON）
+pub fn format_event(format: OutputFormat, event: &WorkflowEventDto) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => serde_json::to_string(event)?,
+        OutputFormat::Table => {
+            let state = match (&event.state_id, &event.state_type) {
+                (Some(id), Some(ty)) => format!("{id} ({ty})"),
+                (id, _) => cell(id.as_ref()),
+            };
+            let line = format!(
+                "{} #{:<4} {:<24} {}",
+                event.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+                event.event_id,
+                event.event_type,
+                state,
+            );
+            match &event.attributes {
+                Some(attrs) => format!("{line} {attrs}"),
+                None => line,
+            }
+        }
+    })
+}
+
+impl TableRow for WorkflowEventDto {
+    fn headers() -> &'static [&'static str] {
+        &["EVENT ID", "TYPE", "STATE", "STATE TYPE", "TIMESTAMP", "ATTRIBUTES"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.event_id.to_string(),
+            self.event_type.clone(),
+            cell(self.state_id.as_ref()),
+            cell(self.state_type.as_ref()),
+            self.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            cell(self.attributes.as_ref()),
+        ]
+    }
+}
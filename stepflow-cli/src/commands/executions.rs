@@ -0,0 +1,150 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use serde_json::Value;
+use std::path::PathBuf;
+use stepflow_dto::dto::execution::{ExecDto, ExecStart, ExecStop};
+
+use super::{read_optional_document, Context};
+use crate::output::{cell, json_cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum ExecutionCommand {
+    /// 启动执行：运行已有模板（`--template`）或 DSL 文件（`--file`）
+    Start {
+        /// 模板 ID
+        #[arg(short, long, conflicts_with = "file")]
+        template: Option<String>,
+        /// 模板修订号，默认最新发布的修订
+        #[arg(long, requires = "template", conflicts_with = "alias")]
+        revision: Option<i64>,
+        /// 模板别名（如 `stable`）
+        #[arg(long, requires = "template")]
+        alias: Option<String>,
+        /// 直接运行的 DSL 文件（JSON / YAML，`-` 表示 stdin）
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+        /// 初始上下文文件（JSON / YAML，`-` 表示 stdin）
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// 以 DEFERRED 模式启动，立即返回 run_id；默认 INLINE 同步运行到结束
+        #[arg(long)]
+        deferred: bool,
+    },
+    /// 列出执行
+    List {
+        /// 只列出该状态的执行（如 RUNNING、FAILED）
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+        #[arg(long, default_value_t = 0)]
+        offset: i64,
+    },
+    /// 查看执行详情
+    Get { run_id: String },
+    /// 列出子执行
+    Children { run_id: String },
+    /// 向执行发送信号
+    Signal {
+        run_id: String,
+        name: String,
+        /// 信号 payload 文件（JSON / YAML，`-` 表示 stdin）
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// 取消执行（运行 cleanup 状态）
+    Cancel {
+        run_id: String,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// 强制终止执行
+    Terminate {
+        run_id: String,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+pub async fn run(ctx: &Context, cmd: ExecutionCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        ExecutionCommand::Start { template, revision, alias, file, input, deferred } => {
+            let dsl = read_optional_document(file.as_ref())?;
+            if template.is_none() && dsl.is_none() {
+                bail!("Either --template or --file is required");
+            }
+            let body = ExecStart {
+                mode: if deferred { "DEFERRED" } else { "INLINE" }.into(),
+                template_id: template,
+                template_revision: revision,
+                template_alias: alias,
+                dsl,
+                init_ctx: read_optional_document(input.as_ref())?,
+            };
+            let exec: ExecDto = client.post("/executions", &body).await?;
+            print_one(ctx.output, &exec)
+        }
+        ExecutionCommand::List { status, limit, offset } => {
+            let mut query = vec![("limit", limit.to_string()), ("offset", offset.to_string())];
+            let path = match status {
+                Some(status) => {
+                    query.push(("status", status));
+                    "/executions/by_status"
+                }
+                None => "/executions",
+            };
+            let list: Vec<ExecDto> = client.get(path, &query).await?;
+            print_list(ctx.output, &list)
+        }
+        ExecutionCommand::Get { run_id } => {
+            let exec: ExecDto = client.get(&format!("/executions/{run_id}"), &[]).await?;
+            print_one(ctx.output, &exec)
+        }
+        ExecutionCommand::Children { run_id } => {
+            let list: Vec<ExecDto> = client.get(&format!("/executions/{run_id}/children"), &[]).await?;
+            print_list(ctx.output, &list)
+        }
+        ExecutionCommand::Signal { run_id, name, input } => {
+            let payload = read_optional_document(input.as_ref())?.unwrap_or(Value::Null);
+            let exec: ExecDto = client
+                .post(&format!("/executions/{run_id}/signals/{name}"), &payload)
+                .await?;
+            print_one(ctx.output, &exec)
+        }
+        ExecutionCommand::Cancel { run_id, reason } => {
+            let exec: ExecDto = client
+                .post(&format!("/executions/{run_id}/cancel"), &ExecStop { reason })
+                .await?;
+            print_one(ctx.output, &exec)
+        }
+        ExecutionCommand::Terminate { run_id, reason } => {
+            let exec: ExecDto = client
+                .post(&format!("/executions/{run_id}/terminate"), &ExecStop { reason })
+                .await?;
+            print_one(ctx.output, &exec)
+        }
+    }
+}
+
+impl TableRow for ExecDto {
+    fn headers() -> &'static [&'static str] {
+        &["RUN ID", "MODE", "STATUS", "TEMPLATE", "STARTED AT", "FINISHED AT", "RESULT"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let template = match (&self.template_id, self.template_revision) {
+            (Some(id), Some(rev)) => format!("{id}@{rev}"),
+            (id, _) => cell(id.as_ref()),
+        };
+        vec![
+            self.run_id.clone(),
+            self.mode.clone(),
+            self.status.clone(),
+            template,
+            time_cell(Some(&self.started_at)),
+            time_cell(self.finished_at.as_ref()),
+            json_cell(self.result.as_ref()),
+        ]
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use clap::Subcommand;
+use stepflow_dto::dto::match_stats::MatchStats;
+
+use super::Context;
+use crate::output::{print_list, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum MatchCommand {
+    /// 查看各队列的待处理任务数、等待中的 worker 数与最近轮询的 worker
+    Stats,
+}
+
+pub async fn run(ctx: &Context, cmd: MatchCommand) -> Result<()> {
+    match cmd {
+        MatchCommand::Stats => {
+            let stats: Vec<MatchStats> = ctx.client.get("/match/stats", &[]).await?;
+            print_list(ctx.output, &stats)
+        }
+    }
+}
+
+impl TableRow for MatchStats {
+    fn headers() -> &'static [&'static str] {
+        &["QUEUE", "PENDING TASKS", "WAITING WORKERS", "POLLERS"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let pollers: Vec<&str> = self.pollers.iter().map(|p| p.worker_id.as_str()).collect();
+        vec![
+            self.queue.clone(),
+            self.pending_tasks.to_string(),
+            self.waiting_workers.to_string(),
+            pollers.join(", "),
+        ]
+    }
+}
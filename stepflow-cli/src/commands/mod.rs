@@ -0,0 +1,58 @@
+pub mod activity_tasks;
+pub mod config;
+pub mod events;
+pub mod executions;
+pub mod r#match;
+pub mod queue_tasks;
+pub mod templates;
+pub mod timers;
+pub mod workers;
+
+use anyhow::{Context as _, Result};
+use serde_json::Value;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::{client::GatewayClient, output::OutputFormat};
+
+/// 子命令共享的运行上下文
+pub struct Context {
+    pub client: GatewayClient,
+    pub output: OutputFormat,
+}
+
+/// 读取 JSON / YAML 文档；路径为 `-` 时从 stdin 读取
+///
+/// `.yaml` / `.yml` 按 YAML 解析，其余先按 JSON、失败再按 YAML 解析
+pub fn read_document(path: &Path) -> Result<Value> {
+    let (raw, yaml_only) = if path == Path::new("-") {
+        let mut raw = String::new();
+        std::io::stdin()
+            .read_to_string(&mut raw)
+            .context("Failed to read stdin")?;
+        (raw, false)
+    } else {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let yaml_only = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml" | "yml")
+        );
+        (raw, yaml_only)
+    };
+    parse_document(&raw, yaml_only).with_context(|| format!("Invalid JSON/YAML in {}", path.display()))
+}
+
+pub fn parse_document(raw: &str, yaml_only: bool) -> Result<Value> {
+    if !yaml_only && let Ok(value) = serde_json::from_str(raw) {
+        return Ok(value);
+    }
+    Ok(serde_yaml::from_str(raw)?)
+}
+
+/// 可选的输入文件：省略时返回 None
+pub fn read_optional_document(path: Option<&PathBuf>) -> Result<Option<Value>> {
+    path.map(|p| read_document(p)).transpose()
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use chrono::Utc;
+use clap::Subcommand;
+use stepflow_dto::dto::queue_task::{QueueTaskDto, UpdateQueueTaskDto};
+
+use super::Context;
+use crate::output::{cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum QueueTaskCommand {
+    /// 按状态列出队列任务
+    List {
+        /// 任务状态（pending / processing / completed / failed …）
+        #[arg(long, default_value = "pending")]
+        status: String,
+        #[arg(long, default_value_t = 100)]
+        limit: i64,
+        #[arg(long, default_value_t = 0)]
+        offset: i64,
+    },
+    /// 列出已到重试时间的任务
+    Due {
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
+    /// 查看队列任务详情
+    Get { task_id: String },
+    /// 把任务重置为 pending 并立即可被认领
+    Retry { task_id: String },
+    /// 删除队列任务
+    Delete { task_id: String },
+}
+
+pub async fn run(ctx: &Context, cmd: QueueTaskCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        QueueTaskCommand::List { status, limit, offset } => {
+            let query = [("status", status), ("limit", limit.to_string()), ("offset", offset.to_string())];
+            let list: Vec<QueueTaskDto> = client.get("/queue_tasks", &query).await?;
+            print_list(ctx.output, &list)
+        }
+        QueueTaskCommand::Due { limit } => {
+            let before = Utc::now().naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string();
+            let query = [("before", before), ("limit", limit.to_string())];
+            let list: Vec<QueueTaskDto> = client.get("/queue_tasks/retry", &query).await?;
+            print_list(ctx.output, &list)
+        }
+        QueueTaskCommand::Get { task_id } => {
+            let task: QueueTaskDto = client.get(&format!("/queue_tasks/{task_id}"), &[]).await?;
+            print_one(ctx.output, &task)
+        }
+        QueueTaskCommand::Retry { task_id } => {
+            client.put_empty(&format!("/queue_tasks/{task_id}"), &retry_update()).await?;
+            let task: QueueTaskDto = client.get(&format!("/queue_tasks/{task_id}"), &[]).await?;
+            print_one(ctx.output, &task)
+        }
+        QueueTaskCommand::Delete { task_id } => {
+            client.delete(&format!("/queue_tasks/{task_id}")).await?;
+            println!("Deleted queue task {task_id}");
+            Ok(())
+        }
+    }
+}
+
+/// 手动重试：回到 pending，清掉重试等待与失败信息，保留 attempts 计数
+pub fn retry_update() -> UpdateQueueTaskDto {
+    UpdateQueueTaskDto {
+        status: Some("pending".into()),
+        error_message: Some(None),
+        next_retry_at: Some(None),
+        processing_at: Some(None),
+        failed_at: Some(None),
+        ..Default::default()
+    }
+}
+
+impl TableRow for QueueTaskDto {
+    fn headers() -> &'static [&'static str] {
+        &["TASK ID", "RUN ID", "STATE", "RESOURCE", "QUEUE", "STATUS", "ATTEMPTS", "NEXT RETRY AT", "ERROR"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.task_id.clone(),
+            self.run_id.clone(),
+            self.state_name.clone(),
+            self.resource.clone(),
+            self.task_queue.clone(),
+            self.status.clone(),
+            format!("{}/{}", self.attempts, self.max_attempts),
+            time_cell(self.next_retry_at.as_ref()),
+            cell(self.error_message.as_ref()),
+        ]
+    }
+}
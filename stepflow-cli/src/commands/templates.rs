@@ -0,0 +1,160 @@
+use anyhow::Result;
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+use stepflow_dto::dto::template::{
+    SetAliasRequest, TemplateAliasDto, TemplateDto, TemplateRevisionDto, TemplateUpsert,
+};
+
+use super::{read_document, Context};
+use crate::output::{cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum TemplateCommand {
+    /// 列出所有模板
+    List,
+    /// 查看模板详情
+    Get { id: String },
+    /// 从 JSON / YAML 文件创建模板
+    Create {
+        /// DSL 文件（`-` 表示 stdin）
+        #[arg(short, long)]
+        file: PathBuf,
+        /// 模板名称，默认取文件名
+        #[arg(long)]
+        name: Option<String>,
+        /// 保存为草稿，不立即发布
+        #[arg(long)]
+        draft: bool,
+    },
+    /// 用 JSON / YAML 文件更新模板（生成新修订）
+    Update {
+        id: String,
+        /// DSL 文件（`-` 表示 stdin）
+        #[arg(short, long)]
+        file: PathBuf,
+        /// 新名称，默认保持不变
+        #[arg(long)]
+        name: Option<String>,
+        /// 保存为草稿，不立即发布
+        #[arg(long)]
+        draft: bool,
+    },
+    /// 删除模板
+    Delete { id: String },
+    /// 列出模板的修订
+    Revisions { id: String },
+    /// 发布草稿修订
+    Publish { id: String, revision: i64 },
+    /// 列出模板别名
+    Aliases { id: String },
+    /// 把别名指向某个已发布修订
+    SetAlias { id: String, alias: String, revision: i64 },
+}
+
+pub async fn run(ctx: &Context, cmd: TemplateCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        TemplateCommand::List => {
+            let list: Vec<TemplateDto> = client.get("/templates", &[]).await?;
+            print_list(ctx.output, &list)
+        }
+        TemplateCommand::Get { id } => {
+            let tpl: TemplateDto = client.get(&format!("/templates/{id}"), &[]).await?;
+            print_one(ctx.output, &tpl)
+        }
+        TemplateCommand::Create { file, name, draft } => {
+            let body = TemplateUpsert {
+                name: name.unwrap_or_else(|| file_stem(&file)),
+                dsl: read_document(&file)?,
+                draft,
+            };
+            let tpl: TemplateDto = client.post("/templates", &body).await?;
+            print_one(ctx.output, &tpl)
+        }
+        TemplateCommand::Update { id, file, name, draft } => {
+            let name = match name {
+                Some(name) => name,
+                None => client.get::<TemplateDto>(&format!("/templates/{id}"), &[]).await?.name,
+            };
+            let body = TemplateUpsert { name, dsl: read_document(&file)?, draft };
+            let tpl: TemplateDto = client.put(&format!("/templates/{id}"), &body).await?;
+            print_one(ctx.output, &tpl)
+        }
+        TemplateCommand::Delete { id } => {
+            client.delete(&format!("/templates/{id}")).await?;
+            println!("Deleted template {id}");
+            Ok(())
+        }
+        TemplateCommand::Revisions { id } => {
+            let list: Vec<TemplateRevisionDto> =
+                client.get(&format!("/templates/{id}/revisions"), &[]).await?;
+            print_list(ctx.output, &list)
+        }
+        TemplateCommand::Publish { id, revision } => {
+            let rev: TemplateRevisionDto = client
+                .post(&format!("/templates/{id}/revisions/{revision}/publish"), &())
+                .await?;
+            print_one(ctx.output, &rev)
+        }
+        TemplateCommand::Aliases { id } => {
+            let list: Vec<TemplateAliasDto> =
+                client.get(&format!("/templates/{id}/aliases"), &[]).await?;
+            print_list(ctx.output, &list)
+        }
+        TemplateCommand::SetAlias { id, alias, revision } => {
+            let body = SetAliasRequest { revision };
+            let alias: TemplateAliasDto =
+                client.put(&format!("/templates/{id}/aliases/{alias}"), &body).await?;
+            print_one(ctx.output, &alias)
+        }
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| *s != "-")
+        .unwrap_or("untitled")
+        .to_string()
+}
+
+impl TableRow for TemplateDto {
+    fn headers() -> &'static [&'static str] {
+        &["ID", "NAME", "LATEST REVISION", "UPDATED AT"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.id.clone(),
+            self.name.clone(),
+            cell(self.latest_revision),
+            time_cell(Some(&self.updated_at)),
+        ]
+    }
+}
+
+impl TableRow for TemplateRevisionDto {
+    fn headers() -> &'static [&'static str] {
+        &["REVISION", "STATUS", "ALIASES", "CREATED AT", "PUBLISHED AT"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.revision.to_string(),
+            self.status.clone(),
+            self.aliases.join(", "),
+            time_cell(Some(&self.created_at)),
+            time_cell(self.published_at.as_ref()),
+        ]
+    }
+}
+
+impl TableRow for TemplateAliasDto {
+    fn headers() -> &'static [&'static str] {
+        &["ALIAS", "REVISION", "UPDATED AT"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.alias.clone(), self.revision.to_string(), time_cell(Some(&self.updated_at))]
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Subcommand;
+use stepflow_dto::dto::timer::TimerDto;
+
+use super::Context;
+use crate::output::{cell, json_cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum TimerCommand {
+    /// 列出在指定时间窗口内到期的定时器
+    List {
+        /// 从现在起的时间窗口（秒）；0 表示只列出已到期的定时器
+        #[arg(long, default_value_t = 3600)]
+        within_secs: i64,
+        #[arg(long, default_value_t = 100)]
+        limit: i64,
+    },
+    /// 查看定时器详情
+    Get { timer_id: String },
+}
+
+pub async fn run(ctx: &Context, cmd: TimerCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        TimerCommand::List { within_secs, limit } => {
+            let before = (Utc::now() + Duration::seconds(within_secs)).to_rfc3339();
+            let query = [("before", before), ("limit", limit.to_string())];
+            let list: Vec<TimerDto> = client.get("/timers", &query).await?;
+            print_list(ctx.output, &list)
+        }
+        TimerCommand::Get { timer_id } => {
+            let timer: TimerDto = client.get(&format!("/timers/{timer_id}"), &[]).await?;
+            print_one(ctx.output, &timer)
+        }
+    }
+}
+
+impl TableRow for TimerDto {
+    fn headers() -> &'static [&'static str] {
+        &["TIMER ID", "RUN ID", "STATE", "SHARD", "FIRE AT", "STATUS", "PAYLOAD"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.timer_id.clone(),
+            self.run_id.clone(),
+            cell(self.state_name.as_ref()),
+            self.shard_id.to_string(),
+            time_cell(Some(&self.fire_at)),
+            self.status.clone(),
+            json_cell(self.payload.as_ref()),
+        ]
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use clap::Subcommand;
+use serde_json::json;
+use stepflow_dto::dto::worker::WorkerInfoDto;
+
+use super::Context;
+use crate::output::{cell, print_list, print_one, time_cell, TableRow};
+
+#[derive(Debug, Subcommand)]
+pub enum WorkerCommand {
+    /// 列出已注册的 worker 及其存活状态与负载
+    List,
+    /// 查看 worker 详情
+    Get { worker_id: String },
+    /// 排空：不再给该 worker 派发新任务，已持有的任务照常完成
+    Drain { worker_id: String },
+    /// 取消排空，恢复派发
+    Resume { worker_id: String },
+    /// 注销 worker，其持有的任务退回队列
+    Deregister { worker_id: String },
+}
+
+pub async fn run(ctx: &Context, cmd: WorkerCommand) -> Result<()> {
+    let client = &ctx.client;
+    match cmd {
+        WorkerCommand::List => {
+            let list: Vec<WorkerInfoDto> = client.get("/workers", &[]).await?;
+            print_list(ctx.output, &list)
+        }
+        WorkerCommand::Get { worker_id } => {
+            let worker: WorkerInfoDto = client.get(&format!("/workers/{worker_id}"), &[]).await?;
+            print_one(ctx.output, &worker)
+        }
+        WorkerCommand::Drain { worker_id } => {
+            let worker: WorkerInfoDto = client.post(&format!("/workers/{worker_id}/drain"), &json!({})).await?;
+            print_one(ctx.output, &worker)
+        }
+        WorkerCommand::Resume { worker_id } => {
+            let worker: WorkerInfoDto = client.post(&format!("/workers/{worker_id}/resume"), &json!({})).await?;
+            print_one(ctx.output, &worker)
+        }
+        WorkerCommand::Deregister { worker_id } => {
+            client.delete(&format!("/workers/{worker_id}")).await?;
+            println!("Deregistered worker {worker_id}");
+            Ok(())
+        }
+    }
+}
+
+impl TableRow for WorkerInfoDto {
+    fn headers() -> &'static [&'static str] {
+        &["WORKER ID", "STATUS", "IN FLIGHT", "CONCURRENCY", "VERSION", "QUEUES", "LAST SEEN"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let queues: Vec<&str> = self
+            .capabilities
+            .iter()
+            .chain(&self.task_queues)
+            .map(String::as_str)
+            .collect();
+        vec![
+            self.worker_id.clone(),
+            self.status.clone(),
+            self.in_flight.to_string(),
+            self.concurrency.to_string(),
+            cell(self.version.as_ref()),
+            queues.join(", "),
+            time_cell(Some(&self.last_seen_at)),
+        ]
+    }
+}
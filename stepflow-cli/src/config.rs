@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}};
+
+/// 未配置任何 profile 时使用的网关地址（与网关默认 `GATEWAY_BIND` 一致）
+pub const DEFAULT_GATEWAY_URL: &str = "http://127.0.0.1:3000";
+
+/// 未指定 profile 且配置文件未设置 `current_profile` 时使用的 profile 名
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// CLI 配置文件（默认 `~/.stepflow/config.yaml`）
+///
+/// ```yaml
+/// current_profile: prod
+/// profiles:
+///   default:
+///     gateway_url: http://127.0.0.1:3000
+///   prod:
+///     gateway_url: https://stepflow.example.com
+///     token: xxxxx
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CliConfig {
+    /// 未通过 `--profile` / `STEPFLOW_PROFILE` 指定时使用的 profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// 单个网关连接配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// 网关根地址（不含 `/v1`）
+    pub gateway_url: String,
+    /// Bearer token；与 `username` / `password` 同时配置时优先使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Basic 认证用户名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Basic 认证密码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            gateway_url: DEFAULT_GATEWAY_URL.into(),
+            token: None,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+impl CliConfig {
+    /// 默认配置文件路径：`~/.stepflow/config.yaml`
+    pub fn default_path() -> Result<PathBuf> {
+        dirs::home_dir()
+            .map(|home| home.join(".stepflow").join("config.yaml"))
+            .ok_or_else(|| anyhow!("Cannot determine home directory, use --config"))
+    }
+
+    /// 读取配置文件；文件不存在时返回空配置
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        serde_yaml::from_str(&raw)
+            .with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// 写回配置文件（按需创建目录）
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let raw = serde_yaml::to_string(self)?;
+        fs::write(path, raw).with_context(|| format!("Failed to write config {}", path.display()))
+    }
+
+    /// 解析本次调用使用的 profile
+    ///
+    /// 优先级：显式指定的 profile → `current_profile` → `default`；
+    /// 显式指定但不存在时报错，隐式的 `default` 不存在时回退到本地网关
+    pub fn resolve(&self, requested: Option<&str>) -> Result<(String, Profile)> {
+        if let Some(name) = requested {
+            let profile = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| anyhow!("Profile '{}' not found in config", name))?;
+            return Ok((name.to_string(), profile.clone()));
+        }
+
+        let name = self.current_profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+        match self.profiles.get(name) {
+            Some(profile) => Ok((name.to_string(), profile.clone())),
+            None if self.current_profile.is_some() => {
+                Err(anyhow!("current_profile '{}' not found in config", name))
+            }
+            None => Ok((name.to_string(), Profile::default())),
+        }
+    }
+}
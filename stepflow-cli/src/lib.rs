@@ -0,0 +1,98 @@
+pub mod client;
+pub mod commands;
+pub mod config;
+pub mod output;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::{
+    client::GatewayClient,
+    commands::{
+        activity_tasks::ActivityTaskCommand, config::ConfigCommand, events::EventCommand,
+        executions::ExecutionCommand, queue_tasks::QueueTaskCommand, r#match::MatchCommand,
+        templates::TemplateCommand, timers::TimerCommand, workers::WorkerCommand, Context,
+    },
+    config::CliConfig,
+    output::OutputFormat,
+};
+
+/// Stepflow 命令行：通过网关 `/v1/*` 接口管理模板、执行与队列
+#[derive(Debug, Parser)]
+#[command(name = "stepflow", version)]
+pub struct Cli {
+    /// 使用的 profile，默认取配置文件中的 `current_profile`
+    #[arg(long, global = true, env = "STEPFLOW_PROFILE")]
+    pub profile: Option<String>,
+    /// 覆盖 profile 中的网关地址
+    #[arg(long, global = true, env = "STEPFLOW_GATEWAY_URL")]
+    pub gateway_url: Option<String>,
+    /// 配置文件路径，默认 `~/.stepflow/config.yaml`
+    #[arg(long, global = true, env = "STEPFLOW_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// 输出格式
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 工作流模板
+    #[command(subcommand, alias = "tpl")]
+    Templates(TemplateCommand),
+    /// 工作流执行
+    #[command(subcommand, alias = "exec")]
+    Executions(ExecutionCommand),
+    /// 执行事件
+    #[command(subcommand)]
+    Events(EventCommand),
+    /// 活动任务
+    #[command(subcommand)]
+    ActivityTasks(ActivityTaskCommand),
+    /// 队列任务
+    #[command(subcommand)]
+    QueueTasks(QueueTaskCommand),
+    /// 定时器
+    #[command(subcommand)]
+    Timers(TimerCommand),
+    /// 任务匹配服务
+    #[command(subcommand)]
+    Match(MatchCommand),
+    /// Worker 注册表
+    #[command(subcommand)]
+    Workers(WorkerCommand),
+    /// 本地 profile 配置
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let config_path = match cli.config {
+        Some(path) => path,
+        None => CliConfig::default_path()?,
+    };
+    if let Command::Config(cmd) = cli.command {
+        return commands::config::run(&config_path, cli.output, cmd);
+    }
+
+    let (_, mut profile) = CliConfig::load(&config_path)?.resolve(cli.profile.as_deref())?;
+    if let Some(url) = cli.gateway_url {
+        profile.gateway_url = url;
+    }
+    let ctx = Context { client: GatewayClient::new(profile)?, output: cli.output };
+
+    match cli.command {
+        Command::Templates(cmd) => commands::templates::run(&ctx, cmd).await,
+        Command::Executions(cmd) => commands::executions::run(&ctx, cmd).await,
+        Command::Events(cmd) => commands::events::run(&ctx, cmd).await,
+        Command::ActivityTasks(cmd) => commands::activity_tasks::run(&ctx, cmd).await,
+        Command::QueueTasks(cmd) => commands::queue_tasks::run(&ctx, cmd).await,
+        Command::Timers(cmd) => commands::timers::run(&ctx, cmd).await,
+        Command::Match(cmd) => commands::r#match::run(&ctx, cmd).await,
+        Command::Workers(cmd) => commands::workers::run(&ctx, cmd).await,
+        Command::Config(_) => unreachable!("handled above"),
+    }
+}
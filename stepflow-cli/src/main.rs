@@ -0,0 +1,11 @@
+use clap::Parser;
+use stepflow_cli::{run, Cli};
+
+#[tokio::main]
+async fn main() {
+    // 只输出错误链，不带 backtrace
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("Error: {e:#}");
+        std::process::exit(1);
+    }
+}
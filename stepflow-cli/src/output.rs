@@ -0,0 +1,79 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL_CONDENSED, ContentArrangement, Table};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 命令输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 人类可读的表格
+    #[default]
+    Table,
+    /// 原样输出网关返回的 JSON
+    Json,
+}
+
+/// 可以按表格输出的记录
+pub trait TableRow {
+    fn headers() -> &'static [&'static str];
+    fn row(&self) -> Vec<String>;
+}
+
+/// 输出一组记录
+pub fn print_list<T: TableRow + Serialize>(format: OutputFormat, items: &[T]) -> Result<()> {
+    match format {
+        OutputFormat::Json => print_json(&items),
+        OutputFormat::Table => {
+            println!("{}", render_table(T::headers(), items.iter().map(TableRow::row)));
+            Ok(())
+        }
+    }
+}
+
+/// 输出单条记录
+pub fn print_one<T: TableRow + Serialize>(format: OutputFormat, item: &T) -> Result<()> {
+    match format {
+        OutputFormat::Json => print_json(item),
+        OutputFormat::Table => print_list(format, std::slice::from_ref(item)),
+    }
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+pub fn render_table(headers: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(headers.iter().copied());
+    for row in rows {
+        table.add_row(row);
+    }
+    table
+}
+
+/// 表格单元格：None 显示为 `-`
+pub fn cell<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "-".into())
+}
+
+pub fn time_cell(value: Option<&DateTime<Utc>>) -> String {
+    cell(value.map(|t| t.format("%Y-%m-%d %H:%M:%S")))
+}
+
+/// JSON 单元格：截断过长的内容，完整内容用 `--output json` 查看
+pub fn json_cell(value: Option<&Value>) -> String {
+    const MAX: usize = 60;
+    let Some(value) = value else { return "-".into() };
+    let raw = value.to_string();
+    if raw.chars().count() <= MAX {
+        raw
+    } else {
+        format!("{}…", raw.chars().take(MAX).collect::<String>())
+    }
+}
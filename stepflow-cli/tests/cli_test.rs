@@ -0,0 +1,196 @@
+use clap::Parser;
+use serde_json::json;
+use stepflow_cli::{
+    client::GatewayClient,
+    commands::{events, parse_document, queue_tasks::retry_update, Context},
+    config::{CliConfig, Profile, DEFAULT_GATEWAY_URL},
+    output::OutputFormat,
+    Cli, Command,
+};
+use stepflow_dto::dto::template::TemplateDto;
+use tempfile::tempdir;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn exec_body(status: &str) -> serde_json::Value {
+    json!({
+        "run_id": "run-1",
+        "mode": "DEFERRED",
+        "status": status,
+        "result": null,
+        "started_at": "2025-06-01T00:00:00Z",
+        "finished_at": null
+    })
+}
+
+#[test]
+fn test_profile_resolution() {
+    let config: CliConfig = serde_yaml::from_str(
+        r#"
+current_profile: prod
+profiles:
+  default:
+    gateway_url: http://127.0.0.1:3000
+  prod:
+    gateway_url: https://stepflow.example.com
+    token: secret
+"#,
+    )
+    .unwrap();
+
+    let (name, profile) = config.resolve(None).unwrap();
+    assert_eq!(name, "prod");
+    assert_eq!(profile.token.as_deref(), Some("secret"));
+
+    let (_, profile) = config.resolve(Some("default")).unwrap();
+    assert_eq!(profile.gateway_url, "http://127.0.0.1:3000");
+
+    assert!(config.resolve(Some("staging")).is_err());
+
+    // 没有配置文件时回退到本地网关
+    let (name, profile) = CliConfig::default().resolve(None).unwrap();
+    assert_eq!(name, "default");
+    assert_eq!(profile.gateway_url, DEFAULT_GATEWAY_URL);
+}
+
+#[test]
+fn test_config_save_and_load() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("nested").join("config.yaml");
+    assert!(CliConfig::load(&path).unwrap().profiles.is_empty());
+
+    let mut config = CliConfig::default();
+    config.profiles.insert(
+        "local".into(),
+        Profile { username: Some("admin".into()), password: Some("pw".into()), ..Default::default() },
+    );
+    config.current_profile = Some("local".into());
+    config.save(&path).unwrap();
+
+    let loaded = CliConfig::load(&path).unwrap();
+    assert_eq!(loaded.current_profile.as_deref(), Some("local"));
+    assert_eq!(loaded.profiles["local"], config.profiles["local"]);
+}
+
+#[test]
+fn test_parse_json_and_yaml_documents() {
+    let from_json = parse_document(r#"{"startAt": "A", "states": {}}"#, false).unwrap();
+    let from_yaml = parse_document("startAt: A\nstates: {}\n", false).unwrap();
+    assert_eq!(from_json, from_yaml);
+    assert_eq!(from_yaml, json!({ "startAt": "A", "states": {} }));
+}
+
+#[test]
+fn test_cli_parses_global_flags_after_subcommand() {
+    let cli = Cli::try_parse_from([
+        "stepflow", "executions", "start", "--template", "tpl-1", "--alias", "stable", "--deferred", "-o", "json",
+    ])
+    .unwrap();
+    assert_eq!(cli.output, OutputFormat::Json);
+    assert!(matches!(cli.command, Command::Executions(_)));
+
+    // --revision 与 --alias 互斥，--revision 需要 --template
+    assert!(Cli::try_parse_from([
+        "stepflow", "executions", "start", "--template", "t", "--revision", "2", "--alias", "stable",
+    ])
+    .is_err());
+    assert!(Cli::try_parse_from(["stepflow", "executions", "start", "--revision", "2"]).is_err());
+}
+
+#[test]
+fn test_retry_update_resets_task_to_pending() {
+    let body = serde_json::to_value(retry_update()).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "status": "pending",
+            "error_message": null,
+            "next_retry_at": null,
+            "processing_at": null,
+            "failed_at": null
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_client_sends_token_and_surfaces_gateway_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/templates/tpl-1"))
+        .and(header("authorization", "Bearer secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "tpl-1",
+            "name": "order",
+            "dsl": { "startAt": "A", "states": {} },
+            "latest_revision": 2,
+            "created_at": "2025-06-01T00:00:00Z",
+            "updated_at": "2025-06-02T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/templates"))
+        .and(body_json(json!({ "name": "bad", "dsl": {}, "draft": false })))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({ "error": "invalid dsl" })))
+        .mount(&server)
+        .await;
+
+    let client = GatewayClient::new(Profile {
+        gateway_url: format!("{}/", server.uri()),
+        token: Some("secret".into()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(client.base_url(), format!("{}/v1", server.uri()));
+
+    let tpl: TemplateDto = client.get("/templates/tpl-1", &[]).await.unwrap();
+    assert_eq!(tpl.latest_revision, Some(2));
+
+    let Err(err) = client
+        .post::<_, TemplateDto>("/templates", &json!({ "name": "bad", "dsl": {}, "draft": false }))
+        .await
+    else {
+        panic!("expected gateway error");
+    };
+    let msg = err.to_string();
+    assert!(msg.contains("400") && msg.contains("invalid dsl"), "{msg}");
+}
+
+#[tokio::test]
+async fn test_tail_follow_keeps_polling_paused_execution() {
+    let server = MockServer::start().await;
+    // 第一次查询时执行处于暂停，之后完成：暂停不算结束，tail 继续轮询
+    Mock::given(method("GET"))
+        .and(path("/v1/executions/run-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(exec_body("PAUSED")))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/executions/run-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(exec_body("COMPLETED")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/workflow_events/run/run-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&server)
+        .await;
+
+    let ctx = Context {
+        client: GatewayClient::new(Profile { gateway_url: server.uri(), ..Default::default() }).unwrap(),
+        output: OutputFormat::Json,
+    };
+    let tail = events::EventCommand::Tail { run_id: "run-1".into(), follow: true, interval_ms: 10 };
+    events::run(&ctx, tail).await.unwrap();
+
+    let status_polls = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == "/v1/executions/run-1")
+        .count();
+    assert_eq!(status_polls, 2);
+}
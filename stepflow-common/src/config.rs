@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use std::{env, fmt, path::PathBuf};
+use std::{collections::HashMap, env, fmt, path::PathBuf, str::FromStr};
 use dirs;
 
 /// 系统运行时环境（控制入口行为）
@@ -54,17 +54,70 @@ impl fmt::Display for StepflowExecMode {
     }
 }
 
+/// Worker 从网关获取任务的方式（轮询模式下）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerTransport {
+    /// HTTP 长轮询 `/v1/worker/poll`
+    Poll,
+    /// WebSocket `/v1/worker/stream`，网关按空闲槽位推送
+    Stream,
+}
+
+impl WorkerTransport {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "poll" | "polling" => Ok(Self::Poll),
+            "stream" | "ws" | "websocket" => Ok(Self::Stream),
+            other => Err(anyhow!("Unsupported worker transport: {} (use 'poll' or 'stream')", other)),
+        }
+    }
+}
+
+impl fmt::Display for WorkerTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerTransport::Poll => write!(f, "poll"),
+            WorkerTransport::Stream => write!(f, "stream"),
+        }
+    }
+}
+
 /// 通用配置结构：适用于 Engine / Worker / Gateway / FRB
 #[derive(Debug, Clone)]
 pub struct StepflowConfig {
     pub runtime: StepflowRuntime,
     pub exec_mode: StepflowExecMode,
     pub db_path: String,
+    /// 数据库连接 URL；`postgres://` / `postgresql://` 时使用 PostgreSQL，未设置时使用 `db_path` 下的 SQLite
+    pub database_url: Option<String>,
     pub worker_id: String,
     pub gateway_server_url: String,
     pub capabilities: Vec<String>,
+    /// 除 capabilities 对应的默认队列外，额外轮询的命名任务队列（Task 状态的 `taskQueue`）
+    pub task_queues: Vec<String>,
+    /// 任务每等待多少秒有效优先级 +1（0 = 不老化）
+    pub task_aging_secs: u64,
+    /// 队列限速（任务 / 秒），如 `http=5,gpu=0.5`
+    pub queue_rate_limits: HashMap<String, f64>,
+    /// 模板派发权重，如 `billing=3`（缺省 1）
+    pub template_weights: HashMap<String, u32>,
+    pub worker_transport: WorkerTransport,
+    /// 长轮询时长（秒）：无任务时网关挂起 poll 请求的最长时间
+    pub poll_wait_secs: u64,
     pub gateway_bind: String,
     pub concurrency: usize,
+    /// 定时器扫描间隔（毫秒）
+    pub timer_poll_interval_ms: u64,
+    /// 本实例负责的定时器分片（空 = 全部分片）
+    pub timer_shards: Vec<i64>,
+    /// 定时器分片总数：定时器按 run_id 哈希落入 `0..timer_shard_count`（各实例须一致）
+    pub timer_shard_count: u32,
+    /// 超时 / 心跳超时任务的回收扫描间隔（毫秒）
+    pub task_reap_interval_ms: u64,
+    /// worker 存活期限（秒）：超过未心跳视为失联，其持有的任务退回队列；worker 按三分之一周期发送存活心跳
+    pub worker_ttl_secs: u64,
+    /// 未发布 outbox 消息的补投扫描间隔（毫秒）
+    pub outbox_relay_interval_ms: u64,
 }
 
 impl StepflowConfig {
@@ -88,6 +141,11 @@ impl StepflowConfig {
             .trim()
             .to_string();
 
+        let database_url = env::var("STEPFLOW_DATABASE_URL")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
         let base_id = env::var("WORKER_ID").unwrap_or_else(|_| "tool-worker".into());
         let worker_id = format!("{}-{}", base_id.trim(), index);
 
@@ -103,6 +161,30 @@ impl StepflowConfig {
             .filter(|s| !s.is_empty())
             .collect();
 
+        let task_queues = env::var("WORKER_TASK_QUEUES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let task_aging_secs = env::var("TASK_AGING_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .unwrap_or(30);
+
+        let queue_rate_limits = parse_pairs(&env::var("QUEUE_RATE_LIMITS").unwrap_or_default());
+        let template_weights = parse_pairs(&env::var("TEMPLATE_WEIGHTS").unwrap_or_default());
+
+        let worker_transport = WorkerTransport::from_str(
+            &env::var("WORKER_TRANSPORT").unwrap_or_else(|_| "poll".into())
+        )?;
+
+        let poll_wait_secs = env::var("WORKER_POLL_WAIT_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .unwrap_or(30);
+
         let gateway_bind = env::var("GATEWAY_BIND")
             .unwrap_or_else(|_| "127.0.0.1:3000".into())
             .trim()
@@ -114,15 +196,64 @@ impl StepflowConfig {
             .filter(|&c| c > 0)
             .unwrap_or(4);
 
+        let timer_poll_interval_ms = env::var("TIMER_POLL_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&ms| ms > 0)
+            .unwrap_or(1000);
+
+        let task_reap_interval_ms = env::var("TASK_REAP_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&ms| ms > 0)
+            .unwrap_or(5000);
+
+        let worker_ttl_secs = env::var("WORKER_TTL_SECS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|&s| s > 0)
+            .unwrap_or(30);
+
+        let outbox_relay_interval_ms = env::var("OUTBOX_RELAY_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&ms| ms > 0)
+            .unwrap_or(5000);
+
+        let timer_shards = env::var("TIMER_SHARDS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|s| s.trim().parse::<i64>().ok())
+            .collect();
+
+        let timer_shard_count = env::var("TIMER_SHARD_COUNT")
+            .ok()
+            .and_then(|s| s.trim().parse::<u32>().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(1);
+
         Ok(Self {
             runtime,
             exec_mode,
             db_path,
+            database_url,
             worker_id,
             gateway_server_url,
             capabilities,
+            task_queues,
+            task_aging_secs,
+            queue_rate_limits,
+            template_weights,
+            worker_transport,
+            poll_wait_secs,
             gateway_bind,
             concurrency,
+            timer_poll_interval_ms,
+            timer_shards,
+            timer_shard_count,
+            task_reap_interval_ms,
+            worker_ttl_secs,
+            outbox_relay_interval_ms,
         })
     }
 
@@ -146,11 +277,19 @@ impl StepflowConfig {
         self.capabilities.iter().any(|c| c == capability)
     }
 
+    /// 配置了 PostgreSQL 连接 URL 时返回该 URL
+    pub fn postgres_url(&self) -> Option<&str> {
+        self.database_url
+            .as_deref()
+            .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
+    }
+
     /// 日志摘要
     pub fn summary(&self) -> String {
         format!(
-            "runtime={}, exec_mode={}, worker_id={}, db_path={}, concurrency={}, capabilities={:?}",
-            self.runtime, self.exec_mode, self.worker_id, self.db_path, self.concurrency, self.capabilities
+            "runtime={}, exec_mode={}, worker_id={}, db_path={}, concurrency={}, capabilities={:?}, task_queues={:?}, transport={}",
+            self.runtime, self.exec_mode, self.worker_id, self.db_path, self.concurrency, self.capabilities, self.task_queues,
+            self.worker_transport
         )
     }
     pub fn for_flutter() -> Result<Self> {
@@ -171,11 +310,36 @@ impl StepflowConfig {
             runtime: StepflowRuntime::FrbOnly,
             exec_mode: StepflowExecMode::EventDriven,
             db_path,
+            database_url: None,
             worker_id: "frb-worker".to_string(),
             gateway_server_url: "".into(),
             capabilities: vec!["http".into(), "shell".into()],
+            task_queues: vec![],
+            task_aging_secs: 30,
+            queue_rate_limits: HashMap::new(),
+            template_weights: HashMap::new(),
+            worker_transport: WorkerTransport::Poll,
+            poll_wait_secs: 30,
             gateway_bind: "127.0.0.1:3000".into(),
             concurrency: 2,
+            timer_poll_interval_ms: 1000,
+            timer_shards: vec![],
+            timer_shard_count: 1,
+            task_reap_interval_ms: 5000,
+            worker_ttl_secs: 30,
+            outbox_relay_interval_ms: 5000,
         })
     }
 }
+
+/// 解析 `key=value,key=value`，忽略格式不对的项
+fn parse_pairs<T: FromStr>(raw: &str) -> HashMap<String, T> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = key.trim();
+            (!key.is_empty()).then_some(())?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
@@ -1,13 +1,15 @@
 use std::{sync::Arc, collections::HashMap};
 use tokio::sync::Mutex;
 use tokio::sync::broadcast::Receiver;
-use stepflow_engine::engine::WorkflowEngine;
-use stepflow_match::service::MatchService;
+use stepflow_engine::engine::{root_run_id, WorkflowEngine};
+use stepflow_match::service::{MatchService, QueuePollers};
 use stepflow_storage::db::DynPM;
 use stepflow_hook::EngineEventDispatcher;
 use stepflow_eventbus::core::bus::EventBus;
 use stepflow_dto::dto::event_envelope::EventEnvelope;
+use stepflow_dto::dto::signal::ExecutionSignal;
 use stepflow_engine::handler::registry::StateHandlerRegistry;
+use tracing::info;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -15,8 +17,12 @@ pub struct AppState {
     pub engines: Arc<Mutex<HashMap<String, WorkflowEngine>>>,
     pub event_dispatcher: Arc<EngineEventDispatcher>,
     pub match_service: Arc<dyn MatchService>,
+    /// 各队列最近的轮询 worker
+    pub pollers: Arc<QueuePollers>,
     pub event_bus: Arc<dyn EventBus>,
     pub state_handler_registry: Arc<StateHandlerRegistry>,
+    /// 定时器分片总数，传给新建 / 恢复的引擎
+    pub timer_shard_count: u32,
 }
 
 impl AppState {
@@ -31,8 +37,10 @@ impl AppState {
             engines: Arc::new(Mutex::new(HashMap::new())),
             event_dispatcher,
             match_service,
+            pollers: Default::default(),
             event_bus,
             state_handler_registry: Arc::new(StateHandlerRegistry::new()),
+            timer_shard_count: 1,
         }
     }
 
@@ -41,6 +49,115 @@ impl AppState {
     pub fn subscribe_events(&self) -> Receiver<EventEnvelope> {
         self.event_bus.subscribe()
     }
+
+    /// 向 `run_id` 所属根执行的引擎投递信号并推进（引擎不在内存中时从存储恢复），
+    /// 执行结束后移除引擎
+    pub async fn deliver_signal(&self, run_id: &str, signal: ExecutionSignal) -> Result<(), String> {
+        let root_id = root_run_id(run_id).to_string();
+        let mut engines = self.engines.lock().await;
+
+        if !engines.contains_key(&root_id) {
+            info!(run_id = %root_id, "♻️ restoring engine for signal");
+            let engine = WorkflowEngine::restore(
+                root_id.clone(),
+                self.event_dispatcher.clone(),
+                self.persist.clone(),
+                self.state_handler_registry.clone(),
+            )
+            .await?
+            .with_timer_shard_count(self.timer_shard_count);
+            engines.insert(root_id.clone(), engine);
+        }
+        let engine = engines
+            .get_mut(&root_id)
+            .ok_or_else(|| format!("engine {root_id} not loaded"))?;
+
+        engine
+            .get_signal_sender()
+            .ok_or_else(|| "no signal sender".to_string())?
+            .send(signal)
+            .map_err(|e| format!("send signal failed: {e}"))?;
+
+        let result = match engine.handle_next_signal().await {
+            Ok(_) => engine.advance_until_blocked().await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+
+        if engine.finished {
+            engines.remove(&root_id);
+            info!(run_id = %root_id, "🏁 workflow finished, engine removed");
+        }
+        result
+    }
+
+    /// 取消（`terminate = false`）或终止根执行：先撤下该执行及其子执行尚未结束的
+    /// 队列任务与定时器（持有任务的 worker 在下一次心跳被拒绝后中止），
+    /// 再由引擎记录终态；取消时 `onCancel` 清理状态随后继续推进
+    pub async fn stop_execution(
+        &self,
+        run_id: &str,
+        reason: Option<String>,
+        terminate: bool,
+    ) -> Result<(), String> {
+        let execution = self
+            .persist
+            .get_execution(run_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Execution {run_id} not found"))?;
+        if let Some(parent) = &execution.parent_run_id {
+            return Err(format!(
+                "Execution {run_id} is a child of {parent}; cancel the root execution instead"
+            ));
+        }
+        if execution.close_time.is_some() {
+            return Err(format!("Execution {run_id} is already closed ({})", execution.status));
+        }
+        if !terminate && execution.status == "CANCELLING" {
+            return Err(format!("Execution {run_id} is already being cancelled"));
+        }
+
+        self.revoke_pending_work(&self.run_tree(run_id).await?).await?;
+
+        let signal = if terminate {
+            ExecutionSignal::TerminateRequested { run_id: run_id.to_string(), reason }
+        } else {
+            ExecutionSignal::CancelRequested { run_id: run_id.to_string(), reason }
+        };
+        self.deliver_signal(run_id, signal).await
+    }
+
+    /// 执行及其所有子执行（分支、SubWorkflow 及其后代），广度优先
+    pub async fn run_tree(&self, run_id: &str) -> Result<Vec<String>, String> {
+        let mut runs = vec![run_id.to_string()];
+        let mut i = 0;
+        while i < runs.len() {
+            let children = self
+                .persist
+                .find_executions_by_parent(&runs[i], i64::MAX, 0)
+                .await
+                .map_err(|e| e.to_string())?;
+            runs.extend(children.into_iter().map(|c| c.run_id));
+            i += 1;
+        }
+        Ok(runs)
+    }
+
+    /// 撤销这些执行仍在队列中的任务与未触发的定时器
+    pub async fn revoke_pending_work(&self, runs: &[String]) -> Result<(), String> {
+        for run in runs {
+            let tasks = self.match_service.cancel_run_tasks(run).await?;
+            let timers = self
+                .persist
+                .cancel_timers_by_run(run)
+                .await
+                .map_err(|e| e.to_string())?;
+            if tasks + timers > 0 {
+                info!(run_id = %run, tasks, timers, "🛑 pending work cancelled");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl std::fmt::Debug for AppState {
@@ -49,8 +166,10 @@ impl std::fmt::Debug for AppState {
             .field("engines", &"Mutex<HashMap<...>>")
             .field("event_dispatcher", &"EventDispatcher")
             .field("match_service", &"MatchService")
+            .field("pollers", &"QueuePollers")
             .field("event_bus", &"EventBus")
             .field("state_handler_registry", &"StateHandlerRegistry")
+            .field("timer_shard_count", &self.timer_shard_count)
             .finish()
     }
 }
\ No newline at end of file
@@ -9,6 +9,7 @@ use sqlx::{
 use stepflow_engine::handler::{
     choice::ChoiceHandler, fail::FailHandler, pass::PassHandler, registry::StateHandlerRegistry,
     succeed::SucceedHandler, task::TaskHandler, wait::WaitHandler,
+    wait_for_signal::WaitForSignalHandler,
 };
 use stepflow_eventbus::impls::local::LocalEventBus;
 use stepflow_hook::{
@@ -17,27 +18,38 @@ use stepflow_hook::{
 };
 use stepflow_match::queue::PersistentStore;
 use stepflow_match::service::{
-    HybridMatchService, HybridMatchServiceWithEvent, MatchService, MemoryMatchService,
-    PersistentMatchService,
+    DispatchPolicy, Dispatcher, HybridMatchService, HybridMatchServiceWithEvent, MatchService,
+    MemoryMatchService, PersistentMatchService,
 };
+use stepflow_postgres::PostgresStorageManager;
 use stepflow_sqlite::SqliteStorageManager;
-use stepflow_storage::traits::{EventStorage, StateStorage, WorkflowStorage};
+use stepflow_storage::db::DynPM;
+use stepflow_storage::traits::{StateStorage, WorkflowStorage};
 
 use prometheus::Registry;
 use stepflow_common::config::{StepflowConfig, StepflowExecMode};
 
 use crate::app_state::AppState;
 
-pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
-    // ---- DB & Storage ----
-    println!("🔧 使用数据库路径: {}", cfg.db_path);
-
-    let db_path = Path::new(&cfg.db_path);
-    if !db_path.exists() {
-        println!("📂 数据库文件不存在，将尝试创建: {}", db_path.display());
+/// 按配置选择存储后端：`postgres://` URL → PostgreSQL，否则 SQLite
+async fn build_persistence(cfg: &StepflowConfig) -> Result<DynPM> {
+    if let Some(url) = cfg.postgres_url() {
+        let manager = PostgresStorageManager::connect(url).await?;
+        println!("✅ PostgreSQL 连接建立成功");
+        return Ok(Arc::new(manager));
     }
 
-    let db_url = format!("sqlite://{}", cfg.db_path);
+    let db_url = match &cfg.database_url {
+        Some(url) => url.clone(),
+        None => {
+            println!("🔧 使用数据库路径: {}", cfg.db_path);
+            let db_path = Path::new(&cfg.db_path);
+            if !db_path.exists() {
+                println!("📂 数据库文件不存在，将尝试创建: {}", db_path.display());
+            }
+            format!("sqlite://{}", cfg.db_path)
+        }
+    };
     let db_options = SqliteConnectOptions::from_str(&db_url)?
         .create_if_missing(true)
         .journal_mode(SqliteJournalMode::Wal)
@@ -48,14 +60,17 @@ pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
 
     // 初始化 StorageManager（async）
     let manager = SqliteStorageManager::new(pool).await?;
+    Ok(Arc::new(manager))
+}
+
+pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
+    // ---- DB & Storage ----
+    let persist = build_persistence(cfg).await?;
     println!("✅ StorageManager 初始化完成");
 
-    // ✅ 包成 Arc 并赋值给各个 trait
-    let persist = Arc::new(manager);
     // ---- Storage Traits ----
     let workflow_store: Arc<dyn WorkflowStorage> = persist.clone();
     let state_store: Arc<dyn StateStorage> = persist.clone();
-    let event_store: Arc<dyn EventStorage> = persist.clone();
 
     // ---- EventBus ----
     let event_bus = Arc::new(LocalEventBus::new(100));
@@ -65,7 +80,7 @@ pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
         vec![
             LogHook::new(),
             MetricsHook::new(&Registry::new()),
-            PersistHook::new(workflow_store, state_store, event_store),
+            PersistHook::new(workflow_store, state_store),
         ],
         event_bus.clone(),
     );
@@ -73,18 +88,26 @@ pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
     let event_dispatcher = Arc::new(dispatcher);
 
     // ---- Match Service ----
+    // 内存层与持久化层共用一个 Dispatcher，优先级 / 公平轮转 / 限速状态只有一份
+    let dispatch = Arc::new(Dispatcher::new(DispatchPolicy {
+        aging_secs: cfg.task_aging_secs,
+        template_weights: cfg.template_weights.clone(),
+        rate_limits: cfg.queue_rate_limits.clone(),
+    }));
     let match_service: Arc<dyn MatchService> = match cfg.exec_mode {
         StepflowExecMode::Polling => HybridMatchService::new(
-            MemoryMatchService::new(),
-            PersistentMatchService::new(
+            MemoryMatchService::with_dispatcher(dispatch.clone()),
+            PersistentMatchService::with_dispatcher(
                 Arc::new(PersistentStore::new(persist.clone())),
                 persist.clone(),
+                dispatch,
             ),
         ),
         StepflowExecMode::EventDriven => {
-            let persistent = PersistentMatchService::new(
+            let persistent = PersistentMatchService::with_dispatcher(
                 Arc::new(PersistentStore::new(persist.clone())),
                 persist.clone(),
+                dispatch,
             );
             HybridMatchServiceWithEvent::new(persistent, event_bus.clone())
         }
@@ -95,6 +118,7 @@ pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
         StateHandlerRegistry::new()
             .register("task", Arc::new(TaskHandler::new(match_service.clone())))
             .register("wait", Arc::new(WaitHandler::new()))
+            .register("waitForSignal", Arc::new(WaitForSignalHandler::new()))
             .register("pass", Arc::new(PassHandler::new()))
             .register("choice", Arc::new(ChoiceHandler::new()))
             .register("succeed", Arc::new(SucceedHandler::new()))
@@ -106,7 +130,9 @@ pub async fn build_app_state(cfg: &StepflowConfig) -> Result<AppState> {
         engines: Default::default(),
         event_dispatcher,
         match_service,
+        pollers: Default::default(),
         event_bus,
         state_handler_registry,
+        timer_shard_count: cfg.timer_shard_count,
     })
 }
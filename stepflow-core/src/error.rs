@@ -10,6 +10,9 @@ pub enum AppError {
     NotFound,
     #[error("bad request: {0}")]
     BadRequest(String),
+    /// 校验失败，携带全部问题（响应体中以 `details` 数组返回）
+    #[error("validation failed: {}", .0.join("; "))]
+    Validation(Vec<String>),
     #[error("internal error: {0}")]
     Internal(String),
     #[error(transparent)]
@@ -23,8 +26,13 @@ impl IntoResponse for AppError {
         let status = match self {
             AppError::NotFound      => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
             _                       => StatusCode::INTERNAL_SERVER_ERROR,
         };
-        (status, Json(json!({"error":{"message":self.to_string()}}))).into_response()
+        let body = match &self {
+            AppError::Validation(details) => json!({"error":{"message":self.to_string(),"details":details}}),
+            _ => json!({"error":{"message":self.to_string()}}),
+        };
+        (status, Json(body)).into_response()
     }
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ use tracing::{debug, error, info};
 use crate::app_state::AppState;
 use stepflow_dto::dto::engine_event::EngineEvent;
 use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::root_run_id;
 use stepflow_common::config::{StepflowConfig, StepflowExecMode};
 use std::sync::Arc;
 /// 启动事件驱动执行器
@@ -18,8 +19,10 @@ pub fn start_event_runner(app: AppState) {
                 } => {
                     info!(%run_id, %state_name, "📩 Received TaskFinished");
 
+                    // Parallel 分支子执行的信号由根执行的引擎处理
+                    let root_id = root_run_id(run_id);
                     let mut engines = app.engines.lock().await;
-                    let engine = match engines.get_mut(run_id) {
+                    let engine = match engines.get_mut(root_id) {
                         Some(e) => e,
                         None => {
                             debug!(%run_id, "⚠️ No active engine found");
@@ -61,7 +64,7 @@ pub fn start_event_runner(app: AppState) {
                     info!(%run_id, "✅ advance_until_blocked complete");
 
                     if engine.finished {
-                        engines.remove(run_id);
+                        engines.remove(root_id);
                         info!(%run_id, "🏁 workflow finished, engine removed");
                     }
                 }
@@ -2,6 +2,9 @@ pub mod app_state;
 pub mod error;
 pub mod builder;
 pub mod event;
+pub mod outbox;
+pub mod reaper;
+pub mod timer;
 
 pub use app_state::AppState;
 pub use error::{AppError, AppResult};
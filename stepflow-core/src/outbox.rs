@@ -0,0 +1,62 @@
+//! Outbox 补投：引擎步骤提交后会立即投递 outbox 消息（引擎事件、待入队任务），
+//! 进程在提交与投递之间退出时消息保持未发布，由本循环定期补投。
+//!
+//! * 只处理创建时间早于 `min_age` 的消息，避免与引擎的即时投递竞争
+//! * 投递语义为至少一次：消费方需容忍重复的事件 / 任务
+
+use std::time::Duration;
+
+use stepflow_common::config::StepflowConfig;
+use stepflow_engine::engine::outbox::relay_unpublished;
+use tracing::{error, info};
+
+use crate::app_state::AppState;
+
+/// 单次扫描补投的最大消息数
+const RELAY_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct OutboxRelayConfig {
+    pub poll_interval: Duration,
+    pub min_age: Duration,
+}
+
+impl OutboxRelayConfig {
+    pub fn from_config(cfg: &StepflowConfig) -> Self {
+        let poll_interval = Duration::from_millis(cfg.outbox_relay_interval_ms);
+        Self {
+            poll_interval,
+            min_age: poll_interval,
+        }
+    }
+}
+
+/// 后台启动 outbox 补投循环
+pub fn spawn_outbox_relay(app: &AppState, cfg: OutboxRelayConfig) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        info!(?cfg, "📮 Outbox relay started");
+        let mut ticker = tokio::time::interval(cfg.poll_interval);
+        loop {
+            ticker.tick().await;
+            match relay_outbox(&app, cfg.min_age).await {
+                Ok(0) => {}
+                Ok(n) => info!(count = n, "📮 relayed unpublished outbox messages"),
+                Err(e) => error!(%e, "❌ outbox relay failed"),
+            }
+        }
+    });
+}
+
+/// 扫描一次：补投所有超过 `min_age` 仍未发布的消息，返回成功投递的数量
+pub async fn relay_outbox(app: &AppState, min_age: Duration) -> Result<usize, String> {
+    let min_age = chrono::Duration::from_std(min_age).map_err(|e| e.to_string())?;
+    relay_unpublished(
+        &app.persist,
+        &app.event_dispatcher,
+        &app.state_handler_registry,
+        min_age,
+        RELAY_BATCH_SIZE,
+    )
+    .await
+}
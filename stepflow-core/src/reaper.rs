@@ -0,0 +1,103 @@
+//! 任务回收：扫描超时 / 心跳超时的 processing 任务，把错误送回引擎。
+//!
+//! * 由 `MatchService::reap_expired_tasks` 判定过期并把队列任务标记为 failed
+//! * 对每个过期任务向根执行的引擎发送 `TaskFailed`，错误类型为 `States.Timeout` / `States.HeartbeatTimeout`，
+//!   由引擎按 Retry 重新入队或交给 Catch；引擎不在内存中时从存储恢复
+//! * 超过 `WORKER_TTL_SECS` 未心跳的已注册 worker 标记为下线，其持有的任务退回队列（不计重试次数）
+
+use std::time::Duration;
+
+use chrono::Utc;
+use stepflow_common::config::StepflowConfig;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use tracing::{error, info, warn};
+
+use crate::app_state::AppState;
+
+#[derive(Debug, Clone)]
+pub struct TaskReaperConfig {
+    pub poll_interval: Duration,
+    /// worker 超过该时长未心跳视为失联
+    pub worker_ttl: Duration,
+}
+
+impl TaskReaperConfig {
+    pub fn from_config(cfg: &StepflowConfig) -> Self {
+        Self {
+            poll_interval: Duration::from_millis(cfg.task_reap_interval_ms),
+            worker_ttl: Duration::from_secs(cfg.worker_ttl_secs),
+        }
+    }
+}
+
+/// 后台启动任务回收循环
+pub fn spawn_task_reaper(app: &AppState, cfg: TaskReaperConfig) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        info!(?cfg, "🧹 Task reaper started");
+        let mut ticker = tokio::time::interval(cfg.poll_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = reap_expired_tasks(&app).await {
+                error!(?e, "❌ task reap failed");
+            }
+            if let Err(e) = reap_dead_workers(&app, cfg.worker_ttl).await {
+                error!(?e, "❌ worker reap failed");
+            }
+        }
+    });
+}
+
+/// 扫描一次：回收所有过期任务并通知引擎，返回回收的数量
+pub async fn reap_expired_tasks(app: &AppState) -> anyhow::Result<usize> {
+    let expired = app
+        .match_service
+        .reap_expired_tasks(Utc::now())
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    for e in &expired {
+        let (run_id, state_name) = (&e.task.run_id, &e.task.state_name);
+        warn!(%run_id, %state_name, error_type = e.error_type, reason = %e.reason, "⏱️ task expired");
+
+        let signal = ExecutionSignal::TaskFailed {
+            run_id: run_id.clone(),
+            state_name: state_name.clone(),
+            error: e.reason.clone(),
+            error_type: Some(e.error_type.to_string()),
+        };
+        if let Err(err) = app.deliver_signal(run_id, signal).await {
+            error!(%run_id, %state_name, %err, "❌ failed to deliver task timeout");
+        }
+    }
+
+    Ok(expired.len())
+}
+
+/// 扫描一次：把超过 `ttl` 未心跳的 worker 标记为下线并退回其持有的任务，返回下线的 worker 数
+pub async fn reap_dead_workers(app: &AppState, ttl: Duration) -> anyhow::Result<usize> {
+    let cutoff = (Utc::now() - chrono::Duration::from_std(ttl)?).naive_utc();
+    let stale = app.persist.find_stale_workers(cutoff).await?;
+
+    let mut reaped = 0;
+    for worker in &stale {
+        // 条件下线：查询之后 worker 又心跳了（或已被其他网关下线）则跳过，不退回它正在处理的任务
+        if !app.persist.mark_worker_offline_if_stale(&worker.worker_id, cutoff).await? {
+            continue;
+        }
+        reaped += 1;
+        let requeued = app
+            .match_service
+            .requeue_worker_tasks(&worker.worker_id)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        warn!(
+            worker_id = %worker.worker_id,
+            last_seen_at = %worker.last_seen_at,
+            requeued = requeued.len(),
+            "💀 worker missed heartbeats, marked offline"
+        );
+    }
+
+    Ok(reaped)
+}
@@ -0,0 +1,138 @@
+//! 定时器调度：扫描到期的 deferred Wait 定时器并恢复对应的工作流。
+//!
+//! * 通过 `claim_due_timers` 原子认领本实例负责的 `shard_id` 中到期的 pending 定时器
+//!   （pending -> fired，version + 1；Postgres 下使用 `FOR UPDATE SKIP LOCKED`），
+//!   多实例部署时同一定时器只触发一次
+//! * 认领成功后发出 `EngineEvent::TimerFired`，向根执行的引擎发送 `TimerFired` 信号并推进；
+//!   引擎不在内存中时从存储恢复
+//! * 超时定时器（payload 为 `TimerPayload::Timeout`）发送 `TimeoutFired` 信号；
+//!   执行因此以 TIMED_OUT 结束时，撤销其及子执行仍在队列中的任务与定时器
+
+use std::time::Duration;
+
+use chrono::Utc;
+use stepflow_common::config::StepflowConfig;
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_dto::dto::execution::TIMED_OUT;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_dto::dto::timer::TimerPayload;
+use tracing::{debug, error, info, warn};
+
+use crate::app_state::AppState;
+
+/// 单次扫描最多处理的定时器数
+const DEFAULT_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct TimerSchedulerConfig {
+    pub poll_interval: Duration,
+    pub batch_size: i64,
+    /// 本实例负责的分片（空 = 全部分片）
+    pub shards: Vec<i64>,
+}
+
+impl TimerSchedulerConfig {
+    pub fn from_config(cfg: &StepflowConfig) -> Self {
+        Self {
+            poll_interval: Duration::from_millis(cfg.timer_poll_interval_ms),
+            batch_size: DEFAULT_BATCH_SIZE,
+            shards: cfg.timer_shards.clone(),
+        }
+    }
+}
+
+/// 后台启动定时器扫描循环
+pub fn spawn_timer_scheduler(app: &AppState, cfg: TimerSchedulerConfig) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        info!(?cfg, "⏰ Timer scheduler started");
+        let mut ticker = tokio::time::interval(cfg.poll_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = fire_due_timers(&app, &cfg).await {
+                error!(?e, "❌ timer scan failed");
+            }
+        }
+    });
+}
+
+/// 扫描一次：认领并触发所有到期定时器，返回成功触发的数量
+pub async fn fire_due_timers(app: &AppState, cfg: &TimerSchedulerConfig) -> anyhow::Result<usize> {
+    let due = app
+        .persist
+        .claim_due_timers(Utc::now().naive_utc(), &cfg.shards, cfg.batch_size)
+        .await?;
+    debug!(count = due.len(), "claimed due timers");
+
+    let mut fired = 0;
+    for timer in due {
+        let payload = timer
+            .payload
+            .clone()
+            .and_then(|p| serde_json::from_value::<TimerPayload>(p).ok());
+        if let Some(TimerPayload::Timeout { visit_event_id, attempt }) = payload {
+            if fire_timeout(app, &timer.run_id, timer.state_name.clone(), visit_event_id, attempt).await {
+                fired += 1;
+            }
+            continue;
+        }
+
+        let Some(state_name) = timer.state_name.clone() else {
+            warn!(timer_id = %timer.timer_id, "timer has no state_name, skipped");
+            continue;
+        };
+
+        app.event_dispatcher
+            .dispatch(EngineEvent::TimerFired {
+                run_id: timer.run_id.clone(),
+                state_name: state_name.clone(),
+            })
+            .await;
+
+        let signal = ExecutionSignal::TimerFired {
+            run_id: timer.run_id.clone(),
+            state_name: state_name.clone(),
+        };
+        match app.deliver_signal(&timer.run_id, signal).await {
+            Ok(()) => fired += 1,
+            Err(e) => error!(run_id = %timer.run_id, %state_name, %e, "❌ failed to resume workflow"),
+        }
+    }
+
+    Ok(fired)
+}
+
+async fn fire_timeout(
+    app: &AppState,
+    run_id: &str,
+    state_name: Option<String>,
+    visit_event_id: Option<i64>,
+    attempt: Option<i64>,
+) -> bool {
+    let signal = ExecutionSignal::TimeoutFired {
+        run_id: run_id.to_string(),
+        state_name: state_name.clone(),
+        visit_event_id,
+        attempt,
+    };
+    if let Err(e) = app.deliver_signal(run_id, signal).await {
+        error!(%run_id, ?state_name, %e, "❌ failed to apply timeout");
+        return false;
+    }
+
+    let timed_out = matches!(
+        app.persist.get_execution(run_id).await,
+        Ok(Some(execution)) if execution.status == TIMED_OUT
+    );
+    if timed_out {
+        info!(%run_id, ?state_name, "⌛ execution timed out");
+        let revoked = match app.run_tree(run_id).await {
+            Ok(runs) => app.revoke_pending_work(&runs).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = revoked {
+            warn!(%run_id, %e, "failed to revoke pending work of timed-out execution");
+        }
+    }
+    true
+}
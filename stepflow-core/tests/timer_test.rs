@@ -0,0 +1,306 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use stepflow_core::app_state::AppState;
+use stepflow_core::timer::{fire_due_timers, TimerSchedulerConfig};
+use stepflow_dsl::WorkflowDSL;
+use stepflow_engine::engine::{timer_shard, WorkflowEngine, WorkflowMode};
+use stepflow_engine::handler::{
+    registry::StateHandlerRegistry, succeed::SucceedHandler, wait::WaitHandler,
+};
+use stepflow_eventbus::impls::local::LocalEventBus;
+use stepflow_hook::EngineEventDispatcher;
+use stepflow_match::service::MemoryMatchService;
+use stepflow_sqlite::SqliteStorageManager;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::{
+    timer::UpdateStoredTimer, workflow_execution::StoredWorkflowExecution,
+    workflow_template::StoredWorkflowTemplate,
+    workflow_template_revision::{StoredTemplateRevision, REVISION_PUBLISHED},
+};
+
+async fn app_state() -> AppState {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let persist: DynPM = Arc::new(SqliteStorageManager::new(pool).await.unwrap());
+    let event_bus = Arc::new(LocalEventBus::new(100));
+
+    AppState {
+        persist,
+        engines: Default::default(),
+        event_dispatcher: Arc::new(EngineEventDispatcher::new(vec![], event_bus.clone())),
+        match_service: MemoryMatchService::new(),
+        pollers: Default::default(),
+        event_bus,
+        state_handler_registry: Arc::new(
+            StateHandlerRegistry::new()
+                .register("wait", Arc::new(WaitHandler::new()))
+                .register("succeed", Arc::new(SucceedHandler::new())),
+        ),
+        timer_shard_count: 1,
+    }
+}
+
+/// 落库模板 + 执行，推进到 Wait 挂起后丢弃引擎（模拟进程重启）
+async fn start_waiting_execution(app: &AppState, run_id: &str, timeout_seconds: Option<u64>) {
+    let dsl = json!({
+        "startAt": "Sleep",
+        "timeoutSeconds": timeout_seconds,
+        "states": {
+            "Sleep": { "type": "wait", "seconds": 60, "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let now = Utc::now().naive_utc();
+    app.persist
+        .create_template(&StoredWorkflowTemplate {
+            template_id: format!("tpl-{run_id}"),
+            name: "wait".into(),
+            description: None,
+            dsl_definition: dsl.to_string(),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+    app.persist
+        .create_template_revision(&StoredTemplateRevision {
+            template_id: format!("tpl-{run_id}"),
+            revision: 1,
+            dsl_definition: dsl.to_string(),
+            status: REVISION_PUBLISHED.into(),
+            created_at: now,
+            published_at: Some(now),
+        })
+        .await
+        .unwrap();
+    app.persist
+        .create_execution(&StoredWorkflowExecution {
+            run_id: run_id.into(),
+            workflow_id: Some(format!("wf-{run_id}")),
+            shard_id: 0,
+            template_id: Some(format!("tpl-{run_id}")),
+            template_revision: Some(1),
+            parent_run_id: None,
+            mode: "DEFERRED".into(),
+            current_state_name: Some("Sleep".into()),
+            status: "RUNNING".into(),
+            workflow_type: "default".into(),
+            input: Some(json!({})),
+            input_version: 1,
+            result: None,
+            result_version: 1,
+            start_time: now,
+            close_time: None,
+            current_event_id: 0,
+            memo: None,
+            search_attrs: None,
+            context_snapshot: None,
+            version: 1,
+        })
+        .await
+        .unwrap();
+
+    let dsl: WorkflowDSL = serde_json::from_value(dsl).unwrap();
+    let mut engine = WorkflowEngine::new(
+        run_id.into(),
+        dsl,
+        json!({}),
+        WorkflowMode::Deferred,
+        app.event_dispatcher.clone(),
+        app.persist.clone(),
+        app.state_handler_registry.clone(),
+    )
+    .with_timer_shard_count(app.timer_shard_count);
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+}
+
+#[tokio::test]
+async fn test_scheduler_fires_due_timer_and_restores_engine() {
+    let app = app_state().await;
+    start_waiting_execution(&app, "run-timer", None).await;
+    let cfg = TimerSchedulerConfig {
+        poll_interval: Duration::from_millis(10),
+        batch_size: 10,
+        shards: vec![],
+    };
+
+    // 尚未到期
+    assert_eq!(fire_due_timers(&app, &cfg).await.unwrap(), 0);
+
+    let timer = app
+        .persist
+        .find_timers_before(Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap()
+        .remove(0);
+    app.persist
+        .update_timer(
+            &timer.timer_id,
+            &UpdateStoredTimer {
+                fire_at: Some(Utc::now().naive_utc() - chrono::Duration::seconds(1)),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // 其他分片的调度器不处理该定时器
+    let other_shard = TimerSchedulerConfig {
+        shards: vec![7],
+        ..cfg.clone()
+    };
+    assert_eq!(fire_due_timers(&app, &other_shard).await.unwrap(), 0);
+
+    assert_eq!(fire_due_timers(&app, &cfg).await.unwrap(), 1);
+    let exec = app.persist.get_execution("run-timer").await.unwrap().unwrap();
+    assert_eq!(exec.status, "COMPLETED");
+    assert!(app.engines.lock().await.is_empty());
+
+    // 已认领的定时器不会再次触发
+    let stored = app.persist.get_timer(&timer.timer_id).await.unwrap().unwrap();
+    assert_eq!(stored.status, "fired");
+    assert!(!app.persist.claim_timer(&timer.timer_id, timer.version).await.unwrap());
+    assert_eq!(fire_due_timers(&app, &cfg).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_schedulers_claim_only_their_own_shard() {
+    let mut app = app_state().await;
+    app.timer_shard_count = 2;
+
+    // 各取一个落在分片 0 / 分片 1 的执行
+    let run_in = |shard: i64| {
+        (0..)
+            .map(|i| format!("run-shard-{i}"))
+            .find(|run_id| timer_shard(run_id, 2) == shard)
+            .unwrap()
+    };
+    let (run0, run1) = (run_in(0), run_in(1));
+    start_waiting_execution(&app, &run0, None).await;
+    start_waiting_execution(&app, &run1, None).await;
+
+    let timers = app
+        .persist
+        .find_timers_before(Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert_eq!(timers.len(), 2);
+    for timer in &timers {
+        assert_eq!(timer.shard_id, timer_shard(&timer.run_id, 2));
+        app.persist
+            .update_timer(
+                &timer.timer_id,
+                &UpdateStoredTimer {
+                    fire_at: Some(Utc::now().naive_utc() - chrono::Duration::seconds(1)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let scheduler = |shard: i64| TimerSchedulerConfig {
+        poll_interval: Duration::from_millis(10),
+        batch_size: 10,
+        shards: vec![shard],
+    };
+
+    // 分片 0 的调度器只触发分片 0 的定时器
+    assert_eq!(fire_due_timers(&app, &scheduler(0)).await.unwrap(), 1);
+    let exec0 = app.persist.get_execution(&run0).await.unwrap().unwrap();
+    let exec1 = app.persist.get_execution(&run1).await.unwrap().unwrap();
+    assert_eq!(exec0.status, "COMPLETED");
+    assert_eq!(exec1.status, "RUNNING");
+    assert_eq!(fire_due_timers(&app, &scheduler(0)).await.unwrap(), 0);
+
+    // 分片 1 的定时器由分片 1 的调度器触发
+    assert_eq!(fire_due_timers(&app, &scheduler(1)).await.unwrap(), 1);
+    let exec1 = app.persist.get_execution(&run1).await.unwrap().unwrap();
+    assert_eq!(exec1.status, "COMPLETED");
+}
+
+#[tokio::test]
+async fn test_cancel_execution_cancels_pending_timer() {
+    let app = app_state().await;
+    start_waiting_execution(&app, "run-timer-cancel", None).await;
+    let cfg = TimerSchedulerConfig {
+        poll_interval: Duration::from_millis(10),
+        batch_size: 10,
+        shards: vec![],
+    };
+
+    app.stop_execution("run-timer-cancel", Some("no longer needed".into()), false)
+        .await
+        .unwrap();
+    let exec = app.persist.get_execution("run-timer-cancel").await.unwrap().unwrap();
+    assert_eq!(exec.status, "CANCELLED");
+    assert_eq!(exec.result.unwrap()["Cause"], "no longer needed");
+    assert!(exec.close_time.is_some());
+    assert!(app.engines.lock().await.is_empty());
+
+    // 定时器被撤下，到期后也不会触发
+    let timer = app
+        .persist
+        .find_timers_before(Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert!(timer.is_empty());
+
+    // 已结束的执行不能再次取消 / 终止
+    let err = app.stop_execution("run-timer-cancel", None, true).await.unwrap_err();
+    assert!(err.contains("already closed"), "{err}");
+    assert_eq!(fire_due_timers(&app, &cfg).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_scheduler_fires_execution_timeout() {
+    let app = app_state().await;
+    start_waiting_execution(&app, "run-timer-timeout", Some(30)).await;
+    let cfg = TimerSchedulerConfig {
+        poll_interval: Duration::from_millis(10),
+        batch_size: 10,
+        shards: vec![],
+    };
+
+    // Wait 定时器与执行级超时定时器（不绑定状态）
+    let timers = app
+        .persist
+        .find_timers_before(Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert_eq!(timers.len(), 2);
+    let timeout = timers.iter().find(|t| t.state_name.is_none()).unwrap();
+    assert_eq!(timeout.payload.as_ref().unwrap()["kind"], "timeout");
+    app.persist
+        .update_timer(
+            &timeout.timer_id,
+            &UpdateStoredTimer {
+                fire_at: Some(Utc::now().naive_utc() - chrono::Duration::seconds(1)),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(fire_due_timers(&app, &cfg).await.unwrap(), 1);
+    let exec = app.persist.get_execution("run-timer-timeout").await.unwrap().unwrap();
+    assert_eq!(exec.status, "TIMED_OUT");
+    assert_eq!(exec.result.unwrap()["Error"], "States.Timeout");
+    assert!(app.engines.lock().await.is_empty());
+
+    // 仍在等待的 Wait 定时器被撤下
+    let remaining = app
+        .persist
+        .find_timers_before(Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert!(remaining.is_empty());
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::dsl::WorkflowDSL;
 use crate::state::State;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,4 +9,20 @@ use crate::state::State;
 pub struct Branch {
     pub start_at: String,
     pub states: HashMap<String, State>,
-}
\ No newline at end of file
+}
+
+impl Branch {
+    /// Build a standalone `WorkflowDSL` so a branch can run as a child execution.
+    pub fn to_workflow(&self) -> WorkflowDSL {
+        WorkflowDSL {
+            comment: None,
+            version: None,
+            start_at: self.start_at.clone(),
+            global_config: None,
+            error_handling: None,
+            states: self.states.clone(),
+            on_cancel: None,
+            timeout_seconds: None,
+        }
+    }
+}
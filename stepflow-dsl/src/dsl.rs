@@ -36,6 +36,16 @@ pub struct WorkflowDSL {
 
     /// All states keyed by state name
     pub states: HashMap<String, State>,
+
+    /// Optional cleanup state entered when the execution is cancelled;
+    /// the execution is recorded as CANCELLED once the cleanup path ends
+    #[serde(default)]
+    pub on_cancel: Option<String>,
+
+    /// Maximum run time of the whole execution in seconds;
+    /// exceeding it ends the execution as TIMED_OUT
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
 }
 
 impl WorkflowDSL {
@@ -48,17 +58,7 @@ impl WorkflowDSL {
         name: &str,
     ) -> (&State, &BaseState) {
         let st = self.states.get(name).expect("state not found");
-        let base = match st {
-            State::Task(t)     => &t.base,
-            State::Pass(p)     => &p.base,
-            State::Wait(w)     => &w.base,
-            State::Choice(c)   => &c.base,
-            State::Succeed(s)  => &s.base,
-            State::Fail(f)     => &f.base,
-            State::Parallel(p) => &p.base,
-            State::Map(m)      => &m.base,
-        };
-        (st, base)
+        (st, st.base())
     }
 }
 
@@ -66,8 +66,12 @@ impl Default for BaseState {
     fn default() -> Self {
         Self {
             comment: None,
+            input_path: None,
             input_mapping: None,
+            result_selector: None,
             output_mapping: None,
+            result_path: None,
+            output_path: None,
             retry: None,
             catch: None,
             next: None,
@@ -94,7 +98,6 @@ mod tests {
                     ..Default::default()
                 },
                 result: None,
-                result_path: None,
             }),
         );
         let dsl = WorkflowDSL {
@@ -104,6 +107,8 @@ mod tests {
             global_config: Some(json!({"foo": 1})),
             error_handling: None,
             states,
+            on_cancel: None,
+            timeout_seconds: None,
         };
         let ser = serde_json::to_string(&dsl).unwrap();
         let de: WorkflowDSL = serde_json::from_str(&ser).unwrap();
@@ -123,6 +128,8 @@ mod tests {
             global_config: None,
             error_handling: None,
             states: HashMap::new(),
+            on_cancel: None,
+            timeout_seconds: None,
         };
         let ser = serde_json::to_string(&dsl).unwrap();
         let de: WorkflowDSL = serde_json::from_str(&ser).unwrap();
@@ -135,7 +142,6 @@ mod tests {
         let pass = State::Pass(PassState {
             base: BaseState::default(),
             result: None,
-            result_path: None,
         });
         let ser = serde_json::to_string(&pass).unwrap();
         let de: State = serde_json::from_str(&ser).unwrap();
@@ -149,8 +155,12 @@ mod tests {
     fn test_base_state_mapping_fields() {
         let base = BaseState {
             comment: Some("cmt".to_string()),
+            input_path: Some("$.in".to_string()),
             input_mapping: None,
+            result_selector: Some(json!({ "id.$": "$.id" })),
             output_mapping: None,
+            result_path: Some("$.out".to_string()),
+            output_path: None,
             retry: None,
             catch: None,
             next: Some("next".to_string()),
@@ -161,6 +171,9 @@ mod tests {
         assert_eq!(de.comment.as_deref(), Some("cmt"));
         assert_eq!(de.next.as_deref(), Some("next"));
         assert_eq!(de.end, Some(true));
+        assert_eq!(de.input_path.as_deref(), Some("$.in"));
+        assert_eq!(de.result_selector, Some(json!({ "id.$": "$.id" })));
+        assert_eq!(de.result_path.as_deref(), Some("$.out"));
     }
 
     #[test]
@@ -172,20 +185,24 @@ mod tests {
             State::Task(TaskState {
                 base: BaseState::default(),
                 resource: "res".to_string(),
+                task_queue: Some("gpu".to_string()),
                 parameters: None,
                 execution_config: None,
                 heartbeat_seconds: None,
                 heartbeat_expr: None,
+                wait_for_task_token: Some(true),
+                timeout_seconds: Some(30),
             }),
             State::Pass(PassState {
                 base: BaseState::default(),
                 result: None,
-                result_path: None,
             }),
             State::Wait(WaitState {
                 base: BaseState::default(),
                 seconds: None,
                 timestamp: None,
+                seconds_path: None,
+                timestamp_path: None,
             }),
             State::Choice(ChoiceState {
                 base: BaseState::default(),
@@ -204,6 +221,7 @@ mod tests {
                 base: BaseState::default(),
                 branches: vec![],
                 max_concurrency: None,
+                timeout_seconds: None,
             }),
             State::Map(MapState {
                 base: BaseState::default(),
@@ -213,6 +231,19 @@ mod tests {
                     states: HashMap::new(),
                 },
                 max_concurrency: None,
+                tolerated_failure_percentage: None,
+                timeout_seconds: None,
+            }),
+            State::SubWorkflow(SubWorkflowState {
+                base: BaseState::default(),
+                template_id: "tpl".to_string(),
+                template_version: Some(1),
+                template_alias: None,
+                timeout_seconds: Some(600),
+            }),
+            State::WaitForSignal(WaitForSignalState {
+                base: BaseState::default(),
+                signal_name: "approved".to_string(),
             }),
         ];
         for state in variants {
@@ -228,6 +259,8 @@ mod tests {
                 (State::Fail(_), State::Fail(_)) => {}
                 (State::Parallel(_), State::Parallel(_)) => {}
                 (State::Map(_), State::Map(_)) => {}
+                (State::SubWorkflow(_), State::SubWorkflow(_)) => {}
+                (State::WaitForSignal(_), State::WaitForSignal(_)) => {}
                 _ => panic!("variant mismatch: {:?} vs {:?}", state, de),
             }
         }
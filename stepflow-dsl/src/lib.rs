@@ -7,6 +7,6 @@ pub mod validation;
 pub use branch::*;
 pub use logic::*;
 pub use state::*;
-pub use validation::{ValidationError};
+pub use validation::{ValidationError, ValidationErrors};
 
 pub use crate::dsl::WorkflowDSL;
\ No newline at end of file
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::validation::check_json_path;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChoiceLogic {
@@ -17,4 +19,173 @@ pub struct ChoiceLogic {
 pub struct ChoiceRule {
     pub condition: ChoiceLogic,
     pub next: String,
-}
\ No newline at end of file
+}
+
+/// 有序比较
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equals,
+    LessThan,
+    LessThanEquals,
+    GreaterThan,
+    GreaterThanEquals,
+}
+
+impl Comparison {
+    fn from_suffix(s: &str) -> Option<Self> {
+        Some(match s {
+            "Equals" => Self::Equals,
+            "LessThan" => Self::LessThan,
+            "LessThanEquals" => Self::LessThanEquals,
+            "GreaterThan" => Self::GreaterThan,
+            "GreaterThanEquals" => Self::GreaterThanEquals,
+            _ => return None,
+        })
+    }
+
+    pub fn holds(self, ord: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Self::Equals => ord == Equal,
+            Self::LessThan => ord == Less,
+            Self::LessThanEquals => ord != Greater,
+            Self::GreaterThan => ord == Greater,
+            Self::GreaterThanEquals => ord != Less,
+        }
+    }
+}
+
+/// 叶子条件的运算符（对齐 Step Functions 的比较集合）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChoiceOperatorKind {
+    /// 任意 JSON 值相等 / 不等
+    Equals,
+    NotEquals,
+    /// `GreaterThan` / `NumericEquals` 等数值比较
+    Numeric(Comparison),
+    String(Comparison),
+    /// 支持 `*` 通配符（`\*` 转义）
+    StringMatches,
+    /// RFC3339 时间比较
+    Timestamp(Comparison),
+    BooleanEquals,
+    /// 数组包含某个元素
+    Contains,
+    IsNull,
+    IsPresent,
+    IsString,
+    IsBoolean,
+    IsNumeric,
+    IsTimestamp,
+}
+
+/// 解析后的运算符；`path = true` 表示 `value` 是另一个 JSONPath（`*Path` 变体）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChoiceOperator {
+    pub kind: ChoiceOperatorKind,
+    pub path: bool,
+}
+
+impl ChoiceOperator {
+    pub fn parse(name: &str) -> Option<Self> {
+        use ChoiceOperatorKind::*;
+
+        let (base, path) = match name.strip_suffix("Path") {
+            Some(base) => (base, true),
+            None => (name, false),
+        };
+        let kind = match base {
+            "Equals" => Equals,
+            "NotEquals" => NotEquals,
+            "StringMatches" => StringMatches,
+            "BooleanEquals" => BooleanEquals,
+            "Contains" => Contains,
+            "IsNull" => IsNull,
+            "IsPresent" => IsPresent,
+            "IsString" => IsString,
+            "IsBoolean" => IsBoolean,
+            "IsNumeric" => IsNumeric,
+            "IsTimestamp" => IsTimestamp,
+            _ => {
+                if let Some(cmp) = base.strip_prefix("String").and_then(Comparison::from_suffix) {
+                    String(cmp)
+                } else if let Some(cmp) = base.strip_prefix("Timestamp").and_then(Comparison::from_suffix) {
+                    Timestamp(cmp)
+                } else if let Some(cmp) = base.strip_prefix("Numeric").and_then(Comparison::from_suffix) {
+                    Numeric(cmp)
+                } else {
+                    // 兼容原有的 GreaterThan / LessThanEquals 等写法（不含 Equals，Equals 为通用相等）
+                    Numeric(Comparison::from_suffix(base).filter(|c| *c != Comparison::Equals)?)
+                }
+            }
+        };
+
+        let op = Self { kind, path };
+        // 类型判断与通配匹配没有 Path 变体
+        if path && (op.is_unary() || kind == StringMatches) {
+            return None;
+        }
+        Some(op)
+    }
+
+    /// 类型 / 存在性判断：`value` 可省略（默认 true），为 false 时取反
+    pub fn is_unary(&self) -> bool {
+        use ChoiceOperatorKind::*;
+        matches!(
+            self.kind,
+            IsNull | IsPresent | IsString | IsBoolean | IsNumeric | IsTimestamp
+        )
+    }
+}
+
+impl ChoiceLogic {
+    /// 结构与运算符校验：模板创建时即可拒绝未知运算符或缺失字段
+    pub fn validate(&self) -> Result<(), String> {
+        let combinators = [self.and_.is_some(), self.or_.is_some(), self.not_.is_some()]
+            .into_iter()
+            .filter(|b| *b)
+            .count();
+        let is_leaf = self.variable.is_some() || self.operator.is_some();
+
+        if combinators + is_leaf as usize != 1 {
+            return Err("condition must be exactly one of and / or / not / a comparison".into());
+        }
+
+        if let Some(list) = self.and_.as_ref().or(self.or_.as_ref()) {
+            if list.is_empty() {
+                return Err("and / or requires at least one condition".into());
+            }
+            return list.iter().try_for_each(ChoiceLogic::validate);
+        }
+        if let Some(not) = &self.not_ {
+            return not.validate();
+        }
+
+        let variable = self.variable.as_deref().ok_or("missing variable")?;
+        if !variable.starts_with('$') {
+            return Err(format!("variable '{variable}' must be a JSONPath starting with '$'"));
+        }
+        check_json_path(variable)?;
+        let name = self.operator.as_deref().ok_or("missing operator")?;
+        let op = ChoiceOperator::parse(name).ok_or_else(|| format!("unknown operator '{name}'"))?;
+
+        match (&self.value, op) {
+            (None, op) if op.is_unary() => Ok(()),
+            (Some(Value::Bool(_)), op) if op.is_unary() => Ok(()),
+            (_, op) if op.is_unary() => Err(format!("{name} expects a boolean value")),
+            (None, _) => Err(format!("{name} requires a value")),
+            (Some(Value::String(p)), ChoiceOperator { path: true, .. }) if p.starts_with('$') => check_json_path(p),
+            (Some(_), ChoiceOperator { path: true, .. }) => Err(format!("{name} expects a JSONPath value")),
+            (Some(v), ChoiceOperator { kind, .. }) => {
+                use ChoiceOperatorKind::*;
+                let ok = match kind {
+                    Numeric(_) => v.is_number(),
+                    String(_) | StringMatches | Timestamp(_) => v.is_string(),
+                    BooleanEquals => v.is_boolean(),
+                    _ => true,
+                };
+                if ok { Ok(()) } else { Err(format!("{name} has a value of the wrong type: {v}")) }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use stepflow_mapping::MappingDSL;
 
 use stepflow_dto::dto::error_policy::{RetryPolicy, CatchPolicy};
@@ -9,12 +10,28 @@ pub struct BaseState {
     #[serde(default)]
     pub comment: Option<String>,
 
+    /// 进入状态前从 context 中选取输入（先于 inputMapping）
+    #[serde(default)]
+    pub input_path: Option<String>,
+
     #[serde(default)]
     pub input_mapping: Option<MappingDSL>,
 
+    /// 用载荷模板（`"key.$": "$.path"` / `States.*` 函数）重塑状态结果，先于 outputMapping
+    #[serde(default)]
+    pub result_selector: Option<Value>,
+
     #[serde(default)]
     pub output_mapping: Option<MappingDSL>,
 
+    /// 结果写入 context 的位置（`$` 或 `$.a.b`）；缺省时按 outputMapping 的合并策略浅合并
+    #[serde(default)]
+    pub result_path: Option<String>,
+
+    /// 状态结束后从 context 中选取传给下一状态的部分
+    #[serde(default)]
+    pub output_path: Option<String>,
+
     #[serde(default)]
     pub retry: Option<Vec<RetryPolicy>>,
 
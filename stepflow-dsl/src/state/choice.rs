@@ -11,6 +11,7 @@ pub struct ChoiceState {
 
     pub choices: Vec<ChoiceRule>,
 
-    #[serde(default)]
+    /// 兼容 Step Functions 风格的 `default` 写法
+    #[serde(default, alias = "default")]
     pub default_next: Option<String>,
 }
\ No newline at end of file
@@ -15,4 +15,12 @@ pub struct MapState {
 
     #[serde(default)]
     pub max_concurrency: Option<u32>,
+
+    /// 允许失败的 item 百分比（0-100），超过后整个 Map 失败；未设置 = 0
+    #[serde(default)]
+    pub tolerated_failure_percentage: Option<f64>,
+
+    /// 全部 item 完成的时限（秒），超时以 `States.Timeout` 失败
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
 }
\ No newline at end of file
@@ -7,6 +7,8 @@ pub mod succeed;
 pub mod fail;
 pub mod parallel;
 pub mod map;
+pub mod sub_workflow;
+pub mod wait_for_signal;
 
 use serde::{Deserialize, Serialize};
 
@@ -19,6 +21,8 @@ pub use succeed::SucceedState;
 pub use fail::FailState;
 pub use parallel::ParallelState;
 pub use map::MapState;
+pub use sub_workflow::SubWorkflowState;
+pub use wait_for_signal::WaitForSignalState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -31,6 +35,8 @@ pub enum State {
     Fail(FailState),
     Parallel(ParallelState),
     Map(MapState),
+    SubWorkflow(SubWorkflowState),
+    WaitForSignal(WaitForSignalState),
 }
 
 impl State {
@@ -44,6 +50,35 @@ impl State {
             State::Fail(_) => "fail",
             State::Parallel(_) => "parallel",
             State::Map(_) => "map",
+            State::SubWorkflow(_) => "subWorkflow",
+            State::WaitForSignal(_) => "waitForSignal",
+        }
+    }
+
+    /// 各状态共有的字段
+    pub fn base(&self) -> &BaseState {
+        match self {
+            State::Task(s) => &s.base,
+            State::Pass(s) => &s.base,
+            State::Wait(s) => &s.base,
+            State::Choice(s) => &s.base,
+            State::Succeed(s) => &s.base,
+            State::Fail(s) => &s.base,
+            State::Parallel(s) => &s.base,
+            State::Map(s) => &s.base,
+            State::SubWorkflow(s) => &s.base,
+            State::WaitForSignal(s) => &s.base,
+        }
+    }
+
+    /// 状态声明的 `timeoutSeconds`（仅 Task / Parallel / Map / SubWorkflow 支持）
+    pub fn timeout_seconds(&self) -> Option<u64> {
+        match self {
+            State::Task(s) => s.timeout_seconds,
+            State::Parallel(s) => s.timeout_seconds,
+            State::Map(s) => s.timeout_seconds,
+            State::SubWorkflow(s) => s.timeout_seconds,
+            _ => None,
         }
     }
 }
\ No newline at end of file
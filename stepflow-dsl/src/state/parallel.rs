@@ -13,4 +13,8 @@ pub struct ParallelState {
 
     #[serde(default)]
     pub max_concurrency: Option<u32>,
+
+    /// 全部分支完成的时限（秒），超时以 `States.Timeout` 失败
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
 }
\ No newline at end of file
@@ -9,9 +9,7 @@ pub struct PassState {
     #[serde(flatten)]
     pub base: BaseState,
 
+    /// 固定结果；缺省时以状态输入作为结果
     #[serde(default)]
     pub result: Option<Value>,
-
-    #[serde(default)]
-    pub result_path: Option<String>,
 }
\ No newline at end of file
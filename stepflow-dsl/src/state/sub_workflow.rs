@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use super::base::BaseState;
+
+/// 以已保存的模板启动一个子执行，等待其结束后继续；输入 / 输出沿用 BaseState 的 mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubWorkflowState {
+    #[serde(flatten)]
+    pub base: BaseState,
+
+    pub template_id: String,
+
+    /// 运行的模板修订号；与 `templateAlias` 都未设置时使用最新发布的修订
+    #[serde(default)]
+    pub template_version: Option<i64>,
+
+    /// 运行别名（如 `stable`）指向的修订，与 `templateVersion` 二选一
+    #[serde(default)]
+    pub template_alias: Option<String>,
+
+    /// 等待子执行结束的时限（秒），超时以 `States.Timeout` 失败
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
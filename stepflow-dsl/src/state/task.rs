@@ -11,6 +11,11 @@ pub struct TaskState {
 
     pub resource: String,
 
+    /// 入队的命名任务队列，只有轮询该队列的 worker 会领取；缺省为 `resource`
+    #[serde(default)]
+    pub task_queue: Option<String>,
+
+    /// 载荷模板，求值后作为工具调用的 `parameters`（状态输入作为 `input`）
     #[serde(default)]
     pub parameters: Option<Value>,
 
@@ -20,6 +25,25 @@ pub struct TaskState {
     #[serde(default)]
     pub heartbeat_seconds: Option<u32>,
 
+    /// 单次尝试的超时（秒），超时以 `States.Timeout` 失败，可被 Retry / Catch 处理
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+
     #[serde(default)]
     pub heartbeat_expr: Option<String>,
+
+    /// 回调模式：不调用工具，引擎签发 task token 后挂起，直到外部系统携带 token 回调
+    #[serde(default)]
+    pub wait_for_task_token: Option<bool>,
+}
+
+impl TaskState {
+    /// Deferred 模式下任务入队的队列：`taskQueue`，未设置时为 `resource`
+    pub fn task_queue(&self) -> &str {
+        self.task_queue.as_deref().unwrap_or(&self.resource)
+    }
+
+    pub fn waits_for_task_token(&self) -> bool {
+        self.wait_for_task_token.unwrap_or(false)
+    }
 }
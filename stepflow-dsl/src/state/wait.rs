@@ -11,6 +11,15 @@ pub struct WaitState {
     #[serde(default)]
     pub seconds: Option<u64>,
 
+    /// RFC3339 绝对时间
     #[serde(default)]
     pub timestamp: Option<String>,
+
+    /// 从输入中按 JSONPath 读取等待秒数
+    #[serde(default)]
+    pub seconds_path: Option<String>,
+
+    /// 从输入中按 JSONPath 读取 RFC3339 绝对时间
+    #[serde(default)]
+    pub timestamp_path: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use super::base::BaseState;
+
+/// 挂起直到收到发给本执行的同名外部信号；信号 payload 作为状态输出交给 OutputMapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForSignalState {
+    #[serde(flatten)]
+    pub base: BaseState,
+
+    pub signal_name: String,
+}
@@ -1,113 +1,454 @@
-use crate::WorkflowDSL;
+//! WorkflowDSL 静态校验：一次返回全部问题，每个问题带状态路径。
+//!
+//! * 路径形如 `Check.choices[1].next`、`Par.branches[0].Inner`、`MapX.iterator.startAt`
+//! * 覆盖：转移目标存在性、next/end 冲突与缺失、终止状态、可达性、Choice 规则、
+//!   JSONPath 语法、输入输出路径与载荷模板（Parameters / ResultSelector）、内嵌 MappingDSL、
+//!   SubWorkflow 的模板引用，以及 Parallel / Map 的嵌套分支
+
+use crate::branch::Branch;
 use crate::state::State;
+use crate::WorkflowDSL;
+use jsonpath_lib::select;
+use serde_json::Value;
+use stepflow_exception::STATES_ALL;
+use stepflow_mapping::intrinsic::validate_payload;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
 use thiserror::Error;
-use std::collections::HashSet;
 
 #[derive(Error, Debug)]
 pub enum ValidationError {
-    #[error("Start state '{0}' not found in states")]
-    StartStateNotFound(String),
-    
-    #[error("State '{0}' referenced in 'next' field not found")]
-    NextStateNotFound(String),
-    
+    #[error("{0}: start state '{1}' not found in states")]
+    StartStateNotFound(String, String),
+
+    #[error("{0}: transition target '{1}' not found")]
+    NextStateNotFound(String, String),
+
     #[error("State '{0}' has both 'next' and 'end' fields set")]
     NextAndEndConflict(String),
-    
-    #[error("No end state found in workflow")]
-    NoEndState,
-    
+
+    #[error("State '{0}' has neither 'next' nor 'end'")]
+    MissingTransition(String),
+
+    #[error("No end state found in {0}")]
+    NoEndState(String),
+
+    #[error("State '{0}' is unreachable from startAt")]
+    UnreachableState(String),
+
     #[error("Invalid state type for '{0}': {1}")]
     InvalidStateType(String, String),
-    
+
     #[error("Missing required field in state '{0}': {1}")]
     MissingRequiredField(String, String),
+
+    #[error("Invalid choice rule #{1} in state '{0}': {2}")]
+    InvalidChoiceRule(String, usize, String),
+
+    #[error("{0}: invalid path: {1}")]
+    InvalidPath(String, String),
+
+    #[error("{0}: invalid mapping: {1}")]
+    InvalidMapping(String, String),
+
+    #[error("{0}: invalid payload template: {1}")]
+    InvalidPayload(String, String),
+
+    #[error("{0}: not supported on {1} states")]
+    UnsupportedField(String, String),
+
+    #[error("{0}: timeoutSeconds must be greater than 0")]
+    InvalidTimeout(String),
+
+    #[error("{0}: invalid template reference: {1}")]
+    InvalidTemplateReference(String, String),
+
+    #[error("{0}: taskQueue must not be empty")]
+    EmptyTaskQueue(String),
+
+    #[error("{0}: invalid retry policy: {1}")]
+    InvalidRetry(String, String),
+
+    #[error("{0}: invalid errorEquals: {1}")]
+    InvalidErrorEquals(String, String),
+
+    #[error("{0}: fields are mutually exclusive: {1}")]
+    MutuallyExclusiveFields(String, String),
+}
+
+/// 校验发现的全部问题（按状态名排序，结果稳定）
+#[derive(Debug)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationError> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msgs: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", msgs.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// 校验 JSONPath 语法（不求值）
+pub(crate) fn check_json_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('$') {
+        return Err(format!("'{path}' must start with '$'"));
+    }
+    select(&Value::Null, path)
+        .map(|_| ())
+        .map_err(|e| format!("'{path}': {e}"))
+}
+
+/// 各状态类型不支持的输入输出字段：Choice 按原始 context 判断分支，Fail 没有输出；
+/// 不产生结果的状态不支持 resultSelector / resultPath
+fn unsupported_fields(state: &State) -> &'static [&'static str] {
+    match state {
+        State::Choice(_) | State::Fail(_) => &["inputPath", "resultSelector", "resultPath", "outputPath"],
+        State::Wait(_) | State::Succeed(_) => &["resultSelector", "resultPath"],
+        State::Pass(_) => &["resultSelector"],
+        _ => &[],
+    }
+}
+
+/// `errorEquals` 中的 `States.ALL`（及 `*`）必须单独出现，且只能出现在最后一条策略中
+fn check_catch_all(path: &str, policies: Vec<&Vec<String>>, errors: &mut Vec<ValidationError>) {
+    let last = policies.len().saturating_sub(1);
+    for (idx, error_equals) in policies.into_iter().enumerate() {
+        if !error_equals.iter().any(|e| e == STATES_ALL || e == "*") {
+            continue;
+        }
+        let field = format!("{path}[{idx}].errorEquals");
+        if error_equals.len() > 1 {
+            errors.push(ValidationError::InvalidErrorEquals(
+                field.clone(),
+                format!("{STATES_ALL} must appear alone"),
+            ));
+        }
+        if idx != last {
+            errors.push(ValidationError::InvalidErrorEquals(
+                field,
+                format!("{STATES_ALL} is only allowed in the last policy"),
+            ));
+        }
+    }
+}
+
+/// ResultPath 仅支持 `$` 或点号路径 `$.a.b`（与引擎的写入实现一致）
+fn check_result_path(path: &str) -> Result<(), String> {
+    if path == "$" {
+        return Ok(());
+    }
+    match path.strip_prefix("$.") {
+        Some(rest) if rest.split('.').all(|k| !k.is_empty()) => Ok(()),
+        _ => Err(format!("'{path}' must be '$' or a dotted path like '$.a.b'")),
+    }
+}
+
+/// 状态的全部出边：`(字段路径后缀, 目标)`
+fn transitions(state: &State) -> Vec<(String, &str)> {
+    let base = state.base();
+    let mut out = Vec::new();
+    if let Some(next) = &base.next {
+        out.push(("next".to_string(), next.as_str()));
+    }
+    if let State::Choice(choice) = state {
+        for (idx, rule) in choice.choices.iter().enumerate() {
+            out.push((format!("choices[{idx}].next"), rule.next.as_str()));
+        }
+        if let Some(default) = &choice.default_next {
+            out.push(("defaultNext".to_string(), default.as_str()));
+        }
+    }
+    for (idx, catcher) in base.catch.iter().flatten().enumerate() {
+        out.push((format!("catch[{idx}].next"), catcher.next.as_str()));
+    }
+    out
 }
 
 impl WorkflowDSL {
-    /// Validates the workflow definition
-    pub fn validate(&self) -> Result<(), ValidationError> {
-        // 1. Verify start state exists
-        if !self.states.contains_key(&self.start_at) {
-            return Err(ValidationError::StartStateNotFound(self.start_at.clone()));
-        }
-
-        // 2. Track visited states to ensure all states are reachable
-        let mut visited = HashSet::new();
-        let mut has_end_state = false;
-
-        // 3. Validate each state
-        for (name, state) in &self.states {
-            self.validate_state(name, state, &mut visited, &mut has_end_state)?;
-        }
-
-        // 4. Verify at least one end state exists
-        if !has_end_state {
-            return Err(ValidationError::NoEndState);
-        }
-
-        Ok(())
-    }
-
-    fn validate_state(
-        &self,
-        name: &str,
-        state: &State,
-        visited: &mut HashSet<String>,
-        has_end_state: &mut bool,
-    ) -> Result<(), ValidationError> {
-        visited.insert(name.to_string());
-
-        // Get base state for common validations
-        let base = match state {
-            State::Task(t) => &t.base,
-            State::Pass(p) => &p.base,
-            State::Wait(w) => &w.base,
-            State::Choice(c) => &c.base,
-            State::Succeed(s) => &s.base,
-            State::Fail(f) => &f.base,
-            State::Parallel(p) => &p.base,
-            State::Map(m) => &m.base,
-        };
-
-        // Check next state exists if specified
-        if let Some(next) = &base.next {
-            if !self.states.contains_key(next) {
-                return Err(ValidationError::NextStateNotFound(next.clone()));
+    /// 校验整个工作流定义，返回发现的全部问题
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = Vec::new();
+        validate_scope(&self.start_at, self.on_cancel.as_deref(), &self.states, "", &mut errors);
+        if self.timeout_seconds == Some(0) {
+            errors.push(ValidationError::InvalidTimeout("timeoutSeconds".into()));
+        }
+        if let Some(cleanup) = &self.on_cancel
+            && !self.states.contains_key(cleanup)
+        {
+            errors.push(ValidationError::NextStateNotFound("onCancel".into(), cleanup.clone()));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(errors))
+        }
+    }
+}
+
+/// 校验一个状态集合（顶层工作流或 Parallel / Map 的分支），`prefix` 为路径前缀；
+/// `on_cancel` 为取消时进入的清理状态，从它出发可达的状态不视为不可达
+fn validate_scope(
+    start_at: &str,
+    on_cancel: Option<&str>,
+    states: &HashMap<String, State>,
+    prefix: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let sorted: BTreeMap<&String, &State> = states.iter().collect();
+
+    // 1. 起始状态
+    if !states.contains_key(start_at) {
+        errors.push(ValidationError::StartStateNotFound(
+            format!("{prefix}startAt"),
+            start_at.to_string(),
+        ));
+    }
+
+    // 2. 逐个状态校验
+    let mut has_end_state = false;
+    for (name, state) in &sorted {
+        let base = state.base();
+        if base.end.unwrap_or(false) || matches!(state, State::Succeed(_) | State::Fail(_)) {
+            has_end_state = true;
+        }
+        validate_state(&format!("{prefix}{name}"), state, states, errors);
+    }
+
+    // 3. 至少一个终止状态
+    if !has_end_state {
+        let scope = prefix.strip_suffix('.').unwrap_or("workflow");
+        errors.push(ValidationError::NoEndState(scope.to_string()));
+    }
+
+    // 4. 可达性：从 startAt（及 onCancel 清理入口）出发沿所有转移 BFS
+    if states.contains_key(start_at) {
+        let roots: Vec<&str> = std::iter::once(start_at)
+            .chain(on_cancel.filter(|name| states.contains_key(*name)))
+            .collect();
+        let mut reached: HashSet<&str> = roots.iter().copied().collect();
+        let mut queue = VecDeque::from(roots);
+        while let Some(name) = queue.pop_front() {
+            let Some(state) = states.get(name) else { continue };
+            for (_, target) in transitions(state) {
+                if states.contains_key(target) && reached.insert(target) {
+                    queue.push_back(target);
+                }
             }
         }
+        for name in sorted.keys() {
+            if !reached.contains(name.as_str()) {
+                errors.push(ValidationError::UnreachableState(format!("{prefix}{name}")));
+            }
+        }
+    }
+}
 
-        // Check for next/end conflict
-        if base.next.is_some() && base.end.unwrap_or(false) {
-            return Err(ValidationError::NextAndEndConflict(name.to_string()));
+fn validate_state(
+    path: &str,
+    state: &State,
+    states: &HashMap<String, State>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let base = state.base();
+    let is_end = base.end.unwrap_or(false);
+
+    // 转移目标必须存在
+    for (field, target) in transitions(state) {
+        if !states.contains_key(target) {
+            errors.push(ValidationError::NextStateNotFound(
+                format!("{path}.{field}"),
+                target.to_string(),
+            ));
         }
+    }
 
-        // Mark if this is an end state
-        if base.end.unwrap_or(false) {
-            *has_end_state = true;
+    // next / end
+    if base.next.is_some() && is_end {
+        errors.push(ValidationError::NextAndEndConflict(path.to_string()));
+    }
+    let self_terminating = matches!(state, State::Choice(_) | State::Succeed(_) | State::Fail(_));
+    if !self_terminating && base.next.is_none() && !is_end {
+        errors.push(ValidationError::MissingTransition(path.to_string()));
+    }
+
+    if state.timeout_seconds() == Some(0) {
+        errors.push(ValidationError::InvalidTimeout(format!("{path}.timeoutSeconds")));
+    }
+
+    // Retry：退避倍率不小于 1，间隔与重试次数为正数
+    for (idx, retrier) in base.retry.iter().flatten().enumerate() {
+        let field = |name: &str| format!("{path}.retry[{idx}].{name}");
+        if let Some(rate) = retrier.backoff_rate
+            && !(rate >= 1.0 && rate.is_finite())
+        {
+            errors.push(ValidationError::InvalidRetry(field("backoffRate"), format!("{rate} must be at least 1.0")));
+        }
+        if retrier.interval_seconds == Some(0) {
+            errors.push(ValidationError::InvalidRetry(field("intervalSeconds"), "must be greater than 0".into()));
+        }
+        if retrier.max_attempts == Some(0) {
+            errors.push(ValidationError::InvalidRetry(field("maxAttempts"), "must be greater than 0".into()));
+        }
+    }
+
+    // States.ALL 须单独出现，且只能用于最后一个 Retrier / Catcher
+    let retriers = base.retry.iter().flatten().map(|r| &r.error_equals);
+    let catchers = base.catch.iter().flatten().map(|c| &c.error_equals);
+    check_catch_all(&format!("{path}.retry"), retriers.collect(), errors);
+    check_catch_all(&format!("{path}.catch"), catchers.collect(), errors);
+
+    // Catch ResultPath
+    for (idx, catcher) in base.catch.iter().flatten().enumerate() {
+        if let Some(rp) = &catcher.result_path
+            && let Err(reason) = check_result_path(rp)
+        {
+            errors.push(ValidationError::InvalidPath(format!("{path}.catch[{idx}].resultPath"), reason));
         }
+    }
 
-        // State-specific validations
-        match state {
-            State::Task(task) => {
-                if task.resource.is_empty() {
-                    return Err(ValidationError::MissingRequiredField(
-                        name.to_string(),
-                        "resource".to_string(),
-                    ));
-                }
+    // 输入输出路径与 ResultSelector
+    let declared = [
+        ("inputPath", base.input_path.is_some()),
+        ("resultSelector", base.result_selector.is_some()),
+        ("resultPath", base.result_path.is_some()),
+        ("outputPath", base.output_path.is_some()),
+    ];
+    for (field, _) in declared.iter().filter(|(f, set)| *set && unsupported_fields(state).contains(f)) {
+        errors.push(ValidationError::UnsupportedField(
+            format!("{path}.{field}"),
+            state.variant_name().to_string(),
+        ));
+    }
+    for (field, value) in [("inputPath", &base.input_path), ("outputPath", &base.output_path)] {
+        if let Some(p) = value
+            && let Err(reason) = check_json_path(p)
+        {
+            errors.push(ValidationError::InvalidPath(format!("{path}.{field}"), reason));
+        }
+    }
+    if let Some(rp) = &base.result_path
+        && let Err(reason) = check_result_path(rp)
+    {
+        errors.push(ValidationError::InvalidPath(format!("{path}.resultPath"), reason));
+    }
+    for (field, reason) in base.result_selector.iter().flat_map(validate_payload) {
+        errors.push(ValidationError::InvalidPayload(format!("{path}.resultSelector.{field}"), reason));
+    }
+
+    // 内嵌 MappingDSL
+    for (field, mapping) in [("inputMapping", &base.input_mapping), ("outputMapping", &base.output_mapping)] {
+        for (rule, err) in mapping.iter().flat_map(|m| m.validate()) {
+            errors.push(ValidationError::InvalidMapping(format!("{path}.{field}.{rule}"), err.to_string()));
+        }
+    }
+
+    let mut check_path = |field: &str, value: Option<&String>| {
+        if let Some(p) = value
+            && let Err(reason) = check_json_path(p)
+        {
+            errors.push(ValidationError::InvalidPath(format!("{path}.{field}"), reason));
+        }
+    };
+
+    // 各类型特有的校验
+    match state {
+        State::Task(task) => {
+            check_path("heartbeatExpr", task.heartbeat_expr.as_ref());
+            for (field, reason) in task.parameters.iter().flat_map(validate_payload) {
+                errors.push(ValidationError::InvalidPayload(format!("{path}.parameters.{field}"), reason));
+            }
+            if task.resource.is_empty() {
+                errors.push(ValidationError::MissingRequiredField(path.to_string(), "resource".to_string()));
+            }
+            if task.task_queue.as_deref().is_some_and(|q| q.trim().is_empty()) {
+                errors.push(ValidationError::EmptyTaskQueue(format!("{path}.taskQueue")));
+            }
+        }
+        State::Wait(wait) => {
+            check_path("secondsPath", wait.seconds_path.as_ref());
+            check_path("timestampPath", wait.timestamp_path.as_ref());
+            // seconds / timestamp / secondsPath / timestampPath 必须且只能设置一个
+            let durations: Vec<&str> = [
+                ("seconds", wait.seconds.is_some()),
+                ("timestamp", wait.timestamp.is_some()),
+                ("secondsPath", wait.seconds_path.is_some()),
+                ("timestampPath", wait.timestamp_path.is_some()),
+            ]
+            .into_iter()
+            .filter_map(|(field, set)| set.then_some(field))
+            .collect();
+            match durations.len() {
+                0 => errors.push(ValidationError::MissingRequiredField(
+                    path.to_string(),
+                    "seconds, timestamp, secondsPath or timestampPath".to_string(),
+                )),
+                1 => {}
+                _ => errors.push(ValidationError::MutuallyExclusiveFields(
+                    path.to_string(),
+                    durations.join(", "),
+                )),
+            }
+        }
+        State::Choice(choice) => {
+            if choice.choices.is_empty() && choice.default_next.is_none() {
+                errors.push(ValidationError::MissingRequiredField(
+                    path.to_string(),
+                    "choices or default_next".to_string(),
+                ));
             }
-            State::Choice(choice) => {
-                if choice.choices.is_empty() && choice.default_next.is_none() {
-                    return Err(ValidationError::MissingRequiredField(
-                        name.to_string(),
-                        "choices or default_next".to_string(),
-                    ));
+            for (idx, rule) in choice.choices.iter().enumerate() {
+                if let Err(reason) = rule.condition.validate() {
+                    errors.push(ValidationError::InvalidChoiceRule(path.to_string(), idx, reason));
                 }
             }
-            _ => {}
         }
-
-        Ok(())
+        State::Parallel(parallel) => {
+            if parallel.branches.is_empty() {
+                errors.push(ValidationError::MissingRequiredField(path.to_string(), "branches".to_string()));
+            }
+            for (idx, branch) in parallel.branches.iter().enumerate() {
+                validate_branch(branch, &format!("{path}.branches[{idx}]."), errors);
+            }
+        }
+        State::Map(map) => {
+            check_path("itemsPath", Some(&map.items_path));
+            validate_branch(&map.iterator, &format!("{path}.iterator."), errors);
+        }
+        State::SubWorkflow(sub) => {
+            if sub.template_id.is_empty() {
+                errors.push(ValidationError::MissingRequiredField(path.to_string(), "templateId".to_string()));
+            }
+            if sub.template_version.is_some() && sub.template_alias.is_some() {
+                errors.push(ValidationError::InvalidTemplateReference(
+                    path.to_string(),
+                    "templateVersion and templateAlias are mutually exclusive".to_string(),
+                ));
+            }
+            if let Some(revision) = sub.template_version
+                && revision < 1
+            {
+                errors.push(ValidationError::InvalidTemplateReference(
+                    path.to_string(),
+                    format!("templateVersion must be at least 1, got {revision}"),
+                ));
+            }
+        }
+        State::WaitForSignal(wait) => {
+            if wait.signal_name.is_empty() {
+                errors.push(ValidationError::MissingRequiredField(path.to_string(), "signalName".to_string()));
+            }
+        }
+        State::Pass(_) | State::Succeed(_) | State::Fail(_) => {}
     }
-} 
\ No newline at end of file
+}
+
+fn validate_branch(branch: &Branch, prefix: &str, errors: &mut Vec<ValidationError>) {
+    validate_scope(&branch.start_at, None, &branch.states, prefix, errors);
+}
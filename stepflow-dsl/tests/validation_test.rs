@@ -32,10 +32,8 @@ fn test_invalid_start_state() {
     });
 
     let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
-    match workflow.validate() {
-        Err(ValidationError::StartStateNotFound(_)) => (),
-        _ => panic!("Expected StartStateNotFound error"),
-    }
+    let errors = workflow.validate().unwrap_err();
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::StartStateNotFound(_, _))), "{errors}");
 }
 
 #[test]
@@ -52,10 +50,8 @@ fn test_invalid_next_state() {
     });
 
     let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
-    match workflow.validate() {
-        Err(ValidationError::NextStateNotFound(_)) => (),
-        _ => panic!("Expected NextStateNotFound error"),
-    }
+    let errors = workflow.validate().unwrap_err();
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::NextStateNotFound(_, _))), "{errors}");
 }
 
 #[test]
@@ -78,10 +74,8 @@ fn test_next_and_end_conflict() {
     });
 
     let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
-    match workflow.validate() {
-        Err(ValidationError::NextAndEndConflict(_)) => (),
-        _ => panic!("Expected NextAndEndConflict error"),
-    }
+    let errors = workflow.validate().unwrap_err();
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::NextAndEndConflict(_))), "{errors}");
 }
 
 #[test]
@@ -103,8 +97,360 @@ fn test_no_end_state() {
     });
 
     let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
-    match workflow.validate() {
-        Err(ValidationError::NoEndState) => (),
-        _ => panic!("Expected NoEndState error"),
+    let errors = workflow.validate().unwrap_err();
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::NoEndState(_))), "{errors}");
+} 
+#[test]
+fn test_invalid_choice_operator() {
+    let workflow_json = json!({
+        "startAt": "Check",
+        "states": {
+            "Check": {
+                "type": "choice",
+                "choices": [
+                    { "condition": { "variable": "$.name", "operator": "StringMatches", "value": "a*" }, "next": "Done" },
+                    { "condition": { "variable": "$.x", "operator": "Resembles", "value": 1 }, "next": "Done" }
+                ],
+                "defaultNext": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    });
+
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    let errors = workflow.validate().unwrap_err();
+    match errors.0.as_slice() {
+        [ValidationError::InvalidChoiceRule(state, 1, reason)] => {
+            assert_eq!(state, "Check");
+            assert!(reason.contains("Resembles"), "{reason}");
+        }
+        other => panic!("Expected InvalidChoiceRule error, got {other:?}"),
     }
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_reports_all_problems_with_paths() {
+    let workflow_json = json!({
+        "startAt": "Check",
+        "states": {
+            "Check": {
+                "type": "choice",
+                "choices": [
+                    { "condition": { "variable": "$.x", "operator": "NumericEquals", "value": 1 }, "next": "Missing" }
+                ],
+                "defaultNext": "Par"
+            },
+            "Par": {
+                "type": "parallel",
+                "branches": [{
+                    "startAt": "Inner",
+                    "states": {
+                        "Inner": {
+                            "type": "task",
+                            "resource": "http",
+                            "inputMapping": { "mappings": [{ "key": "a", "type": "jsonPath", "source": "$.[" }] }
+                        }
+                    }
+                }],
+                "catch": [{ "errorEquals": ["States.ALL"], "next": "Nowhere" }],
+                "next": "Done"
+            },
+            "Orphan": { "type": "wait", "secondsPath": "delay", "end": true },
+            "Done": { "type": "succeed" }
+        }
+    });
+
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    let errors: Vec<String> = workflow.validate().unwrap_err().iter().map(|e| e.to_string()).collect();
+    let expected = [
+        "Check.choices[0].next: transition target 'Missing' not found",
+        "Orphan.secondsPath: invalid path: 'delay' must start with '$'",
+        "Par.catch[0].next: transition target 'Nowhere' not found",
+        "State 'Par.branches[0].Inner' has neither 'next' nor 'end'",
+        "Par.branches[0].Inner.inputMapping.a: invalid mapping: jsonpath error: '$.['",
+        "No end state found in Par.branches[0]",
+        "State 'Orphan' is unreachable from startAt",
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:?}");
+    for (actual, prefix) in errors.iter().zip(expected) {
+        assert!(actual.starts_with(prefix), "{actual} !~ {prefix}");
+    }
+}
+
+#[test]
+fn test_sub_workflow_requires_template_id() {
+    let workflow_json = json!({
+        "startAt": "Child",
+        "states": {
+            "Child": { "type": "subWorkflow", "templateId": "", "end": true }
+        }
+    });
+
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    let errors = workflow.validate().unwrap_err();
+    match errors.0.as_slice() {
+        [ValidationError::MissingRequiredField(state, field)] => {
+            assert_eq!(state, "Child");
+            assert_eq!(field, "templateId");
+        }
+        other => panic!("Expected MissingRequiredField error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_sub_workflow_template_reference_is_checked() {
+    let workflow_json = json!({
+        "startAt": "Child",
+        "states": {
+            "Child": { "type": "subWorkflow", "templateId": "tpl", "templateVersion": 2, "templateAlias": "stable", "next": "Old" },
+            "Old": { "type": "subWorkflow", "templateId": "tpl", "templateVersion": 0, "end": true }
+        }
+    });
+
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    let errors = workflow.validate().unwrap_err().0;
+    let states: Vec<&str> = errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::InvalidTemplateReference(state, _) => Some(state.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(states, vec!["Child", "Old"]);
+}
+
+#[test]
+fn test_on_cancel_cleanup_states_are_reachable() {
+    let workflow_json = json!({
+        "startAt": "Work",
+        "onCancel": "Cleanup",
+        "states": {
+            "Work": { "type": "pass", "end": true },
+            "Cleanup": { "type": "pass", "next": "Cleaned" },
+            "Cleaned": { "type": "succeed" }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    assert!(workflow.validate().is_ok());
+
+    let mut broken = workflow.clone();
+    broken.on_cancel = Some("Missing".into());
+    let errors = broken.validate().unwrap_err().0;
+    assert!(
+        errors.iter().any(|e| matches!(e, ValidationError::NextStateNotFound(path, target) if path == "onCancel" && target == "Missing")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn test_zero_timeout_is_rejected() {
+    let workflow_json = json!({
+        "startAt": "Work",
+        "timeoutSeconds": 0,
+        "states": {
+            "Work": { "type": "task", "resource": "http", "timeoutSeconds": 0, "next": "Fan" },
+            "Fan": {
+                "type": "parallel",
+                "timeoutSeconds": 5,
+                "branches": [{ "startAt": "A", "states": { "A": { "type": "succeed" } } }],
+                "end": true
+            }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    assert_eq!(workflow.states["Fan"].timeout_seconds(), Some(5));
+
+    let errors = workflow.validate().unwrap_err().0;
+    let paths: Vec<&str> = errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::InvalidTimeout(path) => Some(path.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(paths, vec!["Work.timeoutSeconds", "timeoutSeconds"]);
+}
+
+#[test]
+fn test_payload_templates_and_io_paths_are_checked() {
+    let workflow_json = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "inputPath": "$.order",
+                "parameters": {
+                    "url.$": "States.Format('https://x/{}', $.id)",
+                    "headers": { "trace.$": "States.Unknown()" }
+                },
+                "resultSelector": { "items.$": "States.ArrayLength()" },
+                "resultPath": "order.result",
+                "next": "Check"
+            },
+            "Check": {
+                "type": "choice",
+                "outputPath": "$.order",
+                "choices": [],
+                "defaultNext": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+
+    let errors = workflow.validate().unwrap_err().0;
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 4, "{messages:?}");
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::InvalidPayload(path, _) if path == "Call.parameters.headers.trace.$")));
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::InvalidPayload(path, _) if path == "Call.resultSelector.items.$")));
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::InvalidPath(path, _) if path == "Call.resultPath")));
+    assert!(errors.iter().any(|e| matches!(e, ValidationError::UnsupportedField(path, ty) if path == "Check.outputPath" && ty == "choice")));
+}
+
+#[test]
+fn test_task_queue_defaults_to_resource_and_rejects_blank() {
+    let workflow_json = json!({
+        "startAt": "Render",
+        "states": {
+            "Render": { "type": "task", "resource": "shell", "taskQueue": "gpu", "next": "Notify" },
+            "Notify": { "type": "task", "resource": "http", "next": "Broken" },
+            "Broken": { "type": "task", "resource": "http", "taskQueue": " ", "end": true }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+    let queue = |name: &str| match &workflow.states[name] {
+        stepflow_dsl::State::Task(task) => task.task_queue().to_string(),
+        other => panic!("Expected task state, got {other:?}"),
+    };
+    assert_eq!(queue("Render"), "gpu");
+    assert_eq!(queue("Notify"), "http");
+
+    let errors = workflow.validate().unwrap_err().0;
+    assert!(
+        matches!(errors.as_slice(), [ValidationError::EmptyTaskQueue(path)] if path == "Broken.taskQueue"),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn test_retry_policy_bounds_are_checked() {
+    let workflow_json = json!({
+        "startAt": "Work",
+        "states": {
+            "Work": {
+                "type": "task",
+                "resource": "http",
+                "retry": [
+                    { "errorEquals": ["Http.Timeout"], "intervalSeconds": 2, "backoffRate": 1.5, "maxAttempts": 3 },
+                    { "errorEquals": ["Http.Busy"], "intervalSeconds": 0, "backoffRate": 0.5, "maxAttempts": 0 }
+                ],
+                "end": true
+            }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+
+    let errors = workflow.validate().unwrap_err().0;
+    let paths: Vec<&str> = errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::InvalidRetry(path, _) => Some(path.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        vec!["Work.retry[1].backoffRate", "Work.retry[1].intervalSeconds", "Work.retry[1].maxAttempts"]
+    );
+}
+
+#[test]
+fn test_states_all_must_be_alone_and_last() {
+    let workflow_json = json!({
+        "startAt": "Work",
+        "states": {
+            "Work": {
+                "type": "task",
+                "resource": "http",
+                "retry": [
+                    { "errorEquals": ["States.ALL"], "maxAttempts": 2 },
+                    { "errorEquals": ["Http.Timeout"], "maxAttempts": 3 }
+                ],
+                "catch": [
+                    { "errorEquals": ["Http.Timeout"], "next": "Done" },
+                    { "errorEquals": ["States.ALL", "Http.Busy"], "next": "Done" }
+                ],
+                "next": "Done"
+            },
+            "Done": {
+                "type": "succeed",
+                "retry": [{ "errorEquals": ["Custom.Error"] }, { "errorEquals": ["States.ALL"] }]
+            }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+
+    let errors = workflow.validate().unwrap_err().0;
+    let found: Vec<(&str, &str)> = errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::InvalidErrorEquals(path, reason) => Some((path.as_str(), reason.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("Work.retry[0].errorEquals", "States.ALL is only allowed in the last policy"),
+            ("Work.catch[1].errorEquals", "States.ALL must appear alone"),
+        ]
+    );
+}
+
+#[test]
+fn test_wait_requires_exactly_one_duration() {
+    let workflow_json = json!({
+        "startAt": "Both",
+        "states": {
+            "Both": { "type": "wait", "seconds": 5, "timestampPath": "$.at", "next": "None" },
+            "None": { "type": "wait", "next": "Par" },
+            "Par": {
+                "type": "parallel",
+                "branches": [{
+                    "startAt": "Inner",
+                    "states": {
+                        "Inner": {
+                            "type": "wait",
+                            "timestamp": "2030-01-01T00:00:00Z",
+                            "secondsPath": "$.delay",
+                            "end": true
+                        }
+                    }
+                }],
+                "end": true
+            }
+        }
+    });
+    let workflow: WorkflowDSL = serde_json::from_value(workflow_json).unwrap();
+
+    let errors = workflow.validate().unwrap_err().0;
+    let conflicts: Vec<(&str, &str)> = errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::MutuallyExclusiveFields(path, fields) => Some((path.as_str(), fields.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            ("Both", "seconds, timestampPath"),
+            ("Par.branches[0].Inner", "timestamp, secondsPath"),
+        ]
+    );
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        ValidationError::MissingRequiredField(state, _) if state == "None"
+    )));
+}
@@ -18,7 +18,7 @@ pub struct ListQuery {
 fn default_limit() -> i64 { 20 }
 
 /// 活动任务详情
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivityTaskDto {
     /// 任务令牌
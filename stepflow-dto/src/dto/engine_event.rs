@@ -7,11 +7,28 @@ pub enum EngineEvent {
     // === 核心流程 ===
     WorkflowStarted {
         run_id: String,
+        #[serde(default)]
+        input: Value,
     },
     WorkflowFinished {
         run_id: String,
         result: Value,
     },
+    WorkflowFailed {
+        run_id: String,
+        error_type: String,
+        cause: String,
+    },
+    /// 执行被取消（有清理状态时在清理路径结束后记录）
+    WorkflowCancelled {
+        run_id: String,
+        reason: String,
+    },
+    /// 执行被强制终止
+    WorkflowTerminated {
+        run_id: String,
+        reason: String,
+    },
 
     NodeEnter {
         run_id: String,
@@ -33,11 +50,25 @@ pub enum EngineEvent {
         state_name: String,
         reason: String,
     },
+    NodeRetrying {
+        run_id: String,
+        state_name: String,
+        attempt: u32,
+        error_type: String,
+        error: String,
+        next_retry_at: String,
+    },
     NodeExit {
         run_id: String,
         state_name: String,
-        status: String, // "success", "failed", "cancelled"
+        status: String, // "success", "failed", "caught", "cancelled"
         duration_ms: Option<u64>,
+        /// 离开状态后的执行上下文
+        #[serde(default)]
+        context: Option<Value>,
+        /// 转移目标；None 表示执行结束
+        #[serde(default)]
+        next_state: Option<String>,
     },
 
     // === 调度相关 ===
@@ -55,6 +86,12 @@ pub enum EngineEvent {
         run_id: String,
         state_name: String,
     },
+    /// WaitForSignal 挂起，等待具名外部信号
+    SignalAwaited {
+        run_id: String,
+        state_name: String,
+        signal_name: String,
+    },
     TaskReady {
         run_id: String,
         state_name: String,
@@ -73,3 +110,53 @@ pub enum EngineEvent {
         ui_event: Value,
     },
 }
+
+impl EngineEvent {
+    /// 事件类型名（写入历史表的 `event_type`）
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            EngineEvent::WorkflowStarted { .. } => "WorkflowStarted",
+            EngineEvent::WorkflowFinished { .. } => "WorkflowFinished",
+            EngineEvent::WorkflowFailed { .. } => "WorkflowFailed",
+            EngineEvent::WorkflowCancelled { .. } => "WorkflowCancelled",
+            EngineEvent::WorkflowTerminated { .. } => "WorkflowTerminated",
+            EngineEvent::NodeEnter { .. } => "NodeEnter",
+            EngineEvent::NodeSuccess { .. } => "NodeSuccess",
+            EngineEvent::NodeFailed { .. } => "NodeFailed",
+            EngineEvent::NodeCancelled { .. } => "NodeCancelled",
+            EngineEvent::NodeRetrying { .. } => "NodeRetrying",
+            EngineEvent::NodeExit { .. } => "NodeExit",
+            EngineEvent::NodeDispatched { .. } => "NodeDispatched",
+            EngineEvent::TimerScheduled { .. } => "TimerScheduled",
+            EngineEvent::TimerFired { .. } => "TimerFired",
+            EngineEvent::SignalAwaited { .. } => "SignalAwaited",
+            EngineEvent::TaskReady { .. } => "TaskReady",
+            EngineEvent::TaskFinished { .. } => "TaskFinished",
+            EngineEvent::UiEventPushed { .. } => "UiEventPushed",
+        }
+    }
+
+    /// 状态级事件所属的状态名；执行级事件返回 None
+    pub fn state_name(&self) -> Option<&str> {
+        match self {
+            EngineEvent::NodeEnter { state_name, .. }
+            | EngineEvent::NodeSuccess { state_name, .. }
+            | EngineEvent::NodeFailed { state_name, .. }
+            | EngineEvent::NodeCancelled { state_name, .. }
+            | EngineEvent::NodeRetrying { state_name, .. }
+            | EngineEvent::NodeExit { state_name, .. }
+            | EngineEvent::NodeDispatched { state_name, .. }
+            | EngineEvent::TimerScheduled { state_name, .. }
+            | EngineEvent::TimerFired { state_name, .. }
+            | EngineEvent::SignalAwaited { state_name, .. }
+            | EngineEvent::TaskReady { state_name, .. }
+            | EngineEvent::TaskFinished { state_name, .. } => Some(state_name),
+            EngineEvent::WorkflowStarted { .. }
+            | EngineEvent::WorkflowFinished { .. }
+            | EngineEvent::WorkflowFailed { .. }
+            | EngineEvent::WorkflowCancelled { .. }
+            | EngineEvent::WorkflowTerminated { .. }
+            | EngineEvent::UiEventPushed { .. } => None,
+        }
+    }
+}
@@ -4,16 +4,20 @@ use serde_json::Value;
 use chrono::{DateTime, Utc};
 use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ExecStart {
     #[schema(example = "INLINE")]
     pub mode: String,                 // "INLINE" | "DEFERRED"
     pub template_id: Option<String>,  // 二选一
+    /// 运行的模板修订号；与 `template_alias` 都省略时为最新发布的修订
+    pub template_revision: Option<i64>,
+    /// 运行别名（如 `stable`）指向的修订
+    pub template_alias: Option<String>,
     pub dsl:         Option<Value>,
     pub init_ctx:    Option<Value>,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ExecDto {
     pub run_id:  String,
     pub mode:    String,
@@ -21,5 +25,25 @@ pub struct ExecDto {
     pub result:  Option<Value>,
     pub started_at: DateTime<Utc>,
     pub finished_at: Option<DateTime<Utc>>,
+    /// 子执行（SubWorkflow / 分支）所属的父执行
+    pub parent_run_id: Option<String>,
+    pub template_id: Option<String>,
+    /// 执行开始时固定的模板修订
+    pub template_revision: Option<i64>,
 }
 
+
+/// 因超时结束的执行状态
+pub const TIMED_OUT: &str = "TIMED_OUT";
+
+/// 执行已进入终态（完成 / 失败 / 取消 / 终止 / 超时），不会再产生新事件；暂停的执行不算结束
+pub fn is_closed(status: &str) -> bool {
+    matches!(status, "COMPLETED" | "FAILED" | TIMED_OUT | "CANCELLED" | "TERMINATED")
+}
+
+/// 取消 / 终止执行的请求体（可省略）
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct ExecStop {
+    /// 取消原因，写入执行结果的 `Cause`
+    pub reason: Option<String>,
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use crate::dto::queue_task::QueueTaskDto;
 use utoipa::{ToSchema};
@@ -25,9 +26,24 @@ pub struct PollResponse {
     pub task: Option<QueueTaskDto>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+/// 单个队列的积压与轮询情况
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct MatchStats {
+    #[schema(example = "http")]
     pub queue: String,
+    /// 等待领取的任务数（积压）
     pub pending_tasks: usize,
+    /// 正在该队列上长轮询的 worker 数
     pub waiting_workers: usize,
-}
\ No newline at end of file
+    /// 最近轮询过该队列的 worker
+    #[serde(default)]
+    pub pollers: Vec<QueuePoller>,
+}
+
+/// 轮询过某个队列的 worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct QueuePoller {
+    #[schema(example = "tool-worker-0")]
+    pub worker_id: String,
+    pub last_poll_at: DateTime<Utc>,
+}
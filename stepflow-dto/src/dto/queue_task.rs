@@ -9,12 +9,24 @@ pub struct QueueTaskDto {
     pub run_id: String,                         // 工作流运行ID
     pub state_name: String,                     // 节点状态名
     pub resource: String,                       // 资源类型（工具标识，如 "http"）
+    #[serde(default)]
+    pub task_queue: String,                     // 所属命名队列（缺省与 resource 相同）
+    #[serde(default)]
+    pub root_run_id: String,                    // 根执行ID（分支 / 子执行共用，公平派发的轮转单位）
+    #[serde(default)]
+    pub template_id: Option<String>,            // 根执行所用模板（公平派发按模板加权）
+    #[serde(default)]
+    pub worker_id: Option<String>,              // 当前持有任务的 worker（processing 时）
     pub task_payload: Option<Value>,            // 上下文数据（通常为输入）
     pub status: String,                         // 状态（pending, processing, completed 等）
     pub attempts: i64,                          // 当前重试次数
     pub max_attempts: i64,                      // 最大重试次数
     pub priority: Option<u8>,                   // 优先级（0-255，越大越高）
     pub timeout_seconds: Option<i64>,           // 超时时间（秒）
+    #[serde(default)]
+    pub heartbeat_seconds: Option<i64>,         // 心跳间隔上限（秒），超过未心跳视为失联
+    #[serde(default)]
+    pub last_heartbeat_at: Option<DateTime<Utc>>, // 最近一次心跳时间
     pub error_message: Option<String>,          // 错误信息（如有）
     pub last_error_at: Option<DateTime<Utc>>,   // 上次错误时间
     pub next_retry_at: Option<DateTime<Utc>>,   // 下一次重试时间
@@ -44,6 +56,12 @@ pub struct UpdateQueueTaskDto {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_seconds: Option<i64>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heartbeat_seconds: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_heartbeat_at: Option<Option<DateTime<Utc>>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resource: Option<String>,
 
@@ -64,6 +82,9 @@ pub struct UpdateQueueTaskDto {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failed_at: Option<Option<DateTime<Utc>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worker_id: Option<Option<String>>,
 }
 
 /// worker 汇报结果时用这个
@@ -13,6 +13,9 @@ pub enum ExecutionSignal {
         run_id: String,
         state_name: String,
         error: String,
+        /// worker 上报的错误类型（用于 Retry / Catch 匹配），缺省按 `States.TaskFailed` 处理
+        #[serde(default)]
+        error_type: Option<String>,
     },
     TaskCancelled {
         run_id: String,
@@ -23,9 +26,39 @@ pub enum ExecutionSignal {
         run_id: String,
         state_name: String,
     },
+    /// `timeoutSeconds` 到期：`state_name` 为空时为执行级超时，
+    /// 否则为该状态某次访问 / 尝试的超时（已结束的访问 / 尝试忽略）
+    TimeoutFired {
+        run_id: String,
+        #[serde(default)]
+        state_name: Option<String>,
+        #[serde(default)]
+        visit_event_id: Option<i64>,
+        #[serde(default)]
+        attempt: Option<i64>,
+    },
     Heartbeat {
         run_id: String,
         state_name: String,
         details: Option<Value>,
     },
+    /// 发给执行的具名外部信号，由等待同名信号的 WaitForSignal 状态接收
+    SignalReceived {
+        run_id: String,
+        signal_name: String,
+        #[serde(default)]
+        payload: Value,
+    },
+    /// 取消执行：先运行 DSL 的 `onCancel` 清理状态（如有），结束后记录为 CANCELLED
+    CancelRequested {
+        run_id: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// 强制终止执行：不运行清理状态，直接记录为 TERMINATED
+    TerminateRequested {
+        run_id: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
 }
\ No newline at end of file
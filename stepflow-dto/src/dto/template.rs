@@ -1,29 +1,97 @@
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
 use chrono::{DateTime, Utc};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct TemplateUpsert {
     pub name: String,
     pub dsl:  Value,
+    /// 保存为草稿：不立即发布，之后通过 publish 发布
+    #[serde(default)]
+    pub draft: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct TemplateDto {
     pub id:   String,
     pub name: String,
+    /// 最新发布修订的 DSL（尚无发布的修订时为首个草稿）
     pub dsl:  Value,
+    /// 最新发布的修订号，尚无发布的修订时为 None
+    pub latest_revision: Option<i64>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 impl From<TemplateUpsert> for TemplateDto {
     fn from(u: TemplateUpsert) -> Self {
+        let now = chrono::Utc::now();
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             name: u.name,
             dsl: u.dsl,
-            created_at: chrono::Utc::now(),
+            latest_revision: None,
+            created_at: now,
+            updated_at: now,
         }
     }
-}
\ No newline at end of file
+}
+
+/// 模板的一个不可变修订版本
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct TemplateRevisionDto {
+    pub template_id: String,
+    pub revision: i64,
+    #[schema(example = "PUBLISHED")]
+    pub status: String,            // "DRAFT" | "PUBLISHED"
+    pub dsl: Value,
+    /// 指向该修订的别名（含 `latest`）
+    pub aliases: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// 模板别名
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct TemplateAliasDto {
+    pub template_id: String,
+    #[schema(example = "stable")]
+    pub alias: String,
+    pub revision: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 设置别名的请求体
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SetAliasRequest {
+    /// 别名指向的已发布修订
+    pub revision: i64,
+}
+
+/// 修订对比的查询参数：修订号、别名或 `latest`
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct RevisionDiffQuery {
+    pub from: String,
+    /// 省略时为 `latest`
+    pub to: Option<String>,
+}
+
+/// 两个修订之间 DSL 的差异
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct TemplateDiffDto {
+    pub template_id: String,
+    pub from_revision: i64,
+    pub to_revision: i64,
+    pub changes: Vec<DslChange>,
+}
+
+/// 一处差异；`path` 为 JSON Pointer（如 `/states/Check/next`）
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, ToSchema)]
+pub struct DslChange {
+    pub path: String,
+    #[schema(example = "changed")]
+    pub kind: String,              // "added" | "removed" | "changed"
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
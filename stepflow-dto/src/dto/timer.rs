@@ -38,4 +38,17 @@ pub struct UpdateTimerDto {
     pub status: Option<String>,
     pub version: Option<i64>,
     pub payload: Option<Option<Value>>,
-}
\ No newline at end of file
+}
+/// 定时器 payload：Wait 定时器为空；`timeoutSeconds` 超时定时器到期后
+/// 以 `ExecutionSignal::TimeoutFired` 唤醒执行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TimerPayload {
+    #[serde(rename_all = "camelCase")]
+    Timeout {
+        /// 状态级超时所属的那次状态访问（进入该状态的转移事件 id）；执行级超时为空
+        visit_event_id: Option<i64>,
+        /// 状态级超时对应的尝试次数（Retry 后为新的尝试重新计时）
+        attempt: Option<i64>,
+    },
+}
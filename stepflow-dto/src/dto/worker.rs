@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use utoipa::ToSchema;
@@ -6,8 +7,28 @@ use utoipa::ToSchema;
 pub struct PollRequest {
     /// 唯一 Worker 标识
     pub worker_id: String,
-    /// 支持的工具类型，例如 ["http", "shell"]
+    /// 支持的工具类型，例如 ["http", "shell"]；同时轮询以这些工具命名的默认队列
     pub capabilities: Vec<String>,
+    /// 额外轮询的命名任务队列（Task 状态的 `taskQueue`），例如 ["gpu"]
+    #[serde(default)]
+    pub task_queues: Vec<String>,
+    /// 长轮询时长（秒）：无任务时网关最多挂起这么久再返回空响应；缺省由网关决定，超过上限按上限处理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_seconds: Option<u64>,
+}
+
+impl PollRequest {
+    /// 本次轮询覆盖的队列：capabilities 在前、task_queues 在后，去重并保持顺序
+    pub fn queues(&self) -> Vec<String> {
+        let mut queues: Vec<String> = Vec::new();
+        for q in self.capabilities.iter().chain(&self.task_queues) {
+            let q = q.trim();
+            if !q.is_empty() && !queues.iter().any(|existing| existing == q) {
+                queues.push(q.to_string());
+            }
+        }
+        queues
+    }
 }
 
 /// Worker 请求任务响应结构（精简版）
@@ -35,6 +56,10 @@ pub struct PollResponse {
     /// 任务 ID（可用于追踪或重试，后续扩展）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_id: Option<String>,
+
+    /// 心跳上限（秒）：设置后 Worker 需在该间隔内调用 `/heartbeat`，否则任务被回收
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heartbeat_seconds: Option<i64>,
 }
 
 impl PollResponse {
@@ -47,10 +72,55 @@ impl PollResponse {
             tool_type: None,
             input:     None,
             task_id:   None,
+            heartbeat_seconds: None,
         }
     }
 }
 
+/// Worker 经 `/v1/worker/stream`（WebSocket）发给网关的消息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerStreamRequest {
+    /// 首条消息：声明轮询的队列与当前空闲槽位数
+    Subscribe {
+        worker_id: String,
+        capabilities: Vec<String>,
+        #[serde(default)]
+        task_queues: Vec<String>,
+        /// 空闲槽位：网关最多先推送这么多任务
+        slots: u32,
+    },
+    /// 又空出了 `slots` 个槽位（任务执行完毕），网关可继续推送
+    Credit { slots: u32 },
+}
+
+impl WorkerStreamRequest {
+    /// Subscribe 对应的轮询请求（用于统一计算队列）
+    pub fn poll_request(&self) -> Option<PollRequest> {
+        match self {
+            Self::Subscribe { worker_id, capabilities, task_queues, .. } => Some(PollRequest {
+                worker_id: worker_id.clone(),
+                capabilities: capabilities.clone(),
+                task_queues: task_queues.clone(),
+                wait_seconds: None,
+            }),
+            Self::Credit { .. } => None,
+        }
+    }
+}
+
+/// 网关经 `/v1/worker/stream` 推送给 Worker 的消息
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerStreamEvent {
+    /// 订阅成功，之后按空闲槽位推送任务
+    Subscribed { queues: Vec<String> },
+    /// 推送一个任务（字段与轮询响应一致，`has_task` 恒为 true）
+    Task(PollResponse),
+    /// 订阅参数错误等，网关随后关闭连接
+    Error { message: String },
+}
+
 /// Worker 上报状态的枚举（执行结果）
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -87,6 +157,19 @@ pub struct UpdateRequest {
     pub task_id: Option<String>,
 }
 
+/// Worker 执行任务期间的心跳
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WorkerHeartbeatRequest {
+    /// 唯一 Worker 标识
+    pub worker_id: String,
+
+    /// 工作流运行 ID
+    pub run_id: String,
+
+    /// 节点状态名称
+    pub state_name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CompleteRequest {
     /// 工作流运行 ID
@@ -111,10 +194,57 @@ pub struct CompleteRequest {
     pub task_id: Option<String>,
 }
 
-#[derive(Debug)]
+/// Worker 启动时向 `/v1/workers` 注册
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RegisterWorkerRequest {
+    /// 唯一 Worker 标识
+    pub worker_id: String,
+    /// 支持的工具类型
+    pub capabilities: Vec<String>,
+    /// 额外轮询的命名任务队列
+    #[serde(default)]
+    pub task_queues: Vec<String>,
+    /// Worker 版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 并发槽位数
+    #[serde(default = "default_concurrency")]
+    pub concurrency: u32,
+}
+
+fn default_concurrency() -> u32 {
+    1
+}
+
+/// Worker 存活心跳（与任务心跳无关），同时上报当前执行中的任务数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct WorkerLivenessRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_flight: Option<u32>,
+}
+
+/// 注册表中的 Worker
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WorkerInfoDto {
+    pub worker_id: String,
+    pub capabilities: Vec<String>,
+    pub task_queues: Vec<String>,
+    pub version: Option<String>,
+    pub concurrency: i64,
+    /// 最近一次心跳上报的执行中任务数
+    pub in_flight: i64,
+    /// active / draining / offline
+    #[schema(example = "active")]
+    pub status: String,
+    pub registered_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
 pub struct TaskDetails {
     pub run_id: String,
     pub state_name: String,
     pub tool_type: String,
     pub parameters: Value,
+    pub heartbeat_seconds: Option<i64>,
 }
\ No newline at end of file
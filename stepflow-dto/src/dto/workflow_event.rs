@@ -20,7 +20,7 @@ pub struct RecordEventRequest {
 }
 
 /// 工作流事件 DTO
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WorkflowEventDto {
     pub id: i64,
     pub run_id: String,
@@ -33,6 +33,26 @@ pub enum Command {
         error: Option<String>,
         cause: Option<String>,
     },
+    Parallel {
+        state_name: String,
+        branch_count: usize,
+        next_state: Option<String>,
+    },
+    Map {
+        state_name: String,
+        items_path: String,
+        next_state: Option<String>,
+    },
+    WaitForSignal {
+        state_name: String,
+        signal_name: String,
+        next_state: Option<String>,
+    },
+    SubWorkflow {
+        state_name: String,
+        template_id: String,
+        next_state: Option<String>,
+    },
 }
 
 impl Command {
@@ -43,7 +63,11 @@ impl Command {
             Command::Pass { state_name, .. } |
             Command::Choice { state_name, .. } |
             Command::Succeed { state_name, .. } |
-            Command::Fail { state_name, .. } => state_name
+            Command::Fail { state_name, .. } |
+            Command::Parallel { state_name, .. } |
+            Command::Map { state_name, .. } |
+            Command::WaitForSignal { state_name, .. } |
+            Command::SubWorkflow { state_name, .. } => state_name
         }
     }
 }
\ No newline at end of file
@@ -1,9 +1,10 @@
 // stepflow-engine/src/command/generator.rs
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::Utc;
 use serde_json::Value;
 use stepflow_dsl::{WorkflowDSL, State};
 use crate::command::Command;
+use crate::handler::wait::resolve_wait_until;
 use crate::logic::choice_eval::eval_choice_logic;
 
 pub fn step_once(
@@ -23,13 +24,7 @@ pub fn step_once(
 
         State::Wait(wait) => {
             let now = Utc::now();
-            let wait_until = if let Some(seconds) = wait.seconds {
-                now + Duration::seconds(seconds as i64)
-            } else if let Some(ts) = &wait.timestamp {
-                DateTime::parse_from_rfc3339(ts).map_err(|e| e.to_string())?.with_timezone(&Utc)
-            } else {
-                return Err("WaitState must define Seconds or Timestamp".to_string());
-            };
+            let wait_until = resolve_wait_until(wait, context, now).map_err(|e| e.to_string())?;
 
             let seconds = (wait_until - now).num_seconds().max(0) as u64;
 
@@ -87,7 +82,25 @@ pub fn step_once(
             }
         }
 
-        State::Map(_) => Err("Map state is not yet implemented".to_string()),
-        State::Parallel(_) => Err("Parallel state is not yet implemented".to_string()),
+        State::Map(map) => Ok(Command::Map {
+            state_name: state_name.to_string(),
+            items_path: map.items_path.clone(),
+            next_state: map.base.next.clone(),
+        }),
+        State::Parallel(parallel) => Ok(Command::Parallel {
+            state_name: state_name.to_string(),
+            branch_count: parallel.branches.len(),
+            next_state: parallel.base.next.clone(),
+        }),
+        State::WaitForSignal(wait) => Ok(Command::WaitForSignal {
+            state_name: state_name.to_string(),
+            signal_name: wait.signal_name.clone(),
+            next_state: wait.base.next.clone(),
+        }),
+        State::SubWorkflow(sub) => Ok(Command::SubWorkflow {
+            state_name: state_name.to_string(),
+            template_id: sub.template_id.clone(),
+            next_state: sub.base.next.clone(),
+        }),
     }
 }
@@ -16,6 +16,10 @@ impl Command {
             Command::Choice { .. } => "Choice",
             Command::Succeed { .. } => "Succeed",
             Command::Fail { .. } => "Fail",
+            Command::Parallel { .. } => "Parallel",
+            Command::Map { .. } => "Map",
+            Command::WaitForSignal { .. } => "WaitForSignal",
+            Command::SubWorkflow { .. } => "SubWorkflow",
         }
     }
 }
\ No newline at end of file
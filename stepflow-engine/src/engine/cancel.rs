@@ -0,0 +1,150 @@
+//! 取消 / 终止：由外部控制发起的执行结束。
+//!
+//! * cancel：当前状态记为 CANCELLED；DSL 声明了 `onCancel` 时先转到清理状态继续推进
+//!   （执行状态为 CANCELLING），清理路径结束（成功或失败）后记录为 CANCELLED
+//! * terminate：不运行清理状态，直接记录为 TERMINATED
+//! * 两者都会把仍在运行的子执行标记为 CANCELLED；原因以 `{ "Error": "States.Cancelled", "Cause": ... }`
+//!   写入 execution 的 result，并保留在 `last_error` 供父执行读取
+
+use chrono::Utc;
+use serde_json::Value;
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_exception::{ErrorOrigin, StepError, STATES_CANCELLED};
+use stepflow_storage::entities::{
+    workflow_execution::UpdateStoredWorkflowExecution, workflow_state::UpdateStoredWorkflowState,
+};
+use tracing::info;
+
+use super::{catch::error_object, core::WorkflowEngine};
+
+/// 清理状态运行期间的执行状态
+pub(crate) const CANCELLING: &str = "CANCELLING";
+
+impl WorkflowEngine {
+    /// 取消执行；有 `onCancel` 清理状态时游标转到清理状态，由后续推进执行
+    pub async fn cancel(&mut self, reason: Option<String>) -> Result<(), String> {
+        self.stop(reason, false).await
+    }
+
+    /// 强制终止执行，不运行清理状态
+    pub async fn terminate(&mut self, reason: Option<String>) -> Result<(), String> {
+        self.stop(reason, true).await
+    }
+
+    async fn stop(&mut self, reason: Option<String>, terminate: bool) -> Result<(), String> {
+        if self.finished {
+            return Err(format!("Execution {} is already closed", self.run_id));
+        }
+        if self.cancellation.is_some() && !terminate {
+            return Err(format!("Execution {} is already being cancelled", self.run_id));
+        }
+
+        let verb = if terminate { "terminated" } else { "cancelled" };
+        let reason = reason.unwrap_or_else(|| format!("Execution {verb}"));
+        info!("[{}] {verb} @ {}: {reason}", self.run_id, self.current_state);
+
+        let error = StepError {
+            error_type: STATES_CANCELLED.to_string(),
+            message: reason.clone(),
+            origin: ErrorOrigin::Engine,
+        };
+        let cleanup = if terminate {
+            None
+        } else {
+            self.dsl.on_cancel.clone().filter(|name| self.dsl.states.contains_key(name))
+        };
+
+        // 当前状态记为 CANCELLED，NodeExit 以 cancelled 转到清理状态（或结束）
+        self.pending.states.push((
+            format!("{}:{}", self.run_id, self.current_state),
+            UpdateStoredWorkflowState {
+                status: Some("CANCELLED".into()),
+                error: Some(Some(reason.clone())),
+                completed_at: Some(Some(Utc::now().naive_utc())),
+                ..Default::default()
+            },
+        ));
+        self.dispatch_event(EngineEvent::NodeCancelled {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            reason: reason.clone(),
+        })
+        .await?;
+        self.dispatch_event(EngineEvent::NodeExit {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            status: "cancelled".into(),
+            duration_ms: Some((Utc::now() - self.updated_at).num_milliseconds() as u64),
+            context: Some(self.context.clone()),
+            next_state: cleanup.clone(),
+        })
+        .await?;
+
+        match cleanup {
+            Some(cleanup) => {
+                self.save_execution(UpdateStoredWorkflowExecution {
+                    status: Some(CANCELLING.into()),
+                    current_state_name: Some(Some(cleanup.clone())),
+                    result: Some(Some(error_object(&error))),
+                    ..Default::default()
+                })
+                .await?;
+                self.current_state = cleanup;
+                self.awaiting_signal = false;
+                self.cancellation = Some(error);
+                self.updated_at = Utc::now();
+                self.cancel_children().await
+            }
+            None => {
+                let status = if terminate { "TERMINATED" } else { "CANCELLED" };
+                self.close_stopped(status, error).await
+            }
+        }
+    }
+
+    /// 记录取消 / 终止的终态（清理路径结束时同样经由此处）
+    pub(crate) async fn close_stopped(&mut self, status: &str, error: StepError) -> Result<(), String> {
+        let closed_at = Utc::now();
+        let event = if status == "TERMINATED" {
+            EngineEvent::WorkflowTerminated {
+                run_id: self.run_id.clone(),
+                reason: error.message.clone(),
+            }
+        } else {
+            EngineEvent::WorkflowCancelled {
+                run_id: self.run_id.clone(),
+                reason: error.message.clone(),
+            }
+        };
+        self.dispatch_event(event).await?;
+        self.save_execution(UpdateStoredWorkflowExecution {
+            status: Some(status.into()),
+            result: Some(Some(error_object(&error))),
+            context_snapshot: Some(Some(self.context.clone())),
+            close_time: Some(Some(closed_at.naive_utc())),
+            ..Default::default()
+        })
+        .await?;
+
+        self.awaiting_signal = false;
+        self.finished = true;
+        self.updated_at = closed_at;
+        self.cancellation = None;
+        self.last_error = Some(error);
+        self.record_visibility().await;
+        self.cancel_children().await
+    }
+}
+
+/// 从 CANCELLING 执行的 result 中恢复取消原因
+pub(crate) fn cancellation_from_result(result: Option<&Value>) -> StepError {
+    StepError {
+        error_type: STATES_CANCELLED.to_string(),
+        message: result
+            .and_then(|r| r.get("Cause"))
+            .and_then(Value::as_str)
+            .unwrap_or("Execution cancelled")
+            .to_string(),
+        origin: ErrorOrigin::Engine,
+    }
+}
@@ -0,0 +1,137 @@
+//! Catch：Retry 用尽后的失败处理。
+//!
+//! * 按声明顺序匹配 `CatchPolicy.error_equals`（`States.ALL` / `*` 匹配任意错误）
+//! * 命中：错误对象 `{ "Error": ..., "Cause": ... }` 写入 `result_path`（缺省替换整个输入），
+//!   当前状态记为 FAILED 后转到 `next`
+//! * 未命中：执行标记为 FAILED（`States.Timeout` 为 TIMED_OUT），错误保留在 `last_error` 供父执行（Parallel / Map）读取
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use stepflow_dto::dto::{engine_event::EngineEvent, execution::TIMED_OUT};
+use stepflow_exception::{match_catch, StepError, STATES_TIMEOUT};
+use stepflow_storage::entities::workflow_execution::UpdateStoredWorkflowExecution;
+use tracing::{debug, warn};
+
+use crate::mapping::write_result_path;
+
+use super::{core::WorkflowEngine, types::StepOutcome};
+
+/// Catch / 失败结果中的错误对象
+pub fn error_object(error: &StepError) -> Value {
+    json!({
+        "Error": error.error_type,
+        "Cause": error.message,
+    })
+}
+
+impl WorkflowEngine {
+    /// 当前状态最终失败：命中 Catch 则转到恢复状态，否则整个执行失败
+    pub(crate) async fn handle_state_failure(
+        &mut self,
+        error: StepError,
+    ) -> Result<StepOutcome, String> {
+        let (_, base) = self.dsl.get_state_and_base(&self.current_state);
+        let Some(catcher) = match_catch(&error.error_type, base.catch.as_deref()).cloned() else {
+            return self.fail_execution(error).await;
+        };
+
+        debug!(
+            "[{}] {} caught {} -> {}",
+            self.run_id, self.current_state, error.error_type, catcher.next
+        );
+
+        let updated_context =
+            write_result_path(&self.context, catcher.result_path.as_deref(), error_object(&error))?;
+
+        self.record_state_finished(false, None, Some(error.message.clone()));
+        self.dispatch_event(EngineEvent::NodeFailed {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            error: error.message.clone(),
+        })
+        .await?;
+        self.dispatch_event(EngineEvent::NodeExit {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            status: "caught".into(),
+            duration_ms: Some((Utc::now() - self.updated_at).num_milliseconds() as u64),
+            context: Some(updated_context.clone()),
+            next_state: Some(catcher.next.clone()),
+        })
+        .await?;
+
+        // 与正常完成一致：context / 游标与状态记录、事件一并提交（CAS 冲突时整体回滚）
+        self.save_execution(UpdateStoredWorkflowExecution {
+            current_state_name: Some(Some(catcher.next.clone())),
+            context_snapshot: Some(Some(updated_context.clone())),
+            ..Default::default()
+        })
+        .await?;
+
+        self.awaiting_signal = false;
+        self.context = updated_context;
+        self.current_state = catcher.next;
+        self.updated_at = Utc::now();
+
+        Ok(StepOutcome {
+            should_continue: true,
+            updated_context: self.context.clone(),
+            suspended: false,
+        })
+    }
+
+    /// 未被捕获的失败：记录状态 / 执行为 FAILED（超时为 TIMED_OUT），返回错误信息
+    pub(crate) async fn fail_execution(&mut self, error: StepError) -> Result<StepOutcome, String> {
+        warn!(
+            "[{}] execution failed @ {}: {} ({})",
+            self.run_id, self.current_state, error.message, error.error_type
+        );
+
+        let closed_at = Utc::now();
+        self.record_state_finished(false, None, Some(error.message.clone()));
+        self.dispatch_event(EngineEvent::NodeFailed {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            error: error.message.clone(),
+        })
+        .await?;
+
+        // onCancel 清理路径失败：执行仍以取消结束
+        if let Some(cancellation) = self.cancellation.clone() {
+            warn!("[{}] cleanup failed during cancellation: {}", self.run_id, error.message);
+            self.close_stopped("CANCELLED", cancellation).await?;
+            return Ok(StepOutcome {
+                should_continue: false,
+                updated_context: self.context.clone(),
+                suspended: false,
+            });
+        }
+
+        self.dispatch_event(EngineEvent::WorkflowFailed {
+            run_id: self.run_id.clone(),
+            error_type: error.error_type.clone(),
+            cause: error.message.clone(),
+        })
+        .await?;
+
+        // 未被捕获的超时记为 TIMED_OUT
+        let status = if error.error_type == STATES_TIMEOUT { TIMED_OUT } else { "FAILED" };
+        self.save_execution(UpdateStoredWorkflowExecution {
+            status: Some(status.into()),
+            result: Some(Some(error_object(&error))),
+            close_time: Some(Some(closed_at.naive_utc())),
+            ..Default::default()
+        })
+        .await?;
+
+        self.awaiting_signal = false;
+        self.finished = true;
+        self.updated_at = closed_at;
+        self.record_visibility().await;
+        self.cancel_children().await?;
+
+        let message = error.message.clone();
+        self.last_error = Some(error);
+        Err(message)
+    }
+}
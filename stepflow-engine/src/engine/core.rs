@@ -1,21 +1,34 @@
-use crate::command::step_once;
+use crate::command::{step_once, Command};
 use crate::mapping::MappingPipeline;
 use crate::signal::handler::apply_signal;
 use chrono::{DateTime, Utc};
 use log::{debug, warn};
 use serde_json::Value;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use stepflow_dsl::{State, WorkflowDSL};
 use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_dto::dto::execution;
 use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_exception::{ErrorOrigin, StepError, STATES_FAIL, STATES_TASK_FAILED};
 use stepflow_hook::EngineEventDispatcher;
 use stepflow_storage::db::DynPM;
-use stepflow_storage::entities::workflow_execution::UpdateStoredWorkflowExecution;
-use crate::handler::registry::StateHandlerRegistry;
+use stepflow_storage::entities::{
+    outbox::StoredOutboxMessage, step_commit::StepCommit,
+    workflow_execution::UpdateStoredWorkflowExecution, workflow_state::UpdateStoredWorkflowState,
+};
+use crate::handler::{execution_scope::StepWrites, registry::StateHandlerRegistry, task::TASK_TOKEN_KEY};
 use tokio::sync::mpsc;
 
 use super::{
+    cancel::{cancellation_from_result, CANCELLING},
     dispatch::dispatch_command,
+    history::{load_history, HistoryCursor},
+    outbox::publish_and_mark,
+    replay::Replayer,
+    retry::classify_error,
+    template::{self, RevisionSelector},
+    timeout::within,
     types::{StateExecutionResult, StepOutcome, WorkflowMode},
 };
 
@@ -39,6 +52,9 @@ impl StateStatus {
     }
 }
 
+/// execution 写入发生版本冲突后，重新加载并重试当前步骤的最大次数
+const MAX_CONFLICT_RETRIES: usize = 3;
+
 pub type SignalSender = mpsc::UnboundedSender<ExecutionSignal>;
 pub type SignalReceiver = mpsc::UnboundedReceiver<ExecutionSignal>;
 
@@ -56,6 +72,29 @@ pub struct WorkflowEngine {
 
     pub finished: bool,
     pub updated_at: DateTime<Utc>,
+    // 执行开始时间（执行级 timeoutSeconds 的起点）
+    pub(crate) started_at: DateTime<Utc>,
+    // 定时器分片数（定时器的 shard_id 由 run_id 哈希得到，见 `timer_shard`）
+    pub(crate) timer_shard_count: u32,
+
+    // Parallel / Map 分支子执行（deferred 模式下挂起等待信号）
+    pub(crate) children: HashMap<String, WorkflowEngine>,
+    // 当前 deferred Task 已入队 / Wait 定时器已创建，等待外部信号（避免重复入队）
+    pub(crate) awaiting_signal: bool,
+    // 导致执行失败的错误（未被 Catch 时保留，供父执行读取）
+    pub last_error: Option<StepError>,
+    // 取消后正在运行 onCancel 清理状态：清理路径结束时以此记录 CANCELLED
+    pub(crate) cancellation: Option<StepError>,
+    // 事件历史写入游标（event_id 编号与 parent 链接）
+    pub(crate) history: HistoryCursor,
+    // execution 记录的已知版本（CAS 写入的期望值，None 表示尚未读取）
+    pub(crate) version: Option<i64>,
+    // 最近一次 execution 写入因版本冲突失败，需 reload 后重试
+    pub(crate) conflicted: bool,
+    // 当前步骤尚未提交的写入（状态记录 / 历史事件 / 定时器 / outbox）
+    pub(crate) pending: StepCommit,
+    // 最近一次成功提交时的历史游标（提交失败时回退编号）
+    committed_history: HistoryCursor,
 
     // Signal handling
     signal_sender: Option<SignalSender>,
@@ -75,6 +114,7 @@ impl WorkflowEngine {
         let (signal_sender, signal_receiver) = mpsc::unbounded_channel();
 
         Self {
+            pending: StepCommit::new(run_id.clone()),
             run_id,
             current_state: dsl.start_at.clone(),
             last_task_state: None, // 初始化为 None
@@ -86,11 +126,32 @@ impl WorkflowEngine {
             state_handler_registry,
             finished: false,
             updated_at: Utc::now(),
+            started_at: Utc::now(),
+            timer_shard_count: 1,
+            children: HashMap::new(),
+            awaiting_signal: false,
+            last_error: None,
+            cancellation: None,
+            history: HistoryCursor::default(),
+            version: None,
+            conflicted: false,
+            committed_history: HistoryCursor::default(),
             signal_sender: Some(signal_sender),
             signal_receiver: Some(signal_receiver),
         }
     }
 
+    /// 设置定时器分片数（默认 1，即全部定时器落在分片 0）；子执行继承该设置
+    pub fn with_timer_shard_count(mut self, shard_count: u32) -> Self {
+        self.timer_shard_count = shard_count.max(1);
+        self
+    }
+
+    /// 本执行创建的定时器所属分片
+    pub(crate) fn timer_shard(&self) -> i64 {
+        super::timeout::timer_shard(&self.run_id, self.timer_shard_count)
+    }
+
     // Signal handling methods
     pub fn get_signal_sender(&self) -> Option<SignalSender> {
         self.signal_sender.clone()
@@ -109,8 +170,24 @@ impl WorkflowEngine {
 
         match receiver.try_recv() {
             Ok(signal) => {
-                apply_signal(self, signal).await?;
-                Ok(true)
+                // 版本冲突：重新加载后按最新状态重新应用（已被其他实例处理的信号会被拒绝）
+                let mut retries = 0;
+                let result = loop {
+                    match apply_signal(self, signal.clone()).await {
+                        Ok(_) => break Ok(true),
+                        Err(e) if self.take_conflict() && retries < MAX_CONFLICT_RETRIES => {
+                            retries += 1;
+                            warn!("[{}] {e}; reloading before re-applying signal", self.run_id);
+                            self.reload().await?;
+                        }
+                        Err(e) => break Err(e),
+                    }
+                };
+                // 未随 execution 一起提交的写入（重新入队、NodeFailed 等）
+                let flushed = self.flush().await;
+                let handled = result?;
+                flushed?;
+                Ok(handled)
             }
             Err(mpsc::error::TryRecvError::Empty) => Ok(false),
             Err(mpsc::error::TryRecvError::Disconnected) => {
@@ -129,8 +206,32 @@ impl WorkflowEngine {
         persistence: DynPM,
         state_handler_registry: Arc<StateHandlerRegistry>,
     ) -> Result<Self, String> {
+        let dsl = Self::load_template_dsl(&persistence, &run_id).await?;
+        Self::restore_with_dsl(run_id, dsl, event_dispatcher, persistence, state_handler_registry)
+            .await
+    }
+
+    /// 与 `restore` 相同，但 context 与游标由事件历史回放得到（不读取 `context_snapshot`）
+    pub async fn restore_from_history(
+        run_id: String,
+        event_dispatcher: Arc<EngineEventDispatcher>,
+        persistence: DynPM,
+        state_handler_registry: Arc<StateHandlerRegistry>,
+    ) -> Result<Self, String> {
+        let dsl = Self::load_template_dsl(&persistence, &run_id).await?;
+        Self::restore_with_dsl_from_history(
+            run_id,
+            dsl,
+            event_dispatcher,
+            persistence,
+            state_handler_registry,
+        )
+        .await
+    }
+
+    async fn load_template_dsl(persistence: &DynPM, run_id: &str) -> Result<WorkflowDSL, String> {
         let execution = persistence
-            .get_execution(&run_id)
+            .get_execution(run_id)
             .await
             .map_err(|e| e.to_string())?
             .ok_or_else(|| format!("Execution {} not found", run_id))?;
@@ -139,66 +240,245 @@ impl WorkflowEngine {
             .template_id
             .ok_or_else(|| "Template ID missing".to_string())?;
 
-        let template = persistence
-            .get_template(&template_id)
-            .await
-            .map_err(|e| e.to_string())?
-            .ok_or_else(|| format!("Template {} not found", template_id))?;
+        match execution.template_revision {
+            Some(revision) => template::pinned_dsl(persistence, &template_id, revision).await,
+            // 未记录修订的执行：按最新发布的修订恢复
+            None => {
+                let revision =
+                    template::runnable_revision(persistence, &template_id, &RevisionSelector::Latest).await?;
+                template::revision_dsl(&revision)
+            }
+        }
+    }
+
+    /// 使用给定 DSL 从 execution 记录恢复引擎（分支子执行没有模板，由父引擎提供 DSL）
+    pub async fn restore_with_dsl(
+        run_id: String,
+        dsl: WorkflowDSL,
+        event_dispatcher: Arc<EngineEventDispatcher>,
+        persistence: DynPM,
+        state_handler_registry: Arc<StateHandlerRegistry>,
+    ) -> Result<Self, String> {
+        let mut engine = Self::new(
+            run_id,
+            dsl,
+            Value::Object(Default::default()),
+            WorkflowMode::Deferred,
+            event_dispatcher,
+            persistence,
+            state_handler_registry,
+        );
+        engine.reload().await?;
 
-        let dsl: WorkflowDSL =
-            serde_json::from_str(&template.dsl_definition).map_err(|e| e.to_string())?;
+        // 挂起中的 Parallel / Map 分支在收到信号 / 下一次推进时按需恢复（见 fanout.rs）
+        Ok(engine)
+    }
 
-        let context = execution
-            .context_snapshot
-            .unwrap_or_else(|| Value::Object(Default::default()));
+    /// 从 execution 记录重新加载 context / 游标 / 版本（恢复时及版本冲突后调用）
+    pub(crate) async fn reload(&mut self) -> Result<(), String> {
+        let execution = self
+            .persistence
+            .get_execution(&self.run_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Execution {} not found", self.run_id))?;
 
         let current_state = execution
             .current_state_name
-            .unwrap_or_else(|| dsl.start_at.clone());
-
-        let mode = match execution.mode.as_str() {
-            "INLINE" => WorkflowMode::Inline,
-            "DEFERRED" => WorkflowMode::Deferred,
-            _ => return Err(format!("Invalid mode {}", execution.mode)),
+            .clone()
+            .filter(|name| self.dsl.states.contains_key(name))
+            .unwrap_or_else(|| self.dsl.start_at.clone());
+
+        let mode = parse_mode(&execution.mode)?;
+        let finished = is_halted(&execution.status);
+
+        // deferred Task / Wait / WaitForSignal 已进入（STARTED / RETRYING）但未完成：
+        // 任务已在队列中 / token 已签发 / 定时器已创建（Inline 长等待同样会转为定时器）；
+        // Deferred 扇出处于 RETRYING：等待重试定时器
+        let awaited_statuses: &[&str] = match self.dsl.states.get(&current_state) {
+            Some(State::Task(task)) if mode == WorkflowMode::Deferred || task.waits_for_task_token() => {
+                &["STARTED", "RETRYING"]
+            }
+            Some(State::Wait(_)) | Some(State::WaitForSignal(_)) => &["STARTED", "RETRYING"],
+            Some(State::Parallel(_)) | Some(State::Map(_)) | Some(State::SubWorkflow(_))
+                if mode == WorkflowMode::Deferred =>
+            {
+                &["RETRYING"]
+            }
+            _ => &[],
         };
+        let awaiting_signal = !finished
+            && !awaited_statuses.is_empty()
+            && self
+                .persistence
+                .get_state(&format!("{}:{current_state}", self.run_id))
+                .await
+                .map_err(|e| e.to_string())?
+                .is_some_and(|row| awaited_statuses.contains(&row.status.as_str()));
+
+        // 新事件接在已有历史之后编号；未提交的写入作废
+        self.history = HistoryCursor::from_events(&load_history(&self.persistence, &self.run_id).await?);
+        self.committed_history = self.history.clone();
+        self.pending = StepCommit::new(self.run_id.clone());
+
+        self.context = execution
+            .context_snapshot
+            .unwrap_or_else(|| Value::Object(Default::default()));
+        self.current_state = current_state;
+        self.mode = mode;
+        self.finished = finished;
+        self.awaiting_signal = awaiting_signal;
+        self.cancellation = (execution.status == CANCELLING)
+            .then(|| cancellation_from_result(execution.result.as_ref()));
+        self.version = Some(execution.version);
+        self.conflicted = false;
+        self.children.clear();
+        self.updated_at = execution.close_time.unwrap_or(execution.start_time).and_utc();
+        self.started_at = execution.start_time.and_utc();
+        Ok(())
+    }
 
-        let finished = matches!(
-            execution.status.as_str(),
-            "COMPLETED" | "FAILED" | "TERMINATED" | "PAUSED" | "SUSPENDED"
-        );
+    /// 使用给定 DSL 回放事件历史恢复引擎；DSL 与历史不一致时返回非确定性错误
+    pub async fn restore_with_dsl_from_history(
+        run_id: String,
+        dsl: WorkflowDSL,
+        event_dispatcher: Arc<EngineEventDispatcher>,
+        persistence: DynPM,
+        state_handler_registry: Arc<StateHandlerRegistry>,
+    ) -> Result<Self, String> {
+        let execution = persistence
+            .get_execution(&run_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Execution {} not found", run_id))?;
+        let mode = parse_mode(&execution.mode)?;
 
-        let (signal_sender, signal_receiver) = mpsc::unbounded_channel();
+        let events = load_history(&persistence, &run_id).await?;
+        let replayed = Replayer::new(&dsl, mode)
+            .replay(&events)
+            .map_err(|e| e.to_string())?;
 
-        Ok(Self {
+        let mut engine = Self::new(
             run_id,
-            current_state,
-            last_task_state: None,
             dsl,
-            context,
+            replayed.context,
             mode,
             event_dispatcher,
             persistence,
             state_handler_registry,
-            finished,
-            updated_at: execution
-                .close_time
-                .unwrap_or_else(|| execution.start_time)
-                .and_utc(),
-            signal_sender: Some(signal_sender),
-            signal_receiver: Some(signal_receiver),
-        })
+        );
+        engine.current_state = replayed.current_state;
+        engine.last_task_state = replayed.last_task_state;
+        // 暂停等外部控制不产生历史事件，仍以 execution 状态为准
+        engine.finished = replayed.finished || is_halted(&execution.status);
+        engine.awaiting_signal = replayed.awaiting_signal && !engine.finished;
+        engine.cancellation = (execution.status == CANCELLING)
+            .then(|| cancellation_from_result(execution.result.as_ref()));
+        engine.committed_history = replayed.cursor.clone();
+        engine.history = replayed.cursor;
+        engine.version = Some(execution.version);
+        engine.updated_at = execution.close_time.unwrap_or(execution.start_time).and_utc();
+        engine.started_at = execution.start_time.and_utc();
+        Ok(engine)
     }
 
     // --------------------- 调度辅助 -----------------------------
 
-    pub(crate) async fn dispatch_event(&self, ev: EngineEvent) {
-        self.event_dispatcher.dispatch(ev).await;
-    }
     pub(crate) fn state_def(&self) -> &State {
         &self.dsl.states[&self.current_state]
     }
-    fn deferred_task(&self) -> bool {
-        self.mode == WorkflowMode::Deferred && matches!(self.state_def(), State::Task(_))
+    /// 当前状态是否为挂起等待外部回调的 Task（Deferred 入队 / 回调 token）
+    pub(crate) fn deferred_task(&self) -> bool {
+        match self.state_def() {
+            State::Task(task) => self.mode == WorkflowMode::Deferred || task.waits_for_task_token(),
+            _ => false,
+        }
+    }
+
+    /// 回调 Task：把签发的 token 写入 context（`$.taskToken`），随 context 快照持久化
+    pub(crate) fn expose_task_token(&mut self, metadata: Option<&Value>) {
+        if let (Some(token), Value::Object(context)) =
+            (metadata.and_then(|m| m.get("task_token")), &mut self.context)
+        {
+            context.insert(TASK_TOKEN_KEY.to_string(), token.clone());
+        }
+    }
+
+    /// 读取并清除冲突标记
+    fn take_conflict(&mut self) -> bool {
+        std::mem::take(&mut self.conflicted)
+    }
+
+    /// 提交当前步骤：缓冲的状态记录 / 历史事件 / 定时器 / outbox 与 execution 变更在同一事务中写入。
+    /// execution 以 CAS 方式写入（带上已知版本），成功后版本 +1；
+    /// 版本冲突时整步回滚并置 `conflicted`，由调用方 `reload` 后重试
+    pub(crate) async fn save_execution(
+        &mut self,
+        mut update: UpdateStoredWorkflowExecution,
+    ) -> Result<(), String> {
+        if self.version.is_none() {
+            self.version = self
+                .persistence
+                .get_execution(&self.run_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|e| e.version);
+        }
+        update.version = self.version;
+        self.pending.execution = Some(update);
+        self.commit_pending().await
+    }
+
+    /// 提交不涉及 execution 的缓冲写入（如重新入队、仅记录事件的失败）
+    pub(crate) async fn flush(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.commit_pending().await
+    }
+
+    async fn commit_pending(&mut self) -> Result<(), String> {
+        let commit = std::mem::replace(&mut self.pending, StepCommit::new(self.run_id.clone()));
+        match self.persistence.commit_step(&commit).await {
+            Ok(outbox) => {
+                if commit.execution.is_some() {
+                    self.version = self.version.map(|v| v + 1);
+                }
+                self.committed_history = self.history.clone();
+                self.publish_outbox(&outbox).await;
+                Ok(())
+            }
+            Err(e) => {
+                // 整步回滚：缓冲已丢弃，事件编号回到上次提交处
+                self.history = self.committed_history.clone();
+                self.conflicted = e.is_conflict();
+                Err(e.to_string())
+            }
+        }
+    }
+
+    /// 投递已提交的 outbox 消息；投递失败的消息保持未发布，由 outbox relay 补投
+    async fn publish_outbox(&self, messages: &[StoredOutboxMessage]) {
+        for message in messages {
+            if let Err(e) = publish_and_mark(
+                &self.persistence,
+                &self.event_dispatcher,
+                &self.state_handler_registry,
+                message,
+            )
+            .await
+            {
+                warn!("[{}] outbox #{} ({}) not published: {e}", self.run_id, message.id, message.topic);
+            }
+        }
+    }
+
+    /// 把 handler 产生的写入并入当前步骤（映射轨迹由 `record_mapping_trace` 单独处理）
+    pub(crate) fn absorb_writes(&mut self, writes: Mutex<StepWrites>) {
+        let writes = writes.into_inner().unwrap_or_else(|e| e.into_inner());
+        self.pending.timers.extend(writes.timers);
+        self.pending.activity_tasks.extend(writes.activity_tasks);
+        self.pending.outbox.extend(writes.outbox);
     }
 
     // --------------------- 主入口 -------------------------------
@@ -218,34 +498,60 @@ impl WorkflowEngine {
     }
 
     async fn run_state(&mut self) -> Result<Value, String> {
-        if self.mode == WorkflowMode::Inline {
-            self.dispatch_event(EngineEvent::WorkflowStarted {
-                run_id: self.run_id.clone(),
-            })
-            .await;
-        }
+        // Inline 执行整体受 timeoutSeconds 限制；Deferred 执行由超时定时器限制
+        let result = match self.execution_deadline().filter(|_| self.mode == WorkflowMode::Inline) {
+            Some(deadline) => self.run_steps_until(deadline).await,
+            None => self.run_steps().await,
+        };
+        // 未随 execution 一起提交的写入（如 Inline 失败路径上的事件）在退出前提交
+        let flushed = self.flush().await;
+        let out = result?;
+        flushed?;
+        Ok(out)
+    }
+
+    pub(crate) async fn run_steps(&mut self) -> Result<Value, String> {
+        let mut conflicts = 0;
         loop {
+            // 首次推进：历史以 WorkflowStarted（携带初始输入）开头（冲突回滚后重新写入）
+            if self.history.last_event_id == 0 {
+                self.dispatch_event(EngineEvent::WorkflowStarted {
+                    run_id: self.run_id.clone(),
+                    input: self.context.clone(),
+                })
+                .await?;
+                if self.mode == WorkflowMode::Deferred {
+                    self.schedule_workflow_timeout();
+                }
+            }
             if self.finished {
                 break;
             }
 
-            // 记录当前 state 是否是 Task（SendData 之类）
-            let is_task_state = matches!(self.state_def(), State::Task(_));
-
-            let step_out = self.advance_once().await?;
+            let step_out = match self.advance_once().await {
+                Ok(out) => out,
+                // 其他实例已推进 / 修改了该执行：以最新记录为准重新执行当前步骤
+                Err(e) if self.take_conflict() && conflicts < MAX_CONFLICT_RETRIES => {
+                    conflicts += 1;
+                    warn!("[{}] {e}; reloading and retrying step", self.run_id);
+                    self.reload().await?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             debug!(
                 "🔁 advance_once done | should_continue={} | new_state={}",
                 step_out.should_continue, self.current_state
             );
 
-            if !step_out.should_continue {
-                break; // End 节点
+            // ---- Task 已写入队列 / 分支未汇合：立即挂起 ----
+            if step_out.suspended {
+                debug!("⏸ state suspended, engine waits for signal");
+                break; // 等待外部 worker 通过 /update 或分支信号触发推进
             }
 
-            // ---- 如果刚才执行的就是 Task 状态，说明任务已写入队列；立即挂起 ----
-            if is_task_state && self.mode == WorkflowMode::Deferred {
-                debug!("⏸ task scheduled, engine suspend");
-                break; // 立即退出，等待外部 worker 通过 /update 触发信号处理
+            if !step_out.should_continue {
+                break; // End 节点
             }
 
             // ---- 处理 task 完成/失败的情况 ----
@@ -283,57 +589,41 @@ impl WorkflowEngine {
             match t.status.as_str() {
                 "completed" => {
                     if let Some(payload) = t.task_payload {
-                        let State::Task(task_state) = self.state_def() else {
+                        let state @ State::Task(_) = self.state_def() else {
                             return Err("Expected Task state".into());
                         };
-                        let pipeline = MappingPipeline {
-                            input_mapping: task_state.base.input_mapping.as_ref(),
-                            output_mapping: task_state.base.output_mapping.as_ref(),
-                        };
-                        self.context = pipeline
-                            .apply_output(&payload, &self.context)
-                            .map_err(|e| e.to_string())?;
-
-                        self.persistence
-                            .update_execution(
-                                &self.run_id,
-                                &UpdateStoredWorkflowExecution {
-                                    context_snapshot: Some(Some(self.context.clone())),
-                                    ..Default::default()
-                                },
-                            )
-                            .await
-                            .map_err(|e| e.to_string())?;
+                        let writes = Mutex::new(StepWrites::default());
+                        let applied = MappingPipeline::for_state(state)
+                            .with_writes(&writes)
+                            .apply_output(&payload, &self.context);
+                        self.record_mapping_trace(&writes).await?;
+                        self.context = applied?;
 
                         self.dispatch_event(EngineEvent::NodeSuccess {
                             run_id: self.run_id.clone(),
                             state_name: self.current_state.clone(),
                             output: payload,
                         })
-                        .await;
+                        .await?;
+
+                        self.save_execution(UpdateStoredWorkflowExecution {
+                            context_snapshot: Some(Some(self.context.clone())),
+                            ..Default::default()
+                        })
+                        .await?;
                     }
                     Ok(true)
                 }
                 "failed" => {
                     let err_msg = t.error_message.unwrap_or_else(|| "Task failed".to_string());
-                    self.persistence
-                        .update_execution(
-                            &self.run_id,
-                            &UpdateStoredWorkflowExecution {
-                                status: Some("FAILED".into()),
-                                close_time: Some(Some(Utc::now().naive_utc())),
-                                ..Default::default()
-                            },
-                        )
-                        .await
-                        .map_err(|e| e.to_string())?;
-                    self.dispatch_event(EngineEvent::NodeFailed {
-                        run_id: self.run_id.clone(),
-                        state_name: self.current_state.clone(),
-                        error: err_msg.clone(),
+                    // 命中 Catch 时游标已转到恢复状态，继续推进
+                    self.handle_state_failure(StepError {
+                        error_type: STATES_TASK_FAILED.to_string(),
+                        message: err_msg,
+                        origin: ErrorOrigin::Tool,
                     })
-                    .await;
-                    Err(err_msg)
+                    .await?;
+                    Ok(false)
                 }
                 _ => Ok(true),
             }
@@ -343,9 +633,7 @@ impl WorkflowEngine {
     }
 
     // ---- 只在首次进入节点时写 input ---------------------------------
-    async fn record_state_started(&self) -> Result<(), String> {
-        use stepflow_storage::entities::workflow_state::UpdateStoredWorkflowState;
-
+    fn record_state_started(&mut self, new_visit: bool) {
         let state_id = format!("{}:{}", self.run_id, self.current_state);
         let state_type = match self.state_def() {
             State::Task(_) => "Task",
@@ -356,49 +644,99 @@ impl WorkflowEngine {
             State::Succeed(_) => "Succeed",
             State::Parallel(_) => "Parallel",
             State::Map(_) => "Map",
+            State::SubWorkflow(_) => "SubWorkflow",
+            State::WaitForSignal(_) => "WaitForSignal",
         };
 
-        self.persistence
-            .update_state(
-                &state_id,
-                &UpdateStoredWorkflowState {
-                    state_name: Some(self.current_state.clone()),
-                    state_type: Some(state_type.into()),
+        // 扇出状态每次推进都会重新进入：同一访问内保留 input 与已记录的重试次数
+        if !new_visit {
+            self.pending.states.push((
+                state_id,
+                UpdateStoredWorkflowState {
                     status: Some("STARTED".into()),
-                    input: Some(Some(self.context.clone())), // ✅ 只在这里写入
-                    started_at: Some(Some(Utc::now().naive_utc())),
+                    mapping_trace: Some(None),
                     ..Default::default()
                 },
-            )
-            .await
-            .map_err(|e| e.to_string())
+            ));
+            return;
+        }
+
+        self.pending.states.push((
+            state_id,
+            UpdateStoredWorkflowState {
+                state_name: Some(self.current_state.clone()),
+                state_type: Some(state_type.into()),
+                status: Some("STARTED".into()),
+                input: Some(Some(self.context.clone())), // ✅ 只在这里写入
+                started_at: Some(Some(Utc::now().naive_utc())),
+                attempts: Some(0),
+                mapping_trace: Some(None),
+                ..Default::default()
+            },
+        ));
     }
 
     // ---- 完成 / 失败时，只更新 output & status ------------------------
-    async fn record_state_finished(
-        &self,
+    pub(crate) fn record_state_finished(
+        &mut self,
         success: bool,
         output: Option<Value>,
         error: Option<String>,
-    ) -> Result<(), String> {
-        use stepflow_storage::entities::workflow_state::UpdateStoredWorkflowState;
+    ) {
+        let state_id = format!("{}:{}", self.run_id, self.current_state);
 
+        self.pending.states.push((
+            state_id,
+            UpdateStoredWorkflowState {
+                status: Some(if success { "COMPLETED" } else { "FAILED" }.into()),
+                output: Some(output),
+                error: Some(error),
+                completed_at: Some(Some(Utc::now().naive_utc())),
+                // ⛔ 不触碰 input
+                ..Default::default()
+            },
+        ));
+    }
+
+    /// 把 *writes* 中的映射轨迹追加到当前状态记录（MappingDSL `debug`）。
+    /// 本步骤已有缓冲时接在其后，否则接在已持久化的轨迹后（Deferred 的输出映射发生在回调时）
+    pub(crate) async fn record_mapping_trace(&mut self, writes: &Mutex<StepWrites>) -> Result<(), String> {
+        let trace = std::mem::take(&mut writes.lock().unwrap_or_else(|e| e.into_inner()).mapping_trace);
+        if trace.is_empty() {
+            return Ok(());
+        }
         let state_id = format!("{}:{}", self.run_id, self.current_state);
 
-        self.persistence
-            .update_state(
-                &state_id,
-                &UpdateStoredWorkflowState {
-                    status: Some(if success { "COMPLETED" } else { "FAILED" }.into()),
-                    output: Some(output),
-                    error: Some(error),
-                    completed_at: Some(Some(Utc::now().naive_utc())),
-                    // ⛔ 不触碰 input
-                    ..Default::default()
-                },
-            )
-            .await
-            .map_err(|e| e.to_string())
+        let buffered = self
+            .pending
+            .states
+            .iter()
+            .rev()
+            .find(|(id, changes)| *id == state_id && changes.mapping_trace.is_some())
+            .and_then(|(_, changes)| changes.mapping_trace.clone());
+        let existing = match buffered {
+            Some(existing) => existing,
+            None => self
+                .persistence
+                .get_state(&state_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .and_then(|row| row.mapping_trace),
+        };
+        let mut entries = match existing {
+            Some(Value::Array(entries)) => entries,
+            _ => Vec::new(),
+        };
+        entries.extend(trace);
+
+        self.pending.states.push((
+            state_id,
+            UpdateStoredWorkflowState {
+                mapping_trace: Some(Some(Value::Array(entries))),
+                ..Default::default()
+            },
+        ));
+        Ok(())
     }
 
     // ------------------ 单步执行 -------------------------------
@@ -409,22 +747,41 @@ impl WorkflowEngine {
             return Ok(StepOutcome {
                 should_continue: false,
                 updated_context: self.context.clone(),
+                suspended: false,
             });
         }
 
+        // 任务已在队列中 / 定时器未触发，继续等待信号
+        if self.awaiting_signal {
+            return Ok(StepOutcome {
+                should_continue: true,
+                updated_context: self.context.clone(),
+                suspended: true,
+            });
+        }
+
+        // 扇出状态每次推进都会重新进入，只在本次访问首次进入时创建超时定时器
+        let new_visit = self.entering_new_visit();
+
         // —— ① NodeEnter & 记录 STARTED ——
         self.dispatch_event(EngineEvent::NodeEnter {
             run_id: self.run_id.clone(),
             state_name: self.current_state.clone(),
             input: self.context.clone(),
         })
-        .await;
+        .await?;
 
         // ⚠️ 只在首次进入时插入，避免后续覆盖 input
-        self.record_state_started().await?;
+        self.record_state_started(new_visit);
 
         // —— ② 真正执行当前节点 ——
-        let cmd = step_once(&self.dsl, &self.current_state, &self.context)?;
+        let cmd = match step_once(&self.dsl, &self.current_state, &self.context) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                let step_error = classify_error(self.state_def(), &e);
+                return self.handle_state_failure(step_error).await;
+            }
+        };
         debug!(
             "[{}] step_once => {:?} @ {}",
             self.run_id,
@@ -432,100 +789,253 @@ impl WorkflowEngine {
             self.current_state
         );
 
-        let (outcome, next_state_opt, _raw_out, _meta) = dispatch_command(
-            &cmd,
-            self.state_def(),
-            &self.context,
-            &self.run_id,
-            self.mode,
-            &self.persistence,
-            &self.state_handler_registry,
-        )
-        .await?;
+        // 同步执行的 Task / 扇出受状态 timeoutSeconds 限制
+        let state_timeout = self.inline_state_timeout();
+        let mut attempt = 0;
+        let (outcome, next_state_opt, _raw_out, meta) = match cmd {
+            // Parallel / Map / SubWorkflow 由引擎直接扇出子执行（需要持有子引擎）
+            // 失败同样先按 Retry 策略重试：每次尝试启动新一轮分支（见 `child_run_id`）
+            Command::Parallel { .. } | Command::Map { .. } | Command::SubWorkflow { .. } => loop {
+                let step_error = match within(state_timeout, self.dispatch_fanout()).await {
+                    Some(Ok(out)) => break out,
+                    Some(Err(e)) => self
+                        .last_error
+                        .take()
+                        .unwrap_or_else(|| classify_error(self.state_def(), &e)),
+                    // 超时：仍在运行的分支已随 future 中止
+                    None => {
+                        self.cancel_children().await?;
+                        self.state_timeout_error()
+                    }
+                };
+                let Some(decision) = self.schedule_retry(&step_error).await? else {
+                    return self.handle_state_failure(step_error).await;
+                };
+                debug!(
+                    "[{}] retry #{} of {} in {:?} ({})",
+                    self.run_id, decision.attempt, self.current_state, decision.delay, step_error.error_type
+                );
+
+                if self.mode == WorkflowMode::Deferred {
+                    return self.defer_fanout_retry(&decision).await;
+                }
+                tokio::time::sleep(decision.delay).await;
+            },
+            _ => loop {
+                let writes = Mutex::new(StepWrites::default());
+                let dispatched = within(
+                    state_timeout,
+                    dispatch_command(
+                        &cmd,
+                        self.state_def(),
+                        &self.context,
+                        &self.run_id,
+                        self.mode,
+                        self.timer_shard(),
+                        &self.persistence,
+                        &self.state_handler_registry,
+                        &writes,
+                    ),
+                )
+                .await;
+                // 映射轨迹在失败 / 超时时同样保留
+                self.record_mapping_trace(&writes).await?;
+
+                // 失败：先按 Retry 策略重试，用尽后交给 Catch
+                let step_error = match dispatched {
+                    Some(Ok(out)) => {
+                        self.absorb_writes(writes);
+                        break out;
+                    }
+                    Some(Err(e)) => classify_error(self.state_def(), &e),
+                    None => self.state_timeout_error(),
+                };
+                let Some(decision) = self.schedule_retry(&step_error).await? else {
+                    return self.handle_state_failure(step_error).await;
+                };
+                debug!(
+                    "[{}] retry #{} of {} in {:?} ({})",
+                    self.run_id, decision.attempt, self.current_state, decision.delay, step_error.error_type
+                );
+
+                if self.deferred_task() {
+                    self.redispatch_task(&decision).await?;
+                    attempt = decision.attempt;
+                    break (
+                        StepOutcome {
+                            should_continue: true,
+                            updated_context: self.context.clone(),
+                            suspended: true,
+                        },
+                        None,
+                        Value::Null,
+                        None,
+                    );
+                }
+                tokio::time::sleep(decision.delay).await;
+            },
+        };
+
+        // Fail 状态：以声明的 error / cause 结束执行
+        if let Command::Fail { error, cause, .. } = &cmd {
+            return self
+                .fail_execution(StepError {
+                    error_type: error.clone().unwrap_or_else(|| STATES_FAIL.to_string()),
+                    message: cause.clone().or_else(|| error.clone()).unwrap_or_default(),
+                    origin: ErrorOrigin::Engine,
+                })
+                .await;
+        }
+
+        // —— ③ 挂起：deferred Task 已入队 / Wait 定时器已创建 / 分支尚未全部完成，游标停留在当前状态 ——
+        if outcome.suspended {
+            if new_visit {
+                self.schedule_state_timeout(attempt);
+            }
+            match self.state_def() {
+                State::Task(_) => {
+                    self.expose_task_token(meta.as_ref());
+                    self.last_task_state = Some(self.current_state.clone());
+                    self.awaiting_signal = true;
+                    self.dispatch_event(EngineEvent::NodeDispatched {
+                        run_id: self.run_id.clone(),
+                        state_name: self.current_state.clone(),
+                        context: self.context.clone(),
+                    })
+                    .await?;
+                }
+                State::Wait(_) => {
+                    self.awaiting_signal = true;
+                    let fire_at = meta
+                        .as_ref()
+                        .and_then(|m| m.get("fire_at"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string();
+                    self.dispatch_event(EngineEvent::TimerScheduled {
+                        run_id: self.run_id.clone(),
+                        state_name: self.current_state.clone(),
+                        timestamp: fire_at,
+                    })
+                    .await?;
+                }
+                State::WaitForSignal(wait) => {
+                    let signal_name = wait.signal_name.clone();
+                    self.awaiting_signal = true;
+                    self.dispatch_event(EngineEvent::SignalAwaited {
+                        run_id: self.run_id.clone(),
+                        state_name: self.current_state.clone(),
+                        signal_name,
+                    })
+                    .await?;
+                }
+                _ => {}
+            }
+
+            // 任务入队 / 定时器与游标、事件一并提交
+            self.save_execution(UpdateStoredWorkflowExecution {
+                current_state_name: Some(Some(self.current_state.clone())),
+                context_snapshot: Some(Some(self.context.clone())),
+                ..Default::default()
+            })
+            .await?;
+
+            debug!("⏸ [{}] suspended @ {}", self.run_id, self.current_state);
+            return Ok(outcome);
+        }
+
+        // —— ④ 记录完成并推进游标 ——
+        self.complete_current_state(outcome.updated_context.clone(), next_state_opt)
+            .await?;
+
+        debug!("🔁 step_once returned: {:?}", cmd);
+        debug!("📤 step outcome: {:?}", outcome);
+
+        Ok(outcome)
+    }
+
+    /// 当前状态执行完毕：记录 COMPLETED/FAILED、推进游标 or 结束工作流。
+    ///
+    /// 同步执行的状态在 `advance_once` 内调用；挂起的状态（deferred Task）在收到信号后调用。
+    pub(crate) async fn complete_current_state(
+        &mut self,
+        updated_context: Value,
+        next_state: Option<String>,
+    ) -> Result<(), String> {
+        let should_continue = next_state.is_some();
+        self.awaiting_signal = false;
 
         // 更新本地 context
-        self.context = outcome.updated_context.clone();
+        self.context = updated_context;
         self.updated_at = Utc::now();
 
-        // —— ③ 记录 COMPLETED/FAILED，只更新 output/status ——
+        // —— execution 变更（CAS）与状态记录、事件在同一事务中提交：冲突时整体回滚 ——
+        let mut exec_update = UpdateStoredWorkflowExecution {
+            context_snapshot: Some(Some(self.context.clone())),
+            ..Default::default()
+        };
+        match &next_state {
+            Some(next) => exec_update.current_state_name = Some(Some(next.clone())),
+            None => {
+                exec_update.status = Some("COMPLETED".into());
+                exec_update.close_time = Some(Some(self.updated_at.naive_utc()));
+            }
+        }
+
+        // —— 记录 COMPLETED/FAILED，只更新 output/status ——
         self.record_state_finished(
-            /* success */ outcome.should_continue,
+            /* success */ should_continue,
             /* output  */ Some(self.context.clone()),
             /* error   */ None,
-        )
-        .await?;
+        );
 
-        // 发送 NodeExit
+        // 发送 NodeExit（携带转移后的 context 与目标，供历史回放）
         self.dispatch_event(EngineEvent::NodeExit {
             run_id: self.run_id.clone(),
             state_name: self.current_state.clone(),
-            status: if outcome.should_continue {
-                "success"
-            } else {
-                "failed"
-            }
-            .into(),
+            status: if should_continue { "success" } else { "failed" }.into(),
             duration_ms: Some((Utc::now() - self.updated_at).num_milliseconds() as u64),
+            context: Some(self.context.clone()),
+            next_state: next_state.clone(),
         })
-        .await;
+        .await?;
 
-        // —— ④ 推进游标 or 结束工作流 ——
-        let mut exec_update = UpdateStoredWorkflowExecution {
-            context_snapshot: Some(Some(self.context.clone())),
-            ..Default::default()
-        };
+        // onCancel 清理路径结束：记录为 CANCELLED
+        if let (None, Some(cancellation)) = (&next_state, self.cancellation.clone()) {
+            return self.close_stopped("CANCELLED", cancellation).await;
+        }
 
-        if outcome.should_continue {
-            let next = next_state_opt.ok_or_else(|| {
-                format!(
-                    "state {} should continue but next_state is None",
-                    self.current_state
-                )
-            })?;
+        if !should_continue {
+            self.dispatch_event(EngineEvent::WorkflowFinished {
+                run_id: self.run_id.clone(),
+                result: self.context.clone(),
+            })
+            .await?;
+        }
+        self.save_execution(exec_update).await?;
 
+        // —— 提交成功后推进游标 or 结束工作流 ——
+        if let Some(next) = next_state {
             // Task 节点：记下 “上一个 task”
             if matches!(self.state_def(), State::Task(_)) {
                 self.last_task_state = Some(self.current_state.clone());
             }
-
-            self.current_state = next.clone();
-            exec_update.current_state_name = Some(Some(next));
+            self.current_state = next;
         } else {
             self.finished = true;
-            exec_update.status = Some("COMPLETED".into());
-            exec_update.close_time = Some(Some(self.updated_at.naive_utc()));
-            self.dispatch_event(EngineEvent::WorkflowFinished {
-                run_id: self.run_id.clone(),
-                result: self.context.clone(),
-            })
-            .await;
+            self.record_visibility().await;
         }
-
-        // 写 execution 表
-        self.persistence
-            .update_execution(&self.run_id, &exec_update)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        debug!("🔁 step_once returned: {:?}", cmd);
-        debug!("📤 step outcome: {:?}", outcome);
-
-        Ok(outcome)
+        Ok(())
     }
 
     // ----------------- 外部控制 -------------------------------
 
     pub async fn pause(&mut self) -> Result<(), String> {
-        self.persistence
-            .update_execution(
-                &self.run_id,
-                &UpdateStoredWorkflowExecution {
-                    status: Some("PAUSED".into()),
-                    ..Default::default()
-                },
-            )
-            .await
-            .map_err(|e| e.to_string())?;
+        self.save_execution(UpdateStoredWorkflowExecution {
+            status: Some("PAUSED".into()),
+            ..Default::default()
+        })
+        .await?;
         self.finished = true;
         Ok(())
     }
@@ -547,3 +1057,16 @@ impl WorkflowEngine {
         }
     }
 }
+
+fn parse_mode(mode: &str) -> Result<WorkflowMode, String> {
+    match mode {
+        "INLINE" => Ok(WorkflowMode::Inline),
+        "DEFERRED" => Ok(WorkflowMode::Deferred),
+        _ => Err(format!("Invalid mode {}", mode)),
+    }
+}
+
+/// 执行已结束或被暂停，引擎不再自动推进
+fn is_halted(status: &str) -> bool {
+    execution::is_closed(status) || matches!(status, "PAUSED" | "SUSPENDED")
+}
@@ -4,10 +4,11 @@ use crate::{
     mapping::MappingPipeline,
 };
 use serde_json::Value;
-use stepflow_dsl::{state::base::BaseState, State};
+use stepflow_dsl::State;
+use std::sync::Mutex;
 use stepflow_storage::db::DynPM;
 use super::types::{StepOutcome, WorkflowMode};
-use crate::handler::execution_scope::StateExecutionScope;
+use crate::handler::execution_scope::{StateExecutionScope, StepWrites};
 
 /// 调度失败类型
 #[derive(thiserror::Error, Debug)]
@@ -32,42 +33,34 @@ impl From<DispatchError> for String {
     }
 }
 
-/// WorkflowEngine 调用的统一状态执行入口（无事件）
+/// WorkflowEngine 调用的统一状态执行入口（无事件）；handler 的定时器 / 入队写入 `writes`，随步骤提交
 /// 返回：(StepOutcome, Option<next_state>, raw_output, metadata)
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn dispatch_command(
     cmd: &Command,
     state_enum: &State,
     context: &Value,
     run_id: &str,
     mode: WorkflowMode,
+    timer_shard: i64,
     persistence: &DynPM,
     registry: &StateHandlerRegistry,
+    writes: &Mutex<StepWrites>,
 ) -> Result<(StepOutcome, Option<String>, Value, Option<Value>), String> {
     let state_name = cmd.state_name().to_string();
     let state_type = state_enum.variant_name();
 
-    // ---------- 1. 提取基础状态定义 ----------
-    let base: &BaseState = match state_enum {
-        State::Task(s) => &s.base,
-        State::Wait(s) => &s.base,
-        State::Pass(s) => &s.base,
-        State::Choice(s) => &s.base,
-        State::Fail(s) => &s.base,
-        State::Succeed(s) => &s.base,
-        State::Parallel(_) | State::Map(_) => {
-            return Err("Parallel / Map not yet supported".to_string());
-        }
-    };
+    // ---------- 1. 分支类状态由 fan-out 执行 ----------
+    if matches!(state_enum, State::Parallel(_) | State::Map(_) | State::SubWorkflow(_)) {
+        return Err(format!(
+            "{state_type} state '{state_name}' must be dispatched through engine fan-out"
+        ));
+    }
 
-    // ---------- 2. 执行输入映射 ----------
-    let pipeline = MappingPipeline {
-        input_mapping: base.input_mapping.as_ref(),
-        output_mapping: base.output_mapping.as_ref(),
-    };
+    // ---------- 2. 执行输入映射（InputPath / InputMapping / Parameters） ----------
+    let pipeline = MappingPipeline::for_state(state_enum).with_writes(writes);
 
-    let exec_in = pipeline
-        .apply_input(context)
-        .map_err(|e| format!("apply_input failed: {e:?}"))?;
+    let exec_in = pipeline.apply_input(context)?;
 
     // ---------- 3. 查找 handler 并执行 ----------
     let handler = registry
@@ -82,21 +75,56 @@ pub(crate) async fn dispatch_command(
         None,
         persistence,
         state_enum,
-    );
+    )
+    .with_timer_shard(timer_shard)
+    .with_writes(writes);
 
     let result = handler
         .handle(&scope, &exec_in)
         .await
         .map_err(|e| DispatchError::StateError(e.to_string()))?;
 
-    // ---------- 4. Choice 特殊处理 ----------
+    // ---------- 4. Deferred Task 已入队 / 回调 Task 已签发 token / WaitForSignal：等待外部信号 ----------
+    let awaits_callback = match state_enum {
+        State::Task(task) => mode == WorkflowMode::Deferred || task.waits_for_task_token(),
+        State::WaitForSignal(_) => true,
+        _ => false,
+    };
+    if awaits_callback {
+        return Ok((
+            StepOutcome {
+                should_continue: true,
+                updated_context: context.clone(),
+                suspended: true,
+            },
+            result.next_state.clone(),
+            result.output,
+            result.metadata,
+        ));
+    }
+
+    // ---------- 5. Wait 已转为定时器（Deferred / Inline 长等待）：等待 TimerFired ----------
+    if matches!(state_enum, State::Wait(_)) && result.metadata.is_some() {
+        return Ok((
+            StepOutcome {
+                should_continue: true,
+                updated_context: context.clone(),
+                suspended: true,
+            },
+            result.next_state.clone(),
+            result.output,
+            result.metadata,
+        ));
+    }
+
+    // ---------- 6. Choice 特殊处理 ----------
     let logical_next = if let (Command::Choice { next_state, .. }, State::Choice(_)) = (cmd, state_enum) {
         Some(next_state.clone())
     } else {
         result.next_state.clone()
     };
 
-    // ---------- 5. 执行输出映射 ----------
+    // ---------- 7. 执行输出映射 ----------
     let new_ctx = pipeline
         .apply_output(&result.output, context)
         .map_err(|e| DispatchError::MappingError(e.to_string()))?;
@@ -105,6 +133,7 @@ pub(crate) async fn dispatch_command(
         StepOutcome {
             should_continue: logical_next.is_some(),
             updated_context: new_ctx,
+            suspended: false,
         },
         logical_next,
         result.output,
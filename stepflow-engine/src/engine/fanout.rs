@@ -0,0 +1,866 @@
+//! Parallel / Map / SubWorkflow 扇出执行：每个 `Branch`（Map 为每个 item 跑一次 `iterator`）
+//! 作为一个子执行（child sub-execution）运行，全部完成后按分支顺序汇合为数组，
+//! 再交给状态的 OutputMapping。
+//!
+//! * SubWorkflow 视为只有一个分支的扇出：分支 DSL 来自引用的模板，汇合结果为子执行的输出本身
+//! * 子执行记录的 `parent_run_id` 指向父执行，可通过 `find_executions_by_parent` 列出
+//!
+//! * 子执行 run_id：`{parent}::{state}@{attempt}[{index}]`，外部信号按 [`root_run_id`] 路由回根引擎；
+//!   `attempt` 为本轮尝试的起始事件 id（进入状态的转移事件，Retry 后为 NodeRetrying），
+//!   再次进入状态（如 Choice 循环）或重试时启动新一轮子执行，发给上一轮子执行的信号被拒绝
+//! * 分支进度写入 `workflow_states`（state_id：`{parent}:{state}@{attempt}[{index}]`），
+//!   引擎恢复后本轮已完成的分支直接复用输出，不会重跑
+//! * Inline：子引擎并发运行，`max_concurrency` 由信号量限制（0 / 未设置 = 不限）
+//! * Deferred：子引擎挂起在 `children` 中，收到信号后推进，全部完成才推进父状态
+//! * 失败容忍：Map 的 `toleratedFailurePercentage` 允许部分 item 失败，失败 item 在
+//!   汇合结果中以错误对象 `{ "Error": ..., "Cause": ... }` 占位；Parallel 任一分支失败即失败，
+//!   失败分支的错误类型原样上抛给父状态的 Retry / Catch
+//! * 取消：子执行被取消时以 `States.Cancelled` 上抛；父执行失败时仍在运行的子执行被标记为 CANCELLED
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use jsonpath_lib::select;
+use serde_json::Value;
+use stepflow_dsl::{state::SubWorkflowState, State, WorkflowDSL};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_exception::{
+    ErrorOrigin, StepError, STATES_EXCEED_TOLERATED_FAILURE_THRESHOLD, STATES_RUNTIME,
+};
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::{
+    workflow_execution::{StoredWorkflowExecution, UpdateStoredWorkflowExecution},
+    workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState},
+};
+use tokio::{sync::Semaphore, task::JoinHandle};
+use tracing::{debug, warn};
+
+use crate::handler::execution_scope::StepWrites;
+use crate::mapping::MappingPipeline;
+use super::catch::error_object;
+use crate::signal::handler::apply_signal;
+
+use super::{
+    core::WorkflowEngine,
+    template::{self, RevisionSelector},
+    types::{StateExecutionResult, StepOutcome, WorkflowMode},
+};
+
+const BRANCH_SEPARATOR: &str = "::";
+
+/// 分支子执行的 run_id（`attempt`：本轮尝试的起始事件 id）
+pub fn branch_run_id(parent_run_id: &str, state_name: &str, attempt: i64, index: usize) -> String {
+    format!("{parent_run_id}{BRANCH_SEPARATOR}{state_name}@{attempt}[{index}]")
+}
+
+/// 子执行所属的根执行 run_id（顶层执行返回自身）
+pub fn root_run_id(run_id: &str) -> &str {
+    run_id.split(BRANCH_SEPARATOR).next().unwrap_or(run_id)
+}
+
+fn branch_state_id(parent_run_id: &str, state_name: &str, attempt: i64, index: usize) -> String {
+    format!("{parent_run_id}:{state_name}@{attempt}[{index}]")
+}
+
+/// 持久化在 workflow_states 中的分支进度
+#[derive(Debug, Clone)]
+enum BranchProgress {
+    NotStarted,
+    Running,
+    Completed(Value),
+    Failed(StepError),
+}
+
+type BranchResult = Result<Value, StepError>;
+
+enum BranchJob {
+    Done(Value),
+    Failed(StepError),
+    Spawned(AbortOnDrop),
+}
+
+/// 汇合被放弃（如父状态超时）时中止仍在运行的 Inline 分支
+struct AbortOnDrop(JoinHandle<BranchResult>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+type BranchFuture = Pin<Box<dyn Future<Output = BranchResult> + Send>>;
+
+/// 非分支自身产生的失败（调度 / 恢复出错）
+fn runtime_error(message: String) -> StepError {
+    StepError {
+        error_type: STATES_RUNTIME.to_string(),
+        message,
+        origin: ErrorOrigin::Engine,
+    }
+}
+
+/// 子执行失败时的类型化错误（子引擎未记录时按 States.Runtime 处理）
+fn child_error(child: &mut WorkflowEngine, message: String) -> StepError {
+    child.last_error.take().unwrap_or_else(|| runtime_error(message))
+}
+
+/// Inline 分支：拿到并发许可后跑完整个子执行
+fn run_branch_inline(mut child: WorkflowEngine, semaphore: Arc<Semaphore>) -> BranchFuture {
+    Box::pin(async move {
+        let _permit = semaphore
+            .acquire_owned()
+            .await
+            .map_err(|e| runtime_error(format!("branch semaphore closed: {e}")))?;
+        match child.run_inline().await {
+            Ok(output) => Ok(output),
+            Err(e) => Err(child_error(&mut child, e)),
+        }
+    })
+}
+
+/// 当前 fan-out 状态展开后的分支
+struct FanoutPlan {
+    /// (子执行 DSL, 子执行输入)
+    branches: Vec<(WorkflowDSL, Value)>,
+    /// 并发上限（0 = 不限）
+    max_concurrency: usize,
+    /// 最多允许失败的分支数
+    tolerated_failures: usize,
+}
+
+fn fanout_plan(state: &State, input: &Value) -> Result<FanoutPlan, String> {
+    match state {
+        State::Parallel(parallel) => Ok(FanoutPlan {
+            branches: parallel
+                .branches
+                .iter()
+                .map(|branch| (branch.to_workflow(), input.clone()))
+                .collect(),
+            max_concurrency: parallel.max_concurrency.unwrap_or(0) as usize,
+            tolerated_failures: 0,
+        }),
+        State::Map(map) => {
+            let items = select_items(input, &map.items_path)?;
+            let percentage = map.tolerated_failure_percentage.unwrap_or(0.0);
+            if !(0.0..=100.0).contains(&percentage) {
+                return Err(format!(
+                    "toleratedFailurePercentage must be within 0..=100, got {percentage}"
+                ));
+            }
+            let tolerated_failures = (items.len() as f64 * percentage / 100.0).floor() as usize;
+            let iterator = map.iterator.to_workflow();
+
+            Ok(FanoutPlan {
+                branches: items
+                    .into_iter()
+                    .map(|item| (iterator.clone(), item))
+                    .collect(),
+                max_concurrency: map.max_concurrency.unwrap_or(0) as usize,
+                tolerated_failures,
+            })
+        }
+        other => Err(format!(
+            "State type '{}' does not fan out",
+            other.variant_name()
+        )),
+    }
+}
+
+/// 解析 SubWorkflow 引用的模板修订（`templateVersion` / `templateAlias`，默认最新发布的修订）并加载 DSL
+async fn sub_workflow_dsl(persistence: &DynPM, sub: &SubWorkflowState) -> Result<WorkflowDSL, String> {
+    let selector = RevisionSelector::from_parts(sub.template_version, sub.template_alias.as_deref());
+    let revision = template::runnable_revision(persistence, &sub.template_id, &selector).await?;
+    template::revision_dsl(&revision)
+}
+
+/// 按 `itemsPath` 取出 Map 要遍历的数组
+fn select_items(input: &Value, items_path: &str) -> Result<Vec<Value>, String> {
+    let hits = select(input, items_path)
+        .map_err(|e| format!("itemsPath '{items_path}' is invalid: {e}"))?;
+    match hits.first() {
+        Some(Value::Array(items)) => Ok(items.clone()),
+        Some(other) => Err(format!(
+            "itemsPath '{items_path}' must select an array, got {other}"
+        )),
+        None => Err(format!("itemsPath '{items_path}' selected nothing")),
+    }
+}
+
+/// 把 SubWorkflow 唯一分支的汇合结果还原为子执行本身的输出 / 错误
+fn unwrap_single_child(joined: BranchResult, child_id: &str) -> BranchResult {
+    match joined {
+        Ok(Value::Array(mut outputs)) => Ok(outputs.pop().unwrap_or(Value::Null)),
+        Ok(other) => Ok(other),
+        Err(error) => Err(StepError {
+            message: format!(
+                "Child execution {child_id} failed: {}",
+                error.message.strip_prefix("Branch 0 failed: ").unwrap_or(&error.message)
+            ),
+            ..error
+        }),
+    }
+}
+
+/// 按分支顺序汇合结果；失败数不超过容忍值时，失败分支以错误对象占位
+fn join_branches(results: Vec<BranchResult>, tolerated_failures: usize) -> BranchResult {
+    let failures: Vec<(usize, &StepError)> = results
+        .iter()
+        .enumerate()
+        .filter_map(|(index, r)| r.as_ref().err().map(|e| (index, e)))
+        .collect();
+
+    if failures.len() > tolerated_failures {
+        return Err(failure_error(&failures, results.len(), tolerated_failures));
+    }
+
+    Ok(Value::Array(
+        results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|e| error_object(&e)))
+            .collect(),
+    ))
+}
+
+fn failed_count(outputs: &[Option<BranchResult>]) -> usize {
+    outputs.iter().filter(|o| matches!(o, Some(Err(_)))).count()
+}
+
+/// 超出容忍度时父状态的错误：不容忍失败时沿用首个失败分支的错误类型，
+/// 否则为 `States.ExceedToleratedFailureThreshold`
+fn failure_error(failures: &[(usize, &StepError)], total: usize, tolerated: usize) -> StepError {
+    let (index, first) = failures[0];
+    if tolerated == 0 {
+        let message = if failures.len() == 1 {
+            format!("Branch {index} failed: {}", first.message)
+        } else {
+            format!(
+                "{} of {} branch(es) failed; branch {} failed: {}",
+                failures.len(),
+                total,
+                index,
+                first.message
+            )
+        };
+        return StepError {
+            error_type: first.error_type.clone(),
+            message,
+            origin: first.origin.clone(),
+        };
+    }
+
+    StepError {
+        error_type: STATES_EXCEED_TOLERATED_FAILURE_THRESHOLD.to_string(),
+        message: format!(
+            "{} of {} branch(es) failed (tolerated {}); branch {} failed: {}",
+            failures.len(),
+            total,
+            tolerated,
+            index,
+            first.message
+        ),
+        origin: ErrorOrigin::Engine,
+    }
+}
+
+impl WorkflowEngine {
+    /// 当前扇出状态本轮尝试中第 `index` 个分支的子执行 run_id
+    pub fn child_run_id(&self, index: usize) -> String {
+        branch_run_id(&self.run_id, &self.current_state, self.attempt_event_id(), index)
+    }
+
+    /// 执行当前 Parallel / Map / SubWorkflow 状态。
+    /// 返回值与 `dispatch_command` 一致；分支未全部完成时返回 `suspended` 的 StepOutcome。
+    /// 分支失败超出容忍度时返回 Err，类型化错误记录在 `last_error` 中交给 Retry / Catch。
+    pub(crate) async fn dispatch_fanout(
+        &mut self,
+    ) -> Result<(StepOutcome, Option<String>, Value, Option<Value>), String> {
+        let state = self.state_def().clone();
+        let (_, base) = self.dsl.get_state_and_base(&self.current_state);
+        let base = base.clone();
+
+        let writes = Mutex::new(StepWrites::default());
+        let pipeline = MappingPipeline::for_state(&state).with_writes(&writes);
+
+        let exec_in = pipeline.apply_input(&self.context);
+        self.record_mapping_trace(&writes).await?;
+        let exec_in = exec_in?;
+
+        let plan = match &state {
+            State::SubWorkflow(sub) => FanoutPlan {
+                branches: vec![(sub_workflow_dsl(&self.persistence, sub).await?, exec_in)],
+                max_concurrency: 1,
+                tolerated_failures: 0,
+            },
+            other => fanout_plan(other, &exec_in)?,
+        };
+        let joined = match self.mode {
+            WorkflowMode::Inline => self.run_branches_inline(plan).await?,
+            WorkflowMode::Deferred => match self.run_branches_deferred(plan).await? {
+                Some(joined) => joined,
+                None => {
+                        return Ok((
+                            StepOutcome {
+                                should_continue: true,
+                                updated_context: self.context.clone(),
+                                suspended: true,
+                            },
+                            base.next.clone(),
+                            Value::Null,
+                            None,
+                        ));
+                }
+            },
+        };
+
+        let joined = match &state {
+            State::SubWorkflow(_) => {
+                unwrap_single_child(joined, &self.child_run_id(0))
+            }
+            _ => joined,
+        };
+        let joined = match joined {
+            Ok(joined) => joined,
+            Err(error) => {
+                let message = error.message.clone();
+                self.last_error = Some(error);
+                return Err(message);
+            }
+        };
+
+        let new_ctx = pipeline.apply_output(&joined, &self.context);
+        self.record_mapping_trace(&writes).await?;
+        let new_ctx = new_ctx?;
+
+        Ok((
+            StepOutcome {
+                should_continue: base.next.is_some(),
+                updated_context: new_ctx,
+                suspended: false,
+            },
+            base.next.clone(),
+            joined,
+            None,
+        ))
+    }
+
+    // ------------------ Inline ---------------------------------
+
+    /// 外层 Err 为调度 / 存储错误；内层为分支汇合结果
+    async fn run_branches_inline(&self, plan: FanoutPlan) -> Result<BranchResult, String> {
+        let FanoutPlan {
+            branches,
+            max_concurrency,
+            tolerated_failures,
+        } = plan;
+        let limit = if max_concurrency == 0 {
+            branches.len().max(1)
+        } else {
+            max_concurrency
+        };
+        let semaphore = Arc::new(Semaphore::new(limit));
+
+        let mut jobs = Vec::with_capacity(branches.len());
+        for (index, (dsl, input)) in branches.into_iter().enumerate() {
+            match self.load_branch_progress(index).await? {
+                BranchProgress::Completed(output) => {
+                    debug!("[{}] branch {} already completed, reuse output", self.run_id, index);
+                    jobs.push(BranchJob::Done(output));
+                }
+                BranchProgress::Failed(e) => jobs.push(BranchJob::Failed(e)),
+                BranchProgress::NotStarted | BranchProgress::Running => {
+                    let child = self.start_branch_engine(index, dsl, input).await?;
+                    jobs.push(BranchJob::Spawned(AbortOnDrop(tokio::spawn(run_branch_inline(
+                        child,
+                        semaphore.clone(),
+                    )))));
+                }
+            }
+        }
+
+        // 等所有分支结束后再按容忍度汇合
+        let mut results = Vec::with_capacity(jobs.len());
+        for (index, job) in jobs.into_iter().enumerate() {
+            let mut handle = match job {
+                BranchJob::Done(output) => {
+                    results.push(Ok(output));
+                    continue;
+                }
+                BranchJob::Failed(e) => {
+                    results.push(Err(e));
+                    continue;
+                }
+                BranchJob::Spawned(handle) => handle,
+            };
+
+            let result = (&mut handle.0)
+                .await
+                .map_err(|e| runtime_error(format!("branch task aborted: {e}")))
+                .and_then(|r| r);
+
+            match &result {
+                Ok(output) => self.finish_branch(index, Ok(output)).await?,
+                Err(e) => {
+                    warn!("[{}] branch {} failed: {}", self.run_id, index, e.message);
+                    self.finish_branch(index, Err(e)).await?;
+                }
+            }
+            results.push(result);
+        }
+
+        Ok(join_branches(results, tolerated_failures))
+    }
+
+    // ------------------ Deferred -------------------------------
+
+    /// 推进所有分支；全部完成返回 `Some(joined)`，仍有分支在等待信号返回 `None`
+    async fn run_branches_deferred(
+        &mut self,
+        plan: FanoutPlan,
+    ) -> Result<Option<BranchResult>, String> {
+        let FanoutPlan {
+            branches,
+            max_concurrency,
+            tolerated_failures,
+        } = plan;
+        let mut outputs: Vec<Option<BranchResult>> = vec![None; branches.len()];
+        let mut running = 0usize;
+        let mut not_started = Vec::new();
+
+        for (index, (dsl, input)) in branches.into_iter().enumerate() {
+            match self.load_branch_progress(index).await? {
+                BranchProgress::Completed(output) => outputs[index] = Some(Ok(output)),
+                BranchProgress::Failed(e) => outputs[index] = Some(Err(e)),
+                BranchProgress::Running => {
+                    let child_id = self.child_run_id(index);
+                    let child = self.ensure_child(&child_id, index, dsl).await?;
+                    if child.finished {
+                        let output = child.context.clone();
+                        self.children.remove(&child_id);
+                        self.finish_branch(index, Ok(&output)).await?;
+                        outputs[index] = Some(Ok(output));
+                    } else {
+                        running += 1;
+                    }
+                }
+                BranchProgress::NotStarted => not_started.push((index, dsl, input)),
+            }
+        }
+
+        for (index, dsl, input) in not_started {
+            if max_concurrency > 0 && running >= max_concurrency {
+                break;
+            }
+            // 已超出失败容忍度时不再启动新分支
+            if failed_count(&outputs) > tolerated_failures {
+                break;
+            }
+
+            let mut child = self.start_branch_engine(index, dsl, input).await?;
+            if let Err(e) = Box::pin(child.advance_until_blocked()).await {
+                warn!("[{}] branch {} failed: {}", self.run_id, index, e);
+                let error = child_error(&mut child, e);
+                self.finish_branch(index, Err(&error)).await?;
+                outputs[index] = Some(Err(error));
+                continue;
+            }
+
+            if child.finished {
+                self.finish_branch(index, Ok(&child.context)).await?;
+                outputs[index] = Some(Ok(child.context.clone()));
+            } else {
+                running += 1;
+                self.children.insert(child.run_id.clone(), child);
+            }
+        }
+
+        let failures: Vec<(usize, &StepError)> = outputs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, o)| match o {
+                Some(Err(e)) => Some((index, e)),
+                _ => None,
+            })
+            .collect();
+        if failures.len() > tolerated_failures {
+            // 超出容忍度：不再等待仍在运行的分支
+            let error = failure_error(&failures, outputs.len(), tolerated_failures);
+            self.cancel_children().await?;
+            return Ok(Some(Err(error)));
+        }
+
+        if outputs.iter().all(Option::is_some) {
+            let results = outputs.into_iter().flatten().collect();
+            Ok(Some(join_branches(results, tolerated_failures)))
+        } else {
+            debug!(
+                "[{}] {} branch(es) still running @ {}",
+                self.run_id, running, self.current_state
+            );
+            Ok(None)
+        }
+    }
+
+    /// 把发给分支子执行的信号交给对应子引擎，并在子执行结束时记录分支进度。
+    /// 父状态本身在下一次 `advance_until_blocked` 时汇合。
+    pub(crate) async fn route_branch_signal(
+        &mut self,
+        target_run_id: &str,
+        signal: ExecutionSignal,
+    ) -> Result<StateExecutionResult, String> {
+        let prefix = format!("{}{BRANCH_SEPARATOR}", self.run_id);
+        let segment = target_run_id
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split(BRANCH_SEPARATOR).next())
+            .ok_or_else(|| format!("Signal run_id '{target_run_id}' is not a branch of '{}'", self.run_id))?;
+
+        let attempt = format!("@{}[", self.attempt_event_id());
+        let index = segment
+            .strip_prefix(self.current_state.as_str())
+            .and_then(|rest| rest.strip_prefix(attempt.as_str()))
+            .and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|idx| idx.parse::<usize>().ok())
+            .ok_or_else(|| {
+                format!(
+                    "Signal run_id '{target_run_id}' does not belong to the current attempt of state '{}'",
+                    self.current_state
+                )
+            })?;
+
+        let child_id = format!("{prefix}{segment}");
+        let dsl = self.branch_dsl(index).await?;
+        let child = self.ensure_child(&child_id, index, dsl).await?;
+
+        let result = match Box::pin(apply_signal(child, signal)).await {
+            Ok(_) => Box::pin(child.advance_until_blocked()).await.map(|_| ()),
+            // 失败路径上未随 execution 提交的写入（重新入队等）
+            Err(e) => child.flush().await.and(Err(e)),
+        };
+
+        match result {
+            // 子执行以失败结束但信号本身已处理（如执行级超时）：错误保留在 last_error
+            Ok(()) if child.finished => {
+                let outcome = match child.last_error.take() {
+                    Some(error) => Err(error),
+                    None => Ok(child.context.clone()),
+                };
+                self.children.remove(&child_id);
+                self.finish_branch(index, outcome.as_ref()).await?;
+            }
+            Ok(()) => {}
+            Err(e) => {
+                let error = child_error(child, e);
+                self.children.remove(&child_id);
+                self.finish_branch(index, Err(&error)).await?;
+            }
+        }
+
+        Ok(StateExecutionResult {
+            output: self.context.clone(),
+            next_state: Some(self.current_state.clone()),
+            should_continue: true,
+            metadata: None,
+        })
+    }
+
+    // ------------------ 子执行 & 进度 ---------------------------
+
+    async fn branch_dsl(&self, index: usize) -> Result<WorkflowDSL, String> {
+        match self.state_def() {
+            State::Parallel(parallel) => parallel
+                .branches
+                .get(index)
+                .map(|branch| branch.to_workflow())
+                .ok_or_else(|| format!("Branch {index} not found in '{}'", self.current_state)),
+            State::Map(map) => Ok(map.iterator.to_workflow()),
+            State::SubWorkflow(sub) => {
+                // 已开始的子执行按其记录的修订恢复
+                let child_id = self.child_run_id(index);
+                let child = self
+                    .persistence
+                    .get_execution(&child_id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                match child.and_then(|c| c.template_revision) {
+                    Some(revision) => template::pinned_dsl(&self.persistence, &sub.template_id, revision).await,
+                    None => sub_workflow_dsl(&self.persistence, sub).await,
+                }
+            }
+            other => Err(format!(
+                "State '{}' ({}) has no branches",
+                self.current_state,
+                other.variant_name()
+            )),
+        }
+    }
+
+    /// 取内存中的子引擎；进程重启后从 execution 记录恢复
+    async fn ensure_child(
+        &mut self,
+        child_id: &str,
+        index: usize,
+        dsl: WorkflowDSL,
+    ) -> Result<&mut WorkflowEngine, String> {
+        if !self.children.contains_key(child_id) {
+            debug!("[{}] restoring branch {} ({})", self.run_id, index, child_id);
+            let child = WorkflowEngine::restore_with_dsl(
+                child_id.to_string(),
+                dsl,
+                self.event_dispatcher.clone(),
+                self.persistence.clone(),
+                self.state_handler_registry.clone(),
+            )
+            .await?
+            .with_timer_shard_count(self.timer_shard_count);
+            self.children.insert(child_id.to_string(), child);
+        }
+        self.children
+            .get_mut(child_id)
+            .ok_or_else(|| format!("Branch {child_id} not loaded"))
+    }
+
+    /// 落库子执行记录 + 分支进度（RUNNING），返回新的子引擎
+    async fn start_branch_engine(
+        &self,
+        index: usize,
+        dsl: WorkflowDSL,
+        input: Value,
+    ) -> Result<WorkflowEngine, String> {
+        let child_id = self.child_run_id(index);
+        let now = Utc::now().naive_utc();
+
+        let existing = self
+            .persistence
+            .get_execution(&child_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if existing.is_none() {
+            let (template_id, template_revision, workflow_type) = match self.state_def() {
+                State::SubWorkflow(sub) => (
+                    Some(sub.template_id.clone()),
+                    template::pinned_revision(&dsl),
+                    "subworkflow",
+                ),
+                _ => (None, None, "branch"),
+            };
+            let exec_row = StoredWorkflowExecution {
+                run_id: child_id.clone(),
+                workflow_id: Some(format!("wf-{child_id}")),
+                shard_id: 0,
+                template_id,
+                template_revision,
+                parent_run_id: Some(self.run_id.clone()),
+                mode: match self.mode {
+                    WorkflowMode::Inline => "INLINE",
+                    WorkflowMode::Deferred => "DEFERRED",
+                }
+                .into(),
+                current_state_name: Some(dsl.start_at.clone()),
+                status: "RUNNING".into(),
+                workflow_type: workflow_type.into(),
+                input: Some(input.clone()),
+                input_version: 1,
+                result: None,
+                result_version: 1,
+                start_time: now,
+                close_time: None,
+                current_event_id: 0,
+                memo: None,
+                search_attrs: None,
+                context_snapshot: Some(input.clone()),
+                version: 1,
+            };
+            self.persistence
+                .create_execution(&exec_row)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        self.save_branch_progress(
+            index,
+            UpdateStoredWorkflowState {
+                status: Some("RUNNING".into()),
+                input: Some(Some(input.clone())),
+                output: Some(None),
+                error: Some(None),
+                started_at: Some(Some(now)),
+                completed_at: Some(None),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(WorkflowEngine::new(
+            child_id,
+            dsl,
+            input,
+            self.mode,
+            self.event_dispatcher.clone(),
+            self.persistence.clone(),
+            self.state_handler_registry.clone(),
+        )
+        .with_timer_shard_count(self.timer_shard_count))
+    }
+
+    /// 把仍在运行的子执行（含更深层的后代）标记为 CANCELLED，父执行已不再等待它们；
+    /// 同时撤销它们仍在匹配队列中的任务与未触发的定时器，避免 worker 领取、定时器触发已取消的执行
+    pub(crate) async fn cancel_children(&mut self) -> Result<(), String> {
+        self.children.clear();
+        for child in cancel_running_descendants(&self.persistence, &self.run_id).await? {
+            let tasks = self.state_handler_registry.revoke(&child).await?;
+            let timers = self
+                .persistence
+                .cancel_timers_by_run(&child)
+                .await
+                .map_err(|e| e.to_string())?;
+            if tasks + timers > 0 {
+                debug!("[{}] revoked {tasks} task(s) and {timers} timer(s) of {child}", self.run_id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish_branch(
+        &self,
+        index: usize,
+        result: Result<&Value, &StepError>,
+    ) -> Result<(), String> {
+        let (status, output, error, error_type) = match result {
+            Ok(output) => ("COMPLETED", Some(output.clone()), None, None),
+            Err(e) => ("FAILED", None, Some(e.message.clone()), Some(e.error_type.clone())),
+        };
+        self.save_branch_progress(
+            index,
+            UpdateStoredWorkflowState {
+                status: Some(status.into()),
+                output: Some(output),
+                error: Some(error),
+                error_details: Some(error_type),
+                completed_at: Some(Some(Utc::now().naive_utc())),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    async fn load_branch_progress(&self, index: usize) -> Result<BranchProgress, String> {
+        let state_id = branch_state_id(&self.run_id, &self.current_state, self.attempt_event_id(), index);
+        let row = self
+            .persistence
+            .get_state(&state_id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(match row {
+            Some(row) => match row.status.as_str() {
+                "COMPLETED" => BranchProgress::Completed(row.output.unwrap_or(Value::Null)),
+                "FAILED" => BranchProgress::Failed(StepError {
+                    error_type: row
+                        .error_details
+                        .unwrap_or_else(|| STATES_RUNTIME.to_string()),
+                    message: row.error.unwrap_or_default(),
+                    origin: ErrorOrigin::Engine,
+                }),
+                "RUNNING" => BranchProgress::Running,
+                _ => BranchProgress::NotStarted,
+            },
+            None => BranchProgress::NotStarted,
+        })
+    }
+
+    async fn save_branch_progress(
+        &self,
+        index: usize,
+        changes: UpdateStoredWorkflowState,
+    ) -> Result<(), String> {
+        let state_id = branch_state_id(&self.run_id, &self.current_state, self.attempt_event_id(), index);
+        let exists = self
+            .persistence
+            .get_state(&state_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .is_some();
+
+        if exists {
+            return self
+                .persistence
+                .update_state(&state_id, &changes)
+                .await
+                .map_err(|e| e.to_string());
+        }
+
+        let now = Utc::now().naive_utc();
+        let row = StoredWorkflowState {
+            state_id,
+            run_id: self.run_id.clone(),
+            shard_id: 0,
+            state_name: format!("{}[{}]", self.current_state, index),
+            state_type: "Branch".into(),
+            status: changes.status.unwrap_or_else(|| "RUNNING".into()),
+            input: changes.input.flatten(),
+            output: changes.output.flatten(),
+            error: changes.error.flatten(),
+            error_details: changes.error_details.flatten(),
+            started_at: changes.started_at.flatten().or(Some(now)),
+            completed_at: changes.completed_at.flatten(),
+            attempts: 0,
+            mapping_trace: None,
+            created_at: now,
+            updated_at: now,
+            version: 1,
+        };
+        self.persistence
+            .create_state(&row)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+async fn cancel_running_descendants(persistence: &DynPM, run_id: &str) -> Result<Vec<String>, String> {
+    let mut cancelled = vec![];
+    let mut pending = vec![run_id.to_string()];
+    while let Some(parent) = pending.pop() {
+        let children = persistence
+            .find_executions_by_parent(&parent, i64::MAX, 0)
+            .await
+            .map_err(|e| e.to_string())?;
+        for child in children {
+            if matches!(child.status.as_str(), "RUNNING" | "PAUSED") {
+                debug!("[{run_id}] cancelling child execution {}", child.run_id);
+                persistence
+                    .update_execution(
+                        &child.run_id,
+                        &UpdateStoredWorkflowExecution {
+                            status: Some("CANCELLED".into()),
+                            close_time: Some(Some(Utc::now().naive_utc())),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+                cancelled.push(child.run_id.clone());
+            }
+            pending.push(child.run_id);
+        }
+    }
+    Ok(cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_run_id_roundtrip() {
+        let child = branch_run_id("run-1", "Fan", 3, 2);
+        assert_eq!(child, "run-1::Fan@3[2]");
+        assert_eq!(root_run_id(&child), "run-1");
+
+        let grandchild = branch_run_id(&child, "Inner", 1, 0);
+        assert_eq!(root_run_id(&grandchild), "run-1");
+        assert_eq!(root_run_id("run-1"), "run-1");
+    }
+}
@@ -0,0 +1,123 @@
+//! 事件历史：引擎按顺序写入 `workflow_events`（随步骤在同一事务中提交），
+//! 可由 [`Replayer`](super::replay::Replayer) 重建执行。
+//!
+//! * `event_id` 按执行单调递增（从 1 开始），恢复时从已有历史继续编号
+//! * `parent_event_id`：NodeEnter 指向触发转移的 NodeExit（首个状态指向 WorkflowStarted），
+//!   其余状态级事件指向所属状态的 NodeEnter，执行级结束事件指向 WorkflowStarted
+//! * `attributes` 为序列化后的 `EngineEvent`（`attr_version` = [`HISTORY_ATTR_VERSION`]）
+
+use chrono::Utc;
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::workflow_event::StoredWorkflowEvent;
+
+use super::{core::WorkflowEngine, outbox::engine_event_message};
+
+/// 历史事件 attributes 的格式版本
+pub const HISTORY_ATTR_VERSION: i64 = 1;
+
+/// 分页读取历史时的页大小
+const HISTORY_PAGE_SIZE: i64 = 500;
+
+/// 历史写入游标：最后一个事件及用于 parent 链接的锚点
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryCursor {
+    pub last_event_id: i64,
+    pub started_event_id: Option<i64>,
+    pub entered_event_id: Option<i64>,
+    pub exited_event_id: Option<i64>,
+    pub retried_event_id: Option<i64>,
+}
+
+impl HistoryCursor {
+    /// 事件的 parent_event_id
+    fn parent_of(&self, event: &EngineEvent) -> Option<i64> {
+        match event {
+            EngineEvent::WorkflowStarted { .. } => None,
+            EngineEvent::NodeEnter { .. } => self.exited_event_id.or(self.started_event_id),
+            EngineEvent::WorkflowFinished { .. }
+            | EngineEvent::WorkflowFailed { .. }
+            | EngineEvent::WorkflowCancelled { .. }
+            | EngineEvent::WorkflowTerminated { .. } => self.started_event_id,
+            _ if event.state_name().is_some() => self.entered_event_id,
+            _ => self.started_event_id,
+        }
+    }
+
+    /// 记录一个已写入的事件，推进锚点
+    pub(crate) fn advance(&mut self, event_id: i64, event: &EngineEvent) {
+        self.last_event_id = event_id;
+        match event {
+            EngineEvent::WorkflowStarted { .. } => self.started_event_id = Some(event_id),
+            EngineEvent::NodeEnter { .. } => self.entered_event_id = Some(event_id),
+            EngineEvent::NodeExit { .. } => self.exited_event_id = Some(event_id),
+            EngineEvent::NodeRetrying { .. } => self.retried_event_id = Some(event_id),
+            _ => {}
+        }
+    }
+
+    /// 从已有历史重建游标（不解析 attributes，兼容旧格式的事件）
+    pub fn from_events(events: &[StoredWorkflowEvent]) -> Self {
+        let mut cursor = Self::default();
+        for e in events {
+            cursor.last_event_id = cursor.last_event_id.max(e.event_id);
+            match e.event_type.as_str() {
+                "WorkflowStarted" => cursor.started_event_id = Some(e.event_id),
+                "NodeEnter" => cursor.entered_event_id = Some(e.event_id),
+                "NodeExit" => cursor.exited_event_id = Some(e.event_id),
+                "NodeRetrying" => cursor.retried_event_id = Some(e.event_id),
+                _ => {}
+            }
+        }
+        cursor
+    }
+}
+
+/// 读取执行的完整历史（按 event_id 升序）
+pub async fn load_history(
+    persistence: &DynPM,
+    run_id: &str,
+) -> Result<Vec<StoredWorkflowEvent>, String> {
+    let mut events = Vec::new();
+    loop {
+        let page = persistence
+            .find_events_by_run_id(run_id, HISTORY_PAGE_SIZE, events.len() as i64)
+            .await
+            .map_err(|e| e.to_string())?;
+        let done = (page.len() as i64) < HISTORY_PAGE_SIZE;
+        events.extend(page);
+        if done {
+            return Ok(events);
+        }
+    }
+}
+
+impl WorkflowEngine {
+    /// 把历史事件加入当前步骤的写入缓冲；提交后经 outbox 分发给 hooks
+    pub(crate) async fn dispatch_event(&mut self, event: EngineEvent) -> Result<(), String> {
+        let event_id = self.history.last_event_id + 1;
+        let state_name = event.state_name();
+        let stored = StoredWorkflowEvent {
+            id: 0,
+            run_id: self.run_id.clone(),
+            shard_id: 0,
+            event_id,
+            event_type: event.event_type().to_string(),
+            state_id: state_name.map(|s| format!("{}:{}", self.run_id, s)),
+            state_type: state_name
+                .and_then(|s| self.dsl.states.get(s))
+                .map(|s| s.variant_name().to_string()),
+            trace_id: None,
+            parent_event_id: self.history.parent_of(&event),
+            context_version: None,
+            attributes: Some(serde_json::to_string(&event).map_err(|e| e.to_string())?),
+            attr_version: HISTORY_ATTR_VERSION,
+            timestamp: Utc::now().naive_utc(),
+            archived: false,
+        };
+        self.pending.outbox.push(engine_event_message(&self.run_id, &event)?);
+        self.pending.events.push(stored);
+        self.history.advance(event_id, &event);
+        Ok(())
+    }
+}
@@ -1,5 +1,17 @@
+mod cancel;
+pub mod catch;
 mod core;
 mod dispatch;
+mod fanout;
+pub mod history;
+pub mod outbox;
+pub mod replay;
+pub mod retry;
+pub mod template;
+mod timeout;
 mod types;
+mod visibility;
 pub use core::WorkflowEngine;
+pub use fanout::{branch_run_id, root_run_id};
+pub use timeout::timer_shard;
 pub use types::WorkflowMode;
\ No newline at end of file
@@ -0,0 +1,89 @@
+//! Transactional outbox：引擎每一步的写入（状态记录、execution 游标、历史事件、定时器）
+//! 通过 `commit_step` 在一个事务中提交；需要对外发布的内容作为 outbox 消息写入同一事务，提交后再投递。
+//!
+//! * `engine_event`：payload 为 `EngineEvent`，交给 `EngineEventDispatcher`（hooks / 事件总线）
+//! * 其余 topic 为状态类型（如 `task`），交给对应 handler 的 `StateHandler::publish`（如入队任务）
+//! * 投递成功后标记 `published_at`；提交后、投递前进程崩溃的消息由 [`relay_unpublished`] 补投，
+//!   因此投递语义为至少一次
+
+use chrono::{Duration, Utc};
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_hook::EngineEventDispatcher;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::outbox::StoredOutboxMessage;
+use tracing::warn;
+
+use crate::handler::registry::StateHandlerRegistry;
+
+/// 引擎事件消息的 topic
+pub const ENGINE_EVENT_TOPIC: &str = "engine_event";
+
+/// 把引擎事件包装为 outbox 消息
+pub fn engine_event_message(run_id: &str, event: &EngineEvent) -> Result<StoredOutboxMessage, String> {
+    Ok(StoredOutboxMessage {
+        id: 0,
+        run_id: run_id.to_string(),
+        topic: ENGINE_EVENT_TOPIC.to_string(),
+        payload: serde_json::to_value(event).map_err(|e| e.to_string())?,
+        created_at: Utc::now().naive_utc(),
+        published_at: None,
+    })
+}
+
+/// 投递一条 outbox 消息（不修改发布标记）
+pub async fn publish_message(
+    dispatcher: &EngineEventDispatcher,
+    registry: &StateHandlerRegistry,
+    message: &StoredOutboxMessage,
+) -> Result<(), String> {
+    match message.topic.as_str() {
+        ENGINE_EVENT_TOPIC => {
+            let event: EngineEvent = serde_json::from_value(message.payload.clone())
+                .map_err(|e| format!("Invalid engine event in outbox #{}: {e}", message.id))?;
+            dispatcher.dispatch(event).await;
+            Ok(())
+        }
+        topic => registry
+            .get(topic)
+            .ok_or_else(|| format!("No handler registered for outbox topic: {topic}"))?
+            .publish(&message.payload)
+            .await,
+    }
+}
+
+/// 投递并标记为已发布
+pub async fn publish_and_mark(
+    persistence: &DynPM,
+    dispatcher: &EngineEventDispatcher,
+    registry: &StateHandlerRegistry,
+    message: &StoredOutboxMessage,
+) -> Result<(), String> {
+    publish_message(dispatcher, registry, message).await?;
+    persistence
+        .mark_outbox_published(message.id, Utc::now().naive_utc())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 补投创建时间早于 `min_age` 之前的未发布消息，返回成功投递的条数
+pub async fn relay_unpublished(
+    persistence: &DynPM,
+    dispatcher: &EngineEventDispatcher,
+    registry: &StateHandlerRegistry,
+    min_age: Duration,
+    limit: i64,
+) -> Result<usize, String> {
+    let pending = persistence
+        .find_unpublished_outbox(Utc::now().naive_utc() - min_age, limit)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut published = 0;
+    for message in &pending {
+        match publish_and_mark(persistence, dispatcher, registry, message).await {
+            Ok(()) => published += 1,
+            Err(e) => warn!("outbox #{} ({}) relay failed: {e}", message.id, message.topic),
+        }
+    }
+    Ok(published)
+}
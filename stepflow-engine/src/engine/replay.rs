@@ -0,0 +1,213 @@
+//! 历史回放：仅凭事件历史重建引擎的 context 与游标。
+//!
+//! * 按 event_id 顺序折叠 `EngineEvent`：NodeEnter 定位游标，NodeExit 写回 context 并转移，
+//!   NodeDispatched / TimerScheduled / SignalAwaited 表示挂起等待信号，
+//!   WorkflowFinished / WorkflowFailed / WorkflowCancelled / WorkflowTerminated 结束执行
+//!   （取消时 NodeExit 以 `cancelled` 状态转到 `onCancel` 清理状态）
+//! * 同时用当前 DSL 复核每一步：状态是否存在、类型是否一致、转移是否仍由 DSL 产生
+//!   （Choice 按进入时的输入重新求值）。模板在执行中途被修改时返回 [`ReplayError::NonDeterministic`]
+
+use serde_json::Value;
+use stepflow_dsl::{State, WorkflowDSL};
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_storage::entities::workflow_event::StoredWorkflowEvent;
+use thiserror::Error;
+
+use crate::command::{step_once, Command};
+
+use super::{history::HistoryCursor, types::WorkflowMode};
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("history event #{event_id} is malformed: {reason}")]
+    Malformed { event_id: i64, reason: String },
+    #[error("non-deterministic history at event #{event_id}: {reason}")]
+    NonDeterministic { event_id: i64, reason: String },
+}
+
+/// 回放得到的引擎状态
+#[derive(Debug, Clone)]
+pub struct ReplayedExecution {
+    pub context: Value,
+    pub current_state: String,
+    pub last_task_state: Option<String>,
+    pub finished: bool,
+    pub awaiting_signal: bool,
+    pub cursor: HistoryCursor,
+}
+
+pub struct Replayer<'a> {
+    dsl: &'a WorkflowDSL,
+    mode: WorkflowMode,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(dsl: &'a WorkflowDSL, mode: WorkflowMode) -> Self {
+        Self { dsl, mode }
+    }
+
+    pub fn replay(&self, events: &[StoredWorkflowEvent]) -> Result<ReplayedExecution, ReplayError> {
+        let mut out = ReplayedExecution {
+            context: Value::Object(Default::default()),
+            current_state: self.dsl.start_at.clone(),
+            last_task_state: None,
+            finished: false,
+            awaiting_signal: false,
+            cursor: HistoryCursor::default(),
+        };
+        // 当前状态进入时的输入（用于 Choice 重新求值）
+        let mut entered_input = Value::Null;
+
+        for stored in events {
+            let event_id = stored.event_id;
+            let nondeterministic = |reason: String| ReplayError::NonDeterministic { event_id, reason };
+
+            if event_id <= out.cursor.last_event_id {
+                return Err(ReplayError::Malformed {
+                    event_id,
+                    reason: format!("event ids must increase (previous #{})", out.cursor.last_event_id),
+                });
+            }
+            let event: EngineEvent = stored
+                .attributes
+                .as_deref()
+                .ok_or_else(|| "missing attributes".to_string())
+                .and_then(|a| serde_json::from_str(a).map_err(|e| e.to_string()))
+                .map_err(|reason| ReplayError::Malformed { event_id, reason })?;
+
+            // 状态级事件：状态必须仍存在且类型一致
+            if let Some(name) = event.state_name() {
+                let state = self
+                    .dsl
+                    .states
+                    .get(name)
+                    .ok_or_else(|| nondeterministic(format!("state '{name}' no longer exists")))?;
+                if let Some(recorded) = stored.state_type.as_deref() {
+                    if recorded != state.variant_name() {
+                        return Err(nondeterministic(format!(
+                            "state '{name}' changed type from {recorded} to {}",
+                            state.variant_name()
+                        )));
+                    }
+                }
+            }
+
+            match &event {
+                EngineEvent::WorkflowStarted { input, .. } => {
+                    out.context = input.clone();
+                    out.current_state = self.dsl.start_at.clone();
+                }
+                EngineEvent::NodeEnter { state_name, input, .. } => {
+                    if *state_name != out.current_state {
+                        return Err(nondeterministic(format!(
+                            "expected to enter '{}', history entered '{state_name}'",
+                            out.current_state
+                        )));
+                    }
+                    out.context = input.clone();
+                    out.awaiting_signal = false;
+                    entered_input = input.clone();
+                }
+                EngineEvent::NodeDispatched { state_name, .. } => {
+                    out.awaiting_signal = true;
+                    out.last_task_state = Some(state_name.clone());
+                }
+                EngineEvent::TimerScheduled { .. } | EngineEvent::SignalAwaited { .. } => {
+                    out.awaiting_signal = true
+                }
+                EngineEvent::NodeRetrying { state_name, .. } => {
+                    // Deferred / 回调 Task 的重试已重新入队 / 签发，Inline 重试在进程内完成
+                    out.awaiting_signal = match self.dsl.states.get(state_name) {
+                        Some(State::Task(task)) => {
+                            self.mode == WorkflowMode::Deferred || task.waits_for_task_token()
+                        }
+                        _ => false,
+                    };
+                }
+                EngineEvent::NodeExit {
+                    state_name,
+                    status,
+                    context,
+                    next_state,
+                    ..
+                } => {
+                    if *state_name != out.current_state {
+                        return Err(nondeterministic(format!(
+                            "exit of '{state_name}' while at '{}'",
+                            out.current_state
+                        )));
+                    }
+                    let expected = self
+                        .expected_transitions(state_name, status, &entered_input)
+                        .map_err(&nondeterministic)?;
+                    if !expected.contains(next_state) {
+                        return Err(nondeterministic(format!(
+                            "'{state_name}' transitioned to {next_state:?}, template now allows {expected:?}"
+                        )));
+                    }
+
+                    if let Some(ctx) = context {
+                        out.context = ctx.clone();
+                    }
+                    out.awaiting_signal = false;
+                    match next_state {
+                        Some(next) => {
+                            if matches!(self.dsl.states.get(state_name), Some(State::Task(_))) {
+                                out.last_task_state = Some(state_name.clone());
+                            }
+                            out.current_state = next.clone();
+                        }
+                        None => out.finished = true,
+                    }
+                }
+                EngineEvent::WorkflowFinished { result, .. } => {
+                    out.context = result.clone();
+                    out.finished = true;
+                }
+                EngineEvent::NodeCancelled { .. } => out.awaiting_signal = false,
+                EngineEvent::WorkflowFailed { .. }
+                | EngineEvent::WorkflowCancelled { .. }
+                | EngineEvent::WorkflowTerminated { .. } => {
+                    out.awaiting_signal = false;
+                    out.finished = true;
+                }
+                _ => {}
+            }
+
+            out.cursor.advance(event_id, &event);
+        }
+
+        Ok(out)
+    }
+
+    /// 当前 DSL 下离开 `state_name` 允许的转移目标
+    fn expected_transitions(
+        &self,
+        state_name: &str,
+        status: &str,
+        entered_input: &Value,
+    ) -> Result<Vec<Option<String>>, String> {
+        let (state, base) = self.dsl.get_state_and_base(state_name);
+
+        if status == "cancelled" {
+            return Ok(vec![self.dsl.on_cancel.clone(), None]);
+        }
+        if status == "caught" {
+            return Ok(base
+                .catch
+                .iter()
+                .flatten()
+                .map(|c| Some(c.next.clone()))
+                .collect());
+        }
+
+        match state {
+            State::Choice(_) => match step_once(self.dsl, state_name, entered_input)? {
+                Command::Choice { next_state, .. } => Ok(vec![Some(next_state)]),
+                other => Err(format!("unexpected command {:?} for choice '{state_name}'", other.kind())),
+            },
+            State::Succeed(_) => Ok(vec![None]),
+            _ => Ok(vec![base.next.clone()]),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Retry：把失败归类为 `StepError.error_type`，按状态声明的 `RetryPolicy` 安排下一次尝试。
+//!
+//! * 第 n 次重试的等待时间：`interval_seconds * backoff_rate^(n-1)`
+//! * 未声明的字段取默认值：interval 1s / backoff 2.0 / max_attempts 3
+//! * 等待时间不超过 `MAX_RETRY_DELAY_SECONDS`（指数退避溢出时同样取该上限）
+//! * Inline：引擎 sleep 后原地重跑；Deferred Task：带 `next_retry_at` 重新入队；
+//!   Deferred 扇出（Parallel / Map / SubWorkflow）：挂起在重试定时器上，`TimerFired` 后启动新一轮分支
+//! * 每次重试写入 state 记录（status = RETRYING, attempts = n）并发出 `NodeRetrying`
+
+use std::{sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use stepflow_dsl::State;
+use stepflow_dto::dto::{engine_event::EngineEvent, error_policy::RetryPolicy};
+use stepflow_exception::{
+    match_retry, ErrorOrigin, StepError, STATES_MAPPING_ERROR, STATES_RUNTIME, STATES_TASK_FAILED,
+};
+use stepflow_storage::entities::{
+    workflow_execution::UpdateStoredWorkflowExecution, workflow_state::UpdateStoredWorkflowState,
+};
+
+use crate::handler::execution_scope::{StateExecutionScope, StepWrites};
+use crate::mapping::{is_mapping_error, MappingPipeline};
+
+use super::{core::WorkflowEngine, types::StepOutcome};
+
+const DEFAULT_INTERVAL_SECONDS: u32 = 1;
+const DEFAULT_BACKOFF_RATE: f64 = 2.0;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// 单次重试等待的上限（一年）
+const MAX_RETRY_DELAY_SECONDS: f64 = 31_622_400.0;
+
+/// 一次已安排的重试
+#[derive(Debug, Clone)]
+pub struct RetryDecision {
+    /// 第几次重试（从 1 开始）
+    pub attempt: u32,
+    pub delay: Duration,
+    pub retry_at: DateTime<Utc>,
+}
+
+/// 把引擎 / handler 返回的字符串错误归类为 StepError
+pub fn classify_error(state: &State, message: &str) -> StepError {
+    let (error_type, origin) = match state {
+        _ if is_mapping_error(message) => (STATES_MAPPING_ERROR, ErrorOrigin::Mapping),
+        State::Task(_) => (STATES_TASK_FAILED, ErrorOrigin::Tool),
+        State::Choice(_) if message.starts_with("No matching choice") => {
+            ("ChoiceNoMatch", ErrorOrigin::Engine)
+        }
+        _ => (STATES_RUNTIME, ErrorOrigin::Engine),
+    };
+
+    StepError {
+        error_type: error_type.to_string(),
+        message: message.to_string(),
+        origin,
+    }
+}
+
+/// 已重试 `attempts` 次后，按匹配的策略计算下一次重试；无匹配策略或次数耗尽返回 None
+pub fn next_retry(
+    error_type: &str,
+    policies: Option<&[RetryPolicy]>,
+    attempts: u32,
+) -> Option<RetryDecision> {
+    let policy = match_retry(error_type, policies)?;
+    if attempts >= policy.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS) {
+        return None;
+    }
+
+    let interval = policy.interval_seconds.unwrap_or(DEFAULT_INTERVAL_SECONDS) as f64;
+    let backoff = policy.backoff_rate.unwrap_or(DEFAULT_BACKOFF_RATE);
+    let exponent = i32::try_from(attempts).unwrap_or(i32::MAX);
+    // interval 为 0 时 0 × ∞ 得 NaN：视为不等待
+    let secs = match interval * backoff.powi(exponent) {
+        s if s.is_nan() => 0.0,
+        s => s.clamp(0.0, MAX_RETRY_DELAY_SECONDS),
+    };
+    let delay = Duration::try_from_secs_f64(secs)
+        .unwrap_or(Duration::from_secs(MAX_RETRY_DELAY_SECONDS as u64));
+    let retry_at = Utc::now()
+        + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero());
+
+    Some(RetryDecision {
+        attempt: attempts + 1,
+        delay,
+        retry_at,
+    })
+}
+
+/// 队列任务上的 `max_attempts`：所有策略中最大的重试次数（未声明 Retry 为 0）
+pub fn max_retry_attempts(policies: Option<&[RetryPolicy]>) -> u32 {
+    policies
+        .unwrap_or_default()
+        .iter()
+        .map(|p| p.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS))
+        .max()
+        .unwrap_or(0)
+}
+
+impl WorkflowEngine {
+    /// 当前状态已重试的次数
+    async fn current_attempts(&self) -> Result<u32, String> {
+        let state_id = format!("{}:{}", self.run_id, self.current_state);
+
+        // 本步骤尚未提交的记录优先（Inline 原地重试时 state 记录还在缓冲中）
+        let buffered = self
+            .pending
+            .states
+            .iter()
+            .rev()
+            .find(|(id, changes)| *id == state_id && changes.attempts.is_some())
+            .and_then(|(_, changes)| changes.attempts);
+        let attempts = match buffered {
+            Some(attempts) => attempts,
+            None => self
+                .persistence
+                .get_state(&state_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|row| row.attempts)
+                .unwrap_or(0),
+        };
+        Ok(attempts.max(0) as u32)
+    }
+
+    /// 当前状态失败后按 Retry 策略安排下一次尝试（只做记录，不负责执行）
+    pub(crate) async fn schedule_retry(
+        &mut self,
+        error: &StepError,
+    ) -> Result<Option<RetryDecision>, String> {
+        let (_, base) = self.dsl.get_state_and_base(&self.current_state);
+        let state_id = format!("{}:{}", self.run_id, self.current_state);
+        let attempts = self.current_attempts().await?;
+
+        let Some(decision) = next_retry(&error.error_type, base.retry.as_deref(), attempts) else {
+            return Ok(None);
+        };
+
+        self.pending.states.push((
+            state_id,
+            UpdateStoredWorkflowState {
+                status: Some("RETRYING".into()),
+                error: Some(Some(error.message.clone())),
+                error_details: Some(Some(error.error_type.clone())),
+                attempts: Some(decision.attempt as i64),
+                ..Default::default()
+            },
+        ));
+
+        self.dispatch_event(EngineEvent::NodeRetrying {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            attempt: decision.attempt,
+            error_type: error.error_type.clone(),
+            error: error.message.clone(),
+            next_retry_at: decision.retry_at.to_rfc3339(),
+        })
+        .await?;
+
+        Ok(Some(decision))
+    }
+
+    /// Deferred Task 重试：带 `next_retry_at` 重新入队 / 回调 Task 重新签发 token（随步骤提交），
+    /// 引擎继续挂起在该 Task
+    pub(crate) async fn redispatch_task(&mut self, decision: &RetryDecision) -> Result<(), String> {
+        let writes = Mutex::new(StepWrites::default());
+        let exec_in = MappingPipeline::for_state(self.state_def())
+            .with_writes(&writes)
+            .apply_input(&self.context);
+        self.record_mapping_trace(&writes).await?;
+        let exec_in = exec_in?;
+
+        let state = self.state_def();
+        let state_type = state.variant_name();
+
+        let handler = self
+            .state_handler_registry
+            .get(state_type)
+            .ok_or_else(|| format!("No handler registered for state type: {state_type}"))?;
+
+        let scope = StateExecutionScope::new(
+            &self.run_id,
+            &self.current_state,
+            state_type,
+            self.mode,
+            None,
+            &self.persistence,
+            state,
+        )
+        .with_retry(decision.attempt, decision.retry_at)
+        .with_timer_shard(self.timer_shard())
+        .with_writes(&writes);
+
+        let result = handler.handle(&scope, &exec_in).await?;
+        self.absorb_writes(writes);
+        // 回调 Task 重试签发了新 token
+        self.expose_task_token(result.metadata.as_ref());
+        Ok(())
+    }
+
+    /// Deferred 扇出重试：创建重试定时器并挂起，到期前不启动新一轮分支
+    pub(crate) async fn defer_fanout_retry(&mut self, decision: &RetryDecision) -> Result<StepOutcome, String> {
+        self.push_timer(Some(self.current_state.clone()), decision.retry_at, None);
+        self.awaiting_signal = true;
+        self.dispatch_event(EngineEvent::TimerScheduled {
+            run_id: self.run_id.clone(),
+            state_name: self.current_state.clone(),
+            timestamp: decision.retry_at.to_rfc3339(),
+        })
+        .await?;
+        self.save_execution(UpdateStoredWorkflowExecution {
+            current_state_name: Some(Some(self.current_state.clone())),
+            context_snapshot: Some(Some(self.context.clone())),
+            ..Default::default()
+        })
+        .await?;
+
+        Ok(StepOutcome {
+            should_continue: true,
+            updated_context: self.context.clone(),
+            suspended: true,
+        })
+    }
+
+    /// 挂起的扇出失败（超时）：按 Retry 策略延后启动新一轮分支，重试用尽后交给 Catch
+    pub(crate) async fn retry_or_fail_fanout(&mut self, error: StepError) -> Result<(), String> {
+        let Some(decision) = self.schedule_retry(&error).await? else {
+            return self.handle_state_failure(error).await.map(|_| ());
+        };
+        self.defer_fanout_retry(&decision).await.map(|_| ())
+    }
+
+    /// 扇出的重试定时器到期（`TimerFired`）：下一次推进时启动新一轮分支，
+    /// 新尝试的超时定时器随该步骤提交
+    pub(crate) async fn resume_fanout_retry(&mut self) -> Result<(), String> {
+        let attempts = self.current_attempts().await?;
+        self.awaiting_signal = false;
+        self.schedule_state_timeout(attempts);
+        Ok(())
+    }
+
+    /// 挂起的 Task 失败（外部回报失败 / 超时）：按 Retry 策略重新派发并为新的尝试创建超时定时器，
+    /// 重试用尽后交给 Catch
+    pub(crate) async fn retry_or_fail_task(&mut self, error: StepError) -> Result<(), String> {
+        let Some(decision) = self.schedule_retry(&error).await? else {
+            // 命中 Catch 则游标转到恢复状态，否则执行失败
+            return self.handle_state_failure(error).await.map(|_| ());
+        };
+
+        self.redispatch_task(&decision).await?;
+        self.schedule_state_timeout(decision.attempt);
+        // 回调 Task 重新签发的 token 已写入 context，随本步提交
+        if matches!(self.state_def(), State::Task(task) if task.waits_for_task_token()) {
+            self.save_execution(UpdateStoredWorkflowExecution {
+                context_snapshot: Some(Some(self.context.clone())),
+                ..Default::default()
+            })
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(errors: &[&str], interval: u32, backoff: f64, max: u32) -> RetryPolicy {
+        RetryPolicy {
+            error_equals: errors.iter().map(|e| e.to_string()).collect(),
+            interval_seconds: Some(interval),
+            backoff_rate: Some(backoff),
+            max_attempts: Some(max),
+        }
+    }
+
+    #[test]
+    fn test_next_retry_backoff_and_exhaustion() {
+        let policies = vec![policy(&[STATES_TASK_FAILED], 2, 3.0, 2)];
+
+        let first = next_retry(STATES_TASK_FAILED, Some(&policies), 0).unwrap();
+        assert_eq!(first.attempt, 1);
+        assert_eq!(first.delay, Duration::from_secs(2));
+
+        let second = next_retry(STATES_TASK_FAILED, Some(&policies), 1).unwrap();
+        assert_eq!(second.attempt, 2);
+        assert_eq!(second.delay, Duration::from_secs(6));
+
+        assert!(next_retry(STATES_TASK_FAILED, Some(&policies), 2).is_none());
+        assert!(next_retry(STATES_RUNTIME, Some(&policies), 0).is_none());
+        assert!(next_retry(STATES_TASK_FAILED, None, 0).is_none());
+    }
+
+    #[test]
+    fn test_next_retry_delay_is_capped() {
+        let cap = Duration::from_secs(MAX_RETRY_DELAY_SECONDS as u64);
+
+        // 指数退避溢出为 ∞、间隔本身超过上限：都取上限而不是 panic
+        let policies = vec![policy(&[STATES_TASK_FAILED], 1, 10.0, u32::MAX)];
+        assert_eq!(next_retry(STATES_TASK_FAILED, Some(&policies), 400).unwrap().delay, cap);
+        assert_eq!(next_retry(STATES_TASK_FAILED, Some(&policies), u32::MAX - 1).unwrap().delay, cap);
+
+        let policies = vec![policy(&[STATES_TASK_FAILED], u32::MAX, f64::MAX, 3)];
+        assert_eq!(next_retry(STATES_TASK_FAILED, Some(&policies), 2).unwrap().delay, cap);
+
+        let policies = vec![policy(&[STATES_TASK_FAILED], 0, 10.0, u32::MAX)];
+        assert_eq!(next_retry(STATES_TASK_FAILED, Some(&policies), 400).unwrap().delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_max_retry_attempts() {
+        let policies = vec![policy(&["A"], 1, 1.0, 5), policy(&["*"], 1, 1.0, 2)];
+        assert_eq!(max_retry_attempts(Some(&policies)), 5);
+        assert_eq!(max_retry_attempts(None), 0);
+    }
+}
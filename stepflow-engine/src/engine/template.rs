@@ -0,0 +1,181 @@
+//! 模板修订版本：执行开始时把模板引用解析为一个已发布的修订，修订号记录在 execution 上
+//! （`template_revision`）；恢复执行与恢复 SubWorkflow 子执行时按记录的修订加载 DSL，
+//! 之后发布的修订不影响已开始的执行。
+//!
+//! * 引用方式：修订号、别名（如 `stable`）或 `latest`（最新发布的修订，未指定时的默认值）
+//! * 只有已发布的修订可以运行；草稿只能按修订号查看 / 对比
+//! * 加载得到的 `WorkflowDSL.version` 为修订号
+
+use std::{collections::BTreeSet, fmt};
+
+use serde_json::Value;
+use stepflow_dsl::WorkflowDSL;
+use stepflow_dto::dto::template::DslChange;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::workflow_template_revision::{StoredTemplateRevision, REVISION_PUBLISHED};
+
+/// 指向最新发布修订的保留别名
+pub const LATEST: &str = "latest";
+
+/// 对模板某个修订的引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevisionSelector {
+    Latest,
+    Number(i64),
+    Alias(String),
+}
+
+impl RevisionSelector {
+    /// 解析路径 / 查询参数中的引用：数字为修订号，`latest` 为最新发布的修订，其余为别名
+    pub fn parse(raw: &str) -> Self {
+        match raw.parse::<i64>() {
+            Ok(revision) => Self::Number(revision),
+            Err(_) if raw == LATEST => Self::Latest,
+            Err(_) => Self::Alias(raw.to_string()),
+        }
+    }
+
+    /// 由修订号 / 别名二选一的请求字段构造；都未给出时为 `Latest`
+    pub fn from_parts(revision: Option<i64>, alias: Option<&str>) -> Self {
+        match (revision, alias) {
+            (Some(revision), _) => Self::Number(revision),
+            (None, Some(alias)) => Self::parse(alias),
+            (None, None) => Self::Latest,
+        }
+    }
+}
+
+impl fmt::Display for RevisionSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Latest => f.write_str(LATEST),
+            Self::Number(revision) => write!(f, "revision {revision}"),
+            Self::Alias(alias) => write!(f, "alias '{alias}'"),
+        }
+    }
+}
+
+/// 查找引用的修订（不要求已发布）；模板、修订或别名不存在时返回 None
+pub async fn find_revision(
+    persistence: &DynPM,
+    template_id: &str,
+    selector: &RevisionSelector,
+) -> Result<Option<StoredTemplateRevision>, String> {
+    let revision = match selector {
+        RevisionSelector::Number(revision) => *revision,
+        RevisionSelector::Alias(alias) => {
+            match persistence
+                .get_template_alias(template_id, alias)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                Some(alias) => alias.revision,
+                None => return Ok(None),
+            }
+        }
+        RevisionSelector::Latest => {
+            let revisions = persistence
+                .find_template_revisions(template_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            return Ok(revisions
+                .into_iter()
+                .filter(|rev| rev.status == REVISION_PUBLISHED)
+                .max_by_key(|rev| rev.revision));
+        }
+    };
+    persistence
+        .get_template_revision(template_id, revision)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 解析可运行的修订：必须存在且已发布
+pub async fn runnable_revision(
+    persistence: &DynPM,
+    template_id: &str,
+    selector: &RevisionSelector,
+) -> Result<StoredTemplateRevision, String> {
+    let revision = find_revision(persistence, template_id, selector)
+        .await?
+        .ok_or_else(|| match selector {
+            RevisionSelector::Latest => format!("Template {template_id} has no published revision"),
+            other => format!("Template {template_id} has no {other}"),
+        })?;
+    if revision.status != REVISION_PUBLISHED {
+        return Err(format!(
+            "Template {template_id} revision {} is a draft and cannot be run",
+            revision.revision
+        ));
+    }
+    Ok(revision)
+}
+
+/// 修订的 DSL，`version` 设为修订号
+pub fn revision_dsl(revision: &StoredTemplateRevision) -> Result<WorkflowDSL, String> {
+    let mut dsl: WorkflowDSL = serde_json::from_str(&revision.dsl_definition).map_err(|e| {
+        format!(
+            "Template {} revision {} has an invalid DSL: {e}",
+            revision.template_id, revision.revision
+        )
+    })?;
+    dsl.version = Some(revision.revision.to_string());
+    Ok(dsl)
+}
+
+/// 按 execution 记录的修订加载 DSL
+pub async fn pinned_dsl(persistence: &DynPM, template_id: &str, revision: i64) -> Result<WorkflowDSL, String> {
+    let revision = persistence
+        .get_template_revision(template_id, revision)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Template {template_id} revision {revision} not found"))?;
+    revision_dsl(&revision)
+}
+
+/// 由模板修订加载的 DSL 对应的修订号
+pub fn pinned_revision(dsl: &WorkflowDSL) -> Option<i64> {
+    dsl.version.as_deref().and_then(|v| v.parse().ok())
+}
+
+/// 对比两个修订的 DSL：对象逐键、数组逐项递归，其余值整体比较；路径为 JSON Pointer
+pub fn diff_dsl(before: &Value, after: &Value) -> Vec<DslChange> {
+    let mut changes = Vec::new();
+    diff_at(String::new(), Some(before), Some(after), &mut changes);
+    changes
+}
+
+fn diff_at(path: String, before: Option<&Value>, after: Option<&Value>, changes: &mut Vec<DslChange>) {
+    match (before, after) {
+        (Some(Value::Object(b)), Some(Value::Object(a))) => {
+            let keys: BTreeSet<&String> = b.keys().chain(a.keys()).collect();
+            for key in keys {
+                diff_at(format!("{path}/{}", escape_pointer(key)), b.get(key), a.get(key), changes);
+            }
+        }
+        (Some(Value::Array(b)), Some(Value::Array(a))) => {
+            for index in 0..b.len().max(a.len()) {
+                diff_at(format!("{path}/{index}"), b.get(index), a.get(index), changes);
+            }
+        }
+        (b, a) if b == a => {}
+        (b, a) => {
+            let kind = match (b, a) {
+                (None, _) => "added",
+                (_, None) => "removed",
+                _ => "changed",
+            };
+            changes.push(DslChange {
+                path: if path.is_empty() { "/".into() } else { path },
+                kind: kind.into(),
+                before: b.cloned(),
+                after: a.cloned(),
+            });
+        }
+    }
+}
+
+/// JSON Pointer 转义（RFC 6901）
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,267 @@
+//! 超时（`timeoutSeconds`）：执行级与状态级（Task / Parallel / Map / SubWorkflow）。
+//!
+//! * 同步执行的状态（Inline Task、Inline 扇出）由引擎以 `tokio::time::timeout` 限时；
+//!   Inline 执行整体以 `started_at + timeoutSeconds` 为截止时间
+//! * 挂起等待的状态（Deferred / 回调 Task、Deferred 扇出）与 Deferred 执行由超时定时器限时：
+//!   定时器 payload 为 [`TimerPayload::Timeout`]，到期后调度器发送 `TimeoutFired` 信号
+//! * 状态超时以 `States.Timeout` 失败，参与 Retry / Catch；执行级超时直接结束执行
+//! * 未被捕获的超时把执行记为 TIMED_OUT
+//! * 状态定时器记录所属的状态访问（触发进入该状态的事件 id）与尝试次数，
+//!   状态已完成 / 已重试后到期的定时器直接忽略，无需在完成时撤销
+
+use std::{future::Future, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use stepflow_dsl::State;
+use stepflow_dto::dto::{execution::TIMED_OUT, timer::TimerPayload};
+use stepflow_exception::{ErrorOrigin, StepError, STATES_TIMEOUT};
+use stepflow_storage::entities::{activity_task::UpdateStoredActivityTask, timer::StoredTimer};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::handler::task::TASK_TOKEN_KEY;
+
+use super::{core::WorkflowEngine, types::WorkflowMode};
+
+/// 定时器所属分片：对 run_id 做稳定哈希（FNV-1a）后按分片数取模，
+/// 各进程 / 各版本计算结果一致，调度器按 `TIMER_SHARDS` 只认领自己负责的分片
+pub fn timer_shard(run_id: &str, shard_count: u32) -> i64 {
+    let hash = run_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3));
+    (hash % u64::from(shard_count.max(1))) as i64
+}
+
+/// `States.Timeout` 错误
+pub(crate) fn timeout_error(scope: &str, seconds: u64) -> StepError {
+    StepError {
+        error_type: STATES_TIMEOUT.to_string(),
+        message: format!("{scope} timed out after {seconds}s"),
+        origin: ErrorOrigin::Engine,
+    }
+}
+
+/// 限时运行；未设置超时时原样等待，超时返回 None（future 被丢弃）
+pub(crate) async fn within<F: Future>(seconds: Option<u64>, fut: F) -> Option<F::Output> {
+    match seconds {
+        Some(seconds) => tokio::time::timeout(Duration::from_secs(seconds), fut).await.ok(),
+        None => Some(fut.await),
+    }
+}
+
+impl WorkflowEngine {
+    /// 当前状态的超时错误
+    pub(crate) fn state_timeout_error(&self) -> StepError {
+        let seconds = self.state_def().timeout_seconds().unwrap_or_default();
+        timeout_error(&format!("State '{}'", self.current_state), seconds)
+    }
+
+    /// 当前状态在引擎内同步执行时的超时（挂起等待的状态改由定时器限时）
+    pub(crate) fn inline_state_timeout(&self) -> Option<u64> {
+        let synchronous = match self.state_def() {
+            State::Task(_) => !self.deferred_task(),
+            State::Parallel(_) | State::Map(_) | State::SubWorkflow(_) => {
+                self.mode == WorkflowMode::Inline
+            }
+            _ => false,
+        };
+        self.state_def().timeout_seconds().filter(|_| synchronous)
+    }
+
+    /// 当前状态访问的标识：触发进入该状态的事件（NodeExit，首个状态为 WorkflowStarted）
+    pub(crate) fn visit_event_id(&self) -> i64 {
+        self.history
+            .exited_event_id
+            .or(self.history.started_event_id)
+            .unwrap_or_default()
+    }
+
+    /// 当前尝试的标识：本次访问的转移事件，Retry 后为最近的 NodeRetrying（扇出按此区分各轮分支）
+    pub(crate) fn attempt_event_id(&self) -> i64 {
+        self.history
+            .retried_event_id
+            .unwrap_or_default()
+            .max(self.visit_event_id())
+    }
+
+    /// 尚未为当前状态访问写入 NodeEnter（扇出状态每次推进都会重新进入）
+    pub(crate) fn entering_new_visit(&self) -> bool {
+        self.history.entered_event_id.unwrap_or_default() < self.visit_event_id()
+    }
+
+    /// 状态挂起等待：为本次尝试创建超时定时器（随步骤提交）
+    pub(crate) fn schedule_state_timeout(&mut self, attempt: u32) {
+        let Some(seconds) = self.state_def().timeout_seconds() else {
+            return;
+        };
+        let payload = TimerPayload::Timeout {
+            visit_event_id: Some(self.visit_event_id()),
+            attempt: Some(attempt as i64),
+        };
+        let fire_at = Utc::now() + chrono::Duration::seconds(seconds as i64);
+        self.push_timer(Some(self.current_state.clone()), fire_at, Some(payload));
+    }
+
+    /// Deferred 执行开始：创建执行级超时定时器
+    pub(crate) fn schedule_workflow_timeout(&mut self) {
+        if let Some(deadline) = self.execution_deadline() {
+            let payload = TimerPayload::Timeout {
+                visit_event_id: None,
+                attempt: None,
+            };
+            self.push_timer(None, deadline, Some(payload));
+        }
+    }
+
+    /// 创建定时器（随步骤提交）；payload 为空的定时器到期后发送 `TimerFired`
+    pub(crate) fn push_timer(&mut self, state_name: Option<String>, fire_at: DateTime<Utc>, payload: Option<TimerPayload>) {
+        let now = Utc::now().naive_utc();
+        self.pending.timers.push(StoredTimer {
+            timer_id: Uuid::new_v4().to_string(),
+            run_id: self.run_id.clone(),
+            shard_id: self.timer_shard(),
+            fire_at: fire_at.naive_utc(),
+            status: "pending".to_string(),
+            version: 1,
+            state_name,
+            payload: payload.and_then(|p| serde_json::to_value(p).ok()),
+            created_at: now,
+            updated_at: now,
+        });
+    }
+
+    /// 执行级截止时间
+    pub(crate) fn execution_deadline(&self) -> Option<DateTime<Utc>> {
+        self.dsl
+            .timeout_seconds
+            .map(|seconds| self.started_at + chrono::Duration::seconds(seconds as i64))
+    }
+
+    /// Inline 执行：超过截止时间后放弃当前步骤，按最新提交的记录把执行记为 TIMED_OUT
+    pub(crate) async fn run_steps_until(&mut self, deadline: DateTime<Utc>) -> Result<Value, String> {
+        let remaining = (deadline - Utc::now()).to_std().unwrap_or_default();
+        if let Ok(result) = tokio::time::timeout(remaining, self.run_steps()).await {
+            return result;
+        }
+
+        self.reload().await?;
+        if self.finished {
+            return Ok(self.context.clone());
+        }
+        let error = timeout_error("Execution", self.dsl.timeout_seconds.unwrap_or_default());
+        self.fail_execution(error).await.map(|_| self.context.clone())
+    }
+
+    /// 超时定时器到期（`TimeoutFired`）；过期的定时器忽略
+    pub(crate) async fn on_timeout_fired(
+        &mut self,
+        state_name: Option<String>,
+        visit_event_id: Option<i64>,
+        attempt: Option<i64>,
+    ) -> Result<(), String> {
+        if self.finished {
+            debug!("[{}] timeout fired after execution closed, ignored", self.run_id);
+            return Ok(());
+        }
+
+        let result = match state_name {
+            None => {
+                info!("[{}] execution timed out @ {}", self.run_id, self.current_state);
+                self.revoke_current_task().await?;
+                let error = timeout_error("Execution", self.dsl.timeout_seconds.unwrap_or_default());
+                self.fail_execution(error).await.map(|_| ())
+            }
+            Some(state_name) => {
+                if !self.is_current_attempt(&state_name, visit_event_id, attempt).await? {
+                    debug!("[{}] stale timeout for '{state_name}', ignored", self.run_id);
+                    return Ok(());
+                }
+                info!("[{}] state '{state_name}' timed out", self.run_id);
+                self.time_out_current_state().await
+            }
+        };
+
+        // 超时结束了执行：TIMED_OUT 已记录，父执行据 last_error 读取错误
+        match result {
+            Err(e) if !self.finished => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// 定时器是否属于当前挂起的状态访问与尝试
+    async fn is_current_attempt(
+        &self,
+        state_name: &str,
+        visit_event_id: Option<i64>,
+        attempt: Option<i64>,
+    ) -> Result<bool, String> {
+        if state_name != self.current_state || visit_event_id != Some(self.visit_event_id()) {
+            return Ok(false);
+        }
+        let suspended = match self.state_def() {
+            State::Task(_) => self.awaiting_signal,
+            State::Parallel(_) | State::Map(_) | State::SubWorkflow(_) => true,
+            _ => false,
+        };
+        if !suspended {
+            return Ok(false);
+        }
+
+        let attempts = self
+            .persistence
+            .get_state(&format!("{}:{}", self.run_id, self.current_state))
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|row| row.attempts)
+            .unwrap_or_default();
+        Ok(attempt.unwrap_or_default() == attempts)
+    }
+
+    /// 挂起的状态超时：Task 撤销已入队的任务、扇出状态取消子执行后按 Retry / Catch 处理
+    async fn time_out_current_state(&mut self) -> Result<(), String> {
+        let error = self.state_timeout_error();
+        if matches!(self.state_def(), State::Task(_)) {
+            self.revoke_current_task().await?;
+            return self.retry_or_fail_task(error).await;
+        }
+        self.cancel_children().await?;
+        self.retry_or_fail_fanout(error).await
+    }
+
+    /// 撤销当前 Task 仍在队列中 / 执行中的任务（回调 Task 的 token 置为 TIMED_OUT），迟到的结果不再并入 context
+    async fn revoke_current_task(&mut self) -> Result<(), String> {
+        let State::Task(task) = self.state_def() else {
+            return Ok(());
+        };
+        if !self.awaiting_signal {
+            return Ok(());
+        }
+        if task.waits_for_task_token() {
+            if let Some(token) = self.context.get(TASK_TOKEN_KEY).and_then(Value::as_str) {
+                self.persistence
+                    .update_task(
+                        token,
+                        &UpdateStoredActivityTask {
+                            status: Some(TIMED_OUT.into()),
+                            completed_at: Some(Some(Utc::now().naive_utc())),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        if self.last_task_state.as_deref() == Some(self.current_state.as_str()) {
+            self.last_task_state = None;
+        }
+        // 经 MatchService 撤销：内存 / 混合模式下进程内队列中的任务也一并撤下
+        if self.mode == WorkflowMode::Deferred {
+            let revoked = self.state_handler_registry.revoke(&self.run_id).await?;
+            if revoked > 0 {
+                warn!("[{}] revoked {revoked} queued task(s) of '{}'", self.run_id, self.current_state);
+            }
+        }
+        Ok(())
+    }
+}
@@ -10,6 +10,8 @@ pub enum WorkflowMode {
 pub struct StepOutcome {
     pub should_continue: bool,
     pub updated_context: Value,
+    /// 状态尚未完成（deferred Task 已入队 / 分支未汇合），引擎需等待信号
+    pub suspended: bool,
 } 
 
 // Re-export StateExecutionResult from handler
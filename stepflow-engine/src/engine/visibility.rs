@@ -0,0 +1,68 @@
+//! 可见性记录：执行结束时把终态（COMPLETED / FAILED / TIMED_OUT / CANCELLED / TERMINATED）
+//! 与结束时间同步到 `workflow_visibility`，供按状态检索。
+//!
+//! 可见性是 execution 记录的派生数据：终态已随步骤提交，这里只做尽力写入，失败仅记录日志。
+
+use stepflow_storage::entities::workflow_visibility::{
+    StoredWorkflowVisibility, UpdateStoredWorkflowVisibility,
+};
+use tracing::warn;
+
+use super::core::WorkflowEngine;
+
+impl WorkflowEngine {
+    /// 按已提交的 execution 记录更新（不存在时创建）可见性记录
+    pub(crate) async fn record_visibility(&self) {
+        if let Err(e) = self.upsert_visibility().await {
+            warn!("[{}] visibility not recorded: {e}", self.run_id);
+        }
+    }
+
+    async fn upsert_visibility(&self) -> Result<(), String> {
+        let Some(execution) = self
+            .persistence
+            .get_execution(&self.run_id)
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(());
+        };
+
+        let existing = self
+            .persistence
+            .get_visibility(&self.run_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let result = match existing {
+            Some(vis) => {
+                self.persistence
+                    .update_visibility(
+                        &self.run_id,
+                        &UpdateStoredWorkflowVisibility {
+                            status: Some(Some(execution.status)),
+                            close_time: Some(execution.close_time),
+                            version: Some(vis.version + 1),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+            }
+            None => {
+                self.persistence
+                    .create_visibility(&StoredWorkflowVisibility {
+                        run_id: execution.run_id,
+                        workflow_id: execution.workflow_id,
+                        workflow_type: Some(execution.workflow_type),
+                        start_time: Some(execution.start_time),
+                        close_time: execution.close_time,
+                        status: Some(execution.status),
+                        memo: execution.memo,
+                        search_attrs: execution.search_attrs,
+                        version: 1,
+                    })
+                    .await
+            }
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
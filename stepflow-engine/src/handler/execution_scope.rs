@@ -1,8 +1,12 @@
+use chrono::{DateTime, Utc};
 use serde_json::Value;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use stepflow_hook::EngineEventDispatcher;
 use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::{
+    activity_task::StoredActivityTask, outbox::StoredOutboxMessage, timer::StoredTimer,
+};
 use stepflow_dsl::State;
 use crate::engine::WorkflowMode;
 
@@ -17,6 +21,20 @@ pub struct StateExecutionResult {
     pub metadata: Option<Value>, 
 }
 
+/// ------------------------------------------------------------
+/// StepWrites —— handler 产生的副作用，随引擎步骤在同一事务中提交
+/// ------------------------------------------------------------
+#[derive(Debug, Default)]
+pub struct StepWrites {
+    pub timers: Vec<StoredTimer>,
+    /// 签发的回调 task token
+    pub activity_tasks: Vec<StoredActivityTask>,
+    /// 提交后才对外发布的消息（如待入队的任务）
+    pub outbox: Vec<StoredOutboxMessage>,
+    /// MappingDSL `debug` 时的映射轨迹，追加到状态记录的 `mapping_trace`
+    pub mapping_trace: Vec<Value>,
+}
+
 /// ------------------------------------------------------------
 /// StateExecutionContext —— handler 运行时上下文
 /// ------------------------------------------------------------
@@ -28,6 +46,14 @@ pub struct StateExecutionScope<'a> {
     pub dispatcher: Option<&'a Arc<EngineEventDispatcher>>,
     pub persistence: &'a DynPM,
     pub state_def: &'a State,
+    /// 第几次重试（首次执行为 0）
+    pub attempt: u32,
+    /// Deferred 重试的最早执行时间
+    pub retry_at: Option<DateTime<Utc>>,
+    /// 本执行创建的定时器所属分片
+    pub timer_shard: i64,
+    /// 引擎步骤的写入缓冲；为空时 handler 直接写入存储 / 投递
+    pub writes: Option<&'a Mutex<StepWrites>>,
 }
 
 impl<'a> StateExecutionScope<'a> {
@@ -48,6 +74,26 @@ impl<'a> StateExecutionScope<'a> {
             dispatcher,
             persistence,
             state_def,
+            attempt: 0,
+            retry_at: None,
+            timer_shard: 0,
+            writes: None,
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_retry(mut self, attempt: u32, retry_at: DateTime<Utc>) -> Self {
+        self.attempt = attempt;
+        self.retry_at = Some(retry_at);
+        self
+    }
+
+    pub fn with_timer_shard(mut self, timer_shard: i64) -> Self {
+        self.timer_shard = timer_shard;
+        self
+    }
+
+    pub fn with_writes(mut self, writes: &'a Mutex<StepWrites>) -> Self {
+        self.writes = Some(writes);
+        self
+    }
+}
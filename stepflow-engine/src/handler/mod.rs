@@ -6,12 +6,14 @@ pub mod wait;
 pub mod choice;
 pub mod succeed;
 pub mod fail;
+pub mod wait_for_signal;
 pub mod registry;
-pub use execution_scope::{StateExecutionScope, StateExecutionResult};
+pub use execution_scope::{StateExecutionScope, StateExecutionResult, StepWrites};
 pub use traits::StateHandler;
 pub use task::TaskHandler;
 pub use pass::PassHandler;
 pub use wait::WaitHandler;
 pub use choice::ChoiceHandler;
 pub use succeed::SucceedHandler;
-pub use fail::FailHandler;
\ No newline at end of file
+pub use fail::FailHandler;
+pub use wait_for_signal::WaitForSignalHandler;
\ No newline at end of file
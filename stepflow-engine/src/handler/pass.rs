@@ -32,10 +32,15 @@ impl StateHandler for PassHandler {
         let pipeline = MappingPipeline {
             input_mapping: state.base.input_mapping.as_ref(),
             output_mapping: state.base.output_mapping.as_ref(),
+            ..Default::default()
         };
 
         let exec_input = pipeline.apply_input(input)?;
-        let output = pipeline.apply_output(&exec_input, input)?;
+        // 声明了 result 时以其作为状态结果，由引擎按 ResultPath / 合并策略写回 context
+        let output = match &state.result {
+            Some(result) => result.clone(),
+            None => pipeline.apply_output(&exec_input, input)?,
+        };
 
         debug!("PassHandler output: {}", output);
 
@@ -19,4 +19,13 @@ impl StateHandlerRegistry {
     pub fn get(&self, state_type: &str) -> Option<&Arc<dyn StateHandler>> {
         self.handlers.get(state_type)
     }
+
+    /// 让各 handler 撤销该执行尚未完成的工作，返回撤销的总数
+    pub async fn revoke(&self, run_id: &str) -> Result<u64, String> {
+        let mut revoked = 0;
+        for handler in self.handlers.values() {
+            revoked += handler.revoke(run_id).await?;
+        }
+        Ok(revoked)
+    }
 }
\ No newline at end of file
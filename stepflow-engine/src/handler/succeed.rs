@@ -34,6 +34,7 @@ impl StateHandler for SucceedHandler {
         let pipeline = MappingPipeline {
             input_mapping: state.base.input_mapping.as_ref(),
             output_mapping: state.base.output_mapping.as_ref(),
+            ..Default::default()
         };
 
         let exec_input = pipeline.apply_input(input)?;
@@ -1,18 +1,24 @@
 use chrono::Utc;
-use serde_json::Value;
+use serde_json::{json, Value};
 use stepflow_dsl::state::{task::TaskState, State};
-use crate::engine::WorkflowMode;
+use crate::engine::{retry::max_retry_attempts, root_run_id, WorkflowMode};
 use stepflow_match::service::MatchService;
 use stepflow_dto::dto::queue_task::QueueTaskDto;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::{activity_task::StoredActivityTask, outbox::StoredOutboxMessage};
 use stepflow_tool::common::context::ToolContext;
 use stepflow_tool::registry::globals::GLOBAL_TOOL_REGISTRY;
 
 use std::sync::Arc;
 use async_trait::async_trait;
 use tracing::{debug, warn};
+use uuid::Uuid;
 
 use super::{StateHandler, StateExecutionScope, StateExecutionResult};
 
+/// 回调 Task 签发的 token 在 context / 任务输入中的键名
+pub const TASK_TOKEN_KEY: &str = "taskToken";
+
 pub struct TaskHandler {
     match_service: Arc<dyn MatchService>,
 }
@@ -54,22 +60,101 @@ impl TaskHandler {
         input: &Value,
     ) -> Result<(Value, Value), String> {
         debug!(
-            "Creating deferred task for resource: {} via MatchService",
-            state.resource
+            "Creating deferred task for resource: {} on queue: {} via MatchService",
+            state.resource,
+            state.task_queue()
         );
 
-        let task = build_queue_task(scope.run_id, scope.state_name, state, input);
+        let template_id = owning_template(scope.persistence, scope.run_id).await;
+        let task = build_queue_task(scope, state, input, template_id);
 
-        self.match_service
-            .enqueue_task(&state.resource, task.clone())
-            .await
-            .map_err(|e| format!("Failed to enqueue task via MatchService: {}", e))?;
+        match scope.writes {
+            // 引擎步骤提交后再经 outbox 入队，避免回滚的步骤留下任务
+            Some(writes) => writes
+                .lock()
+                .map_err(|e| format!("Step writes poisoned: {e}"))?
+                .outbox
+                .push(StoredOutboxMessage {
+                    id: 0,
+                    run_id: scope.run_id.to_string(),
+                    topic: scope.state_type.to_string(),
+                    payload: json!({ "queue": state.task_queue(), "task": task }),
+                    created_at: Utc::now().naive_utc(),
+                    published_at: None,
+                }),
+            None => self.enqueue(state.task_queue(), task.clone()).await?,
+        }
 
         let metadata = serde_json::to_value(&task)
             .map_err(|e| format!("Failed to serialize task metadata: {}", e))?;
 
         Ok((input.clone(), metadata))
     }
+
+    /// 回调模式：签发 task token 并记录为 RUNNING 的 activity task（随步骤提交），
+    /// 不调用工具也不入队；外部系统凭 token 回调完成 / 失败
+    async fn handle_callback(
+        &self,
+        scope: &StateExecutionScope<'_>,
+        state: &TaskState,
+        input: &Value,
+    ) -> Result<(Value, Value), String> {
+        let token = Uuid::new_v4().to_string();
+        let now = Utc::now().naive_utc();
+        let (_, timeout_seconds) = extract_priority_and_timeout(state, scope.run_id, scope.state_name);
+
+        let mut task_input = input.clone();
+        if let Value::Object(map) = &mut task_input {
+            map.insert(TASK_TOKEN_KEY.to_string(), json!(token));
+        }
+
+        let task = StoredActivityTask {
+            task_token: token.clone(),
+            run_id: scope.run_id.to_string(),
+            shard_id: 0,
+            seq: scope.attempt as i64,
+            activity_type: state.resource.clone(),
+            state_name: Some(scope.state_name.to_string()),
+            input: Some(task_input),
+            result: None,
+            status: "RUNNING".to_string(),
+            error: None,
+            error_details: None,
+            attempt: scope.attempt as i64,
+            max_attempts: max_retry_attempts(state.base.retry.as_deref()) as i64,
+            heartbeat_at: None,
+            scheduled_at: now,
+            started_at: Some(now),
+            completed_at: None,
+            timeout_seconds,
+            retry_policy: None,
+            version: 1,
+        };
+        debug!("Issued task token for {}.{} ({})", scope.run_id, scope.state_name, state.resource);
+
+        match scope.writes {
+            Some(writes) => writes
+                .lock()
+                .map_err(|e| format!("Step writes poisoned: {e}"))?
+                .activity_tasks
+                .push(task),
+            None => scope
+                .persistence
+                .create_task(&task)
+                .await
+                .map_err(|e| format!("Failed to record task token: {e}"))?,
+        }
+
+        Ok((input.clone(), json!({ "task_token": token })))
+    }
+
+    async fn enqueue(&self, queue: &str, task: QueueTaskDto) -> Result<(), String> {
+        self.match_service
+            .enqueue_task(queue, task)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to enqueue task via MatchService: {}", e))
+    }
 }
 
 #[async_trait]
@@ -85,6 +170,10 @@ impl StateHandler for TaskHandler {
         };
 
         let (output, metadata) = match scope.mode {
+            _ if state.waits_for_task_token() => {
+                let (out, meta) = self.handle_callback(scope, state, input).await?;
+                (out, Some(meta))
+            }
             WorkflowMode::Inline => (self.handle_inline(state, input).await?, None),
             WorkflowMode::Deferred => {
                 let (out, meta) = self.handle_deferred(scope, state, input).await?;
@@ -100,6 +189,20 @@ impl StateHandler for TaskHandler {
         })
     }
 
+    async fn publish(&self, payload: &Value) -> Result<(), String> {
+        let queue = payload
+            .get("queue")
+            .and_then(Value::as_str)
+            .ok_or("Outbox task message missing queue")?;
+        let task: QueueTaskDto = serde_json::from_value(payload.get("task").cloned().unwrap_or_default())
+            .map_err(|e| format!("Invalid outbox task message: {e}"))?;
+        self.enqueue(queue, task).await
+    }
+
+    async fn revoke(&self, run_id: &str) -> Result<u64, String> {
+        self.match_service.cancel_run_tasks(run_id).await
+    }
+
     fn state_type(&self) -> &'static str {
         "task"
     }
@@ -132,31 +235,74 @@ fn extract_priority_and_timeout(
         }
     }
 
+    // 未在 execution_config 中覆盖时沿用状态的 timeoutSeconds
+    let timeout_seconds = timeout_seconds.or(state.timeout_seconds.map(|s| s as i64));
     (priority, timeout_seconds)
 }
 
-fn build_queue_task(
+/// 心跳上限：`heartbeatExpr`（输入上的 JSONPath）优先，否则取 `heartbeatSeconds`
+fn resolve_heartbeat_seconds(
+    state: &TaskState,
+    input: &Value,
     run_id: &str,
     state_name: &str,
+) -> Option<i64> {
+    if let Some(expr) = &state.heartbeat_expr {
+        let selected = jsonpath_lib::select(input, expr)
+            .ok()
+            .and_then(|vals| vals.first().and_then(|v| v.as_i64()));
+        match selected {
+            Some(secs) => return Some(secs),
+            None => warn!(
+                "heartbeatExpr '{}' did not resolve to an integer for {}.{}, falling back to heartbeatSeconds",
+                expr, run_id, state_name
+            ),
+        }
+    }
+    state.heartbeat_seconds.map(i64::from)
+}
+
+/// 任务所属模板：从执行向上找最近一个记录了模板的祖先（Map / Parallel 分支自身不记录模板）
+async fn owning_template(persistence: &DynPM, run_id: &str) -> Option<String> {
+    let mut current = run_id.to_string();
+    loop {
+        let execution = persistence.get_execution(&current).await.ok()??;
+        if execution.template_id.is_some() {
+            return execution.template_id;
+        }
+        current = execution.parent_run_id?;
+    }
+}
+
+fn build_queue_task(
+    scope: &StateExecutionScope<'_>,
     state: &TaskState,
     input: &Value,
+    template_id: Option<String>,
 ) -> QueueTaskDto {
-    let (priority, timeout_seconds) = extract_priority_and_timeout(state, run_id, state_name);
+    let (priority, timeout_seconds) =
+        extract_priority_and_timeout(state, scope.run_id, scope.state_name);
 
     QueueTaskDto {
         task_id: "".to_string(),
-        run_id: run_id.to_string(),
-        state_name: state_name.to_string(),
+        run_id: scope.run_id.to_string(),
+        state_name: scope.state_name.to_string(),
         resource: state.resource.clone(),
+        task_queue: state.task_queue().to_string(),
+        root_run_id: root_run_id(scope.run_id).to_string(),
+        template_id,
+        worker_id: None,
         task_payload: Some(input.clone()),
         status: "pending".to_string(),
-        attempts: 0,
-        max_attempts: 3,
+        attempts: scope.attempt as i64,
+        max_attempts: max_retry_attempts(state.base.retry.as_deref()) as i64,
         priority,
         timeout_seconds,
+        heartbeat_seconds: resolve_heartbeat_seconds(state, input, scope.run_id, scope.state_name),
+        last_heartbeat_at: None,
         error_message: None,
         last_error_at: None,
-        next_retry_at: None,
+        next_retry_at: scope.retry_at,
         queued_at: Utc::now(),
         processing_at: None,
         completed_at: None,
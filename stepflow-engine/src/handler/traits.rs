@@ -12,6 +12,16 @@ pub trait StateHandler: Send + Sync {
         input: &Value,
     ) -> Result<StateExecutionResult, String>;
 
+    /// 投递该 handler 写入 outbox 的消息（步骤提交后调用，可能重复投递）
+    async fn publish(&self, _payload: &Value) -> Result<(), String> {
+        Err(format!("{} handler does not publish outbox messages", self.state_type()))
+    }
+
+    /// 撤销该执行经本 handler 派发、尚未完成的工作（如匹配队列中的任务），返回撤销的数量
+    async fn revoke(&self, _run_id: &str) -> Result<u64, String> {
+        Ok(0)
+    }
+
     /// 获取状态类型
     fn state_type(&self) -> &'static str;
 } 
\ No newline at end of file
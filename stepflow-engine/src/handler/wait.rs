@@ -1,9 +1,10 @@
 use async_trait::async_trait;
+use jsonpath_lib::select;
 use serde_json::Value;
 use std::time::Duration;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 use uuid::Uuid;
-use chrono::{Utc, DateTime};
+use chrono::{Utc, DateTime, TimeDelta};
 use thiserror::Error;
 
 use stepflow_dsl::state::{wait::WaitState, State};
@@ -17,18 +18,84 @@ use crate::{
 
 use super::{StateHandler, StateExecutionScope, StateExecutionResult};
 
+/// Inline 模式下直接 sleep 的上限；更长的等待转为持久化定时器
 const MAX_INLINE_WAIT_SECONDS: u64 = 300;
 
 #[derive(Error, Debug)]
 pub enum WaitError {
-    #[error("Timestamp-based wait not yet supported")]
-    TimestampNotSupported,
-    #[error("Wait time too long for inline mode: {0} seconds")]
-    WaitTooLong(u64),
+    #[error("WaitState must define seconds, timestamp, secondsPath or timestampPath")]
+    MissingDuration,
+    #[error("Invalid timestamp '{0}', expected RFC3339")]
+    InvalidTimestamp(String),
+    #[error("Wait of {0} seconds is out of range")]
+    OutOfRange(u64),
+    #[error("Wait path '{path}' is invalid: {reason}")]
+    InvalidPath { path: String, reason: String },
     #[error("Database error: {0}")]
     DatabaseError(String),
 }
 
+fn parse_timestamp(ts: &str) -> Result<DateTime<Utc>, WaitError> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| WaitError::InvalidTimestamp(ts.to_string()))
+}
+
+/// 按 JSONPath 从输入中取出单个值
+fn select_one<'a>(input: &'a Value, path: &str) -> Result<&'a Value, WaitError> {
+    let invalid = |reason: String| WaitError::InvalidPath {
+        path: path.to_string(),
+        reason,
+    };
+    select(input, path)
+        .map_err(|e| invalid(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| invalid("selected nothing".into()))
+}
+
+/// `now` 之后 `secs` 秒；超出时间可表示的范围时报错而不是溢出 panic
+fn add_seconds(now: DateTime<Utc>, secs: u64) -> Result<DateTime<Utc>, WaitError> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|delta| now.checked_add_signed(delta))
+        .ok_or(WaitError::OutOfRange(secs))
+}
+
+/// 计算 Wait 的到期时间（已过去的时间点表示无需等待）
+pub fn resolve_wait_until(
+    state: &WaitState,
+    input: &Value,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, WaitError> {
+    if let Some(secs) = state.seconds {
+        return add_seconds(now, secs);
+    }
+    if let Some(ts) = &state.timestamp {
+        return parse_timestamp(ts);
+    }
+    if let Some(path) = &state.seconds_path {
+        let secs = select_one(input, path)?
+            .as_u64()
+            .ok_or_else(|| WaitError::InvalidPath {
+                path: path.clone(),
+                reason: "expected a non-negative integer".into(),
+            })?;
+        return add_seconds(now, secs);
+    }
+    if let Some(path) = &state.timestamp_path {
+        let ts = select_one(input, path)?
+            .as_str()
+            .ok_or_else(|| WaitError::InvalidPath {
+                path: path.clone(),
+                reason: "expected an RFC3339 string".into(),
+            })?;
+        return parse_timestamp(ts);
+    }
+    Err(WaitError::MissingDuration)
+}
+
 pub struct WaitHandler;
 
 impl WaitHandler {
@@ -36,30 +103,26 @@ impl WaitHandler {
         Self
     }
 
-    async fn handle_inline(&self, secs: u64) -> Result<(), String> {
-        if secs > MAX_INLINE_WAIT_SECONDS {
-            return Err(WaitError::WaitTooLong(secs).to_string());
-        }
-        info!("⏳ Inline wait for {} seconds", secs);
-        tokio::time::sleep(Duration::from_secs(secs)).await;
+    async fn handle_inline(&self, delay: Duration) {
+        info!("⏳ Inline wait for {:?}", delay);
+        tokio::time::sleep(delay).await;
         debug!("✅ Inline wait complete");
-        Ok(())
     }
 
     async fn handle_deferred(
         &self,
         scope: &StateExecutionScope<'_>,
-        secs: u64,
+        wait_until: DateTime<Utc>,
     ) -> Result<Value, String> {
         let now = Utc::now().naive_utc();
-        let fire_at = now + chrono::Duration::seconds(secs as i64);
+        let fire_at = wait_until.naive_utc();
 
         let timer = StoredTimer {
             timer_id: Uuid::new_v4().to_string(),
             run_id: scope.run_id.to_string(),
             state_name: Some(scope.state_name.to_string()),
             fire_at,
-            shard_id: 0,
+            shard_id: scope.timer_shard,
             version: 1,
             status: "pending".to_string(),
             payload: None,
@@ -67,10 +130,18 @@ impl WaitHandler {
             updated_at: now,
         };
 
-        scope.persistence
-            .create_timer(&timer)
-            .await
-            .map_err(|e| WaitError::DatabaseError(e.to_string()).to_string())?;
+        match scope.writes {
+            // 随引擎步骤一起提交
+            Some(writes) => writes
+                .lock()
+                .map_err(|e| format!("Step writes poisoned: {e}"))?
+                .timers
+                .push(timer.clone()),
+            None => scope.persistence
+                .create_timer(&timer)
+                .await
+                .map_err(|e| WaitError::DatabaseError(e.to_string()).to_string())?,
+        }
 
         info!("🕒 Deferred timer created to fire at {}", fire_at);
 
@@ -78,45 +149,47 @@ impl WaitHandler {
             timer_id: timer.timer_id,
             run_id: timer.run_id,
             shard_id: timer.shard_id,
-            fire_at: DateTime::<Utc>::from_utc(timer.fire_at, Utc),
+            fire_at: DateTime::from_naive_utc_and_offset(timer.fire_at, Utc),
             status: timer.status,
             version: timer.version,
             state_name: timer.state_name,
             payload: timer.payload,
-            created_at: DateTime::<Utc>::from_utc(timer.created_at, Utc),
-            updated_at: DateTime::<Utc>::from_utc(timer.updated_at, Utc),
+            created_at: DateTime::from_naive_utc_and_offset(timer.created_at, Utc),
+            updated_at: DateTime::from_naive_utc_and_offset(timer.updated_at, Utc),
         }).map_err(|e| format!("Failed to serialize timer metadata: {e}"))?;
 
         Ok(metadata)
     }
 
+    /// 返回 Some(timer 元数据) 表示已转为持久化定时器，引擎需挂起等待 TimerFired
     async fn process_wait(
         &self,
         scope: &StateExecutionScope<'_>,
         state: &WaitState,
-        _exec_input: &Value,
+        exec_input: &Value,
     ) -> Result<Option<Value>, String> {
-        if let Some(secs) = state.seconds {
-            if secs == 0 {
-                debug!("⏩ Wait = 0s, skipping wait");
-                return Ok(None);
-            }
+        let now = Utc::now();
+        let wait_until = resolve_wait_until(state, exec_input, now).map_err(|e| e.to_string())?;
 
-            match scope.mode {
-                WorkflowMode::Inline => {
-                    self.handle_inline(secs).await?;
-                    Ok(None)
-                },
-                WorkflowMode::Deferred => {
-                    let metadata = self.handle_deferred(scope, secs).await?;
-                    Ok(Some(metadata))
-                },
+        let Ok(delay) = (wait_until - now).to_std() else {
+            debug!("⏩ Wait until {} already passed, skipping wait", wait_until);
+            return Ok(None);
+        };
+        if delay.is_zero() {
+            debug!("⏩ Wait = 0s, skipping wait");
+            return Ok(None);
+        }
+
+        match scope.mode {
+            WorkflowMode::Inline if delay <= Duration::from_secs(MAX_INLINE_WAIT_SECONDS) => {
+                self.handle_inline(delay).await;
+                Ok(None)
+            }
+            // 长等待即使在 Inline 模式下也转为定时器，由调度器唤醒
+            _ => {
+                let metadata = self.handle_deferred(scope, wait_until).await?;
+                Ok(Some(metadata))
             }
-        } else if state.timestamp.is_some() {
-            warn!("Timestamp wait specified, but not supported yet");
-            Err(WaitError::TimestampNotSupported.to_string())
-        } else {
-            Ok(None)
         }
     }
 }
@@ -136,6 +209,7 @@ impl StateHandler for WaitHandler {
         let pipeline = MappingPipeline {
             input_mapping: state.base.input_mapping.as_ref(),
             output_mapping: state.base.output_mapping.as_ref(),
+            ..Default::default()
         };
 
         let exec_input = pipeline.apply_input(input)?;
@@ -158,4 +232,50 @@ impl StateHandler for WaitHandler {
     fn state_type(&self) -> &'static str {
         "wait"
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn wait_state(v: Value) -> WaitState {
+        serde_json::from_value(v).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_wait_until_sources() {
+        let now = Utc::now();
+        let input = json!({ "delay": 30, "deadline": "2030-01-01T00:00:00Z", "bad": "soon" });
+
+        let secs = resolve_wait_until(&wait_state(json!({ "seconds": 5 })), &input, now).unwrap();
+        assert_eq!(secs - now, chrono::Duration::seconds(5));
+
+        let path = resolve_wait_until(&wait_state(json!({ "secondsPath": "$.delay" })), &input, now).unwrap();
+        assert_eq!(path - now, chrono::Duration::seconds(30));
+
+        let ts = resolve_wait_until(&wait_state(json!({ "timestampPath": "$.deadline" })), &input, now).unwrap();
+        assert_eq!(ts, parse_timestamp("2030-01-01T00:00:00Z").unwrap());
+
+        assert!(matches!(
+            resolve_wait_until(&wait_state(json!({ "timestampPath": "$.bad" })), &input, now),
+            Err(WaitError::InvalidTimestamp(_))
+        ));
+        assert!(matches!(
+            resolve_wait_until(&wait_state(json!({ "secondsPath": "$.missing" })), &input, now),
+            Err(WaitError::InvalidPath { .. })
+        ));
+        assert!(matches!(
+            resolve_wait_until(&wait_state(json!({ "secondsPath": "$.forever" })), &json!({ "forever": u64::MAX }), now),
+            Err(WaitError::OutOfRange(u64::MAX))
+        ));
+        assert!(matches!(
+            resolve_wait_until(&wait_state(json!({ "seconds": i64::MAX as u64 })), &input, now),
+            Err(WaitError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            resolve_wait_until(&wait_state(json!({})), &input, now),
+            Err(WaitError::MissingDuration)
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use stepflow_dsl::state::State;
+use super::{StateHandler, StateExecutionScope, StateExecutionResult};
+
+/// ---------------------------------------------------------------------
+/// WaitForSignalHandler：不产生副作用，只告诉引擎挂起等待哪个信号；
+/// 信号到达后由 `apply_signal` 以信号 payload 完成该状态
+/// ---------------------------------------------------------------------
+#[derive(Default)]
+pub struct WaitForSignalHandler;
+
+impl WaitForSignalHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl StateHandler for WaitForSignalHandler {
+    async fn handle(
+        &self,
+        scope: &StateExecutionScope<'_>,
+        input: &Value,
+    ) -> Result<StateExecutionResult, String> {
+        let state = match scope.state_def {
+            State::WaitForSignal(ref s) => s,
+            _ => return Err("Invalid state type for WaitForSignalHandler".into()),
+        };
+
+        debug!("[{}] {} waiting for signal '{}'", scope.run_id, scope.state_name, state.signal_name);
+
+        Ok(StateExecutionResult {
+            output: input.clone(),
+            next_state: state.base.next.clone(),
+            should_continue: true,
+            metadata: Some(json!({ "signal_name": state.signal_name })),
+        })
+    }
+
+    fn state_type(&self) -> &'static str {
+        "waitForSignal"
+    }
+}
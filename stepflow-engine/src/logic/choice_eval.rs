@@ -1,7 +1,14 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, FixedOffset};
 use jsonpath_lib::select;
 use serde_json::Value;
-use stepflow_dsl::logic::ChoiceLogic;
+use stepflow_dsl::logic::{ChoiceLogic, ChoiceOperator, ChoiceOperatorKind, Comparison};
 
+/// 评估 Choice 条件。
+///
+/// 字符串 / 时间 / 布尔比较与 Step Functions 一致：变量缺失或类型不符时为 false；
+/// 原有数值比较（`GreaterThan` 等）仍要求两侧均为数字。
 pub fn eval_choice_logic(logic: &ChoiceLogic, data: &Value) -> Result<bool, String> {
     // And
     if let Some(and) = &logic.and_ {
@@ -35,41 +42,194 @@ pub fn eval_choice_logic(logic: &ChoiceLogic, data: &Value) -> Result<bool, Stri
         .operator
         .as_deref()
         .ok_or("Missing operator")?;
-    let cmp_value = logic.value.clone().unwrap_or(Value::Null);
+    let op = ChoiceOperator::parse(operator)
+        .ok_or_else(|| format!("Unsupported operator {operator}"))?;
 
-    let selected = select(data, variable)
-        .map_err(|e| format!("Invalid JSONPath '{}': {e}", variable))?
-        .first()
-        .cloned()
-        .cloned()
-        .unwrap_or(Value::Null);
+    let selected = select_first(data, variable)?;
+
+    if op.is_unary() {
+        let expected = match &logic.value {
+            None => true,
+            Some(Value::Bool(b)) => *b,
+            Some(other) => return Err(format!("{operator} expects a boolean value, got {other}")),
+        };
+        return Ok(eval_unary(op.kind, selected.as_ref()) == expected);
+    }
+
+    // *Path 变体：右侧取自上下文中的另一个值
+    let cmp_value = match (&logic.value, op.path) {
+        (Some(Value::String(path)), true) => select_first(data, path)?,
+        (_, true) => return Err(format!("{operator} expects a JSONPath value")),
+        (value, false) => Some(value.clone().unwrap_or(Value::Null)),
+    };
 
-    Ok(match operator {
-        "Equals"    => selected == cmp_value,
-        "NotEquals" => selected != cmp_value,
+    let left = selected.unwrap_or(Value::Null);
+    let right = cmp_value.unwrap_or(Value::Null);
+    eval_binary(op.kind, &left, &right)
+}
+
+fn eval_unary(kind: ChoiceOperatorKind, selected: Option<&Value>) -> bool {
+    match kind {
+        ChoiceOperatorKind::IsPresent   => selected.is_some(),
+        ChoiceOperatorKind::IsNull      => selected.is_some_and(Value::is_null),
+        ChoiceOperatorKind::IsString    => selected.is_some_and(Value::is_string),
+        ChoiceOperatorKind::IsBoolean   => selected.is_some_and(Value::is_boolean),
+        ChoiceOperatorKind::IsNumeric   => selected.is_some_and(Value::is_number),
+        ChoiceOperatorKind::IsTimestamp => selected.and_then(as_timestamp).is_some(),
+        _ => false,
+    }
+}
+
+fn eval_binary(kind: ChoiceOperatorKind, left: &Value, right: &Value) -> Result<bool, String> {
+    Ok(match kind {
+        ChoiceOperatorKind::Equals    => left == right,
+        ChoiceOperatorKind::NotEquals => left != right,
 
         // ↓ 数值比较（需都能转为 f64）
-        "GreaterThan"        => num_cmp(&selected, &cmp_value, |a, b| a >  b)?,
-        "GreaterThanEquals"  => num_cmp(&selected, &cmp_value, |a, b| a >= b)?,
-        "LessThan"           => num_cmp(&selected, &cmp_value, |a, b| a <  b)?,
-        "LessThanEquals"     => num_cmp(&selected, &cmp_value, |a, b| a <= b)?,
-
-        // 类型判断
-        "IsNull"     => selected.is_null(),
-        "IsString"   => selected.is_string(),
-        "IsBoolean"  => selected.is_boolean(),
-        "IsNumeric"  => selected.is_number(),
-
-        _ => return Err(format!("Unsupported operator {operator}")),
+        ChoiceOperatorKind::Numeric(cmp) => num_cmp(left, right, cmp)?,
+
+        ChoiceOperatorKind::String(cmp) => match (left.as_str(), right.as_str()) {
+            (Some(a), Some(b)) => cmp.holds(a.cmp(b)),
+            _ => false,
+        },
+        ChoiceOperatorKind::StringMatches => match (left.as_str(), right.as_str()) {
+            (Some(a), Some(pattern)) => wildcard_match(pattern, a),
+            _ => false,
+        },
+        ChoiceOperatorKind::Timestamp(cmp) => match (as_timestamp(left), as_timestamp(right)) {
+            (Some(a), Some(b)) => cmp.holds(a.cmp(&b)),
+            _ => false,
+        },
+        ChoiceOperatorKind::BooleanEquals => match (left.as_bool(), right.as_bool()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+        ChoiceOperatorKind::Contains => left.as_array().is_some_and(|items| items.contains(right)),
+
+        unary => return Err(format!("{unary:?} is not a binary operator")),
     })
 }
 
+/// 取 JSONPath 的第一个匹配；`None` 表示变量不存在
+fn select_first(data: &Value, path: &str) -> Result<Option<Value>, String> {
+    Ok(select(data, path)
+        .map_err(|e| format!("Invalid JSONPath '{}': {e}", path))?
+        .first()
+        .map(|v| (*v).clone()))
+}
+
+fn as_timestamp(value: &Value) -> Option<DateTime<FixedOffset>> {
+    value.as_str().and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+}
+
+/// `*` 匹配任意长度字符，`\*` 表示字面量 `*`
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    // 预处理为 token：None = 通配，Some(c) = 字面字符
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => tokens.push(Some(chars.next().unwrap_or('\\'))),
+            '*' => tokens.push(None),
+            c => tokens.push(Some(c)),
+        }
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Some(c)) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            Some(None) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bt)) => {
+                    backtrack = Some((bp, bt + 1));
+                    p = bp + 1;
+                    t = bt + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(Option::is_none)
+}
+
 // ---------- helper ----------
-fn num_cmp<F>(a: &Value, b: &Value, op: F) -> Result<bool, String>
-where
-    F: Fn(f64, f64) -> bool,
-{
+fn num_cmp(a: &Value, b: &Value, cmp: Comparison) -> Result<bool, String> {
     let fa = a.as_f64().ok_or("Left side not numeric")?;
     let fb = b.as_f64().ok_or("Right side not numeric")?;
-    Ok(op(fa, fb))
-}
\ No newline at end of file
+    Ok(fa.partial_cmp(&fb).is_some_and(|ord: Ordering| cmp.holds(ord)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn leaf(variable: &str, operator: &str, value: Option<Value>) -> ChoiceLogic {
+        ChoiceLogic {
+            and_: None,
+            or_: None,
+            not_: None,
+            variable: Some(variable.into()),
+            operator: Some(operator.into()),
+            value,
+        }
+    }
+
+    fn eval(variable: &str, operator: &str, value: Value) -> bool {
+        let data = json!({
+            "name": "report-2024.csv",
+            "other": "report-2024.csv",
+            "limit": 10,
+            "count": 3,
+            "flag": true,
+            "tags": ["a", "b"],
+            "at": "2024-05-01T10:00:00Z",
+            "deadline": "2024-05-01T12:00:00+02:00",
+            "nothing": null
+        });
+        let value = if value.is_null() { None } else { Some(value) };
+        eval_choice_logic(&leaf(variable, operator, value), &data).unwrap()
+    }
+
+    #[test]
+    fn test_string_and_wildcard_operators() {
+        assert!(eval("$.name", "StringEquals", json!("report-2024.csv")));
+        assert!(eval("$.name", "StringLessThan", json!("z")));
+        assert!(eval("$.name", "StringMatches", json!("report-*.csv")));
+        assert!(!eval("$.name", "StringMatches", json!("*.json")));
+        assert!(eval("$.name", "StringEqualsPath", json!("$.other")));
+        // 类型不符 → false
+        assert!(!eval("$.count", "StringEquals", json!("3")));
+
+        assert!(wildcard_match("a\\*b*", "a*bcd"));
+        assert!(!wildcard_match("a\\*b", "axb"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn test_timestamp_presence_and_contains() {
+        assert!(eval("$.at", "TimestampEqualsPath", json!("$.deadline")));
+        assert!(eval("$.at", "TimestampLessThan", json!("2024-05-02T00:00:00Z")));
+        assert!(eval("$.at", "IsTimestamp", json!(true)));
+        assert!(!eval("$.name", "IsTimestamp", Value::Null));
+
+        assert!(eval("$.nothing", "IsPresent", Value::Null));
+        assert!(eval("$.missing", "IsPresent", json!(false)));
+        assert!(eval("$.nothing", "IsNull", Value::Null));
+        assert!(!eval("$.missing", "IsNull", Value::Null));
+
+        assert!(eval("$.tags", "Contains", json!("b")));
+        assert!(eval("$.count", "LessThanPath", json!("$.limit")));
+        assert!(eval("$.count", "NumericEquals", json!(3)));
+        assert!(eval("$.flag", "BooleanEquals", json!(true)));
+    }
+}
@@ -1,61 +1,209 @@
 //! Mapping utilities shared by the workflow engine.
 //!
 //! `MappingPipeline` wraps optional **InputMapping** and **OutputMapping** definitions
-//! (both are `stepflow_mapping::MappingDSL`).  The engine调用顺序：
+//! (both are `stepflow_mapping::MappingDSL`) together with the Step Functions style
+//! path fields of `BaseState`.  The engine调用顺序：
 //!
-//!   ① `apply_input(ctx)`   —— 在进入具体 handler 前执行
+//!   ① `apply_input(ctx)`   —— 在进入具体 handler 前执行：InputPath → InputMapping → Parameters
 //!   ② 业务 handler         —— 仅关心 exec_in → raw_out
-//!   ③ `apply_output(out)` —— handler 返回后执行
+//!   ③ `apply_output(out)` —— handler 返回后执行：ResultSelector → OutputMapping → ResultPath → OutputPath
 //!
 //! **当前简化**
-//! * 只实现 `MergeStrategy::Overwrite` / `Ignore` 两种浅合并策略
+//! * 只实现 `MergeStrategy::Overwrite` / `Ignore` 两种浅合并策略；声明 ResultPath 时改为写入该路径
 //! * `stepflow_mapping::MappingEngine` 仅需支持 `JsonPath` / `Constant` 两变体
+//! * Parameters / ResultSelector 为载荷模板，由 `stepflow_mapping::intrinsic` 求值
+//! * 任一阶段失败均报告为 `States.MappingError`；MappingDSL 开启 `debug` 时逐条规则快照写入状态记录
 //!
 //! 后续若要支持 Append / Merge 深合并、更多映射类型，只需扩展此文件即可。
 
-use serde_json::{Map, Value};
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use serde_json::{json, Map, Value};
+use stepflow_dsl::State;
+use stepflow_exception::STATES_MAPPING_ERROR;
+use stepflow_mapping::intrinsic::{evaluate_payload, select_path};
 use stepflow_mapping::{MappingDSL, MappingEngine};
 use stepflow_mapping::model::rule::MergeStrategy;
 
+use crate::handler::execution_scope::StepWrites;
+use crate::utils::tool_payload::build_tool_payload;
+
+/// 映射错误信息以 `States.MappingError` 开头，`classify_error` 据此归类
+fn mapping_error(stage: &str, err: impl Display) -> String {
+    format!("{STATES_MAPPING_ERROR}: {stage} error: {err}")
+}
+
+/// 错误信息是否来自输入 / 输出处理
+pub fn is_mapping_error(message: &str) -> bool {
+    message.contains(STATES_MAPPING_ERROR)
+}
+
 /// Lightweight pipeline for a single state (borrowed refs to DSL).
-#[derive(Debug, Clone, Copy)]
+///
+/// handler 内部只需 InputMapping / OutputMapping 时，其余字段留空（`..Default::default()`）。
+#[derive(Debug, Clone, Copy, Default)]
 pub struct MappingPipeline<'a> {
-    pub input_mapping:  Option<&'a MappingDSL>,
-    pub output_mapping: Option<&'a MappingDSL>,
+    pub input_path:      Option<&'a str>,
+    pub input_mapping:   Option<&'a MappingDSL>,
+    pub parameters:      Option<&'a Value>,
+    /// Task 的资源名：Parameters 求值结果作为工具载荷的 `parameters`，映射后的输入作为 `input`
+    pub resource:        Option<&'a str>,
+    pub result_selector: Option<&'a Value>,
+    pub output_mapping:  Option<&'a MappingDSL>,
+    pub result_path:     Option<&'a str>,
+    pub output_path:     Option<&'a str>,
+    /// 引擎步骤的写入缓冲；MappingDSL 开启 `debug` 时记录映射轨迹
+    pub writes:          Option<&'a Mutex<StepWrites>>,
 }
 
 impl<'a> MappingPipeline<'a> {
-    /// Apply `input_mapping` to *ctx* (clone if None).
+    /// 状态声明的完整输入 / 输出处理
+    pub fn for_state(state: &'a State) -> Self {
+        let base = state.base();
+        let (parameters, resource) = match state {
+            State::Task(task) => (task.parameters.as_ref(), Some(task.resource.as_str())),
+            _ => (None, None),
+        };
+        Self {
+            input_path: base.input_path.as_deref(),
+            input_mapping: base.input_mapping.as_ref(),
+            parameters,
+            resource,
+            result_selector: base.result_selector.as_ref(),
+            output_mapping: base.output_mapping.as_ref(),
+            result_path: base.result_path.as_deref(),
+            output_path: base.output_path.as_deref(),
+            writes: None,
+        }
+    }
+
+    pub fn with_writes(mut self, writes: &'a Mutex<StepWrites>) -> Self {
+        self.writes = Some(writes);
+        self
+    }
+
+    /// 执行一份 MappingDSL；`debug` 时把规则快照（或失败原因）追加到映射轨迹
+    fn run_mapping(&self, stage: &str, cfg: &MappingDSL, input: &Value) -> Result<Value, String> {
+        let result = MappingEngine::apply(cfg.clone(), input);
+        if let (true, Some(writes)) = (cfg.debug, self.writes) {
+            let entry = match &result {
+                Ok(r) => json!({ "stage": stage, "steps": r.steps, "output": r.output }),
+                Err(e) => json!({ "stage": stage, "error": e.to_string() }),
+            };
+            writes
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .mapping_trace
+                .push(entry);
+        }
+        result.map(|r| r.output).map_err(|e| mapping_error(stage, e))
+    }
+
+    /// InputPath → `input_mapping` → Parameters（均未声明时原样 clone）
     pub fn apply_input(&self, ctx: &Value) -> Result<Value, String> {
-        if let Some(cfg) = self.input_mapping {
-            MappingEngine::apply(cfg.clone(), ctx)
-                .map_err(|e| format!("InputMapping error: {e}"))
+        let selected = match self.input_path {
+            Some(path) => select_path(ctx, path).map_err(|e| mapping_error("InputPath", e))?,
+            None => ctx.clone(),
+        };
+        let input = if let Some(cfg) = self.input_mapping {
+            self.run_mapping("InputMapping", cfg, &selected)?
         } else {
-            Ok(ctx.clone())
-        }
+            selected
+        };
+
+        let Some(template) = self.parameters else {
+            return Ok(input);
+        };
+        let parameters = evaluate_payload(template, &input)
+            .map_err(|e| mapping_error("Parameters", e))?;
+        Ok(match self.resource {
+            Some(resource) => build_tool_payload(resource, &input, &parameters),
+            None => parameters,
+        })
     }
 
-    /// Apply `output_mapping` to *raw_out* then merge with *base_ctx*.
+    /// ResultSelector → `output_mapping` → 写入 *base_ctx*（ResultPath 或浅合并）→ OutputPath
     pub fn apply_output(&self, raw_out: &Value, base_ctx: &Value) -> Result<Value, String> {
-        // 1️⃣ 执行 OutputMapping（若有）
+        // 1️⃣ ResultSelector 重塑原始结果
+        let selected = match self.result_selector {
+            Some(template) => evaluate_payload(template, raw_out)
+                .map_err(|e| mapping_error("ResultSelector", e))?,
+            None => raw_out.clone(),
+        };
+
+        // 2️⃣ 执行 OutputMapping（若有）
         let mapped = if let Some(cfg) = self.output_mapping {
-            MappingEngine::apply(cfg.clone(), raw_out)
-                .map_err(|e| format!("OutputMapping error: {e}"))?
+            self.run_mapping("OutputMapping", cfg, &selected)?
         } else {
-            raw_out.clone()
+            selected
         };
 
-        // 2️⃣ 根据第 1 条 rule 的 merge_strategy（默认 Overwrite）做浅合并
-        let strategy = self
-            .output_mapping
-            .and_then(|m| m.mappings.first())
-            .map(|r| r.merge_strategy)
-            .unwrap_or(MergeStrategy::Overwrite);
+        // 3️⃣ 声明了 ResultPath 时写入该位置，否则根据第 1 条 rule 的 merge_strategy（默认 Overwrite）做浅合并
+        let merged = match self.result_path {
+            Some(path) => write_result_path(base_ctx, Some(path), mapped)
+                .map_err(|e| mapping_error("ResultPath", e))?,
+            None => {
+                let strategy = self
+                    .output_mapping
+                    .and_then(|m| m.mappings.first())
+                    .map(|r| r.merge_strategy)
+                    .unwrap_or(MergeStrategy::Overwrite);
+                merge_shallow(base_ctx, &mapped, strategy)
+            }
+        };
 
-        Ok(merge_shallow(base_ctx, &mapped, strategy))
+        // 4️⃣ OutputPath 选取传给下一状态的部分
+        match self.output_path {
+            Some(path) => select_path(&merged, path).map_err(|e| mapping_error("OutputPath", e)),
+            None => Ok(merged),
+        }
     }
 }
 
+// -----------------------------------------------------------------------------
+// Helper – write a value at a simple `$.a.b` path (Catch ResultPath)
+// -----------------------------------------------------------------------------
+/// 把 *value* 写到 *ctx* 的 `path` 处；`None` / `"$"` 表示整体替换。
+/// 仅支持点号路径，中间缺失的对象会自动创建。
+pub fn write_result_path(ctx: &Value, path: Option<&str>, value: Value) -> Result<Value, String> {
+    let path = match path {
+        None | Some("$") => return Ok(value),
+        Some(p) => p,
+    };
+    let keys: Vec<&str> = path
+        .strip_prefix("$.")
+        .ok_or_else(|| format!("ResultPath '{path}' must start with '$.'"))?
+        .split('.')
+        .collect();
+    if keys.iter().any(|k| k.is_empty()) {
+        return Err(format!("ResultPath '{path}' is invalid"));
+    }
+
+    let mut root = match ctx {
+        Value::Object(_) => ctx.clone(),
+        Value::Null => Value::Object(Map::new()),
+        _ => return Err(format!("ResultPath '{path}' requires an object input")),
+    };
+
+    let (last, parents) = keys.split_last().expect("non-empty path");
+    let mut cursor = &mut root;
+    for key in parents {
+        let obj = cursor
+            .as_object_mut()
+            .ok_or_else(|| format!("ResultPath '{path}' crosses a non-object at '{key}'"))?;
+        cursor = obj
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    cursor
+        .as_object_mut()
+        .ok_or_else(|| format!("ResultPath '{path}' crosses a non-object at '{last}'"))?
+        .insert(last.to_string(), value);
+
+    Ok(root)
+}
+
 // -----------------------------------------------------------------------------
 // Helper – shallow merge with simple strategy selector
 // -----------------------------------------------------------------------------
@@ -107,4 +255,37 @@ mod tests {
         let r = merge_shallow(&a, &b, MergeStrategy::Ignore);
         assert_eq!(r, json!({ "x": 1, "y": 2, "z": 3 }));
     }
+
+    #[test]
+    fn test_write_result_path() {
+        let ctx = json!({ "x": 1 });
+        let err = json!({ "Error": "E" });
+
+        assert_eq!(write_result_path(&ctx, None, err.clone()).unwrap(), err);
+        assert_eq!(
+            write_result_path(&ctx, Some("$.failure.detail"), err.clone()).unwrap(),
+            json!({ "x": 1, "failure": { "detail": { "Error": "E" } } })
+        );
+        assert!(write_result_path(&ctx, Some("$.x.y"), err.clone()).is_err());
+        assert!(write_result_path(&ctx, Some("x"), err).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_paths_and_selector() {
+        let selector = json!({ "id.$": "$.body.id", "tag.$": "States.Format('#{}', $.body.id)" });
+        let pipeline = MappingPipeline {
+            input_path: Some("$.order"),
+            result_selector: Some(&selector),
+            result_path: Some("$.order.created"),
+            output_path: Some("$.order"),
+            ..Default::default()
+        };
+        let ctx = json!({ "order": { "sku": "x" }, "other": 1 });
+
+        assert_eq!(pipeline.apply_input(&ctx).unwrap(), json!({ "sku": "x" }));
+        let out = pipeline
+            .apply_output(&json!({ "body": { "id": 7 }, "status": 200 }), &ctx)
+            .unwrap();
+        assert_eq!(out, json!({ "sku": "x", "created": { "id": 7, "tag": "#7" } }));
+    }
 }
\ No newline at end of file
@@ -1,15 +1,37 @@
+use std::sync::Mutex;
+
 use stepflow_dsl::State;
 use stepflow_dto::dto::signal::ExecutionSignal;
-use crate::engine::WorkflowEngine;
-use crate::handler::execution_scope::StateExecutionResult;
-use stepflow_storage::entities::workflow_execution::UpdateStoredWorkflowExecution;
-use chrono::Utc;
+use crate::engine::{retry::classify_error, root_run_id, WorkflowEngine};
+use crate::handler::execution_scope::{StateExecutionResult, StepWrites};
+use crate::mapping::MappingPipeline;
+use stepflow_exception::{ErrorOrigin, StepError, STATES_CANCELLED, STATES_TASK_FAILED};
+
+fn signal_run_id(signal: &ExecutionSignal) -> &str {
+    match signal {
+        ExecutionSignal::TaskCompleted { run_id, .. }
+        | ExecutionSignal::TaskFailed { run_id, .. }
+        | ExecutionSignal::TaskCancelled { run_id, .. }
+        | ExecutionSignal::TimerFired { run_id, .. }
+        | ExecutionSignal::TimeoutFired { run_id, .. }
+        | ExecutionSignal::Heartbeat { run_id, .. }
+        | ExecutionSignal::SignalReceived { run_id, .. }
+        | ExecutionSignal::CancelRequested { run_id, .. }
+        | ExecutionSignal::TerminateRequested { run_id, .. } => run_id,
+    }
+}
 
 /// 应用信号并推进引擎，返回 StepExecutionResult
 pub async fn apply_signal(
     engine: &mut WorkflowEngine,
     signal: ExecutionSignal,
 ) -> Result<StateExecutionResult, String> {
+    // 发给 Parallel / Map 分支子执行的信号交给子引擎处理
+    let target = signal_run_id(&signal).to_string();
+    if target != engine.run_id && root_run_id(&target) == root_run_id(&engine.run_id) {
+        return engine.route_branch_signal(&target, signal).await;
+    }
+
     match signal {
         ExecutionSignal::TaskCompleted {
             run_id,
@@ -42,14 +64,34 @@ pub async fn apply_signal(
                 return Err("TaskCompleted signal applied to non-Task state".into());
             };
 
-            let pipeline = crate::mapping::MappingPipeline {
-                input_mapping: task_state.base.input_mapping.as_ref(),
-                output_mapping: task_state.base.output_mapping.as_ref(),
-            };
+            let writes = Mutex::new(StepWrites::default());
+            let applied = MappingPipeline::for_state(state)
+                .with_writes(&writes)
+                .apply_output(&output, &engine.context);
+            let next_state = task_state.base.next.clone();
+
+            // 引擎挂起在该 Task 上：回调即完成该状态，推进游标
+            let suspended_here = state_name == engine.current_state
+                && matches!(engine.state_def(), State::Task(_));
 
-            engine.context = pipeline
-                .apply_output(&output, &engine.context)
-                .map_err(|e| format!("output mapping failed: {e}"))?;
+            let new_context = match applied {
+                Ok(new_context) => new_context,
+                // 输出处理失败（States.MappingError）：与 Task 失败一样先 Retry 再 Catch
+                Err(e) if suspended_here => {
+                    engine.record_mapping_trace(&writes).await?;
+                    engine.retry_or_fail_task(classify_error(engine.state_def(), &e)).await?;
+                    return Ok(StateExecutionResult {
+                        output: engine.context.clone(),
+                        next_state: Some(engine.current_state.clone()),
+                        should_continue: true,
+                        metadata: None,
+                    });
+                }
+                Err(e) => return Err(e),
+            };
+            if suspended_here {
+                engine.record_mapping_trace(&writes).await?;
+            }
 
             // // ✅ 先补发 NodeEnter（为了持久化/日志一致）
             // engine.dispatch_event(stepflow_dto::dto::engine_event::EngineEvent::NodeEnter {
@@ -63,7 +105,13 @@ pub async fn apply_signal(
                 run_id,
                 state_name,
                 output: output.clone(),
-            }).await;
+            }).await?;
+
+            if suspended_here {
+                engine.complete_current_state(new_context, next_state).await?;
+            } else {
+                engine.context = new_context;
+            }
 
             Ok(StateExecutionResult {
                 output: engine.context.clone(),
@@ -77,6 +125,7 @@ pub async fn apply_signal(
             run_id,
             state_name,
             error,
+            error_type,
         } => {
             // 只验证 run_id
             if run_id != engine.run_id {
@@ -91,11 +140,27 @@ pub async fn apply_signal(
                 ));
             }
 
+            // 引擎挂起在该 Task 上：先按 Retry 策略重新入队，再交给 Catch
+            if state_name == engine.current_state && matches!(engine.state_def(), State::Task(_)) {
+                let step_error = StepError {
+                    error_type: error_type.unwrap_or_else(|| STATES_TASK_FAILED.to_string()),
+                    message: error.clone(),
+                    origin: ErrorOrigin::Tool,
+                };
+                engine.retry_or_fail_task(step_error).await?;
+                return Ok(StateExecutionResult {
+                    output: engine.context.clone(),
+                    next_state: Some(engine.current_state.clone()),
+                    should_continue: true,
+                    metadata: None,
+                });
+            }
+
             engine.dispatch_event(stepflow_dto::dto::engine_event::EngineEvent::NodeFailed {
                 run_id,
                 state_name,
                 error: error.clone(),
-            }).await;
+            }).await?;
 
             Err(format!("Task failed: {}", error))
         }
@@ -131,39 +196,183 @@ pub async fn apply_signal(
                 return Err("TaskCancelled signal applied to non-Task state".into());
             };
 
-            // 更新任务状态为 cancelled
-            engine.persistence
-                .update_execution(
-                    &run_id,
-                    &UpdateStoredWorkflowExecution {
-                        status: Some("CANCELLED".into()),
-                        close_time: Some(Some(Utc::now().naive_utc())),
-                        ..Default::default()
-                    },
-                )
-                .await
-                .map_err(|e| e.to_string())?;
-
             // 发送节点取消事件
             engine.dispatch_event(stepflow_dto::dto::engine_event::EngineEvent::NodeCancelled {
                 run_id: run_id.clone(),
                 state_name: state_name.clone(),
                 reason: reason.clone().unwrap_or_else(|| "Task cancelled".to_string()),
-            }).await;
+            }).await?;
 
-            // 标记引擎为已完成
-            engine.finished = true;
+            // 执行记为 CANCELLED（与事件一并提交），仍在运行的子执行一并取消；
+            // 父执行（SubWorkflow / 分支）据 last_error 以 States.Cancelled 处理
+            let message = format!("Task cancelled: {}", reason.unwrap_or_else(|| "Task cancelled".to_string()));
+            engine
+                .close_stopped(
+                    "CANCELLED",
+                    StepError {
+                        error_type: STATES_CANCELLED.to_string(),
+                        message: message.clone(),
+                        origin: ErrorOrigin::Engine,
+                    },
+                )
+                .await?;
+            Err(message)
+        }
 
-            // 返回带有取消元数据的结果
-            Err(format!("Task cancelled: {}", reason.unwrap_or_else(|| "Task cancelled".to_string())))
+        ExecutionSignal::TimerFired { run_id, state_name } => {
+            if run_id != engine.run_id {
+                return Err("Signal mismatch: wrong run_id".into());
+            }
+
+            // 只接受引擎当前挂起的 Wait / 扇出重试（重复 / 过期的定时器直接拒绝）
+            if state_name != engine.current_state || !engine.awaiting_signal {
+                return Err(format!(
+                    "TimerFired for '{}' but engine is not waiting there (current state '{}')",
+                    state_name, engine.current_state
+                ));
+            }
+            let wait_state = match engine.state_def() {
+                State::Wait(wait_state) => wait_state,
+                State::Parallel(_) | State::Map(_) | State::SubWorkflow(_) => {
+                    engine.resume_fanout_retry().await?;
+                    return Ok(StateExecutionResult {
+                        output: engine.context.clone(),
+                        next_state: Some(engine.current_state.clone()),
+                        should_continue: true,
+                        metadata: None,
+                    });
+                }
+                _ => return Err("TimerFired signal applied to non-Wait state".into()),
+            };
+
+            // 与 WaitHandler 一致：输入映射后的数据经输出映射并入 context
+            let writes = Mutex::new(StepWrites::default());
+            let pipeline = MappingPipeline::for_state(engine.state_def()).with_writes(&writes);
+            let applied = pipeline
+                .apply_input(&engine.context)
+                .and_then(|exec_in| pipeline.apply_output(&exec_in, &engine.context));
+            let next_state = wait_state.base.next.clone();
+
+            engine.record_mapping_trace(&writes).await?;
+            match applied {
+                Ok(new_context) => engine.complete_current_state(new_context, next_state).await?,
+                Err(e) => {
+                    engine.handle_state_failure(classify_error(engine.state_def(), &e)).await?;
+                }
+            }
+
+            Ok(StateExecutionResult {
+                output: engine.context.clone(),
+                next_state: Some(engine.current_state.clone()),
+                should_continue: true,
+                metadata: None,
+            })
         }
 
-        ExecutionSignal::TimerFired { .. } => {
-            Err("TimerFired signal not yet supported".into())
+        ExecutionSignal::TimeoutFired {
+            run_id,
+            state_name,
+            visit_event_id,
+            attempt,
+        } => {
+            if run_id != engine.run_id {
+                return Err("Signal mismatch: wrong run_id".into());
+            }
+            engine.on_timeout_fired(state_name, visit_event_id, attempt).await?;
+            Ok(StateExecutionResult {
+                output: engine.context.clone(),
+                next_state: Some(engine.current_state.clone()),
+                should_continue: !engine.finished,
+                metadata: None,
+            })
         }
 
         ExecutionSignal::Heartbeat { .. } => {
             Err("Heartbeat signal not yet supported".into())
         }
+
+        ExecutionSignal::CancelRequested { run_id, reason } => {
+            if run_id != engine.run_id {
+                return Err("Signal mismatch: wrong run_id".into());
+            }
+            engine.cancel(reason).await?;
+            Ok(StateExecutionResult {
+                output: engine.context.clone(),
+                next_state: Some(engine.current_state.clone()),
+                should_continue: !engine.finished,
+                metadata: None,
+            })
+        }
+
+        ExecutionSignal::TerminateRequested { run_id, reason } => {
+            if run_id != engine.run_id {
+                return Err("Signal mismatch: wrong run_id".into());
+            }
+            engine.terminate(reason).await?;
+            Ok(StateExecutionResult {
+                output: engine.context.clone(),
+                next_state: None,
+                should_continue: false,
+                metadata: None,
+            })
+        }
+
+        ExecutionSignal::SignalReceived {
+            run_id,
+            signal_name,
+            payload,
+        } => {
+            if run_id != engine.run_id {
+                return Err("Signal mismatch: wrong run_id".into());
+            }
+
+            // 只有挂起在等待同名信号的 WaitForSignal 上时才接收（未等待的信号不缓存）
+            let waiting_for = match engine.state_def() {
+                State::WaitForSignal(wait) if engine.awaiting_signal && !engine.finished => Some(wait),
+                _ => None,
+            };
+            let Some(wait_state) = waiting_for.filter(|w| w.signal_name == signal_name) else {
+                return Err(format!(
+                    "Execution {} is not waiting for signal '{}' (current state '{}')",
+                    engine.run_id, signal_name, engine.current_state
+                ));
+            };
+
+            // 信号 payload 即状态输出，经输出映射并入 context
+            let writes = Mutex::new(StepWrites::default());
+            let applied = MappingPipeline::for_state(engine.state_def())
+                .with_writes(&writes)
+                .apply_output(&payload, &engine.context);
+            let next_state = wait_state.base.next.clone();
+
+            engine.record_mapping_trace(&writes).await?;
+            let new_context = match applied {
+                Ok(new_context) => new_context,
+                // 输出处理失败（States.MappingError）交给 Catch
+                Err(e) => {
+                    engine.handle_state_failure(classify_error(engine.state_def(), &e)).await?;
+                    return Ok(StateExecutionResult {
+                        output: engine.context.clone(),
+                        next_state: Some(engine.current_state.clone()),
+                        should_continue: !engine.finished,
+                        metadata: Some(payload),
+                    });
+                }
+            };
+
+            engine.dispatch_event(stepflow_dto::dto::engine_event::EngineEvent::NodeSuccess {
+                run_id,
+                state_name: engine.current_state.clone(),
+                output: payload.clone(),
+            }).await?;
+            engine.complete_current_state(new_context, next_state).await?;
+
+            Ok(StateExecutionResult {
+                output: engine.context.clone(),
+                next_state: Some(engine.current_state.clone()),
+                should_continue: true,
+                metadata: Some(payload),
+            })
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+mod common;
+
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::history::load_history;
+use stepflow_engine::engine::replay::Replayer;
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+
+fn hold_dsl(on_cancel: Option<&str>) -> Value {
+    json!({
+        "startAt": "Hold",
+        "onCancel": on_cancel,
+        "states": {
+            "Hold": { "type": "waitForSignal", "signalName": "resume", "next": "Done" },
+            "Done": { "type": "succeed" },
+            "Cleanup": { "type": "pass", "outputMapping": constant_output("cleaned", json!(true)), "next": "Release" },
+            "Release": { "type": "wait", "seconds": 60, "end": true }
+        }
+    })
+}
+
+async fn stop(engine: &mut WorkflowEngine, reason: &str, terminate: bool) -> Result<(), String> {
+    let (run_id, reason) = (engine.run_id.clone(), Some(reason.to_string()));
+    let signal = if terminate {
+        ExecutionSignal::TerminateRequested { run_id, reason }
+    } else {
+        ExecutionSignal::CancelRequested { run_id, reason }
+    };
+    engine.get_signal_sender().unwrap().send(signal).unwrap();
+    engine.handle_next_signal().await?;
+    engine.advance_until_blocked().await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_cancel_without_cleanup_records_cancelled() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-cancel", hold_dsl(None), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    stop(&mut engine, "user request", false).await.unwrap();
+    assert!(engine.finished);
+    let row = h.persistence.get_execution("run-cancel").await.unwrap().unwrap();
+    assert_eq!(row.status, "CANCELLED");
+    assert_eq!(row.result.unwrap(), json!({ "Error": "States.Cancelled", "Cause": "user request" }));
+    assert!(row.close_time.is_some());
+    let state = h.persistence.get_state("run-cancel:Hold").await.unwrap().unwrap();
+    assert_eq!(state.status, "CANCELLED");
+
+    // 回放得到同样的终态
+    let events = load_history(&h.persistence, "run-cancel").await.unwrap();
+    assert_eq!(events.last().unwrap().event_type, "WorkflowCancelled");
+    let replayed = Replayer::new(&engine.dsl, WorkflowMode::Deferred).replay(&events).unwrap();
+    assert!(replayed.finished);
+
+    // 已结束的执行不能再次取消
+    let err = stop(&mut engine, "again", false).await.unwrap_err();
+    assert!(err.contains("already closed"), "{err}");
+}
+
+#[tokio::test]
+async fn test_terminate_skips_cleanup() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-terminate", hold_dsl(Some("Cleanup")), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    stop(&mut engine, "shutdown", true).await.unwrap();
+    assert!(engine.finished);
+    assert!(engine.context.get("cleaned").is_none());
+    let row = h.persistence.get_execution("run-terminate").await.unwrap().unwrap();
+    assert_eq!(row.status, "TERMINATED");
+    assert_eq!(row.result.unwrap()["Cause"], "shutdown");
+}
+
+#[tokio::test]
+async fn test_cancel_runs_cleanup_states_then_records_cancelled() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-cleanup", hold_dsl(Some("Cleanup")), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 清理路径在 Release（Wait）处挂起，执行处于 CANCELLING
+    stop(&mut engine, "user request", false).await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Release");
+    assert_eq!(engine.context["cleaned"], true);
+    let row = h.persistence.get_execution("run-cleanup").await.unwrap().unwrap();
+    assert_eq!(row.status, "CANCELLING");
+
+    // 清理期间不接受重复取消；重启后仍记得取消原因
+    assert!(stop(&mut engine, "again", false).await.is_err());
+    let dsl = engine.dsl.clone();
+    drop(engine);
+    let mut restored = WorkflowEngine::restore_with_dsl(
+        "run-cleanup".into(),
+        dsl.clone(),
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap();
+    restored
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimerFired {
+            run_id: "run-cleanup".into(),
+            state_name: "Release".into(),
+        })
+        .unwrap();
+    restored.handle_next_signal().await.unwrap();
+    restored.advance_until_blocked().await.unwrap();
+
+    assert!(restored.finished);
+    let row = h.persistence.get_execution("run-cleanup").await.unwrap().unwrap();
+    assert_eq!(row.status, "CANCELLED");
+    assert_eq!(row.result.unwrap()["Cause"], "user request");
+
+    let events = load_history(&h.persistence, "run-cleanup").await.unwrap();
+    let replayed = Replayer::new(&dsl, WorkflowMode::Deferred).replay(&events).unwrap();
+    assert!(replayed.finished);
+    assert_eq!(replayed.context["cleaned"], true);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+#[tokio::test]
+async fn test_inline_task_failure_caught_into_result_path() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "no-such-tool",
+                "catch": [
+                    { "errorEquals": ["Other.Error"], "next": "Wrong" },
+                    { "errorEquals": ["States.ALL"], "next": "Recover", "resultPath": "$.failure" }
+                ],
+                "end": true
+            },
+            "Wrong": { "type": "fail", "error": "Wrong.Branch" },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-catch-inline", dsl, json!({ "x": 1 }), WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(out["x"], 1);
+    assert_eq!(out["failure"]["Error"], "States.TaskFailed");
+    assert!(out["failure"]["Cause"].as_str().unwrap().contains("Tool not found"));
+
+    let state = h
+        .persistence
+        .get_state("run-catch-inline:Call")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.status, "FAILED");
+}
+
+#[tokio::test]
+async fn test_deferred_task_failure_caught_after_retries() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "catch": [{ "errorEquals": ["Http.Timeout"], "next": "Recover" }],
+                "end": true
+            },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-catch-deferred", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskFailed {
+            run_id: "run-catch-deferred".into(),
+            state_name: "Call".into(),
+            error: "upstream timed out".into(),
+            error_type: Some("Http.Timeout".into()),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(
+        engine.context,
+        json!({ "Error": "Http.Timeout", "Cause": "upstream timed out" })
+    );
+    let exec = h
+        .persistence
+        .get_execution("run-catch-deferred")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(exec.status, "COMPLETED");
+}
+
+#[tokio::test]
+async fn test_parallel_branch_fail_state_caught_by_error_name() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [
+                    { "startAt": "Ok", "states": { "Ok": { "type": "pass", "end": true } } },
+                    {
+                        "startAt": "Boom",
+                        "states": { "Boom": { "type": "fail", "error": "Custom.Boom", "cause": "bad item" } }
+                    }
+                ],
+                "catch": [{ "errorEquals": ["Custom.Boom"], "next": "Recover", "resultPath": "$.error" }],
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-catch-par", dsl, json!({}), WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert_eq!(out["error"]["Error"], "Custom.Boom");
+    assert!(out["error"]["Cause"].as_str().unwrap().contains("bad item"));
+    assert_eq!(engine.current_state, "Recover");
+}
+
+#[tokio::test]
+async fn test_uncaught_failure_marks_execution_failed() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Boom",
+        "states": {
+            "Boom": { "type": "fail", "error": "Custom.Boom", "cause": "bad input" }
+        }
+    });
+    let mut engine = h.engine("run-uncaught", dsl, json!({}), WorkflowMode::Inline).await;
+
+    let err = engine.run_inline().await.unwrap_err();
+    assert_eq!(err, "bad input");
+    assert_eq!(engine.last_error.as_ref().unwrap().error_type, "Custom.Boom");
+
+    let exec = h
+        .persistence
+        .get_execution("run-uncaught")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(exec.status, "FAILED");
+    assert_eq!(exec.result, Some(json!({ "Error": "Custom.Boom", "Cause": "bad input" })));
+}
+
+#[tokio::test]
+async fn test_states_all_does_not_catch_runtime_error() {
+    let h = Harness::new().await;
+    let dsl = |catch_error: &str| {
+        json!({
+            "startAt": "Sleep",
+            "states": {
+                "Sleep": {
+                    "type": "wait",
+                    "secondsPath": "$.delay",
+                    "catch": [{ "errorEquals": [catch_error], "next": "Recover" }],
+                    "next": "Done"
+                },
+                "Recover": { "type": "succeed" },
+                "Done": { "type": "succeed" }
+            }
+        })
+    };
+    let input = json!({ "delay": u64::MAX });
+
+    // States.ALL 不匹配引擎运行时错误：执行以 States.Runtime 失败
+    let mut engine = h
+        .engine("run-catch-all-runtime", dsl("States.ALL"), input.clone(), WorkflowMode::Deferred)
+        .await;
+    assert!(engine.advance_until_blocked().await.is_err());
+    assert_eq!(engine.last_error.as_ref().unwrap().error_type, "States.Runtime");
+    let exec = h
+        .persistence
+        .get_execution("run-catch-all-runtime")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(exec.status, "FAILED");
+
+    // 显式声明 States.Runtime 的 Catch 可以捕获
+    let mut engine = h
+        .engine("run-catch-runtime", dsl("States.Runtime"), input, WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+    assert!(engine.finished);
+    assert_eq!(engine.current_state, "Recover");
+    assert_eq!(engine.context["Error"], "States.Runtime");
+}
@@ -0,0 +1,133 @@
+//! 引擎集成测试共用的内存 SQLite / 事件分发 / handler 注册表
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use stepflow_dsl::WorkflowDSL;
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+use stepflow_engine::handler::{
+    choice::ChoiceHandler, fail::FailHandler, pass::PassHandler, registry::StateHandlerRegistry,
+    succeed::SucceedHandler, task::TaskHandler, wait::WaitHandler,
+    wait_for_signal::WaitForSignalHandler,
+};
+use stepflow_eventbus::impls::local::LocalEventBus;
+use stepflow_hook::EngineEventDispatcher;
+use stepflow_match::service::{MatchService, MemoryMatchService};
+use stepflow_sqlite::SqliteStorageManager;
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::workflow_execution::StoredWorkflowExecution;
+
+pub struct Harness {
+    pub persistence: DynPM,
+    pub dispatcher: Arc<EngineEventDispatcher>,
+    pub match_service: Arc<dyn MatchService>,
+    pub registry: Arc<StateHandlerRegistry>,
+}
+
+impl Harness {
+    pub async fn new() -> Self {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite");
+        Self::with_pool(pool).await
+    }
+
+    /// 基于 SQLite 文件的多连接存储（模拟多个实例并发访问同一数据库）
+    pub async fn on_file(path: &std::path::Path) -> Self {
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await
+            .expect("file sqlite");
+        Self::with_pool(pool).await
+    }
+
+    async fn with_pool(pool: SqlitePool) -> Self {
+        let persistence: DynPM = Arc::new(SqliteStorageManager::new(pool).await.expect("schema"));
+
+        let dispatcher = Arc::new(EngineEventDispatcher::new(
+            vec![],
+            Arc::new(LocalEventBus::new(100)),
+        ));
+
+        let match_service: Arc<dyn MatchService> = MemoryMatchService::new();
+        let registry = Arc::new(
+            StateHandlerRegistry::new()
+                .register("task", Arc::new(TaskHandler::new(match_service.clone())))
+                .register("wait", Arc::new(WaitHandler::new()))
+                .register("waitForSignal", Arc::new(WaitForSignalHandler::new()))
+                .register("pass", Arc::new(PassHandler::new()))
+                .register("choice", Arc::new(ChoiceHandler::new()))
+                .register("succeed", Arc::new(SucceedHandler::new()))
+                .register("fail", Arc::new(FailHandler::new())),
+        );
+
+        Self {
+            persistence,
+            dispatcher,
+            match_service,
+            registry,
+        }
+    }
+
+    /// 落库 execution 记录并创建引擎（与 gateway 启动执行的流程一致）
+    pub async fn engine(&self, run_id: &str, dsl: Value, input: Value, mode: WorkflowMode) -> WorkflowEngine {
+        let dsl: WorkflowDSL = serde_json::from_value(dsl).expect("valid dsl");
+        let now = chrono::Utc::now().naive_utc();
+        self.persistence
+            .create_execution(&StoredWorkflowExecution {
+                run_id: run_id.to_string(),
+                workflow_id: Some(format!("wf-{run_id}")),
+                shard_id: 0,
+                template_id: None,
+                template_revision: None,
+                parent_run_id: None,
+                mode: match mode {
+                    WorkflowMode::Inline => "INLINE",
+                    WorkflowMode::Deferred => "DEFERRED",
+                }
+                .into(),
+                current_state_name: Some(dsl.start_at.clone()),
+                status: "RUNNING".into(),
+                workflow_type: "default".into(),
+                input: Some(input.clone()),
+                input_version: 1,
+                result: None,
+                result_version: 1,
+                start_time: now,
+                close_time: None,
+                current_event_id: 0,
+                memo: None,
+                search_attrs: None,
+                context_snapshot: None,
+                version: 1,
+            })
+            .await
+            .expect("create execution");
+
+        WorkflowEngine::new(
+            run_id.to_string(),
+            dsl,
+            input,
+            mode,
+            self.dispatcher.clone(),
+            self.persistence.clone(),
+            self.registry.clone(),
+        )
+    }
+}
+
+/// 输出映射：把常量写入 `key`
+pub fn constant_output(key: &str, value: Value) -> Value {
+    serde_json::json!({
+        "mappings": [
+            { "key": key, "type": "constant", "value": value }
+        ]
+    })
+}
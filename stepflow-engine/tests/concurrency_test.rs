@@ -0,0 +1,147 @@
+mod common;
+
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dsl::WorkflowDSL;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+use stepflow_storage::db::DynPM;
+use stepflow_storage::entities::workflow_execution::UpdateStoredWorkflowExecution;
+
+fn task_dsl() -> Value {
+    json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "outputMapping": constant_output("called", json!(true)),
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+fn completed(run_id: &str) -> ExecutionSignal {
+    ExecutionSignal::TaskCompleted {
+        run_id: run_id.into(),
+        state_name: "Call".into(),
+        output: json!({ "status": 200 }),
+    }
+}
+
+async fn restore(h: &Harness, run_id: &str, dsl: &WorkflowDSL) -> WorkflowEngine {
+    WorkflowEngine::restore_with_dsl(
+        run_id.into(),
+        dsl.clone(),
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap()
+}
+
+async fn snapshot(persistence: &DynPM, run_id: &str) -> (Value, i64, String) {
+    let row = persistence.get_execution(run_id).await.unwrap().unwrap();
+    (row.context_snapshot.unwrap_or(Value::Null), row.version, row.status)
+}
+
+#[tokio::test]
+async fn test_stale_engine_reloads_instead_of_overwriting() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = Harness::on_file(&dir.path().join("stepflow.db")).await;
+    let mut origin = h.engine("run-cas", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+    origin.advance_until_blocked().await.unwrap();
+
+    // 两个实例同时持有挂起在 Call 上的执行
+    let mut a = restore(&h, "run-cas", &origin.dsl).await;
+    let mut b = restore(&h, "run-cas", &origin.dsl).await;
+
+    a.get_signal_sender().unwrap().send(completed("run-cas")).unwrap();
+    a.handle_next_signal().await.unwrap();
+    a.advance_until_blocked().await.unwrap();
+    assert!(a.finished);
+    let (context, version, status) = snapshot(&h.persistence, "run-cas").await;
+    assert_eq!(status, "COMPLETED");
+
+    // 旧实例的写入冲突 → 重新加载后发现已完成，重复回调被拒绝，不覆盖结果
+    b.get_signal_sender().unwrap().send(completed("run-cas")).unwrap();
+    assert!(b.handle_next_signal().await.is_err());
+    assert!(b.finished);
+    assert_eq!(b.context, context);
+    assert_eq!(snapshot(&h.persistence, "run-cas").await, (context, version, status));
+}
+
+#[tokio::test]
+async fn test_engine_retries_step_after_external_update() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = Harness::on_file(&dir.path().join("stepflow.db")).await;
+    let mut engine = h.engine("run-merge", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 外部（如 REST 接口）基于最新版本修改 context
+    let (mut context, version, _) = snapshot(&h.persistence, "run-merge").await;
+    context["approved"] = json!(true);
+    h.persistence
+        .update_execution(
+            "run-merge",
+            &UpdateStoredWorkflowExecution {
+                context_snapshot: Some(Some(context)),
+                version: Some(version),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // 引擎持有旧版本：冲突后重新加载并在最新 context 上重新应用回调
+    engine.get_signal_sender().unwrap().send(completed("run-merge")).unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(engine.finished);
+
+    let (context, _, status) = snapshot(&h.persistence, "run-merge").await;
+    assert_eq!(status, "COMPLETED");
+    assert_eq!(context, json!({ "approved": true, "called": true }));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_concurrent_cas_updates_are_not_lost() {
+    let dir = tempfile::tempdir().unwrap();
+    let h = Harness::on_file(&dir.path().join("stepflow.db")).await;
+    h.engine("run-counter", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+
+    // 两个写者并发地读 - 改 - 写计数器，冲突时重读重试
+    let writers: Vec<_> = (0..2)
+        .map(|_| {
+            let persistence = h.persistence.clone();
+            tokio::spawn(async move {
+                for _ in 0..20 {
+                    loop {
+                        let (context, version, _) = snapshot(&persistence, "run-counter").await;
+                        let count = context["count"].as_i64().unwrap_or(0);
+                        let update = UpdateStoredWorkflowExecution {
+                            context_snapshot: Some(Some(json!({ "count": count + 1 }))),
+                            version: Some(version),
+                            ..Default::default()
+                        };
+                        match persistence.update_execution("run-counter", &update).await {
+                            Ok(()) => break,
+                            Err(e) if e.is_conflict() => tokio::task::yield_now().await,
+                            Err(e) => panic!("{e}"),
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    for w in writers {
+        w.await.unwrap();
+    }
+
+    let (context, version, _) = snapshot(&h.persistence, "run-counter").await;
+    assert_eq!(context["count"], 40);
+    assert_eq!(version, 41);
+}
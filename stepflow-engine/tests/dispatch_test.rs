@@ -0,0 +1,128 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use common::Harness;
+use stepflow_dto::dto::queue_task::QueueTaskDto;
+use stepflow_match::queue::PersistentStore;
+use stepflow_match::service::{
+    DispatchPolicy, Dispatcher, MatchService, MemoryMatchService, PersistentMatchService,
+};
+
+fn task(run_id: &str, state: &str, template: Option<&str>, priority: u8) -> QueueTaskDto {
+    QueueTaskDto {
+        task_id: format!("{run_id}/{state}"),
+        run_id: run_id.into(),
+        state_name: state.into(),
+        resource: "http".into(),
+        task_queue: "http".into(),
+        root_run_id: run_id.split("::").next().unwrap().into(),
+        template_id: template.map(str::to_string),
+        worker_id: None,
+        task_payload: None,
+        status: "pending".into(),
+        attempts: 0,
+        max_attempts: 3,
+        priority: Some(priority),
+        timeout_seconds: None,
+        heartbeat_seconds: None,
+        last_heartbeat_at: None,
+        error_message: None,
+        last_error_at: None,
+        next_retry_at: None,
+        queued_at: Utc::now(),
+        processing_at: None,
+        completed_at: None,
+        failed_at: None,
+    }
+}
+
+/// 按顺序入队后逐个取出，返回派发顺序（run_id/state_name）
+async fn dispatch_order(service: Arc<dyn MatchService>) -> Vec<String> {
+    let tasks = vec![
+        task("noisy", "A", Some("bulk"), 0),
+        task("noisy::Map[0]", "B", Some("bulk"), 0),
+        task("noisy::Map[1]", "B", Some("bulk"), 0),
+        task("noisy::Map[2]", "B", Some("bulk"), 0),
+        task("quiet", "A", Some("bulk"), 0),
+        task("vip", "A", Some("billing"), 0),
+        task("vip", "B", Some("billing"), 0),
+        task("urgent", "A", None, 9),
+    ];
+    for t in tasks {
+        service.enqueue_task("http", t).await.unwrap();
+        // 保证入队时间严格递增，两种实现的先后判定一致
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let mut order = Vec::new();
+    while let Some(t) = service
+        .take_task_from(&["http".into()], "worker-1", Duration::from_millis(20))
+        .await
+    {
+        order.push(format!("{}/{}", t.run_id, t.state_name));
+    }
+    order
+}
+
+fn dispatcher() -> Arc<Dispatcher> {
+    Arc::new(Dispatcher::new(DispatchPolicy {
+        template_weights: [("billing".to_string(), 2)].into(),
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn test_memory_and_persistent_dispatch_in_same_order() {
+    let h = Harness::new().await;
+    let memory = MemoryMatchService::with_dispatcher(dispatcher());
+    let persistent = PersistentMatchService::with_dispatcher(
+        Arc::new(PersistentStore::new(h.persistence.clone())),
+        h.persistence.clone(),
+        dispatcher(),
+    );
+
+    let from_memory = dispatch_order(memory).await;
+    let from_persistent = dispatch_order(persistent).await;
+    assert_eq!(from_memory, from_persistent);
+
+    // 高优先级最先；noisy 的分支不会挡住 quiet；billing（权重 2）每轮派发两次
+    assert_eq!(
+        from_memory,
+        [
+            "urgent/A",
+            "noisy/A",
+            "vip/A",
+            "vip/B",
+            "quiet/A",
+            "noisy::Map[0]/B",
+            "noisy::Map[1]/B",
+            "noisy::Map[2]/B",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limited_queue_defers_dispatch() {
+    let h = Harness::new().await;
+    let limited = Arc::new(Dispatcher::new(DispatchPolicy {
+        rate_limits: [("http".to_string(), 1.0)].into(),
+        ..Default::default()
+    }));
+    let service = PersistentMatchService::with_dispatcher(
+        Arc::new(PersistentStore::new(h.persistence.clone())),
+        h.persistence.clone(),
+        limited,
+    );
+    for run in ["r1", "r2"] {
+        service.enqueue_task("http", task(run, "A", None, 0)).await.unwrap();
+    }
+
+    let queues = ["http".to_string()];
+    assert!(service.take_task_from(&queues, "w", Duration::from_millis(10)).await.is_some());
+    // 每秒 1 个：令牌耗尽后拿不到，补回令牌后才能拿到
+    assert!(service.take_task_from(&queues, "w", Duration::from_millis(10)).await.is_none());
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    assert!(service.take_task_from(&queues, "w", Duration::from_millis(10)).await.is_some());
+}
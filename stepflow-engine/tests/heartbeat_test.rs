@@ -0,0 +1,96 @@
+mod common;
+
+use std::time::Duration;
+
+use chrono::Utc;
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+use stepflow_match::service::ExpiredTask;
+
+/// 与 stepflow-core 的回收器一致：把过期任务转成 TaskFailed 信号
+fn timeout_signal(e: &ExpiredTask) -> ExecutionSignal {
+    ExecutionSignal::TaskFailed {
+        run_id: e.task.run_id.clone(),
+        state_name: e.task.state_name.clone(),
+        error: e.reason.clone(),
+        error_type: Some(e.error_type.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_expired_tasks_are_retried_then_caught() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "heartbeatSeconds": 5,
+                "executionConfig": { "timeout_seconds": 60 },
+                "retry": [
+                    { "errorEquals": ["States.HeartbeatTimeout"], "intervalSeconds": 0, "maxAttempts": 1 }
+                ],
+                "catch": [{ "errorEquals": ["States.Timeout"], "next": "Recover", "resultPath": "$.timeout" }],
+                "end": true
+            },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-heartbeat", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let task = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("task enqueued");
+    assert_eq!(task.heartbeat_seconds, Some(5));
+    assert_eq!(task.timeout_seconds, Some(60));
+
+    // 心跳刷新后，心跳窗口内不回收
+    h.match_service.heartbeat("run-heartbeat", "Call").await.unwrap();
+    let now = Utc::now();
+    assert!(h.match_service.reap_expired_tasks(now + chrono::Duration::seconds(3)).await.unwrap().is_empty());
+
+    // 心跳超时 → 按 Retry 重新入队
+    let expired = h
+        .match_service
+        .reap_expired_tasks(now + chrono::Duration::seconds(10))
+        .await
+        .unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].error_type, "States.HeartbeatTimeout");
+    assert!(h.match_service.heartbeat("run-heartbeat", "Call").await.is_err());
+
+    engine.get_signal_sender().unwrap().send(timeout_signal(&expired[0])).unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+
+    let retry = h
+        .match_service
+        .take_task("http", "worker-2", Duration::from_millis(10))
+        .await
+        .expect("task re-enqueued");
+    assert_eq!(retry.attempts, 1);
+
+    // 整体超时优先于心跳超时 → 交给 Catch
+    h.match_service.heartbeat("run-heartbeat", "Call").await.unwrap();
+    let expired = h
+        .match_service
+        .reap_expired_tasks(Utc::now() + chrono::Duration::seconds(61))
+        .await
+        .unwrap();
+    assert_eq!(expired[0].error_type, "States.Timeout");
+
+    engine.get_signal_sender().unwrap().send(timeout_signal(&expired[0])).unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.current_state, "Recover");
+    assert_eq!(engine.context["timeout"]["Error"], "States.Timeout");
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::Harness;
+use serde_json::{json, Value};
+use stepflow_dsl::WorkflowDSL;
+use stepflow_dto::dto::engine_event::EngineEvent;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::history::load_history;
+use stepflow_engine::engine::replay::{ReplayError, Replayer};
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+
+fn routing_dsl(target: &str) -> Value {
+    json!({
+        "startAt": "Route",
+        "states": {
+            "Route": {
+                "type": "choice",
+                "choices": [
+                    { "condition": { "variable": "$.kind", "operator": "StringEquals", "value": "http" }, "next": target }
+                ],
+                "defaultNext": "Done"
+            },
+            "Call": { "type": "task", "resource": "http", "next": "Done" },
+            "Other": { "type": "task", "resource": "http", "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_history_rebuilds_engine_and_links_events() {
+    let h = Harness::new().await;
+    let input = json!({ "kind": "http" });
+    let mut engine = h.engine("run-history", routing_dsl("Call"), input.clone(), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    assert_eq!(engine.current_state, "Call");
+
+    let events = load_history(&h.persistence, "run-history").await.unwrap();
+    let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(
+        types,
+        vec!["WorkflowStarted", "NodeEnter", "NodeExit", "NodeEnter", "NodeDispatched"]
+    );
+    let ids: Vec<i64> = events.iter().map(|e| e.event_id).collect();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    // NodeEnter(Route) ← WorkflowStarted，NodeExit(Route) ← NodeEnter(Route)，NodeEnter(Call) ← NodeExit(Route)
+    let parents: Vec<Option<i64>> = events.iter().map(|e| e.parent_event_id).collect();
+    assert_eq!(parents, vec![None, Some(1), Some(2), Some(3), Some(4)]);
+    let exit: EngineEvent = serde_json::from_str(events[2].attributes.as_deref().unwrap()).unwrap();
+    assert!(matches!(exit, EngineEvent::NodeExit { next_state: Some(ref n), .. } if n == "Call"));
+
+    // 回放结果与引擎内存状态一致
+    let dsl = engine.dsl.clone();
+    let replayed = Replayer::new(&dsl, WorkflowMode::Deferred).replay(&events).unwrap();
+    assert_eq!(replayed.current_state, "Call");
+    assert_eq!(replayed.context, input);
+    assert!(replayed.awaiting_signal);
+    assert!(!replayed.finished);
+    drop(engine);
+
+    let mut restored = WorkflowEngine::restore_with_dsl_from_history(
+        "run-history".into(),
+        dsl.clone(),
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap();
+    // 已挂起等待回调，不会重复入队
+    restored.advance_until_blocked().await.unwrap();
+    assert_eq!(load_history(&h.persistence, "run-history").await.unwrap().len(), 5);
+
+    restored
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: "run-history".into(),
+            state_name: "Call".into(),
+            output: json!({ "status": 200 }),
+        })
+        .unwrap();
+    restored.handle_next_signal().await.unwrap();
+    restored.advance_until_blocked().await.unwrap();
+    assert!(restored.finished);
+
+    // 编号在恢复后继续递增，完整历史可回放到结束状态
+    let events = load_history(&h.persistence, "run-history").await.unwrap();
+    assert!(events.windows(2).all(|w| w[1].event_id == w[0].event_id + 1));
+    let replayed = Replayer::new(&dsl, WorkflowMode::Deferred).replay(&events).unwrap();
+    assert!(replayed.finished);
+    assert_eq!(replayed.context, restored.context);
+}
+
+#[tokio::test]
+async fn test_replay_detects_changed_template() {
+    let h = Harness::new().await;
+    let mut engine = h
+        .engine("run-nondet", routing_dsl("Call"), json!({ "kind": "http" }), WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+    drop(engine);
+
+    // Choice 现在会转到另一个状态
+    let changed: WorkflowDSL = serde_json::from_value(routing_dsl("Other")).unwrap();
+    let events = load_history(&h.persistence, "run-nondet").await.unwrap();
+    let err = Replayer::new(&changed, WorkflowMode::Deferred).replay(&events).unwrap_err();
+    assert!(matches!(err, ReplayError::NonDeterministic { event_id: 3, .. }), "{err}");
+
+    let restored = WorkflowEngine::restore_with_dsl_from_history(
+        "run-nondet".into(),
+        changed,
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await;
+    assert!(restored.is_err());
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+/// iterator：`ok == true` 的 item 打上 `done` 标记，否则 Choice 无匹配而失败
+fn map_dsl(tolerated: Option<f64>) -> Value {
+    json!({
+        "startAt": "Each",
+        "states": {
+            "Each": {
+                "type": "map",
+                "itemsPath": "$.items",
+                "maxConcurrency": 2,
+                "toleratedFailurePercentage": tolerated,
+                "iterator": {
+                    "startAt": "Check",
+                    "states": {
+                        "Check": {
+                            "type": "choice",
+                            "choices": [
+                                {
+                                    "condition": { "variable": "$.ok", "operator": "Equals", "value": true },
+                                    "next": "Mark"
+                                }
+                            ]
+                        },
+                        "Mark": {
+                            "type": "pass",
+                            "outputMapping": constant_output("done", json!(true)),
+                            "end": true
+                        }
+                    }
+                },
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+fn items(oks: &[bool]) -> Value {
+    json!({ "items": oks.iter().enumerate().map(|(i, ok)| json!({ "id": i, "ok": ok })).collect::<Vec<_>>() })
+}
+
+#[tokio::test]
+async fn test_map_inline_collects_in_input_order() {
+    let h = Harness::new().await;
+    let mut engine = h
+        .engine("run-map-inline", map_dsl(None), items(&[true, true, true]), WorkflowMode::Inline)
+        .await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert_eq!(
+        out,
+        json!([
+            { "id": 0, "ok": true, "done": true },
+            { "id": 1, "ok": true, "done": true },
+            { "id": 2, "ok": true, "done": true }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_map_tolerates_failures_up_to_percentage() {
+    let h = Harness::new().await;
+    let mut engine = h
+        .engine(
+            "run-map-tolerated",
+            map_dsl(Some(50.0)),
+            items(&[true, false, true, true]),
+            WorkflowMode::Inline,
+        )
+        .await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert_eq!(out[0]["done"], json!(true));
+    assert_eq!(out[1]["Error"], "ChoiceNoMatch");
+    assert!(out[1]["Cause"].as_str().unwrap().contains("No matching choice"));
+    assert_eq!(out[3]["done"], json!(true));
+}
+
+#[tokio::test]
+async fn test_map_fails_when_failures_exceed_tolerance() {
+    let h = Harness::new().await;
+    let mut engine = h
+        .engine(
+            "run-map-intolerant",
+            map_dsl(Some(25.0)),
+            items(&[false, true, false, true]),
+            WorkflowMode::Inline,
+        )
+        .await;
+
+    let err = engine.run_inline().await.unwrap_err();
+
+    assert!(err.contains("2 of 4 branch(es) failed (tolerated 1)"), "{err}");
+}
+
+#[tokio::test]
+async fn test_map_rejects_non_array_items_path() {
+    let h = Harness::new().await;
+    let mut engine = h
+        .engine("run-map-bad-items", map_dsl(None), json!({ "items": {} }), WorkflowMode::Inline)
+        .await;
+
+    let err = engine.run_inline().await.unwrap_err();
+
+    assert!(err.contains("must select an array"), "{err}");
+}
+
+#[tokio::test]
+async fn test_map_deferred_respects_max_concurrency() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Each",
+        "states": {
+            "Each": {
+                "type": "map",
+                "itemsPath": "$.items",
+                "maxConcurrency": 1,
+                "iterator": {
+                    "startAt": "Work",
+                    "states": { "Work": { "type": "task", "resource": "http", "end": true } }
+                },
+                "end": true
+            }
+        }
+    });
+    let mut engine = h
+        .engine("run-map-deferred", dsl, json!({ "items": [1, 2] }), WorkflowMode::Deferred)
+        .await;
+
+    engine.advance_until_blocked().await.unwrap();
+    let second = engine.child_run_id(1);
+    assert!(h.persistence.get_execution(&second).await.unwrap().is_none());
+
+    for index in 0..2 {
+        let signal = ExecutionSignal::TaskCompleted {
+            run_id: engine.child_run_id(index),
+            state_name: "Work".into(),
+            output: json!({ "n": index }),
+        };
+        engine.get_signal_sender().unwrap().send(signal).unwrap();
+        engine.handle_next_signal().await.unwrap();
+        engine.advance_until_blocked().await.unwrap();
+    }
+
+    assert!(engine.finished);
+    assert_eq!(engine.context, json!([{ "n": 0 }, { "n": 1 }]));
+}
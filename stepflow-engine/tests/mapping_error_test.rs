@@ -0,0 +1,102 @@
+mod common;
+
+use std::time::Duration;
+
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+#[tokio::test]
+async fn test_required_rule_failure_caught_as_mapping_error() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Shape",
+        "states": {
+            "Shape": {
+                "type": "pass",
+                "inputMapping": {
+                    "debug": true,
+                    "mappings": [{ "key": "id", "type": "jsonPath", "source": "$.order.id", "required": true }]
+                },
+                "catch": [
+                    { "errorEquals": ["States.Runtime"], "next": "Wrong" },
+                    { "errorEquals": ["States.MappingError"], "next": "Recover", "resultPath": "$.failure" }
+                ],
+                "end": true
+            },
+            "Wrong": { "type": "fail", "error": "Wrong.Branch" },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-map-err-inline", dsl, json!({ "x": 1 }), WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert_eq!(out["failure"]["Error"], "States.MappingError");
+    assert!(out["failure"]["Cause"].as_str().unwrap().contains("mapping rule 'id' failed"));
+
+    let state = h.persistence.get_state("run-map-err-inline:Shape").await.unwrap().unwrap();
+    assert_eq!(state.status, "FAILED");
+    let trace = state.mapping_trace.expect("debug trace recorded");
+    assert_eq!(trace[0]["stage"], "InputMapping");
+    assert!(trace[0]["error"].as_str().unwrap().contains("resolved to null"));
+}
+
+#[tokio::test]
+async fn test_deferred_output_mapping_failure_caught_with_trace() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "inputMapping": {
+                    "debug": true,
+                    "mappings": [{ "key": "orderId", "type": "jsonPath", "source": "$.order.id" }]
+                },
+                "outputMapping": {
+                    "debug": true,
+                    "onError": "fail",
+                    "mappings": [{ "key": "status", "type": "expr", "transform": "1 +" }]
+                },
+                "catch": [{ "errorEquals": ["States.MappingError"], "next": "Recover", "resultPath": "$.failure" }],
+                "end": true
+            },
+            "Recover": { "type": "succeed" }
+        }
+    });
+    let mut engine = h
+        .engine("run-map-err-deferred", dsl, json!({ "order": { "id": 7 } }), WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+    h.match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("task enqueued");
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: "run-map-err-deferred".into(),
+            state_name: "Call".into(),
+            output: json!({ "status": 200 }),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.context["failure"]["Error"], "States.MappingError");
+
+    // 入队时的输入映射快照 + 回调时失败的输出映射
+    let state = h.persistence.get_state("run-map-err-deferred:Call").await.unwrap().unwrap();
+    let trace = state.mapping_trace.expect("debug trace recorded");
+    assert_eq!(trace[0]["stage"], "InputMapping");
+    assert_eq!(trace[0]["steps"][0]["key"], "orderId");
+    assert_eq!(trace[0]["output"], json!({ "orderId": 7 }));
+    assert_eq!(trace[1]["stage"], "OutputMapping");
+    assert!(trace[1]["error"].as_str().unwrap().contains("mapping rule 'status' failed"));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use std::time::Duration;
+
+use chrono::Utc;
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::{history::load_history, outbox::relay_unpublished, WorkflowMode};
+use stepflow_storage::entities::{
+    outbox::StoredOutboxMessage, step_commit::StepCommit,
+    workflow_execution::UpdateStoredWorkflowExecution,
+};
+
+fn task_dsl() -> Value {
+    json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "outputMapping": constant_output("called", json!(true)),
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+async fn unpublished(h: &Harness) -> Vec<StoredOutboxMessage> {
+    h.persistence
+        .find_unpublished_outbox(Utc::now().naive_utc() + chrono::Duration::seconds(1), 100)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_suspended_step_commits_then_enqueues() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-outbox", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 状态记录、游标与历史在同一步提交
+    let state = h.persistence.get_state("run-outbox:Call").await.unwrap().unwrap();
+    assert_eq!(state.status, "STARTED");
+    let execution = h.persistence.get_execution("run-outbox").await.unwrap().unwrap();
+    assert_eq!(execution.current_state_name.as_deref(), Some("Call"));
+    assert_eq!(execution.version, 2);
+    assert_eq!(load_history(&h.persistence, "run-outbox").await.unwrap().len(), 3);
+
+    // 提交后投递：任务已入队，outbox 全部标记为已发布
+    let task = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("task enqueued after commit");
+    assert_eq!(task.state_name, "Call");
+    assert!(unpublished(&h).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_conflicting_step_rolls_back_history() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-rollback", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 外部写入使引擎持有的版本过期
+    let version = h.persistence.get_execution("run-rollback").await.unwrap().unwrap().version;
+    h.persistence
+        .update_execution(
+            "run-rollback",
+            &UpdateStoredWorkflowExecution {
+                memo: Some(Some("touched".into())),
+                version: Some(version),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: "run-rollback".into(),
+            state_name: "Call".into(),
+            output: json!({ "status": 200 }),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(engine.finished);
+
+    // 冲突的那一步整体回滚：事件编号连续，NodeSuccess 只记录一次
+    let history = load_history(&h.persistence, "run-rollback").await.unwrap();
+    let ids: Vec<i64> = history.iter().map(|e| e.event_id).collect();
+    assert_eq!(ids, (1..=history.len() as i64).collect::<Vec<_>>());
+    assert_eq!(history.iter().filter(|e| e.event_type == "NodeSuccess").count(), 1);
+    assert!(unpublished(&h).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_relay_publishes_messages_left_unpublished() {
+    let h = Harness::new().await;
+    h.engine("run-relay", task_dsl(), json!({}), WorkflowMode::Deferred).await;
+
+    // 模拟提交后、投递前进程退出：消息只写入了 outbox
+    let mut commit = StepCommit::new("run-relay");
+    commit.outbox.push(StoredOutboxMessage {
+        id: 0,
+        run_id: "run-relay".into(),
+        topic: "task".into(),
+        payload: json!({
+            "queue": "http",
+            "task": {
+                "task_id": "",
+                "run_id": "run-relay",
+                "state_name": "Call",
+                "resource": "http",
+                "status": "pending",
+                "attempts": 0,
+                "max_attempts": 0,
+                "queued_at": Utc::now(),
+            }
+        }),
+        created_at: Utc::now().naive_utc(),
+        published_at: None,
+    });
+    h.persistence.commit_step(&commit).await.unwrap();
+    assert_eq!(unpublished(&h).await.len(), 1);
+
+    let relay = || relay_unpublished(&h.persistence, &h.dispatcher, &h.registry, chrono::Duration::zero(), 10);
+    assert_eq!(relay().await.unwrap(), 1);
+    assert_eq!(relay().await.unwrap(), 0);
+
+    let task = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("task relayed");
+    assert_eq!(task.run_id, "run-relay");
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+fn parallel_dsl(branch_state: Value, max_concurrency: Option<u32>) -> Value {
+    json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "maxConcurrency": max_concurrency,
+                "branches": [
+                    {
+                        "startAt": "A",
+                        "states": { "A": branch_state_with(&branch_state, "a", 1) }
+                    },
+                    {
+                        "startAt": "B",
+                        "states": { "B": branch_state_with(&branch_state, "b", 2) }
+                    }
+                ],
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+fn branch_state_with(template: &Value, key: &str, value: i64) -> Value {
+    let mut state = template.clone();
+    if state["type"] == "pass" {
+        state["outputMapping"] = constant_output(key, json!(value));
+    }
+    state
+}
+
+#[tokio::test]
+async fn test_parallel_inline_joins_in_branch_order() {
+    let h = Harness::new().await;
+    let dsl = parallel_dsl(json!({ "type": "pass", "end": true }), Some(1));
+    let mut engine = h.engine("run-par-inline", dsl, json!({ "x": 0 }), WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(out, json!([{ "x": 0, "a": 1 }, { "x": 0, "b": 2 }]));
+
+    let progress = h
+        .persistence
+        // 首个状态的本轮尝试以 WorkflowStarted（event 1）为起点
+        .get_state("run-par-inline:Fan@1[1]")
+        .await
+        .unwrap()
+        .expect("branch progress recorded");
+    assert_eq!(progress.status, "COMPLETED");
+    assert_eq!(progress.output, Some(json!({ "x": 0, "b": 2 })));
+}
+
+#[tokio::test]
+async fn test_parallel_deferred_waits_for_all_branches() {
+    let h = Harness::new().await;
+    let dsl = parallel_dsl(json!({ "type": "task", "resource": "http", "end": true }), None);
+    let mut engine = h.engine("run-par-deferred", dsl, json!({}), WorkflowMode::Deferred).await;
+
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Fan");
+
+    for (index, state_name, output) in [(1, "B", json!({ "b": 2 })), (0, "A", json!({ "a": 1 }))] {
+        let signal = ExecutionSignal::TaskCompleted {
+            run_id: engine.child_run_id(index),
+            state_name: state_name.into(),
+            output,
+        };
+        engine.get_signal_sender().unwrap().send(signal).unwrap();
+        engine.handle_next_signal().await.unwrap();
+        engine.advance_until_blocked().await.unwrap();
+    }
+
+    assert!(engine.finished);
+    assert_eq!(engine.context, json!([{ "a": 1 }, { "b": 2 }]));
+}
+
+#[tokio::test]
+async fn test_parallel_reentered_by_choice_loop_runs_fresh_branches() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [{
+                    "startAt": "Echo",
+                    "states": {
+                        "Echo": {
+                            "type": "pass",
+                            "inputMapping": { "mappings": [{ "key": "again", "type": "jsonPath", "source": "$.again" }] },
+                            "end": true
+                        }
+                    }
+                }],
+                "resultPath": "$.seen",
+                "next": "Loop"
+            },
+            "Loop": {
+                "type": "choice",
+                "choices": [
+                    { "condition": { "variable": "$.again", "operator": "Equals", "value": true }, "next": "Done" }
+                ],
+                "defaultNext": "Again"
+            },
+            "Again": {
+                "type": "pass",
+                "outputMapping": { "preserve": "all", "mappings": [{ "key": "again", "type": "constant", "value": true }] },
+                "next": "Fan"
+            },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-par-loop", dsl, json!({}), WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    // 第二次进入时分支重新运行，而不是复用第一次的输出
+    assert_eq!(out["seen"][0]["again"], json!(true));
+    let children = h.persistence.find_executions_by_parent("run-par-loop", 10, 0).await.unwrap();
+    assert_eq!(children.len(), 2);
+    assert!(children.iter().all(|c| c.status == "COMPLETED"));
+}
+
+#[tokio::test]
+async fn test_failed_branch_revokes_sibling_queued_task() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [
+                    { "startAt": "A", "states": { "A": { "type": "task", "resource": "http", "end": true } } },
+                    { "startAt": "B", "states": { "B": { "type": "fail", "error": "Custom.Boom", "cause": "boom" } } }
+                ],
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-par-revoke", dsl, json!({}), WorkflowMode::Deferred).await;
+
+    // 分支 A 的任务已入队，分支 B 失败且无 Retry / Catch：Parallel 失败，A 被取消且任务随之撤销
+    assert!(engine.advance_until_blocked().await.is_err());
+    assert!(engine.finished);
+
+    let sibling = h.persistence.get_execution(&engine.child_run_id(0)).await.unwrap().unwrap();
+    assert_eq!(sibling.status, "CANCELLED");
+    let task = h
+        .match_service
+        .take_task_from(&["http".into()], "worker-1", std::time::Duration::from_millis(50))
+        .await;
+    assert!(task.is_none(), "sibling task was not revoked: {task:?}");
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::Duration;
+
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+#[tokio::test]
+async fn test_inline_task_parameters_and_result_paths() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Greet",
+        "states": {
+            "Greet": {
+                "type": "task",
+                "resource": "shell",
+                "inputPath": "$.user",
+                "parameters": { "command.$": "States.Format('printf {}', $.name)" },
+                "resultSelector": { "text.$": "$.stdout", "code.$": "$.exit_code" },
+                "resultPath": "$.user.greeting",
+                "next": "Flag"
+            },
+            "Flag": {
+                "type": "pass",
+                "result": { "ok": true },
+                "resultPath": "$.user.flags",
+                "outputPath": "$.user",
+                "end": true
+            }
+        }
+    });
+    let input = json!({ "user": { "name": "ann" }, "other": 1 });
+    let mut engine = h.engine("run-params-inline", dsl, input, WorkflowMode::Inline).await;
+
+    let out = engine.run_inline().await.unwrap();
+
+    assert_eq!(
+        out,
+        json!({
+            "name": "ann",
+            "greeting": { "text": "ann", "code": 0 },
+            "flags": { "ok": true }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_deferred_task_payload_and_result_selector() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "parameters": {
+                    "url.$": "States.Format('https://api.example.com/orders/{}', $.order.id)",
+                    "method": "GET",
+                    "requestId.$": "States.Hash($.order.id, 'MD5')"
+                },
+                "resultSelector": { "status.$": "$.status", "items.$": "States.ArrayLength($.body.items)" },
+                "resultPath": "$.response",
+                "end": true
+            }
+        }
+    });
+    let mut engine = h
+        .engine("run-params-deferred", dsl, json!({ "order": { "id": 42 } }), WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 队列任务载荷：映射后的输入 + 求值后的 parameters
+    let task = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("task enqueued");
+    assert_eq!(
+        task.task_payload.unwrap(),
+        json!({
+            "resource": "http",
+            "input": { "order": { "id": 42 } },
+            "parameters": {
+                "url": "https://api.example.com/orders/42",
+                "method": "GET",
+                "requestId": "a1d0c6e83f027327d8461063f4ac58a6"
+            }
+        })
+    );
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: "run-params-deferred".into(),
+            state_name: "Call".into(),
+            output: json!({ "status": 200, "body": { "items": [1, 2, 3] } }),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(
+        engine.context,
+        json!({ "order": { "id": 42 }, "response": { "status": 200, "items": 3 } })
+    );
+}
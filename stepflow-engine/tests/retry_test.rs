@@ -0,0 +1,203 @@
+mod common;
+
+use std::time::Duration;
+
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+
+#[tokio::test]
+async fn test_inline_task_retries_until_max_attempts() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "no-such-tool",
+                "retry": [
+                    { "errorEquals": ["States.TaskFailed"], "intervalSeconds": 0, "maxAttempts": 2 }
+                ],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-retry-inline", dsl, json!({}), WorkflowMode::Inline).await;
+
+    let err = engine.run_inline().await.unwrap_err();
+    assert!(err.contains("Tool not found"), "{err}");
+
+    let state = h
+        .persistence
+        .get_state("run-retry-inline:Call")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.attempts, 2);
+    assert_eq!(state.error_details.as_deref(), Some("States.TaskFailed"));
+}
+
+#[tokio::test]
+async fn test_deferred_task_failure_is_requeued_with_next_retry_at() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "retry": [
+                    { "errorEquals": ["Http.Timeout"], "intervalSeconds": 60, "maxAttempts": 1 }
+                ],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-retry-deferred", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let first = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("first attempt enqueued");
+    assert_eq!(first.attempts, 0);
+    assert_eq!(first.max_attempts, 1);
+
+    let fail = |error_type: &str| ExecutionSignal::TaskFailed {
+        run_id: "run-retry-deferred".into(),
+        state_name: "Call".into(),
+        error: "upstream timed out".into(),
+        error_type: Some(error_type.into()),
+    };
+
+    engine.get_signal_sender().unwrap().send(fail("Http.Timeout")).unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+
+    // 重试任务要等到 next_retry_at 才能被取走
+    assert!(h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .is_none());
+
+    let state = h
+        .persistence
+        .get_state("run-retry-deferred:Call")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.status, "RETRYING");
+    assert_eq!(state.attempts, 1);
+
+    // 次数耗尽后失败
+    engine.get_signal_sender().unwrap().send(fail("Http.Timeout")).unwrap();
+    let err = engine.handle_next_signal().await.unwrap_err();
+    assert!(err.contains("upstream timed out"), "{err}");
+}
+
+#[tokio::test]
+async fn test_inline_parallel_retries_with_fresh_branches() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [{
+                    "startAt": "Boom",
+                    "states": { "Boom": { "type": "fail", "error": "Branch.Broken", "cause": "boom" } }
+                }],
+                "retry": [{ "errorEquals": ["Branch.Broken"], "intervalSeconds": 0, "maxAttempts": 2 }],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-retry-fan", dsl, json!({}), WorkflowMode::Inline).await;
+
+    let err = engine.run_inline().await.unwrap_err();
+    assert!(err.contains("boom"), "{err}");
+
+    let state = h.persistence.get_state("run-retry-fan:Fan").await.unwrap().unwrap();
+    assert_eq!(state.attempts, 2);
+    // 每次尝试都启动新一轮分支子执行
+    let children = h.persistence.find_executions_by_parent("run-retry-fan", 10, 0).await.unwrap();
+    assert_eq!(children.len(), 3);
+}
+
+#[tokio::test]
+async fn test_deferred_parallel_retry_waits_for_timer() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [{
+                    "startAt": "Call",
+                    "states": { "Call": { "type": "task", "resource": "http", "end": true } }
+                }],
+                "retry": [{ "errorEquals": ["Http.Timeout"], "intervalSeconds": 0, "maxAttempts": 1 }],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-retry-fan-deferred", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    let first_child = engine.child_run_id(0);
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskFailed {
+            run_id: first_child.clone(),
+            state_name: "Call".into(),
+            error: "upstream timed out".into(),
+            error_type: Some("Http.Timeout".into()),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+
+    let state = h.persistence.get_state("run-retry-fan-deferred:Fan").await.unwrap().unwrap();
+    assert_eq!((state.status.as_str(), state.attempts), ("RETRYING", 1));
+    let timers = h
+        .persistence
+        .find_timers_before(chrono::Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert!(timers.iter().any(|t| t.state_name.as_deref() == Some("Fan") && t.payload.is_none()));
+
+    // 重试定时器到期后启动新一轮分支
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimerFired {
+            run_id: "run-retry-fan-deferred".into(),
+            state_name: "Fan".into(),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    let second_child = engine.child_run_id(0);
+    assert_ne!(second_child, first_child);
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: second_child,
+            state_name: "Call".into(),
+            output: json!({ "status": 200 }),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.context, json!([{ "status": 200 }]));
+}
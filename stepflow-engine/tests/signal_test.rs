@@ -0,0 +1,159 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::{history::load_history, WorkflowEngine, WorkflowMode};
+
+fn callback_dsl() -> Value {
+    json!({
+        "startAt": "Approve",
+        "states": {
+            "Approve": {
+                "type": "task",
+                "resource": "approval",
+                "waitForTaskToken": true,
+                "retry": [{ "errorEquals": ["Approval.Retry"], "maxAttempts": 1, "intervalSeconds": 0 }],
+                "next": "Done"
+            },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+fn signal_dsl() -> Value {
+    json!({
+        "startAt": "Hold",
+        "states": {
+            "Hold": { "type": "waitForSignal", "signalName": "resume", "next": "After" },
+            "After": { "type": "pass", "outputMapping": constant_output("after", json!(true)), "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+fn received(run_id: &str, name: &str, payload: Value) -> ExecutionSignal {
+    ExecutionSignal::SignalReceived {
+        run_id: run_id.into(),
+        signal_name: name.into(),
+        payload,
+    }
+}
+
+async fn send(engine: &mut WorkflowEngine, signal: ExecutionSignal) -> Result<(), String> {
+    engine.get_signal_sender().unwrap().send(signal).unwrap();
+    engine.handle_next_signal().await?;
+    engine.advance_until_blocked().await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_callback_task_issues_token_and_resumes_on_completion() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-token", callback_dsl(), json!({ "order": 7 }), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+
+    // token 暴露在 context 中，并以 RUNNING 记录在 activity_tasks
+    let token = engine.context["taskToken"].as_str().unwrap().to_string();
+    let task = h.persistence.get_task(&token).await.unwrap().unwrap();
+    assert_eq!(task.status, "RUNNING");
+    assert_eq!(task.run_id, "run-token");
+    assert_eq!(task.state_name.as_deref(), Some("Approve"));
+    assert_eq!(task.input.unwrap()["taskToken"], json!(token));
+
+    // 回调任务不派发给 worker
+    assert!(h
+        .match_service
+        .take_task("approval", "worker-1", Duration::from_millis(10))
+        .await
+        .is_none());
+
+    send(
+        &mut engine,
+        ExecutionSignal::TaskCompleted {
+            run_id: "run-token".into(),
+            state_name: "Approve".into(),
+            output: json!({ "approved": true }),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(engine.finished);
+    assert_eq!(engine.context["approved"], true);
+    assert_eq!(engine.context["order"], 7);
+}
+
+#[tokio::test]
+async fn test_callback_task_retry_issues_fresh_token() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-token-retry", callback_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    let first = engine.context["taskToken"].clone();
+
+    send(
+        &mut engine,
+        ExecutionSignal::TaskFailed {
+            run_id: "run-token-retry".into(),
+            state_name: "Approve".into(),
+            error: "try again".into(),
+            error_type: Some("Approval.Retry".into()),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(!engine.finished);
+
+    // 重试签发新 token，并与 context 一起持久化
+    let second = engine.context["taskToken"].clone();
+    assert_ne!(first, second);
+    let row = h.persistence.get_execution("run-token-retry").await.unwrap().unwrap();
+    assert_eq!(row.context_snapshot.unwrap()["taskToken"], second);
+    let task = h.persistence.get_task(second.as_str().unwrap()).await.unwrap().unwrap();
+    assert_eq!(task.attempt, 1);
+}
+
+#[tokio::test]
+async fn test_wait_for_signal_resumes_with_payload() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-signal", signal_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Hold");
+
+    // 从持久化状态恢复后仍在等待该信号
+    let mut restored = WorkflowEngine::restore_with_dsl(
+        "run-signal".into(),
+        engine.dsl.clone(),
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap();
+    send(&mut restored, received("run-signal", "resume", json!({ "ok": 1 }))).await.unwrap();
+
+    assert!(restored.finished);
+    assert_eq!(restored.context, json!({ "ok": 1, "after": true }));
+    let history = load_history(&h.persistence, "run-signal").await.unwrap();
+    assert!(history.iter().any(|e| e.event_type == "SignalAwaited"));
+}
+
+#[tokio::test]
+async fn test_unexpected_signal_is_rejected() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-signal-bad", signal_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 信号名不匹配：拒绝且不推进
+    let err = send(&mut engine, received("run-signal-bad", "other", Value::Null)).await.unwrap_err();
+    assert!(err.contains("not waiting for signal 'other'"), "{err}");
+    assert_eq!(engine.current_state, "Hold");
+
+    send(&mut engine, received("run-signal-bad", "resume", Value::Null)).await.unwrap();
+    assert!(engine.finished);
+
+    // 已完成的执行不再接收信号
+    assert!(send(&mut engine, received("run-signal-bad", "resume", Value::Null)).await.is_err());
+}
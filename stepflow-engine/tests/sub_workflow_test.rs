@@ -0,0 +1,235 @@
+mod common;
+
+use chrono::Utc;
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::WorkflowMode;
+use stepflow_storage::entities::workflow_template::StoredWorkflowTemplate;
+use stepflow_storage::entities::workflow_template_revision::{StoredTemplateRevision, REVISION_PUBLISHED};
+
+async fn create_template(h: &Harness, template_id: &str, dsl: Value) {
+    let now = Utc::now().naive_utc();
+    h.persistence
+        .create_template(&StoredWorkflowTemplate {
+            template_id: template_id.into(),
+            name: template_id.into(),
+            description: None,
+            dsl_definition: dsl.to_string(),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap();
+    h.persistence
+        .create_template_revision(&StoredTemplateRevision {
+            template_id: template_id.into(),
+            revision: 1,
+            dsl_definition: dsl.to_string(),
+            status: REVISION_PUBLISHED.into(),
+            created_at: now,
+            published_at: Some(now),
+        })
+        .await
+        .unwrap();
+}
+
+fn parent_dsl(template_id: &str, template_version: Option<i64>) -> Value {
+    json!({
+        "startAt": "Child",
+        "states": {
+            "Child": {
+                "type": "subWorkflow",
+                "templateId": template_id,
+                "templateVersion": template_version,
+                "catch": [{ "errorEquals": ["Child.Broken", "States.Cancelled"], "next": "Recover", "resultPath": "$.failure" }],
+                "next": "Done"
+            },
+            "Recover": { "type": "succeed" },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_sub_workflow_inline_runs_child_template() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-child",
+        json!({
+            "startAt": "Work",
+            "states": {
+                "Work": { "type": "pass", "outputMapping": constant_output("child", json!(true)), "end": true }
+            }
+        }),
+    )
+    .await;
+    let mut engine = h
+        .engine("run-sub-inline", parent_dsl("tpl-child", Some(1)), json!({ "x": 1 }), WorkflowMode::Inline)
+        .await;
+
+    let out = engine.run_inline().await.unwrap();
+    assert!(engine.finished);
+    assert_eq!(out, json!({ "x": 1, "child": true }));
+
+    // 子执行挂在父执行下，并记录引用的模板
+    let children = h.persistence.find_executions_by_parent("run-sub-inline", 10, 0).await.unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].run_id, "run-sub-inline::Child@1[0]");
+    assert_eq!(children[0].template_id.as_deref(), Some("tpl-child"));
+    assert_eq!(children[0].workflow_type, "subworkflow");
+    assert_eq!(children[0].status, "COMPLETED");
+}
+
+#[tokio::test]
+async fn test_sub_workflow_deferred_waits_for_child_task() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-task",
+        json!({
+            "startAt": "Call",
+            "states": { "Call": { "type": "task", "resource": "http", "end": true } }
+        }),
+    )
+    .await;
+    let mut engine = h
+        .engine("run-sub-deferred", parent_dsl("tpl-task", None), json!({}), WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Child");
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCompleted {
+            run_id: engine.child_run_id(0),
+            state_name: "Call".into(),
+            output: json!({ "status": 200 }),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.context, json!({ "status": 200 }));
+}
+
+#[tokio::test]
+async fn test_sub_workflow_failure_is_caught_by_parent() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-broken",
+        json!({
+            "startAt": "Boom",
+            "states": { "Boom": { "type": "fail", "error": "Child.Broken", "cause": "bad input" } }
+        }),
+    )
+    .await;
+    let mut engine = h
+        .engine("run-sub-fail", parent_dsl("tpl-broken", None), json!({}), WorkflowMode::Inline)
+        .await;
+
+    let out = engine.run_inline().await.unwrap();
+    assert_eq!(out["failure"]["Error"], "Child.Broken");
+    let cause = out["failure"]["Cause"].as_str().unwrap();
+    assert!(cause.contains("run-sub-fail::Child@1[0]") && cause.contains("bad input"), "{cause}");
+
+    let child = h.persistence.get_execution("run-sub-fail::Child@1[0]").await.unwrap().unwrap();
+    assert_eq!(child.status, "FAILED");
+}
+
+#[tokio::test]
+async fn test_cancelled_child_surfaces_as_states_cancelled() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-cancel",
+        json!({
+            "startAt": "Call",
+            "states": { "Call": { "type": "task", "resource": "http", "end": true } }
+        }),
+    )
+    .await;
+    let mut engine = h
+        .engine("run-sub-cancel", parent_dsl("tpl-cancel", None), json!({}), WorkflowMode::Deferred)
+        .await;
+    engine.advance_until_blocked().await.unwrap();
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TaskCancelled {
+            run_id: engine.child_run_id(0),
+            state_name: "Call".into(),
+            reason: Some("operator abort".into()),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.current_state, "Recover");
+    assert_eq!(engine.context["failure"]["Error"], "States.Cancelled");
+    let child = h.persistence.get_execution("run-sub-cancel::Child@1[0]").await.unwrap().unwrap();
+    assert_eq!(child.status, "CANCELLED");
+}
+
+#[tokio::test]
+async fn test_parent_failure_cancels_running_child() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-slow",
+        json!({
+            "startAt": "Call",
+            "states": { "Call": { "type": "task", "resource": "http", "end": true } }
+        }),
+    )
+    .await;
+    let dsl = json!({
+        "startAt": "Fan",
+        "states": {
+            "Fan": {
+                "type": "parallel",
+                "branches": [
+                    { "startAt": "Sub", "states": { "Sub": { "type": "subWorkflow", "templateId": "tpl-slow", "end": true } } },
+                    { "startAt": "Boom", "states": { "Boom": { "type": "fail", "error": "Branch.Broken" } } }
+                ],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-sub-abort", dsl, json!({}), WorkflowMode::Deferred).await;
+
+    assert!(engine.advance_until_blocked().await.is_err());
+    assert!(engine.finished);
+
+    // 失败的父执行不再等待子执行：仍在运行的后代被标记为 CANCELLED
+    let parent = h.persistence.get_execution("run-sub-abort").await.unwrap().unwrap();
+    assert_eq!(parent.status, "FAILED");
+    let sub = h.persistence.get_execution("run-sub-abort::Fan@1[0]::Sub@1[0]").await.unwrap().unwrap();
+    assert_eq!(sub.status, "CANCELLED");
+}
+
+#[tokio::test]
+async fn test_sub_workflow_rejects_unknown_template_revision() {
+    let h = Harness::new().await;
+    create_template(
+        &h,
+        "tpl-v1",
+        json!({ "startAt": "Done", "states": { "Done": { "type": "succeed" } } }),
+    )
+    .await;
+    let mut engine = h
+        .engine("run-sub-version", parent_dsl("tpl-v1", Some(2)), json!({}), WorkflowMode::Inline)
+        .await;
+
+    let err = engine.run_inline().await.unwrap_err();
+    assert!(err.contains("revision 2"), "{err}");
+    assert!(h.persistence.find_executions_by_parent("run-sub-version", 10, 0).await.unwrap().is_empty());
+}
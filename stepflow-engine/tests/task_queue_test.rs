@@ -0,0 +1,177 @@
+mod common;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::match_stats::MatchStats;
+use stepflow_engine::engine::WorkflowMode;
+use stepflow_eventbus::impls::local::LocalEventBus;
+use stepflow_match::queue::PersistentStore;
+use stepflow_match::service::{
+    EventDrivenMatchService, HybridMatchService, HybridMatchServiceWithEvent, MatchService,
+    MemoryMatchService, PersistentMatchService, QueuePollers,
+};
+
+fn task_dsl(task_queue: Option<&str>) -> serde_json::Value {
+    let mut call = json!({ "type": "task", "resource": "http", "end": true });
+    if let Some(q) = task_queue {
+        call["taskQueue"] = json!(q);
+    }
+    json!({ "startAt": "Call", "states": { "Call": call } })
+}
+
+#[tokio::test]
+async fn test_task_queue_routes_task_away_from_resource_queue() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-gpu", task_dsl(Some("gpu")), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 只声明 http 能力的 worker 拿不到 gpu 队列的任务
+    assert!(h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .is_none());
+
+    let stats = h.match_service.queue_stats().await;
+    assert!(stats.iter().any(|s| s.queue == "gpu" && s.pending_tasks == 1));
+
+    let task = h
+        .match_service
+        .take_task_from(&["shell".into(), "gpu".into()], "worker-2", Duration::from_millis(10))
+        .await
+        .expect("task on gpu queue");
+    assert_eq!(task.task_queue, "gpu");
+    assert_eq!(task.resource, "http");
+}
+
+#[tokio::test]
+async fn test_waiting_worker_only_receives_tasks_from_its_queues() {
+    let h = Harness::new().await;
+
+    let shell = {
+        let ms = h.match_service.clone();
+        tokio::spawn(async move {
+            ms.take_task_from(&["shell".into()], "shell-worker", Duration::from_millis(300)).await
+        })
+    };
+    let http = {
+        let ms = h.match_service.clone();
+        tokio::spawn(async move {
+            ms.take_task_from(&["shell".into(), "http".into()], "http-worker", Duration::from_secs(5)).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // 未设置 taskQueue 时按 resource 入队
+    let mut engine = h.engine("run-http", task_dsl(None), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let task = http.await.unwrap().expect("http worker receives the task");
+    assert_eq!(task.run_id, "run-http");
+    assert_eq!(task.task_queue, "http");
+    assert!(shell.await.unwrap().is_none());
+}
+
+#[test]
+fn test_pollers_are_merged_into_stats_and_expire() {
+    let pollers = QueuePollers::new(chrono::Duration::seconds(60));
+    let now = Utc::now();
+    pollers.record("worker-b", &["http".into(), "gpu".into()], now - chrono::Duration::seconds(90));
+    pollers.record("worker-a", &["http".into()], now);
+
+    let stats = vec![MatchStats { queue: "http".into(), pending_tasks: 3, ..Default::default() }];
+    let merged = pollers.merge_into(stats, now);
+
+    // worker-b 超过 TTL 未轮询，不再计入；gpu 队列随之消失
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].pending_tasks, 3);
+    let ids: Vec<&str> = merged[0].pollers.iter().map(|p| p.worker_id.as_str()).collect();
+    assert_eq!(ids, ["worker-a"]);
+
+    pollers.record("worker-c", &["gpu".into()], now);
+    let merged = pollers.merge_into(vec![], now);
+    assert_eq!(merged.iter().map(|s| s.queue.as_str()).collect::<Vec<_>>(), ["gpu", "http"]);
+}
+
+#[tokio::test]
+async fn test_concurrent_long_polls_from_one_worker_each_receive_a_task() {
+    let h = Harness::new().await;
+
+    // 同一 worker 的多个槽位同时长轮询，挂起互不覆盖
+    let polls: Vec<_> = (0..2)
+        .map(|_| {
+            let ms = h.match_service.clone();
+            tokio::spawn(async move {
+                ms.take_task_from(&["http".into()], "worker-1", Duration::from_secs(5)).await
+            })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let stats = h.match_service.queue_stats().await;
+    assert!(stats.iter().any(|s| s.queue == "http" && s.waiting_workers == 2));
+
+    for run_id in ["run-a", "run-b"] {
+        let mut engine = h.engine(run_id, task_dsl(None), json!({}), WorkflowMode::Deferred).await;
+        engine.advance_until_blocked().await.unwrap();
+    }
+    let mut runs = vec![];
+    for poll in polls {
+        runs.push(poll.await.unwrap().expect("each poll receives a task").run_id);
+    }
+    runs.sort();
+    assert_eq!(runs, ["run-a", "run-b"]);
+}
+
+#[tokio::test]
+async fn test_cancelled_long_poll_does_not_lose_task() {
+    let h = Harness::new().await;
+
+    // 模拟 HTTP 连接断开：长轮询在任务入队前后被取消
+    let abandoned = {
+        let ms = h.match_service.clone();
+        tokio::spawn(async move {
+            ms.take_task_from(&["http".into()], "gone", Duration::from_secs(5)).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    abandoned.abort();
+
+    let mut engine = h.engine("run-x", task_dsl(None), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let task = h
+        .match_service
+        .take_task_from(&["http".into()], "worker-2", Duration::from_millis(100))
+        .await
+        .expect("task stays pending for the next poll");
+    assert_eq!(task.run_id, "run-x");
+}
+
+/// 队列为空时长轮询等满请求的时长才返回空，而不是立即返回让 worker 空转
+async fn assert_empty_poll_waits(service: Arc<dyn MatchService>) {
+    let wait = Duration::from_millis(300);
+    let started = Instant::now();
+    assert!(service.take_task_from(&["http".into()], "worker-1", wait).await.is_none());
+    let elapsed = started.elapsed();
+    assert!(elapsed >= wait && elapsed < wait * 3, "empty poll returned after {elapsed:?}");
+}
+
+#[tokio::test]
+async fn test_empty_long_poll_waits_for_timeout_in_every_match_mode() {
+    let h = Harness::new().await;
+    let persistent = || -> Arc<dyn MatchService> {
+        PersistentMatchService::new(Arc::new(PersistentStore::new(h.persistence.clone())), h.persistence.clone())
+    };
+
+    assert_empty_poll_waits(MemoryMatchService::new()).await;
+    assert_empty_poll_waits(persistent()).await;
+    assert_empty_poll_waits(HybridMatchService::new(MemoryMatchService::new(), persistent())).await;
+    assert_empty_poll_waits(HybridMatchServiceWithEvent::new(persistent(), Arc::new(LocalEventBus::new(16)))).await;
+    assert_empty_poll_waits(EventDrivenMatchService::new(Arc::new(LocalEventBus::new(16)))).await;
+}
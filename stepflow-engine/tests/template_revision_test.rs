@@ -0,0 +1,181 @@
+mod common;
+
+use chrono::Utc;
+use common::{constant_output, Harness};
+use serde_json::{json, Value};
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::template::{self, RevisionSelector};
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+use stepflow_storage::entities::workflow_template::StoredWorkflowTemplate;
+use stepflow_storage::entities::workflow_template_revision::{
+    StoredTemplateAlias, StoredTemplateRevision, REVISION_DRAFT,
+};
+
+/// 追加修订（首个修订时同时创建模板行），`publish` 为 true 时立即发布
+async fn add_revision(h: &Harness, template_id: &str, revision: i64, dsl: Value, publish: bool) {
+    let now = Utc::now().naive_utc();
+    if revision == 1 {
+        h.persistence
+            .create_template(&StoredWorkflowTemplate {
+                template_id: template_id.into(),
+                name: template_id.into(),
+                description: None,
+                dsl_definition: dsl.to_string(),
+                version: 0,
+                created_at: now,
+                updated_at: now,
+            })
+            .await
+            .unwrap();
+    }
+    h.persistence
+        .create_template_revision(&StoredTemplateRevision {
+            template_id: template_id.into(),
+            revision,
+            dsl_definition: dsl.to_string(),
+            status: REVISION_DRAFT.into(),
+            created_at: now,
+            published_at: None,
+        })
+        .await
+        .unwrap();
+    if publish {
+        h.persistence.publish_template_revision(template_id, revision, now).await.unwrap();
+    }
+}
+
+/// 等待信号后写入修订标记的子模板
+fn marked_dsl(mark: i64) -> Value {
+    json!({
+        "startAt": "Hold",
+        "states": {
+            "Hold": { "type": "waitForSignal", "signalName": "go", "next": "Mark" },
+            "Mark": { "type": "pass", "outputMapping": constant_output("rev", json!(mark)), "end": true }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_revision_lifecycle_and_selectors() {
+    let h = Harness::new().await;
+    add_revision(&h, "tpl", 1, marked_dsl(1), true).await;
+    add_revision(&h, "tpl", 2, marked_dsl(2), false).await;
+
+    // 草稿不影响 latest，也不能运行
+    let latest = template::runnable_revision(&h.persistence, "tpl", &RevisionSelector::Latest).await.unwrap();
+    assert_eq!(latest.revision, 1);
+    let err = template::runnable_revision(&h.persistence, "tpl", &RevisionSelector::parse("2")).await.unwrap_err();
+    assert!(err.contains("draft"), "{err}");
+
+    h.persistence
+        .set_template_alias(&StoredTemplateAlias {
+            template_id: "tpl".into(),
+            alias: "stable".into(),
+            revision: 1,
+            updated_at: Utc::now().naive_utc(),
+        })
+        .await
+        .unwrap();
+
+    // 发布后模板行跟随最新发布的修订，别名不变
+    h.persistence.publish_template_revision("tpl", 2, Utc::now().naive_utc()).await.unwrap();
+    let row = h.persistence.get_template("tpl").await.unwrap().unwrap();
+    assert_eq!(row.version, 2);
+    assert_eq!(serde_json::from_str::<Value>(&row.dsl_definition).unwrap(), marked_dsl(2));
+
+    let latest = template::runnable_revision(&h.persistence, "tpl", &RevisionSelector::Latest).await.unwrap();
+    assert_eq!(template::revision_dsl(&latest).unwrap().version.as_deref(), Some("2"));
+    let stable = template::runnable_revision(&h.persistence, "tpl", &RevisionSelector::parse("stable")).await.unwrap();
+    assert_eq!(stable.revision, 1);
+    let err = template::runnable_revision(&h.persistence, "tpl", &RevisionSelector::parse("canary")).await.unwrap_err();
+    assert!(err.contains("alias 'canary'"), "{err}");
+
+    // 再发布更早的修订不会让模板行回退
+    add_revision(&h, "tpl", 3, marked_dsl(3), false).await;
+    h.persistence.publish_template_revision("tpl", 1, Utc::now().naive_utc()).await.unwrap();
+    assert_eq!(h.persistence.get_template("tpl").await.unwrap().unwrap().version, 2);
+
+    h.persistence.delete_template("tpl").await.unwrap();
+    assert!(h.persistence.find_template_revisions("tpl").await.unwrap().is_empty());
+    assert!(h.persistence.find_template_aliases("tpl").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_started_sub_workflow_stays_on_its_revision() {
+    let h = Harness::new().await;
+    add_revision(&h, "tpl-child", 1, marked_dsl(1), true).await;
+    let parent_dsl = json!({
+        "startAt": "Child",
+        "states": {
+            "Child": { "type": "subWorkflow", "templateId": "tpl-child", "end": true }
+        }
+    });
+    let mut engine = h.engine("run-pinned", parent_dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let child_id = engine.child_run_id(0);
+    let child = h.persistence.get_execution(&child_id).await.unwrap().unwrap();
+    assert_eq!(child.template_revision, Some(1));
+
+    // 子执行运行期间发布新修订，并模拟进程重启
+    add_revision(&h, "tpl-child", 2, marked_dsl(2), true).await;
+    let dsl = engine.dsl.clone();
+    drop(engine);
+    let mut restored = WorkflowEngine::restore_with_dsl(
+        "run-pinned".into(),
+        dsl,
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap();
+    restored
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::SignalReceived {
+            run_id: child_id,
+            signal_name: "go".into(),
+            payload: Value::Null,
+        })
+        .unwrap();
+    restored.handle_next_signal().await.unwrap();
+    restored.advance_until_blocked().await.unwrap();
+
+    assert!(restored.finished);
+    assert_eq!(restored.context["rev"], 1);
+}
+
+#[test]
+fn test_diff_dsl_reports_json_pointer_paths() {
+    let before = json!({
+        "startAt": "A",
+        "states": {
+            "A": { "type": "pass", "next": "B" },
+            "B": { "type": "succeed" },
+            "a/b": { "type": "succeed" }
+        }
+    });
+    let after = json!({
+        "startAt": "A",
+        "states": {
+            "A": { "type": "pass", "next": "C" },
+            "C": { "type": "succeed" },
+            "a/b": { "type": "succeed" }
+        }
+    });
+
+    let changes: Vec<(String, String)> = template::diff_dsl(&before, &after)
+        .into_iter()
+        .map(|c| (c.path, c.kind))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("/states/A/next".to_string(), "changed".to_string()),
+            ("/states/B".to_string(), "removed".to_string()),
+            ("/states/C".to_string(), "added".to_string()),
+        ]
+    );
+    assert!(template::diff_dsl(&before, &before).is_empty());
+}
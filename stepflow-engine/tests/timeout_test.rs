@@ -0,0 +1,203 @@
+mod common;
+
+use std::time::Duration;
+
+use chrono::Utc;
+use common::Harness;
+use serde_json::{json, Value};
+use stepflow_dto::dto::{signal::ExecutionSignal, timer::TimerPayload};
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+use stepflow_storage::entities::timer::StoredTimer;
+
+fn sleep_input(seconds: u64) -> Value {
+    json!({
+        "resource": "shell",
+        "input": null,
+        "parameters": { "command": format!("sleep {seconds}") }
+    })
+}
+
+/// 认领该执行所有已创建的定时器（不论是否到期），按创建顺序返回超时定时器
+async fn timeout_timers(h: &Harness, run_id: &str) -> Vec<(StoredTimer, TimerPayload)> {
+    let due = h
+        .persistence
+        .claim_due_timers(Utc::now().naive_utc() + chrono::Duration::days(1), &[], 100)
+        .await
+        .unwrap();
+    due.into_iter()
+        .filter(|t| t.run_id == run_id)
+        .filter_map(|t| {
+            let payload = serde_json::from_value(t.payload.clone()?).ok()?;
+            Some((t, payload))
+        })
+        .collect()
+}
+
+async fn fire(engine: &mut WorkflowEngine, timer: &StoredTimer, payload: &TimerPayload) -> Result<(), String> {
+    let TimerPayload::Timeout { visit_event_id, attempt } = payload.clone();
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimeoutFired {
+            run_id: timer.run_id.clone(),
+            state_name: timer.state_name.clone(),
+            visit_event_id,
+            attempt,
+        })
+        .unwrap();
+    engine.handle_next_signal().await?;
+    engine.advance_until_blocked().await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_inline_task_timeout_is_caught() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Slow",
+        "states": {
+            "Slow": {
+                "type": "task",
+                "resource": "shell",
+                "timeoutSeconds": 1,
+                "catch": [{ "errorEquals": ["States.Timeout"], "resultPath": "$.error", "next": "Recovered" }],
+                "next": "Done"
+            },
+            "Recovered": { "type": "succeed" },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-timeout-catch", dsl, sleep_input(5), WorkflowMode::Inline).await;
+
+    let started = std::time::Instant::now();
+    let out = engine.run_inline().await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(4));
+    assert_eq!(engine.current_state, "Recovered");
+    assert_eq!(out["error"]["Error"], "States.Timeout");
+
+    let row = h.persistence.get_execution("run-timeout-catch").await.unwrap().unwrap();
+    assert_eq!(row.status, "COMPLETED");
+    let state = h.persistence.get_state("run-timeout-catch:Slow").await.unwrap().unwrap();
+    assert_eq!(state.status, "FAILED");
+}
+
+#[tokio::test]
+async fn test_inline_execution_timeout_records_timed_out() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Slow",
+        "timeoutSeconds": 1,
+        "states": {
+            "Slow": { "type": "task", "resource": "shell", "end": true }
+        }
+    });
+    let mut engine = h.engine("run-timeout-wf", dsl, sleep_input(5), WorkflowMode::Inline).await;
+
+    let err = engine.run_inline().await.unwrap_err();
+    assert!(err.contains("timed out"), "{err}");
+    assert!(engine.finished);
+    assert_eq!(engine.last_error.as_ref().unwrap().error_type, "States.Timeout");
+
+    let row = h.persistence.get_execution("run-timeout-wf").await.unwrap().unwrap();
+    assert_eq!(row.status, "TIMED_OUT");
+    assert_eq!(row.result.unwrap()["Error"], "States.Timeout");
+    let vis = h.persistence.get_visibility("run-timeout-wf").await.unwrap().unwrap();
+    assert_eq!(vis.status.as_deref(), Some("TIMED_OUT"));
+    assert!(vis.close_time.is_some());
+}
+
+#[tokio::test]
+async fn test_deferred_task_timeout_retries_then_times_out() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": {
+                "type": "task",
+                "resource": "http",
+                "timeoutSeconds": 30,
+                "retry": [{ "errorEquals": ["States.Timeout"], "intervalSeconds": 0, "maxAttempts": 1 }],
+                "end": true
+            }
+        }
+    });
+    let mut engine = h.engine("run-timeout-deferred", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 首次派发：定时器记录状态访问与第 0 次尝试，队列任务带上 timeoutSeconds
+    let mut timers = timeout_timers(&h, "run-timeout-deferred").await;
+    assert_eq!(timers.len(), 1);
+    let (first, first_payload) = timers.remove(0);
+    assert_eq!(first.state_name.as_deref(), Some("Call"));
+    assert!(matches!(first_payload, TimerPayload::Timeout { attempt: Some(0), .. }));
+    let task = h
+        .match_service
+        .take_task("http", "worker-1", Duration::from_millis(10))
+        .await
+        .expect("first attempt enqueued");
+    assert_eq!(task.timeout_seconds, Some(30));
+
+    // 超时：按 Retry 重新派发
+    fire(&mut engine, &first, &first_payload).await.unwrap();
+    assert!(!engine.finished);
+    let state = h.persistence.get_state("run-timeout-deferred:Call").await.unwrap().unwrap();
+    assert_eq!(state.attempts, 1);
+    assert_eq!(state.error_details.as_deref(), Some("States.Timeout"));
+
+    // 上一次尝试的定时器再次到达：忽略
+    fire(&mut engine, &first, &first_payload).await.unwrap();
+    assert!(!engine.finished);
+
+    let (second, second_payload) = timeout_timers(&h, "run-timeout-deferred").await.remove(0);
+    assert!(matches!(second_payload, TimerPayload::Timeout { attempt: Some(1), .. }));
+    fire(&mut engine, &second, &second_payload).await.unwrap();
+
+    assert!(engine.finished);
+    let row = h.persistence.get_execution("run-timeout-deferred").await.unwrap().unwrap();
+    assert_eq!(row.status, "TIMED_OUT");
+    assert_eq!(row.result.unwrap()["Error"], "States.Timeout");
+}
+
+#[tokio::test]
+async fn test_deferred_workflow_timeout_ends_waiting_execution() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Hold",
+        "timeoutSeconds": 120,
+        "states": {
+            "Hold": { "type": "waitForSignal", "signalName": "resume", "end": true }
+        }
+    });
+    let mut engine = h.engine("run-timeout-hold", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    let (timer, payload) = timeout_timers(&h, "run-timeout-hold").await.remove(0);
+    assert!(timer.state_name.is_none());
+    fire(&mut engine, &timer, &payload).await.unwrap();
+
+    assert!(engine.finished);
+    let row = h.persistence.get_execution("run-timeout-hold").await.unwrap().unwrap();
+    assert_eq!(row.status, "TIMED_OUT");
+    let state = h.persistence.get_state("run-timeout-hold:Hold").await.unwrap().unwrap();
+    assert_eq!(state.status, "FAILED");
+}
+
+#[tokio::test]
+async fn test_timed_out_task_is_revoked_from_match_queue() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Call",
+        "states": {
+            "Call": { "type": "task", "resource": "http", "timeoutSeconds": 30, "end": true }
+        }
+    });
+    let mut engine = h.engine("run-timeout-revoke", dsl, json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+
+    // 任务还在（内存）匹配队列中就超时：经 MatchService 撤下，不再派发给 worker
+    let (timer, payload) = timeout_timers(&h, "run-timeout-revoke").await.remove(0);
+    fire(&mut engine, &timer, &payload).await.unwrap();
+    assert!(engine.finished);
+
+    let task = h.match_service.take_task("http", "worker-1", Duration::from_millis(20)).await;
+    assert!(task.is_none(), "timed-out task still dispatched: {task:?}");
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use common::Harness;
+use serde_json::json;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
+
+fn wait_dsl() -> serde_json::Value {
+    json!({
+        "startAt": "Sleep",
+        "states": {
+            "Sleep": { "type": "wait", "seconds": 60, "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_deferred_wait_suspends_until_timer_fired() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-wait", wait_dsl(), json!({ "x": 1 }), WorkflowMode::Deferred).await;
+
+    engine.advance_until_blocked().await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Sleep");
+
+    let timers = h
+        .persistence
+        .find_timers_before(chrono::Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+        .await
+        .unwrap();
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].run_id, "run-wait");
+
+    // 再次推进不会重复创建定时器
+    engine.advance_until_blocked().await.unwrap();
+    assert_eq!(
+        h.persistence
+            .find_timers_before(chrono::Utc::now().naive_utc() + chrono::Duration::hours(1), 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimerFired {
+            run_id: "run-wait".into(),
+            state_name: "Sleep".into(),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+
+    assert!(engine.finished);
+    assert_eq!(engine.context, json!({ "x": 1 }));
+}
+
+#[tokio::test]
+async fn test_restored_engine_accepts_timer_fired() {
+    let h = Harness::new().await;
+    let mut engine = h.engine("run-wait-restore", wait_dsl(), json!({}), WorkflowMode::Deferred).await;
+    engine.advance_until_blocked().await.unwrap();
+    let dsl = engine.dsl.clone();
+    drop(engine);
+
+    let mut restored = WorkflowEngine::restore_with_dsl(
+        "run-wait-restore".into(),
+        dsl,
+        h.dispatcher.clone(),
+        h.persistence.clone(),
+        h.registry.clone(),
+    )
+    .await
+    .unwrap();
+
+    // 恢复后仍挂起在 Wait，不会重新创建定时器
+    restored.advance_until_blocked().await.unwrap();
+    assert_eq!(restored.current_state, "Sleep");
+
+    restored
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimerFired {
+            run_id: "run-wait-restore".into(),
+            state_name: "Sleep".into(),
+        })
+        .unwrap();
+    restored.handle_next_signal().await.unwrap();
+    restored.advance_until_blocked().await.unwrap();
+    assert!(restored.finished);
+}
+
+#[tokio::test]
+async fn test_inline_long_wait_becomes_persisted_timer() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Sleep",
+        "states": {
+            "Sleep": { "type": "wait", "secondsPath": "$.delay", "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let mut engine = h.engine("run-wait-long", dsl, json!({ "delay": 3600 }), WorkflowMode::Inline).await;
+
+    engine.run_inline().await.unwrap();
+    assert!(!engine.finished);
+    assert_eq!(engine.current_state, "Sleep");
+
+    let timer = h
+        .persistence
+        .find_timers_before(chrono::Utc::now().naive_utc() + chrono::Duration::hours(2), 10)
+        .await
+        .unwrap()
+        .remove(0);
+    let delay = timer.fire_at - chrono::Utc::now().naive_utc();
+    assert!(delay > chrono::Duration::minutes(59), "{delay}");
+
+    engine
+        .get_signal_sender()
+        .unwrap()
+        .send(ExecutionSignal::TimerFired {
+            run_id: "run-wait-long".into(),
+            state_name: "Sleep".into(),
+        })
+        .unwrap();
+    engine.handle_next_signal().await.unwrap();
+    engine.advance_until_blocked().await.unwrap();
+    assert!(engine.finished);
+}
+
+#[tokio::test]
+async fn test_timestamp_path_in_the_past_does_not_wait() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Sleep",
+        "states": {
+            "Sleep": { "type": "wait", "timestampPath": "$.deadline", "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let input = json!({ "deadline": "2020-01-01T00:00:00Z" });
+    let mut engine = h.engine("run-wait-past", dsl, input, WorkflowMode::Deferred).await;
+
+    engine.advance_until_blocked().await.unwrap();
+    assert!(engine.finished);
+}
+
+#[tokio::test]
+async fn test_out_of_range_seconds_path_fails_with_runtime_error() {
+    let h = Harness::new().await;
+    let dsl = json!({
+        "startAt": "Sleep",
+        "states": {
+            "Sleep": { "type": "wait", "secondsPath": "$.delay", "next": "Done" },
+            "Done": { "type": "succeed" }
+        }
+    });
+    let input = json!({ "delay": u64::MAX });
+    let mut engine = h.engine("run-wait-overflow", dsl, input, WorkflowMode::Deferred).await;
+
+    // 到期时间溢出：执行以 States.Runtime 失败，而不是让引擎 panic
+    assert!(engine.advance_until_blocked().await.is_err());
+    assert_eq!(engine.last_error.as_ref().unwrap().error_type, "States.Runtime");
+    let exec = h.persistence.get_execution("run-wait-overflow").await.unwrap().unwrap();
+    assert_eq!(exec.status, "FAILED");
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use common::Harness;
+use stepflow_dto::dto::queue_task::QueueTaskDto;
+use stepflow_match::queue::PersistentStore;
+use stepflow_match::service::{
+    HybridMatchService, MatchService, MemoryMatchService, PersistentMatchService,
+};
+use stepflow_storage::entities::worker::{StoredWorker, WORKER_ACTIVE, WORKER_DRAINING, WORKER_OFFLINE};
+
+fn task(run_id: &str) -> QueueTaskDto {
+    QueueTaskDto {
+        task_id: format!("{run_id}/A"),
+        run_id: run_id.into(),
+        state_name: "A".into(),
+        resource: "http".into(),
+        task_queue: "http".into(),
+        root_run_id: run_id.into(),
+        template_id: None,
+        worker_id: None,
+        task_payload: None,
+        status: "pending".into(),
+        attempts: 0,
+        max_attempts: 3,
+        priority: None,
+        timeout_seconds: None,
+        heartbeat_seconds: None,
+        last_heartbeat_at: None,
+        error_message: None,
+        last_error_at: None,
+        next_retry_at: None,
+        queued_at: Utc::now(),
+        processing_at: None,
+        completed_at: None,
+        failed_at: None,
+    }
+}
+
+fn persistent(h: &Harness) -> Arc<PersistentMatchService> {
+    PersistentMatchService::new(Arc::new(PersistentStore::new(h.persistence.clone())), h.persistence.clone())
+}
+
+/// worker-a 认领两个任务后失联：退回的任务不计重试次数，由 worker-b 接手
+async fn assert_requeue_hands_tasks_to_another_worker(service: Arc<dyn MatchService>) {
+    let queues = ["http".to_string()];
+    let wait = Duration::from_millis(20);
+    for run in ["r1", "r2"] {
+        service.enqueue_task("http", task(run)).await.unwrap();
+    }
+    for _ in 0..2 {
+        let t = service.take_task_from(&queues, "worker-a", wait).await.expect("task for worker-a");
+        assert_eq!(t.worker_id.as_deref(), Some("worker-a"));
+    }
+    assert!(service.take_task_from(&queues, "worker-b", wait).await.is_none());
+
+    let mut requeued: Vec<String> = service
+        .requeue_worker_tasks("worker-a")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.run_id)
+        .collect();
+    requeued.sort();
+    assert_eq!(requeued, ["r1", "r2"]);
+    assert!(service.requeue_worker_tasks("worker-a").await.unwrap().is_empty());
+
+    let mut taken = vec![];
+    while let Some(t) = service.take_task_from(&queues, "worker-b", wait).await {
+        assert_eq!((t.worker_id.as_deref(), t.attempts), (Some("worker-b"), 0));
+        taken.push(t.run_id);
+    }
+    taken.sort();
+    assert_eq!(taken, ["r1", "r2"]);
+}
+
+#[tokio::test]
+async fn test_requeue_worker_tasks_in_every_match_mode() {
+    assert_requeue_hands_tasks_to_another_worker(MemoryMatchService::new()).await;
+
+    let h = Harness::new().await;
+    assert_requeue_hands_tasks_to_another_worker(persistent(&h)).await;
+
+    let h = Harness::new().await;
+    let hybrid = HybridMatchService::new(MemoryMatchService::new(), persistent(&h));
+    assert_requeue_hands_tasks_to_another_worker(hybrid).await;
+}
+
+/// 推送失败时单个任务退回 pending：不计重试次数，由其他 worker 接手
+async fn assert_requeue_task_returns_undelivered_task(service: Arc<dyn MatchService>) {
+    let queues = ["http".to_string()];
+    let wait = Duration::from_millis(20);
+    service.enqueue_task("http", task("r1")).await.unwrap();
+    service.take_task_from(&queues, "worker-a", wait).await.expect("task for worker-a");
+
+    let requeued = service.requeue_task("r1", "A").await.unwrap();
+    assert_eq!((requeued.status.as_str(), requeued.worker_id), ("pending", None));
+    assert!(service.requeue_task("r1", "A").await.is_err(), "task is no longer processing");
+
+    let t = service.take_task_from(&queues, "worker-b", wait).await.expect("task for worker-b");
+    assert_eq!((t.run_id.as_str(), t.worker_id.as_deref(), t.attempts), ("r1", Some("worker-b"), 0));
+    assert!(service.take_task_from(&queues, "worker-c", wait).await.is_none());
+}
+
+#[tokio::test]
+async fn test_requeue_task_in_every_match_mode() {
+    assert_requeue_task_returns_undelivered_task(MemoryMatchService::new()).await;
+
+    let h = Harness::new().await;
+    assert_requeue_task_returns_undelivered_task(persistent(&h)).await;
+
+    let h = Harness::new().await;
+    let hybrid = HybridMatchService::new(MemoryMatchService::new(), persistent(&h));
+    assert_requeue_task_returns_undelivered_task(hybrid).await;
+}
+
+#[tokio::test]
+async fn test_persistent_long_poll_wakes_on_enqueue() {
+    let h = Harness::new().await;
+    let service = persistent(&h);
+
+    let poll = {
+        let service = service.clone();
+        tokio::spawn(async move {
+            service.take_task_from(&["http".into()], "worker-a", Duration::from_secs(5)).await
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let enqueued = Instant::now();
+    service.enqueue_task("http", task("r1")).await.unwrap();
+
+    let t = poll.await.unwrap().expect("poll receives the enqueued task");
+    assert_eq!(t.run_id, "r1");
+    // 入队即唤醒，不必等下一次数据库兜底查询
+    assert!(enqueued.elapsed() < Duration::from_millis(400), "woke after {:?}", enqueued.elapsed());
+}
+
+#[tokio::test]
+async fn test_hybrid_drops_memory_task_claimed_elsewhere() {
+    let h = Harness::new().await;
+    let memory = MemoryMatchService::new();
+    let hybrid = HybridMatchService::new(memory.clone(), persistent(&h));
+    let queues = ["http".to_string()];
+    hybrid.enqueue_task("http", task("r1")).await.unwrap();
+
+    // 另一网关共享同一数据库，先在库中认领了这条任务
+    let other = persistent(&h);
+    let t = other.take_task_from(&queues, "worker-x", Duration::from_millis(20)).await.expect("claimed elsewhere");
+    assert_eq!(t.run_id, "r1");
+
+    assert!(hybrid.take_task_from(&queues, "worker-a", Duration::from_millis(100)).await.is_none());
+    assert!(memory.heartbeat("r1", "A").await.is_err(), "memory copy is dropped, not left processing");
+    assert!(memory.queue_stats().await.iter().all(|s| s.pending_tasks == 0));
+}
+
+#[tokio::test]
+async fn test_hybrid_long_poll_picks_up_tasks_only_in_database() {
+    let h = Harness::new().await;
+    let hybrid = HybridMatchService::new(MemoryMatchService::new(), persistent(&h));
+
+    // 任务由另一网关写入，只在库中；内存层的等待不会挡住整次长轮询
+    persistent(&h).enqueue_task("http", task("r1")).await.unwrap();
+    let started = Instant::now();
+    let t = hybrid
+        .take_task_from(&["http".into()], "worker-a", Duration::from_secs(10))
+        .await
+        .expect("task from database");
+    assert_eq!((t.run_id.as_str(), t.worker_id.as_deref()), ("r1", Some("worker-a")));
+    assert!(started.elapsed() < Duration::from_secs(3), "took {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn test_worker_registry_tracks_liveness_and_status() {
+    let h = Harness::new().await;
+    let pm = &h.persistence;
+    let now = Utc::now().naive_utc();
+    let mut worker = StoredWorker {
+        worker_id: "worker-a".into(),
+        capabilities: vec!["http".into(), "shell".into()],
+        task_queues: vec!["gpu".into()],
+        version: Some("0.1.0".into()),
+        concurrency: 4,
+        in_flight: 0,
+        status: WORKER_ACTIVE.into(),
+        registered_at: now - chrono::Duration::minutes(5),
+        last_seen_at: now - chrono::Duration::minutes(5),
+    };
+    pm.upsert_worker(&worker).await.unwrap();
+
+    let cutoff = now - chrono::Duration::minutes(1);
+    assert_eq!(pm.find_stale_workers(cutoff).await.unwrap().len(), 1);
+    assert!(pm.touch_worker("worker-a", Some(3), now).await.unwrap());
+    assert!(pm.find_stale_workers(cutoff).await.unwrap().is_empty());
+    // 扫描之后又心跳了：条件下线不生效
+    assert!(!pm.mark_worker_offline_if_stale("worker-a", cutoff).await.unwrap());
+    assert!(!pm.touch_worker("worker-x", None, now).await.unwrap());
+
+    assert!(pm.set_worker_status("worker-a", WORKER_DRAINING).await.unwrap());
+    let stored = pm.get_worker("worker-a").await.unwrap().unwrap();
+    assert_eq!((stored.status.as_str(), stored.in_flight), (WORKER_DRAINING, 3));
+    assert_eq!(stored.task_queues, vec!["gpu".to_string()]);
+
+    // 下线后心跳被拒绝；重新注册恢复为 active 并保留首次注册时间
+    assert!(pm.set_worker_status("worker-a", WORKER_OFFLINE).await.unwrap());
+    assert!(!pm.touch_worker("worker-a", None, now).await.unwrap());
+    worker.registered_at = now;
+    worker.last_seen_at = now;
+    pm.upsert_worker(&worker).await.unwrap();
+    let listed = pm.list_workers().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].status, WORKER_ACTIVE);
+    assert!(listed[0].registered_at < now);
+
+    // 条件下线只生效一次
+    let later = now + chrono::Duration::minutes(1);
+    assert!(pm.mark_worker_offline_if_stale("worker-a", later).await.unwrap());
+    assert!(!pm.mark_worker_offline_if_stale("worker-a", later).await.unwrap());
+    assert_eq!(pm.get_worker("worker-a").await.unwrap().unwrap().status, WORKER_OFFLINE);
+}
@@ -1,7 +1,9 @@
 pub mod engine;
+pub mod states;
 
 pub fn register_all_builtin_errors() {
     engine::register_engine_errors();
+    states::register_states_errors();
     // dsl::register_dsl_errors();
     // ...
-}
\ No newline at end of file
+}
@@ -0,0 +1,45 @@
+use crate::registry::{register_error, ErrorDescriptor};
+
+/// 预定义的 `States.*` 错误类型（Retry / Catch 的 `errorEquals` 可直接引用）
+pub const STATES_ALL: &str = "States.ALL";
+pub const STATES_TASK_FAILED: &str = "States.TaskFailed";
+pub const STATES_FAIL: &str = "States.Fail";
+pub const STATES_EXCEED_TOLERATED_FAILURE_THRESHOLD: &str = "States.ExceedToleratedFailureThreshold";
+pub const STATES_RUNTIME: &str = "States.Runtime";
+pub const STATES_TIMEOUT: &str = "States.Timeout";
+pub const STATES_HEARTBEAT_TIMEOUT: &str = "States.HeartbeatTimeout";
+pub const STATES_CANCELLED: &str = "States.Cancelled";
+pub const STATES_MAPPING_ERROR: &str = "States.MappingError";
+
+/// 注册 `States.*` 错误类型
+pub fn register_states_errors() {
+    for (name, description) in [
+        (STATES_ALL, "Matches any error type except States.Runtime"),
+        (STATES_TASK_FAILED, "A Task state failed during execution"),
+        (STATES_FAIL, "A Fail state was reached without an error name"),
+        (
+            STATES_EXCEED_TOLERATED_FAILURE_THRESHOLD,
+            "More Map items failed than toleratedFailurePercentage allows",
+        ),
+        (STATES_RUNTIME, "The engine failed to execute the state"),
+        (STATES_TIMEOUT, "A Task ran longer than its timeout_seconds"),
+        (
+            STATES_HEARTBEAT_TIMEOUT,
+            "A Task worker stopped sending heartbeats within heartbeatSeconds",
+        ),
+        (STATES_CANCELLED, "The execution (or a child execution) was cancelled"),
+        (
+            STATES_MAPPING_ERROR,
+            "Input / output processing (paths, mappings, Parameters, ResultSelector) failed",
+        ),
+    ] {
+        register_error(
+            name,
+            ErrorDescriptor {
+                name,
+                category: "States",
+                description,
+            },
+        );
+    }
+}
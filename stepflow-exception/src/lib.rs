@@ -24,3 +24,8 @@ pub mod export;
 // 7. 内置错误注册器
 pub mod builtin;
 pub use builtin::register_all_builtin_errors;
+pub use builtin::states::{
+    STATES_ALL, STATES_EXCEED_TOLERATED_FAILURE_THRESHOLD, STATES_RUNTIME,
+    STATES_FAIL, STATES_TASK_FAILED, STATES_TIMEOUT, STATES_HEARTBEAT_TIMEOUT, STATES_CANCELLED,
+    STATES_MAPPING_ERROR,
+};
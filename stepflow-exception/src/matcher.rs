@@ -1,22 +1,32 @@
 use stepflow_dto::dto::error_policy::{RetryPolicy, CatchPolicy};
+use crate::builtin::states::{STATES_ALL, STATES_RUNTIME};
 
-/// 匹配最先符合的 Retry 策略（如多个）
+/// `States.ALL` 与 `*` 匹配除 `States.Runtime` 外的任意错误类型；
+/// 引擎自身的运行时错误只能由显式声明 `States.Runtime` 的策略处理
+fn error_matches(pattern: &str, error_type: &str) -> bool {
+    if pattern == "*" || pattern == STATES_ALL {
+        return error_type != STATES_RUNTIME;
+    }
+    pattern == error_type
+}
+
+/// 匹配最先符合的 Retry 策略（按声明顺序）
 /// error_type 可为 `"HttpTimeout"`、`"MappingJsonPathError"` 等
 pub fn match_retry<'a>(
     error_type: &str,
     retry_policies: Option<&'a [RetryPolicy]>
 ) -> Option<&'a RetryPolicy> {
     retry_policies?.iter().find(|policy| {
-        policy.error_equals.iter().any(|eq| eq == "*" || eq == error_type)
+        policy.error_equals.iter().any(|eq| error_matches(eq, error_type))
     })
 }
 
-/// 匹配最先符合的 Catch 策略
+/// 匹配最先符合的 Catch 策略（按声明顺序）
 pub fn match_catch<'a>(
     error_type: &str,
     catch_policies: Option<&'a [CatchPolicy]>
 ) -> Option<&'a CatchPolicy> {
     catch_policies?.iter().find(|policy| {
-        policy.error_equals.iter().any(|eq| eq == "*" || eq == error_type)
+        policy.error_equals.iter().any(|eq| error_matches(eq, error_type))
     })
 }
\ No newline at end of file
@@ -18,6 +18,8 @@ pub async fn start_execution(svc: &ExecutionSqlxSvc, req: FrbStartExecutionReque
 
     let inner = ExecStart {
         template_id: req.template_id.clone(),
+        template_revision: None,
+        template_alias: None,
         dsl,
         init_ctx,
         mode: req.mode.clone(),
@@ -10,6 +10,8 @@ use stepflow_common::config::{StepflowConfig, StepflowExecMode};
 use stepflow_core::{
     builder::build_app_state,
     event::{maybe_start_event_runner, spawn_event_logger},
+    outbox::{spawn_outbox_relay, OutboxRelayConfig},
+    timer::{spawn_timer_scheduler, TimerSchedulerConfig},
     init_tracing,
 };
 
@@ -34,9 +36,11 @@ pub async fn init_stepflow() -> anyhow::Result<()> {
     let app_state = Arc::new(build_app_state(&config).await?);
     set_global_event_bus(app_state.event_bus.clone())?;
 
-    // ④ 启动引擎监听器 + 日志监听器
+    // ④ 启动引擎监听器 + 日志监听器 + 定时器调度 + outbox 补投
     maybe_start_event_runner(&config, &app_state);
     spawn_event_logger(&app_state);
+    spawn_timer_scheduler(&app_state, TimerSchedulerConfig::from_config(&config));
+    spawn_outbox_relay(&app_state, OutboxRelayConfig::from_config(&config));
 
     // ⑤ 注册全局状态
     GLOBAL_APP_STATE
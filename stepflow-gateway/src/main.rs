@@ -10,6 +10,9 @@ use stepflow_core::{
     builder::build_app_state, 
     app_state::AppState, 
     event::{maybe_start_event_runner, spawn_event_logger},
+    outbox::{spawn_outbox_relay, OutboxRelayConfig},
+    reaper::{spawn_task_reaper, TaskReaperConfig},
+    timer::{spawn_timer_scheduler, TimerSchedulerConfig},
     init_tracing
 };
 use stepflow_eventbus::global::set_global_event_bus;
@@ -28,9 +31,12 @@ async fn main() -> anyhow::Result<()> {
     let app_state = build_app_state(&config).await?;
     set_global_event_bus(app_state.event_bus.clone())?;
 
-    // ③ 启动 EventRunner（如启用）+ 日志监听器
+    // ③ 启动 EventRunner（如启用）+ 日志监听器 + 定时器调度 + 超时任务回收 + outbox 补投
     maybe_start_event_runner(&config, &app_state);
     spawn_event_logger(&app_state);
+    spawn_timer_scheduler(&app_state, TimerSchedulerConfig::from_config(&config));
+    spawn_task_reaper(&app_state, TaskReaperConfig::from_config(&config));
+    spawn_outbox_relay(&app_state, OutboxRelayConfig::from_config(&config));
 
     // ④ 启动 HTTP + Worker 服务
     run_gateway_server(config, app_state).await
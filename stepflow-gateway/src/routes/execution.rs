@@ -34,6 +34,10 @@ pub fn router(svc: ExecutionSvc) -> Router<AppState> {
         .route("/", post(start).get(list))
         .route("/:id", get(get_one).put(update).delete(delete_one))
         .route("/by_status", get(list_by_status))
+        .route("/:id/children", get(list_children))
+        .route("/:id/signals/:name", post(send_signal))
+        .route("/:id/cancel", post(cancel))
+        .route("/:id/terminate", post(terminate))
         .with_state(svc)
 }
 
@@ -170,4 +174,100 @@ pub async fn list_by_status(
         p.limit.unwrap_or(20),
         p.offset.unwrap_or(0),
     ).await?))
-}
\ No newline at end of file
+}
+
+/// 获取子执行（SubWorkflow / Parallel / Map 分支）列表
+#[utoipa::path(
+    get,
+    path = "/v1/executions/{id}/children",
+    params(
+        ("id" = String, Path, description = "父执行 ID"),
+        ("limit" = Option<i64>, Query, description = "每页数量"),
+        ("offset" = Option<i64>, Query, description = "偏移量")
+    ),
+    responses(
+        (status = 200, description = "成功获取子执行列表", body = Vec<ExecDto>),
+        (status = 404, description = "执行不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "executions"
+)]
+pub async fn list_children(
+    State(svc): State<ExecutionSvc>,
+    Path(id): Path<String>,
+    Query(p): Query<Page>,
+) -> AppResult<Json<Vec<ExecDto>>> {
+    Ok(Json(svc.children(&id, p.limit.unwrap_or(20), p.offset.unwrap_or(0)).await?))
+}
+
+/// 向执行发送具名信号，唤醒等待该信号的 WaitForSignal 状态
+#[utoipa::path(
+    post,
+    path = "/v1/executions/{id}/signals/{name}",
+    request_body(content = Option<Value>, description = "信号 payload，作为 WaitForSignal 状态的输出"),
+    params(
+        ("id" = String, Path, description = "执行 ID"),
+        ("name" = String, Path, description = "信号名称")
+    ),
+    responses(
+        (status = 200, description = "信号已送达", body = ExecDto),
+        (status = 400, description = "执行未在等待该信号"),
+        (status = 404, description = "执行不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "executions"
+)]
+pub async fn send_signal(
+    State(svc): State<ExecutionSvc>,
+    Path((id, name)): Path<(String, String)>,
+    body: Option<Json<Value>>,
+) -> AppResult<Json<ExecDto>> {
+    let payload = body.map(|Json(v)| v).unwrap_or(Value::Null);
+    Ok(Json(svc.signal(&id, &name, payload).await?))
+}
+
+/// 取消执行：撤下未完成的任务与定时器，运行 onCancel 清理状态（如有）后记录为 CANCELLED
+#[utoipa::path(
+    post,
+    path = "/v1/executions/{id}/cancel",
+    request_body(content = Option<ExecStop>, description = "取消原因"),
+    params(("id" = String, Path, description = "执行 ID")),
+    responses(
+        (status = 200, description = "已取消（或正在运行清理状态）", body = ExecDto),
+        (status = 400, description = "执行已结束、正在取消或不是根执行"),
+        (status = 404, description = "执行不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "executions"
+)]
+pub async fn cancel(
+    State(svc): State<ExecutionSvc>,
+    Path(id): Path<String>,
+    body: Option<Json<ExecStop>>,
+) -> AppResult<Json<ExecDto>> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    Ok(Json(svc.stop(&id, body.reason, false).await?))
+}
+
+/// 强制终止执行：不运行清理状态，直接记录为 TERMINATED
+#[utoipa::path(
+    post,
+    path = "/v1/executions/{id}/terminate",
+    request_body(content = Option<ExecStop>, description = "终止原因"),
+    params(("id" = String, Path, description = "执行 ID")),
+    responses(
+        (status = 200, description = "已终止", body = ExecDto),
+        (status = 400, description = "执行已结束或不是根执行"),
+        (status = 404, description = "执行不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "executions"
+)]
+pub async fn terminate(
+    State(svc): State<ExecutionSvc>,
+    Path(id): Path<String>,
+    body: Option<Json<ExecStop>>,
+) -> AppResult<Json<ExecDto>> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    Ok(Json(svc.stop(&id, body.reason, true).await?))
+}
@@ -5,6 +5,7 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
+use chrono::Utc;
 use std::time::Duration;
 
 use stepflow_core::{
@@ -55,6 +56,8 @@ pub async fn poll_task(
     State(app): State<AppState>,
     Json(payload): Json<PollRequest>,
 ) -> AppResult<Json<PollResponse>> {
+    app.pollers.record(&payload.worker_id, std::slice::from_ref(&payload.queue), Utc::now());
+
     let task_opt = app
         .match_service
         .take_task(
@@ -70,7 +73,7 @@ pub async fn poll_task(
     }))
 }
 
-/// ③ 查询队列统计
+/// ③ 查询队列统计：每个队列的积压、等待中的 worker 与最近的 pollers
 #[utoipa::path(
     get,
     path = "/v1/match/stats",
@@ -81,7 +84,8 @@ pub async fn poll_task(
     )
 )]
 pub async fn get_stats(State(app): State<AppState>) -> AppResult<Json<Vec<MatchStats>>> {
-    Ok(Json(app.match_service.queue_stats().await))
+    let stats = app.match_service.queue_stats().await;
+    Ok(Json(app.pollers.merge_into(stats, Utc::now())))
 }
 
 /// ④ 组合路由
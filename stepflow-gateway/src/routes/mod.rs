@@ -7,6 +7,7 @@ pub mod workflow_event;
 pub mod queue_task;
 pub mod timer;
 pub mod match_router;
+pub mod workers;
 use crate::{
     service::{
         template::TemplateSqlxSvc,
@@ -26,7 +27,7 @@ pub fn new(state: AppState) -> Router<AppState> {
     let tpl_svc = TemplateSqlxSvc::new(state.persist.clone());
     let exec_svc = ExecutionSqlxSvc::new(state.clone());
     let event_svc = WorkflowEventSqlxSvc::new(state.persist.clone());
-    let task_svc = ActivityTaskSqlxSvc::new(state.persist.clone());
+    let task_svc = ActivityTaskSqlxSvc::new(state.clone());
     let queue_svc = QueueTaskSqlxSvc::new(state.clone());
     let timer_svc = TimerSqlxSvc::new(state.clone());
 
@@ -36,6 +37,7 @@ pub fn new(state: AppState) -> Router<AppState> {
         .nest("/v1/executions", execution::router(exec_svc))
         .nest("/v1/activity_tasks", activity_task::router(task_svc))
         .nest("/v1/worker", worker::router())
+        .nest("/v1/workers", workers::router())
         .nest("/v1/workflow_events", workflow_event::router(event_svc))
         .nest("/v1/queue_tasks", queue_task::router(queue_svc))
         .nest("/v1/timers", timer::router(timer_svc))
@@ -56,14 +58,34 @@ use stepflow_dto::dto;
         template::get_one,
         template::update,
         template::delete_one,
+        template::list_revisions,
+        template::get_revision,
+        template::publish_revision,
+        template::diff_revisions,
+        template::list_aliases,
+        template::set_alias,
+        template::delete_alias,
         execution::start,
         execution::list,
         execution::get_one,
         execution::update,
         execution::delete_one,
         execution::list_by_status,
+        execution::list_children,
+        execution::send_signal,
+        execution::cancel,
+        execution::terminate,
         worker::poll_task,
+        worker::stream_tasks,
         worker::update_task_status,
+        worker::heartbeat_task,
+        workers::list,
+        workers::register,
+        workers::get_one,
+        workers::heartbeat,
+        workers::drain,
+        workers::resume,
+        workers::deregister,
         activity_task::list_tasks,
         activity_task::get_task,
         activity_task::get_tasks_by_run_id,
@@ -95,11 +117,24 @@ use stepflow_dto::dto;
         schemas(
             dto::template::TemplateDto,
             dto::template::TemplateUpsert,
+            dto::template::TemplateRevisionDto,
+            dto::template::TemplateAliasDto,
+            dto::template::SetAliasRequest,
+            dto::template::RevisionDiffQuery,
+            dto::template::TemplateDiffDto,
+            dto::template::DslChange,
             dto::execution::ExecStart,
             dto::execution::ExecDto,
+            dto::execution::ExecStop,
             dto::worker::PollRequest,
             dto::worker::PollResponse,
             dto::worker::UpdateRequest,
+            dto::worker::WorkerHeartbeatRequest,
+            dto::worker::WorkerStreamRequest,
+            dto::worker::WorkerStreamEvent,
+            dto::worker::RegisterWorkerRequest,
+            dto::worker::WorkerLivenessRequest,
+            dto::worker::WorkerInfoDto,
             dto::activity_task::ListQuery,
             dto::activity_task::ActivityTaskDto,
             dto::activity_task::CompleteRequest,
@@ -123,6 +158,7 @@ use stepflow_dto::dto;
         (name = "templates", description = "工作流模板管理"),
         (name = "executions", description = "工作流执行管理"),
         (name = "worker", description = "Worker 任务管理"),
+        (name = "workers", description = "Worker 注册表"),
         (name = "activity_tasks", description = "活动任务管理"),
         (name = "workflow_events", description = "工作流事件管理"),
         (name = "queue_tasks", description = "队列任务管理"),
@@ -1,9 +1,12 @@
 use axum::{
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
-    extract::{Path, State}
+    extract::{Path, Query, State}
+};
+use stepflow_dto::dto::template::{
+    RevisionDiffQuery, SetAliasRequest, TemplateAliasDto, TemplateDiffDto, TemplateDto,
+    TemplateRevisionDto, TemplateUpsert,
 };
-use stepflow_dto::dto::template::{TemplateUpsert, TemplateDto};   
 
 use crate::{
     service::{TemplateSvc, TemplateService},
@@ -12,10 +15,17 @@ use stepflow_core::{
     app_state::AppState,
     error::AppResult,
 };
+use stepflow_engine::engine::template::LATEST;
 pub fn router(svc: TemplateSvc) -> Router<AppState> {
     Router::new()
         .route("/", post(create).get(list))
         .route("/:id", get(get_one).put(update).delete(delete_one))
+        .route("/:id/revisions", get(list_revisions))
+        .route("/:id/revisions/:revision", get(get_revision))
+        .route("/:id/revisions/:revision/publish", post(publish_revision))
+        .route("/:id/diff", get(diff_revisions))
+        .route("/:id/aliases", get(list_aliases))
+        .route("/:id/aliases/:alias", put(set_alias).delete(delete_alias))
         .with_state(svc)
 }
 
@@ -75,7 +85,7 @@ pub async fn get_one(
     Ok(Json(svc.get(&id).await?))
 }
 
-/// 更新工作流模板
+/// 更新工作流模板：DSL 作为新的修订追加（`draft = true` 时保存为草稿），已有修订不变
 #[utoipa::path(
     put,
     path = "/v1/templates/{id}",
@@ -119,4 +129,162 @@ pub async fn delete_one(
 ) -> AppResult<()> {
     svc.delete(&id).await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// 获取模板的全部修订
+#[utoipa::path(
+    get,
+    path = "/v1/templates/{id}/revisions",
+    params(
+        ("id" = String, Path, description = "模板 ID")
+    ),
+    responses(
+        (status = 200, description = "成功获取修订列表", body = Vec<TemplateRevisionDto>),
+        (status = 404, description = "模板不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn list_revisions(
+    State(svc): State<TemplateSvc>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<TemplateRevisionDto>>> {
+    Ok(Json(svc.revisions(&id).await?))
+}
+
+/// 获取模板的某个修订
+#[utoipa::path(
+    get,
+    path = "/v1/templates/{id}/revisions/{revision}",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        ("revision" = String, Path, description = "修订号、别名或 latest")
+    ),
+    responses(
+        (status = 200, description = "成功获取修订", body = TemplateRevisionDto),
+        (status = 404, description = "模板或修订不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn get_revision(
+    State(svc): State<TemplateSvc>,
+    Path((id, revision)): Path<(String, String)>,
+) -> AppResult<Json<TemplateRevisionDto>> {
+    Ok(Json(svc.revision(&id, &revision).await?))
+}
+
+/// 发布草稿修订；发布后可被执行与别名引用
+#[utoipa::path(
+    post,
+    path = "/v1/templates/{id}/revisions/{revision}/publish",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        ("revision" = i64, Path, description = "修订号")
+    ),
+    responses(
+        (status = 200, description = "成功发布修订", body = TemplateRevisionDto),
+        (status = 404, description = "模板或修订不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn publish_revision(
+    State(svc): State<TemplateSvc>,
+    Path((id, revision)): Path<(String, i64)>,
+) -> AppResult<Json<TemplateRevisionDto>> {
+    Ok(Json(svc.publish(&id, revision).await?))
+}
+
+/// 对比两个修订的 DSL
+#[utoipa::path(
+    get,
+    path = "/v1/templates/{id}/diff",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        RevisionDiffQuery
+    ),
+    responses(
+        (status = 200, description = "成功对比修订", body = TemplateDiffDto),
+        (status = 404, description = "模板或修订不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn diff_revisions(
+    State(svc): State<TemplateSvc>,
+    Path(id): Path<String>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> AppResult<Json<TemplateDiffDto>> {
+    let to = query.to.as_deref().unwrap_or(LATEST);
+    Ok(Json(svc.diff(&id, &query.from, to).await?))
+}
+
+/// 获取模板的全部别名
+#[utoipa::path(
+    get,
+    path = "/v1/templates/{id}/aliases",
+    params(
+        ("id" = String, Path, description = "模板 ID")
+    ),
+    responses(
+        (status = 200, description = "成功获取别名列表", body = Vec<TemplateAliasDto>),
+        (status = 404, description = "模板不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn list_aliases(
+    State(svc): State<TemplateSvc>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Vec<TemplateAliasDto>>> {
+    Ok(Json(svc.aliases(&id).await?))
+}
+
+/// 创建或移动别名，使其指向某个已发布的修订
+#[utoipa::path(
+    put,
+    path = "/v1/templates/{id}/aliases/{alias}",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        ("alias" = String, Path, description = "别名，如 stable")
+    ),
+    request_body = SetAliasRequest,
+    responses(
+        (status = 200, description = "成功设置别名", body = TemplateAliasDto),
+        (status = 400, description = "别名保留或修订未发布"),
+        (status = 404, description = "模板不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn set_alias(
+    State(svc): State<TemplateSvc>,
+    Path((id, alias)): Path<(String, String)>,
+    Json(body): Json<SetAliasRequest>,
+) -> AppResult<Json<TemplateAliasDto>> {
+    Ok(Json(svc.set_alias(&id, &alias, body.revision).await?))
+}
+
+/// 删除别名
+#[utoipa::path(
+    delete,
+    path = "/v1/templates/{id}/aliases/{alias}",
+    params(
+        ("id" = String, Path, description = "模板 ID"),
+        ("alias" = String, Path, description = "别名")
+    ),
+    responses(
+        (status = 200, description = "成功删除别名"),
+        (status = 404, description = "别名不存在"),
+        (status = 500, description = "服务器内部错误")
+    ),
+    tag = "templates"
+)]
+pub async fn delete_alias(
+    State(svc): State<TemplateSvc>,
+    Path((id, alias)): Path<(String, String)>,
+) -> AppResult<()> {
+    svc.delete_alias(&id, &alias).await?;
+    Ok(())
+}
@@ -1,27 +1,76 @@
 //! routes/worker.rs — 适配新 MatchService Trait
 
 use axum::{
-    extract::State,
-    routing::post,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::{get, post},
     Json, Router,
 };
 use chrono::Utc;
-use std::time::Duration;
-use tracing::{debug, info};    
+use futures::{SinkExt, StreamExt};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
 
 use stepflow_core::{
     app_state::AppState,
     error::{AppError, AppResult},
 };
 
+use stepflow_engine::engine::root_run_id;
+use stepflow_storage::entities::worker::WORKER_ACTIVE;
 use stepflow_dto::dto::{
     queue_task::UpdateQueueTaskDto,
     signal::ExecutionSignal,
-    worker::{PollRequest, PollResponse, TaskStatus, UpdateRequest},
+    queue_task::QueueTaskDto,
+    worker::{
+        PollRequest, PollResponse, TaskStatus, UpdateRequest, WorkerHeartbeatRequest,
+        WorkerStreamEvent, WorkerStreamRequest,
+    },
 };
 
-const DEFAULT_QUEUE:  &str = "default_task_queue";
-const POLL_TIMEOUT_S: u64  = 30;
+/// 长轮询：请求未指定 `wait_seconds` 时的挂起时长与上限
+const DEFAULT_POLL_WAIT_S: u64 = 30;
+const MAX_POLL_WAIT_S: u64 = 60;
+
+/// 推送连接每次等待任务的时长；到点后检查连接是否仍在再继续等待
+const STREAM_WAIT_S: u64 = 10;
+/// 推送连接每轮取任务的最短耗时：匹配服务没有等待就返回空时在此补足，避免空转
+const MIN_STREAM_POLL: Duration = Duration::from_secs(1);
+
+/// 派发出去的任务 → 轮询响应
+fn task_response(task: QueueTaskDto) -> PollResponse {
+    PollResponse {
+        has_task:  true,
+        run_id:    Some(task.run_id),
+        state_name:Some(task.state_name),
+        tool_type: Some(task.resource),
+        task_id:   Some(task.task_id),
+        input:     task.task_payload,
+        heartbeat_seconds: task.heartbeat_seconds,
+    }
+}
+
+/// 刷新已注册 worker 的存活时间，并判断能否给它派发新任务：
+/// 排空中 / 已下线的不再派发；未注册的 worker 照常派发
+async fn accepts_new_tasks(app: &AppState, worker_id: &str) -> bool {
+    if let Err(e) = app.persist.touch_worker(worker_id, None, Utc::now().naive_utc()).await {
+        warn!("touch worker {worker_id} failed: {e}");
+    }
+    match app.persist.get_worker(worker_id).await {
+        Ok(worker) => worker.is_none_or(|w| w.status == WORKER_ACTIVE),
+        Err(e) => {
+            warn!("get worker {worker_id} failed: {e}");
+            true
+        }
+    }
+}
 
 // ───────────────────────── poll ────────────────────────────────
 #[utoipa::path(
@@ -39,34 +88,45 @@ pub async fn poll_task(
     State(app): State<AppState>,
     Json(req): Json<PollRequest>,
 ) -> AppResult<Json<PollResponse>> {
+    // 轮询的队列 = capabilities（以工具命名的默认队列）∪ task_queues（命名队列）
+    let queues = req.queues();
+    if queues.is_empty() {
+        return Err(AppError::BadRequest(
+            "capabilities or task_queues must name at least one queue".into(),
+        ));
+    }
+    // 长轮询：无任务时挂起到有任务入队或超时
+    let wait_s = req.wait_seconds.unwrap_or(DEFAULT_POLL_WAIT_S).min(MAX_POLL_WAIT_S);
     info!(
-        "[/poll] worker={} queue={} timeout={}s",
-        req.worker_id, DEFAULT_QUEUE, POLL_TIMEOUT_S
+        "[/poll] worker={} queues={:?} timeout={}s",
+        req.worker_id, queues, wait_s
     );
 
+    app.pollers.record(&req.worker_id, &queues, Utc::now());
+
+    // 排空中的 worker：挂起到超时后返回空响应，避免它立即重新轮询
+    if !accepts_new_tasks(&app, &req.worker_id).await {
+        info!("🚰 worker={} is draining, no new tasks", req.worker_id);
+        tokio::time::sleep(Duration::from_secs(wait_s)).await;
+        return Ok(Json(PollResponse::empty()));
+    }
+
     let task_opt = app
         .match_service
-        .take_task(
-            DEFAULT_QUEUE,
+        .take_task_from(
+            &queues,
             &req.worker_id,
-            Duration::from_secs(POLL_TIMEOUT_S),
+            Duration::from_secs(wait_s),
         )
         .await;
 
     let resp = match task_opt {
         Some(task) => {
             info!(
-                "✅ task found: run_id={} state={}",
-                task.run_id, task.state_name
+                "✅ task found: run_id={} state={} queue={}",
+                task.run_id, task.state_name, task.task_queue
             );
-            PollResponse {
-                has_task:  true,
-                run_id:    Some(task.run_id),
-                state_name:Some(task.state_name),
-                tool_type: Some(task.resource),
-                task_id:   Some(task.task_id),
-                input:     task.task_payload,
-            }
+            task_response(task)
         }
         None => {
             info!("❌ no task for worker={}", req.worker_id);
@@ -77,6 +137,112 @@ pub async fn poll_task(
     Ok(Json(resp))
 }
 
+// ───────────────────────── stream ──────────────────────────────
+/// WebSocket 推送：首条消息 `subscribe` 声明队列与空闲槽位，网关据此推送任务，
+/// 每推送一个占用一个槽位；worker 执行完后发 `credit` 归还槽位。结果仍经 `/update` 上报
+#[utoipa::path(
+    get,
+    path       = "/v1/worker/stream",
+    tag        = "worker",
+    responses(
+        (status = 101, description = "升级为 WebSocket；消息格式见 WorkerStreamRequest / WorkerStreamEvent"),
+    )
+)]
+pub async fn stream_tasks(State(app): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| serve_stream(app, socket))
+}
+
+async fn serve_stream(app: AppState, socket: WebSocket) {
+    let (mut sink, mut stream) = socket.split();
+
+    // ① 订阅
+    let subscribe = match stream.next().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str::<WorkerStreamRequest>(&text).ok(),
+        _ => return,
+    };
+    let (req, slots) = match subscribe {
+        Some(sub @ WorkerStreamRequest::Subscribe { slots, .. }) => (sub.poll_request(), slots),
+        _ => (None, 0),
+    };
+    let Some(req) = req.filter(|r| !r.queues().is_empty()) else {
+        let _ = send_event(&mut sink, &WorkerStreamEvent::Error {
+            message: "first message must be a subscribe naming at least one queue".into(),
+        })
+        .await;
+        return;
+    };
+    let queues = req.queues();
+    info!(worker=%req.worker_id, ?queues, slots, "🔌 worker stream subscribed");
+    if send_event(&mut sink, &WorkerStreamEvent::Subscribed { queues: queues.clone() }).await.is_err() {
+        return;
+    }
+
+    // ② 读取 credit：空闲槽位即信号量许可，连接断开时关闭信号量
+    let credits = Arc::new(Semaphore::new(slots as usize));
+    let reader = {
+        let credits = credits.clone();
+        tokio::spawn(async move {
+            while let Some(Ok(msg)) = stream.next().await {
+                match msg {
+                    Message::Text(text) => match serde_json::from_str::<WorkerStreamRequest>(&text) {
+                        Ok(WorkerStreamRequest::Credit { slots }) => credits.add_permits(slots as usize),
+                        _ => debug!("ignored worker stream message: {text}"),
+                    },
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            credits.close();
+        })
+    };
+
+    // ③ 有空闲槽位时取任务并推送；取任务不会被中途取消，派发出去的任务不会凭空丢失
+    while let Ok(permit) = credits.acquire().await {
+        permit.forget();
+        let task = loop {
+            if credits.is_closed() {
+                break None;
+            }
+            app.pollers.record(&req.worker_id, &queues, Utc::now());
+            if !accepts_new_tasks(&app, &req.worker_id).await {
+                tokio::time::sleep(Duration::from_secs(STREAM_WAIT_S)).await;
+                continue;
+            }
+            let started = Instant::now();
+            if let Some(task) = app
+                .match_service
+                .take_task_from(&queues, &req.worker_id, Duration::from_secs(STREAM_WAIT_S))
+                .await
+            {
+                break Some(task);
+            }
+            tokio::time::sleep(MIN_STREAM_POLL.saturating_sub(started.elapsed())).await;
+        };
+        let Some(task) = task else { break };
+
+        info!(worker=%req.worker_id, run_id=%task.run_id, state=%task.state_name, "📤 task pushed");
+        let (run_id, state_name) = (task.run_id.clone(), task.state_name.clone());
+        if send_event(&mut sink, &WorkerStreamEvent::Task(task_response(task))).await.is_err() {
+            // 连接已断开：任务退回 pending（不计重试次数），交给其他 worker
+            if let Err(e) = app.match_service.requeue_task(&run_id, &state_name).await {
+                warn!("release undelivered task {run_id}/{state_name} failed: {e}");
+            }
+            break;
+        }
+    }
+
+    reader.abort();
+    info!(worker=%req.worker_id, "🔌 worker stream closed");
+}
+
+async fn send_event(
+    sink: &mut futures::stream::SplitSink<WebSocket, Message>,
+    event: &WorkerStreamEvent,
+) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).expect("stream event serializes");
+    sink.send(Message::Text(text)).await
+}
+
 // ───────────────────────── update ──────────────────────────────
 #[utoipa::path(
     post,
@@ -127,11 +293,13 @@ pub async fn update_task_status(
         .map_err(|e| AppError::Anyhow(anyhow::anyhow!(e)))?;
 
     // ── 2️⃣ 找到内存中的引擎并推送 ExecutionSignal ────────────────
+    // Parallel 分支子执行的任务由根执行的引擎接收信号
+    let root_id = root_run_id(&req.run_id).to_string();
     let mut engines = app.engines.lock().await;
     debug!("🧠 engine count={}", engines.len());
 
     let engine = engines
-        .get_mut(&req.run_id)
+        .get_mut(&root_id)
         .ok_or(AppError::NotFound)?;
 
     let signal = match req.status {
@@ -144,6 +312,8 @@ pub async fn update_task_status(
             run_id:     req.run_id.clone(),
             state_name: req.state_name.clone(),
             error:      req.result.to_string(),
+            // 失败结果形如 { "error": "<ErrorType>", "cause": ... } 时按该类型匹配 Retry
+            error_type: req.result.get("error").and_then(|v| v.as_str()).map(str::to_string),
         },
         TaskStatus::CANCELLED => ExecutionSignal::TaskCancelled {
             run_id:     req.run_id.clone(),
@@ -170,16 +340,44 @@ pub async fn update_task_status(
 
     // 若执行完毕，移除引擎
     if engine.finished {
-        engines.remove(&req.run_id);
-        info!(run_id=%req.run_id, "🏁 workflow finished, engine removed");
+        engines.remove(&root_id);
+        info!(run_id=%root_id, "🏁 workflow finished, engine removed");
     }
 
     Ok(())
 }
 
+// ───────────────────────── heartbeat ───────────────────────────
+#[utoipa::path(
+    post,
+    path       = "/v1/worker/heartbeat",
+    request_body = WorkerHeartbeatRequest,
+    tag        = "worker",
+    responses(
+        (status = 200, description = "成功"),
+        (status = 404, description = "任务不在处理中（已完成或已被超时回收）")
+    )
+)]
+pub async fn heartbeat_task(
+    State(app): State<AppState>,
+    Json(req): Json<WorkerHeartbeatRequest>,
+) -> AppResult<()> {
+    debug!(worker=%req.worker_id, run_id=%req.run_id, state=%req.state_name, "💓 heartbeat");
+    if let Err(e) = app.persist.touch_worker(&req.worker_id, None, Utc::now().naive_utc()).await {
+        warn!("touch worker {} failed: {e}", req.worker_id);
+    }
+
+    app.match_service
+        .heartbeat(&req.run_id, &req.state_name)
+        .await
+        .map_err(|_| AppError::NotFound)
+}
+
 // ───────────────────────── router ──────────────────────────────
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/poll",   post(poll_task))
-        .route("/update", post(update_task_status))
+        .route("/poll",      post(poll_task))
+        .route("/stream",    get(stream_tasks))
+        .route("/update",    post(update_task_status))
+        .route("/heartbeat", post(heartbeat_task))
 }
\ No newline at end of file
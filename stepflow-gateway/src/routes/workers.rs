@@ -0,0 +1,213 @@
+//! routes/workers.rs —— worker 注册表：注册 / 存活心跳 / 排空 / 注销
+
+use anyhow::anyhow;
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use tracing::info;
+
+use stepflow_core::{
+    app_state::AppState,
+    error::{AppError, AppResult},
+};
+use stepflow_dto::dto::worker::{RegisterWorkerRequest, WorkerInfoDto, WorkerLivenessRequest};
+use stepflow_storage::entities::worker::{StoredWorker, WORKER_ACTIVE, WORKER_DRAINING, WORKER_OFFLINE};
+
+fn to_dto(w: StoredWorker) -> WorkerInfoDto {
+    WorkerInfoDto {
+        worker_id:     w.worker_id,
+        capabilities:  w.capabilities,
+        task_queues:   w.task_queues,
+        version:       w.version,
+        concurrency:   w.concurrency,
+        in_flight:     w.in_flight,
+        status:        w.status,
+        registered_at: w.registered_at.and_utc(),
+        last_seen_at:  w.last_seen_at.and_utc(),
+    }
+}
+
+async fn load(app: &AppState, worker_id: &str) -> AppResult<WorkerInfoDto> {
+    app.persist
+        .get_worker(worker_id)
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("get_worker failed: {e}")))?
+        .map(to_dto)
+        .ok_or(AppError::NotFound)
+}
+
+async fn set_status(app: &AppState, worker_id: &str, status: &str) -> AppResult<()> {
+    let found = app
+        .persist
+        .set_worker_status(worker_id, status)
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("set_worker_status failed: {e}")))?;
+    if found { Ok(()) } else { Err(AppError::NotFound) }
+}
+
+/// 列出全部 worker
+#[utoipa::path(
+    get,
+    path = "/v1/workers",
+    tag  = "workers",
+    responses(
+        (status = 200, description = "已注册的 worker", body = [WorkerInfoDto]),
+        (status = 500, description = "服务器内部错误")
+    )
+)]
+pub async fn list(State(app): State<AppState>) -> AppResult<Json<Vec<WorkerInfoDto>>> {
+    let workers = app
+        .persist
+        .list_workers()
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("list_workers failed: {e}")))?;
+    Ok(Json(workers.into_iter().map(to_dto).collect()))
+}
+
+/// 注册 worker；已注册（含已下线）时刷新能力与容量并恢复为 active
+#[utoipa::path(
+    post,
+    path = "/v1/workers",
+    request_body = RegisterWorkerRequest,
+    tag  = "workers",
+    responses(
+        (status = 200, description = "注册成功", body = WorkerInfoDto),
+        (status = 400, description = "请求参数错误"),
+        (status = 500, description = "服务器内部错误")
+    )
+)]
+pub async fn register(
+    State(app): State<AppState>,
+    Json(req): Json<RegisterWorkerRequest>,
+) -> AppResult<Json<WorkerInfoDto>> {
+    if req.worker_id.trim().is_empty() {
+        return Err(AppError::BadRequest("worker_id must not be empty".into()));
+    }
+    let now = Utc::now().naive_utc();
+    let worker = StoredWorker {
+        worker_id:     req.worker_id.clone(),
+        capabilities:  req.capabilities,
+        task_queues:   req.task_queues,
+        version:       req.version,
+        concurrency:   i64::from(req.concurrency.max(1)),
+        in_flight:     0,
+        status:        WORKER_ACTIVE.into(),
+        registered_at: now,
+        last_seen_at:  now,
+    };
+    app.persist
+        .upsert_worker(&worker)
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("upsert_worker failed: {e}")))?;
+    info!(worker=%worker.worker_id, version=?worker.version, concurrency=worker.concurrency, "📝 worker registered");
+    Ok(Json(load(&app, &req.worker_id).await?))
+}
+
+/// 查询单个 worker
+#[utoipa::path(
+    get,
+    path = "/v1/workers/{id}",
+    params(("id" = String, Path, description = "Worker ID")),
+    tag  = "workers",
+    responses(
+        (status = 200, body = WorkerInfoDto),
+        (status = 404, description = "worker 未注册")
+    )
+)]
+pub async fn get_one(State(app): State<AppState>, Path(id): Path<String>) -> AppResult<Json<WorkerInfoDto>> {
+    Ok(Json(load(&app, &id).await?))
+}
+
+/// 存活心跳：刷新 last_seen 并上报执行中任务数；
+/// 未注册或已被判定下线时返回 404，worker 应重新注册
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/heartbeat",
+    params(("id" = String, Path, description = "Worker ID")),
+    request_body = WorkerLivenessRequest,
+    tag  = "workers",
+    responses(
+        (status = 200, description = "成功"),
+        (status = 404, description = "worker 未注册或已下线")
+    )
+)]
+pub async fn heartbeat(
+    State(app): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<WorkerLivenessRequest>,
+) -> AppResult<()> {
+    let alive = app
+        .persist
+        .touch_worker(&id, req.in_flight.map(i64::from), Utc::now().naive_utc())
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("touch_worker failed: {e}")))?;
+    if alive { Ok(()) } else { Err(AppError::NotFound) }
+}
+
+/// 排空：不再给该 worker 派发新任务，已持有的任务照常完成并上报
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/drain",
+    params(("id" = String, Path, description = "Worker ID")),
+    tag  = "workers",
+    responses(
+        (status = 200, body = WorkerInfoDto),
+        (status = 404, description = "worker 未注册")
+    )
+)]
+pub async fn drain(State(app): State<AppState>, Path(id): Path<String>) -> AppResult<Json<WorkerInfoDto>> {
+    set_status(&app, &id, WORKER_DRAINING).await?;
+    info!(worker=%id, "🚰 worker draining");
+    Ok(Json(load(&app, &id).await?))
+}
+
+/// 取消排空，恢复派发
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/resume",
+    params(("id" = String, Path, description = "Worker ID")),
+    tag  = "workers",
+    responses(
+        (status = 200, body = WorkerInfoDto),
+        (status = 404, description = "worker 未注册")
+    )
+)]
+pub async fn resume(State(app): State<AppState>, Path(id): Path<String>) -> AppResult<Json<WorkerInfoDto>> {
+    set_status(&app, &id, WORKER_ACTIVE).await?;
+    info!(worker=%id, "▶️ worker resumed");
+    Ok(Json(load(&app, &id).await?))
+}
+
+/// 注销（worker 正常退出）：标记为下线，仍持有的任务退回队列交给其他 worker
+#[utoipa::path(
+    delete,
+    path = "/v1/workers/{id}",
+    params(("id" = String, Path, description = "Worker ID")),
+    tag  = "workers",
+    responses(
+        (status = 200, description = "已注销"),
+        (status = 404, description = "worker 未注册")
+    )
+)]
+pub async fn deregister(State(app): State<AppState>, Path(id): Path<String>) -> AppResult<()> {
+    set_status(&app, &id, WORKER_OFFLINE).await?;
+    let requeued = app
+        .match_service
+        .requeue_worker_tasks(&id)
+        .await
+        .map_err(|e| AppError::Anyhow(anyhow!("requeue_worker_tasks failed: {e}")))?;
+    info!(worker=%id, requeued = requeued.len(), "👋 worker deregistered");
+    Ok(())
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list).post(register))
+        .route("/:id", get(get_one).delete(deregister))
+        .route("/:id/heartbeat", post(heartbeat))
+        .route("/:id/drain", post(drain))
+        .route("/:id/resume", post(resume))
+}
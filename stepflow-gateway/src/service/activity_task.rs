@@ -1,14 +1,18 @@
 //! activity_task_sqlx_svc.rs
 //! —— 依赖 sqlx 后端的 ActivityTaskService 实现（使用统一 DynPM 别名）
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use chrono::Utc;
 use serde_json::Value;
 
+use stepflow_core::app_state::AppState;
 use stepflow_dto::dto::activity_task::*;
+use stepflow_dto::dto::signal::ExecutionSignal;
 use stepflow_storage::db::DynPM;
 use stepflow_storage::{
-    entities::activity_task::UpdateStoredActivityTask,
+    entities::activity_task::{StoredActivityTask, UpdateStoredActivityTask},
     error::StorageError,
 };
 
@@ -22,11 +26,31 @@ use stepflow_core::error::{AppError, AppResult};
 #[derive(Clone)]
 pub struct ActivityTaskSqlxSvc {
     pm: DynPM,
+    state: Arc<AppState>,
 }
 
 impl ActivityTaskSqlxSvc {
-    pub fn new(pm: DynPM) -> Self {
-        Self { pm }
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { pm: state.persist.clone(), state }
+    }
+
+    /// 回调 token 完成 / 失败后唤醒挂起在该 Task 上的执行
+    async fn resume_execution(&self, task: &StoredActivityTask, signal: ExecutionSignal) -> AppResult<()> {
+        if task.state_name.is_none() {
+            return Ok(());
+        }
+        self.state
+            .deliver_signal(&task.run_id, signal)
+            .await
+            .map_err(AppError::BadRequest)
+    }
+
+    async fn stored_task(&self, task_token: &str) -> AppResult<StoredActivityTask> {
+        self.pm
+            .get_task(task_token)
+            .await
+            .map_err(AppError::from_storage)?
+            .ok_or(AppError::NotFound)
     }
 }
 
@@ -93,7 +117,7 @@ impl ActivityTaskService for ActivityTaskSqlxSvc {
     }
 
     async fn complete_task(&self, task_token: &str, result: Value) -> AppResult<ActivityTaskDto> {
-        let task = self.get_task(task_token).await?;
+        let task = self.stored_task(task_token).await?;
         if task.status != "RUNNING" {
             return Err(AppError::BadRequest(format!(
                 "Activity task {} is not in running state: {}",
@@ -106,7 +130,7 @@ impl ActivityTaskService for ActivityTaskSqlxSvc {
                 task_token,
                 &UpdateStoredActivityTask {
                     status:       Some("COMPLETED".into()),
-                    result:       Some(Some(result)),
+                    result:       Some(Some(result.clone())),
                     completed_at: Some(Some(Utc::now().naive_utc())),
                     ..Default::default()
                 },
@@ -114,11 +138,18 @@ impl ActivityTaskService for ActivityTaskSqlxSvc {
             .await
             .map_err(AppError::from_storage)?;
 
+        let signal = ExecutionSignal::TaskCompleted {
+            run_id:     task.run_id.clone(),
+            state_name: task.state_name.clone().unwrap_or_default(),
+            output:     result,
+        };
+        self.resume_execution(&task, signal).await?;
+
         self.get_task(task_token).await
     }
 
     async fn fail_task(&self, task_token: &str, req: FailRequest) -> AppResult<ActivityTaskDto> {
-        let task = self.get_task(task_token).await?;
+        let task = self.stored_task(task_token).await?;
         if task.status != "RUNNING" {
             return Err(AppError::BadRequest(format!(
                 "Activity task {} is not in running state: {}",
@@ -131,8 +162,8 @@ impl ActivityTaskService for ActivityTaskSqlxSvc {
                 task_token,
                 &UpdateStoredActivityTask {
                     status:        Some("FAILED".into()),
-                    error:         Some(Some(req.reason)),
-                    error_details: Some(req.details),
+                    error:         Some(Some(req.reason.clone())),
+                    error_details: Some(req.details.clone()),
                     completed_at:  Some(Some(Utc::now().naive_utc())),
                     ..Default::default()
                 },
@@ -140,6 +171,14 @@ impl ActivityTaskService for ActivityTaskSqlxSvc {
             .await
             .map_err(AppError::from_storage)?;
 
+        let signal = ExecutionSignal::TaskFailed {
+            run_id:     task.run_id.clone(),
+            state_name: task.state_name.clone().unwrap_or_default(),
+            error:      req.reason,
+            error_type: req.details,
+        };
+        self.resume_execution(&task, signal).await?;
+
         self.get_task(task_token).await
     }
 
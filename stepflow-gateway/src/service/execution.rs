@@ -1,5 +1,5 @@
 
-use anyhow::{Context, Error};
+use anyhow::Error;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::sync::Arc;
@@ -8,10 +8,26 @@ use stepflow_core::{
     error::{AppError, AppResult},
 };
 use stepflow_dto::dto::execution::*;
+use stepflow_dto::dto::signal::ExecutionSignal;
+use stepflow_engine::engine::template::{self, RevisionSelector};
 use stepflow_engine::engine::{WorkflowEngine, WorkflowMode};
 use stepflow_storage::entities::workflow_execution::StoredWorkflowExecution;
 use stepflow_storage::error::StorageError;
 
+fn exec_dto(row: StoredWorkflowExecution) -> ExecDto {
+    ExecDto {
+        run_id: row.run_id,
+        mode: row.mode,
+        status: row.status,
+        result: row.result,
+        started_at: row.start_time.and_utc(),
+        finished_at: Option::map(row.close_time, |t| t.and_utc()),
+        parent_run_id: row.parent_run_id,
+        template_id: row.template_id,
+        template_revision: row.template_revision,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ExecutionSqlxSvc {
     state: Arc<AppState>,
@@ -36,30 +52,33 @@ impl ExecutionSqlxSvc {
 #[async_trait]
 impl crate::service::ExecutionService for ExecutionSqlxSvc {
     async fn start(&self, req: ExecStart) -> AppResult<ExecDto> {
-        // ① 准备 DSL
-        let dsl_val = if let Some(tpl_id) = &req.template_id {
-            let tpl = self.state.persist.get_template(tpl_id).await
+        // ① 准备 DSL：模板按请求的修订 / 别名（默认最新发布的修订）解析，执行固定在该修订上
+        let (dsl, template_revision) = if let Some(tpl_id) = &req.template_id {
+            self.state.persist.get_template(tpl_id).await
                 .map_err(|e: StorageError| Error::new(e))?
                 .ok_or(AppError::NotFound)?;
-            serde_json::from_str::<Value>(&tpl.dsl_definition)
-                .context("解析模板 DSL 失败")?
+            let selector = RevisionSelector::from_parts(req.template_revision, req.template_alias.as_deref());
+            let revision = template::runnable_revision(&self.state.persist, tpl_id, &selector)
+                .await
+                .map_err(AppError::BadRequest)?;
+            (template::revision_dsl(&revision).map_err(AppError::BadRequest)?, Some(revision.revision))
         } else {
-            req.dsl.clone()
-                .ok_or(AppError::BadRequest("dsl or template_id required".into()))?
-        };
-
-        let dsl = match dsl_val {
-            Value::Object(_) => {
-                serde_json::from_value(dsl_val)
-                    .map_err(|e| AppError::BadRequest(format!("invalid DSL: {e}")))?
-            }
-            Value::String(ref s) => {
-                serde_json::from_str(s)
-                    .map_err(|e| AppError::BadRequest(format!("invalid DSL string: {e}")))?
-            }
-            _ => {
-                return Err(AppError::BadRequest("DSL must be a JSON object or JSON string".into()));
-            }
+            let dsl_val = req.dsl.clone()
+                .ok_or(AppError::BadRequest("dsl or template_id required".into()))?;
+            let dsl = match dsl_val {
+                Value::Object(_) => {
+                    serde_json::from_value(dsl_val)
+                        .map_err(|e| AppError::BadRequest(format!("invalid DSL: {e}")))?
+                }
+                Value::String(ref s) => {
+                    serde_json::from_str(s)
+                        .map_err(|e| AppError::BadRequest(format!("invalid DSL string: {e}")))?
+                }
+                _ => {
+                    return Err(AppError::BadRequest("DSL must be a JSON object or JSON string".into()));
+                }
+            };
+            (dsl, None)
         };
 
         // ---------- ② 生成 run_id / 创建引擎 ----------
@@ -74,7 +93,8 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             self.state.event_dispatcher.clone(),
             self.state.persist.clone(),
             self.state.state_handler_registry.clone(),
-        );
+        )
+        .with_timer_shard_count(self.state.timer_shard_count);
 
         // ---------- ③ 先落库 execution(状态 RUNNING) 防止锁冲突 ----------
         let started_at = chrono::Utc::now();
@@ -83,6 +103,8 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             workflow_id: Some(format!("wf-{run_id}")),
             shard_id: 0,
             template_id: req.template_id.clone(),
+            template_revision,
+            parent_run_id: None,
             mode: req.mode.clone(),
             current_state_name: Some("initial".into()),
             status: "RUNNING".into(),
@@ -112,7 +134,17 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
                     .run_inline()
                     .await
                     .map_err(|e| AppError::BadRequest(format!("执行工作流失败: {e}")))?;
-                ("COMPLETED".to_string(), Some(res), Some(chrono::Utc::now()))
+                if engine.finished {
+                    ("COMPLETED".to_string(), Some(res), Some(chrono::Utc::now()))
+                } else {
+                    // 长等待已转为定时器：交给定时器调度器唤醒
+                    self.state
+                        .engines
+                        .lock()
+                        .await
+                        .insert(run_id.clone(), engine);
+                    ("RUNNING".to_string(), None, None)
+                }
             }
             WorkflowMode::Deferred => {
                 engine.advance_until_blocked().await.ok(); // 允许 None
@@ -150,6 +182,9 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             result: final_result,
             started_at,
             finished_at,
+            parent_run_id: None,
+            template_id: req.template_id,
+            template_revision,
         })
     }
     async fn get(&self, id: &str) -> AppResult<ExecDto> {
@@ -160,14 +195,7 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             .await
             .map_err(|e: StorageError| Error::new(e))?
             .ok_or(AppError::NotFound)?;
-        Ok(ExecDto {
-            run_id: row.run_id,
-            mode: row.mode,
-            status: row.status,
-            result: row.result,
-            started_at: row.start_time.and_utc(),
-            finished_at: Option::map(row.close_time, |t| t.and_utc()),
-        })
+        Ok(exec_dto(row))
     }
 
     async fn list(&self, limit: i64, offset: i64) -> AppResult<Vec<ExecDto>> {
@@ -179,15 +207,8 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             .map_err(|e: StorageError| Error::new(e))?;
         Ok(rows
             .into_iter()
-            .map(|r| ExecDto {
-                run_id: r.run_id,
-                mode: r.mode,
-                status: r.status,
-                result: r.result,
-                started_at: r.start_time.and_utc(),
-                finished_at: Option::map(r.close_time, |t| t.and_utc()),
-            })
-            .collect::<Vec<_>>())
+            .map(exec_dto)
+            .collect())
     }
 
     async fn update(&self, run_id: &str, status: String, result: Option<Value>) -> AppResult<()> {
@@ -229,14 +250,46 @@ impl crate::service::ExecutionService for ExecutionSqlxSvc {
             .map_err(|e| AppError::Anyhow(anyhow::anyhow!("list_by_status failed: {}", e)))?;
         Ok(rows
             .into_iter()
-            .map(|r| ExecDto {
-                run_id: r.run_id,
-                mode: r.mode,
-                status: r.status,
-                result: r.result,
-                started_at: r.start_time.and_utc(),
-                finished_at: Option::map(r.close_time, |t| t.and_utc()),
-            })
+            .map(exec_dto)
             .collect())
     }
+
+    async fn children(&self, run_id: &str, limit: i64, offset: i64) -> AppResult<Vec<ExecDto>> {
+        self.state
+            .persist
+            .get_execution(run_id)
+            .await
+            .map_err(|e: StorageError| Error::new(e))?
+            .ok_or(AppError::NotFound)?;
+        let rows = self
+            .state
+            .persist
+            .find_executions_by_parent(run_id, limit, offset)
+            .await
+            .map_err(|e| AppError::Anyhow(anyhow::anyhow!("list children failed: {}", e)))?;
+        Ok(rows.into_iter().map(exec_dto).collect())
+    }
+
+    async fn signal(&self, run_id: &str, signal_name: &str, payload: Value) -> AppResult<ExecDto> {
+        self.get(run_id).await?;
+        let signal = ExecutionSignal::SignalReceived {
+            run_id: run_id.to_string(),
+            signal_name: signal_name.to_string(),
+            payload,
+        };
+        self.state
+            .deliver_signal(run_id, signal)
+            .await
+            .map_err(AppError::BadRequest)?;
+        self.get(run_id).await
+    }
+
+    async fn stop(&self, run_id: &str, reason: Option<String>, terminate: bool) -> AppResult<ExecDto> {
+        self.get(run_id).await?;
+        self.state
+            .stop_execution(run_id, reason, terminate)
+            .await
+            .map_err(AppError::BadRequest)?;
+        self.get(run_id).await
+    }
 }
@@ -12,6 +12,15 @@ pub trait TemplateService: Clone + Send + Sync + 'static {
     async fn get   (&self, id: &str) -> AppResult<TemplateDto>;
     async fn list  (&self) -> AppResult<Vec<TemplateDto>>;
     async fn delete(&self, id: &str) -> AppResult<()>;
+    /// 模板的全部修订（按修订号升序）
+    async fn revisions(&self, id: &str) -> AppResult<Vec<TemplateRevisionDto>>;
+    /// 按修订号、别名或 `latest` 获取修订
+    async fn revision(&self, id: &str, selector: &str) -> AppResult<TemplateRevisionDto>;
+    async fn publish(&self, id: &str, revision: i64) -> AppResult<TemplateRevisionDto>;
+    async fn diff(&self, id: &str, from: &str, to: &str) -> AppResult<TemplateDiffDto>;
+    async fn aliases(&self, id: &str) -> AppResult<Vec<TemplateAliasDto>>;
+    async fn set_alias(&self, id: &str, alias: &str, revision: i64) -> AppResult<TemplateAliasDto>;
+    async fn delete_alias(&self, id: &str, alias: &str) -> AppResult<()>;
 }
 
 pub use template::TemplateSqlxSvc as TemplateSvc;
@@ -29,6 +38,10 @@ pub trait ExecutionService: Clone + Send + Sync + 'static {
     async fn update(&self, run_id: &str, status: String, result: Option<Value>) -> AppResult<()>;
     async fn delete(&self, run_id: &str) -> AppResult<()>;
     async fn list_by_status(&self, status: &str, limit: i64, offset: i64) -> AppResult<Vec<ExecDto>>;
+    async fn children(&self, run_id: &str, limit: i64, offset: i64) -> AppResult<Vec<ExecDto>>;
+    async fn signal(&self, run_id: &str, signal_name: &str, payload: Value) -> AppResult<ExecDto>;
+    /// 取消（`terminate = false`，运行 onCancel 清理状态）或终止执行
+    async fn stop(&self, run_id: &str, reason: Option<String>, terminate: bool) -> AppResult<ExecDto>;
 }
 
 pub mod activity_task;
@@ -27,12 +27,18 @@ impl QueueTaskSqlxSvc {
             run_id: stored.run_id,
             state_name: stored.state_name,
             resource: stored.resource,
+            task_queue: stored.task_queue,
+            root_run_id: stored.root_run_id,
+            template_id: stored.template_id,
+            worker_id: stored.worker_id,
             task_payload: stored.task_payload,
             status: stored.status,
             attempts: stored.attempts,
             max_attempts: stored.max_attempts,
             priority: stored.priority.map(|p| p as u8),
             timeout_seconds: stored.timeout_seconds,
+            heartbeat_seconds: stored.heartbeat_seconds,
+            last_heartbeat_at: stored.last_heartbeat_at.map(|t| t.and_utc()),
             error_message: stored.error_message,
             last_error_at: stored.last_error_at.map(|t| t.and_utc()),
             next_retry_at: stored.next_retry_at.map(|t| t.and_utc()),
@@ -51,11 +57,14 @@ impl QueueTaskSqlxSvc {
             error_message: dto.error_message,
             priority: dto.priority.map(|p| p as i64),
             timeout_seconds: dto.timeout_seconds,
+            heartbeat_seconds: dto.heartbeat_seconds,
+            last_heartbeat_at: dto.last_heartbeat_at.map(|opt| opt.map(|dt| dt.naive_utc())),
             last_error_at: dto.last_error_at.map(|opt| opt.map(|dt| dt.naive_utc())),
             next_retry_at: dto.next_retry_at.map(|opt| opt.map(|dt| dt.naive_utc())),
             processing_at: dto.processing_at.map(|opt| opt.map(|dt| dt.naive_utc())),
             completed_at: dto.completed_at.map(|opt| opt.map(|dt| dt.naive_utc())),
             failed_at: dto.failed_at.map(|opt| opt.map(|dt| dt.naive_utc())),
+            worker_id: dto.worker_id,
         }
     }
 
@@ -1,13 +1,60 @@
 use async_trait::async_trait;
+use chrono::Utc;
 use stepflow_storage::db::DynPM;
 use stepflow_storage::error::StorageError;
 use stepflow_storage::entities::workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate};
+use stepflow_storage::entities::workflow_template_revision::{
+    StoredTemplateAlias, StoredTemplateRevision, REVISION_DRAFT, REVISION_PUBLISHED,
+};
 use stepflow_dto::dto::template::*;
+use stepflow_dsl::WorkflowDSL;
+use stepflow_engine::engine::template::{self, RevisionSelector, LATEST};
 use stepflow_core::{
     error::{AppError, AppResult},
 };
 use anyhow::{Context, Error};
 
+fn template_dto(row: StoredWorkflowTemplate) -> TemplateDto {
+    TemplateDto {
+        id: row.template_id,
+        name: row.name,
+        dsl: serde_json::from_str(&row.dsl_definition).unwrap_or_default(),
+        latest_revision: (row.version > 0).then_some(row.version),
+        created_at: row.created_at.and_utc(),
+        updated_at: row.updated_at.and_utc(),
+    }
+}
+
+/// `latest` 与指向该修订的别名一并返回
+fn revision_dto(rev: StoredTemplateRevision, aliases: &[StoredTemplateAlias], latest: Option<i64>) -> TemplateRevisionDto {
+    let mut names: Vec<String> = aliases
+        .iter()
+        .filter(|a| a.revision == rev.revision)
+        .map(|a| a.alias.clone())
+        .collect();
+    if latest == Some(rev.revision) {
+        names.insert(0, LATEST.to_string());
+    }
+    TemplateRevisionDto {
+        template_id: rev.template_id,
+        revision: rev.revision,
+        status: rev.status,
+        dsl: serde_json::from_str(&rev.dsl_definition).unwrap_or_default(),
+        aliases: names,
+        created_at: rev.created_at.and_utc(),
+        published_at: rev.published_at.map(|t| t.and_utc()),
+    }
+}
+
+fn alias_dto(alias: StoredTemplateAlias) -> TemplateAliasDto {
+    TemplateAliasDto {
+        template_id: alias.template_id,
+        alias: alias.alias,
+        revision: alias.revision,
+        updated_at: alias.updated_at.and_utc(),
+    }
+}
+
 #[derive(Clone)]
 pub struct TemplateSqlxSvc {
     pm: DynPM,
@@ -15,90 +62,116 @@ pub struct TemplateSqlxSvc {
 impl TemplateSqlxSvc {
     pub fn new(pm: DynPM) -> Self { Self { pm } }
 
-    async fn insert_or_update(
-        &self,
-        id: &str,
-        body: TemplateUpsert,
-        is_create: bool,
-    ) -> AppResult<TemplateDto> {
-        if is_create {
-            let row = StoredWorkflowTemplate {
-                template_id: id.to_string(),
-                name: body.name.clone(),
-                description: None,
-                dsl_definition: serde_json::to_string(&body.dsl).context("序列化 DSL 失败")?,
-                version: 1,
-                created_at: chrono::Utc::now().naive_utc(),
-                updated_at: chrono::Utc::now().naive_utc(),
-            };
-            self.pm.create_template(&row).await
-                .map_err(|e: StorageError| Error::new(e))?;
-            Ok(TemplateDto {
-                id: row.template_id,
-                name: row.name,
-                dsl: body.dsl,
-                created_at: row.created_at.and_utc(),
-            })
-        } else {
-            // 若不存在可决定返回 404 或新增，这里选择 404
-            if self.pm.get_template(id).await
-                .map_err(|e: StorageError| Error::new(e))?.is_none() {
-                return Err(AppError::NotFound);
-            }
-            let changes = UpdateStoredWorkflowTemplate {
-                name: Some(body.name),
-                description: None,
-                dsl_definition: Some(serde_json::to_string(&body.dsl).context("序列化 DSL 失败")?),
-                version: Some(1),
-            };
-            self.pm.update_template(id, &changes).await
+    /// 解析并校验 DSL，所有问题（悬空转移、不可达状态、非法 JSONPath 等）在创建 / 更新模板时一并返回 400
+    fn validate_dsl(dsl: &serde_json::Value) -> AppResult<()> {
+        let parsed: WorkflowDSL = serde_json::from_value(dsl.clone())
+            .map_err(|e| AppError::BadRequest(format!("invalid DSL: {e}")))?;
+        parsed
+            .validate()
+            .map_err(|errs| AppError::Validation(errs.iter().map(|e| e.to_string()).collect()))
+    }
+
+    /// 别名不能是修订号或保留的 `latest`
+    fn validate_alias(alias: &str) -> AppResult<()> {
+        if alias.is_empty() || alias == LATEST || alias.parse::<i64>().is_ok() {
+            return Err(AppError::BadRequest(format!(
+                "alias '{alias}' is reserved; use a non-numeric name other than '{LATEST}'"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn template_row(&self, id: &str) -> AppResult<StoredWorkflowTemplate> {
+        self.pm.get_template(id).await
+            .map_err(|e: StorageError| Error::new(e))?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn revision_dto(&self, template: &StoredWorkflowTemplate, rev: StoredTemplateRevision) -> AppResult<TemplateRevisionDto> {
+        let aliases = self.pm.find_template_aliases(&template.template_id).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        let latest = (template.version > 0).then_some(template.version);
+        Ok(revision_dto(rev, &aliases, latest))
+    }
+
+    /// 按修订号 / 别名 / `latest` 查找修订（草稿也可按修订号查看）
+    async fn find_revision(&self, id: &str, selector: &str) -> AppResult<StoredTemplateRevision> {
+        template::find_revision(&self.pm, id, &RevisionSelector::parse(selector)).await
+            .map_err(AppError::Internal)?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// 追加一个不可变修订；非草稿立即发布，模板行随之指向该修订
+    async fn append_revision(&self, id: &str, revision: i64, body: &TemplateUpsert) -> AppResult<()> {
+        let row = StoredTemplateRevision {
+            template_id: id.to_string(),
+            revision,
+            dsl_definition: serde_json::to_string(&body.dsl).context("序列化 DSL 失败")?,
+            status: REVISION_DRAFT.to_string(),
+            created_at: Utc::now().naive_utc(),
+            published_at: None,
+        };
+        self.pm.create_template_revision(&row).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        if !body.draft {
+            self.pm.publish_template_revision(id, revision, Utc::now().naive_utc()).await
                 .map_err(|e: StorageError| Error::new(e))?;
-            
-            // 重新获取更新后的数据
-            let row = self.pm.get_template(id).await
-                .map_err(|e: StorageError| Error::new(e))?.unwrap();
-            Ok(TemplateDto {
-                id: row.template_id,
-                name: row.name,
-                dsl: serde_json::from_str(&row.dsl_definition).unwrap_or_default(),
-                created_at: row.created_at.and_utc(),
-            })
         }
+        Ok(())
     }
 }
 
 #[async_trait]
 impl crate::service::TemplateService for TemplateSqlxSvc {
     async fn create(&self, body: TemplateUpsert) -> AppResult<TemplateDto> {
+        Self::validate_dsl(&body.dsl)?;
         let id = uuid::Uuid::new_v4().to_string();
-        self.insert_or_update(&id, body, true).await
+        let now = Utc::now().naive_utc();
+        // version 为最新发布的修订号，0 表示尚无发布的修订
+        let row = StoredWorkflowTemplate {
+            template_id: id.clone(),
+            name: body.name.clone(),
+            description: None,
+            dsl_definition: serde_json::to_string(&body.dsl).context("序列化 DSL 失败")?,
+            version: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        self.pm.create_template(&row).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        self.append_revision(&id, 1, &body).await?;
+        self.get(&id).await
     }
 
+    /// 不修改已有修订：DSL 作为新的修订追加
     async fn update(&self, id:&str, body:TemplateUpsert) -> AppResult<TemplateDto> {
-        self.insert_or_update(id, body, false).await
+        Self::validate_dsl(&body.dsl)?;
+        self.template_row(id).await?;
+        let next = self.pm.find_template_revisions(id).await
+            .map_err(|e: StorageError| Error::new(e))?
+            .iter()
+            .map(|r| r.revision)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let changes = UpdateStoredWorkflowTemplate {
+            name: Some(body.name.clone()),
+            ..Default::default()
+        };
+        self.pm.update_template(id, &changes).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        self.append_revision(id, next, &body).await?;
+        self.get(id).await
     }
 
     async fn get(&self, id:&str) -> AppResult<TemplateDto> {
-        let row = self.pm.get_template(id).await
-            .map_err(|e: StorageError| Error::new(e))?
-            .ok_or(AppError::NotFound)?;
-        Ok(TemplateDto {
-            id: row.template_id,
-            name: row.name,
-            dsl: serde_json::from_str(&row.dsl_definition).unwrap_or_default(),
-            created_at: row.created_at.and_utc(),
-        })
+        Ok(template_dto(self.template_row(id).await?))
     }
 
     async fn list(&self) -> AppResult<Vec<TemplateDto>> {
         let rows = self.pm.find_templates(100, 0).await
             .map_err(|e: StorageError| Error::new(e))?;
-        Ok(rows.into_iter().map(|r| TemplateDto {
-            id: r.template_id,
-            name: r.name,
-            dsl: serde_json::from_str(&r.dsl_definition).unwrap_or_default(),
-            created_at: r.created_at.and_utc(),
-        }).collect())
+        Ok(rows.into_iter().map(template_dto).collect())
     }
 
     async fn delete(&self, id:&str) -> AppResult<()> {
@@ -106,4 +179,86 @@ impl crate::service::TemplateService for TemplateSqlxSvc {
             .map_err(|e: StorageError| Error::new(e))?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn revisions(&self, id: &str) -> AppResult<Vec<TemplateRevisionDto>> {
+        let template = self.template_row(id).await?;
+        let aliases = self.pm.find_template_aliases(id).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        let latest = (template.version > 0).then_some(template.version);
+        let rows = self.pm.find_template_revisions(id).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        Ok(rows.into_iter().map(|r| revision_dto(r, &aliases, latest)).collect())
+    }
+
+    async fn revision(&self, id: &str, selector: &str) -> AppResult<TemplateRevisionDto> {
+        let template = self.template_row(id).await?;
+        let rev = self.find_revision(id, selector).await?;
+        self.revision_dto(&template, rev).await
+    }
+
+    async fn publish(&self, id: &str, revision: i64) -> AppResult<TemplateRevisionDto> {
+        self.template_row(id).await?;
+        self.pm.get_template_revision(id, revision).await
+            .map_err(|e: StorageError| Error::new(e))?
+            .ok_or(AppError::NotFound)?;
+        self.pm.publish_template_revision(id, revision, Utc::now().naive_utc()).await
+            .map_err(|e: StorageError| Error::new(e))?;
+
+        let template = self.template_row(id).await?;
+        let rev = self.find_revision(id, &revision.to_string()).await?;
+        self.revision_dto(&template, rev).await
+    }
+
+    async fn diff(&self, id: &str, from: &str, to: &str) -> AppResult<TemplateDiffDto> {
+        self.template_row(id).await?;
+        let before = self.find_revision(id, from).await?;
+        let after = self.find_revision(id, to).await?;
+        let parse = |rev: &StoredTemplateRevision| -> serde_json::Value {
+            serde_json::from_str(&rev.dsl_definition).unwrap_or_default()
+        };
+        Ok(TemplateDiffDto {
+            template_id: id.to_string(),
+            from_revision: before.revision,
+            to_revision: after.revision,
+            changes: template::diff_dsl(&parse(&before), &parse(&after)),
+        })
+    }
+
+    async fn aliases(&self, id: &str) -> AppResult<Vec<TemplateAliasDto>> {
+        self.template_row(id).await?;
+        let rows = self.pm.find_template_aliases(id).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        Ok(rows.into_iter().map(alias_dto).collect())
+    }
+
+    async fn set_alias(&self, id: &str, alias: &str, revision: i64) -> AppResult<TemplateAliasDto> {
+        Self::validate_alias(alias)?;
+        self.template_row(id).await?;
+        let rev = self.pm.get_template_revision(id, revision).await
+            .map_err(|e: StorageError| Error::new(e))?
+            .ok_or_else(|| AppError::BadRequest(format!("template {id} has no revision {revision}")))?;
+        if rev.status != REVISION_PUBLISHED {
+            return Err(AppError::BadRequest(format!(
+                "revision {revision} is a draft; publish it before pointing '{alias}' at it"
+            )));
+        }
+        let row = StoredTemplateAlias {
+            template_id: id.to_string(),
+            alias: alias.to_string(),
+            revision,
+            updated_at: Utc::now().naive_utc(),
+        };
+        self.pm.set_template_alias(&row).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        Ok(alias_dto(row))
+    }
+
+    async fn delete_alias(&self, id: &str, alias: &str) -> AppResult<()> {
+        self.pm.get_template_alias(id, alias).await
+            .map_err(|e: StorageError| Error::new(e))?
+            .ok_or(AppError::NotFound)?;
+        self.pm.delete_template_alias(id, alias).await
+            .map_err(|e: StorageError| Error::new(e))?;
+        Ok(())
+    }
+}
@@ -4,50 +4,24 @@ use serde_json::json;
 use std::sync::Arc;
 use stepflow_dto::dto::engine_event::EngineEvent;
 use stepflow_storage::entities::{
-    workflow_event::StoredWorkflowEvent,
     workflow_execution::{StoredWorkflowExecution, UpdateStoredWorkflowExecution},
     workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState},
 };
-use stepflow_storage::traits::{EventStorage, StateStorage, WorkflowStorage};
+use stepflow_storage::traits::{StateStorage, WorkflowStorage};
 use tracing::error;
 
+/// 把引擎事件投影到 execution / state 表。
+///
+/// 事件历史（`workflow_events`）由引擎按顺序同步写入（见 `stepflow_engine::engine::history`），
+/// 这里不再重复记录。
 pub struct PersistHook {
     workflow: Arc<dyn WorkflowStorage>,
     state: Arc<dyn StateStorage>,
-    event: Arc<dyn EventStorage>,
 }
 
 impl PersistHook {
-    pub fn new(
-        workflow: Arc<dyn WorkflowStorage>,
-        state: Arc<dyn StateStorage>,
-        event: Arc<dyn EventStorage>,
-    ) -> Arc<Self> {
-        Arc::new(Self {
-            workflow,
-            state,
-            event,
-        })
-    }
-
-    async fn record_event(&self, run_id: &str, event_type: &str, message: &str) {
-        let evt = StoredWorkflowEvent {
-            id: 0,
-            run_id: run_id.to_string(),
-            shard_id: 1,
-            event_id: 1,
-            event_type: event_type.to_string(),
-            state_id: None,
-            state_type: None,
-            trace_id: None,
-            parent_event_id: None,
-            context_version: None,
-            attributes: Some(message.to_string()),
-            attr_version: 1,
-            timestamp: Utc::now().naive_utc(),
-            archived: false,
-        };
-        let _ = self.event.create_event(&evt).await;
+    pub fn new(workflow: Arc<dyn WorkflowStorage>, state: Arc<dyn StateStorage>) -> Arc<Self> {
+        Arc::new(Self { workflow, state })
     }
 }
 
@@ -55,17 +29,19 @@ impl PersistHook {
 impl EngineEventHandler for PersistHook {
     async fn handle_event(&self, event: EngineEvent) {
         match event {
-            EngineEvent::WorkflowStarted { run_id } => {
+            EngineEvent::WorkflowStarted { run_id, input } => {
                 let exec = StoredWorkflowExecution {
                     run_id: run_id.clone(),
                     workflow_id: Some(format!("wf_{}", run_id)),
                     shard_id: 1,
                     template_id: None,
+                    template_revision: None,
+                    parent_run_id: None,
                     mode: "default".to_string(),
                     current_state_name: None,
                     workflow_type: "default".to_string(),
                     status: "running".to_string(),
-                    input: None,
+                    input: Some(input),
                     input_version: 1,
                     result: None,
                     result_version: 1,
@@ -98,17 +74,13 @@ impl EngineEventHandler for PersistHook {
                     completed_at: None,
                     error: None,
                     error_details: None,
+                    attempts: 0,
+                    mapping_trace: None,
                     created_at: Utc::now().naive_utc(),
                     updated_at: Utc::now().naive_utc(),
                     version: 1,
                 };
                 let _ = self.state.create_state(&state).await;
-                self.record_event(
-                    &run_id,
-                    "NodeEnter",
-                    &format!("Entered state: {}", state_name),
-                )
-                .await;
             }
 
             EngineEvent::NodeSuccess {
@@ -127,6 +99,8 @@ impl EngineEventHandler for PersistHook {
                     error: None,
                     error_details: None,
                     started_at: None,
+                    attempts: None,
+                    mapping_trace: None,
                     version: None,
                 };
                 let _ = self.state.update_state(&state_id, &update).await;
@@ -148,6 +122,8 @@ impl EngineEventHandler for PersistHook {
                     error: Some(Some(error)),
                     error_details: None,
                     started_at: None,
+                    attempts: None,
+                    mapping_trace: None,
                     version: None,
                 };
                 let _ = self.state.update_state(&state_id, &update).await;
@@ -157,27 +133,14 @@ impl EngineEventHandler for PersistHook {
                 run_id,
                 state_name,
                 status,
-                duration_ms,
+                ..
             } => {
-                // 1) 先照旧写事件
-                self.record_event(
-                    &run_id,
-                    "NodeExit",
-                    &json!({
-                        "state": state_name,
-                        "status": status,            // "success" / "failed"
-                        "duration_ms": duration_ms,  // Option<u64>
-                    })
-                    .to_string(),
-                )
-                .await;
-            
-                // 2) 再把 state 表的 status 和 completed_at 更新一下
+                // 把 state 表的 status 和 completed_at 更新一下
                 let state_id = format!("{run_id}:{state_name}");
                 let update = UpdateStoredWorkflowState {
                     status: Some(match status.as_str() {
                         "success" => "COMPLETED".to_string(),
-                        "failed"  => "FAILED".to_string(),
+                        "failed" | "caught" => "FAILED".to_string(),
                         other     => other.to_uppercase(),
                     }),
                     completed_at: Some(Some(Utc::now().naive_utc())),
@@ -225,17 +188,15 @@ mod tests {
     use serde_json::json;
     use std::sync::{Arc, Mutex};
     use stepflow_storage::entities::{
-        workflow_event::StoredWorkflowEvent,
         workflow_execution::{StoredWorkflowExecution, UpdateStoredWorkflowExecution},
         workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState},
     };
     use stepflow_storage::error::StorageError;
-    use stepflow_storage::traits::{EventStorage, StateStorage, WorkflowStorage};
+    use stepflow_storage::traits::{StateStorage, WorkflowStorage};
 
     #[derive(Default)]
     struct MockPersistence {
         pub created_states: Arc<Mutex<Vec<StoredWorkflowState>>>,
-        pub created_events: Arc<Mutex<Vec<StoredWorkflowEvent>>>,
         pub created_executions: Arc<Mutex<Vec<StoredWorkflowExecution>>>,
         pub updated_states: Arc<Mutex<Vec<(String, UpdateStoredWorkflowState)>>>,
         pub updated_executions: Arc<Mutex<Vec<(String, UpdateStoredWorkflowExecution)>>>,
@@ -274,40 +235,6 @@ mod tests {
         }
     }
     #[async_trait]
-    impl EventStorage for MockPersistence {
-        async fn create_event(&self, event: &StoredWorkflowEvent) -> Result<i64, StorageError> {
-            self.created_events.lock().unwrap().push(event.clone());
-            Ok(1)
-        }
-        async fn get_event(&self, _: i64) -> Result<Option<StoredWorkflowEvent>, StorageError> {
-            Ok(None)
-        }
-        async fn find_events_by_run_id(
-            &self,
-            _: &str,
-            _: i64,
-            _: i64,
-        ) -> Result<Vec<StoredWorkflowEvent>, StorageError> {
-            Ok(vec![])
-        }
-        async fn update_event(
-            &self,
-            _: i64,
-            _: &stepflow_storage::entities::workflow_event::UpdateStoredWorkflowEvent,
-        ) -> Result<(), StorageError> {
-            Ok(())
-        }
-        async fn archive_event(&self, _: i64) -> Result<(), StorageError> {
-            Ok(())
-        }
-        async fn delete_event(&self, _: i64) -> Result<(), StorageError> {
-            Ok(())
-        }
-        async fn delete_events_by_run_id(&self, _: &str) -> Result<u64, StorageError> {
-            Ok(0)
-        }
-    }
-    #[async_trait]
     impl WorkflowStorage for MockPersistence {
         async fn create_execution(
             &self,
@@ -337,6 +264,14 @@ mod tests {
         ) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
             Ok(vec![])
         }
+        async fn find_executions_by_parent(
+            &self,
+            _: &str,
+            _: i64,
+            _: i64,
+        ) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+            Ok(vec![])
+        }
         async fn update_execution(
             &self,
             run_id: &str,
@@ -354,9 +289,9 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_node_enter_creates_state_and_event() {
+    async fn test_node_enter_creates_state() {
         let mock = Arc::new(MockPersistence::default());
-        let hook = PersistHook::new(mock.clone(), mock.clone(), mock.clone());
+        let hook = PersistHook::new(mock.clone(), mock.clone());
 
         let run_id = "test_run";
         let state_name = "MyState";
@@ -375,12 +310,5 @@ mod tests {
         assert_eq!(states[0].run_id, run_id);
         assert_eq!(states[0].state_name, state_name);
         assert_eq!(states[0].input, Some(input));
-
-        // 验证事件被写入
-        let events = mock.created_events.lock().unwrap();
-        assert_eq!(events.len(), 1);
-        assert_eq!(events[0].run_id, run_id);
-        assert_eq!(events[0].event_type, "NodeEnter");
-        assert!(events[0].attributes.as_ref().unwrap().contains(state_name));
     }
 }
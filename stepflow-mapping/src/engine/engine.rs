@@ -1,12 +1,12 @@
 use serde_json::{Map, Value};
 
 use crate::{
-    error::Result,
+    error::{MappingError, Result},
     engine::context::MappingContext,
     graph::builder::sort_rules,
     model::{
-        dsl::{MappingDSL, PreserveFields},
-        result::MappingStepSnapshot,
+        dsl::{MappingDSL, OnError, PreserveFields},
+        result::{MappingResult, MappingStepSnapshot},
         rule::MappingType,
     },
     resolver,
@@ -17,8 +17,8 @@ pub struct MappingEngine;
 
 impl MappingEngine {
     /// 执行映射：支持 Constant / JsonPath / Expr / Template / SubMapping
-    /// 并按 `dependsOn` 拓扑排序
-    pub fn apply(dsl: MappingDSL, input: &Value) -> Result<Value> {
+    /// 并按 `dependsOn` 拓扑排序；规则失败按 `onError` / `required` 处理
+    pub fn apply(dsl: MappingDSL, input: &Value) -> Result<MappingResult> {
         // 1️⃣ preserve
         let init = match dsl.preserve {
             PreserveFields::All => input.as_object().cloned().unwrap_or_default(),
@@ -55,7 +55,13 @@ impl MappingEngine {
                 input.clone()
             };
 
-            match resolver::resolve(&rule, &resolver_input) {
+            let resolved = match resolver::resolve(&rule, &resolver_input) {
+                Ok(Value::Null) if rule.required => Err("required value resolved to null".to_string()),
+                Ok(val) => Ok(val),
+                Err(err) => Err(err.to_string()),
+            };
+
+            match resolved {
                 Ok(val) => {
                     merge_value(&mut ctx.output, &rule.key, val.clone(), rule.merge_strategy);
                     ctx.steps.push(MappingStepSnapshot {
@@ -65,19 +71,32 @@ impl MappingEngine {
                         output: Some(val),
                     });
                 }
-                Err(err) => {
+                // 必填规则或 onError: fail —— 整体失败
+                Err(reason) if rule.required || dsl.on_error == OnError::Fail => {
+                    return Err(MappingError::RuleFailed { key: rule.key, reason });
+                }
+                Err(reason) => {
+                    // onError: default 写入回退值，skip 则不写入
+                    let fallback = (dsl.on_error == OnError::Default)
+                        .then(|| rule.default.clone().unwrap_or(Value::Null));
+                    if let Some(val) = &fallback {
+                        merge_value(&mut ctx.output, &rule.key, val.clone(), rule.merge_strategy);
+                    }
                     ctx.steps.push(MappingStepSnapshot {
                         key: rule.key,
                         success: false,
-                        error: Some(err.to_string()),
-                        output: None,
+                        error: Some(reason),
+                        output: fallback,
                     });
                 }
             }
         }
 
-        // 3️⃣ 返回合并后的 JSON
-        Ok(Value::Object(ctx.output))
+        // 3️⃣ 返回合并后的 JSON 与逐条规则快照
+        Ok(MappingResult {
+            output: Value::Object(ctx.output),
+            steps: ctx.steps,
+        })
     }
 }
 
@@ -95,6 +114,7 @@ mod tests {
             description: None,
             preserve: PreserveFields::None,
             debug: false,
+            on_error: Default::default(),
             mappings: vec![
                 MappingRule {
                     key: "foo".to_string(),
@@ -111,11 +131,13 @@ mod tests {
                     lang: None,
                     expected_type: None,
                     schema: None,
+                    required: false,
+                    default: None,
                 }
             ],
         };
         let input = json!({"bar": 1});
-        let result = MappingEngine::apply(dsl, &input).unwrap();
+        let result = MappingEngine::apply(dsl, &input).unwrap().output;
         assert_eq!(result["foo"], 42);
         assert!(result.get("bar").is_none());
     }
@@ -128,7 +150,7 @@ mod tests {
             ..Default::default()
         };
         let input = json!({"a": 1, "b": 2});
-        let result = MappingEngine::apply(dsl, &input).unwrap();
+        let result = MappingEngine::apply(dsl, &input).unwrap().output;
         assert_eq!(result["a"], 1);
         assert_eq!(result["b"], 2);
     }
@@ -153,6 +175,8 @@ mod tests {
                     lang: None,
                     expected_type: None,
                     schema: None,
+                    required: false,
+                    default: None,
                 },
                 MappingRule {
                     key: "arr".to_string(),
@@ -169,12 +193,57 @@ mod tests {
                     lang: None,
                     expected_type: None,
                     schema: None,
+                    required: false,
+                    default: None,
                 },
             ],
             ..Default::default()
         };
         let input = json!({});
-        let result = MappingEngine::apply(dsl, &input).unwrap();
+        let result = MappingEngine::apply(dsl, &input).unwrap().output;
         assert_eq!(result["arr"], json!([1, 2]));
     }
-}
\ No newline at end of file
+
+    fn strict_dsl(on_error: &str, required: bool) -> MappingDSL {
+        serde_json::from_value(json!({
+            "onError": on_error,
+            "mappings": [
+                { "key": "ok", "type": "jsonPath", "source": "$.a" },
+                { "key": "bad", "type": "expr", "transform": "1 +", "required": required, "default": "n/a" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_on_error_modes() {
+        let input = json!({ "a": 1 });
+
+        // skip：失败字段缺失，但快照中可见
+        let result = MappingEngine::apply(strict_dsl("skip", false), &input).unwrap();
+        assert_eq!(result.output, json!({ "ok": 1 }));
+        assert_eq!(result.steps.len(), 2);
+        assert!(!result.steps[1].success && result.steps[1].error.is_some());
+
+        // default：写入规则 default
+        let result = MappingEngine::apply(strict_dsl("default", false), &input).unwrap();
+        assert_eq!(result.output, json!({ "ok": 1, "bad": "n/a" }));
+
+        // fail：整体失败并指明规则
+        let err = MappingEngine::apply(strict_dsl("fail", false), &input).unwrap_err();
+        assert!(matches!(err, MappingError::RuleFailed { ref key, .. } if key == "bad"), "{err}");
+    }
+
+    #[test]
+    fn test_required_rule_fails_on_error_and_null() {
+        let err = MappingEngine::apply(strict_dsl("default", true), &json!({ "a": 1 })).unwrap_err();
+        assert!(matches!(err, MappingError::RuleFailed { ref key, .. } if key == "bad"), "{err}");
+
+        let dsl: MappingDSL = serde_json::from_value(json!({
+            "mappings": [{ "key": "id", "type": "jsonPath", "source": "$.missing", "required": true }]
+        }))
+        .unwrap();
+        let err = MappingEngine::apply(dsl, &json!({})).unwrap_err();
+        assert!(err.to_string().contains("resolved to null"), "{err}");
+    }
+}
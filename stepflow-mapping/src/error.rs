@@ -22,6 +22,9 @@ pub enum MappingError {
     #[error("template render error: {0}")]
     Template(String),
 
+    #[error("intrinsic function error: {0}")]
+    Intrinsic(String),
+
     // ───────────────────── 规则/配置层面 ─────────────────────────
     #[error("missing required field: {0}")]
     MissingField(&'static str),
@@ -29,6 +32,9 @@ pub enum MappingError {
     #[error("unsupported mapping type: {0}")]
     UnsupportedType(String),
 
+    #[error("mapping rule '{key}' failed: {reason}")]
+    RuleFailed { key: String, reason: String },
+
     #[error("condition not satisfied")]
     Skipped,
 
@@ -38,6 +44,9 @@ pub enum MappingError {
 
     #[error("circular dependency detected among mapping rules")]
     CircularDependency,
+
+    #[error("depends on unknown rule: {0}")]
+    UnknownDependency(String),
 }
 
 /// 项目统一 Result 别名
@@ -87,6 +87,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
             MappingRule {
                 key: "b".to_string(),
@@ -103,6 +105,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
         ];
         let sorted = sort_rules(&rules).unwrap();
@@ -127,6 +131,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
             MappingRule {
                 key: "b".to_string(),
@@ -143,6 +149,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
         ];
         let sorted = sort_rules(&rules).unwrap();
@@ -168,6 +176,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
             MappingRule {
                 key: "b".to_string(),
@@ -184,6 +194,8 @@ mod tests {
                 lang: None,
                 expected_type: None,
                 schema: None,
+                required: false,
+                default: None,
             },
         ];
         let sorted = sort_rules(&rules);
@@ -0,0 +1,351 @@
+//! 内置函数注册表：默认提供 `States.*`，可按名称注册自定义函数
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::{Map, Number, Value};
+use sha2::Digest;
+
+use crate::error::{MappingError, Result};
+
+/// 函数实现：参数已求值
+pub type IntrinsicFn = Arc<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+/// 注册的函数及其参数个数范围（`max_args` 为 None 表示不限）
+#[derive(Clone)]
+pub struct Intrinsic {
+    pub min_args: usize,
+    pub max_args: Option<usize>,
+    pub func: IntrinsicFn,
+}
+
+#[derive(Clone, Default)]
+pub struct IntrinsicRegistry {
+    functions: HashMap<String, Intrinsic>,
+}
+
+impl IntrinsicRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册（同名覆盖）
+    pub fn register<F>(&mut self, name: &str, min_args: usize, max_args: Option<usize>, func: F)
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.functions.insert(
+            name.to_string(),
+            Intrinsic {
+                min_args,
+                max_args,
+                func: Arc::new(func),
+            },
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Intrinsic> {
+        self.functions.get(name)
+    }
+
+    /// 校验函数存在且参数个数合法（不求值）
+    pub fn check_call(&self, name: &str, argc: usize) -> Result<()> {
+        let f = self
+            .get(name)
+            .ok_or_else(|| MappingError::Intrinsic(format!("unknown intrinsic function '{name}'")))?;
+        let too_many = f.max_args.is_some_and(|max| argc > max);
+        if argc < f.min_args || too_many {
+            let expected = match f.max_args {
+                Some(max) if max == f.min_args => format!("{max}"),
+                Some(max) => format!("{}..={max}", f.min_args),
+                None => format!("at least {}", f.min_args),
+            };
+            return Err(MappingError::Intrinsic(format!(
+                "{name} expects {expected} argument(s), got {argc}"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn call(&self, name: &str, args: &[Value]) -> Result<Value> {
+        self.check_call(name, args.len())?;
+        (self.functions[name].func)(args)
+    }
+
+    /// 内置的 `States.*` 函数
+    pub fn with_builtins() -> Self {
+        let mut r = Self::new();
+        r.register("States.Format", 1, None, format);
+        r.register("States.StringToJson", 1, Some(1), |a| {
+            serde_json::from_str(str_arg("States.StringToJson", a, 0)?).map_err(Into::into)
+        });
+        r.register("States.JsonToString", 1, Some(1), |a| Ok(Value::String(a[0].to_string())));
+        r.register("States.JsonMerge", 2, Some(3), json_merge);
+        r.register("States.Array", 0, None, |a| Ok(Value::Array(a.to_vec())));
+        r.register("States.ArrayPartition", 2, Some(2), |a| {
+            let items = array_arg("States.ArrayPartition", a, 0)?;
+            let size = int_arg("States.ArrayPartition", a, 1)?;
+            if size < 1 {
+                return Err(arg_error("States.ArrayPartition", "chunk size must be at least 1"));
+            }
+            Ok(Value::Array(
+                items.chunks(size as usize).map(|c| Value::Array(c.to_vec())).collect(),
+            ))
+        });
+        r.register("States.ArrayContains", 2, Some(2), |a| {
+            Ok(Value::Bool(array_arg("States.ArrayContains", a, 0)?.contains(&a[1])))
+        });
+        r.register("States.ArrayRange", 3, Some(3), array_range);
+        r.register("States.ArrayGetItem", 2, Some(2), |a| {
+            let items = array_arg("States.ArrayGetItem", a, 0)?;
+            let index = int_arg("States.ArrayGetItem", a, 1)?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|i| items.get(i))
+                .cloned()
+                .ok_or_else(|| arg_error("States.ArrayGetItem", &format!("index {index} out of bounds")))
+        });
+        r.register("States.ArrayLength", 1, Some(1), |a| {
+            Ok(Value::from(array_arg("States.ArrayLength", a, 0)?.len()))
+        });
+        r.register("States.ArrayUnique", 1, Some(1), |a| {
+            let mut seen = HashSet::new();
+            let items = array_arg("States.ArrayUnique", a, 0)?;
+            Ok(Value::Array(
+                items.iter().filter(|v| seen.insert(v.to_string())).cloned().collect(),
+            ))
+        });
+        r.register("States.Base64Encode", 1, Some(1), |a| {
+            Ok(Value::String(BASE64.encode(str_arg("States.Base64Encode", a, 0)?)))
+        });
+        r.register("States.Base64Decode", 1, Some(1), |a| {
+            let bytes = BASE64
+                .decode(str_arg("States.Base64Decode", a, 0)?)
+                .map_err(|e| arg_error("States.Base64Decode", &e.to_string()))?;
+            String::from_utf8(bytes)
+                .map(Value::String)
+                .map_err(|e| arg_error("States.Base64Decode", &e.to_string()))
+        });
+        r.register("States.Hash", 2, Some(2), hash);
+        r.register("States.MathRandom", 2, Some(3), math_random);
+        r.register("States.MathAdd", 2, Some(2), math_add);
+        r.register("States.StringSplit", 2, Some(2), |a| {
+            let s = str_arg("States.StringSplit", a, 0)?;
+            let delimiters: Vec<char> = str_arg("States.StringSplit", a, 1)?.chars().collect();
+            Ok(Value::Array(
+                s.split(delimiters.as_slice())
+                    .filter(|part| !part.is_empty())
+                    .map(|part| Value::String(part.to_string()))
+                    .collect(),
+            ))
+        });
+        r.register("States.UUID", 0, Some(0), |_| Ok(Value::String(uuid::Uuid::new_v4().to_string())));
+        r
+    }
+}
+
+fn arg_error(name: &str, msg: &str) -> MappingError {
+    MappingError::Intrinsic(format!("{name}: {msg}"))
+}
+
+fn str_arg<'a>(name: &str, args: &'a [Value], idx: usize) -> Result<&'a str> {
+    args[idx]
+        .as_str()
+        .ok_or_else(|| arg_error(name, &format!("argument {} must be a string", idx + 1)))
+}
+
+fn int_arg(name: &str, args: &[Value], idx: usize) -> Result<i64> {
+    args[idx]
+        .as_i64()
+        .ok_or_else(|| arg_error(name, &format!("argument {} must be an integer", idx + 1)))
+}
+
+fn array_arg<'a>(name: &str, args: &'a [Value], idx: usize) -> Result<&'a Vec<Value>> {
+    args[idx]
+        .as_array()
+        .ok_or_else(|| arg_error(name, &format!("argument {} must be an array", idx + 1)))
+}
+
+/// `{}` 依次替换为参数（字符串原样，其余为 JSON 文本）；`\{` `\}` 为字面量花括号
+fn format(args: &[Value]) -> Result<Value> {
+    let template = str_arg("States.Format", args, 0)?;
+    let mut values = args[1..].iter();
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some('{' | '}')) => out.push(chars.next().unwrap_or_default()),
+            '{' if chars.peek() == Some(&'}') => {
+                chars.next();
+                match values.next() {
+                    Some(Value::String(s)) => out.push_str(s),
+                    Some(v) => out.push_str(&v.to_string()),
+                    None => return Err(arg_error("States.Format", "more placeholders than arguments")),
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    if values.next().is_some() {
+        return Err(arg_error("States.Format", "more arguments than placeholders"));
+    }
+    Ok(Value::String(out))
+}
+
+/// 第三个参数为 true 时深合并（嵌套对象逐层合并），否则浅合并；同名键以第二个对象为准
+fn json_merge(args: &[Value]) -> Result<Value> {
+    fn merge(base: &mut Map<String, Value>, overlay: &Map<String, Value>, deep: bool) {
+        for (k, v) in overlay {
+            match (base.get_mut(k), v) {
+                (Some(Value::Object(b)), Value::Object(o)) if deep => merge(b, o, deep),
+                _ => {
+                    base.insert(k.clone(), v.clone());
+                }
+            }
+        }
+    }
+    let (Some(a), Some(b)) = (args[0].as_object(), args[1].as_object()) else {
+        return Err(arg_error("States.JsonMerge", "arguments 1 and 2 must be objects"));
+    };
+    let deep = match args.get(2) {
+        None => false,
+        Some(v) => v.as_bool().ok_or_else(|| arg_error("States.JsonMerge", "argument 3 must be a boolean"))?,
+    };
+    let mut merged = a.clone();
+    merge(&mut merged, b, deep);
+    Ok(Value::Object(merged))
+}
+
+/// 半开区间以 step 递增 / 递减；上限 1000 项
+fn array_range(args: &[Value]) -> Result<Value> {
+    const MAX_ITEMS: usize = 1000;
+    let start = int_arg("States.ArrayRange", args, 0)?;
+    let end = int_arg("States.ArrayRange", args, 1)?;
+    let step = int_arg("States.ArrayRange", args, 2)?;
+    if step == 0 {
+        return Err(arg_error("States.ArrayRange", "step must not be 0"));
+    }
+    let mut items = Vec::new();
+    let mut i = start;
+    while (step > 0 && i <= end) || (step < 0 && i >= end) {
+        if items.len() == MAX_ITEMS {
+            return Err(arg_error("States.ArrayRange", &format!("range exceeds {MAX_ITEMS} items")));
+        }
+        items.push(Value::from(i));
+        // 越过 i64 边界即已超出 end，区间到此结束
+        match i.checked_add(step) {
+            Some(next) => i = next,
+            None => break,
+        }
+    }
+    Ok(Value::Array(items))
+}
+
+fn hash(args: &[Value]) -> Result<Value> {
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+    let data = match &args[0] {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let digest = match str_arg("States.Hash", args, 1)? {
+        "MD5" => hex(&md5::Md5::digest(data.as_bytes())),
+        "SHA-1" => hex(&sha1::Sha1::digest(data.as_bytes())),
+        "SHA-256" => hex(&sha2::Sha256::digest(data.as_bytes())),
+        "SHA-384" => hex(&sha2::Sha384::digest(data.as_bytes())),
+        "SHA-512" => hex(&sha2::Sha512::digest(data.as_bytes())),
+        other => {
+            return Err(arg_error(
+                "States.Hash",
+                &format!("unsupported algorithm '{other}', expected MD5, SHA-1, SHA-256, SHA-384 or SHA-512"),
+            ));
+        }
+    };
+    Ok(Value::String(digest))
+}
+
+/// `[start, end]` 内的随机整数；给出 seed 时结果可复现
+fn math_random(args: &[Value]) -> Result<Value> {
+    let start = int_arg("States.MathRandom", args, 0)?;
+    let end = int_arg("States.MathRandom", args, 1)?;
+    if start > end {
+        return Err(arg_error("States.MathRandom", "start must not exceed end"));
+    }
+    let value = match args.get(2) {
+        Some(_) => StdRng::seed_from_u64(int_arg("States.MathRandom", args, 2)? as u64).gen_range(start..=end),
+        None => rand::thread_rng().gen_range(start..=end),
+    };
+    Ok(Value::from(value))
+}
+
+/// 两个整数相加；任一为小数时按浮点数相加
+fn math_add(args: &[Value]) -> Result<Value> {
+    match (args[0].as_i64(), args[1].as_i64()) {
+        (Some(a), Some(b)) => a
+            .checked_add(b)
+            .map(Value::from)
+            .ok_or_else(|| arg_error("States.MathAdd", "integer overflow")),
+        _ => {
+            let (Some(a), Some(b)) = (args[0].as_f64(), args[1].as_f64()) else {
+                return Err(arg_error("States.MathAdd", "arguments must be numbers"));
+            };
+            Number::from_f64(a + b)
+                .map(Value::Number)
+                .ok_or_else(|| arg_error("States.MathAdd", "result is not a finite number"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(name: &str, args: Value) -> Result<Value> {
+        IntrinsicRegistry::with_builtins().call(name, args.as_array().unwrap())
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        assert_eq!(call("States.Format", json!(["{} has {} \\{x\\}", "a", 2])).unwrap(), json!("a has 2 {x}"));
+        assert_eq!(call("States.StringToJson", json!(["{\"a\":1}"])).unwrap(), json!({ "a": 1 }));
+        assert_eq!(call("States.JsonToString", json!([{ "a": 1 }])).unwrap(), json!("{\"a\":1}"));
+        assert_eq!(
+            call("States.JsonMerge", json!([{ "a": { "x": 1 }, "b": 1 }, { "a": { "y": 2 } }, true])).unwrap(),
+            json!({ "a": { "x": 1, "y": 2 }, "b": 1 })
+        );
+        assert_eq!(call("States.ArrayPartition", json!([[1, 2, 3], 2])).unwrap(), json!([[1, 2], [3]]));
+        assert_eq!(call("States.ArrayRange", json!([1, 9, 3])).unwrap(), json!([1, 4, 7]));
+        assert_eq!(
+            call("States.ArrayRange", json!([9223372036854775800_i64, i64::MAX, 1])).unwrap().as_array().unwrap().len(),
+            8
+        );
+        assert_eq!(call("States.ArrayRange", json!([0, i64::MAX, i64::MAX])).unwrap(), json!([0, i64::MAX]));
+        assert_eq!(call("States.ArrayRange", json!([-1, i64::MIN, i64::MIN])).unwrap(), json!([-1]));
+        assert_eq!(call("States.ArrayUnique", json!([[1, 1, "1"]])).unwrap(), json!([1, "1"]));
+        assert_eq!(call("States.Base64Decode", json!([call("States.Base64Encode", json!(["hi"])).unwrap()])).unwrap(), json!("hi"));
+        assert_eq!(
+            call("States.Hash", json!(["abc", "SHA-256"])).unwrap(),
+            json!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(call("States.MathAdd", json!([1, -3])).unwrap(), json!(-2));
+        assert_eq!(call("States.StringSplit", json!(["a,b;;c", ",;"])).unwrap(), json!(["a", "b", "c"]));
+        assert_eq!(
+            call("States.MathRandom", json!([1, 100, 7])).unwrap(),
+            call("States.MathRandom", json!([1, 100, 7])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_argument_errors() {
+        let registry = IntrinsicRegistry::with_builtins();
+        assert!(registry.check_call("States.Nope", 0).is_err());
+        assert!(registry.check_call("States.UUID", 1).is_err());
+        assert!(call("States.Format", json!(["{} {}", "a"])).is_err());
+        assert!(call("States.ArrayGetItem", json!([[1], 5])).is_err());
+        assert!(call("States.Hash", json!(["abc", "CRC32"])).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Step Functions 风格的载荷模板与内置函数（`Parameters` / `ResultSelector`）
+//!
+//! * 键以 `.$` 结尾的字段：值为表达式（JSONPath 或 `States.*` 调用），求值后写入去掉 `.$` 的键
+//! * 其余字段按字面量保留，对象与数组递归处理
+//! * 内置函数来自全局注册表，可通过 [`register_intrinsic`] 追加自定义函数
+//! * JSONPath 未命中视为错误；含通配 / 过滤 / 切片的路径返回命中项数组
+
+pub mod functions;
+pub mod parser;
+
+use std::sync::RwLock;
+
+use jsonpath_lib::select;
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::error::{MappingError, Result};
+pub use functions::{Intrinsic, IntrinsicFn, IntrinsicRegistry};
+pub use parser::{parse_expression, Expr};
+
+/// 表达式字段的键后缀
+pub const EXPRESSION_SUFFIX: &str = ".$";
+
+static GLOBAL_INTRINSICS: Lazy<RwLock<IntrinsicRegistry>> =
+    Lazy::new(|| RwLock::new(IntrinsicRegistry::with_builtins()));
+
+/// 注册自定义内置函数（同名覆盖内置实现）
+pub fn register_intrinsic<F>(name: &str, min_args: usize, max_args: Option<usize>, func: F)
+where
+    F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+{
+    GLOBAL_INTRINSICS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(name, min_args, max_args, func);
+}
+
+/// 按载荷模板构造新值
+pub fn evaluate_payload(template: &Value, input: &Value) -> Result<Value> {
+    let registry = GLOBAL_INTRINSICS.read().unwrap_or_else(|e| e.into_inner());
+    evaluate_with(&registry, template, input)
+}
+
+fn evaluate_with(registry: &IntrinsicRegistry, template: &Value, input: &Value) -> Result<Value> {
+    match template {
+        Value::Object(fields) => {
+            let mut out = Map::new();
+            for (key, value) in fields {
+                match key.strip_suffix(EXPRESSION_SUFFIX) {
+                    Some(target) => {
+                        let src = value.as_str().ok_or_else(|| {
+                            MappingError::Intrinsic(format!("field '{key}' must be a string expression"))
+                        })?;
+                        let evaluated = eval_expr(registry, &parse_expression(src)?, input)
+                            .map_err(|e| MappingError::Intrinsic(format!("field '{key}': {e}")))?;
+                        out.insert(target.to_string(), evaluated);
+                    }
+                    None => {
+                        out.insert(key.clone(), evaluate_with(registry, value, input)?);
+                    }
+                }
+            }
+            Ok(Value::Object(out))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| evaluate_with(registry, item, input))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        other => Ok(other.clone()),
+    }
+}
+
+fn eval_expr(registry: &IntrinsicRegistry, expr: &Expr, input: &Value) -> Result<Value> {
+    match expr {
+        Expr::Path(path) => select_path(input, path),
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Call { name, args } => {
+            let args = args
+                .iter()
+                .map(|arg| eval_expr(registry, arg, input))
+                .collect::<Result<Vec<_>>>()?;
+            registry.call(name, &args)
+        }
+    }
+}
+
+/// 对 *input* 求值 JSONPath；`$` 为整个输入
+pub fn select_path(input: &Value, path: &str) -> Result<Value> {
+    if path == "$" {
+        return Ok(input.clone());
+    }
+    let hits = select(input, path).map_err(|e| MappingError::JsonPath(format!("'{path}': {e}")))?;
+    if !is_definite(path) {
+        return Ok(Value::Array(hits.into_iter().cloned().collect()));
+    }
+    hits.first()
+        .map(|v| (*v).clone())
+        .ok_or_else(|| MappingError::JsonPath(format!("'{path}' matched nothing in the input")))
+}
+
+/// 只指向单个节点的路径（无通配、递归下降、过滤、切片或多选）
+fn is_definite(path: &str) -> bool {
+    !path.contains(['*', '?', ':', ',']) && !path.contains("..")
+}
+
+/// 静态校验载荷模板：返回 `(字段路径, 原因)`，路径相对模板根、以 `.` 分隔
+pub fn validate_payload(template: &Value) -> Vec<(String, String)> {
+    let registry = GLOBAL_INTRINSICS.read().unwrap_or_else(|e| e.into_inner());
+    let mut problems = Vec::new();
+    validate_at(&registry, template, "", &mut problems);
+    problems
+}
+
+fn validate_at(registry: &IntrinsicRegistry, template: &Value, path: &str, problems: &mut Vec<(String, String)>) {
+    let join = |key: &str| if path.is_empty() { key.to_string() } else { format!("{path}.{key}") };
+    match template {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                if !key.ends_with(EXPRESSION_SUFFIX) {
+                    validate_at(registry, value, &join(key), problems);
+                    continue;
+                }
+                let checked = value
+                    .as_str()
+                    .ok_or_else(|| "must be a string expression".to_string())
+                    .and_then(|src| validate_expression_with(registry, src));
+                if let Err(reason) = checked {
+                    problems.push((join(key), reason));
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                validate_at(registry, item, &format!("{path}[{idx}]"), problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 校验单个表达式的语法、JSONPath 与函数参数个数
+pub fn validate_expression(src: &str) -> std::result::Result<(), String> {
+    let registry = GLOBAL_INTRINSICS.read().unwrap_or_else(|e| e.into_inner());
+    validate_expression_with(&registry, src)
+}
+
+fn validate_expression_with(registry: &IntrinsicRegistry, src: &str) -> std::result::Result<(), String> {
+    fn check(registry: &IntrinsicRegistry, expr: &Expr) -> Result<()> {
+        match expr {
+            Expr::Path(path) if path.starts_with("$$") => Err(MappingError::Intrinsic(format!(
+                "context object path '{path}' is not supported"
+            ))),
+            Expr::Path(path) => select(&Value::Null, path)
+                .map(|_| ())
+                .map_err(|e| MappingError::JsonPath(format!("'{path}': {e}"))),
+            Expr::Literal(_) => Ok(()),
+            Expr::Call { name, args } => {
+                registry.check_call(name, args.len())?;
+                args.iter().try_for_each(|arg| check(registry, arg))
+            }
+        }
+    }
+    parse_expression(src)
+        .and_then(|expr| check(registry, &expr))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_evaluate_payload() {
+        let input = json!({ "user": { "name": "ann", "tags": ["a", "b"] }, "n": 2 });
+        let template = json!({
+            "greeting.$": "States.Format('hello {}', $.user.name)",
+            "static": { "nested": [1, { "count.$": "States.ArrayLength($.user.tags)" }] },
+            "all.$": "$",
+            "tags.$": "$.user.tags[*]",
+            "next.$": "States.MathAdd($.n, 1)"
+        });
+        let out = evaluate_payload(&template, &input).unwrap();
+        assert_eq!(
+            out,
+            json!({
+                "greeting": "hello ann",
+                "static": { "nested": [1, { "count": 2 }] },
+                "all": input,
+                "tags": ["a", "b"],
+                "next": 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_path_and_custom_function() {
+        let err = evaluate_payload(&json!({ "x.$": "$.missing" }), &json!({})).unwrap_err();
+        assert!(err.to_string().contains("field 'x.$'"), "{err}");
+
+        register_intrinsic("Custom.Double", 1, Some(1), |args| {
+            Ok(json!(args[0].as_i64().unwrap_or_default() * 2))
+        });
+        let out = evaluate_payload(&json!({ "x.$": "Custom.Double($.n)" }), &json!({ "n": 21 })).unwrap();
+        assert_eq!(out, json!({ "x": 42 }));
+    }
+
+    #[test]
+    fn test_validate_payload_reports_field_paths() {
+        let problems = validate_payload(&json!({
+            "ok.$": "States.UUID()",
+            "a": { "bad.$": "States.Unknown($.x)" },
+            "list": [{ "arity.$": "States.MathAdd(1)" }],
+            "num.$": 5
+        }));
+        let paths: Vec<&str> = problems.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["a.bad.$", "list[0].arity.$", "num.$"]);
+    }
+}
@@ -0,0 +1,211 @@
+//! 表达式解析：`$.a.b`（JSONPath）或 `States.Xxx(arg, ...)`（内置函数，可嵌套）
+//!
+//! 参数字面量：`'字符串'`（`\'` `\\` `\{` `\}` 转义）、数字、`true` / `false` / `null`
+
+use serde_json::{Number, Value};
+
+use crate::error::{MappingError, Result};
+
+/// 解析后的表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// JSONPath，对当前输入求值
+    Path(String),
+    /// 字面量参数
+    Literal(Value),
+    /// 内置函数调用
+    Call { name: String, args: Vec<Expr> },
+}
+
+/// 解析 `.$` 字段的值
+pub fn parse_expression(src: &str) -> Result<Expr> {
+    let mut parser = Parser { src, pos: 0 };
+    parser.skip_ws();
+    let expr = match parser.peek() {
+        Some('$') => parser.path()?,
+        Some(c) if c.is_ascii_alphabetic() => parser.call()?,
+        _ => return Err(parser.error("expected a JSONPath or an intrinsic function call")),
+    };
+    parser.skip_ws();
+    if parser.pos < src.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(expr)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn error(&self, msg: &str) -> MappingError {
+        MappingError::Intrinsic(format!("{msg} at offset {} in '{}'", self.pos, self.src))
+    }
+
+    /// JSONPath 截止到同层的 `,` / `)`；方括号与引号内的字符原样保留
+    fn path(&mut self) -> Result<Expr> {
+        let start = self.pos;
+        let (mut depth, mut quote) = (0usize, None::<char>);
+        while let Some(c) = self.peek() {
+            match (quote, c) {
+                (Some(q), _) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '[' | '(') => depth += 1,
+                (None, ']' | ')') if depth > 0 => depth -= 1,
+                (None, ',' | ')') => break,
+                (None, c) if c.is_whitespace() && depth == 0 => break,
+                _ => {}
+            }
+            self.bump();
+        }
+        if quote.is_some() || depth > 0 {
+            return Err(self.error("unterminated JSONPath"));
+        }
+        Ok(Expr::Path(self.src[start..self.pos].to_string()))
+    }
+
+    fn call(&mut self) -> Result<Expr> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+            self.bump();
+        }
+        let name = self.src[start..self.pos].to_string();
+        self.skip_ws();
+        if self.bump() != Some('(') {
+            return Err(self.error(&format!("expected '(' after '{name}'")));
+        }
+
+        let mut args = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(')') {
+            self.bump();
+            return Ok(Expr::Call { name, args });
+        }
+        loop {
+            self.skip_ws();
+            args.push(self.argument()?);
+            self.skip_ws();
+            match self.bump() {
+                Some(',') => continue,
+                Some(')') => return Ok(Expr::Call { name, args }),
+                _ => return Err(self.error(&format!("expected ',' or ')' in arguments of '{name}'"))),
+            }
+        }
+    }
+
+    fn argument(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some('$') => self.path(),
+            Some('\'') => self.string(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                for (word, value) in [("true", Value::Bool(true)), ("false", Value::Bool(false)), ("null", Value::Null)] {
+                    let rest = &self.src[self.pos..];
+                    if rest.starts_with(word)
+                        && !rest[word.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '.' || c == '(')
+                    {
+                        self.pos += word.len();
+                        return Ok(Expr::Literal(value));
+                    }
+                }
+                self.call()
+            }
+            _ => Err(self.error("expected an argument")),
+        }
+    }
+
+    /// 单引号字符串；`\{` `\}` 保留反斜杠，由 `States.Format` 识别为字面量花括号
+    fn string(&mut self) -> Result<Expr> {
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(Expr::Literal(Value::String(out))),
+                Some('\\') => match self.bump() {
+                    Some(c @ ('\'' | '\\')) => out.push(c),
+                    Some(c @ ('{' | '}')) => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) => out.push(c),
+                None => return Err(self.error("unterminated string literal")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Expr> {
+        let start = self.pos;
+        self.bump();
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) {
+            self.bump();
+        }
+        let raw = &self.src[start..self.pos];
+        let number = match raw.parse::<i64>() {
+            Ok(i) => Number::from(i),
+            Err(_) => raw
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .ok_or_else(|| self.error(&format!("invalid number '{raw}'")))?,
+        };
+        Ok(Expr::Literal(Value::Number(number)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_path_and_nested_calls() {
+        assert_eq!(parse_expression("$.a['b,c']").unwrap(), Expr::Path("$.a['b,c']".into()));
+        assert_eq!(
+            parse_expression("States.Format('Hi {} \\'{}\\'', $.name, States.MathAdd(1, -2.5), true)").unwrap(),
+            Expr::Call {
+                name: "States.Format".into(),
+                args: vec![
+                    Expr::Literal(json!("Hi {} '{}'")),
+                    Expr::Path("$.name".into()),
+                    Expr::Call {
+                        name: "States.MathAdd".into(),
+                        args: vec![Expr::Literal(json!(1)), Expr::Literal(json!(-2.5))],
+                    },
+                    Expr::Literal(json!(true)),
+                ],
+            }
+        );
+        assert_eq!(
+            parse_expression("States.UUID()").unwrap(),
+            Expr::Call { name: "States.UUID".into(), args: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_expression("a.b").is_err());
+        assert!(parse_expression("States.Array(1, 2").is_err());
+        assert!(parse_expression("States.Format('open)").is_err());
+        assert!(parse_expression("$.a extra").is_err());
+    }
+}
@@ -7,8 +7,9 @@ pub mod model;      //  ← 提到 re-export 之前
 pub mod graph;
 pub mod resolver;
 pub mod engine;
+pub mod intrinsic;
 
 // —— 再做公开 re-export —— //
-pub use crate::model::{MappingDSL, MappingRule, MappingResult, PreserveFields};
+pub use crate::model::{MappingDSL, MappingRule, MappingResult, MappingStepSnapshot, OnError, PreserveFields};
 pub use crate::engine::MappingEngine;
 pub use crate::engine::context::MappingContext;
\ No newline at end of file
@@ -2,7 +2,13 @@ use serde::{Deserialize, Serialize};
 use serde::de::{self, Deserializer, SeqAccess, Visitor};
 use std::fmt;
 
-use super::rule::MappingRule;
+use jsonpath_lib::select;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use super::rule::{MappingRule, MappingType};
+use crate::error::MappingError;
+use crate::graph::builder::sort_rules;
 
 /// 输入字段保留策略
 #[derive(Debug, Clone, PartialEq)]
@@ -75,6 +81,19 @@ impl<'de> Deserialize<'de> for PreserveFields {
     }
 }
 
+/// 规则执行失败时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// 任一规则失败即返回错误
+    Fail,
+    /// 跳过失败规则，输出中不含该字段（默认）
+    #[default]
+    Skip,
+    /// 失败规则写入其 `default` 值（缺省为 null）
+    Default,
+}
+
 /// 顶层 DSL —— 对应一整个映射任务
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -91,10 +110,78 @@ pub struct MappingDSL {
     /// 是否启用调试模式（默认 false）
     #[serde(default)]
     pub debug: bool,
+    /// 规则失败处理策略：fail / skip（默认）/ default；`required` 规则总是失败
+    #[serde(default)]
+    pub on_error: OnError,
     /// 映射规则列表
     pub mappings: Vec<MappingRule>,
 }
 
+impl MappingDSL {
+    /// 静态校验：必填字段、JSONPath 语法、dependsOn 引用与环。
+    ///
+    /// 返回全部问题，每项为 `(规则路径, 错误)`，子映射路径形如 `items.name`；空列表表示通过。
+    pub fn validate(&self) -> Vec<(String, MappingError)> {
+        let mut problems = Vec::new();
+        validate_rules(&self.mappings, "", &mut problems);
+
+        let keys: HashSet<&str> = self.mappings.iter().map(|r| r.key.as_str()).collect();
+        let mut unknown_dep = false;
+        for rule in &self.mappings {
+            for dep in rule.depends_on.iter().flatten() {
+                if !keys.contains(dep.as_str()) {
+                    unknown_dep = true;
+                    problems.push((rule.key.clone(), MappingError::UnknownDependency(dep.clone())));
+                }
+            }
+        }
+        // 未知依赖同样会让拓扑排序失败，避免重复报告为环
+        if !unknown_dep
+            && let Err(e) = sort_rules(&self.mappings)
+        {
+            problems.push(("mappings".to_string(), e));
+        }
+        problems
+    }
+}
+
+fn validate_rules(rules: &[MappingRule], prefix: &str, problems: &mut Vec<(String, MappingError)>) {
+    for rule in rules {
+        let path = format!("{prefix}{}", rule.key);
+        let mut report = |e: MappingError| problems.push((path.clone(), e));
+
+        let required: &[(&'static str, bool)] = match rule.mapping_type {
+            MappingType::Constant => &[("value", rule.value.is_some())],
+            MappingType::JsonPath => &[("source", rule.source.is_some())],
+            MappingType::Expr => &[("transform", rule.transform.is_some())],
+            MappingType::Template => &[("template", rule.template.is_some())],
+            MappingType::SubMapping => &[
+                ("source", rule.source.is_some()),
+                ("subMappings", rule.sub_mappings.is_some()),
+            ],
+            MappingType::FormField => {
+                report(MappingError::UnsupportedType(format!("{:?}", rule.mapping_type)));
+                &[]
+            }
+        };
+        for (field, present) in required {
+            if !present {
+                report(MappingError::MissingField(field));
+            }
+        }
+
+        if let (MappingType::JsonPath | MappingType::SubMapping, Some(source)) = (&rule.mapping_type, &rule.source)
+            && let Err(e) = select(&Value::Null, source)
+        {
+            report(MappingError::JsonPath(format!("'{source}': {e}")));
+        }
+
+        if let (MappingType::SubMapping, Some(subs)) = (&rule.mapping_type, &rule.sub_mappings) {
+            validate_rules(subs, &format!("{path}."), problems);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +227,7 @@ mod tests {
             description: Some("desc".to_string()),
             preserve: PreserveFields::Some(vec!["foo".to_string(), "bar".to_string()]),
             debug: false,
+            on_error: OnError::Skip,
             mappings: vec![],
         };
         let ser = serde_json::to_string(&dsl).unwrap();
@@ -147,4 +235,26 @@ mod tests {
         assert_eq!(de.namespace, Some("ns1".to_string()));
         assert_eq!(de.preserve, PreserveFields::Some(vec!["foo".to_string(), "bar".to_string()]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let dsl: MappingDSL = serde_json::from_value(json!({
+            "mappings": [
+                { "key": "a", "type": "jsonPath", "source": "$.[" },
+                { "key": "b", "type": "constant", "dependsOn": ["missing"] },
+                { "key": "c", "type": "subMapping", "source": "$.items", "subMappings": [
+                    { "key": "name", "type": "expr" }
+                ]}
+            ]
+        }))
+        .unwrap();
+
+        let problems = dsl.validate();
+        let paths: Vec<&str> = problems.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["a", "b", "c.name", "b"]);
+        assert!(matches!(problems[0].1, MappingError::JsonPath(_)));
+        assert!(matches!(problems[1].1, MappingError::MissingField("value")));
+        assert!(matches!(problems[2].1, MappingError::MissingField("transform")));
+        assert!(matches!(problems[3].1, MappingError::UnknownDependency(_)));
+    }
+}
@@ -52,6 +52,12 @@ pub struct MappingRule {
     pub condition: Option<String>,      // future
     pub depends_on: Option<Vec<String>>,
 
+    /// 必填：解析失败或结果为 null 时映射整体失败（不受 `onError` 影响）
+    #[serde(default)]
+    pub required: bool,
+    /// `onError: default` 时规则失败写入的回退值（缺省写入 null）
+    pub default: Option<Value>,
+
     // —— UI / 文档辅助 ——
     pub comment: Option<String>,
     pub lang: Option<String>,
@@ -72,6 +78,8 @@ impl Default for MappingRule {
             merge_strategy: MergeStrategy::Overwrite,
             condition: None,
             depends_on: None,
+            required: false,
+            default: None,
             comment: None,
             lang: None,
             expected_type: None,
@@ -47,6 +47,8 @@ mod tests {
             lang: None,
             expected_type: None,
             schema: None,
+            required: false,
+            default: None,
         };
         let input = json!({});
         let out = resolve(&rule, &input).unwrap();
@@ -70,6 +72,8 @@ mod tests {
             lang: None,
             expected_type: None,
             schema: None,
+            required: false,
+            default: None,
         };
         let input = json!({"foo": 42});
         let out = resolve(&rule, &input).unwrap();
@@ -93,6 +97,8 @@ mod tests {
             lang: None,
             expected_type: None,
             schema: None,
+            required: false,
+            default: None,
         };
         let input = json!({"foo": 1});
         let out = resolve(&rule, &input);
@@ -116,6 +122,8 @@ mod tests {
             lang: None,
             expected_type: None,
             schema: None,
+            required: false,
+            default: None,
         };
         let _sub_dsl = MappingDSL {
             mappings: vec![sub_rule],
@@ -140,6 +148,8 @@ mod tests {
                     lang: None,
                     expected_type: None,
                     schema: None,
+                    required: false,
+                    default: None,
                 }
             ]),
             value: None,
@@ -153,6 +163,8 @@ mod tests {
             lang: None,
             expected_type: None,
             schema: None,
+            required: false,
+            default: None,
         };
         let input = json!({});
         let out = resolve(&rule, &input).unwrap();
@@ -53,7 +53,8 @@ fn run_all_examples() {
             let expect = fs::read_to_string(path.join("expected_output.json")).unwrap();
             let expect_json: Value = serde_json::from_str(&expect).unwrap();
             let output = MappingEngine::apply(dsl, &input_json)
-                .unwrap_or_else(|e| panic!("case `{}` unexpected error: {}", case_name, e));
+                .unwrap_or_else(|e| panic!("case `{}` unexpected error: {}", case_name, e))
+                .output;
             assert_eq!(
                 output,
                 expect_json,
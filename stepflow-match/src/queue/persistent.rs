@@ -4,6 +4,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use super::traits::TaskStore;
+use stepflow_dto::dto::queue_task::QueueTaskDto;
 use stepflow_storage::entities::queue_task::{StoredQueueTask, UpdateStoredQueueTask};
 use stepflow_storage::db::DynPM;
 
@@ -67,29 +68,29 @@ impl PersistentStore {
 #[async_trait]
 impl TaskStore for PersistentStore {
     // ---------------- insert ----------------
-    async fn insert_task(
-        &self,
-        run_id: &str,
-        state_name: &str,
-        resource: &str,
-        input: &Value,
-    ) -> Result<String, String> {
+    async fn insert_task(&self, task_queue: &str, task: &QueueTaskDto) -> Result<String, String> {
         let now = Utc::now().naive_utc();
         let task_id = Uuid::new_v4().to_string();
         let rec = StoredQueueTask {
             task_id: task_id.clone(),
-            run_id: run_id.into(),
-            state_name: state_name.into(),
-            resource: resource.into(),
-            task_payload: Some(input.clone()),
+            run_id: task.run_id.clone(),
+            state_name: task.state_name.clone(),
+            resource: task.resource.clone(),
+            task_queue: task_queue.into(),
+            root_run_id: task.root_run_id.clone(),
+            template_id: task.template_id.clone(),
+            worker_id: None,
+            task_payload: Some(task.task_payload.clone().unwrap_or(Value::Null)),
             status: "pending".into(),
-            attempts: 0,
+            attempts: task.attempts,
             max_attempts: 3,
-            priority: Some(0),
-            timeout_seconds: Some(300),
+            priority: Some(task.priority.unwrap_or(0) as i64),
+            timeout_seconds: task.timeout_seconds.or(Some(300)),
+            heartbeat_seconds: task.heartbeat_seconds,
+            last_heartbeat_at: None,
             error_message: None,
             last_error_at: None,
-            next_retry_at: None,
+            next_retry_at: task.next_retry_at.map(|d| d.naive_utc()),
             queued_at: now,
             processing_at: None,
             completed_at: None,
@@ -1,18 +1,13 @@
 use serde_json::Value;
+use stepflow_dto::dto::queue_task::QueueTaskDto;
 
 use stepflow_storage::db::DynPM;
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait TaskStore {
-    /// 插入一条新任务，返回该行的 `task_id`
-    async fn insert_task(
-        &self,
-        run_id: &str,
-        state_name: &str,
-        resource: &str,
-        input: &Value,
-    ) -> Result<String, String>;
+    /// 把任务插入 `task_queue`（状态 pending），返回新行的 `task_id`
+    async fn insert_task(&self, task_queue: &str, task: &QueueTaskDto) -> Result<String, String>;
 
     /// 更新任务状态（processing/completed/failed/...）
     async fn update_task_status(
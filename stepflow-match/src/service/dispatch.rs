@@ -0,0 +1,306 @@
+//! service/dispatch.rs
+//!  - 派发策略：优先级老化 + 公平轮转（按模板加权、模板内按根执行轮转）+ 队列限速
+//!  - 内存 / 持久化 / 混合匹配服务共用同一个 Dispatcher 挑选任务，派发顺序一致
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use stepflow_dto::dto::queue_task::QueueTaskDto;
+
+/// 默认每等待 30 秒有效优先级 +1
+pub const DEFAULT_AGING_SECS: u64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct DispatchPolicy {
+    /// 任务每等待 `aging_secs` 秒有效优先级 +1，低优先级任务不会被持续饿死；0 表示不老化
+    pub aging_secs: u64,
+    /// 模板权重（缺省 1）：权重为 2 的模板获得两倍的派发份额
+    pub template_weights: HashMap<String, u32>,
+    /// 队列限速（任务 / 秒），未配置的队列不限速
+    pub rate_limits: HashMap<String, f64>,
+}
+
+impl Default for DispatchPolicy {
+    fn default() -> Self {
+        Self {
+            aging_secs: DEFAULT_AGING_SECS,
+            template_weights: HashMap::new(),
+            rate_limits: HashMap::new(),
+        }
+    }
+}
+
+/// 令牌桶：容量为 max(rate, 1)，按经过的时间补充
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens:     f64,
+    updated_at: DateTime<Utc>,
+}
+
+/// 公平轮转状态（stride 调度）：每次派发推进模板 / 根执行的虚拟时间，虚拟时间最小者优先
+#[derive(Debug, Default)]
+struct FairShare {
+    templates:      HashMap<String, f64>,
+    /// 根执行只在所属模板内轮转：(模板, 根执行) → 虚拟时间
+    runs:           HashMap<(String, String), f64>,
+    /// 最近一次派发时的虚拟时间；新加入（或闲置后回来）的模板 / 执行从这里起步，不能攒份额
+    template_clock: f64,
+    run_clocks:     HashMap<String, f64>,
+    buckets:        HashMap<String, TokenBucket>,
+}
+
+impl FairShare {
+    fn template_vt(&self, template: &str) -> f64 {
+        self.templates.get(template).map_or(self.template_clock, |vt| vt.max(self.template_clock))
+    }
+
+    fn run_clock(&self, template: &str) -> f64 {
+        self.run_clocks.get(template).copied().unwrap_or(0.0)
+    }
+
+    fn run_vt(&self, template: &str, run: &str) -> f64 {
+        let clock = self.run_clock(template);
+        self.runs
+            .get(&(template.to_string(), run.to_string()))
+            .map_or(clock, |vt| vt.max(clock))
+    }
+}
+
+pub struct Dispatcher {
+    policy: DispatchPolicy,
+    state:  Mutex<FairShare>,
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self::new(DispatchPolicy::default())
+    }
+}
+
+impl Dispatcher {
+    pub fn new(policy: DispatchPolicy) -> Self {
+        Self { policy, state: Mutex::new(FairShare::default()) }
+    }
+
+    pub fn policy(&self) -> &DispatchPolicy {
+        &self.policy
+    }
+
+    /// 有效优先级 = priority + 已等待秒数 / aging_secs
+    pub fn effective_priority(&self, task: &QueueTaskDto, now: DateTime<Utc>) -> i64 {
+        let base = task.priority.unwrap_or(0) as i64;
+        if self.policy.aging_secs == 0 {
+            return base;
+        }
+        let waited = (now - task.queued_at).num_seconds().max(0);
+        base + waited / self.policy.aging_secs as i64
+    }
+
+    /// 从候选任务中挑出下一个派发的，返回其下标：
+    /// 跳过未到重试时间或所在队列已被限速的任务 → 取有效优先级最高的一档 →
+    /// 虚拟时间最小的模板 → 该模板内虚拟时间最小的根执行 → 最早入队
+    pub fn pick<'a>(
+        &self,
+        candidates: impl IntoIterator<Item = &'a QueueTaskDto>,
+        now: DateTime<Utc>,
+    ) -> Option<usize> {
+        let state = self.state.lock().unwrap();
+        let ready: Vec<(usize, &QueueTaskDto, i64)> = candidates
+            .into_iter()
+            .enumerate()
+            .filter(|(_, t)| t.next_retry_at.is_none_or(|at| at <= now))
+            .filter(|(_, t)| self.tokens(&state, &t.task_queue, now) >= 1.0)
+            .map(|(i, t)| (i, t, self.effective_priority(t, now)))
+            .collect();
+        let top = ready.iter().map(|(_, _, p)| *p).max()?;
+
+        ready
+            .into_iter()
+            .filter(|(_, _, p)| *p == top)
+            .min_by(|(_, a, _), (_, b, _)| {
+                let key = |t: &QueueTaskDto| {
+                    (state.template_vt(template_key(t)), state.run_vt(template_key(t), run_key(t)))
+                };
+                key(a)
+                    .partial_cmp(&key(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.queued_at.cmp(&b.queued_at))
+                    .then_with(|| a.task_id.cmp(&b.task_id))
+            })
+            .map(|(i, _, _)| i)
+    }
+
+    /// 记录一次派发：消耗队列令牌并推进模板 / 根执行的虚拟时间
+    pub fn record(&self, task: &QueueTaskDto, now: DateTime<Utc>) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(tokens) = self.refill(&state, &task.task_queue, now) {
+            state.buckets.insert(
+                task.task_queue.clone(),
+                TokenBucket { tokens: (tokens - 1.0).max(0.0), updated_at: now },
+            );
+        }
+
+        let template = template_key(task);
+        let weight = self.policy.template_weights.get(template).copied().unwrap_or(1).max(1);
+        let template_vt = state.template_vt(template);
+        state.template_clock = template_vt;
+        state.templates.insert(template.to_string(), template_vt + 1.0 / weight as f64);
+
+        let run_vt = state.run_vt(template, run_key(task));
+        state.run_clocks.insert(template.to_string(), run_vt);
+        state.runs.insert((template.to_string(), run_key(task).to_string()), run_vt + 1.0);
+
+        // 落后于时钟的记录与新加入者等价，直接丢弃，避免表无限增长
+        let template_clock = state.template_clock;
+        state.templates.retain(|_, vt| *vt > template_clock);
+        let FairShare { runs, run_clocks, .. } = &mut *state;
+        runs.retain(|(tpl, _), vt| *vt > run_clocks.get(tpl).copied().unwrap_or(0.0));
+        run_clocks.retain(|tpl, _| runs.keys().any(|(t, _)| t == tpl));
+    }
+
+    /// 队列当前是否还有令牌（未限速的队列总是有）
+    pub fn admits(&self, queue: &str, now: DateTime<Utc>) -> bool {
+        let state = self.state.lock().unwrap();
+        self.tokens(&state, queue, now) >= 1.0
+    }
+
+    /// 这些队列中被限速的那些最早多久后补回一个令牌；都未被限速时返回 None
+    pub fn next_token_in(&self, queues: &[String], now: DateTime<Utc>) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        queues
+            .iter()
+            .filter_map(|q| {
+                let rate = *self.policy.rate_limits.get(q).filter(|r| **r > 0.0)?;
+                let tokens = self.refill(&state, q, now)?;
+                (tokens < 1.0).then(|| Duration::from_secs_f64((1.0 - tokens) / rate))
+            })
+            .min()
+    }
+
+    fn tokens(&self, state: &FairShare, queue: &str, now: DateTime<Utc>) -> f64 {
+        self.refill(state, queue, now).unwrap_or(f64::INFINITY)
+    }
+
+    /// 限速队列在 `now` 的令牌数；未限速返回 None
+    fn refill(&self, state: &FairShare, queue: &str, now: DateTime<Utc>) -> Option<f64> {
+        let rate = *self.policy.rate_limits.get(queue).filter(|r| **r > 0.0)?;
+        let capacity = rate.max(1.0);
+        Some(match state.buckets.get(queue) {
+            Some(bucket) => {
+                let elapsed = (now - bucket.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+                (bucket.tokens + elapsed * rate).min(capacity)
+            }
+            None => capacity,
+        })
+    }
+}
+
+/// 公平轮转的执行维度：根执行（未设置时退回 run_id）
+fn run_key(task: &QueueTaskDto) -> &str {
+    if task.root_run_id.is_empty() { &task.run_id } else { &task.root_run_id }
+}
+
+/// 公平轮转的模板维度：直接运行 DSL 的执行共用一个空模板
+fn template_key(task: &QueueTaskDto) -> &str {
+    task.template_id.as_deref().unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, run: &str, template: Option<&str>, priority: u8, queued_secs_ago: i64) -> QueueTaskDto {
+        let now = Utc::now();
+        QueueTaskDto {
+            task_id: id.into(),
+            run_id: run.into(),
+            state_name: "S".into(),
+            resource: "http".into(),
+            task_queue: "http".into(),
+            root_run_id: String::new(),
+            template_id: template.map(str::to_string),
+            worker_id: None,
+            task_payload: None,
+            status: "pending".into(),
+            attempts: 0,
+            max_attempts: 3,
+            priority: Some(priority),
+            timeout_seconds: None,
+            heartbeat_seconds: None,
+            last_heartbeat_at: None,
+            error_message: None,
+            last_error_at: None,
+            next_retry_at: None,
+            queued_at: now - chrono::Duration::seconds(queued_secs_ago),
+            processing_at: None,
+            completed_at: None,
+            failed_at: None,
+        }
+    }
+
+    /// 反复挑选并派发，返回派发顺序
+    fn drain(d: &Dispatcher, mut pending: Vec<QueueTaskDto>, now: DateTime<Utc>) -> Vec<String> {
+        let mut order = Vec::new();
+        while let Some(i) = d.pick(&pending, now) {
+            let t = pending.remove(i);
+            d.record(&t, now);
+            order.push(t.task_id);
+        }
+        order
+    }
+
+    #[test]
+    fn test_priority_wins_and_aging_prevents_starvation() {
+        let d = Dispatcher::default();
+        let now = Utc::now();
+        let tasks = vec![task("low", "r1", None, 0, 0), task("high", "r2", None, 5, 0)];
+        assert_eq!(drain(&d, tasks, now), ["high", "low"]);
+
+        // 等了 3 分钟的低优先级任务（0 + 180/30 = 6）超过刚入队的高优先级任务
+        let tasks = vec![task("old", "r1", None, 0, 180), task("new", "r2", None, 5, 0)];
+        assert_eq!(drain(&d, tasks, now)[0], "old");
+    }
+
+    #[test]
+    fn test_runs_and_templates_share_dispatch_fairly() {
+        let d = Dispatcher::new(DispatchPolicy {
+            template_weights: HashMap::from([("heavy".to_string(), 2)]),
+            ..Default::default()
+        });
+        let now = Utc::now();
+
+        // 同一执行的大量任务先入队，不会挡住后来的执行
+        let mut tasks: Vec<_> = (0..4).map(|i| task(&format!("a{i}"), "noisy", None, 0, 10)).collect();
+        tasks.push(task("b0", "quiet", None, 0, 0));
+        assert_eq!(drain(&d, tasks, now)[..2], ["a0", "b0"]);
+
+        // 权重 2 的模板每轮派发两次
+        let d = Dispatcher::new(d.policy().clone());
+        let mut tasks: Vec<_> = (0..4).map(|i| task(&format!("h{i}"), &format!("h{i}"), Some("heavy"), 0, 0)).collect();
+        tasks.extend((0..4).map(|i| task(&format!("l{i}"), &format!("l{i}"), Some("light"), 0, 0)));
+        let order = drain(&d, tasks, now);
+        let heavy_first_six = order[..6].iter().filter(|id| id.starts_with('h')).count();
+        assert_eq!(heavy_first_six, 4);
+    }
+
+    #[test]
+    fn test_rate_limit_blocks_queue_until_tokens_refill() {
+        let d = Dispatcher::new(DispatchPolicy {
+            rate_limits: HashMap::from([("http".to_string(), 2.0)]),
+            ..Default::default()
+        });
+        let now = Utc::now();
+        let tasks: Vec<_> = (0..5).map(|i| task(&format!("t{i}"), &format!("r{i}"), None, 0, 0)).collect();
+
+        // 容量 2：连续派发两个后被限速
+        assert_eq!(drain(&d, tasks.clone(), now).len(), 2);
+        assert!(!d.admits("http", now));
+        assert!(d.admits("shell", now));
+        let wait = d.next_token_in(&["http".into(), "shell".into()], now).unwrap();
+        assert!(wait <= Duration::from_millis(500));
+
+        // 半秒后补回一个令牌
+        assert!(d.admits("http", now + chrono::Duration::milliseconds(500)));
+    }
+}
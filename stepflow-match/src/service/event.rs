@@ -45,12 +45,14 @@ impl MatchService for EventDrivenMatchService {
         Ok(task_id)
     }
 
-    async fn take_task(
+    async fn take_task_from(
         &self,
-        _queue: &str,
+        _queues: &[String],
         _worker_id: &str,
-        _timeout: Duration,
+        timeout: Duration,
     ) -> Option<QueueTaskDto> {
+        // 无任务存储，任务只经 EventBus 投递：轮询等满超时后返回空，避免调用方空转
+        tokio::time::sleep(timeout).await;
         None
     }
 
@@ -63,6 +65,15 @@ impl MatchService for EventDrivenMatchService {
         Ok(())
     }
 
+    async fn heartbeat(&self, _run_id: &str, _state_name: &str) -> Result<(), String> {
+        // 无任务存储，心跳无需记录
+        Ok(())
+    }
+
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String> {
+        Err(format!("task {run_id}:{state_name} is not processing"))
+    }
+
     async fn wait_for_completion(
         &self,
         _run_id: &str,
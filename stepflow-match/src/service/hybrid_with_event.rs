@@ -1,9 +1,11 @@
 use std::{any::Any, sync::Arc, time::Duration};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
-use crate::service::interface::{DynPM, MatchService};
+use crate::service::interface::{DynPM, ExpiredTask, MatchService};
 use stepflow_dto::dto::{
+    match_stats::MatchStats,
     queue_task::{QueueTaskDto, UpdateQueueTaskDto},
     engine_event::EngineEvent,
 };
@@ -33,6 +35,10 @@ impl MatchService for HybridMatchServiceWithEvent {
         self
     }
 
+    async fn queue_stats(&self) -> Vec<MatchStats> {
+        self.persistent_service.queue_stats().await
+    }
+
     async fn enqueue_task(
         &self,
         queue: &str,
@@ -55,14 +61,18 @@ impl MatchService for HybridMatchServiceWithEvent {
         Ok(task_id)
     }
 
-    async fn take_task(
+    async fn take_task_from(
         &self,
-        _queue: &str,
-        _worker_id: &str,
-        _timeout: Duration,
+        queues: &[String],
+        worker_id: &str,
+        timeout: Duration,
     ) -> Option<QueueTaskDto> {
-        // ⚠️ 在事件驱动模式中，不再使用轮询获取任务
-        None
+        // 订阅 EventBus 的 worker 不轮询；仍走轮询 / 推送的 worker 由持久化层长轮询认领
+        self.persistent_service.take_task_from(queues, worker_id, timeout).await
+    }
+
+    async fn claim_task(&self, task_id: &str, worker_id: &str) -> Result<Option<QueueTaskDto>, String> {
+        self.persistent_service.claim_task(task_id, worker_id).await
     }
 
     async fn finish_task(
@@ -76,6 +86,26 @@ impl MatchService for HybridMatchServiceWithEvent {
             .await
     }
 
+    async fn heartbeat(&self, run_id: &str, state_name: &str) -> Result<(), String> {
+        self.persistent_service.heartbeat(run_id, state_name).await
+    }
+
+    async fn reap_expired_tasks(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredTask>, String> {
+        self.persistent_service.reap_expired_tasks(now).await
+    }
+
+    async fn cancel_run_tasks(&self, run_id: &str) -> Result<u64, String> {
+        self.persistent_service.cancel_run_tasks(run_id).await
+    }
+
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String> {
+        self.persistent_service.requeue_task(run_id, state_name).await
+    }
+
+    async fn requeue_worker_tasks(&self, worker_id: &str) -> Result<Vec<QueueTaskDto>, String> {
+        self.persistent_service.requeue_worker_tasks(worker_id).await
+    }
+
     async fn wait_for_completion(
         &self,
         run_id: &str,
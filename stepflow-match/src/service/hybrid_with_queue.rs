@@ -2,18 +2,27 @@
 //!  - “内存优先 + 持久化兜底” 的调度层
 //!  - 适配最新版 MatchService Trait
 
-use std::{any::Any, sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 use tracing::{debug, warn};
 
-use crate::service::interface::{DynPM, MatchService};
+use crate::service::interface::{DynPM, ExpiredTask, MatchService};
 use stepflow_dto::dto::{
+    match_stats::MatchStats,
     queue_task::{QueueTaskDto, UpdateQueueTaskDto},
 };
 
+/// 长轮询时在内存层每次最多等待的时长，之后回落到持久化查一次：
+/// 其他网关写入、只在库中的任务不会被内存层的长等待挡住
+const MEMORY_WAIT_SLICE: Duration = Duration::from_secs(1);
+
 /// 内存 + 持久化混合实现
 pub struct HybridMatchService {
     memory_service:     Arc<dyn MatchService>,
@@ -39,6 +48,19 @@ impl MatchService for HybridMatchService {
     // 允许向下转型
     fn as_any(&self) -> &dyn Any { self }
 
+    // ────────── stats ───────────────────────────────────────────
+    /// 积压以持久化为准，等待中的 worker 来自内存层
+    async fn queue_stats(&self) -> Vec<MatchStats> {
+        let mut stats = self.persistent_service.queue_stats().await;
+        for mem in self.memory_service.queue_stats().await {
+            match stats.iter_mut().find(|s| s.queue == mem.queue) {
+                Some(s) => s.waiting_workers = mem.waiting_workers,
+                None => stats.push(MatchStats { pending_tasks: 0, ..mem }),
+            }
+        }
+        stats
+    }
+
     // ────────── enqueue ─────────────────────────────────────────
     /// 1️⃣ 先写持久化获取 task_id → 2️⃣ 回写内存（回填同一个 task_id）
     async fn enqueue_task(
@@ -63,54 +85,55 @@ impl MatchService for HybridMatchService {
     }
 
     // ────────── take_task ───────────────────────────────────────
-    /// 先尝试「内存」，若 miss 且允许则退到持久化。
-    async fn take_task(
+    /// 先尝试「内存」，若 miss 且允许则退到持久化；两层轮流检查直到超时。
+    async fn take_task_from(
         &self,
-        queue: &str,
+        queues: &[String],
         worker_id: &str,
         timeout: Duration,
     ) -> Option<QueueTaskDto> {
-        // ① 内存队列
-        if let Some(task) = self
-            .memory_service
-            .take_task(queue, worker_id, timeout)
-            .await
-        {
-            debug!("take (memory) -> {:?}", task);
-
-            // 同步把持久化那条改成 processing
-            let patch = UpdateQueueTaskDto {
-                status:        Some("processing".into()),
-                processing_at: Some(Some(Utc::now())),
-                ..Default::default()
-            };
-            if let Err(e) = self
-                .persistent_service
-                .finish_task(&task.run_id, &task.state_name, patch)
+        let deadline = Instant::now() + timeout;
+        loop {
+            // ① 内存队列：只等一小段，库中的任务不被挡住
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if let Some(task) = self
+                .memory_service
+                .take_task_from(queues, worker_id, remaining.min(MEMORY_WAIT_SLICE))
                 .await
             {
-                warn!("shadow mark processing failed: {e}");
+                // 以库为准按 task_id 条件认领；认领失败说明已被其他 worker（如另一网关）取走，
+                // 丢弃内存副本后继续挑选
+                match self.persistent_service.claim_task(&task.task_id, worker_id).await {
+                    Ok(Some(claimed)) => {
+                        debug!("take (memory) -> {:?}", claimed);
+                        return Some(claimed);
+                    }
+                    Ok(None) => debug!("memory task {} already claimed, dropped", task.task_id),
+                    Err(e) => warn!("claim memory task {} failed, dropped: {e}", task.task_id),
+                }
+                let _ = self
+                    .memory_service
+                    .finish_task(&task.run_id, &task.state_name, UpdateQueueTaskDto::default())
+                    .await;
+                continue;
             }
 
-            return Some(task);
-        }
-
-        // ② 持久化队列（兜底）
-        if self.fallback_enabled {
-            if let Some(task) = self
-                .persistent_service
-                .take_task(queue, worker_id, timeout)
-                .await
+            // ② 持久化队列（兜底）
+            if self.fallback_enabled
+                && let Some(task) = self
+                    .persistent_service
+                    .take_task_from(queues, worker_id, Duration::ZERO)
+                    .await
             {
+                // 已在库中认领为 processing；内存里的副本之后认领失败会被丢弃
                 debug!("take (persistent) -> {:?}", task);
-
-                // 放一份到内存，方便 wait/notify
-                let _ = self.memory_service.enqueue_task(queue, task.clone()).await;
                 return Some(task);
             }
-        }
 
-        None
+            if Instant::now() >= deadline {
+                return None;
+            }
+        }
     }
 
     // ────────── finish_task ─────────────────────────────────────
@@ -138,6 +161,87 @@ impl MatchService for HybridMatchService {
         Ok(())
     }
 
+    // ────────── heartbeat ───────────────────────────────────────
+    /// 以持久化为准，内存副本同步刷新（失败只告警）
+    async fn heartbeat(&self, run_id: &str, state_name: &str) -> Result<(), String> {
+        self.persistent_service.heartbeat(run_id, state_name).await?;
+
+        if let Err(e) = self.memory_service.heartbeat(run_id, state_name).await {
+            warn!("memory heartbeat failed: {e}");
+        }
+        Ok(())
+    }
+
+    // ────────── reap ────────────────────────────────────────────
+    /// 由持久化判定过期，再把内存中的同一任务一并结束
+    async fn reap_expired_tasks(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredTask>, String> {
+        let expired = self.persistent_service.reap_expired_tasks(now).await?;
+
+        for e in &expired {
+            if let Err(err) = self
+                .memory_service
+                .finish_task(&e.task.run_id, &e.task.state_name, e.failed_patch(now))
+                .await
+            {
+                warn!("memory finish_task for expired task failed: {err}");
+            }
+        }
+        Ok(expired)
+    }
+
+    // ────────── cancel ──────────────────────────────────────────
+    /// 两侧都撤下该执行的任务，以持久化的计数为准
+    async fn cancel_run_tasks(&self, run_id: &str) -> Result<u64, String> {
+        let cancelled = self.persistent_service.cancel_run_tasks(run_id).await?;
+
+        if let Err(e) = self.memory_service.cancel_run_tasks(run_id).await {
+            warn!("memory cancel_run_tasks failed: {e}");
+        }
+        Ok(cancelled)
+    }
+
+    // ────────── requeue ─────────────────────────────────────────
+    /// 以持久化退回的任务为准；内存里没有处理中副本（从库中兜底认领）时补入内存队列
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String> {
+        let task = self.persistent_service.requeue_task(run_id, state_name).await?;
+
+        if self.memory_service.requeue_task(run_id, state_name).await.is_err()
+            && let Err(e) = self
+                .memory_service
+                .enqueue_task(&task.task_queue, task.clone())
+                .await
+        {
+            warn!("re-enqueue requeued task to memory failed: {e}");
+        }
+        Ok(task)
+    }
+
+    /// 以持久化退回的任务为准；从库中兜底认领、内存里没有副本的任务补入内存队列
+    async fn requeue_worker_tasks(&self, worker_id: &str) -> Result<Vec<QueueTaskDto>, String> {
+        let requeued = self.persistent_service.requeue_worker_tasks(worker_id).await?;
+
+        let in_memory = match self.memory_service.requeue_worker_tasks(worker_id).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                warn!("memory requeue_worker_tasks failed: {e}");
+                Vec::new()
+            }
+        };
+        for task in &requeued {
+            if in_memory.iter().any(|t| t.task_id == task.task_id) {
+                continue;
+            }
+            if let Err(e) = self
+                .memory_service
+                .enqueue_task(&task.task_queue, task.clone())
+                .await
+            {
+                warn!("re-enqueue requeued task to memory failed: {e}");
+            }
+        }
+        Ok(requeued)
+    }
+
     // ────────── wait_for_completion ────────────────────────────
     /// 仍由内存实现最快返回
     async fn wait_for_completion(
@@ -1,6 +1,7 @@
 //! service/interface.rs
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 use std::{any::Any, sync::Arc, time::Duration};
 
@@ -8,9 +9,59 @@ use stepflow_dto::dto::{
     match_stats::MatchStats,
     queue_task::{QueueTaskDto, UpdateQueueTaskDto},   // ← 就这俩
 };
-use stepflow_storage::{db::DbBackend, persistence_manager::PersistenceManager};
+use stepflow_exception::{STATES_HEARTBEAT_TIMEOUT, STATES_TIMEOUT};
+use stepflow_storage::persistence_manager::PersistenceManager;
 
-pub type DynPM = Arc<dyn PersistenceManager<DB = DbBackend> + Send + Sync>;
+pub type DynPM = Arc<dyn PersistenceManager + Send + Sync>;
+
+/// 被回收的 processing 任务：整体超时或心跳超时
+#[derive(Debug, Clone)]
+pub struct ExpiredTask {
+    pub task: QueueTaskDto,
+    /// `States.Timeout` / `States.HeartbeatTimeout`
+    pub error_type: &'static str,
+    pub reason: String,
+}
+
+impl ExpiredTask {
+    /// 判断 processing 任务在 `now` 是否已过期：
+    /// `timeout_seconds` 从 processing_at 起算；`heartbeat_seconds` 从最近一次心跳（无心跳则 processing_at）起算
+    pub fn check(task: &QueueTaskDto, now: DateTime<Utc>) -> Option<Self> {
+        let started = task.processing_at?;
+        let expired = |since: DateTime<Utc>, secs: i64| now - since >= chrono::Duration::seconds(secs);
+
+        if let Some(secs) = task.timeout_seconds.filter(|&s| s > 0)
+            && expired(started, secs)
+        {
+            return Some(Self {
+                task: task.clone(),
+                error_type: STATES_TIMEOUT,
+                reason: format!("Task did not finish within {secs}s"),
+            });
+        }
+        if let Some(secs) = task.heartbeat_seconds.filter(|&s| s > 0)
+            && expired(task.last_heartbeat_at.unwrap_or(started), secs)
+        {
+            return Some(Self {
+                task: task.clone(),
+                error_type: STATES_HEARTBEAT_TIMEOUT,
+                reason: format!("No heartbeat received within {secs}s"),
+            });
+        }
+        None
+    }
+
+    /// 回收时写回队列表的 patch
+    pub fn failed_patch(&self, now: DateTime<Utc>) -> UpdateQueueTaskDto {
+        UpdateQueueTaskDto {
+            status:        Some("failed".into()),
+            error_message: Some(Some(format!("{}: {}", self.error_type, self.reason))),
+            last_error_at: Some(Some(now)),
+            failed_at:     Some(Some(now)),
+            ..Default::default()
+        }
+    }
+}
 
 #[async_trait]
 pub trait MatchService: Send + Sync {
@@ -23,14 +74,30 @@ pub trait MatchService: Send + Sync {
     /// 入队，返回 task_id
     async fn enqueue_task(&self, queue: &str, task: QueueTaskDto) -> Result<String, String>;
 
-    /// worker 取任务；取到即标记为 processing
+    /// worker 从单个队列取任务；取到即标记为 processing
     async fn take_task(
         &self,
         queue: &str,
         worker_id: &str,
         timeout: Duration,
+    ) -> Option<QueueTaskDto> {
+        self.take_task_from(&[queue.to_string()], worker_id, timeout).await
+    }
+
+    /// worker 同时轮询多个队列（按 capabilities / taskQueue 汇总），取到任一队列的任务即返回
+    async fn take_task_from(
+        &self,
+        queues: &[String],
+        worker_id: &str,
+        timeout: Duration,
     ) -> Option<QueueTaskDto>;
 
+    /// 按 task_id 原子认领一条 pending 任务（pending → processing），已被其他 worker 认领时返回 `None`；
+    /// 混合模式用它确认内存层派发的任务在库中仍可认领
+    async fn claim_task(&self, task_id: &str, _worker_id: &str) -> Result<Option<QueueTaskDto>, String> {
+        Err(format!("claim of task {task_id} is not supported"))
+    }
+
     /// 任务结束（完成 / 失败 / 取消）
     ///
     /// *用 `(run_id, state_name)` 双键定位任务*，同时带上 **局部 patch**。
@@ -41,6 +108,31 @@ pub trait MatchService: Send + Sync {
         patch:      UpdateQueueTaskDto,
     ) -> Result<(), String>;
 
+    /// worker 心跳：刷新 processing 任务的 `last_heartbeat_at`；任务不在处理中时返回错误
+    async fn heartbeat(&self, run_id: &str, state_name: &str) -> Result<(), String>;
+
+    /// 回收已超时 / 心跳超时的 processing 任务：标记为 failed 并返回，
+    /// 由调用方把 `States.Timeout` / `States.HeartbeatTimeout` 送回引擎走 Retry / Catch
+    async fn reap_expired_tasks(&self, _now: DateTime<Utc>) -> Result<Vec<ExpiredTask>, String> {
+        Ok(Vec::new())
+    }
+
+    /// 执行被取消 / 终止：撤下该执行尚未派发的任务，处理中的任务标记为 cancelled
+    /// （持有任务的 worker 心跳随之被拒绝，据此中止执行），返回受影响的任务数
+    async fn cancel_run_tasks(&self, _run_id: &str) -> Result<u64, String> {
+        Ok(0)
+    }
+
+    /// 已认领的任务没能送达 worker（如推送连接断开）：退回 pending（不计重试次数）
+    /// 以便其他 worker 认领，返回被退回的任务；任务不在处理中时返回错误
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String>;
+
+    /// worker 注销或失联：把它仍在处理的任务退回 pending（不计重试次数）以便其他 worker 认领，
+    /// 返回被退回的任务
+    async fn requeue_worker_tasks(&self, _worker_id: &str) -> Result<Vec<QueueTaskDto>, String> {
+        Ok(Vec::new())
+    }
+
     // ---------------- Engine 专用 ----------------
 
     async fn wait_for_completion(
@@ -3,43 +3,163 @@
 
 use std::{
     any::Any,
-    collections::{HashMap, VecDeque},
-    sync::Arc,
-    time::Duration,
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 use tokio::{
     sync::{oneshot, Mutex},
     time::timeout,
 };
 
-use crate::service::interface::{DynPM, MatchService};
+use crate::service::{
+    dispatch::Dispatcher,
+    interface::{DynPM, ExpiredTask, MatchService},
+};
 use stepflow_dto::dto::{
     match_stats::MatchStats,
     queue_task::{QueueTaskDto, UpdateQueueTaskDto},
 };
 
+/// 限速 / 重试任务到点后重新挑选的最短间隔
+const MIN_RECHECK: Duration = Duration::from_millis(10);
+
+/// 挂起中的 worker：轮询的队列 + 唤醒用的 oneshot Sender
+type Waiter = (Vec<String>, oneshot::Sender<()>);
+
 /// 线程安全的内存实现，适合单机测试/开发
 pub struct MemoryMatchService {
     /// queue → pending 任务
     pending_tasks: Mutex<HashMap<String, VecDeque<QueueTaskDto>>>,
-    /// worker_id → 挂起中的 oneshot Sender
-    waiting_workers: Mutex<HashMap<String, oneshot::Sender<QueueTaskDto>>>,
+    /// 挂起中的轮询 → (轮询的队列, oneshot Sender)；按每次轮询编号，
+    /// 同一 worker 的多个并发长轮询（多槽位）各自挂起、互不覆盖
+    waiting_workers: Mutex<HashMap<u64, Waiter>>,
+    next_waiter: AtomicU64,
     /// (run_id, state_name) → 完成输出
     finished_results: Mutex<HashMap<(String, String), Value>>,
+    /// (run_id, state_name) → 已派发、处理中的任务（心跳 / 超时回收用）
+    processing_tasks: Mutex<HashMap<(String, String), QueueTaskDto>>,
+    /// 派发策略：优先级老化、公平轮转与队列限速
+    dispatcher: Arc<Dispatcher>,
 }
 
 impl MemoryMatchService {
     pub fn new() -> Arc<Self> {
+        Self::with_dispatcher(Arc::new(Dispatcher::default()))
+    }
+
+    /// 使用给定的派发策略（混合模式下与持久化层共用同一个 Dispatcher）
+    pub fn with_dispatcher(dispatcher: Arc<Dispatcher>) -> Arc<Self> {
         Arc::new(Self {
             pending_tasks:    Mutex::new(HashMap::new()),
             waiting_workers:  Mutex::new(HashMap::new()),
+            next_waiter:      AtomicU64::new(0),
             finished_results: Mutex::new(HashMap::new()),
+            processing_tasks: Mutex::new(HashMap::new()),
+            dispatcher,
         })
     }
+
+    /// 取出一个轮询了 `queue` 的挂起 worker（编号最小即最早挂起的优先）
+    async fn take_waiter(&self, queue: &str) -> Option<(u64, Waiter)> {
+        let mut waiting = self.waiting_workers.lock().await;
+        let waiter_id = waiting
+            .iter()
+            .filter(|(_, (queues, _))| queues.iter().any(|q| q == queue))
+            .map(|(id, _)| *id)
+            .min()?;
+        waiting.remove_entry(&waiter_id)
+    }
+
+    /// 唤醒一个轮询了 `queue` 的挂起 worker，由它自己按派发策略挑选任务；
+    /// 只传信号不传任务，长轮询被中途取消（如 HTTP 连接断开）时任务仍留在 pending
+    async fn wake_waiter(&self, queue: &str) {
+        while let Some((_, (_, waiter))) = self.take_waiter(queue).await {
+            if waiter.send(()).is_ok() {
+                return;
+            }
+        }
+    }
+
+    /// 按派发策略从这些队列的 pending 中挑一个任务并标记为由 `worker_id` 处理
+    async fn dispatch_pending(&self, queues: &[String], worker_id: &str) -> Option<QueueTaskDto> {
+        let now = Utc::now();
+        let mut task = {
+            let mut pending = self.pending_tasks.lock().await;
+            let (queue, idx) = {
+                let slots: Vec<(&String, usize, &QueueTaskDto)> = queues
+                    .iter()
+                    .filter_map(|q| pending.get(q).map(|list| (q, list)))
+                    .flat_map(|(q, list)| list.iter().enumerate().map(move |(i, t)| (q, i, t)))
+                    .collect();
+                let picked = self.dispatcher.pick(slots.iter().map(|(_, _, t)| *t), now)?;
+                (slots[picked].0.clone(), slots[picked].1)
+            };
+            pending.get_mut(&queue)?.remove(idx)?
+        };
+        self.dispatcher.record(&task, now);
+        self.mark_processing(&mut task, worker_id).await;
+        Some(task)
+    }
+
+    /// 这些队列里被限速或等待重试的任务最早多久后可派发
+    async fn next_ready_in(&self, queues: &[String]) -> Option<Duration> {
+        let now = Utc::now();
+        let pending = self.pending_tasks.lock().await;
+        let backlog: Vec<String> = queues
+            .iter()
+            .filter(|q| pending.get(*q).is_some_and(|list| !list.is_empty()))
+            .cloned()
+            .collect();
+        let retry_in = backlog
+            .iter()
+            .filter_map(|q| pending.get(q))
+            .flatten()
+            .filter_map(|t| t.next_retry_at.filter(|at| *at > now))
+            .min()
+            .and_then(|at| (at - now).to_std().ok());
+        let token_in = self.dispatcher.next_token_in(&backlog, now);
+        retry_in.into_iter().chain(token_in).min().map(|d| d.max(MIN_RECHECK))
+    }
+
+    /// 把已移出处理中列表的任务退回 pending（不计重试次数）并唤醒挂起的 worker
+    async fn return_to_pending(&self, mut tasks: Vec<QueueTaskDto>) -> Vec<QueueTaskDto> {
+        tasks.sort_by_key(|t| t.queued_at);
+
+        // 退回队首：这些任务本已派发过，优先于后来入队的任务
+        {
+            let mut pending = self.pending_tasks.lock().await;
+            for task in tasks.iter_mut().rev() {
+                task.status            = "pending".into();
+                task.processing_at     = None;
+                task.last_heartbeat_at = None;
+                task.worker_id         = None;
+                pending.entry(task.task_queue.clone()).or_default().push_front(task.clone());
+            }
+        }
+        for task in &tasks {
+            self.wake_waiter(&task.task_queue).await;
+        }
+        tasks
+    }
+
+    /// 标记为 processing 并登记到处理中列表
+    async fn mark_processing(&self, task: &mut QueueTaskDto, worker_id: &str) {
+        task.status        = "processing".into();
+        task.processing_at = Some(Utc::now());
+        task.worker_id     = Some(worker_id.to_string());
+        self.processing_tasks
+            .lock()
+            .await
+            .insert((task.run_id.clone(), task.state_name.clone()), task.clone());
+    }
 }
 
 #[async_trait]
@@ -51,14 +171,19 @@ impl MatchService for MemoryMatchService {
         let pending = self.pending_tasks.lock().await;
         let waiting = self.waiting_workers.lock().await;
 
-        pending
-            .iter()
-            .map(|(q, list)| MatchStats {
-                queue: q.clone(),
-                pending_tasks: list.len(),
-                waiting_workers: waiting.len(),
-            })
-            .collect()
+        // 按队列汇总：积压来自 pending，等待数来自各 worker 轮询的队列
+        let mut stats: BTreeMap<&str, MatchStats> = BTreeMap::new();
+        for (q, list) in pending.iter() {
+            stats.entry(q).or_insert_with(|| MatchStats { queue: q.clone(), ..Default::default() })
+                .pending_tasks = list.len();
+        }
+        // 轮询被取消后遗留的挂起项（接收端已关闭）不计入
+        let live = waiting.values().filter(|(_, tx)| !tx.is_closed());
+        for q in live.flat_map(|(queues, _)| queues) {
+            stats.entry(q).or_insert_with(|| MatchStats { queue: q.clone(), ..Default::default() })
+                .waiting_workers += 1;
+        }
+        stats.into_values().collect()
     }
 
     // ───────── enqueue ───────
@@ -68,57 +193,55 @@ impl MatchService for MemoryMatchService {
         mut task: QueueTaskDto,
     ) -> Result<String, String> {
         let task_id = task.task_id.clone();
+        task.task_queue = queue.to_string();
 
-        // 有 worker 等待就立即派发
-        if let Some((_, waiter)) = self.waiting_workers.lock().await.drain().next() {
-            task.status        = "processing".into();
-            task.processing_at = Some(Utc::now());
-            let _ = waiter.send(task);
-            return Ok(task_id);
-        }
-
-        // 否则放进 pending
+        // 先进 pending，再唤醒一个轮询该队列的挂起 worker（如有）来挑选；
+        // 延迟重试 / 被限速的任务留在 pending，到点后再派发
         self.pending_tasks
             .lock()
             .await
             .entry(queue.to_string())
             .or_default()
             .push_back(task);
+        self.wake_waiter(queue).await;
         Ok(task_id)
     }
 
     // ───────── take_task ─────
-    async fn take_task(
+    async fn take_task_from(
         &self,
-        queue: &str,
+        queues: &[String],
         worker_id: &str,
         wait: Duration,
     ) -> Option<QueueTaskDto> {
-        // ① 现有 pending
-        if let Some(mut task) = self
-            .pending_tasks
-            .lock()
-            .await
-            .get_mut(queue)
-            .and_then(|q| q.pop_front())
-        {
-            task.status        = "processing".into();
-            task.processing_at = Some(Utc::now());
-            return Some(task);
-        }
+        let deadline = Instant::now() + wait;
+        let waiter_id = self.next_waiter.fetch_add(1, Ordering::Relaxed);
+        loop {
+            // 先挂起再查 pending：两步之间入队的任务也会唤醒本 worker
+            let (tx, rx) = oneshot::channel();
+            self.waiting_workers
+                .lock()
+                .await
+                .insert(waiter_id, (queues.to_vec(), tx));
 
-        // ② 没任务 → 长轮询
-        let (tx, rx) = oneshot::channel();
-        self.waiting_workers
-            .lock()
-            .await
-            .insert(worker_id.to_string(), tx);
+            if let Some(task) = self.dispatch_pending(queues, worker_id).await {
+                self.waiting_workers.lock().await.remove(&waiter_id);
+                return Some(task);
+            }
 
-        match timeout(wait, rx).await {
-            Ok(Ok(task)) => Some(task),
-            _ => {
-                self.waiting_workers.lock().await.remove(worker_id);
-                None
+            // 长轮询：等新任务入队，或等限速 / 重试任务到点后重新挑选
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let recheck = self
+                .next_ready_in(queues)
+                .await
+                .map_or(remaining, |d| d.min(remaining));
+            if let Ok(Ok(())) = timeout(recheck, rx).await {
+                // 被新任务唤醒：立即重新挑选（挂起项已被唤醒方取走）
+                continue;
+            }
+            self.waiting_workers.lock().await.remove(&waiter_id);
+            if Instant::now() >= deadline {
+                return None;
             }
         }
     }
@@ -130,6 +253,11 @@ impl MatchService for MemoryMatchService {
         state_name: &str,
         patch:      UpdateQueueTaskDto,
     ) -> Result<(), String> {
+        self.processing_tasks
+            .lock()
+            .await
+            .remove(&(run_id.to_string(), state_name.to_string()));
+
         // 只有 status = completed 且带 task_payload 时才缓存结果
         if matches!(patch.status.as_deref(), Some("completed")) {
             if let Some(Some(output)) = patch.task_payload {
@@ -142,6 +270,68 @@ impl MatchService for MemoryMatchService {
         Ok(())
     }
 
+    // ───────── heartbeat ─────
+    async fn heartbeat(&self, run_id: &str, state_name: &str) -> Result<(), String> {
+        let mut processing = self.processing_tasks.lock().await;
+        let task = processing
+            .get_mut(&(run_id.to_string(), state_name.to_string()))
+            .ok_or_else(|| format!("task {run_id}:{state_name} is not processing"))?;
+        task.last_heartbeat_at = Some(Utc::now());
+        Ok(())
+    }
+
+    // ───────── reap ──────────
+    async fn reap_expired_tasks(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredTask>, String> {
+        let mut processing = self.processing_tasks.lock().await;
+        let expired: Vec<ExpiredTask> = processing
+            .values()
+            .filter_map(|t| ExpiredTask::check(t, now))
+            .collect();
+        for e in &expired {
+            processing.remove(&(e.task.run_id.clone(), e.task.state_name.clone()));
+        }
+        Ok(expired)
+    }
+
+    // ───────── cancel ────────
+    async fn cancel_run_tasks(&self, run_id: &str) -> Result<u64, String> {
+        let mut cancelled = 0;
+        for queue in self.pending_tasks.lock().await.values_mut() {
+            let before = queue.len();
+            queue.retain(|t| t.run_id != run_id);
+            cancelled += before - queue.len();
+        }
+        let mut processing = self.processing_tasks.lock().await;
+        let before = processing.len();
+        processing.retain(|(run, _), _| run != run_id);
+        cancelled += before - processing.len();
+        Ok(cancelled as u64)
+    }
+
+    // ───────── requeue ───────
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String> {
+        let task = self
+            .processing_tasks
+            .lock()
+            .await
+            .remove(&(run_id.to_string(), state_name.to_string()))
+            .ok_or_else(|| format!("task {run_id}:{state_name} is not processing"))?;
+        Ok(self.return_to_pending(vec![task]).await.remove(0))
+    }
+
+    async fn requeue_worker_tasks(&self, worker_id: &str) -> Result<Vec<QueueTaskDto>, String> {
+        let requeued: Vec<QueueTaskDto> = {
+            let mut processing = self.processing_tasks.lock().await;
+            let keys: Vec<(String, String)> = processing
+                .iter()
+                .filter(|(_, t)| t.worker_id.as_deref() == Some(worker_id))
+                .map(|(k, _)| k.clone())
+                .collect();
+            keys.iter().filter_map(|k| processing.remove(k)).collect()
+        };
+        Ok(self.return_to_pending(requeued).await)
+    }
+
     // ───────── wait_for_completion ─
     async fn wait_for_completion(
         &self,
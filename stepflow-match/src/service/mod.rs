@@ -4,13 +4,17 @@ mod persistent;
 mod event;
 mod hybrid_with_queue;
 mod hybrid_with_event;
+mod pollers;
+mod dispatch;
 
-pub use self::interface::MatchService;
+pub use self::interface::{ExpiredTask, MatchService};
 pub use self::memory::MemoryMatchService;
 pub use self::persistent::PersistentMatchService;
 pub use self::event::EventDrivenMatchService;
 pub use self::hybrid_with_queue::HybridMatchService;
 pub use self::hybrid_with_event::HybridMatchServiceWithEvent;
+pub use self::dispatch::{DispatchPolicy, Dispatcher, DEFAULT_AGING_SECS};
+pub use self::pollers::{QueuePollers, DEFAULT_POLLER_TTL_SECS};
 
 // 重导出一些常用的类型，方便使用方直接从 match_service 模块导入
 pub use std::time::Duration;
@@ -1,11 +1,16 @@
 // src/service/persistent_match_service.rs
 //! 把基于数据库的 PersistentStore 适配成 MatchService
 
-use std::{any::Any, sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
+use tokio::{sync::Notify, time::timeout};
 
 use stepflow_storage::{
     db::DynPM,
@@ -14,7 +19,10 @@ use stepflow_storage::{
 
 use crate::{
     queue::{PersistentStore, TaskStore},
-    service::interface::MatchService, 
+    service::{
+        dispatch::Dispatcher,
+        interface::{ExpiredTask, MatchService},
+    },
 };
 
 use stepflow_dto::dto::{
@@ -22,15 +30,71 @@ use stepflow_dto::dto::{
     queue_task::{QueueTaskDto, UpdateQueueTaskDto},
 };
 
+/// 超时回收每次扫描的 processing 行数
+const PROCESSING_SCAN_LIMIT: i64 = 100;
+
+/// 每次挑选时每个根执行最多取几条候选任务、候选总数上限
+const CANDIDATES_PER_RUN: i64 = 4;
+const CANDIDATE_LIMIT: i64 = 200;
+
+/// 认领时被其他 worker 抢先的重试次数
+const CLAIM_ATTEMPTS: usize = 3;
+
+/// 长轮询期间没有收到本进程的入队通知时重新查询数据库的间隔：
+/// 兜底其他网关写入的任务与到点的延迟重试
+const DB_RECHECK: Duration = Duration::from_millis(500);
+
 /// 真正的服务对象
 pub struct PersistentMatchService {
     store:       Arc<PersistentStore>,
     persistence: DynPM,
+    /// 派发策略：优先级老化、公平轮转与队列限速
+    dispatcher:  Arc<Dispatcher>,
+    /// 本进程入队 / 退回任务时唤醒挂起中的长轮询
+    ready:       Notify,
 }
 
 impl PersistentMatchService {
     pub fn new(store: Arc<PersistentStore>, persistence: DynPM) -> Arc<Self> {
-        Arc::new(Self { store, persistence })
+        Self::with_dispatcher(store, persistence, Arc::new(Dispatcher::default()))
+    }
+
+    /// 使用给定的派发策略（混合模式下与内存层共用同一个 Dispatcher）
+    pub fn with_dispatcher(
+        store: Arc<PersistentStore>,
+        persistence: DynPM,
+        dispatcher: Arc<Dispatcher>,
+    ) -> Arc<Self> {
+        Arc::new(Self { store, persistence, dispatcher, ready: Notify::new() })
+    }
+
+    /// 取各根执行排在最前的已就绪任务，按派发策略挑一个后按 task_id 原子认领；
+    /// 多个网关共享同一数据库时不会重复派发，被抢先认领则重新挑选
+    async fn claim_next(&self, queues: &[String], worker_id: &str) -> Option<QueueTaskDto> {
+        for _ in 0..CLAIM_ATTEMPTS {
+            let now = Utc::now();
+            let candidates: Vec<QueueTaskDto> = self
+                .persistence
+                .find_dispatch_candidates(queues, now.naive_utc(), CANDIDATES_PER_RUN, CANDIDATE_LIMIT)
+                .await
+                .ok()?
+                .into_iter()
+                .map(Self::to_dto)
+                .collect();
+            let picked = self.dispatcher.pick(&candidates, now)?;
+
+            if let Some(row) = self
+                .persistence
+                .claim_queue_task_by_id(&candidates[picked].task_id, worker_id, now.naive_utc())
+                .await
+                .ok()?
+            {
+                let task = Self::to_dto(row);
+                self.dispatcher.record(&task, now);
+                return Some(task);
+            }
+        }
+        None
     }
 
     /// 把数据库行 → DTO
@@ -40,12 +104,18 @@ impl PersistentMatchService {
             run_id:          row.run_id,
             state_name:      row.state_name,
             resource:        row.resource,
+            task_queue:      row.task_queue,
+            root_run_id:     row.root_run_id,
+            template_id:     row.template_id,
+            worker_id:       row.worker_id,
             task_payload:    row.task_payload,
             status:          row.status,
             attempts:        row.attempts,
             max_attempts:    row.max_attempts,
             priority:        row.priority.map(|p| p as u8),
             timeout_seconds: row.timeout_seconds,
+            heartbeat_seconds: row.heartbeat_seconds,
+            last_heartbeat_at: row.last_heartbeat_at.map(|t| t.and_utc()),
             error_message:   row.error_message,
             last_error_at:   row.last_error_at.map(|t| t.and_utc()),
             next_retry_at:   row.next_retry_at.map(|t| t.and_utc()),
@@ -64,56 +134,64 @@ impl MatchService for PersistentMatchService {
 
     // ───────── stats ─────────
     async fn queue_stats(&self) -> Vec<MatchStats> {
-        // 简化实现：仅返回 0
-        vec![MatchStats {
-            queue: "default_task_queue".into(),
-            pending_tasks: 0,
-            waiting_workers: 0,
-        }]
+        // 数据库只记录积压；等待中的 worker 由内存层 / 网关的轮询登记补充
+        match self.persistence.count_pending_queue_tasks().await {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|(queue, pending)| MatchStats {
+                    queue,
+                    pending_tasks: pending as usize,
+                    ..Default::default()
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("count pending queue tasks failed: {e}");
+                Vec::new()
+            }
+        }
     }
 
     // ───────── enqueue ───────
-    async fn enqueue_task(&self, _queue: &str, task: QueueTaskDto) -> Result<String, String> {
-        self.store
-            .insert_task(
-                &task.run_id,
-                &task.state_name,
-                &task.resource,
-                &task.task_payload.clone().unwrap_or(Value::Null),
-            )
-            .await
+    async fn enqueue_task(&self, queue: &str, task: QueueTaskDto) -> Result<String, String> {
+        let task_id = self.store.insert_task(queue, &task).await?;
+        self.ready.notify_waiters();
+        Ok(task_id)
     }
 
     // ───────── take_task ─────
-    async fn take_task(
+    async fn take_task_from(
         &self,
-        _queue: &str,
-        _worker_id: &str,
-        _timeout: Duration,
+        queues: &[String],
+        worker_id: &str,
+        wait: Duration,
     ) -> Option<QueueTaskDto> {
-        // 1. 取一条 pending
-        let task = self
-            .persistence
-            .find_queue_tasks_by_status("pending", 1, 0)
-            .await
-            .ok()?
-            .into_iter()
-            .next()?;
+        let deadline = Instant::now() + wait;
+        loop {
+            // 先登记唤醒再查数据库：两步之间本进程入队的任务也会唤醒本次轮询
+            let notified = self.ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
 
-        // 2. 标记为 processing（忽略错误）
-        let _ = self
-            .persistence
-            .update_queue_task(
-                &task.task_id,
-                &UpdateStoredQueueTask {
-                    status:        Some("processing".into()),
-                    processing_at: Some(Some(Utc::now().naive_utc())),
-                    ..Default::default()
-                },
-            )
-            .await;
+            if let Some(task) = self.claim_next(queues, worker_id).await {
+                return Some(task);
+            }
 
-        Some(Self::to_dto(task))
+            // 长轮询：等本进程入队的通知，或定期重新查询数据库
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            let _ = timeout(remaining.min(DB_RECHECK), notified).await;
+        }
+    }
+
+    async fn claim_task(&self, task_id: &str, worker_id: &str) -> Result<Option<QueueTaskDto>, String> {
+        let row = self
+            .persistence
+            .claim_queue_task_by_id(task_id, worker_id, Utc::now().naive_utc())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(row.map(Self::to_dto))
     }
 
     // ───────── finish_task ───
@@ -140,12 +218,15 @@ impl MatchService for PersistentMatchService {
             attempts:        patch.attempts,
             priority:        patch.priority.map(|v| v as i64),
             timeout_seconds: patch.timeout_seconds,
+            heartbeat_seconds: patch.heartbeat_seconds,
+            last_heartbeat_at: patch.last_heartbeat_at.map(|opt| opt.map(|d| d.naive_utc())),
             error_message:   patch.error_message,
             last_error_at:   patch.last_error_at.map(|opt| opt.map(|d| d.naive_utc())),
             next_retry_at:   patch.next_retry_at.map(|opt| opt.map(|d| d.naive_utc())),
             processing_at:   patch.processing_at.map(|opt| opt.map(|d| d.naive_utc())),
             completed_at:    patch.completed_at.map(|opt| opt.map(|d| d.naive_utc())),
             failed_at:       patch.failed_at.map(|opt| opt.map(|d| d.naive_utc())),
+            worker_id:       patch.worker_id,
         };
 
         // 3. 更新
@@ -155,6 +236,113 @@ impl MatchService for PersistentMatchService {
             .map_err(|e| e.to_string())
     }
 
+    // ───────── heartbeat ─────
+    async fn heartbeat(&self, run_id: &str, state_name: &str) -> Result<(), String> {
+        let task = self
+            .persistence
+            .find_queue_task_by_run_state(run_id, state_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|t| t.status == "processing")
+            .ok_or_else(|| format!("task {run_id}:{state_name} is not processing"))?;
+
+        self.persistence
+            .update_queue_task(
+                &task.task_id,
+                &UpdateStoredQueueTask {
+                    last_heartbeat_at: Some(Some(Utc::now().naive_utc())),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    // ───────── reap ──────────
+    async fn reap_expired_tasks(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredTask>, String> {
+        let rows = self
+            .persistence
+            .find_queue_tasks_by_status("processing", PROCESSING_SCAN_LIMIT, 0)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut expired = Vec::new();
+        for row in rows {
+            let task_id = row.task_id.clone();
+            let Some(e) = ExpiredTask::check(&Self::to_dto(row), now) else {
+                continue;
+            };
+            let patch = e.failed_patch(now);
+            self.persistence
+                .update_queue_task(
+                    &task_id,
+                    &UpdateStoredQueueTask {
+                        status:        patch.status,
+                        error_message: patch.error_message,
+                        last_error_at: Some(Some(now.naive_utc())),
+                        failed_at:     Some(Some(now.naive_utc())),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            expired.push(e);
+        }
+        Ok(expired)
+    }
+
+    // ───────── cancel ────────
+    async fn cancel_run_tasks(&self, run_id: &str) -> Result<u64, String> {
+        self.persistence
+            .cancel_queue_tasks_by_run(run_id, Utc::now().naive_utc())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    // ───────── requeue ───────
+    async fn requeue_task(&self, run_id: &str, state_name: &str) -> Result<QueueTaskDto, String> {
+        let task = self
+            .persistence
+            .find_queue_task_by_run_state(run_id, state_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|t| t.status == "processing")
+            .ok_or_else(|| format!("task {run_id}:{state_name} is not processing"))?;
+
+        let patch = UpdateStoredQueueTask {
+            status:            Some("pending".into()),
+            worker_id:         Some(None),
+            processing_at:     Some(None),
+            last_heartbeat_at: Some(None),
+            ..Default::default()
+        };
+        self.persistence
+            .update_queue_task(&task.task_id, &patch)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.ready.notify_waiters();
+
+        Ok(QueueTaskDto {
+            status:            "pending".into(),
+            worker_id:         None,
+            processing_at:     None,
+            last_heartbeat_at: None,
+            ..Self::to_dto(task)
+        })
+    }
+
+    async fn requeue_worker_tasks(&self, worker_id: &str) -> Result<Vec<QueueTaskDto>, String> {
+        let rows = self
+            .persistence
+            .requeue_worker_queue_tasks(worker_id, Utc::now().naive_utc())
+            .await
+            .map_err(|e| e.to_string())?;
+        if !rows.is_empty() {
+            self.ready.notify_waiters();
+        }
+        Ok(rows.into_iter().map(Self::to_dto).collect())
+    }
+
     // ───────── wait_for_completion ─
     async fn wait_for_completion(
         &self,
@@ -0,0 +1,80 @@
+//! service/pollers.rs
+//!  - 记录哪些 worker 在轮询哪些队列，供 `/v1/match/stats` 报告每个队列的 pollers
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use stepflow_dto::dto::match_stats::{MatchStats, QueuePoller};
+
+/// 超过该时长未再轮询的 worker 不再计入 pollers
+pub const DEFAULT_POLLER_TTL_SECS: i64 = 60;
+
+/// queue → worker_id → 最近一次轮询时间
+pub struct QueuePollers {
+    ttl:     Duration,
+    pollers: Mutex<HashMap<String, HashMap<String, DateTime<Utc>>>>,
+}
+
+impl Default for QueuePollers {
+    fn default() -> Self {
+        Self::new(Duration::seconds(DEFAULT_POLLER_TTL_SECS))
+    }
+}
+
+impl QueuePollers {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, pollers: Mutex::new(HashMap::new()) }
+    }
+
+    /// worker 在 `now` 轮询了这些队列
+    pub fn record(&self, worker_id: &str, queues: &[String], now: DateTime<Utc>) {
+        let mut pollers = self.pollers.lock().unwrap();
+        for queue in queues {
+            pollers
+                .entry(queue.clone())
+                .or_default()
+                .insert(worker_id.to_string(), now);
+        }
+    }
+
+    /// 某队列在 `now` 仍活跃的 pollers（按 worker_id 排序）
+    pub fn pollers(&self, queue: &str, now: DateTime<Utc>) -> Vec<QueuePoller> {
+        self.snapshot(now).remove(queue).unwrap_or_default()
+    }
+
+    /// 把活跃 pollers 合并进队列统计：只有 pollers 没有积压的队列也会出现；结果按队列名排序
+    pub fn merge_into(&self, stats: Vec<MatchStats>, now: DateTime<Utc>) -> Vec<MatchStats> {
+        let mut merged: BTreeMap<String, MatchStats> =
+            stats.into_iter().map(|s| (s.queue.clone(), s)).collect();
+        for (queue, pollers) in self.snapshot(now) {
+            merged
+                .entry(queue.clone())
+                .or_insert_with(|| MatchStats { queue, ..Default::default() })
+                .pollers = pollers;
+        }
+        merged.into_values().collect()
+    }
+
+    /// 清理过期记录后返回 queue → pollers
+    fn snapshot(&self, now: DateTime<Utc>) -> HashMap<String, Vec<QueuePoller>> {
+        let mut pollers = self.pollers.lock().unwrap();
+        pollers.retain(|_, workers| {
+            workers.retain(|_, last| now - *last < self.ttl);
+            !workers.is_empty()
+        });
+        pollers
+            .iter()
+            .map(|(queue, workers)| {
+                let mut list: Vec<QueuePoller> = workers
+                    .iter()
+                    .map(|(id, last)| QueuePoller { worker_id: id.clone(), last_poll_at: *last })
+                    .collect();
+                list.sort_by(|a, b| a.worker_id.cmp(&b.worker_id));
+                (queue.clone(), list)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,7 @@
+-- Add heartbeat tracking to queue_tasks table
+
+ALTER TABLE queue_tasks
+    ADD COLUMN heartbeat_seconds INTEGER;
+
+ALTER TABLE queue_tasks
+    ADD COLUMN last_heartbeat_at TIMESTAMP;
@@ -0,0 +1,4 @@
+-- Indexes backing FOR UPDATE SKIP LOCKED claiming of queue tasks and timers
+
+CREATE INDEX idx_queue_tasks_status_queued_at ON queue_tasks(status, queued_at);
+CREATE INDEX idx_timers_status_fire_at ON timers(status, fire_at);
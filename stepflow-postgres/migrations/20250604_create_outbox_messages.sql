@@ -0,0 +1,12 @@
+-- Outbox: messages written in the same transaction as an engine step, published after commit
+
+CREATE TABLE outbox_messages (
+    id BIGSERIAL PRIMARY KEY,
+    run_id VARCHAR(64) NOT NULL,
+    topic TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMP
+);
+
+CREATE INDEX idx_outbox_unpublished ON outbox_messages(published_at, created_at);
@@ -0,0 +1,3 @@
+-- Workflow-level timeout timers are not bound to a state
+
+ALTER TABLE timers ALTER COLUMN state_name DROP NOT NULL;
@@ -0,0 +1,32 @@
+-- Immutable template revisions, aliases, and the revision an execution was started with
+
+CREATE TABLE workflow_template_revisions (
+    template_id VARCHAR(64) NOT NULL,
+    revision BIGINT NOT NULL,
+    dsl_definition TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMP,
+    PRIMARY KEY (template_id, revision)
+);
+
+CREATE TABLE workflow_template_aliases (
+    template_id VARCHAR(64) NOT NULL,
+    alias VARCHAR(64) NOT NULL,
+    revision BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (template_id, alias)
+);
+
+ALTER TABLE workflow_executions
+    ADD COLUMN template_revision BIGINT;
+
+-- Existing templates: the current definition becomes their first published revision
+INSERT INTO workflow_template_revisions (template_id, revision, dsl_definition, status, created_at, published_at)
+SELECT template_id, version, dsl_definition, 'PUBLISHED', created_at, updated_at
+FROM workflow_templates;
+
+UPDATE workflow_executions e
+SET template_revision = t.version
+FROM workflow_templates t
+WHERE t.template_id = e.template_id;
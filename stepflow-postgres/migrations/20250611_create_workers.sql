@@ -0,0 +1,20 @@
+-- Worker registry: registered workers, their capacity and liveness; queue tasks record the worker holding them
+
+CREATE TABLE workers (
+    worker_id VARCHAR(128) PRIMARY KEY,
+    capabilities TEXT NOT NULL DEFAULT '[]',
+    task_queues TEXT NOT NULL DEFAULT '[]',
+    version VARCHAR(64),
+    concurrency BIGINT NOT NULL DEFAULT 1,
+    in_flight BIGINT NOT NULL DEFAULT 0,
+    status VARCHAR(16) NOT NULL DEFAULT 'active',
+    registered_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_workers_status_last_seen ON workers(status, last_seen_at);
+
+ALTER TABLE queue_tasks
+    ADD COLUMN worker_id TEXT;
+
+CREATE INDEX idx_queue_tasks_worker_status ON queue_tasks(worker_id, status);
@@ -1,3 +1,6 @@
-
 pub mod error;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod persistence;
+mod storage_manager;
+
+pub use storage_manager::PostgresStorageManager;
@@ -0,0 +1,148 @@
+use sqlx::{postgres::PgRow, Executor, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::activity_task::{StoredActivityTask, UpdateStoredActivityTask};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, get_opt_i64, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct ActivityTaskPersistence {
+    pool: PgPool,
+}
+
+impl ActivityTaskPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredActivityTask, sqlx::Error> {
+        Ok(StoredActivityTask {
+            task_token: row.try_get("task_token")?,
+            run_id: row.try_get("run_id")?,
+            shard_id: get_i64(row, "shard_id")?,
+            seq: get_i64(row, "seq")?,
+            activity_type: row.try_get("activity_type")?,
+            state_name: row.try_get("state_name")?,
+            input: get_json(row, "input")?,
+            result: get_json(row, "result")?,
+            status: row.try_get("status")?,
+            error: row.try_get("error")?,
+            error_details: row.try_get("error_details")?,
+            attempt: get_i64(row, "attempt")?,
+            max_attempts: get_i64(row, "max_attempts")?,
+            heartbeat_at: row.try_get("heartbeat_at")?,
+            scheduled_at: row.try_get("scheduled_at")?,
+            started_at: row.try_get("started_at")?,
+            completed_at: row.try_get("completed_at")?,
+            timeout_seconds: get_opt_i64(row, "timeout_seconds")?,
+            retry_policy: row.try_get("retry_policy")?,
+            version: get_i64(row, "version")?,
+        })
+    }
+
+    pub async fn create_task(&self, task: &StoredActivityTask) -> Result<(), StorageError> {
+        Self::insert(&self.pool, task).await
+    }
+
+    // 插入任务（可在事务内调用）
+    pub(crate) async fn insert<'e, E>(executor: E, task: &StoredActivityTask) -> Result<(), StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO activity_tasks (
+                task_token, run_id, shard_id, seq, activity_type, state_name,
+                input, result, status, error, error_details, attempt, max_attempts,
+                heartbeat_at, scheduled_at, started_at, completed_at,
+                timeout_seconds, retry_policy, version
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            "#,
+        )
+        .bind(&task.task_token)
+        .bind(&task.run_id)
+        .bind(task.shard_id)
+        .bind(task.seq)
+        .bind(&task.activity_type)
+        .bind(&task.state_name)
+        .bind(json_text(&task.input))
+        .bind(json_text(&task.result))
+        .bind(&task.status)
+        .bind(&task.error)
+        .bind(&task.error_details)
+        .bind(task.attempt)
+        .bind(task.max_attempts)
+        .bind(task.heartbeat_at)
+        .bind(task.scheduled_at)
+        .bind(task.started_at)
+        .bind(task.completed_at)
+        .bind(task.timeout_seconds)
+        .bind(&task.retry_policy)
+        .bind(task.version)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_task(&self, task_token: &str) -> Result<Option<StoredActivityTask>, StorageError> {
+        let row = sqlx::query("SELECT * FROM activity_tasks WHERE task_token = $1")
+            .bind(task_token)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_tasks_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredActivityTask>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM activity_tasks WHERE status = $1 ORDER BY scheduled_at DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_task(&self, task_token: &str, changes: &UpdateStoredActivityTask) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE activity_tasks SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(state_name);
+        set_field!(input = json_patch(&changes.input));
+        set_field!(result = json_patch(&changes.result));
+        set_field!(status);
+        set_field!(error);
+        set_field!(error_details);
+        set_field!(attempt);
+        set_field!(heartbeat_at);
+        set_field!(started_at);
+        set_field!(completed_at);
+        set_field!(version);
+
+        if !has_fields { return Ok(()); }
+
+        query.push(" WHERE task_token = ").push_bind(task_token);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn delete_task(&self, task_token: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM activity_tasks WHERE task_token = $1")
+            .bind(task_token)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
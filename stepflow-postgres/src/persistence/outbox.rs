@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use stepflow_storage::entities::{outbox::StoredOutboxMessage, step_commit::StepCommit};
+use stepflow_storage::error::StorageError;
+
+use crate::persistence::{
+    activity_task::ActivityTaskPersistence, timer::TimerPersistence, workflow_event::WorkflowEventPersistence,
+    workflow_execution::WorkflowExecutionPersistence, workflow_state::WorkflowStatePersistence,
+};
+use crate::utils::get_i64;
+
+#[derive(Clone)]
+pub struct OutboxPersistence {
+    pool: PgPool,
+}
+
+impl OutboxPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredOutboxMessage, sqlx::Error> {
+        let payload: String = row.try_get("payload")?;
+        Ok(StoredOutboxMessage {
+            id: get_i64(row, "id")?,
+            run_id: row.try_get("run_id")?,
+            topic: row.try_get("topic")?,
+            payload: serde_json::from_str(&payload).unwrap_or_default(),
+            created_at: row.try_get("created_at")?,
+            published_at: row.try_get("published_at")?,
+        })
+    }
+
+    /// 在一个事务中提交引擎步骤的全部写入；任一写入失败（含 execution 版本冲突）整体回滚
+    pub async fn commit_step(&self, commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        let mut tx = self.pool.begin().await?;
+
+        for event in &commit.events {
+            WorkflowEventPersistence::insert(&mut *tx, event).await?;
+        }
+
+        for (state_id, changes) in &commit.states {
+            WorkflowStatePersistence::insert_if_missing(&mut *tx, state_id, &commit.run_id).await?;
+            WorkflowStatePersistence::update_rows(&mut *tx, state_id, changes).await?;
+        }
+
+        for timer in &commit.timers {
+            TimerPersistence::insert(&mut *tx, timer).await?;
+        }
+
+        for task in &commit.activity_tasks {
+            ActivityTaskPersistence::insert(&mut *tx, task).await?;
+        }
+
+        let mut published = Vec::with_capacity(commit.outbox.len());
+        for msg in &commit.outbox {
+            let row = sqlx::query(
+                r#"
+                INSERT INTO outbox_messages (run_id, topic, payload, created_at, published_at)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id
+                "#,
+            )
+            .bind(&msg.run_id)
+            .bind(&msg.topic)
+            .bind(msg.payload.to_string())
+            .bind(msg.created_at)
+            .bind(msg.published_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            published.push(StoredOutboxMessage { id: get_i64(&row, "id")?, ..msg.clone() });
+        }
+
+        // execution 放在最后：CAS 未命中时区分记录不存在与版本冲突
+        if let Some(changes) = &commit.execution {
+            let affected = WorkflowExecutionPersistence::update_rows(&mut *tx, &commit.run_id, changes).await?;
+            if let (Some(expected), 0) = (changes.version, affected) {
+                let actual = WorkflowExecutionPersistence::current_version(&mut *tx, &commit.run_id)
+                    .await?
+                    .ok_or_else(|| StorageError::NotFound(format!("workflow_execution {}", commit.run_id)))?;
+                return Err(StorageError::OptimisticLockConflict {
+                    entity: "workflow_execution".into(),
+                    id: commit.run_id.clone(),
+                    expected_version: expected,
+                    actual_version: actual,
+                });
+            }
+        }
+
+        tx.commit().await?;
+        Ok(published)
+    }
+
+    pub async fn find_unpublished(
+        &self,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM outbox_messages WHERE published_at IS NULL AND created_at < $1 ORDER BY id ASC LIMIT $2",
+        )
+        .bind(created_before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn mark_published(&self, id: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        sqlx::query("UPDATE outbox_messages SET published_at = $1 WHERE id = $2")
+            .bind(published_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::queue_task::{StoredQueueTask, UpdateStoredQueueTask};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, get_opt_i64, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct QueueTaskPersistence {
+    pool: PgPool,
+}
+
+impl QueueTaskPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredQueueTask, sqlx::Error> {
+        Ok(StoredQueueTask {
+            task_id: row.try_get("task_id")?,
+            run_id: row.try_get("run_id")?,
+            state_name: row.try_get("state_name")?,
+            resource: row.try_get("resource")?,
+            task_queue: row.try_get("task_queue")?,
+            root_run_id: row.try_get("root_run_id")?,
+            template_id: row.try_get("template_id")?,
+            worker_id: row.try_get("worker_id")?,
+            task_payload: get_json(row, "task_payload")?,
+            status: row.try_get("status")?,
+            attempts: get_i64(row, "attempts")?,
+            max_attempts: get_i64(row, "max_attempts")?,
+            priority: get_opt_i64(row, "priority")?,
+            timeout_seconds: get_opt_i64(row, "timeout_seconds")?,
+            heartbeat_seconds: get_opt_i64(row, "heartbeat_seconds")?,
+            last_heartbeat_at: row.try_get("last_heartbeat_at")?,
+            error_message: row.try_get("error_message")?,
+            last_error_at: row.try_get("last_error_at")?,
+            next_retry_at: row.try_get("next_retry_at")?,
+            queued_at: row.try_get("queued_at")?,
+            processing_at: row.try_get("processing_at")?,
+            completed_at: row.try_get("completed_at")?,
+            failed_at: row.try_get("failed_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    pub async fn create_queue_task(&self, task: &StoredQueueTask) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO queue_tasks (
+                task_id, run_id, state_name, resource, task_queue, root_run_id, template_id, worker_id, task_payload, status,
+                attempts, max_attempts, priority, timeout_seconds,
+                heartbeat_seconds, last_heartbeat_at,
+                error_message, last_error_at, next_retry_at,
+                queued_at, processing_at, completed_at, failed_at,
+                created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25)
+            "#,
+        )
+        .bind(&task.task_id)
+        .bind(&task.run_id)
+        .bind(&task.state_name)
+        .bind(&task.resource)
+        .bind(&task.task_queue)
+        .bind(&task.root_run_id)
+        .bind(&task.template_id)
+        .bind(&task.worker_id)
+        .bind(json_text(&task.task_payload))
+        .bind(&task.status)
+        .bind(task.attempts)
+        .bind(task.max_attempts)
+        .bind(task.priority)
+        .bind(task.timeout_seconds)
+        .bind(task.heartbeat_seconds)
+        .bind(task.last_heartbeat_at)
+        .bind(&task.error_message)
+        .bind(task.last_error_at)
+        .bind(task.next_retry_at)
+        .bind(task.queued_at)
+        .bind(task.processing_at)
+        .bind(task.completed_at)
+        .bind(task.failed_at)
+        .bind(task.created_at)
+        .bind(task.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_queue_task(&self, task_id: &str) -> Result<Option<StoredQueueTask>, StorageError> {
+        let row = sqlx::query("SELECT * FROM queue_tasks WHERE task_id = $1")
+            .bind(task_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn update_queue_task(&self, task_id: &str, changes: &UpdateStoredQueueTask) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE queue_tasks SET ");
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    query.push(stringify!($field)).push(" = ").push_bind(val).push(", ");
+                }
+            };
+        }
+
+        set_field!(status);
+        set_field!(task_payload = json_patch(&changes.task_payload));
+        set_field!(attempts);
+        set_field!(priority);
+        set_field!(timeout_seconds);
+        set_field!(heartbeat_seconds);
+        set_field!(last_heartbeat_at);
+        set_field!(error_message);
+        set_field!(last_error_at);
+        set_field!(next_retry_at);
+        set_field!(processing_at);
+        set_field!(completed_at);
+        set_field!(failed_at);
+        set_field!(worker_id);
+
+        // 一定更新 updated_at
+        query.push("updated_at = ").push_bind(Utc::now().naive_utc());
+        query.push(" WHERE task_id = ").push_bind(task_id);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn delete_queue_task(&self, task_id: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM queue_tasks WHERE task_id = $1")
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_queue_tasks_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM queue_tasks WHERE status = $1 ORDER BY queued_at ASC LIMIT $2 OFFSET $3",
+        )
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn find_queue_tasks_to_retry(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM queue_tasks
+            WHERE status = 'retrying' AND next_retry_at <= $1
+            ORDER BY next_retry_at ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn find_queue_task_by_run_state(&self, run_id: &str, state_name: &str) -> Result<Option<StoredQueueTask>, StorageError> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM queue_tasks
+            WHERE run_id = $1 AND state_name = $2
+            ORDER BY queued_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(run_id)
+        .bind(state_name)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    // 原子认领最早的可执行任务；SKIP LOCKED 让多个网关共享同一数据库时互不阻塞、互不重复
+    pub async fn cancel_queue_tasks_by_run(&self, run_id: &str, now: NaiveDateTime) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE queue_tasks
+            SET status = 'cancelled', error_message = 'execution cancelled', failed_at = $1, updated_at = $1
+            WHERE run_id = $2 AND status IN ('pending', 'processing')
+            "#,
+        )
+        .bind(now)
+        .bind(run_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn claim_queue_task(&self, queues: &[String], worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        let row = sqlx::query(
+            r#"
+            UPDATE queue_tasks
+            SET status = 'processing', worker_id = $3, processing_at = $1, updated_at = $1
+            WHERE task_id = (
+                SELECT task_id FROM queue_tasks
+                WHERE status = 'pending' AND (next_retry_at IS NULL OR next_retry_at <= $1)
+                  AND task_queue = ANY($2)
+                ORDER BY queued_at ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .bind(queues)
+        .bind(worker_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_dispatch_candidates(
+        &self,
+        queues: &[String],
+        now: NaiveDateTime,
+        per_run: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM (
+                SELECT *, ROW_NUMBER() OVER (
+                    PARTITION BY COALESCE(NULLIF(root_run_id, ''), run_id)
+                    ORDER BY COALESCE(priority, 0) DESC, queued_at ASC
+                ) AS run_rank
+                FROM queue_tasks
+                WHERE status = 'pending' AND (next_retry_at IS NULL OR next_retry_at <= $1)
+                  AND task_queue = ANY($2)
+            ) candidates
+            WHERE run_rank <= $3
+            ORDER BY run_rank, COALESCE(priority, 0) DESC, queued_at ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(now)
+        .bind(queues)
+        .bind(per_run)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn claim_queue_task_by_id(&self, task_id: &str, worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        let row = sqlx::query(
+            r#"
+            UPDATE queue_tasks
+            SET status = 'processing', worker_id = $3, processing_at = $1, updated_at = $1
+            WHERE task_id = $2 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .bind(task_id)
+        .bind(worker_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    // worker 注销或失联：退回它仍在处理的任务，不消耗重试次数
+    pub async fn requeue_worker_queue_tasks(&self, worker_id: &str, now: NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE queue_tasks
+            SET status = 'pending', worker_id = NULL, processing_at = NULL, last_heartbeat_at = NULL, updated_at = $1
+            WHERE worker_id = $2 AND status = 'processing'
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .bind(worker_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT task_queue, COUNT(*) AS pending
+            FROM queue_tasks
+            WHERE status = 'pending'
+            GROUP BY task_queue
+            ORDER BY task_queue
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Ok((row.try_get("task_queue")?, get_i64(row, "pending")?)))
+            .collect::<Result<_, sqlx::Error>>()?)
+    }
+}
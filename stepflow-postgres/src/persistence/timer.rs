@@ -0,0 +1,175 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{postgres::PgRow, Executor, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::timer::{StoredTimer, UpdateStoredTimer};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct TimerPersistence {
+    pool: PgPool,
+}
+
+impl TimerPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredTimer, sqlx::Error> {
+        Ok(StoredTimer {
+            timer_id: row.try_get("timer_id")?,
+            run_id: row.try_get("run_id")?,
+            shard_id: get_i64(row, "shard_id")?,
+            fire_at: row.try_get("fire_at")?,
+            status: row.try_get("status")?,
+            version: get_i64(row, "version")?,
+            state_name: row.try_get("state_name")?,
+            payload: get_json(row, "payload")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    pub async fn create_timer(&self, timer: &StoredTimer) -> Result<(), StorageError> {
+        Self::insert(&self.pool, timer).await
+    }
+
+    // 插入定时器（可在事务内调用）
+    pub(crate) async fn insert<'e, E>(executor: E, timer: &StoredTimer) -> Result<(), StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO timers (
+                timer_id, run_id, shard_id, fire_at, status, version, state_name, payload, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(&timer.timer_id)
+        .bind(&timer.run_id)
+        .bind(timer.shard_id)
+        .bind(timer.fire_at)
+        .bind(&timer.status)
+        .bind(timer.version)
+        .bind(&timer.state_name)
+        .bind(json_text(&timer.payload))
+        .bind(timer.created_at)
+        .bind(timer.updated_at)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_timer(&self, timer_id: &str) -> Result<Option<StoredTimer>, StorageError> {
+        let row = sqlx::query("SELECT * FROM timers WHERE timer_id = $1")
+            .bind(timer_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn update_timer(&self, timer_id: &str, changes: &UpdateStoredTimer) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE timers SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(fire_at);
+        set_field!(status);
+        set_field!(version);
+        set_field!(payload = json_patch(&changes.payload));
+
+        if !has_fields { return Ok(()); }
+
+        query.push(" WHERE timer_id = ").push_bind(timer_id);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn delete_timer(&self, timer_id: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM timers WHERE timer_id = $1")
+            .bind(timer_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_timers_before(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM timers WHERE status = 'pending' AND fire_at <= $1 ORDER BY fire_at ASC LIMIT $2",
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn claim_timer(&self, timer_id: &str, expected_version: i64) -> Result<bool, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE timers
+            SET status = 'fired', version = version + 1, updated_at = $1
+            WHERE timer_id = $2 AND version = $3 AND status = 'pending'
+            "#,
+        )
+        .bind(Utc::now().naive_utc())
+        .bind(timer_id)
+        .bind(expected_version)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn cancel_timers_by_run(&self, run_id: &str) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE timers
+            SET status = 'cancelled', version = version + 1, updated_at = $1
+            WHERE run_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(Utc::now().naive_utc())
+        .bind(run_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // 批量认领到期定时器；SKIP LOCKED 让多个调度器并发扫描时互不阻塞、互不重复
+    pub async fn claim_due_timers(&self, before: NaiveDateTime, shards: &[i64], limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE timers
+            SET status = 'fired', version = version + 1, updated_at = $1
+            WHERE timer_id IN (
+                SELECT timer_id FROM timers
+                WHERE status = 'pending' AND fire_at <= $2
+                  AND (cardinality($3::BIGINT[]) = 0 OR shard_id = ANY($3))
+                ORDER BY fire_at ASC
+                LIMIT $4
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(Utc::now().naive_utc())
+        .bind(before)
+        .bind(shards)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+}
@@ -0,0 +1,135 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use stepflow_storage::entities::worker::StoredWorker;
+use stepflow_storage::error::StorageError;
+
+use crate::utils::get_i64;
+
+#[derive(Clone)]
+pub struct WorkerPersistence {
+    pool: PgPool,
+}
+
+impl WorkerPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorker, sqlx::Error> {
+        let capabilities: String = row.try_get("capabilities")?;
+        let task_queues: String = row.try_get("task_queues")?;
+        Ok(StoredWorker {
+            worker_id: row.try_get("worker_id")?,
+            capabilities: serde_json::from_str(&capabilities).unwrap_or_default(),
+            task_queues: serde_json::from_str(&task_queues).unwrap_or_default(),
+            version: row.try_get("version")?,
+            concurrency: get_i64(row, "concurrency")?,
+            in_flight: get_i64(row, "in_flight")?,
+            status: row.try_get("status")?,
+            registered_at: row.try_get("registered_at")?,
+            last_seen_at: row.try_get("last_seen_at")?,
+        })
+    }
+
+    // 已存在时刷新能力、版本与容量并重新置为 active，保留首次注册时间
+    pub async fn upsert_worker(&self, worker: &StoredWorker) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workers (
+                worker_id, capabilities, task_queues, version, concurrency,
+                in_flight, status, registered_at, last_seen_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (worker_id) DO UPDATE SET
+                capabilities = EXCLUDED.capabilities,
+                task_queues = EXCLUDED.task_queues,
+                version = EXCLUDED.version,
+                concurrency = EXCLUDED.concurrency,
+                in_flight = EXCLUDED.in_flight,
+                status = EXCLUDED.status,
+                last_seen_at = EXCLUDED.last_seen_at
+            "#,
+        )
+        .bind(&worker.worker_id)
+        .bind(serde_json::to_string(&worker.capabilities).unwrap_or_else(|_| "[]".into()))
+        .bind(serde_json::to_string(&worker.task_queues).unwrap_or_else(|_| "[]".into()))
+        .bind(&worker.version)
+        .bind(worker.concurrency)
+        .bind(worker.in_flight)
+        .bind(&worker.status)
+        .bind(worker.registered_at)
+        .bind(worker.last_seen_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_worker(&self, worker_id: &str) -> Result<Option<StoredWorker>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workers WHERE worker_id = $1")
+            .bind(worker_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM workers ORDER BY worker_id ASC")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn touch_worker(&self, worker_id: &str, in_flight: Option<i64>, now: NaiveDateTime) -> Result<bool, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE workers
+            SET last_seen_at = $1, in_flight = COALESCE($2, in_flight)
+            WHERE worker_id = $3 AND status <> 'offline'
+            "#,
+        )
+        .bind(now)
+        .bind(in_flight)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_worker_status(&self, worker_id: &str, status: &str) -> Result<bool, StorageError> {
+        let result = sqlx::query("UPDATE workers SET status = $1 WHERE worker_id = $2")
+            .bind(status)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_stale_workers(&self, seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM workers
+            WHERE status <> 'offline' AND last_seen_at < $1
+            ORDER BY last_seen_at ASC
+            "#,
+        )
+        .bind(seen_before)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn mark_worker_offline_if_stale(&self, worker_id: &str, seen_before: NaiveDateTime) -> Result<bool, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE workers
+            SET status = 'offline'
+            WHERE worker_id = $1 AND status <> 'offline' AND last_seen_at < $2
+            "#,
+        )
+        .bind(worker_id)
+        .bind(seen_before)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
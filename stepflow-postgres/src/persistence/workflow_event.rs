@@ -0,0 +1,151 @@
+use chrono::Utc;
+use sqlx::{postgres::PgRow, Executor, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::workflow_event::{StoredWorkflowEvent, UpdateStoredWorkflowEvent};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_opt_i64};
+
+#[derive(Clone)]
+pub struct WorkflowEventPersistence {
+    pool: PgPool,
+}
+
+impl WorkflowEventPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorkflowEvent, sqlx::Error> {
+        Ok(StoredWorkflowEvent {
+            id: get_i64(row, "id")?,
+            run_id: row.try_get("run_id")?,
+            shard_id: get_i64(row, "shard_id")?,
+            event_id: get_i64(row, "event_id")?,
+            event_type: row.try_get("event_type")?,
+            state_id: row.try_get("state_id")?,
+            state_type: row.try_get("state_type")?,
+            trace_id: row.try_get("trace_id")?,
+            parent_event_id: get_opt_i64(row, "parent_event_id")?,
+            context_version: get_opt_i64(row, "context_version")?,
+            attributes: row.try_get("attributes")?,
+            attr_version: get_i64(row, "attr_version")?,
+            timestamp: row.try_get("timestamp")?,
+            archived: row.try_get("archived")?,
+        })
+    }
+
+    // 返回自增主键
+    pub async fn create_event(&self, event: &StoredWorkflowEvent) -> Result<i64, StorageError> {
+        Self::insert(&self.pool, event).await
+    }
+
+    // 插入事件（可在事务内调用），返回自增 id
+    pub(crate) async fn insert<'e, E>(executor: E, event: &StoredWorkflowEvent) -> Result<i64, StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO workflow_events (
+                run_id, shard_id, event_id, event_type, state_id, state_type, trace_id,
+                parent_event_id, context_version, attributes, attr_version, timestamp, archived
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id
+            "#,
+        )
+        .bind(&event.run_id)
+        .bind(event.shard_id)
+        .bind(event.event_id)
+        .bind(&event.event_type)
+        .bind(&event.state_id)
+        .bind(&event.state_type)
+        .bind(&event.trace_id)
+        .bind(event.parent_event_id)
+        .bind(event.context_version)
+        .bind(&event.attributes)
+        .bind(event.attr_version)
+        .bind(event.timestamp)
+        .bind(event.archived)
+        .fetch_one(executor)
+        .await?;
+        Ok(get_i64(&row, "id")?)
+    }
+
+    pub async fn get_event(&self, id: i64) -> Result<Option<StoredWorkflowEvent>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_events WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_events_by_run_id(&self, run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowEvent>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM workflow_events WHERE run_id = $1 ORDER BY event_id ASC LIMIT $2 OFFSET $3",
+        )
+        .bind(run_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_event(&self, id: i64, changes: &UpdateStoredWorkflowEvent) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE workflow_events SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => {
+                if let Some(val) = changes.$field.clone() {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(event_type);
+        set_field!(state_id);
+        set_field!(state_type);
+        set_field!(trace_id);
+        set_field!(parent_event_id);
+        set_field!(context_version);
+        set_field!(attributes);
+        set_field!(attr_version);
+        set_field!(timestamp);
+        set_field!(archived);
+
+        if !has_fields { return Ok(()); }
+
+        query.push(" WHERE id = ").push_bind(id);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn archive_event(&self, id: i64) -> Result<(), StorageError> {
+        sqlx::query("UPDATE workflow_events SET archived = TRUE, timestamp = $1 WHERE id = $2")
+            .bind(Utc::now().naive_utc())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_event(&self, id: i64) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM workflow_events WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_events_by_run_id(&self, run_id: &str) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM workflow_events WHERE run_id = $1")
+            .bind(run_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,216 @@
+use sqlx::{postgres::PgRow, Executor, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::workflow_execution::{StoredWorkflowExecution, UpdateStoredWorkflowExecution};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, get_opt_i64, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct WorkflowExecutionPersistence {
+    pool: PgPool,
+}
+
+impl WorkflowExecutionPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorkflowExecution, sqlx::Error> {
+        Ok(StoredWorkflowExecution {
+            run_id: row.try_get("run_id")?,
+            workflow_id: row.try_get("workflow_id")?,
+            shard_id: get_i64(row, "shard_id")?,
+            template_id: row.try_get("template_id")?,
+            template_revision: get_opt_i64(row, "template_revision")?,
+            parent_run_id: row.try_get("parent_run_id")?,
+            mode: row.try_get("mode")?,
+            current_state_name: row.try_get("current_state_name")?,
+            status: row.try_get("status")?,
+            workflow_type: row.try_get("workflow_type")?,
+            input: get_json(row, "input")?,
+            input_version: get_i64(row, "input_version")?,
+            result: get_json(row, "result")?,
+            result_version: get_i64(row, "result_version")?,
+            start_time: row.try_get("start_time")?,
+            close_time: row.try_get("close_time")?,
+            current_event_id: get_i64(row, "current_event_id")?,
+            memo: row.try_get("memo")?,
+            search_attrs: get_json(row, "search_attrs")?,
+            context_snapshot: get_json(row, "context_snapshot")?,
+            version: get_i64(row, "version")?,
+        })
+    }
+
+    pub async fn create_execution(&self, exec: &StoredWorkflowExecution) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_executions (
+                run_id, workflow_id, shard_id, template_id, template_revision, parent_run_id, mode,
+                current_state_name, status, workflow_type, input, input_version,
+                result, result_version, start_time, close_time,
+                current_event_id, memo, search_attrs, context_snapshot, version
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+            "#,
+        )
+        .bind(&exec.run_id)
+        .bind(&exec.workflow_id)
+        .bind(exec.shard_id)
+        .bind(&exec.template_id)
+        .bind(exec.template_revision)
+        .bind(&exec.parent_run_id)
+        .bind(&exec.mode)
+        .bind(&exec.current_state_name)
+        .bind(&exec.status)
+        .bind(&exec.workflow_type)
+        .bind(json_text(&exec.input))
+        .bind(exec.input_version)
+        .bind(json_text(&exec.result))
+        .bind(exec.result_version)
+        .bind(exec.start_time)
+        .bind(exec.close_time)
+        .bind(exec.current_event_id)
+        .bind(&exec.memo)
+        .bind(json_text(&exec.search_attrs))
+        .bind(json_text(&exec.context_snapshot))
+        .bind(exec.version)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_execution(&self, run_id: &str) -> Result<Option<StoredWorkflowExecution>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_executions WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_executions(&self, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM workflow_executions ORDER BY start_time DESC LIMIT $1 OFFSET $2")
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn find_executions_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM workflow_executions WHERE status = $1 ORDER BY start_time DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn find_executions_by_parent(&self, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM workflow_executions WHERE parent_run_id = $1 ORDER BY start_time ASC, run_id ASC LIMIT $2 OFFSET $3",
+        )
+        .bind(parent_run_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_execution(
+        &self,
+        run_id: &str,
+        changes: &UpdateStoredWorkflowExecution,
+    ) -> Result<(), StorageError> {
+        let affected = Self::update_rows(&self.pool, run_id, changes).await?;
+
+        if let (Some(expected), 0) = (changes.version, affected) {
+            let current = self
+                .get_execution(run_id)
+                .await?
+                .ok_or_else(|| StorageError::NotFound(format!("workflow_execution {run_id}")))?;
+            return Err(StorageError::OptimisticLockConflict {
+                entity: "workflow_execution".into(),
+                id: run_id.to_string(),
+                expected_version: expected,
+                actual_version: current.version,
+            });
+        }
+        Ok(())
+    }
+
+    // 执行部分更新（可在事务内调用），返回受影响的行数（0 表示 CAS 未命中）
+    pub(crate) async fn update_rows<'e, E>(
+        executor: E,
+        run_id: &str,
+        changes: &UpdateStoredWorkflowExecution,
+    ) -> Result<u64, StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE workflow_executions SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(workflow_id);
+        set_field!(shard_id);
+        set_field!(template_id);
+        set_field!(mode);
+        set_field!(current_state_name);
+        set_field!(status);
+        set_field!(workflow_type);
+        set_field!(input = json_patch(&changes.input));
+        set_field!(input_version);
+        set_field!(result = json_patch(&changes.result));
+        set_field!(result_version);
+        set_field!(start_time);
+        set_field!(close_time);
+        set_field!(current_event_id);
+        set_field!(memo);
+        set_field!(search_attrs = json_patch(&changes.search_attrs));
+        set_field!(context_snapshot = json_patch(&changes.context_snapshot));
+
+        if !has_fields && changes.version.is_none() { return Ok(0); }
+
+        // 版本号 +1；指定 version 时作为 CAS 条件
+        if has_fields { query.push(", "); }
+        query.push("version = version + 1");
+        query.push(" WHERE run_id = ").push_bind(run_id);
+        if let Some(expected) = changes.version {
+            query.push(" AND version = ").push_bind(expected);
+        }
+        Ok(query.build().execute(executor).await?.rows_affected())
+    }
+
+    // 当前版本号（可在事务内调用）
+    pub(crate) async fn current_version<'e, E>(executor: E, run_id: &str) -> Result<Option<i64>, StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let row = sqlx::query("SELECT version FROM workflow_executions WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_optional(executor)
+            .await?;
+        Ok(row.as_ref().map(|r| get_i64(r, "version")).transpose()?)
+    }
+
+    pub async fn delete_execution(&self, run_id: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM workflow_executions WHERE run_id = $1")
+            .bind(run_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
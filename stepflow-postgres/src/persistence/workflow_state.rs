@@ -0,0 +1,187 @@
+use chrono::Utc;
+use sqlx::{postgres::PgRow, Executor, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct WorkflowStatePersistence {
+    pool: PgPool,
+}
+
+impl WorkflowStatePersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorkflowState, sqlx::Error> {
+        Ok(StoredWorkflowState {
+            state_id: row.try_get("state_id")?,
+            run_id: row.try_get("run_id")?,
+            shard_id: get_i64(row, "shard_id")?,
+            state_name: row.try_get("state_name")?,
+            state_type: row.try_get("state_type")?,
+            status: row.try_get("status")?,
+            input: get_json(row, "input")?,
+            output: get_json(row, "output")?,
+            error: row.try_get("error")?,
+            error_details: row.try_get("error_details")?,
+            started_at: row.try_get("started_at")?,
+            completed_at: row.try_get("completed_at")?,
+            attempts: get_i64(row, "attempts")?,
+            mapping_trace: get_json(row, "mapping_trace")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            version: get_i64(row, "version")?,
+        })
+    }
+
+    pub async fn create_state(&self, state: &StoredWorkflowState) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_states (
+                state_id, run_id, shard_id, state_name, state_type, status, input, output,
+                error, error_details, started_at, completed_at, attempts, mapping_trace, created_at, updated_at, version
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            "#,
+        )
+        .bind(&state.state_id)
+        .bind(&state.run_id)
+        .bind(state.shard_id)
+        .bind(&state.state_name)
+        .bind(&state.state_type)
+        .bind(&state.status)
+        .bind(json_text(&state.input))
+        .bind(json_text(&state.output))
+        .bind(&state.error)
+        .bind(&state.error_details)
+        .bind(state.started_at)
+        .bind(state.completed_at)
+        .bind(state.attempts)
+        .bind(json_text(&state.mapping_trace))
+        .bind(state.created_at)
+        .bind(state.updated_at)
+        .bind(state.version)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_state(&self, state_id: &str) -> Result<Option<StoredWorkflowState>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_states WHERE state_id = $1")
+            .bind(state_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_states_by_run_id(&self, run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowState>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM workflow_states WHERE run_id = $1 ORDER BY created_at ASC LIMIT $2 OFFSET $3",
+        )
+        .bind(run_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_state(&self, state_id: &str, changes: &UpdateStoredWorkflowState) -> Result<(), StorageError> {
+        let affected = Self::update_rows(&self.pool, state_id, changes).await?;
+
+        if let (Some(expected), 0) = (changes.version, affected) {
+            let current = self
+                .get_state(state_id)
+                .await?
+                .ok_or_else(|| StorageError::NotFound(format!("workflow_state {state_id}")))?;
+            return Err(StorageError::OptimisticLockConflict {
+                entity: "workflow_state".into(),
+                id: state_id.to_string(),
+                expected_version: expected,
+                actual_version: current.version,
+            });
+        }
+        Ok(())
+    }
+
+    // 状态记录不存在时插入空记录（可在事务内调用）
+    pub(crate) async fn insert_if_missing<'e, E>(executor: E, state_id: &str, run_id: &str) -> Result<(), StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let now = Utc::now().naive_utc();
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_states (
+                state_id, run_id, shard_id, state_name, state_type, status, attempts, created_at, updated_at, version
+            ) VALUES ($1, $2, 0, '', '', '', 0, $3, $3, 1)
+            ON CONFLICT (state_id) DO NOTHING
+            "#,
+        )
+        .bind(state_id)
+        .bind(run_id)
+        .bind(now)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // 执行部分更新（可在事务内调用），返回受影响的行数（0 表示 CAS 未命中）
+    pub(crate) async fn update_rows<'e, E>(
+        executor: E,
+        state_id: &str,
+        changes: &UpdateStoredWorkflowState,
+    ) -> Result<u64, StorageError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE workflow_states SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(state_name);
+        set_field!(state_type);
+        set_field!(status);
+        set_field!(input = json_patch(&changes.input));
+        set_field!(output = json_patch(&changes.output));
+        set_field!(error);
+        set_field!(error_details);
+        set_field!(started_at);
+        set_field!(completed_at);
+        set_field!(attempts);
+        set_field!(mapping_trace = json_patch(&changes.mapping_trace));
+
+        if !has_fields && changes.version.is_none() { return Ok(0); }
+
+        // 版本号 +1；指定 version 时作为 CAS 条件
+        if has_fields { query.push(", "); }
+        query.push("version = version + 1");
+        query.push(", updated_at = ").push_bind(Utc::now().naive_utc());
+        query.push(" WHERE state_id = ").push_bind(state_id);
+        if let Some(expected) = changes.version {
+            query.push(" AND version = ").push_bind(expected);
+        }
+        Ok(query.build().execute(executor).await?.rows_affected())
+    }
+
+    pub async fn delete_state(&self, state_id: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM workflow_states WHERE state_id = $1")
+            .bind(state_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::Utc;
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::get_i64;
+
+#[derive(Clone)]
+pub struct WorkflowTemplatePersistence {
+    pool: PgPool,
+}
+
+impl WorkflowTemplatePersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorkflowTemplate, sqlx::Error> {
+        Ok(StoredWorkflowTemplate {
+            template_id: row.try_get("template_id")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            dsl_definition: row.try_get("dsl_definition")?,
+            version: get_i64(row, "version")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    pub async fn create_template(&self, tpl: &StoredWorkflowTemplate) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_templates
+                (template_id, name, description, dsl_definition, version, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&tpl.template_id)
+        .bind(&tpl.name)
+        .bind(&tpl.description)
+        .bind(&tpl.dsl_definition)
+        .bind(tpl.version)
+        .bind(tpl.created_at)
+        .bind(tpl.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_template(&self, template_id: &str) -> Result<Option<StoredWorkflowTemplate>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_templates WHERE template_id = $1")
+            .bind(template_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_templates(&self, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowTemplate>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM workflow_templates ORDER BY created_at DESC LIMIT $1 OFFSET $2")
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_template(&self, template_id: &str, changes: &UpdateStoredWorkflowTemplate) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE workflow_templates SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => {
+                if let Some(val) = changes.$field.clone() {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(name);
+        set_field!(description);
+        set_field!(dsl_definition);
+        set_field!(version);
+
+        if !has_fields { return Ok(()); }
+
+        query.push(", updated_at = ").push_bind(Utc::now().naive_utc());
+        query.push(" WHERE template_id = ").push_bind(template_id);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// 删除模板及其全部修订版本与别名
+    pub async fn delete_template(&self, template_id: &str) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["workflow_template_aliases", "workflow_template_revisions", "workflow_templates"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE template_id = $1"))
+                .bind(template_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
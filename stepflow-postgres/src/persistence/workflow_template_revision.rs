@@ -0,0 +1,159 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use stepflow_storage::entities::workflow_template_revision::{StoredTemplateAlias, StoredTemplateRevision};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::get_i64;
+
+#[derive(Clone)]
+pub struct WorkflowTemplateRevisionPersistence {
+    pool: PgPool,
+}
+
+impl WorkflowTemplateRevisionPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredTemplateRevision, sqlx::Error> {
+        Ok(StoredTemplateRevision {
+            template_id: row.try_get("template_id")?,
+            revision: get_i64(row, "revision")?,
+            dsl_definition: row.try_get("dsl_definition")?,
+            status: row.try_get("status")?,
+            created_at: row.try_get("created_at")?,
+            published_at: row.try_get("published_at")?,
+        })
+    }
+
+    fn alias_to_entity(row: &PgRow) -> Result<StoredTemplateAlias, sqlx::Error> {
+        Ok(StoredTemplateAlias {
+            template_id: row.try_get("template_id")?,
+            alias: row.try_get("alias")?,
+            revision: get_i64(row, "revision")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    pub async fn create_revision(&self, rev: &StoredTemplateRevision) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_template_revisions
+                (template_id, revision, dsl_definition, status, created_at, published_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(&rev.template_id)
+        .bind(rev.revision)
+        .bind(&rev.dsl_definition)
+        .bind(&rev.status)
+        .bind(rev.created_at)
+        .bind(rev.published_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_revision(&self, template_id: &str, revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_template_revisions WHERE template_id = $1 AND revision = $2")
+            .bind(template_id)
+            .bind(revision)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_revisions(&self, template_id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM workflow_template_revisions WHERE template_id = $1 ORDER BY revision ASC")
+            .bind(template_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    /// 发布修订版本，并在同一事务中让模板行跟随最新发布的修订
+    pub async fn publish_revision(
+        &self,
+        template_id: &str,
+        revision: i64,
+        published_at: NaiveDateTime,
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            UPDATE workflow_template_revisions
+            SET status = 'PUBLISHED', published_at = COALESCE(published_at, $1)
+            WHERE template_id = $2 AND revision = $3
+            RETURNING dsl_definition
+            "#,
+        )
+        .bind(published_at)
+        .bind(template_id)
+        .bind(revision)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| StorageError::NotFound(format!("template {template_id} revision {revision}")))?;
+        let dsl_definition: String = row.try_get("dsl_definition")?;
+
+        sqlx::query(
+            r#"
+            UPDATE workflow_templates
+            SET dsl_definition = $1, version = $2, updated_at = NOW()
+            WHERE template_id = $3 AND version < $2
+            "#,
+        )
+        .bind(dsl_definition)
+        .bind(revision)
+        .bind(template_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_alias(&self, alias: &StoredTemplateAlias) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_template_aliases (template_id, alias, revision, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (template_id, alias) DO UPDATE SET
+                revision = EXCLUDED.revision,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(&alias.template_id)
+        .bind(&alias.alias)
+        .bind(alias.revision)
+        .bind(alias.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_alias(&self, template_id: &str, alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_template_aliases WHERE template_id = $1 AND alias = $2")
+            .bind(template_id)
+            .bind(alias)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::alias_to_entity).transpose()?)
+    }
+
+    pub async fn find_aliases(&self, template_id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM workflow_template_aliases WHERE template_id = $1 ORDER BY alias ASC")
+            .bind(template_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(Self::alias_to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn delete_alias(&self, template_id: &str, alias: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM workflow_template_aliases WHERE template_id = $1 AND alias = $2")
+            .bind(template_id)
+            .bind(alias)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use sqlx::{postgres::PgRow, PgPool, Postgres, QueryBuilder, Row};
+use stepflow_storage::entities::workflow_visibility::{StoredWorkflowVisibility, UpdateStoredWorkflowVisibility};
+use stepflow_storage::error::StorageError;
+
+use crate::utils::{get_i64, get_json, json_patch, json_text};
+
+#[derive(Clone)]
+pub struct WorkflowVisibilityPersistence {
+    pool: PgPool,
+}
+
+impl WorkflowVisibilityPersistence {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // row -> entity
+    fn to_entity(row: &PgRow) -> Result<StoredWorkflowVisibility, sqlx::Error> {
+        Ok(StoredWorkflowVisibility {
+            run_id: row.try_get("run_id")?,
+            workflow_id: row.try_get("workflow_id")?,
+            workflow_type: row.try_get("workflow_type")?,
+            start_time: row.try_get("start_time")?,
+            close_time: row.try_get("close_time")?,
+            status: row.try_get("status")?,
+            memo: row.try_get("memo")?,
+            search_attrs: get_json(row, "search_attrs")?,
+            version: get_i64(row, "version")?,
+        })
+    }
+
+    pub async fn create_visibility(&self, vis: &StoredWorkflowVisibility) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_visibility (
+                run_id, workflow_id, workflow_type, start_time, close_time,
+                status, memo, search_attrs, version
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&vis.run_id)
+        .bind(&vis.workflow_id)
+        .bind(&vis.workflow_type)
+        .bind(vis.start_time)
+        .bind(vis.close_time)
+        .bind(&vis.status)
+        .bind(&vis.memo)
+        .bind(json_text(&vis.search_attrs))
+        .bind(vis.version)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_visibility(&self, run_id: &str) -> Result<Option<StoredWorkflowVisibility>, StorageError> {
+        let row = sqlx::query("SELECT * FROM workflow_visibility WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(Self::to_entity).transpose()?)
+    }
+
+    pub async fn find_visibilities_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowVisibility>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM workflow_visibility WHERE status = $1 ORDER BY start_time DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::to_entity).collect::<Result<_, _>>()?)
+    }
+
+    pub async fn update_visibility(&self, run_id: &str, changes: &UpdateStoredWorkflowVisibility) -> Result<(), StorageError> {
+        let mut query = QueryBuilder::<Postgres>::new("UPDATE workflow_visibility SET ");
+        let mut has_fields = false;
+
+        macro_rules! set_field {
+            ($field:ident) => { set_field!($field = changes.$field.clone()) };
+            ($field:ident = $val:expr) => {
+                if let Some(val) = $val {
+                    if has_fields { query.push(", "); }
+                    query.push(stringify!($field)).push(" = ").push_bind(val);
+                    has_fields = true;
+                }
+            };
+        }
+
+        set_field!(workflow_id);
+        set_field!(workflow_type);
+        set_field!(start_time);
+        set_field!(close_time);
+        set_field!(status);
+        set_field!(memo);
+        set_field!(search_attrs = json_patch(&changes.search_attrs));
+        set_field!(version);
+
+        if !has_fields { return Ok(()); }
+
+        query.push(" WHERE run_id = ").push_bind(run_id);
+        query.build().execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn delete_visibility(&self, run_id: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM workflow_visibility WHERE run_id = $1")
+            .bind(run_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
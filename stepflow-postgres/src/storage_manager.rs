@@ -0,0 +1,451 @@
+use std::future::Future;
+
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres, Transaction};
+use stepflow_storage::{
+    error::StorageError,
+    transaction::TransactionManager,
+    entities::{
+        workflow_execution::{StoredWorkflowExecution, UpdateStoredWorkflowExecution},
+        workflow_event::{StoredWorkflowEvent, UpdateStoredWorkflowEvent},
+        activity_task::{StoredActivityTask, UpdateStoredActivityTask},
+        workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState},
+        timer::{StoredTimer, UpdateStoredTimer},
+        workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate},
+        workflow_template_revision::{StoredTemplateAlias, StoredTemplateRevision},
+        workflow_visibility::{StoredWorkflowVisibility, UpdateStoredWorkflowVisibility},
+        queue_task::{StoredQueueTask, UpdateStoredQueueTask},
+        outbox::StoredOutboxMessage,
+        worker::StoredWorker,
+        step_commit::StepCommit,
+    },
+};
+use crate::persistence::{
+    workflow_execution::WorkflowExecutionPersistence,
+    workflow_event::WorkflowEventPersistence,
+    activity_task::ActivityTaskPersistence,
+    workflow_state::WorkflowStatePersistence,
+    timer::TimerPersistence,
+    workflow_template::WorkflowTemplatePersistence,
+    workflow_template_revision::WorkflowTemplateRevisionPersistence,
+    workflow_visibility::WorkflowVisibilityPersistence,
+    queue_task::QueueTaskPersistence,
+    outbox::OutboxPersistence,
+    worker::WorkerPersistence,
+};
+
+/// 默认连接池大小
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+/// PostgreSQL 存储后端；多个网关可共享同一数据库，
+/// 队列任务 / 定时器的认领通过 `FOR UPDATE SKIP LOCKED` 保证互不重复
+pub struct PostgresStorageManager {
+    pool: PgPool,
+    workflow_execution: WorkflowExecutionPersistence,
+    workflow_event: WorkflowEventPersistence,
+    activity_task: ActivityTaskPersistence,
+    workflow_state: WorkflowStatePersistence,
+    timer: TimerPersistence,
+    workflow_template: WorkflowTemplatePersistence,
+    workflow_template_revision: WorkflowTemplateRevisionPersistence,
+    workflow_visibility: WorkflowVisibilityPersistence,
+    queue_task: QueueTaskPersistence,
+    outbox: OutboxPersistence,
+    worker: WorkerPersistence,
+}
+
+impl PostgresStorageManager {
+    /// 连接数据库并执行迁移
+    pub async fn connect(url: &str) -> Result<Self, StorageError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(DEFAULT_MAX_CONNECTIONS)
+            .connect(url)
+            .await
+            .map_err(|e| StorageError::ConnectionError(e.to_string()))?;
+        Self::new(pool).await
+    }
+
+    pub async fn new(pool: PgPool) -> Result<Self, StorageError> {
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .map_err(|e| StorageError::Database(e.into()))?;
+        tracing::info!("✅ PostgreSQL 迁移完成");
+
+        Ok(Self {
+            workflow_execution: WorkflowExecutionPersistence::new(pool.clone()),
+            workflow_event: WorkflowEventPersistence::new(pool.clone()),
+            activity_task: ActivityTaskPersistence::new(pool.clone()),
+            workflow_state: WorkflowStatePersistence::new(pool.clone()),
+            timer: TimerPersistence::new(pool.clone()),
+            workflow_template: WorkflowTemplatePersistence::new(pool.clone()),
+            workflow_template_revision: WorkflowTemplateRevisionPersistence::new(pool.clone()),
+            workflow_visibility: WorkflowVisibilityPersistence::new(pool.clone()),
+            queue_task: QueueTaskPersistence::new(pool.clone()),
+            outbox: OutboxPersistence::new(pool.clone()),
+            worker: WorkerPersistence::new(pool.clone()),
+            pool,
+        })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub async fn with_transaction<F, Fut, R>(&self, f: F) -> Result<R, StorageError>
+    where
+        F: for<'tx> FnOnce(&'tx mut Transaction<'_, Postgres>) -> Fut + Send,
+        Fut: Future<Output = Result<R, StorageError>> + Send,
+        R: Send,
+    {
+        let mut tx = self.begin_tx().await?;
+        match f(&mut tx).await {
+            Ok(val) => {
+                tx.commit().await.map_err(StorageError::from)?;
+                Ok(val)
+            }
+            Err(e) => {
+                let _ = tx.rollback().await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionManager for PostgresStorageManager {
+    type DB = Postgres;
+
+    async fn begin_tx(&self) -> Result<Transaction<'_, Self::DB>, StorageError> {
+        let tx = self.pool.begin().await.map_err(StorageError::from)?;
+        Ok(tx)
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::WorkflowStorage for PostgresStorageManager {
+    async fn create_execution(&self, exec: &StoredWorkflowExecution) -> Result<(), StorageError> {
+        self.workflow_execution.create_execution(exec).await
+    }
+
+    async fn get_execution(&self, run_id: &str) -> Result<Option<StoredWorkflowExecution>, StorageError> {
+        self.workflow_execution.get_execution(run_id).await
+    }
+
+    async fn find_executions(&self, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        self.workflow_execution.find_executions(limit, offset).await
+    }
+
+    async fn find_executions_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        self.workflow_execution.find_executions_by_status(status, limit, offset).await
+    }
+
+    async fn find_executions_by_parent(&self, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        self.workflow_execution.find_executions_by_parent(parent_run_id, limit, offset).await
+    }
+
+    async fn update_execution(&self, run_id: &str, changes: &UpdateStoredWorkflowExecution) -> Result<(), StorageError> {
+        self.workflow_execution.update_execution(run_id, changes).await
+    }
+
+    async fn delete_execution(&self, run_id: &str) -> Result<(), StorageError> {
+        self.workflow_execution.delete_execution(run_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::EventStorage for PostgresStorageManager {
+    async fn create_event(&self, event: &StoredWorkflowEvent) -> Result<i64, StorageError> {
+        self.workflow_event.create_event(event).await
+    }
+
+    async fn get_event(&self, id: i64) -> Result<Option<StoredWorkflowEvent>, StorageError> {
+        self.workflow_event.get_event(id).await
+    }
+
+    async fn find_events_by_run_id(&self, run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowEvent>, StorageError> {
+        self.workflow_event.find_events_by_run_id(run_id, limit, offset).await
+    }
+
+    async fn update_event(&self, id: i64, changes: &UpdateStoredWorkflowEvent) -> Result<(), StorageError> {
+        self.workflow_event.update_event(id, changes).await
+    }
+
+    async fn archive_event(&self, id: i64) -> Result<(), StorageError> {
+        self.workflow_event.archive_event(id).await
+    }
+
+    async fn delete_event(&self, id: i64) -> Result<(), StorageError> {
+        self.workflow_event.delete_event(id).await
+    }
+
+    async fn delete_events_by_run_id(&self, run_id: &str) -> Result<u64, StorageError> {
+        self.workflow_event.delete_events_by_run_id(run_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::ActivityStorage for PostgresStorageManager {
+    async fn create_task(&self, task: &StoredActivityTask) -> Result<(), StorageError> {
+        self.activity_task.create_task(task).await
+    }
+
+    async fn get_task(&self, task_token: &str) -> Result<Option<StoredActivityTask>, StorageError> {
+        self.activity_task.get_task(task_token).await
+    }
+
+    async fn find_tasks_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredActivityTask>, StorageError> {
+        self.activity_task.find_tasks_by_status(status, limit, offset).await
+    }
+
+    async fn update_task(&self, task_token: &str, changes: &UpdateStoredActivityTask) -> Result<(), StorageError> {
+        self.activity_task.update_task(task_token, changes).await
+    }
+
+    async fn delete_task(&self, task_token: &str) -> Result<(), StorageError> {
+        self.activity_task.delete_task(task_token).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::StateStorage for PostgresStorageManager {
+    async fn create_state(&self, state: &StoredWorkflowState) -> Result<(), StorageError> {
+        self.workflow_state.create_state(state).await
+    }
+
+    async fn get_state(&self, state_id: &str) -> Result<Option<StoredWorkflowState>, StorageError> {
+        self.workflow_state.get_state(state_id).await
+    }
+
+    async fn find_states_by_run_id(&self, run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowState>, StorageError> {
+        self.workflow_state.find_states_by_run_id(run_id, limit, offset).await
+    }
+
+    async fn update_state(&self, state_id: &str, changes: &UpdateStoredWorkflowState) -> Result<(), StorageError> {
+        self.workflow_state.update_state(state_id, changes).await
+    }
+
+    async fn delete_state(&self, state_id: &str) -> Result<(), StorageError> {
+        self.workflow_state.delete_state(state_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::TimerStorage for PostgresStorageManager {
+    async fn create_timer(&self, timer: &StoredTimer) -> Result<(), StorageError> {
+        self.timer.create_timer(timer).await
+    }
+
+    async fn get_timer(&self, timer_id: &str) -> Result<Option<StoredTimer>, StorageError> {
+        self.timer.get_timer(timer_id).await
+    }
+
+    async fn update_timer(&self, timer_id: &str, changes: &UpdateStoredTimer) -> Result<(), StorageError> {
+        self.timer.update_timer(timer_id, changes).await
+    }
+
+    async fn delete_timer(&self, timer_id: &str) -> Result<(), StorageError> {
+        self.timer.delete_timer(timer_id).await
+    }
+
+    async fn find_timers_before(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        self.timer.find_timers_before(before, limit).await
+    }
+
+    async fn claim_timer(&self, timer_id: &str, expected_version: i64) -> Result<bool, StorageError> {
+        self.timer.claim_timer(timer_id, expected_version).await
+    }
+
+    async fn claim_due_timers(&self, before: NaiveDateTime, shards: &[i64], limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        self.timer.claim_due_timers(before, shards, limit).await
+    }
+
+    async fn cancel_timers_by_run(&self, run_id: &str) -> Result<u64, StorageError> {
+        self.timer.cancel_timers_by_run(run_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::TemplateStorage for PostgresStorageManager {
+    async fn create_template(&self, tpl: &StoredWorkflowTemplate) -> Result<(), StorageError> {
+        self.workflow_template.create_template(tpl).await
+    }
+
+    async fn get_template(&self, template_id: &str) -> Result<Option<StoredWorkflowTemplate>, StorageError> {
+        self.workflow_template.get_template(template_id).await
+    }
+
+    async fn find_templates(&self, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowTemplate>, StorageError> {
+        self.workflow_template.find_templates(limit, offset).await
+    }
+
+    async fn update_template(&self, template_id: &str, changes: &UpdateStoredWorkflowTemplate) -> Result<(), StorageError> {
+        self.workflow_template.update_template(template_id, changes).await
+    }
+
+    async fn delete_template(&self, template_id: &str) -> Result<(), StorageError> {
+        self.workflow_template.delete_template(template_id).await
+    }
+
+    async fn create_template_revision(&self, rev: &StoredTemplateRevision) -> Result<(), StorageError> {
+        self.workflow_template_revision.create_revision(rev).await
+    }
+
+    async fn get_template_revision(&self, template_id: &str, revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError> {
+        self.workflow_template_revision.get_revision(template_id, revision).await
+    }
+
+    async fn find_template_revisions(&self, template_id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError> {
+        self.workflow_template_revision.find_revisions(template_id).await
+    }
+
+    async fn publish_template_revision(&self, template_id: &str, revision: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        self.workflow_template_revision.publish_revision(template_id, revision, published_at).await
+    }
+
+    async fn set_template_alias(&self, alias: &StoredTemplateAlias) -> Result<(), StorageError> {
+        self.workflow_template_revision.set_alias(alias).await
+    }
+
+    async fn get_template_alias(&self, template_id: &str, alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError> {
+        self.workflow_template_revision.get_alias(template_id, alias).await
+    }
+
+    async fn find_template_aliases(&self, template_id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError> {
+        self.workflow_template_revision.find_aliases(template_id).await
+    }
+
+    async fn delete_template_alias(&self, template_id: &str, alias: &str) -> Result<(), StorageError> {
+        self.workflow_template_revision.delete_alias(template_id, alias).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::VisibilityStorage for PostgresStorageManager {
+    async fn create_visibility(&self, vis: &StoredWorkflowVisibility) -> Result<(), StorageError> {
+        self.workflow_visibility.create_visibility(vis).await
+    }
+
+    async fn get_visibility(&self, run_id: &str) -> Result<Option<StoredWorkflowVisibility>, StorageError> {
+        self.workflow_visibility.get_visibility(run_id).await
+    }
+
+    async fn find_visibilities_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowVisibility>, StorageError> {
+        self.workflow_visibility.find_visibilities_by_status(status, limit, offset).await
+    }
+
+    async fn update_visibility(&self, run_id: &str, changes: &UpdateStoredWorkflowVisibility) -> Result<(), StorageError> {
+        self.workflow_visibility.update_visibility(run_id, changes).await
+    }
+
+    async fn delete_visibility(&self, run_id: &str) -> Result<(), StorageError> {
+        self.workflow_visibility.delete_visibility(run_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::QueueStorage for PostgresStorageManager {
+    async fn create_queue_task(&self, task: &StoredQueueTask) -> Result<(), StorageError> {
+        self.queue_task.create_queue_task(task).await
+    }
+
+    async fn get_queue_task(&self, task_id: &str) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.get_queue_task(task_id).await
+    }
+
+    async fn update_queue_task(&self, task_id: &str, changes: &UpdateStoredQueueTask) -> Result<(), StorageError> {
+        self.queue_task.update_queue_task(task_id, changes).await
+    }
+
+    async fn delete_queue_task(&self, task_id: &str) -> Result<(), StorageError> {
+        self.queue_task.delete_queue_task(task_id).await
+    }
+
+    async fn find_queue_tasks_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.find_queue_tasks_by_status(status, limit, offset).await
+    }
+
+    async fn find_queue_tasks_to_retry(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.find_queue_tasks_to_retry(before, limit).await
+    }
+
+    async fn find_queue_task_by_run_state(&self, run_id: &str, state_name: &str) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.find_queue_task_by_run_state(run_id, state_name).await
+    }
+
+    async fn claim_queue_task(&self, queues: &[String], worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.claim_queue_task(queues, worker_id, now).await
+    }
+
+    async fn find_dispatch_candidates(
+        &self,
+        queues: &[String],
+        now: NaiveDateTime,
+        per_run: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.find_dispatch_candidates(queues, now, per_run, limit).await
+    }
+
+    async fn claim_queue_task_by_id(&self, task_id: &str, worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.claim_queue_task_by_id(task_id, worker_id, now).await
+    }
+
+    async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError> {
+        self.queue_task.count_pending_queue_tasks().await
+    }
+
+    async fn cancel_queue_tasks_by_run(&self, run_id: &str, now: NaiveDateTime) -> Result<u64, StorageError> {
+        self.queue_task.cancel_queue_tasks_by_run(run_id, now).await
+    }
+
+    async fn requeue_worker_queue_tasks(&self, worker_id: &str, now: NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.requeue_worker_queue_tasks(worker_id, now).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::OutboxStorage for PostgresStorageManager {
+    async fn commit_step(&self, commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        self.outbox.commit_step(commit).await
+    }
+
+    async fn find_unpublished_outbox(&self, created_before: NaiveDateTime, limit: i64) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        self.outbox.find_unpublished(created_before, limit).await
+    }
+
+    async fn mark_outbox_published(&self, id: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        self.outbox.mark_published(id, published_at).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::WorkerStorage for PostgresStorageManager {
+    async fn upsert_worker(&self, worker: &StoredWorker) -> Result<(), StorageError> {
+        self.worker.upsert_worker(worker).await
+    }
+
+    async fn get_worker(&self, worker_id: &str) -> Result<Option<StoredWorker>, StorageError> {
+        self.worker.get_worker(worker_id).await
+    }
+
+    async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError> {
+        self.worker.list_workers().await
+    }
+
+    async fn touch_worker(&self, worker_id: &str, in_flight: Option<i64>, now: NaiveDateTime) -> Result<bool, StorageError> {
+        self.worker.touch_worker(worker_id, in_flight, now).await
+    }
+
+    async fn set_worker_status(&self, worker_id: &str, status: &str) -> Result<bool, StorageError> {
+        self.worker.set_worker_status(worker_id, status).await
+    }
+
+    async fn find_stale_workers(&self, seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError> {
+        self.worker.find_stale_workers(seen_before).await
+    }
+
+    async fn mark_worker_offline_if_stale(&self, worker_id: &str, seen_before: NaiveDateTime) -> Result<bool, StorageError> {
+        self.worker.mark_worker_offline_if_stale(worker_id, seen_before).await
+    }
+}
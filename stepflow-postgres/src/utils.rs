@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{postgres::PgRow, Row};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -12,4 +14,30 @@ impl<T> Default for Patch<T> {
     fn default() -> Self {
         Patch::Keep
     }
-}
\ No newline at end of file
+}
+
+// 迁移沿用 INTEGER（INT4）列，实体统一使用 i64；兼容 INT4 / INT8 两种列类型
+pub fn get_i64(row: &PgRow, col: &str) -> Result<i64, sqlx::Error> {
+    row.try_get::<i64, _>(col)
+        .or_else(|_| row.try_get::<i32, _>(col).map(i64::from))
+}
+
+pub fn get_opt_i64(row: &PgRow, col: &str) -> Result<Option<i64>, sqlx::Error> {
+    row.try_get::<Option<i64>, _>(col)
+        .or_else(|_| row.try_get::<Option<i32>, _>(col).map(|v| v.map(i64::from)))
+}
+
+// JSON 以 TEXT 存储（与 SQLite 一致），解析失败视为空
+pub fn get_json(row: &PgRow, col: &str) -> Result<Option<Value>, sqlx::Error> {
+    let text: Option<String> = row.try_get(col)?;
+    Ok(text.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+pub fn json_text(value: &Option<Value>) -> Option<String> {
+    value.as_ref().map(|v| v.to_string())
+}
+
+// 更新补丁中的 JSON 字段：Some(None) 表示置空
+pub fn json_patch(value: &Option<Option<Value>>) -> Option<Option<String>> {
+    value.as_ref().map(json_text)
+}
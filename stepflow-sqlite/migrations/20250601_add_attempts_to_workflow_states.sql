@@ -0,0 +1,4 @@
+-- Add retry attempt counter to workflow_states
+
+ALTER TABLE workflow_states
+    ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,12 @@
+-- Outbox: messages written in the same transaction as an engine step, published after commit
+
+CREATE TABLE outbox_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    published_at DATETIME
+);
+
+CREATE INDEX idx_outbox_unpublished ON outbox_messages(published_at, created_at);
@@ -0,0 +1,6 @@
+-- Link child executions (sub-workflows / branches) to their parent run
+
+ALTER TABLE workflow_executions
+    ADD COLUMN parent_run_id TEXT;
+
+CREATE INDEX idx_workflow_executions_parent ON workflow_executions (parent_run_id);
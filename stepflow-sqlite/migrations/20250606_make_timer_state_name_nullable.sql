@@ -0,0 +1,22 @@
+-- Workflow-level timeout timers are not bound to a state
+
+CREATE TABLE timers_new (
+    timer_id TEXT PRIMARY KEY,
+    run_id TEXT NOT NULL,
+    shard_id INTEGER NOT NULL,
+    fire_at DATETIME NOT NULL,
+    status TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    state_name TEXT,
+    payload TEXT,
+    created_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00',
+    updated_at DATETIME NOT NULL DEFAULT '1970-01-01 00:00:00'
+);
+
+INSERT INTO timers_new (timer_id, run_id, shard_id, fire_at, status, version, state_name, payload, created_at, updated_at)
+SELECT timer_id, run_id, shard_id, fire_at, status, version, state_name, payload, created_at, updated_at FROM timers;
+
+DROP TABLE timers;
+ALTER TABLE timers_new RENAME TO timers;
+
+CREATE INDEX idx_timers_run ON timers(run_id, fire_at);
@@ -0,0 +1,31 @@
+-- Immutable template revisions, aliases, and the revision an execution was started with
+
+CREATE TABLE workflow_template_revisions (
+    template_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    dsl_definition TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    published_at DATETIME,
+    PRIMARY KEY (template_id, revision)
+);
+
+CREATE TABLE workflow_template_aliases (
+    template_id TEXT NOT NULL,
+    alias TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    updated_at DATETIME NOT NULL,
+    PRIMARY KEY (template_id, alias)
+);
+
+ALTER TABLE workflow_executions
+    ADD COLUMN template_revision INTEGER;
+
+-- Existing templates: the current definition becomes their first published revision
+INSERT INTO workflow_template_revisions (template_id, revision, dsl_definition, status, created_at, published_at)
+SELECT template_id, version, dsl_definition, 'PUBLISHED', created_at, updated_at
+FROM workflow_templates;
+
+UPDATE workflow_executions
+SET template_revision = (SELECT version FROM workflow_templates t WHERE t.template_id = workflow_executions.template_id)
+WHERE template_id IS NOT NULL;
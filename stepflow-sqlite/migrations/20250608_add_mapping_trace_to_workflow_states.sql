@@ -0,0 +1,4 @@
+-- Add mapping debug trace (MappingDSL debug) to workflow_states
+
+ALTER TABLE workflow_states
+    ADD COLUMN mapping_trace TEXT;
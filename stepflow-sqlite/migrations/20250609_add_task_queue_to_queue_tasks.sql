@@ -0,0 +1,8 @@
+-- Route queue tasks by named task queue (Task state `taskQueue`, defaulting to its resource)
+
+ALTER TABLE queue_tasks
+    ADD COLUMN task_queue TEXT NOT NULL DEFAULT '';
+
+UPDATE queue_tasks SET task_queue = resource WHERE task_queue = '';
+
+CREATE INDEX idx_queue_tasks_queue_status ON queue_tasks(task_queue, status);
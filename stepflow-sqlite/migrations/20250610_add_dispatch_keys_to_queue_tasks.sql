@@ -0,0 +1,13 @@
+-- Fairness keys for task dispatch: the root execution (Map / Parallel branches share it) and the template
+
+ALTER TABLE queue_tasks
+    ADD COLUMN root_run_id TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE queue_tasks
+    ADD COLUMN template_id TEXT;
+
+UPDATE queue_tasks
+SET root_run_id = substr(run_id, 1, instr(run_id || '::', '::') - 1)
+WHERE root_run_id = '';
+
+CREATE INDEX idx_queue_tasks_root_run_id ON queue_tasks(root_run_id);
@@ -0,0 +1,20 @@
+-- Worker registry: registered workers, their capacity and liveness; queue tasks record the worker holding them
+
+CREATE TABLE workers (
+    worker_id TEXT PRIMARY KEY,
+    capabilities TEXT NOT NULL DEFAULT '[]',
+    task_queues TEXT NOT NULL DEFAULT '[]',
+    version TEXT,
+    concurrency INTEGER NOT NULL DEFAULT 1,
+    in_flight INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'active',
+    registered_at DATETIME NOT NULL,
+    last_seen_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_workers_status_last_seen ON workers(status, last_seen_at);
+
+ALTER TABLE queue_tasks
+    ADD COLUMN worker_id TEXT;
+
+CREATE INDEX idx_queue_tasks_worker_status ON queue_tasks(worker_id, status);
@@ -5,4 +5,7 @@ pub mod workflow_execution_crud;
 pub mod workflow_event_crud;
 pub mod workflow_state_crud;
 pub mod workflow_template_crud;
-pub mod workflow_visibility_crud; 
\ No newline at end of file
+pub mod workflow_template_revision_crud;
+pub mod workflow_visibility_crud;
+pub mod outbox_crud;
+pub mod worker_crud;
@@ -0,0 +1,69 @@
+use chrono::NaiveDateTime;
+use sqlx::{Executor, Result, Sqlite};
+use crate::models::outbox::OutboxMessage;
+
+// 写入一条待投递消息，返回自增 id
+pub async fn create_message<'e, E>(executor: E, msg: &OutboxMessage) -> Result<i64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO outbox_messages (run_id, topic, payload, created_at, published_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        msg.run_id,
+        msg.topic,
+        msg.payload,
+        msg.created_at,
+        msg.published_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+// 查询创建时间早于 before 的未投递消息（按 id 升序）
+pub async fn find_unpublished<'e, E>(
+    executor: E,
+    created_before: NaiveDateTime,
+    limit: i64,
+) -> Result<Vec<OutboxMessage>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        OutboxMessage,
+        r#"
+        SELECT id as "id!",
+               run_id as "run_id!",
+               topic as "topic!",
+               payload as "payload!",
+               created_at as "created_at!",
+               published_at
+        FROM outbox_messages
+        WHERE published_at IS NULL AND created_at < ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+        created_before,
+        limit
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 标记消息已投递
+pub async fn mark_published<'e, E>(executor: E, id: i64, published_at: NaiveDateTime) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!(
+        "UPDATE outbox_messages SET published_at = ? WHERE id = ?",
+        published_at,
+        id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 //! queue_task_crud.rs
 //! CRUD helpers for `queue_tasks` table (SQLite)
 
+use chrono::NaiveDateTime;
 use sqlx::{Executor, Result, Sqlite};
 use crate::models::queue_task::{QueueTask, UpdateQueueTask};
 use tracing::debug;
@@ -12,24 +13,30 @@ where
     sqlx::query!(
         r#"
         INSERT INTO queue_tasks (
-            task_id, run_id, state_name, resource, task_payload, status,
+            task_id, run_id, state_name, resource, task_queue, root_run_id, template_id, worker_id, task_payload, status,
             attempts, max_attempts, priority, timeout_seconds,
-            error_message, last_error_at, next_retry_at,
+            heartbeat_seconds, last_heartbeat_at, error_message, last_error_at, next_retry_at,
             queued_at, processing_at, completed_at, failed_at,
             created_at, updated_at
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         task.task_id,
         task.run_id,
         task.state_name,
         task.resource,
+        task.task_queue,
+        task.root_run_id,
+        task.template_id,
+        task.worker_id,
         task.task_payload,
         task.status,
         task.attempts,
         task.max_attempts,
         task.priority,
         task.timeout_seconds,
+        task.heartbeat_seconds,
+        task.last_heartbeat_at,
         task.error_message,
         task.last_error_at,
         task.next_retry_at,
@@ -58,12 +65,18 @@ where
             run_id               AS "run_id!",
             state_name           AS "state_name!",
             resource             AS "resource!",
+            task_queue           AS "task_queue!",
+            root_run_id          AS "root_run_id!",
+            template_id,
+            worker_id,
             task_payload,
             status               AS "status!",
             attempts             AS "attempts!",
             max_attempts         AS "max_attempts!",
             priority,
             timeout_seconds,
+            heartbeat_seconds,
+            last_heartbeat_at,
             error_message,
             last_error_at,
             next_retry_at,
@@ -100,12 +113,18 @@ where
             run_id               AS "run_id!",
             state_name           AS "state_name!",
             resource             AS "resource!",
+            task_queue           AS "task_queue!",
+            root_run_id          AS "root_run_id!",
+            template_id,
+            worker_id,
             task_payload,
             status               AS "status!",
             attempts             AS "attempts!",
             max_attempts         AS "max_attempts!",
             priority,
             timeout_seconds,
+            heartbeat_seconds,
+            last_heartbeat_at,
             error_message,
             last_error_at,
             next_retry_at,
@@ -154,6 +173,9 @@ where
     if changes.timeout_seconds.is_some() {
         sets.push("timeout_seconds = ?");
     }
+    if changes.heartbeat_seconds.is_some() {
+        sets.push("heartbeat_seconds = ?");
+    }
 
     // 双 Option 字段：None→NULL, Some(None)→SET NULL, Some(Some(v))→=v
     macro_rules! opt_opt {
@@ -168,12 +190,14 @@ where
         };
     }
     opt_opt!(task_payload);
+    opt_opt!(last_heartbeat_at);
     opt_opt!(error_message);
     opt_opt!(last_error_at);
     opt_opt!(next_retry_at);
     opt_opt!(processing_at);
     opt_opt!(completed_at);
     opt_opt!(failed_at);
+    opt_opt!(worker_id);
 
     // 如果一个更新字段都没有，就跳过
     if sets.is_empty() {
@@ -203,6 +227,9 @@ where
     if let Some(v) = &changes.timeout_seconds {
         q = q.bind(v);
     }
+    if let Some(v) = &changes.heartbeat_seconds {
+        q = q.bind(v);
+    }
     macro_rules! bind_opt_opt {
         ($field:ident) => {
             if let Some(ref opt) = changes.$field {
@@ -213,12 +240,14 @@ where
         };
     }
     bind_opt_opt!(task_payload);
+    bind_opt_opt!(last_heartbeat_at);
     bind_opt_opt!(error_message);
     bind_opt_opt!(last_error_at);
     bind_opt_opt!(next_retry_at);
     bind_opt_opt!(processing_at);
     bind_opt_opt!(completed_at);
     bind_opt_opt!(failed_at);
+    bind_opt_opt!(worker_id);
 
     // 最后 bind 上 WHERE 的 task_id
     q = q.bind(task_id);
@@ -239,7 +268,175 @@ where
     Ok(())
 }
 
-/// 6. find_task_by_run_state
+/// 6. claim_task
+///
+/// 在给定的队列中取最早入队、已到执行时间的 pending 任务并标记为由 `worker_id` 处理；
+/// SQLite 单写者，UPDATE ... RETURNING 本身即原子
+pub async fn claim_task<'e, E>(
+    executor: E,
+    queues: &[String],
+    worker_id: &str,
+    now: NaiveDateTime,
+) -> Result<Option<QueueTask>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let queue_list = serde_json::to_string(queues).unwrap_or_else(|_| "[]".into());
+    sqlx::query_as::<_, QueueTask>(
+        r#"
+        UPDATE queue_tasks
+        SET status = 'processing', worker_id = ?, processing_at = ?, updated_at = ?
+        WHERE task_id = (
+            SELECT task_id FROM queue_tasks
+            WHERE status = 'pending' AND (next_retry_at IS NULL OR next_retry_at <= ?)
+              AND task_queue IN (SELECT value FROM json_each(?))
+            ORDER BY queued_at ASC
+            LIMIT 1
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(worker_id)
+    .bind(now)
+    .bind(now)
+    .bind(now)
+    .bind(queue_list)
+    .fetch_optional(executor)
+    .await
+}
+
+/// 6a. find_dispatch_candidates
+///
+/// 给定队列中已到执行时间的 pending 任务，每个根执行最多取 `per_run` 条（优先级高、入队早的在前），
+/// 总数不超过 `limit`；由匹配服务按优先级老化与公平轮转从中挑选
+pub async fn find_dispatch_candidates<'e, E>(
+    executor: E,
+    queues: &[String],
+    now: NaiveDateTime,
+    per_run: i64,
+    limit: i64,
+) -> Result<Vec<QueueTask>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let queue_list = serde_json::to_string(queues).unwrap_or_else(|_| "[]".into());
+    sqlx::query_as::<_, QueueTask>(
+        r#"
+        SELECT * FROM (
+            SELECT *, ROW_NUMBER() OVER (
+                PARTITION BY COALESCE(NULLIF(root_run_id, ''), run_id)
+                ORDER BY COALESCE(priority, 0) DESC, queued_at ASC
+            ) AS run_rank
+            FROM queue_tasks
+            WHERE status = 'pending' AND (next_retry_at IS NULL OR next_retry_at <= ?)
+              AND task_queue IN (SELECT value FROM json_each(?))
+        )
+        WHERE run_rank <= ?
+        ORDER BY run_rank, COALESCE(priority, 0) DESC, queued_at ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(now)
+    .bind(queue_list)
+    .bind(per_run)
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+}
+
+/// 6b. claim_task_by_id
+///
+/// 任务仍为 pending 时标记为由 `worker_id` 处理并返回；已被其他 worker 认领时返回 None
+pub async fn claim_task_by_id<'e, E>(
+    executor: E,
+    task_id: &str,
+    worker_id: &str,
+    now: NaiveDateTime,
+) -> Result<Option<QueueTask>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, QueueTask>(
+        r#"
+        UPDATE queue_tasks
+        SET status = 'processing', worker_id = ?, processing_at = ?, updated_at = ?
+        WHERE task_id = ? AND status = 'pending'
+        RETURNING *
+        "#,
+    )
+    .bind(worker_id)
+    .bind(now)
+    .bind(now)
+    .bind(task_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// 6c. count_pending_by_queue
+///
+/// 按队列统计 pending 任务数（队列积压）
+pub async fn count_pending_by_queue<'e, E>(executor: E) -> Result<Vec<(String, i64)>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let rows = sqlx::query!(
+        r#"
+        SELECT task_queue AS "task_queue!", COUNT(*) AS "pending!: i64"
+        FROM queue_tasks
+        WHERE status = 'pending'
+        GROUP BY task_queue
+        ORDER BY task_queue
+        "#
+    )
+    .fetch_all(executor)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.task_queue, r.pending)).collect())
+}
+
+/// 6d. cancel_tasks_by_run
+///
+/// 执行被取消 / 终止时，把该执行尚未结束的任务（pending / processing）标记为 cancelled
+pub async fn cancel_tasks_by_run<'e, E>(executor: E, run_id: &str, now: NaiveDateTime) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        r#"
+        UPDATE queue_tasks
+        SET status = 'cancelled', error_message = 'execution cancelled', failed_at = ?, updated_at = ?
+        WHERE run_id = ? AND status IN ('pending', 'processing')
+        "#,
+        now,
+        now,
+        run_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// 6e. requeue_tasks_by_worker
+///
+/// worker 注销或失联时，把它仍在处理的任务退回 pending（不计重试次数）并返回
+pub async fn requeue_tasks_by_worker<'e, E>(executor: E, worker_id: &str, now: NaiveDateTime) -> Result<Vec<QueueTask>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, QueueTask>(
+        r#"
+        UPDATE queue_tasks
+        SET status = 'pending', worker_id = NULL, processing_at = NULL, last_heartbeat_at = NULL, updated_at = ?
+        WHERE worker_id = ? AND status = 'processing'
+        RETURNING *
+        "#,
+    )
+    .bind(now)
+    .bind(worker_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// 7. find_task_by_run_state
 ///
 /// 根据 `(run_id, state_name)` 查询**最新一条**队列任务；
 /// 若同一节点产生多条重试记录，取 `queued_at` 最近的一条。
@@ -259,12 +456,18 @@ where
             run_id               AS "run_id!",
             state_name           AS "state_name!",
             resource             AS "resource!",
+            task_queue           AS "task_queue!",
+            root_run_id          AS "root_run_id!",
+            template_id,
+            worker_id,
             task_payload,
             status               AS "status!",
             attempts             AS "attempts!",
             max_attempts         AS "max_attempts!",
             priority,
             timeout_seconds,
+            heartbeat_seconds,
+            last_heartbeat_at,
             error_message,
             last_error_at,
             next_retry_at,
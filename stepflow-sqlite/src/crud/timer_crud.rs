@@ -119,7 +119,7 @@ where
                created_at as "created_at!",
                updated_at as "updated_at!"
         FROM timers 
-        WHERE fire_at <= ? 
+        WHERE fire_at <= ? AND status = 'pending'
         ORDER BY fire_at ASC
         LIMIT ?
         "#,
@@ -128,4 +128,80 @@ where
     )
     .fetch_all(executor)
     .await
-}
\ No newline at end of file
+}
+// 批量认领到期定时器（pending -> fired），`shards` 为空表示全部分片。
+// SQLite 单写者，UPDATE ... RETURNING 本身即原子
+pub async fn claim_due_timers<'e, E>(
+    executor: E,
+    before: NaiveDateTime,
+    shards: &[i64],
+    limit: i64,
+) -> Result<Vec<Timer>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = chrono::Utc::now().naive_utc();
+    let shard_list = serde_json::to_string(shards).unwrap_or_else(|_| "[]".into());
+    sqlx::query_as::<_, Timer>(
+        r#"
+        UPDATE timers
+        SET status = 'fired', version = version + 1, updated_at = ?
+        WHERE timer_id IN (
+            SELECT timer_id FROM timers
+            WHERE status = 'pending' AND fire_at <= ?
+              AND (? = 0 OR shard_id IN (SELECT value FROM json_each(?)))
+            ORDER BY fire_at ASC
+            LIMIT ?
+        )
+        RETURNING timer_id, run_id, shard_id, fire_at, status, version,
+                  state_name, payload, created_at, updated_at
+        "#,
+    )
+    .bind(now)
+    .bind(before)
+    .bind(shards.len() as i64)
+    .bind(shard_list)
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+}
+
+// 按版本号原子认领到期定时器（pending -> fired），返回是否认领成功
+pub async fn claim_timer<'e, E>(executor: E, timer_id: &str, expected_version: i64) -> Result<bool>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = chrono::Utc::now().naive_utc();
+    let result = sqlx::query!(
+        r#"
+        UPDATE timers
+        SET status = 'fired', version = version + 1, updated_at = ?
+        WHERE timer_id = ? AND version = ? AND status = 'pending'
+        "#,
+        now,
+        timer_id,
+        expected_version
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn cancel_timers_by_run<'e, E>(executor: E, run_id: &str) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = chrono::Utc::now().naive_utc();
+    let result = sqlx::query!(
+        r#"
+        UPDATE timers
+        SET status = 'cancelled', version = version + 1, updated_at = ?
+        WHERE run_id = ? AND status = 'pending'
+        "#,
+        now,
+        run_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
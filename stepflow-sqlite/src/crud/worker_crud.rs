@@ -0,0 +1,180 @@
+use chrono::NaiveDateTime;
+use sqlx::{Executor, Result, Sqlite};
+use crate::models::worker::Worker;
+
+// 注册 worker；已存在时刷新能力、版本与容量并重新置为 active，保留首次注册时间
+pub async fn upsert_worker<'e, E>(executor: E, worker: &Worker) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO workers (
+            worker_id, capabilities, task_queues, version, concurrency,
+            in_flight, status, registered_at, last_seen_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(worker_id) DO UPDATE SET
+            capabilities = excluded.capabilities,
+            task_queues = excluded.task_queues,
+            version = excluded.version,
+            concurrency = excluded.concurrency,
+            in_flight = excluded.in_flight,
+            status = excluded.status,
+            last_seen_at = excluded.last_seen_at
+        "#,
+        worker.worker_id,
+        worker.capabilities,
+        worker.task_queues,
+        worker.version,
+        worker.concurrency,
+        worker.in_flight,
+        worker.status,
+        worker.registered_at,
+        worker.last_seen_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 按 id 查询
+pub async fn get_worker<'e, E>(executor: E, worker_id: &str) -> Result<Option<Worker>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        Worker,
+        r#"
+        SELECT worker_id as "worker_id!",
+               capabilities as "capabilities!",
+               task_queues as "task_queues!",
+               version,
+               concurrency as "concurrency!",
+               in_flight as "in_flight!",
+               status as "status!",
+               registered_at as "registered_at!",
+               last_seen_at as "last_seen_at!"
+        FROM workers
+        WHERE worker_id = ?
+        "#,
+        worker_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 全部 worker，按 id 排序
+pub async fn list_workers<'e, E>(executor: E) -> Result<Vec<Worker>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        Worker,
+        r#"
+        SELECT worker_id as "worker_id!",
+               capabilities as "capabilities!",
+               task_queues as "task_queues!",
+               version,
+               concurrency as "concurrency!",
+               in_flight as "in_flight!",
+               status as "status!",
+               registered_at as "registered_at!",
+               last_seen_at as "last_seen_at!"
+        FROM workers
+        ORDER BY worker_id ASC
+        "#
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 刷新存活时间（及执行中任务数）；未注册或已下线时不更新，返回受影响行数
+pub async fn touch_worker<'e, E>(
+    executor: E,
+    worker_id: &str,
+    in_flight: Option<i64>,
+    now: NaiveDateTime,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        r#"
+        UPDATE workers
+        SET last_seen_at = ?, in_flight = COALESCE(?, in_flight)
+        WHERE worker_id = ? AND status != 'offline'
+        "#,
+        now,
+        in_flight,
+        worker_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// 修改状态，返回受影响行数
+pub async fn set_worker_status<'e, E>(executor: E, worker_id: &str, status: &str) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        "UPDATE workers SET status = ? WHERE worker_id = ?",
+        status,
+        worker_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// 未下线但 seen_before 之后再无心跳的 worker
+pub async fn find_stale_workers<'e, E>(executor: E, seen_before: NaiveDateTime) -> Result<Vec<Worker>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        Worker,
+        r#"
+        SELECT worker_id as "worker_id!",
+               capabilities as "capabilities!",
+               task_queues as "task_queues!",
+               version,
+               concurrency as "concurrency!",
+               in_flight as "in_flight!",
+               status as "status!",
+               registered_at as "registered_at!",
+               last_seen_at as "last_seen_at!"
+        FROM workers
+        WHERE status != 'offline' AND last_seen_at < ?
+        ORDER BY last_seen_at ASC
+        "#,
+        seen_before
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 仍未下线且 seen_before 之后再无心跳时才标记为下线，返回受影响行数
+pub async fn mark_worker_offline_if_stale<'e, E>(
+    executor: E,
+    worker_id: &str,
+    seen_before: NaiveDateTime,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        r#"
+        UPDATE workers
+        SET status = 'offline'
+        WHERE worker_id = ? AND status != 'offline' AND last_seen_at < ?
+        "#,
+        worker_id,
+        seen_before
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
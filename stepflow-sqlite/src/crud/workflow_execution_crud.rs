@@ -9,16 +9,18 @@ where
     sqlx::query!(
         r#"
         INSERT INTO workflow_executions (
-            run_id, workflow_id, shard_id, template_id, mode,
+            run_id, workflow_id, shard_id, template_id, template_revision, parent_run_id, mode,
             current_state_name, status, workflow_type, input, input_version,
             result, result_version, start_time, close_time,
             current_event_id, memo, search_attrs, context_snapshot, version
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         exec.run_id,
         exec.workflow_id,
         exec.shard_id,
         exec.template_id,
+        exec.template_revision,
+        exec.parent_run_id,
         exec.mode,
         exec.current_state_name,
         exec.status,
@@ -50,7 +52,7 @@ where
         r#"
         SELECT 
             run_id as "run_id!", workflow_id, shard_id as "shard_id!", template_id,
-            mode as "mode!", current_state_name, status as "status!",
+            template_revision, parent_run_id, mode as "mode!", current_state_name, status as "status!",
             workflow_type as "workflow_type!", input, input_version as "input_version!",
             result, result_version as "result_version!", start_time as "start_time!",
             close_time, current_event_id as "current_event_id!", memo,
@@ -74,7 +76,7 @@ where
         r#"
         SELECT 
             run_id as "run_id!", workflow_id, shard_id as "shard_id!", template_id,
-            mode as "mode!", current_state_name, status as "status!",
+            template_revision, parent_run_id, mode as "mode!", current_state_name, status as "status!",
             workflow_type as "workflow_type!", input, input_version as "input_version!",
             result, result_version as "result_version!", start_time as "start_time!",
             close_time, current_event_id as "current_event_id!", memo,
@@ -99,7 +101,7 @@ where
         r#"
         SELECT 
             run_id as "run_id!", workflow_id, shard_id as "shard_id!", template_id,
-            mode as "mode!", current_state_name, status as "status!",
+            template_revision, parent_run_id, mode as "mode!", current_state_name, status as "status!",
             workflow_type as "workflow_type!", input, input_version as "input_version!",
             result, result_version as "result_version!", start_time as "start_time!",
             close_time, current_event_id as "current_event_id!", memo,
@@ -115,8 +117,36 @@ where
     .await
 }
 
+// 按父执行查询子执行
+pub async fn find_executions_by_parent<'e, E>(executor: E, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<WorkflowExecution>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        WorkflowExecution,
+        r#"
+        SELECT 
+            run_id as "run_id!", workflow_id, shard_id as "shard_id!", template_id,
+            template_revision, parent_run_id, mode as "mode!", current_state_name, status as "status!",
+            workflow_type as "workflow_type!", input, input_version as "input_version!",
+            result, result_version as "result_version!", start_time as "start_time!",
+            close_time, current_event_id as "current_event_id!", memo,
+            search_attrs, context_snapshot, version as "version!"
+        FROM workflow_executions
+        WHERE parent_run_id = ? ORDER BY start_time ASC, run_id ASC LIMIT ? OFFSET ?
+        "#,
+        parent_run_id,
+        limit,
+        offset
+    )
+    .fetch_all(executor)
+    .await
+}
+
 // 安全动态更新记录
-pub async fn update_execution<'e, E>(executor: E, run_id: &str, changes: &UpdateWorkflowExecution) -> Result<()>
+/// 更新记录并把版本号 +1；`changes.version` 为期望的当前版本（CAS 条件）。
+/// 返回受影响的行数（0 表示记录不存在或版本不匹配）
+pub async fn update_execution<'e, E>(executor: E, run_id: &str, changes: &UpdateWorkflowExecution) -> Result<u64>
 where
     E: Executor<'e, Database = Sqlite>,
 {
@@ -150,14 +180,17 @@ where
     set_field!(memo);
     set_field!(search_attrs);
     set_field!(context_snapshot);
-    set_field!(version);
 
-    if !has_fields { return Ok(()); }
+    if !has_fields && changes.version.is_none() { return Ok(0); }
 
+    if has_fields { query.push(", "); }
+    query.push("version = version + 1");
     query.push(" WHERE run_id = ").push_bind(run_id);
+    if let Some(expected) = changes.version {
+        query.push(" AND version = ").push_bind(expected);
+    }
 
-    query.build().execute(executor).await?;
-    Ok(())
+    Ok(query.build().execute(executor).await?.rows_affected())
 }
 
 // 删除记录
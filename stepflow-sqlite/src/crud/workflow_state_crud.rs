@@ -1,5 +1,5 @@
 use chrono::Utc;
-use sqlx::{Executor, Sqlite, Result, QueryBuilder};
+use sqlx::{Executor, Sqlite, SqliteConnection, Result, QueryBuilder};
 use crate::models::workflow_state::{WorkflowState, UpdateWorkflowState};
 
 // 创建新状态
@@ -11,12 +11,13 @@ where
         r#"
         INSERT INTO workflow_states (
             state_id, run_id, shard_id, state_name, state_type, status, input, output,
-            error, error_details, started_at, completed_at, created_at, updated_at, version
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            error, error_details, started_at, completed_at, attempts, mapping_trace, created_at, updated_at, version
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         state.state_id, state.run_id, state.shard_id, state.state_name, state.state_type,
         state.status, state.input, state.output, state.error, state.error_details,
-        state.started_at, state.completed_at, state.created_at, state.updated_at, state.version
+        state.started_at, state.completed_at, state.attempts, state.mapping_trace, state.created_at, state.updated_at,
+        state.version
     )
     .execute(executor)
     .await?;
@@ -40,6 +41,8 @@ where
             status as "status!",
             input, output, error, error_details,
             started_at, completed_at,
+            attempts as "attempts!",
+            mapping_trace,
             created_at as "created_at!",
             updated_at as "updated_at!",
             version as "version!"
@@ -68,6 +71,8 @@ where
             status as "status!",
             input, output, error, error_details,
             started_at, completed_at,
+            attempts as "attempts!",
+            mapping_trace,
             created_at as "created_at!",
             updated_at as "updated_at!",
             version as "version!"
@@ -80,12 +85,11 @@ where
 }
 
 // 部分更新状态（避免空更新）
-pub async fn update_state<'e, E>(executor: E, state_id: &str, changes: &UpdateWorkflowState) -> Result<()>
-where
-    E: Executor<'e, Database = Sqlite> + Clone,
-{
+/// 更新状态（不存在时先插入空记录）并把版本号 +1；`changes.version` 为期望的当前版本（CAS 条件）。
+/// 返回受影响的行数（0 表示版本不匹配）
+pub async fn update_state(conn: &mut SqliteConnection, state_id: &str, changes: &UpdateWorkflowState) -> Result<u64> {
     // 先获取当前状态
-    let current_state = get_state(executor.clone(), state_id).await?;
+    let current_state = get_state(&mut *conn, state_id).await?;
     
     // 如果状态不存在，创建一个新的
     if current_state.is_none() {
@@ -102,11 +106,13 @@ where
             error_details: None,
             started_at: None,
             completed_at: None,
+            attempts: 0,
+            mapping_trace: None,
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
             version: 1,
         };
-        create_state(executor.clone(), &new_state).await?;
+        create_state(&mut *conn, &new_state).await?;
     }
 
     // 构建更新查询
@@ -132,16 +138,43 @@ where
     set_field!(error_details);
     set_field!(started_at);
     set_field!(completed_at);
-    set_field!(version);
+    set_field!(attempts);
+    set_field!(mapping_trace);
 
-    if !has_fields {
-        return Ok(()); // 无需更新
+    if !has_fields && changes.version.is_none() {
+        return Ok(0); // 无需更新
     }
 
+    if has_fields { query.push(", "); }
+    query.push("version = version + 1");
     query.push(", updated_at = ").push_bind(Utc::now().naive_utc());
     query.push(" WHERE state_id = ").push_bind(state_id);
+    if let Some(expected) = changes.version {
+        query.push(" AND version = ").push_bind(expected);
+    }
 
-    query.build().execute(executor).await?;
+    Ok(query.build().execute(conn).await?.rows_affected())
+}
+
+// 状态记录不存在时插入空记录（已存在则忽略）
+pub async fn insert_state_if_missing<'e, E>(executor: E, state_id: &str, run_id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = Utc::now().naive_utc();
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO workflow_states (
+            state_id, run_id, shard_id, state_name, state_type, status, attempts, created_at, updated_at, version
+        ) VALUES (?, ?, 0, '', '', '', 0, ?, ?, 1)
+        "#,
+        state_id,
+        run_id,
+        now,
+        now
+    )
+    .execute(executor)
+    .await?;
     Ok(())
 }
 
@@ -130,4 +130,32 @@ where
         .execute(executor)
         .await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+// 模板行跟随最新发布的修订：仅当给定修订号比当前版本新时更新 DSL 与版本
+pub async fn advance_template<'e, E>(
+    executor: E,
+    template_id: &str,
+    revision: i64,
+    dsl_definition: &str,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = Utc::now().naive_utc();
+    sqlx::query!(
+        r#"
+        UPDATE workflow_templates
+        SET dsl_definition = ?, version = ?, updated_at = ?
+        WHERE template_id = ? AND version < ?
+        "#,
+        dsl_definition,
+        revision,
+        now,
+        template_id,
+        revision
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
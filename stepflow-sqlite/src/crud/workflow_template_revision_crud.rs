@@ -0,0 +1,199 @@
+use chrono::NaiveDateTime;
+use sqlx::{Executor, Result, Sqlite};
+use crate::models::workflow_template_revision::{WorkflowTemplateAlias, WorkflowTemplateRevision};
+
+// 追加修订版本（主键 template_id + revision 冲突时报错）
+pub async fn create_revision<'e, E>(executor: E, rev: &WorkflowTemplateRevision) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO workflow_template_revisions
+        (template_id, revision, dsl_definition, status, created_at, published_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+        rev.template_id,
+        rev.revision,
+        rev.dsl_definition,
+        rev.status,
+        rev.created_at,
+        rev.published_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 查询单个修订版本
+pub async fn get_revision<'e, E>(executor: E, template_id: &str, revision: i64) -> Result<Option<WorkflowTemplateRevision>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        WorkflowTemplateRevision,
+        r#"
+        SELECT template_id as "template_id!",
+               revision as "revision!",
+               dsl_definition as "dsl_definition!",
+               status as "status!",
+               created_at as "created_at!",
+               published_at
+        FROM workflow_template_revisions
+        WHERE template_id = ? AND revision = ?"#,
+        template_id,
+        revision
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 模板的全部修订版本（按修订号升序）
+pub async fn find_revisions<'e, E>(executor: E, template_id: &str) -> Result<Vec<WorkflowTemplateRevision>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        WorkflowTemplateRevision,
+        r#"
+        SELECT template_id as "template_id!",
+               revision as "revision!",
+               dsl_definition as "dsl_definition!",
+               status as "status!",
+               created_at as "created_at!",
+               published_at
+        FROM workflow_template_revisions
+        WHERE template_id = ?
+        ORDER BY revision ASC"#,
+        template_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 标记为已发布（已发布的保留原发布时间），返回受影响行数
+pub async fn publish_revision<'e, E>(
+    executor: E,
+    template_id: &str,
+    revision: i64,
+    published_at: NaiveDateTime,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query!(
+        r#"
+        UPDATE workflow_template_revisions
+        SET status = 'PUBLISHED', published_at = COALESCE(published_at, ?)
+        WHERE template_id = ? AND revision = ?
+        "#,
+        published_at,
+        template_id,
+        revision
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// 删除模板的全部修订版本
+pub async fn delete_revisions<'e, E>(executor: E, template_id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!("DELETE FROM workflow_template_revisions WHERE template_id = ?", template_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+// 创建或移动别名
+pub async fn upsert_alias<'e, E>(executor: E, alias: &WorkflowTemplateAlias) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!(
+        r#"
+        INSERT INTO workflow_template_aliases (template_id, alias, revision, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(template_id, alias) DO UPDATE SET
+            revision = excluded.revision,
+            updated_at = excluded.updated_at
+        "#,
+        alias.template_id,
+        alias.alias,
+        alias.revision,
+        alias.updated_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 查询别名
+pub async fn get_alias<'e, E>(executor: E, template_id: &str, alias: &str) -> Result<Option<WorkflowTemplateAlias>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        WorkflowTemplateAlias,
+        r#"
+        SELECT template_id as "template_id!",
+               alias as "alias!",
+               revision as "revision!",
+               updated_at as "updated_at!"
+        FROM workflow_template_aliases
+        WHERE template_id = ? AND alias = ?"#,
+        template_id,
+        alias
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+// 模板的全部别名
+pub async fn find_aliases<'e, E>(executor: E, template_id: &str) -> Result<Vec<WorkflowTemplateAlias>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as!(
+        WorkflowTemplateAlias,
+        r#"
+        SELECT template_id as "template_id!",
+               alias as "alias!",
+               revision as "revision!",
+               updated_at as "updated_at!"
+        FROM workflow_template_aliases
+        WHERE template_id = ?
+        ORDER BY alias ASC"#,
+        template_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+// 删除单个别名
+pub async fn delete_alias<'e, E>(executor: E, template_id: &str, alias: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!(
+        "DELETE FROM workflow_template_aliases WHERE template_id = ? AND alias = ?",
+        template_id,
+        alias
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+// 删除模板的全部别名
+pub async fn delete_aliases<'e, E>(executor: E, template_id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query!("DELETE FROM workflow_template_aliases WHERE template_id = ?", template_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
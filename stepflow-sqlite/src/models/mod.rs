@@ -5,7 +5,10 @@ pub mod workflow_execution;
 pub mod workflow_event;
 pub mod workflow_state;
 pub mod workflow_template;
+pub mod workflow_template_revision;
 pub mod workflow_visibility;
+pub mod outbox;
+pub mod worker;
 
 pub use activity_task::*;
 pub use queue_task::*;
@@ -14,4 +17,7 @@ pub use workflow_execution::*;
 pub use workflow_event::*;
 pub use workflow_state::*;
 pub use workflow_template::*;
-pub use workflow_visibility::*; 
\ No newline at end of file
+pub use workflow_template_revision::*;
+pub use workflow_visibility::*;
+pub use outbox::*;
+pub use worker::*; 
\ No newline at end of file
@@ -0,0 +1,12 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OutboxMessage {
+    pub id: i64,
+    pub run_id: String,
+    pub topic: String,
+    pub payload: String,
+    pub created_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+}
@@ -8,6 +8,10 @@ pub struct QueueTask {
     pub run_id: String,
     pub state_name: String,
     pub resource: String,
+    pub task_queue: String,
+    pub root_run_id: String,
+    pub template_id: Option<String>,
+    pub worker_id: Option<String>,
     pub task_payload: Option<String>,
 
     pub status: String,
@@ -16,6 +20,8 @@ pub struct QueueTask {
     pub max_attempts: i64,
     pub priority: Option<i64>,           
     pub timeout_seconds: Option<i64>,    
+    pub heartbeat_seconds: Option<i64>,
+    pub last_heartbeat_at: Option<NaiveDateTime>,
 
     pub error_message: Option<String>,
     pub last_error_at: Option<NaiveDateTime>,
@@ -37,6 +43,8 @@ pub struct UpdateQueueTask {
     pub attempts: Option<i64>,
     pub priority: Option<i64>,           
     pub timeout_seconds: Option<i64>,    
+    pub heartbeat_seconds: Option<i64>,
+    pub last_heartbeat_at: Option<Option<NaiveDateTime>>,
 
     pub error_message: Option<Option<String>>,
     pub last_error_at: Option<Option<NaiveDateTime>>,
@@ -44,5 +52,6 @@ pub struct UpdateQueueTask {
     pub processing_at: Option<Option<NaiveDateTime>>,
     pub completed_at: Option<Option<NaiveDateTime>>,
     pub failed_at: Option<Option<NaiveDateTime>>,
+    pub worker_id: Option<Option<String>>,
     pub updated_at: Option<NaiveDateTime>,
 }
\ No newline at end of file
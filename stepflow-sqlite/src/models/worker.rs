@@ -0,0 +1,15 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Worker {
+    pub worker_id: String,
+    pub capabilities: String,
+    pub task_queues: String,
+    pub version: Option<String>,
+    pub concurrency: i64,
+    pub in_flight: i64,
+    pub status: String,
+    pub registered_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
@@ -7,6 +7,8 @@ pub struct WorkflowExecution {
     pub workflow_id: Option<String>,
     pub shard_id: i64,
     pub template_id: Option<String>,
+    pub template_revision: Option<i64>,
+    pub parent_run_id: Option<String>,
     pub mode: String,
     pub current_state_name: Option<String>,
     pub status: String,
@@ -31,6 +33,8 @@ impl Default for WorkflowExecution {
             workflow_id: None,
             shard_id: 0,
             template_id: None,
+            template_revision: None,
+            parent_run_id: None,
             mode: String::new(),
             current_state_name: None,
             status: String::new(),
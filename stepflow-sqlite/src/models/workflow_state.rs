@@ -15,6 +15,8 @@ pub struct WorkflowState {
     pub error_details: Option<String>,
     pub started_at: Option<NaiveDateTime>,
     pub completed_at: Option<NaiveDateTime>,
+    pub attempts: i64,
+    pub mapping_trace: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub version: i64,
@@ -35,6 +37,8 @@ impl Default for WorkflowState {
             error_details: None,
             started_at: None,
             completed_at: None,
+            attempts: 0,
+            mapping_trace: None,
             created_at: chrono::NaiveDateTime::default(),
             updated_at: chrono::NaiveDateTime::default(),
             version: 0,
@@ -53,5 +57,7 @@ pub struct UpdateWorkflowState {
     pub error_details: Option<Option<String>>,
     pub started_at: Option<Option<NaiveDateTime>>,
     pub completed_at: Option<Option<NaiveDateTime>>,
+    pub attempts: Option<i64>,
+    pub mapping_trace: Option<Option<String>>,
     pub version: Option<i64>,
 }
@@ -0,0 +1,20 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkflowTemplateRevision {
+    pub template_id: String,
+    pub revision: i64,
+    pub dsl_definition: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkflowTemplateAlias {
+    pub template_id: String,
+    pub alias: String,
+    pub revision: i64,
+    pub updated_at: NaiveDateTime,
+}
@@ -43,7 +43,7 @@ impl ActivityTaskPersistence {
     }
 
     // entity -> models 转换
-    fn to_model(entity: &StoredActivityTask) -> ActivityTask {
+    pub(crate) fn to_model(entity: &StoredActivityTask) -> ActivityTask {
         ActivityTask {
             task_token: entity.task_token.clone(),
             run_id: entity.run_id.clone(),
@@ -4,5 +4,8 @@ pub mod activity_task;
 pub mod workflow_state;
 pub mod timer;
 pub mod workflow_template;
+pub mod workflow_template_revision;
 pub mod workflow_visibility;
 pub mod queue_task;
+pub mod outbox;
+pub mod worker;
@@ -0,0 +1,113 @@
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use crate::{
+    crud::{activity_task_crud, outbox_crud, timer_crud, workflow_event_crud, workflow_execution_crud, workflow_state_crud},
+    models::outbox::OutboxMessage,
+    persistence::{
+        activity_task::ActivityTaskPersistence, timer::TimerPersistence, workflow_event::WorkflowEventPersistence,
+        workflow_execution::WorkflowExecutionPersistence, workflow_state::WorkflowStatePersistence,
+    },
+};
+use stepflow_storage::entities::{outbox::StoredOutboxMessage, step_commit::StepCommit};
+use stepflow_storage::error::StorageError;
+
+#[derive(Clone)]
+pub struct OutboxPersistence {
+    pool: SqlitePool,
+}
+
+impl OutboxPersistence {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    // model -> entity
+    fn to_entity(model: OutboxMessage) -> StoredOutboxMessage {
+        StoredOutboxMessage {
+            id: model.id,
+            run_id: model.run_id,
+            topic: model.topic,
+            payload: serde_json::from_str(&model.payload).unwrap_or_default(),
+            created_at: model.created_at,
+            published_at: model.published_at,
+        }
+    }
+
+    // entity -> model
+    fn to_model(entity: &StoredOutboxMessage) -> OutboxMessage {
+        OutboxMessage {
+            id: entity.id,
+            run_id: entity.run_id.clone(),
+            topic: entity.topic.clone(),
+            payload: entity.payload.to_string(),
+            created_at: entity.created_at,
+            published_at: entity.published_at,
+        }
+    }
+
+    /// 在一个事务中提交引擎步骤的全部写入；任一写入失败（含 execution 版本冲突）整体回滚
+    pub async fn commit_step(&self, commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        let mut tx = self.pool.begin().await?;
+
+        for event in &commit.events {
+            let model = WorkflowEventPersistence::to_model(event);
+            workflow_event_crud::create_event(&mut *tx, &model).await?;
+        }
+
+        for (state_id, changes) in &commit.states {
+            workflow_state_crud::insert_state_if_missing(&mut *tx, state_id, &commit.run_id).await?;
+            let model_update = WorkflowStatePersistence::to_model_update(changes);
+            workflow_state_crud::update_state(&mut tx, state_id, &model_update).await?;
+        }
+
+        for timer in &commit.timers {
+            timer_crud::create_timer(&mut *tx, &TimerPersistence::to_model(timer)).await?;
+        }
+
+        for task in &commit.activity_tasks {
+            activity_task_crud::create_task(&mut *tx, &ActivityTaskPersistence::to_model(task)).await?;
+        }
+
+        let mut published = Vec::with_capacity(commit.outbox.len());
+        for msg in &commit.outbox {
+            let id = outbox_crud::create_message(&mut *tx, &Self::to_model(msg)).await?;
+            published.push(StoredOutboxMessage { id, ..msg.clone() });
+        }
+
+        // execution 放在最后：CAS 未命中时区分记录不存在与版本冲突
+        if let Some(changes) = &commit.execution {
+            let model_update = WorkflowExecutionPersistence::to_model_update(changes);
+            let affected =
+                workflow_execution_crud::update_execution(&mut *tx, &commit.run_id, &model_update).await?;
+            if let Some(expected) = changes.version
+                && affected == 0
+            {
+                let current = workflow_execution_crud::get_execution(&mut *tx, &commit.run_id)
+                    .await?
+                    .ok_or_else(|| StorageError::NotFound(format!("workflow_execution {}", commit.run_id)))?;
+                return Err(StorageError::OptimisticLockConflict {
+                    entity: "workflow_execution".into(),
+                    id: commit.run_id.clone(),
+                    expected_version: expected,
+                    actual_version: current.version,
+                });
+            }
+        }
+
+        tx.commit().await?;
+        Ok(published)
+    }
+
+    pub async fn find_unpublished(
+        &self,
+        created_before: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        let models = outbox_crud::find_unpublished(&self.pool, created_before, limit).await?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    pub async fn mark_published(&self, id: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        outbox_crud::mark_published(&self.pool, id, published_at).await.map_err(StorageError::from)
+    }
+}
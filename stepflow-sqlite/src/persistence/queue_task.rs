@@ -24,12 +24,18 @@ impl QueueTaskPersistence {
             run_id: model.run_id,
             state_name: model.state_name,
             resource: model.resource,
+            task_queue: model.task_queue,
+            root_run_id: model.root_run_id,
+            template_id: model.template_id,
+            worker_id: model.worker_id,
             task_payload: model.task_payload.and_then(|s| serde_json::from_str(&s).ok()),
             status: model.status,
             attempts: model.attempts,
             max_attempts: model.max_attempts,
             priority: model.priority,
             timeout_seconds: model.timeout_seconds,
+            heartbeat_seconds: model.heartbeat_seconds,
+            last_heartbeat_at: model.last_heartbeat_at,
             error_message: model.error_message,
             last_error_at: model.last_error_at,
             next_retry_at: model.next_retry_at,
@@ -49,12 +55,18 @@ impl QueueTaskPersistence {
             run_id: entity.run_id.clone(),
             state_name: entity.state_name.clone(),
             resource: entity.resource.clone(),
+            task_queue: entity.task_queue.clone(),
+            root_run_id: entity.root_run_id.clone(),
+            template_id: entity.template_id.clone(),
+            worker_id: entity.worker_id.clone(),
             task_payload: entity.task_payload.as_ref().map(|v| v.to_string()),
             status: entity.status.clone(),
             attempts: entity.attempts,
             max_attempts: entity.max_attempts,
             priority: entity.priority,
             timeout_seconds: entity.timeout_seconds,
+            heartbeat_seconds: entity.heartbeat_seconds,
+            last_heartbeat_at: entity.last_heartbeat_at,
             error_message: entity.error_message.clone(),
             last_error_at: entity.last_error_at,
             next_retry_at: entity.next_retry_at,
@@ -75,12 +87,15 @@ impl QueueTaskPersistence {
             attempts: entity.attempts,
             priority: entity.priority,
             timeout_seconds: entity.timeout_seconds,
+            heartbeat_seconds: entity.heartbeat_seconds,
+            last_heartbeat_at: entity.last_heartbeat_at,
             error_message: entity.error_message.clone(),
             last_error_at: entity.last_error_at.clone(),
             next_retry_at: entity.next_retry_at.clone(),
             processing_at: entity.processing_at.clone(),
             completed_at: entity.completed_at.clone(),
             failed_at: entity.failed_at.clone(),
+            worker_id: entity.worker_id.clone(),
             updated_at: None,
         }
     }
@@ -114,6 +129,34 @@ impl QueueTaskPersistence {
         Ok(model_opt.map(Self::to_entity))
     }
 
+    pub async fn claim_queue_task(&self, queues: &[String], worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        let model_opt = queue_task_crud::claim_task(&self.pool, queues, worker_id, now).await.map_err(StorageError::from)?;
+        Ok(model_opt.map(Self::to_entity))
+    }
+
+    pub async fn find_dispatch_candidates(&self, queues: &[String], now: NaiveDateTime, per_run: i64, limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let models = queue_task_crud::find_dispatch_candidates(&self.pool, queues, now, per_run, limit).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    pub async fn claim_queue_task_by_id(&self, task_id: &str, worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        let model_opt = queue_task_crud::claim_task_by_id(&self.pool, task_id, worker_id, now).await.map_err(StorageError::from)?;
+        Ok(model_opt.map(Self::to_entity))
+    }
+
+    pub async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError> {
+        queue_task_crud::count_pending_by_queue(&self.pool).await.map_err(StorageError::from)
+    }
+
+    pub async fn cancel_queue_tasks_by_run(&self, run_id: &str, now: NaiveDateTime) -> Result<u64, StorageError> {
+        queue_task_crud::cancel_tasks_by_run(&self.pool, run_id, now).await.map_err(StorageError::from)
+    }
+
+    pub async fn requeue_worker_queue_tasks(&self, worker_id: &str, now: NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError> {
+        let models = queue_task_crud::requeue_tasks_by_worker(&self.pool, worker_id, now).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
     pub async fn find_queue_tasks_to_retry(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> {
         let models = sqlx::query_as!(
             QueueTask,
@@ -122,12 +165,18 @@ impl QueueTaskPersistence {
                    run_id as "run_id!",
                    state_name as "state_name!",
                    resource as "resource!",
+                   task_queue as "task_queue!",
+                   root_run_id as "root_run_id!",
+                   template_id,
+                   worker_id,
                    task_payload,
                    status as "status!",
                    attempts as "attempts!",
                    max_attempts as "max_attempts!",
                    priority,
                    timeout_seconds,
+                   heartbeat_seconds,
+                   last_heartbeat_at,
                    error_message,
                    last_error_at,
                    next_retry_at,
@@ -35,7 +35,7 @@ impl TimerPersistence {
     }
 
     // entity -> model
-    fn to_model(entity: &StoredTimer) -> Timer {
+    pub(crate) fn to_model(entity: &StoredTimer) -> Timer {
         Timer {
             timer_id: entity.timer_id.clone(),
             run_id: entity.run_id.clone(),
@@ -84,4 +84,17 @@ impl TimerPersistence {
         let models = timer_crud::find_timers_before(&self.pool, before, limit).await.map_err(StorageError::from)?;
         Ok(models.into_iter().map(Self::to_entity).collect())
     }
+
+    pub async fn claim_timer(&self, timer_id: &str, expected_version: i64) -> Result<bool, StorageError> {
+        timer_crud::claim_timer(&self.pool, timer_id, expected_version).await.map_err(StorageError::from)
+    }
+
+    pub async fn claim_due_timers(&self, before: NaiveDateTime, shards: &[i64], limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        let models = timer_crud::claim_due_timers(&self.pool, before, shards, limit).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    pub async fn cancel_timers_by_run(&self, run_id: &str) -> Result<u64, StorageError> {
+        timer_crud::cancel_timers_by_run(&self.pool, run_id).await.map_err(StorageError::from)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use crate::{crud::worker_crud, models::worker::Worker};
+use stepflow_storage::entities::worker::StoredWorker;
+use stepflow_storage::error::StorageError;
+
+#[derive(Clone)]
+pub struct WorkerPersistence {
+    pool: SqlitePool,
+}
+
+impl WorkerPersistence {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    // model -> entity
+    fn to_entity(model: Worker) -> StoredWorker {
+        StoredWorker {
+            worker_id: model.worker_id,
+            capabilities: serde_json::from_str(&model.capabilities).unwrap_or_default(),
+            task_queues: serde_json::from_str(&model.task_queues).unwrap_or_default(),
+            version: model.version,
+            concurrency: model.concurrency,
+            in_flight: model.in_flight,
+            status: model.status,
+            registered_at: model.registered_at,
+            last_seen_at: model.last_seen_at,
+        }
+    }
+
+    // entity -> model
+    fn to_model(entity: &StoredWorker) -> Worker {
+        Worker {
+            worker_id: entity.worker_id.clone(),
+            capabilities: serde_json::to_string(&entity.capabilities).unwrap_or_else(|_| "[]".into()),
+            task_queues: serde_json::to_string(&entity.task_queues).unwrap_or_else(|_| "[]".into()),
+            version: entity.version.clone(),
+            concurrency: entity.concurrency,
+            in_flight: entity.in_flight,
+            status: entity.status.clone(),
+            registered_at: entity.registered_at,
+            last_seen_at: entity.last_seen_at,
+        }
+    }
+
+    pub async fn upsert_worker(&self, worker: &StoredWorker) -> Result<(), StorageError> {
+        worker_crud::upsert_worker(&self.pool, &Self::to_model(worker)).await.map_err(StorageError::from)
+    }
+
+    pub async fn get_worker(&self, worker_id: &str) -> Result<Option<StoredWorker>, StorageError> {
+        let model_opt = worker_crud::get_worker(&self.pool, worker_id).await.map_err(StorageError::from)?;
+        Ok(model_opt.map(Self::to_entity))
+    }
+
+    pub async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError> {
+        let models = worker_crud::list_workers(&self.pool).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    pub async fn touch_worker(&self, worker_id: &str, in_flight: Option<i64>, now: NaiveDateTime) -> Result<bool, StorageError> {
+        let affected = worker_crud::touch_worker(&self.pool, worker_id, in_flight, now).await.map_err(StorageError::from)?;
+        Ok(affected > 0)
+    }
+
+    pub async fn set_worker_status(&self, worker_id: &str, status: &str) -> Result<bool, StorageError> {
+        let affected = worker_crud::set_worker_status(&self.pool, worker_id, status).await.map_err(StorageError::from)?;
+        Ok(affected > 0)
+    }
+
+    pub async fn find_stale_workers(&self, seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError> {
+        let models = worker_crud::find_stale_workers(&self.pool, seen_before).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    pub async fn mark_worker_offline_if_stale(&self, worker_id: &str, seen_before: NaiveDateTime) -> Result<bool, StorageError> {
+        let affected = worker_crud::mark_worker_offline_if_stale(&self.pool, worker_id, seen_before).await.map_err(StorageError::from)?;
+        Ok(affected > 0)
+    }
+}
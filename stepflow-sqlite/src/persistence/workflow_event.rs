@@ -37,7 +37,7 @@ impl WorkflowEventPersistence {
     }
 
     // entity -> model
-    fn to_model(entity: &StoredWorkflowEvent) -> WorkflowEvent {
+    pub(crate) fn to_model(entity: &StoredWorkflowEvent) -> WorkflowEvent {
         WorkflowEvent {
             id: entity.id,
             run_id: entity.run_id.clone(),
@@ -23,6 +23,8 @@ impl WorkflowExecutionPersistence {
             workflow_id: model.workflow_id,
             shard_id: model.shard_id,
             template_id: model.template_id,
+            template_revision: model.template_revision,
+            parent_run_id: model.parent_run_id,
             mode: model.mode,
             current_state_name: model.current_state_name,
             status: model.status,
@@ -48,6 +50,8 @@ impl WorkflowExecutionPersistence {
             workflow_id: entity.workflow_id.clone(),
             shard_id: entity.shard_id,
             template_id: entity.template_id.clone(),
+            template_revision: entity.template_revision,
+            parent_run_id: entity.parent_run_id.clone(),
             mode: entity.mode.clone(),
             current_state_name: entity.current_state_name.clone(),
             status: entity.status.clone(),
@@ -67,7 +71,7 @@ impl WorkflowExecutionPersistence {
     }
 
     // entity update -> model update
-    fn to_model_update(entity: &UpdateStoredWorkflowExecution) -> UpdateWorkflowExecution {
+    pub(crate) fn to_model_update(entity: &UpdateStoredWorkflowExecution) -> UpdateWorkflowExecution {
         UpdateWorkflowExecution {
             workflow_id: entity.workflow_id.clone(),
             shard_id: entity.shard_id,
@@ -110,13 +114,37 @@ impl WorkflowExecutionPersistence {
         Ok(models.into_iter().map(Self::to_entity).collect())
     }
 
+    pub async fn find_executions_by_parent(&self, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        let models = workflow_execution_crud::find_executions_by_parent(&self.pool, parent_run_id, limit, offset).await.map_err(StorageError::from)?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
     pub async fn update_execution(
         &self,
         run_id: &str,
         changes: &UpdateStoredWorkflowExecution,
     ) -> Result<(), StorageError> {
         let model_update = Self::to_model_update(changes);
-        workflow_execution_crud::update_execution(&self.pool, run_id, &model_update).await.map_err(StorageError::from)
+        let affected = workflow_execution_crud::update_execution(&self.pool, run_id, &model_update)
+            .await
+            .map_err(StorageError::from)?;
+
+        // CAS 未命中：区分记录不存在与版本冲突
+        if let Some(expected) = changes.version
+            && affected == 0
+        {
+            let current = self
+                .get_execution(run_id)
+                .await?
+                .ok_or_else(|| StorageError::NotFound(format!("workflow_execution {run_id}")))?;
+            return Err(StorageError::OptimisticLockConflict {
+                entity: "workflow_execution".into(),
+                id: run_id.to_string(),
+                expected_version: expected,
+                actual_version: current.version,
+            });
+        }
+        Ok(())
     }
 
     pub async fn delete_execution(&self, run_id: &str) -> Result<(), StorageError> {
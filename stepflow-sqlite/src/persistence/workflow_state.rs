@@ -31,6 +31,8 @@ impl WorkflowStatePersistence {
             error_details: model.error_details,
             started_at: model.started_at,
             completed_at: model.completed_at,
+            attempts: model.attempts,
+            mapping_trace: model.mapping_trace.and_then(|s| serde_json::from_str(&s).ok()),
             created_at: model.created_at,
             updated_at: model.updated_at,
             version: model.version,
@@ -52,6 +54,8 @@ impl WorkflowStatePersistence {
             error_details: entity.error_details.clone(),
             started_at: entity.started_at,
             completed_at: entity.completed_at,
+            attempts: entity.attempts,
+            mapping_trace: entity.mapping_trace.as_ref().map(|v| v.to_string()),
             created_at: entity.created_at,
             updated_at: entity.updated_at,
             version: entity.version,
@@ -59,7 +63,7 @@ impl WorkflowStatePersistence {
     }
 
     // entity update -> model update
-    fn to_model_update(entity: &UpdateStoredWorkflowState) -> UpdateWorkflowState {
+    pub(crate) fn to_model_update(entity: &UpdateStoredWorkflowState) -> UpdateWorkflowState {
         UpdateWorkflowState {
             state_name: entity.state_name.clone(),
             state_type: entity.state_type.clone(),
@@ -70,6 +74,8 @@ impl WorkflowStatePersistence {
             error_details: entity.error_details.clone(),
             started_at: entity.started_at.clone(),
             completed_at: entity.completed_at.clone(),
+            attempts: entity.attempts,
+            mapping_trace: entity.mapping_trace.as_ref().map(|v| v.as_ref().map(|vv| vv.to_string())),
             version: entity.version,
         }
     }
@@ -100,7 +106,24 @@ impl WorkflowStatePersistence {
         changes: &UpdateStoredWorkflowState,
     ) -> Result<(), StorageError> {
         let model_update = Self::to_model_update(changes);
-        workflow_state_crud::update_state(&self.pool, state_id, &model_update).await.map_err(StorageError::from)
+        let mut conn = self.pool.acquire().await.map_err(StorageError::from)?;
+        let affected = workflow_state_crud::update_state(&mut conn, state_id, &model_update)
+            .await
+            .map_err(StorageError::from)?;
+
+        // CAS 未命中（记录不存在时 crud 会先插入，因此只可能是版本冲突）
+        if let Some(expected) = changes.version
+            && affected == 0
+        {
+            let actual = self.get_state(state_id).await?.map(|s| s.version).unwrap_or_default();
+            return Err(StorageError::OptimisticLockConflict {
+                entity: "workflow_state".into(),
+                id: state_id.to_string(),
+                expected_version: expected,
+                actual_version: actual,
+            });
+        }
+        Ok(())
     }
 
     pub async fn delete_state(&self, state_id: &str) -> Result<(), StorageError> {
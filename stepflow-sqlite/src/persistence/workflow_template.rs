@@ -1,6 +1,6 @@
 use sqlx::SqlitePool;
 use crate::{
-    crud::workflow_template_crud,
+    crud::{workflow_template_crud, workflow_template_revision_crud},
     models::workflow_template::{WorkflowTemplate, UpdateWorkflowTemplate},
 };
 use stepflow_storage::entities::workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate};
@@ -76,7 +76,13 @@ impl WorkflowTemplatePersistence {
         workflow_template_crud::update_template(&self.pool, template_id, &model_update).await.map_err(StorageError::from)
     }
 
+    /// 删除模板及其全部修订版本与别名
     pub async fn delete_template(&self, template_id: &str) -> Result<(), StorageError> {
-        workflow_template_crud::delete_template(&self.pool, template_id).await.map_err(StorageError::from)
+        let mut tx = self.pool.begin().await?;
+        workflow_template_revision_crud::delete_aliases(&mut *tx, template_id).await?;
+        workflow_template_revision_crud::delete_revisions(&mut *tx, template_id).await?;
+        workflow_template_crud::delete_template(&mut *tx, template_id).await?;
+        tx.commit().await?;
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use crate::{
+    crud::{workflow_template_crud, workflow_template_revision_crud},
+    models::workflow_template_revision::{WorkflowTemplateAlias, WorkflowTemplateRevision},
+};
+use stepflow_storage::entities::workflow_template_revision::{StoredTemplateAlias, StoredTemplateRevision};
+use stepflow_storage::error::StorageError;
+
+#[derive(Clone)]
+pub struct WorkflowTemplateRevisionPersistence {
+    pool: SqlitePool,
+}
+
+impl WorkflowTemplateRevisionPersistence {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    // model -> entity
+    fn to_entity(model: WorkflowTemplateRevision) -> StoredTemplateRevision {
+        StoredTemplateRevision {
+            template_id: model.template_id,
+            revision: model.revision,
+            dsl_definition: model.dsl_definition,
+            status: model.status,
+            created_at: model.created_at,
+            published_at: model.published_at,
+        }
+    }
+
+    // entity -> model
+    fn to_model(entity: &StoredTemplateRevision) -> WorkflowTemplateRevision {
+        WorkflowTemplateRevision {
+            template_id: entity.template_id.clone(),
+            revision: entity.revision,
+            dsl_definition: entity.dsl_definition.clone(),
+            status: entity.status.clone(),
+            created_at: entity.created_at,
+            published_at: entity.published_at,
+        }
+    }
+
+    fn alias_to_entity(model: WorkflowTemplateAlias) -> StoredTemplateAlias {
+        StoredTemplateAlias {
+            template_id: model.template_id,
+            alias: model.alias,
+            revision: model.revision,
+            updated_at: model.updated_at,
+        }
+    }
+
+    fn alias_to_model(entity: &StoredTemplateAlias) -> WorkflowTemplateAlias {
+        WorkflowTemplateAlias {
+            template_id: entity.template_id.clone(),
+            alias: entity.alias.clone(),
+            revision: entity.revision,
+            updated_at: entity.updated_at,
+        }
+    }
+
+    pub async fn create_revision(&self, rev: &StoredTemplateRevision) -> Result<(), StorageError> {
+        let model = Self::to_model(rev);
+        workflow_template_revision_crud::create_revision(&self.pool, &model).await.map_err(StorageError::from)
+    }
+
+    pub async fn get_revision(&self, template_id: &str, revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError> {
+        let model_opt = workflow_template_revision_crud::get_revision(&self.pool, template_id, revision).await?;
+        Ok(model_opt.map(Self::to_entity))
+    }
+
+    pub async fn find_revisions(&self, template_id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError> {
+        let models = workflow_template_revision_crud::find_revisions(&self.pool, template_id).await?;
+        Ok(models.into_iter().map(Self::to_entity).collect())
+    }
+
+    /// 发布修订版本，并在同一事务中让模板行跟随最新发布的修订
+    pub async fn publish_revision(
+        &self,
+        template_id: &str,
+        revision: i64,
+        published_at: NaiveDateTime,
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        let updated =
+            workflow_template_revision_crud::publish_revision(&mut *tx, template_id, revision, published_at).await?;
+        if updated == 0 {
+            return Err(StorageError::NotFound(format!("template {template_id} revision {revision}")));
+        }
+        let model = workflow_template_revision_crud::get_revision(&mut *tx, template_id, revision)
+            .await?
+            .ok_or_else(|| StorageError::NotFound(format!("template {template_id} revision {revision}")))?;
+        workflow_template_crud::advance_template(&mut *tx, template_id, revision, &model.dsl_definition).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_alias(&self, alias: &StoredTemplateAlias) -> Result<(), StorageError> {
+        let model = Self::alias_to_model(alias);
+        workflow_template_revision_crud::upsert_alias(&self.pool, &model).await.map_err(StorageError::from)
+    }
+
+    pub async fn get_alias(&self, template_id: &str, alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError> {
+        let model_opt = workflow_template_revision_crud::get_alias(&self.pool, template_id, alias).await?;
+        Ok(model_opt.map(Self::alias_to_entity))
+    }
+
+    pub async fn find_aliases(&self, template_id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError> {
+        let models = workflow_template_revision_crud::find_aliases(&self.pool, template_id).await?;
+        Ok(models.into_iter().map(Self::alias_to_entity).collect())
+    }
+
+    pub async fn delete_alias(&self, template_id: &str, alias: &str) -> Result<(), StorageError> {
+        workflow_template_revision_crud::delete_alias(&self.pool, template_id, alias).await.map_err(StorageError::from)
+    }
+}
@@ -10,8 +10,12 @@ use stepflow_storage::{
         workflow_state::{StoredWorkflowState, UpdateStoredWorkflowState},
         timer::{StoredTimer, UpdateStoredTimer},
         workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate},
+        workflow_template_revision::{StoredTemplateAlias, StoredTemplateRevision},
         workflow_visibility::{StoredWorkflowVisibility, UpdateStoredWorkflowVisibility},
         queue_task::{StoredQueueTask, UpdateStoredQueueTask},
+        outbox::StoredOutboxMessage,
+        worker::StoredWorker,
+        step_commit::StepCommit,
     },
 };
 use sqlx::{Sqlite, Transaction};
@@ -22,8 +26,11 @@ use crate::persistence::{
     workflow_state::WorkflowStatePersistence,
     timer::TimerPersistence,
     workflow_template::WorkflowTemplatePersistence,
+    workflow_template_revision::WorkflowTemplateRevisionPersistence,
     workflow_visibility::WorkflowVisibilityPersistence,
     queue_task::QueueTaskPersistence,
+    outbox::OutboxPersistence,
+    worker::WorkerPersistence,
 };
 use anyhow::Result;
 use sqlx::Executor;
@@ -36,8 +43,11 @@ pub struct SqliteStorageManager {
     workflow_state: WorkflowStatePersistence,
     timer: TimerPersistence,
     workflow_template: WorkflowTemplatePersistence,
+    workflow_template_revision: WorkflowTemplateRevisionPersistence,
     workflow_visibility: WorkflowVisibilityPersistence,
     queue_task: QueueTaskPersistence,
+    outbox: OutboxPersistence,
+    worker: WorkerPersistence,
 }
 
 impl SqliteStorageManager {
@@ -50,8 +60,11 @@ impl SqliteStorageManager {
             workflow_state: WorkflowStatePersistence::new(pool.clone()),
             timer: TimerPersistence::new(pool.clone()),
             workflow_template: WorkflowTemplatePersistence::new(pool.clone()),
+            workflow_template_revision: WorkflowTemplateRevisionPersistence::new(pool.clone()),
             workflow_visibility: WorkflowVisibilityPersistence::new(pool.clone()),
             queue_task: QueueTaskPersistence::new(pool.clone()),
+            outbox: OutboxPersistence::new(pool.clone()),
+            worker: WorkerPersistence::new(pool.clone()),
             pool,
         })
     }
@@ -110,6 +123,10 @@ impl stepflow_storage::traits::WorkflowStorage for SqliteStorageManager {
         self.workflow_execution.find_executions_by_status(status, limit, offset).await
     }
 
+    async fn find_executions_by_parent(&self, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> {
+        self.workflow_execution.find_executions_by_parent(parent_run_id, limit, offset).await
+    }
+
     async fn update_execution(&self, run_id: &str, changes: &UpdateStoredWorkflowExecution) -> Result<(), StorageError> {
         self.workflow_execution.update_execution(run_id, changes).await
     }
@@ -217,6 +234,18 @@ impl stepflow_storage::traits::TimerStorage for SqliteStorageManager {
     async fn find_timers_before(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
         self.timer.find_timers_before(before, limit).await
     }
+
+    async fn claim_timer(&self, timer_id: &str, expected_version: i64) -> Result<bool, StorageError> {
+        self.timer.claim_timer(timer_id, expected_version).await
+    }
+
+    async fn claim_due_timers(&self, before: NaiveDateTime, shards: &[i64], limit: i64) -> Result<Vec<StoredTimer>, StorageError> {
+        self.timer.claim_due_timers(before, shards, limit).await
+    }
+
+    async fn cancel_timers_by_run(&self, run_id: &str) -> Result<u64, StorageError> {
+        self.timer.cancel_timers_by_run(run_id).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -240,6 +269,38 @@ impl stepflow_storage::traits::TemplateStorage for SqliteStorageManager {
     async fn delete_template(&self, template_id: &str) -> Result<(), StorageError> {
         self.workflow_template.delete_template(template_id).await
     }
+
+    async fn create_template_revision(&self, rev: &StoredTemplateRevision) -> Result<(), StorageError> {
+        self.workflow_template_revision.create_revision(rev).await
+    }
+
+    async fn get_template_revision(&self, template_id: &str, revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError> {
+        self.workflow_template_revision.get_revision(template_id, revision).await
+    }
+
+    async fn find_template_revisions(&self, template_id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError> {
+        self.workflow_template_revision.find_revisions(template_id).await
+    }
+
+    async fn publish_template_revision(&self, template_id: &str, revision: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        self.workflow_template_revision.publish_revision(template_id, revision, published_at).await
+    }
+
+    async fn set_template_alias(&self, alias: &StoredTemplateAlias) -> Result<(), StorageError> {
+        self.workflow_template_revision.set_alias(alias).await
+    }
+
+    async fn get_template_alias(&self, template_id: &str, alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError> {
+        self.workflow_template_revision.get_alias(template_id, alias).await
+    }
+
+    async fn find_template_aliases(&self, template_id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError> {
+        self.workflow_template_revision.find_aliases(template_id).await
+    }
+
+    async fn delete_template_alias(&self, template_id: &str, alias: &str) -> Result<(), StorageError> {
+        self.workflow_template_revision.delete_alias(template_id, alias).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -294,8 +355,84 @@ impl stepflow_storage::traits::QueueStorage for SqliteStorageManager {
     async fn find_queue_task_by_run_state(&self, run_id: &str, state_name: &str) -> Result<Option<StoredQueueTask>, StorageError> {
         self.queue_task.find_queue_task_by_run_state(run_id, state_name).await
     }
+
+    async fn claim_queue_task(&self, queues: &[String], worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.claim_queue_task(queues, worker_id, now).await
+    }
+
+    async fn find_dispatch_candidates(
+        &self,
+        queues: &[String],
+        now: NaiveDateTime,
+        per_run: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.find_dispatch_candidates(queues, now, per_run, limit).await
+    }
+
+    async fn claim_queue_task_by_id(&self, task_id: &str, worker_id: &str, now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> {
+        self.queue_task.claim_queue_task_by_id(task_id, worker_id, now).await
+    }
+
+    async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError> {
+        self.queue_task.count_pending_queue_tasks().await
+    }
+
+    async fn cancel_queue_tasks_by_run(&self, run_id: &str, now: NaiveDateTime) -> Result<u64, StorageError> {
+        self.queue_task.cancel_queue_tasks_by_run(run_id, now).await
+    }
+
+    async fn requeue_worker_queue_tasks(&self, worker_id: &str, now: NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError> {
+        self.queue_task.requeue_worker_queue_tasks(worker_id, now).await
+    }
 } 
 
+#[async_trait::async_trait]
+impl stepflow_storage::traits::OutboxStorage for SqliteStorageManager {
+    async fn commit_step(&self, commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        self.outbox.commit_step(commit).await
+    }
+
+    async fn find_unpublished_outbox(&self, created_before: NaiveDateTime, limit: i64) -> Result<Vec<StoredOutboxMessage>, StorageError> {
+        self.outbox.find_unpublished(created_before, limit).await
+    }
+
+    async fn mark_outbox_published(&self, id: i64, published_at: NaiveDateTime) -> Result<(), StorageError> {
+        self.outbox.mark_published(id, published_at).await
+    }
+}
+
+#[async_trait::async_trait]
+impl stepflow_storage::traits::WorkerStorage for SqliteStorageManager {
+    async fn upsert_worker(&self, worker: &StoredWorker) -> Result<(), StorageError> {
+        self.worker.upsert_worker(worker).await
+    }
+
+    async fn get_worker(&self, worker_id: &str) -> Result<Option<StoredWorker>, StorageError> {
+        self.worker.get_worker(worker_id).await
+    }
+
+    async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError> {
+        self.worker.list_workers().await
+    }
+
+    async fn touch_worker(&self, worker_id: &str, in_flight: Option<i64>, now: NaiveDateTime) -> Result<bool, StorageError> {
+        self.worker.touch_worker(worker_id, in_flight, now).await
+    }
+
+    async fn set_worker_status(&self, worker_id: &str, status: &str) -> Result<bool, StorageError> {
+        self.worker.set_worker_status(worker_id, status).await
+    }
+
+    async fn find_stale_workers(&self, seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError> {
+        self.worker.find_stale_workers(seen_before).await
+    }
+
+    async fn mark_worker_offline_if_stale(&self, worker_id: &str, seen_before: NaiveDateTime) -> Result<bool, StorageError> {
+        self.worker.mark_worker_offline_if_stale(worker_id, seen_before).await
+    }
+}
+
 pub async fn maybe_init_schema(pool: &SqlitePool) -> Result<()> {
     let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE type='table'")
         .fetch_one(pool)
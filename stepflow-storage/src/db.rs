@@ -20,4 +20,5 @@ pub type Tx<'a>     = sqlx::Transaction<'a, DbBackend>;
 use std::sync::Arc;
 
 
-pub type DynPM = Arc<dyn PersistenceManager<DB = DbBackend> + Send + Sync>;
\ No newline at end of file
+/// 运行时选择的存储后端（SQLite / Postgres）
+pub type DynPM = Arc<dyn PersistenceManager + Send + Sync>;
\ No newline at end of file
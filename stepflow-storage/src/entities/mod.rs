@@ -1,17 +1,25 @@
 pub mod workflow_execution;
 pub mod workflow_template;
+pub mod workflow_template_revision;
 pub mod activity_task;
 pub mod queue_task;
 pub mod workflow_event;
 pub mod timer;
 pub mod workflow_state;
 pub mod workflow_visibility;
+pub mod outbox;
+pub mod step_commit;
+pub mod worker;
 
 pub use workflow_execution::*;
 pub use workflow_template::*;
+pub use workflow_template_revision::*;
 pub use activity_task::*;
 pub use queue_task::*;
 pub use workflow_event::*;
 pub use timer::*;
 pub use workflow_state::*;
-pub use workflow_visibility::*;
\ No newline at end of file
+pub use workflow_visibility::*;
+pub use outbox::*;
+pub use step_commit::*;
+pub use worker::*;
\ No newline at end of file
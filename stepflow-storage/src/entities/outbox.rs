@@ -0,0 +1,14 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 待投递消息：与引擎步骤在同一事务中写入，提交后再发布（事件总线 / 任务队列）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredOutboxMessage {
+    pub id: i64, // 自增主键，写入时忽略
+    pub run_id: String,
+    pub topic: String, // "engine_event" 或 handler 的状态类型（如 "task"）
+    pub payload: Value,
+    pub created_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+}
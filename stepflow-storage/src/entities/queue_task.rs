@@ -9,12 +9,22 @@ pub struct StoredQueueTask {
     pub run_id: String,
     pub state_name: String,
     pub resource: String,
+    /// 任务所属的命名队列（Task 状态的 `taskQueue`，缺省为 resource）
+    pub task_queue: String,
+    /// 根执行 ID（Map / Parallel 分支与 SubWorkflow 子执行共用），派发时按它做公平轮转
+    pub root_run_id: String,
+    /// 根执行所用模板，派发时按模板加权轮转
+    pub template_id: Option<String>,
+    /// 认领该任务的 worker（processing 期间有效）
+    pub worker_id: Option<String>,
     pub task_payload: Option<Value>,
     pub status: String,
     pub attempts: i64,
     pub max_attempts: i64,
     pub priority: Option<i64>,           
     pub timeout_seconds: Option<i64>,    
+    pub heartbeat_seconds: Option<i64>,
+    pub last_heartbeat_at: Option<NaiveDateTime>,
     pub error_message: Option<String>,
     pub last_error_at: Option<NaiveDateTime>,
     pub next_retry_at: Option<NaiveDateTime>,
@@ -34,10 +44,13 @@ pub struct UpdateStoredQueueTask {
     pub attempts: Option<i64>,
     pub priority: Option<i64>,           
     pub timeout_seconds: Option<i64>,    
+    pub heartbeat_seconds: Option<i64>,
+    pub last_heartbeat_at: Option<Option<NaiveDateTime>>,
     pub error_message: Option<Option<String>>,
     pub last_error_at: Option<Option<NaiveDateTime>>,
     pub next_retry_at: Option<Option<NaiveDateTime>>,
     pub processing_at: Option<Option<NaiveDateTime>>,
     pub completed_at: Option<Option<NaiveDateTime>>,
     pub failed_at: Option<Option<NaiveDateTime>>,
+    pub worker_id: Option<Option<String>>,
 }
\ No newline at end of file
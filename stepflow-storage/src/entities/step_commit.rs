@@ -0,0 +1,37 @@
+use crate::entities::{
+    activity_task::StoredActivityTask, outbox::StoredOutboxMessage, timer::StoredTimer, workflow_event::StoredWorkflowEvent,
+    workflow_execution::UpdateStoredWorkflowExecution, workflow_state::UpdateStoredWorkflowState,
+};
+
+/// 引擎一个步骤的全部写入，由 `OutboxStorage::commit_step` 在同一事务中提交
+#[derive(Debug, Clone, Default)]
+pub struct StepCommit {
+    pub run_id: String,
+    /// execution 游标 / context 变更（带 `version` 时为 CAS，冲突则整体回滚）
+    pub execution: Option<UpdateStoredWorkflowExecution>,
+    /// 状态记录 upsert：`(state_id, 变更)`，不存在时以 `run_id` 新建
+    pub states: Vec<(String, UpdateStoredWorkflowState)>,
+    pub events: Vec<StoredWorkflowEvent>,
+    pub timers: Vec<StoredTimer>,
+    /// 签发的回调 task token（waitForTaskToken）
+    pub activity_tasks: Vec<StoredActivityTask>,
+    pub outbox: Vec<StoredOutboxMessage>,
+}
+
+impl StepCommit {
+    pub fn new(run_id: impl Into<String>) -> Self {
+        Self {
+            run_id: run_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.execution.is_none()
+            && self.states.is_empty()
+            && self.events.is_empty()
+            && self.timers.is_empty()
+            && self.activity_tasks.is_empty()
+            && self.outbox.is_empty()
+    }
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// 正常接收任务
+pub const WORKER_ACTIVE: &str = "active";
+/// 排空中：不再派发新任务，已持有的任务照常完成
+pub const WORKER_DRAINING: &str = "draining";
+/// 已注销或失联；持有的任务已退回队列
+pub const WORKER_OFFLINE: &str = "offline";
+
+/// 已注册的 worker：能力、容量与存活状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredWorker {
+    pub worker_id: String,
+    pub capabilities: Vec<String>,
+    pub task_queues: Vec<String>,
+    pub version: Option<String>,
+    /// 并发槽位数
+    pub concurrency: i64,
+    /// 最近一次心跳上报的执行中任务数
+    pub in_flight: i64,
+    pub status: String, // active / draining / offline
+    pub registered_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
@@ -8,6 +8,10 @@ pub struct StoredWorkflowExecution {
     pub workflow_id: Option<String>,
     pub shard_id: i64,
     pub template_id: Option<String>,
+    /// 执行开始时固定的模板修订号；之后发布的修订不影响已开始的执行
+    pub template_revision: Option<i64>,
+    /// 父执行（SubWorkflow / Parallel / Map 的子执行），顶层执行为 None
+    pub parent_run_id: Option<String>,
     pub mode: String,
     pub current_state_name: Option<String>,
     pub status: String,
@@ -44,6 +48,7 @@ pub struct UpdateStoredWorkflowExecution {
     pub memo: Option<Option<String>>,
     pub search_attrs: Option<Option<Value>>,
     pub context_snapshot: Option<Option<Value>>,
+    /// 期望的当前版本（CAS）：不匹配时返回 `StorageError::OptimisticLockConflict`；无论是否指定，成功更新后版本号 +1
     pub version: Option<i64>,
 }
 
@@ -16,6 +16,8 @@ pub struct StoredWorkflowState {
     pub error_details: Option<String>, // 添加
     pub started_at: Option<NaiveDateTime>,
     pub completed_at: Option<NaiveDateTime>, // 改名为 completed_at
+    pub attempts: i64,             // 已执行的重试次数
+    pub mapping_trace: Option<Value>, // MappingDSL debug 时的逐条规则快照
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub version: i64,
@@ -32,5 +34,8 @@ pub struct UpdateStoredWorkflowState {
     pub error_details: Option<Option<String>>,
     pub started_at: Option<Option<NaiveDateTime>>,
     pub completed_at: Option<Option<NaiveDateTime>>,
+    pub attempts: Option<i64>,
+    pub mapping_trace: Option<Option<Value>>,
+    /// 期望的当前版本（CAS），语义同 `UpdateStoredWorkflowExecution::version`
     pub version: Option<i64>,
 } 
\ No newline at end of file
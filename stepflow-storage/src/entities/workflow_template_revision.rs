@@ -0,0 +1,28 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// 修订版本状态：草稿（可被发布）
+pub const REVISION_DRAFT: &str = "DRAFT";
+/// 修订版本状态：已发布（可被执行引用）
+pub const REVISION_PUBLISHED: &str = "PUBLISHED";
+
+/// 模板的一个修订版本；写入后 DSL 不再修改，只有状态可从 DRAFT 变为 PUBLISHED
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredTemplateRevision {
+    pub template_id: String,
+    /// 模板内从 1 开始递增的修订号
+    pub revision: i64,
+    pub dsl_definition: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+}
+
+/// 模板别名（如 `stable`），指向某个已发布的修订版本
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredTemplateAlias {
+    pub template_id: String,
+    pub alias: String,
+    pub revision: i64,
+    pub updated_at: NaiveDateTime,
+}
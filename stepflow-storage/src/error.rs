@@ -30,6 +30,7 @@ pub enum StorageError {
         details: String,
     },
 
+    /// 带版本号的更新（CAS）未命中：记录已被其他写入者修改
     #[error("Optimistic lock conflict for {entity} with id '{id}'. Expected version {expected_version}, found {actual_version}")]
     OptimisticLockConflict {
         entity: String,
@@ -48,6 +49,12 @@ pub enum StorageError {
     ConnectionError(String),
 }
 
+impl StorageError {
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, StorageError::OptimisticLockConflict { .. })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,9 +94,11 @@ mod tests {
             expected_version: 2,
             actual_version: 1,
         };
+        assert!(err.is_conflict());
         assert!(format!("{}", err).contains("Order"));
         assert!(format!("{}", err).contains("123"));
         assert!(format!("{}", err).contains("Expected version 2"));
+        assert!(!StorageError::ConcurrentModification("row1".to_string()).is_conflict());
 
         let err = StorageError::SerializationError("ser fail".to_string());
         assert!(format!("{}", err).contains("ser fail"));
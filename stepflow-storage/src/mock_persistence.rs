@@ -12,6 +12,7 @@ impl WorkflowStorage for DummyPersistence {
     async fn get_execution(&self, _id: &str) -> Result<Option<StoredWorkflowExecution>, StorageError> { unimplemented!() }
     async fn find_executions(&self, _start: i64, _end: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> { unimplemented!() }
     async fn find_executions_by_status(&self, _status: &str, _start: i64, _end: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> { unimplemented!() }
+    async fn find_executions_by_parent(&self, _parent_run_id: &str, _start: i64, _end: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError> { unimplemented!() }
     async fn update_execution(&self, _id: &str, _update: &UpdateStoredWorkflowExecution) -> Result<(), StorageError> { unimplemented!() }
     async fn delete_execution(&self, _id: &str) -> Result<(), StorageError> { unimplemented!() }
 }
@@ -48,6 +49,9 @@ impl TimerStorage for DummyPersistence {
     async fn update_timer(&self, _id: &str, _update: &UpdateStoredTimer) -> Result<(), StorageError> { Ok(()) }
     async fn delete_timer(&self, _id: &str) -> Result<(), StorageError> { Ok(()) }
     async fn find_timers_before(&self, _before: NaiveDateTime, _limit: i64) -> Result<Vec<StoredTimer>, StorageError> { Ok(vec![]) }
+    async fn claim_timer(&self, _id: &str, _expected_version: i64) -> Result<bool, StorageError> { Ok(false) }
+    async fn claim_due_timers(&self, _before: NaiveDateTime, _shards: &[i64], _limit: i64) -> Result<Vec<StoredTimer>, StorageError> { Ok(vec![]) }
+    async fn cancel_timers_by_run(&self, _run_id: &str) -> Result<u64, StorageError> { Ok(0) }
 }
 #[async_trait]
 impl TemplateStorage for DummyPersistence {
@@ -56,6 +60,14 @@ impl TemplateStorage for DummyPersistence {
     async fn find_templates(&self, _limit: i64, _offset: i64) -> Result<Vec<StoredWorkflowTemplate>, StorageError> { unimplemented!() }
     async fn update_template(&self, _id: &str, _update: &UpdateStoredWorkflowTemplate) -> Result<(), StorageError> { unimplemented!() }
     async fn delete_template(&self, _id: &str) -> Result<(), StorageError> { unimplemented!() }
+    async fn create_template_revision(&self, _rev: &StoredTemplateRevision) -> Result<(), StorageError> { unimplemented!() }
+    async fn get_template_revision(&self, _id: &str, _revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError> { unimplemented!() }
+    async fn find_template_revisions(&self, _id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError> { unimplemented!() }
+    async fn publish_template_revision(&self, _id: &str, _revision: i64, _published_at: NaiveDateTime) -> Result<(), StorageError> { unimplemented!() }
+    async fn set_template_alias(&self, _alias: &StoredTemplateAlias) -> Result<(), StorageError> { unimplemented!() }
+    async fn get_template_alias(&self, _id: &str, _alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError> { unimplemented!() }
+    async fn find_template_aliases(&self, _id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError> { unimplemented!() }
+    async fn delete_template_alias(&self, _id: &str, _alias: &str) -> Result<(), StorageError> { unimplemented!() }
 }
 #[async_trait]
 impl VisibilityStorage for DummyPersistence {
@@ -74,10 +86,32 @@ impl QueueStorage for DummyPersistence {
     async fn find_queue_tasks_by_status(&self, _status: &str, _limit: i64, _offset: i64) -> Result<Vec<StoredQueueTask>, StorageError> { unimplemented!() }
     async fn find_queue_tasks_to_retry(&self, _before: NaiveDateTime, _limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> { unimplemented!() }
     async fn find_queue_task_by_run_state(&self, _run_id: &str, _state_name: &str) -> Result<Option<StoredQueueTask>, StorageError> { unimplemented!() }
+    async fn claim_queue_task(&self, _queues: &[String], _worker_id: &str, _now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> { unimplemented!() }
+    async fn find_dispatch_candidates(&self, _queues: &[String], _now: NaiveDateTime, _per_run: i64, _limit: i64) -> Result<Vec<StoredQueueTask>, StorageError> { Ok(vec![]) }
+    async fn claim_queue_task_by_id(&self, _task_id: &str, _worker_id: &str, _now: NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError> { Ok(None) }
+    async fn requeue_worker_queue_tasks(&self, _worker_id: &str, _now: NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError> { Ok(vec![]) }
+    async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError> { Ok(vec![]) }
+    async fn cancel_queue_tasks_by_run(&self, _run_id: &str, _now: NaiveDateTime) -> Result<u64, StorageError> { Ok(0) }
+}
+#[async_trait]
+impl OutboxStorage for DummyPersistence {
+    async fn commit_step(&self, _commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError> { unimplemented!() }
+    async fn find_unpublished_outbox(&self, _before: NaiveDateTime, _limit: i64) -> Result<Vec<StoredOutboxMessage>, StorageError> { Ok(vec![]) }
+    async fn mark_outbox_published(&self, _id: i64, _at: NaiveDateTime) -> Result<(), StorageError> { Ok(()) }
+}
+#[async_trait]
+impl WorkerStorage for DummyPersistence {
+    async fn upsert_worker(&self, _worker: &StoredWorker) -> Result<(), StorageError> { Ok(()) }
+    async fn get_worker(&self, _worker_id: &str) -> Result<Option<StoredWorker>, StorageError> { Ok(None) }
+    async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError> { Ok(vec![]) }
+    async fn touch_worker(&self, _worker_id: &str, _in_flight: Option<i64>, _now: NaiveDateTime) -> Result<bool, StorageError> { Ok(false) }
+    async fn set_worker_status(&self, _worker_id: &str, _status: &str) -> Result<bool, StorageError> { Ok(false) }
+    async fn find_stale_workers(&self, _seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError> { Ok(vec![]) }
+    async fn mark_worker_offline_if_stale(&self, _worker_id: &str, _seen_before: NaiveDateTime) -> Result<bool, StorageError> { Ok(false) }
 }
 // #[async_trait]
 // impl TransactionManager for DummyPersistence {
 //     async fn begin_transaction(&self) -> Result<(), StorageError> { Ok(()) }
 //     async fn commit(&self) -> Result<(), StorageError> { Ok(()) }
 //     async fn rollback(&self) -> Result<(), StorageError> { Ok(()) }
-// } 
\ No newline at end of file
+// }
@@ -1,8 +1,11 @@
 // src/persistence_manager.rs
 use async_trait::async_trait;
-use crate::transaction::TransactionManager;
 use crate::traits::*;
 
+/// 与具体数据库无关的存储门面，运行时可在 SQLite / Postgres 之间切换。
+///
+/// 事务句柄的类型依赖具体数据库（`TransactionManager::DB`），因此不放进这里，
+/// 各后端各自实现 `TransactionManager`。
 #[async_trait]     // 只是为了以后若要加异步方法
 pub trait PersistenceManager:
       WorkflowStorage
@@ -13,7 +16,8 @@ pub trait PersistenceManager:
     + TemplateStorage
     + VisibilityStorage
     + QueueStorage
-    + TransactionManager            // ← 带上事务能力，但不写死 DB
+    + OutboxStorage
+    + WorkerStorage
     + Send + Sync
 {}
 
@@ -27,5 +31,6 @@ impl<T> PersistenceManager for T where
       + TemplateStorage
       + VisibilityStorage
       + QueueStorage
-      + TransactionManager
+      + OutboxStorage
+      + WorkerStorage
       + Send + Sync {}
@@ -6,6 +6,8 @@ pub mod timer;
 pub mod template;
 pub mod visibility;
 pub mod queue;
+pub mod outbox;
+pub mod worker;
 
 // Re-export all traits
 pub use workflow::WorkflowStorage;
@@ -16,6 +18,8 @@ pub use timer::TimerStorage;
 pub use template::TemplateStorage;
 pub use visibility::VisibilityStorage;
 pub use queue::QueueStorage;
+pub use outbox::OutboxStorage;
+pub use worker::WorkerStorage;
 
 // Storage trait that combines all storage traits
 pub trait Storage: 
@@ -27,6 +31,8 @@ pub trait Storage:
     TemplateStorage + 
     VisibilityStorage + 
     QueueStorage + 
+    OutboxStorage + 
+    WorkerStorage + 
     Send + 
     Sync 
 {} 
\ No newline at end of file
@@ -0,0 +1,17 @@
+use chrono::NaiveDateTime;
+use crate::error::StorageError;
+use crate::entities::{outbox::StoredOutboxMessage, step_commit::StepCommit};
+
+#[async_trait::async_trait]
+pub trait OutboxStorage: Send + Sync {
+    /// Atomically commit all writes of one engine step (execution, states, events, timers, outbox).
+    /// A version conflict on the execution rolls everything back and returns `StorageError::OptimisticLockConflict`.
+    /// Returns the outbox messages with their assigned ids.
+    async fn commit_step(&self, commit: &StepCommit) -> Result<Vec<StoredOutboxMessage>, StorageError>;
+
+    /// Find unpublished outbox messages created before the given time (oldest first)
+    async fn find_unpublished_outbox(&self, created_before: NaiveDateTime, limit: i64) -> Result<Vec<StoredOutboxMessage>, StorageError>;
+
+    /// Mark an outbox message as published
+    async fn mark_outbox_published(&self, id: i64, published_at: NaiveDateTime) -> Result<(), StorageError>;
+}
@@ -29,4 +29,32 @@ pub trait QueueStorage: Send + Sync {
         run_id: &str,
         state_name: &str,
     ) -> Result<Option<StoredQueueTask>, StorageError>;
+
+    /// Atomically take the oldest pending task in one of `queues` that is ready at `now`
+    /// (no `next_retry_at` or already due) and mark it processing by `worker_id`. Concurrent workers never receive the same task.
+    async fn claim_queue_task(&self, queues: &[String], worker_id: &str, now: chrono::NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError>;
+
+    /// Pending tasks in `queues` that are ready at `now`, at most `per_run` per root execution
+    /// (highest priority first, then oldest) and `limit` overall. The match service picks among them.
+    async fn find_dispatch_candidates(
+        &self,
+        queues: &[String],
+        now: chrono::NaiveDateTime,
+        per_run: i64,
+        limit: i64,
+    ) -> Result<Vec<StoredQueueTask>, StorageError>;
+
+    /// Mark `task_id` processing by `worker_id` if it is still pending; `None` when another worker claimed it first.
+    async fn claim_queue_task_by_id(&self, task_id: &str, worker_id: &str, now: chrono::NaiveDateTime) -> Result<Option<StoredQueueTask>, StorageError>;
+
+    /// Put every processing task held by `worker_id` back to pending (the worker is gone) and return them.
+    /// Attempts are not consumed.
+    async fn requeue_worker_queue_tasks(&self, worker_id: &str, now: chrono::NaiveDateTime) -> Result<Vec<StoredQueueTask>, StorageError>;
+
+    /// Number of pending tasks per task queue, as `(task_queue, count)`
+    async fn count_pending_queue_tasks(&self) -> Result<Vec<(String, i64)>, StorageError>;
+
+    /// Mark every pending / processing task of `run_id` as cancelled (execution cancelled or terminated).
+    /// Workers still holding a cancelled task are told to stop through a rejected heartbeat.
+    async fn cancel_queue_tasks_by_run(&self, run_id: &str, now: chrono::NaiveDateTime) -> Result<u64, StorageError>;
 } 
\ No newline at end of file
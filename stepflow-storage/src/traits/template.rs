@@ -1,5 +1,7 @@
+use chrono::NaiveDateTime;
 use crate::error::StorageError;
 use crate::entities::workflow_template::{StoredWorkflowTemplate, UpdateStoredWorkflowTemplate};
+use crate::entities::workflow_template_revision::{StoredTemplateAlias, StoredTemplateRevision};
 
 #[async_trait::async_trait]
 pub trait TemplateStorage: Send + Sync {
@@ -15,6 +17,31 @@ pub trait TemplateStorage: Send + Sync {
     /// Update a workflow template
     async fn update_template(&self, template_id: &str, changes: &UpdateStoredWorkflowTemplate) -> Result<(), StorageError>;
     
-    /// Delete a workflow template
+    /// Delete a workflow template together with its revisions and aliases
     async fn delete_template(&self, template_id: &str) -> Result<(), StorageError>;
-} 
\ No newline at end of file
+
+    /// Append an immutable revision; fails if the revision number is taken
+    async fn create_template_revision(&self, rev: &StoredTemplateRevision) -> Result<(), StorageError>;
+
+    /// Get one revision of a template
+    async fn get_template_revision(&self, template_id: &str, revision: i64) -> Result<Option<StoredTemplateRevision>, StorageError>;
+
+    /// All revisions of a template, ordered by revision number
+    async fn find_template_revisions(&self, template_id: &str) -> Result<Vec<StoredTemplateRevision>, StorageError>;
+
+    /// Mark a revision PUBLISHED and, if it is the newest published one, copy its DSL
+    /// and number into the template row (`dsl_definition` / `version`) in the same transaction
+    async fn publish_template_revision(&self, template_id: &str, revision: i64, published_at: NaiveDateTime) -> Result<(), StorageError>;
+
+    /// Create or move an alias
+    async fn set_template_alias(&self, alias: &StoredTemplateAlias) -> Result<(), StorageError>;
+
+    /// Get an alias of a template
+    async fn get_template_alias(&self, template_id: &str, alias: &str) -> Result<Option<StoredTemplateAlias>, StorageError>;
+
+    /// All aliases of a template
+    async fn find_template_aliases(&self, template_id: &str) -> Result<Vec<StoredTemplateAlias>, StorageError>;
+
+    /// Delete an alias
+    async fn delete_template_alias(&self, template_id: &str, alias: &str) -> Result<(), StorageError>;
+}
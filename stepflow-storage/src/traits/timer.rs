@@ -16,6 +16,17 @@ pub trait TimerStorage: Send + Sync {
     /// Delete a timer
     async fn delete_timer(&self, timer_id: &str) -> Result<(), StorageError>;
     
-    /// Find timers that should fire before the given time
+    /// Find pending timers that should fire before the given time
     async fn find_timers_before(&self, before: NaiveDateTime, limit: i64) -> Result<Vec<StoredTimer>, StorageError>;
+
+    /// Atomically claim a pending timer (pending -> fired) if its version still matches.
+    /// Returns `false` when another scheduler already claimed it.
+    async fn claim_timer(&self, timer_id: &str, expected_version: i64) -> Result<bool, StorageError>;
+
+    /// Atomically claim up to `limit` pending timers due before `before` (pending -> fired, version + 1)
+    /// in the given shards (empty = all shards). Concurrent schedulers never claim the same timer.
+    async fn claim_due_timers(&self, before: NaiveDateTime, shards: &[i64], limit: i64) -> Result<Vec<StoredTimer>, StorageError>;
+
+    /// Mark every pending timer of `run_id` as cancelled (version + 1) so it never fires.
+    async fn cancel_timers_by_run(&self, run_id: &str) -> Result<u64, StorageError>;
 } 
\ No newline at end of file
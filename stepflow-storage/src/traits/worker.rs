@@ -0,0 +1,30 @@
+use chrono::NaiveDateTime;
+use crate::error::StorageError;
+use crate::entities::worker::StoredWorker;
+
+#[async_trait::async_trait]
+pub trait WorkerStorage: Send + Sync {
+    /// Register a worker, or refresh an existing registration (capabilities, version, concurrency)
+    /// and mark it active again.
+    async fn upsert_worker(&self, worker: &StoredWorker) -> Result<(), StorageError>;
+
+    /// Get a worker by id
+    async fn get_worker(&self, worker_id: &str) -> Result<Option<StoredWorker>, StorageError>;
+
+    /// All registered workers, ordered by worker_id
+    async fn list_workers(&self) -> Result<Vec<StoredWorker>, StorageError>;
+
+    /// Record that the worker is alive at `now`, optionally with its current in-flight task count.
+    /// Returns `false` when the worker is not registered or already offline.
+    async fn touch_worker(&self, worker_id: &str, in_flight: Option<i64>, now: NaiveDateTime) -> Result<bool, StorageError>;
+
+    /// Change the status (active / draining / offline); returns `false` when the worker is not registered
+    async fn set_worker_status(&self, worker_id: &str, status: &str) -> Result<bool, StorageError>;
+
+    /// Workers that are not offline but have not been seen since `seen_before`
+    async fn find_stale_workers(&self, seen_before: NaiveDateTime) -> Result<Vec<StoredWorker>, StorageError>;
+
+    /// Mark the worker offline only if it is still not offline and has not been seen since `seen_before`.
+    /// Returns `false` when it heartbeated (or was already marked offline) in the meantime.
+    async fn mark_worker_offline_if_stale(&self, worker_id: &str, seen_before: NaiveDateTime) -> Result<bool, StorageError>;
+}
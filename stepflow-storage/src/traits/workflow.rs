@@ -15,6 +15,9 @@ pub trait WorkflowStorage: Send + Sync {
     /// Find workflow executions by status with pagination
     async fn find_executions_by_status(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError>;
     
+    /// Find child executions of a parent run with pagination
+    async fn find_executions_by_parent(&self, parent_run_id: &str, limit: i64, offset: i64) -> Result<Vec<StoredWorkflowExecution>, StorageError>;
+    
     /// Update a workflow execution
     async fn update_execution(&self, run_id: &str, changes: &UpdateStoredWorkflowExecution) -> Result<(), StorageError>;
     
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use serde_json::Value;
+use tokio::sync::watch;
 
 use crate::common::config::ToolConfig;
 
@@ -23,6 +25,45 @@ pub struct ToolContext {
     
     /// 额外数据
     pub extra: Value,
+
+    /// 取消信号（执行被取消 / 终止时由 worker 触发）
+    pub cancellation: CancellationToken,
+}
+
+/// 工具执行的取消信号，克隆共享同一状态
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self {
+            tx: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 触发取消
+    pub fn cancel(&self) {
+        self.tx.send_replace(true);
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// 等待取消；用于与工具的执行 future 一起 `select!`
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
 }
 
 impl Default for ToolContext {
@@ -34,6 +75,7 @@ impl Default for ToolContext {
             config: ToolConfig::default(),
             start_time: Instant::now(),
             extra: Value::Null,
+            cancellation: CancellationToken::default(),
         }
     }
 }
@@ -55,6 +97,12 @@ impl ToolContext {
         self
     }
 
+    /// 设置取消信号
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     /// 增加重试次数
     pub fn increment_attempt(&mut self) {
         self.attempt += 1;
@@ -5,6 +5,9 @@ pub enum ToolError {
     #[error("Tool execution timeout")]
     Timeout,
 
+    #[error("Tool execution cancelled")]
+    Cancelled,
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
@@ -5,7 +5,7 @@ use anyhow::{Result, anyhow};
 use serde_json::Value;
 
 use crate::core::tool::Tool;
-use crate::common::context::ToolContext;
+use crate::common::context::{CancellationToken, ToolContext};
 use crate::common::result::ToolResult;
 
 /// 工具注册表
@@ -39,12 +39,22 @@ impl ToolRegistry {
 
     /// 执行工具
     pub async fn execute(&self, kind: &str, input: Value) -> Result<ToolResult> {
+        self.execute_with_cancellation(kind, input, CancellationToken::default()).await
+    }
+
+    /// 执行工具；`cancellation` 触发后工具中止执行（终止子进程、放弃请求）
+    pub async fn execute_with_cancellation(
+        &self,
+        kind: &str,
+        input: Value,
+        cancellation: CancellationToken,
+    ) -> Result<ToolResult> {
         println!("execute tool kind: {:?}", kind);
         println!("execute tool input: {:?}", input);
         let tool = self.get(kind)
             .ok_or_else(|| anyhow!("Tool {} not found", kind))?;
         
-        let context = ToolContext::default();
+        let context = ToolContext::default().with_cancellation(cancellation);
         // tool.validate_input(&input, &context)?;
         tool.execute(input, context).await
     }
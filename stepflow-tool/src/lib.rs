@@ -7,7 +7,7 @@ pub use core::tool::{Tool, ToolMetadata, Validation};
 pub use core::error::ToolError;
 pub use core::registry::ToolRegistry;
 pub use common::config::ToolConfig;
-pub use common::context::ToolContext;
+pub use common::context::{CancellationToken, ToolContext};
 pub use common::result::{ToolResult, ToolMetadata as ResultMetadata};
 
 pub fn add(left: u64, right: u64) -> u64 {
@@ -10,6 +10,7 @@ use tracing::debug;
 use crate::core::tool::{Tool, ToolMetadata};
 use crate::common::config::ToolConfig;
 use crate::common::context::ToolContext;
+use crate::core::error::ToolError;
 use crate::common::result::{ToolResult, ToolMetadata as ResultMetadata};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,16 +112,22 @@ impl Tool for HttpTool {
             req = req.json(&body);
         }
 
-        // 发送请求并取回原始 bytes
-        let resp = req.send().await?;
-        let status = resp.status().as_u16();
-        let headers = resp
-            .headers()
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
-            .collect::<HashMap<_, _>>();
+        // 发送请求并取回原始 bytes；取消时丢弃 future 即中止请求
+        let (status, headers, bytes) = tokio::select! {
+            resp = async {
+                let resp = req.send().await?;
+                let status = resp.status().as_u16();
+                let headers = resp
+                    .headers()
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
+                    .collect::<HashMap<_, _>>();
+                let bytes = resp.bytes().await?;
+                Ok::<_, reqwest::Error>((status, headers, bytes))
+            } => resp?,
+            _ = context.cancellation.cancelled() => return Err(ToolError::Cancelled.into()),
+        };
 
-        let bytes = resp.bytes().await?;
         // 尝试 JSON 解析，失败时回退为 String
         let body = match serde_json::from_slice::<Value>(&bytes) {
             Ok(json) => json,
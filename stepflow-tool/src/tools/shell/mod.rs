@@ -3,11 +3,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::process::Stdio;
 use tokio::process::Command;
 
 use crate::core::tool::{Tool, ToolMetadata};
 use crate::common::config::ToolConfig;
 use crate::common::context::ToolContext;
+use crate::core::error::ToolError;
 use crate::common::result::{ToolResult, ToolMetadata as ResultMetadata};
 use stepflow_dto::dto::tool::ToolInputPayload;
 
@@ -112,7 +114,13 @@ impl Tool for ShellTool {
             }
         }
 
-        let output = command.output().await?;
+        // 取消时丢弃 wait future，kill_on_drop 随之终止子进程
+        command.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+        let child = command.spawn()?;
+        let output = tokio::select! {
+            output = child.wait_with_output() => output?,
+            _ = context.cancellation.cancelled() => return Err(ToolError::Cancelled.into()),
+        };
         let duration = start_time.elapsed().as_millis() as u64;
 
         let shell_output = ShellOutput {
@@ -87,4 +87,47 @@ async fn test_shell_error_command() {
 
     assert_ne!(output.get("exit_code").unwrap().as_i64().unwrap(), 0);
     assert!(!output.get("stderr").unwrap().as_str().unwrap().is_empty());
-}
\ No newline at end of file
+}
+
+/// 进程仍在运行（不存在或已成僵尸进程都视为已结束）
+fn process_alive(pid: u32) -> bool {
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| stat.rsplit(')').next().and_then(|rest| rest.trim_start().chars().next()))
+        .is_some_and(|state| !matches!(state, 'Z' | 'X'))
+}
+
+#[tokio::test]
+async fn test_shell_cancellation_kills_process() {
+    let tool = ShellTool::new(None);
+    let cancellation = stepflow_tool::CancellationToken::new();
+    let context = ToolContext::default().with_cancellation(cancellation.clone());
+
+    // exec 让 sleep 沿用 shell 的 pid，记下后检查它确实被杀掉
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = dir.path().join("pid");
+    let input = build_payload(&format!("echo $$ > {}; exec sleep 30", pid_file.display()), None);
+    let start = std::time::Instant::now();
+    let handle = tokio::spawn(async move { tool.execute(input, context).await });
+
+    let pid = loop {
+        if let Some(pid) = std::fs::read_to_string(&pid_file).ok().and_then(|s| s.trim().parse::<u32>().ok()) {
+            break pid;
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "command did not start");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+    assert!(process_alive(pid));
+    cancellation.cancel();
+
+    let err = handle.await.unwrap().unwrap_err();
+    assert!(err.to_string().contains("cancelled"), "{err}");
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+    // kill_on_drop 发出 SIGKILL 后进程很快退出
+    let killed_at = std::time::Instant::now();
+    while process_alive(pid) {
+        assert!(killed_at.elapsed() < std::time::Duration::from_secs(2), "process {pid} still running");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
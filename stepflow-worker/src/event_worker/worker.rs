@@ -37,6 +37,7 @@ pub async fn start_event_worker(
                 state_name,
                 tool_type: resource,
                 parameters: input.unwrap_or_default(),
+                heartbeat_seconds: None,
             };
 
             // ✅ 等待可用许可，而不是跳过任务
@@ -3,5 +3,6 @@ pub mod event_worker;
 pub mod worker_launcher;
 
 pub use queue_worker::worker::start_queue_worker;
+pub use queue_worker::stream::start_stream_worker;
 pub use event_worker::worker::start_event_worker;
 pub use worker_launcher::launch_worker;
\ No newline at end of file
@@ -1,11 +1,13 @@
 use stepflow_common::config::StepflowConfig;
 use stepflow_dto::dto::worker::*;
 use stepflow_tool::core::registry::ToolRegistry;
+use stepflow_tool::CancellationToken;
 use anyhow::{Context, Result};
 use reqwest::Client;
 use serde_json::json;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+/// 长轮询：无任务时网关挂起请求直到有任务入队或超时（`WORKER_POLL_WAIT_SECS`）
 pub async fn poll_for_task(
     client: &Client,
     config: &StepflowConfig,
@@ -13,7 +15,9 @@ pub async fn poll_for_task(
     let url = format!("{}/poll", config.gateway_server_url);
     let req = PollRequest {
         worker_id: config.worker_id.clone(),
-        capabilities: vec!["http".into(), "shell".into(), "file".into()],
+        capabilities: config.capabilities.clone(),
+        task_queues: config.task_queues.clone(),
+        wait_seconds: Some(config.poll_wait_secs),
     };
 
     let res: PollResponse = client
@@ -28,30 +32,120 @@ pub async fn poll_for_task(
         .await
         .context("Invalid poll response")?;
 
-    if res.has_task {
-        let tool_type = res
-            .tool_type
-            .as_deref()
-            .unwrap_or_default()
-            .trim()
-            .to_string();
-
-        if tool_type.is_empty() {
-            anyhow::bail!("Empty tool_type received");
-        }
-
-        Ok(Some((
-            config.worker_id.clone(),
-            TaskDetails {
-                run_id: res.run_id.unwrap(),
-                state_name: res.state_name.unwrap(),
-                tool_type,
-                parameters: res.input.unwrap_or_default(),
-            },
-        )))
-    } else {
-        Ok(None)
+    Ok(task_details(res)?.map(|task| (config.worker_id.clone(), task)))
+}
+
+/// 轮询响应 / 推送的任务 → 待执行任务；无任务时返回 None
+pub fn task_details(res: PollResponse) -> Result<Option<TaskDetails>> {
+    if !res.has_task {
+        return Ok(None);
+    }
+    let tool_type = res
+        .tool_type
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    if tool_type.is_empty() {
+        anyhow::bail!("Empty tool_type received");
+    }
+
+    Ok(Some(TaskDetails {
+        run_id: res.run_id.context("Task without run_id")?,
+        state_name: res.state_name.context("Task without state_name")?,
+        tool_type,
+        parameters: res.input.unwrap_or_default(),
+        heartbeat_seconds: res.heartbeat_seconds,
+    }))
+}
+
+/// 任务未要求心跳时的上报周期（用于及时感知执行被取消）
+const DEFAULT_HEARTBEAT_PERIOD: Duration = Duration::from_secs(5);
+
+/// 上报任务心跳；任务已结束、被超时回收或执行被取消时网关返回 404，此时返回 `Ok(false)`
+pub async fn send_heartbeat(
+    client: &Client,
+    config: &StepflowConfig,
+    run_id: &str,
+    state_name: &str,
+) -> Result<bool> {
+    let url = format!("{}/heartbeat", config.gateway_server_url);
+    let req = WorkerHeartbeatRequest {
+        worker_id: config.worker_id.clone(),
+        run_id: run_id.to_string(),
+        state_name: state_name.to_string(),
+    };
+
+    let res = client
+        .post(&url)
+        .json(&req)
+        .send()
+        .await
+        .context("Failed to send heartbeat")?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+    res.error_for_status().context("Heartbeat rejected")?;
+
+    Ok(true)
+}
+
+/// 注册表地址：`GATEWAY_SERVER_URL` 指向 `/v1/worker`，注册表在同级的 `/v1/workers`
+pub fn registry_url(gateway_server_url: &str) -> String {
+    let base = gateway_server_url.trim_end_matches('/');
+    let root = base.strip_suffix("/worker").unwrap_or(base);
+    format!("{root}/workers")
+}
+
+/// 向网关注册本 worker（能力、队列、版本与并发槽位）
+pub async fn register_worker(client: &Client, config: &StepflowConfig) -> Result<()> {
+    let req = RegisterWorkerRequest {
+        worker_id: config.worker_id.clone(),
+        capabilities: config.capabilities.clone(),
+        task_queues: config.task_queues.clone(),
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        concurrency: config.concurrency as u32,
+    };
+    client
+        .post(registry_url(&config.gateway_server_url))
+        .json(&req)
+        .send()
+        .await
+        .context("Failed to register worker")?
+        .error_for_status()
+        .context("Register rejected")?;
+    Ok(())
+}
+
+/// 存活心跳并上报执行中任务数；网关不认识本 worker（未注册或已被判定下线）时返回 `Ok(false)`
+pub async fn send_liveness(client: &Client, config: &StepflowConfig, in_flight: usize) -> Result<bool> {
+    let url = format!("{}/{}/heartbeat", registry_url(&config.gateway_server_url), config.worker_id);
+    let req = WorkerLivenessRequest { in_flight: Some(in_flight as u32) };
+    let res = client
+        .post(&url)
+        .json(&req)
+        .send()
+        .await
+        .context("Failed to send worker heartbeat")?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
     }
+    res.error_for_status().context("Worker heartbeat rejected")?;
+    Ok(true)
+}
+
+/// 注销：网关把本 worker 仍持有的任务退回队列
+pub async fn deregister_worker(client: &Client, config: &StepflowConfig) -> Result<()> {
+    let url = format!("{}/{}", registry_url(&config.gateway_server_url), config.worker_id);
+    client
+        .delete(&url)
+        .send()
+        .await
+        .context("Failed to deregister worker")?
+        .error_for_status()
+        .context("Deregister rejected")?;
+    Ok(())
 }
 
 pub async fn execute_task(
@@ -62,8 +156,45 @@ pub async fn execute_task(
 ) -> Result<()> {
     let start = Instant::now();
 
+    // 执行期间持续上报心跳（任务要求心跳时按上限的一半周期），
+    // 网关不再承认该任务时（执行被取消 / 终止）触发取消，工具随之中止
+    let cancellation = CancellationToken::new();
+    let heartbeat = {
+        let (client, config) = (client.clone(), config.clone());
+        let (run_id, state_name) = (task.run_id.clone(), task.state_name.clone());
+        let cancellation = cancellation.clone();
+        let period = task
+            .heartbeat_seconds
+            .filter(|&s| s > 0)
+            .map_or(DEFAULT_HEARTBEAT_PERIOD, |secs| Duration::from_millis(secs as u64 * 500));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match send_heartbeat(&client, &config, &run_id, &state_name).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        eprintln!("[{}] Task {run_id}/{state_name} revoked, cancelling", config.worker_id);
+                        cancellation.cancel();
+                        break;
+                    }
+                    Err(e) => eprintln!("[{}] Heartbeat error: {e:#}", config.worker_id),
+                }
+            }
+        })
+    };
+
     // ✅ 执行工具（仅传 parameters）
-    let result = registry.execute(&task.tool_type, task.parameters.clone()).await;
+    let result = registry
+        .execute_with_cancellation(&task.tool_type, task.parameters.clone(), cancellation.clone())
+        .await;
+    heartbeat.abort();
+    if cancellation.is_cancelled() {
+        // 任务已被撤销，结果不再上报
+        println!("tool cancelled: {}/{}", task.run_id, task.state_name);
+        return Ok(());
+    }
     println!("tool result: {:?}", result);
 
     let (status, result) = match result {
@@ -1,4 +1,7 @@
 pub mod client;
+pub mod registration;
+pub mod stream;
 pub mod worker;
 
+pub use stream::start_stream_worker;
 pub use worker::start_queue_worker;
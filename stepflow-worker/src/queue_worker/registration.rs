@@ -0,0 +1,87 @@
+use crate::queue_worker::client;
+use reqwest::Client;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use stepflow_common::config::StepflowConfig;
+use tokio::{task::JoinHandle, time::sleep};
+
+/// 注册失败（如网关尚未启动）后的重试间隔
+const REGISTER_RETRY: Duration = Duration::from_secs(2);
+
+/// 本 worker 在网关注册表中的登记：启动时注册、定期存活心跳、退出时注销
+pub struct Registration {
+    client: Arc<Client>,
+    config: StepflowConfig,
+    in_flight: AtomicUsize,
+}
+
+/// 执行中的任务；drop 时计数减一
+pub struct InFlightTask(Arc<Registration>);
+
+impl Drop for InFlightTask {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Registration {
+    pub fn new(client: Arc<Client>, config: StepflowConfig) -> Arc<Self> {
+        Arc::new(Self { client, config, in_flight: AtomicUsize::new(0) })
+    }
+
+    /// 登记一个开始执行的任务，随存活心跳上报
+    pub fn track(self: &Arc<Self>) -> InFlightTask {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightTask(self.clone())
+    }
+
+    /// 后台注册并按 `WORKER_TTL_SECS` 的三分之一周期发送存活心跳；
+    /// 网关不再认识本 worker（被判定失联后下线）时重新注册
+    pub fn spawn_liveness(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = self.clone();
+        let period = Duration::from_secs((this.config.worker_ttl_secs / 3).max(1));
+        tokio::spawn(async move {
+            let worker_id = &this.config.worker_id;
+            let mut registered = false;
+            loop {
+                if !registered {
+                    match client::register_worker(&this.client, &this.config).await {
+                        Ok(()) => {
+                            println!("[{worker_id}] Registered with gateway");
+                            registered = true;
+                        }
+                        Err(e) => {
+                            eprintln!("[{worker_id}] Register error: {e:#}");
+                            sleep(REGISTER_RETRY).await;
+                            continue;
+                        }
+                    }
+                }
+                sleep(period).await;
+                let in_flight = this.in_flight.load(Ordering::Relaxed);
+                match client::send_liveness(&this.client, &this.config, in_flight).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        eprintln!("[{worker_id}] Gateway no longer knows this worker, re-registering");
+                        registered = false;
+                    }
+                    Err(e) => eprintln!("[{worker_id}] Worker heartbeat error: {e:#}"),
+                }
+            }
+        })
+    }
+
+    /// 优雅退出：注销，持有的任务由网关退回队列
+    pub async fn deregister(&self) {
+        let worker_id = &self.config.worker_id;
+        match client::deregister_worker(&self.client, &self.config).await {
+            Ok(()) => println!("[{worker_id}] Deregistered from gateway"),
+            Err(e) => eprintln!("[{worker_id}] Deregister error: {e:#}"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::queue_worker::{client, registration::Registration};
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use reqwest::Client;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use stepflow_common::config::StepflowConfig;
+use stepflow_dto::dto::worker::{WorkerStreamEvent, WorkerStreamRequest};
+use stepflow_tool::core::registry::ToolRegistry;
+use tokio::{sync::Notify, time::sleep};
+use tokio_tungstenite::tungstenite::Message;
+
+/// 连接断开后的重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// 空闲槽位：执行完任务时归还，由当前连接以 credit 告知网关
+struct Slots {
+    free: Mutex<usize>,
+    freed: Notify,
+}
+
+/// 启动基于推送的 Worker：与网关保持 WebSocket 连接，按空闲槽位接收任务，断开后自动重连；
+/// Ctrl-C 时注销后返回
+pub async fn start_stream_worker(
+    config: StepflowConfig,
+    client: Arc<Client>,
+    registry: Arc<ToolRegistry>,
+    concurrency: usize,
+) -> Result<()> {
+    let slots = Arc::new(Slots { free: Mutex::new(concurrency), freed: Notify::new() });
+    let registration = Registration::new(client.clone(), config.clone());
+    let liveness = registration.spawn_liveness();
+
+    let reconnect = async {
+        loop {
+            match run_stream(&config, &client, &registry, &slots, &registration).await {
+                Ok(()) => eprintln!("[{}] Task stream closed, reconnecting", config.worker_id),
+                Err(e) => eprintln!("[{}] Task stream error: {e:#}", config.worker_id),
+            }
+            sleep(RECONNECT_DELAY).await;
+        }
+    };
+    tokio::select! {
+        _ = reconnect => {}
+        res = tokio::signal::ctrl_c() => res?,
+    }
+
+    // 断开推送连接后注销，仍在执行的任务退回队列交给其他 worker
+    liveness.abort();
+    registration.deregister().await;
+    Ok(())
+}
+
+/// `http(s)://host/v1/worker` → `ws(s)://host/v1/worker/stream`
+pub fn stream_url(gateway_server_url: &str) -> String {
+    let base = gateway_server_url.trim_end_matches('/');
+    let base = match base.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => base.to_string(),
+    };
+    format!("{base}/stream")
+}
+
+async fn run_stream(
+    config: &StepflowConfig,
+    client: &Arc<Client>,
+    registry: &Arc<ToolRegistry>,
+    slots: &Arc<Slots>,
+    registration: &Arc<Registration>,
+) -> Result<()> {
+    let (socket, _) = tokio_tungstenite::connect_async(stream_url(&config.gateway_server_url))
+        .await
+        .context("Failed to connect task stream")?;
+    let (mut sink, mut stream) = socket.split();
+
+    // 网关认为本连接还可推送的任务数；始终不超过真实的空闲槽位
+    let mut granted = *slots.free.lock().unwrap();
+    let subscribe = WorkerStreamRequest::Subscribe {
+        worker_id: config.worker_id.clone(),
+        capabilities: config.capabilities.clone(),
+        task_queues: config.task_queues.clone(),
+        slots: granted as u32,
+    };
+    sink.send(Message::Text(serde_json::to_string(&subscribe)?)).await?;
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e).context("Task stream read failed"),
+                };
+                match serde_json::from_str::<WorkerStreamEvent>(&text).context("Invalid stream event")? {
+                    WorkerStreamEvent::Subscribed { queues } => {
+                        println!("[{}] Subscribed to {queues:?} with {granted} slots", config.worker_id);
+                    }
+                    WorkerStreamEvent::Task(res) => {
+                        let Some(task) = client::task_details(res)? else { continue };
+                        granted = granted.saturating_sub(1);
+                        *slots.free.lock().unwrap() -= 1;
+                        println!("[{}] Task received: {}", config.worker_id, task.state_name);
+
+                        let (client, config, registry, slots) =
+                            (client.clone(), config.clone(), registry.clone(), slots.clone());
+                        let in_flight = registration.track();
+                        tokio::spawn(async move {
+                            let _in_flight = in_flight;
+                            if let Err(e) = client::execute_task(&client, &config, &registry, task).await {
+                                eprintln!("[{}] Task execution error: {e:#}", config.worker_id);
+                            }
+                            *slots.free.lock().unwrap() += 1;
+                            slots.freed.notify_one();
+                        });
+                    }
+                    WorkerStreamEvent::Error { message } => anyhow::bail!("Gateway rejected stream: {message}"),
+                }
+            }
+            _ = slots.freed.notified() => {
+                let extra = slots.free.lock().unwrap().saturating_sub(granted);
+                if extra > 0 {
+                    granted += extra;
+                    let credit = WorkerStreamRequest::Credit { slots: extra as u32 };
+                    sink.send(Message::Text(serde_json::to_string(&credit)?)).await?;
+                }
+            }
+        }
+    }
+}
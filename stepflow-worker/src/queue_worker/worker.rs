@@ -1,4 +1,4 @@
-use crate::queue_worker::client;
+use crate::queue_worker::{client, registration::Registration};
 use stepflow_common::config::StepflowConfig;
 use anyhow::Result;
 use reqwest::Client;
@@ -7,7 +7,7 @@ use std::sync::Arc;
 use tokio::{sync::Semaphore, time::sleep};
 use std::time::Duration;
 
-/// 启动基于轮询的 Worker
+/// 启动基于长轮询的 Worker：每个并发槽位各自轮询；Ctrl-C 时注销后返回
 pub async fn start_queue_worker(
     config: StepflowConfig,
     client: Arc<Client>,
@@ -15,12 +15,15 @@ pub async fn start_queue_worker(
     concurrency: usize,
 ) -> Result<()> {
     let semaphore = Arc::new(Semaphore::new(concurrency));
+    let registration = Registration::new(client.clone(), config.clone());
+    let liveness = registration.spawn_liveness();
 
     for _i in 0..concurrency {
         let permit = semaphore.clone().acquire_owned().await?;
         let registry = registry.clone();
         let client = client.clone();
         let config = config.clone(); // 支持多实例独立 ID，如 worker-0, worker-1
+        let registration = registration.clone();
 
         tokio::spawn(async move {
             let _permit = permit;
@@ -28,13 +31,14 @@ pub async fn start_queue_worker(
                 match client::poll_for_task(&client, &config).await {
                     Ok(Some((worker_id, task))) => {
                         println!("[{worker_id}] Task received: {}", task.state_name);
+                        let _in_flight = registration.track();
                         if let Err(e) = client::execute_task(&client, &config, &registry, task).await {
                             eprintln!("[{worker_id}] Task execution error: {e:#}");
                         }
                     }
                     Ok(None) => {
+                        // 网关已挂起到超时才返回空响应，直接发起下一次长轮询
                         println!("[{}] No task available", config.worker_id);
-                        sleep(Duration::from_secs(2)).await;
                     }
                     Err(e) => {
                         eprintln!("[{}] Polling error: {e:#}", config.worker_id);
@@ -45,6 +49,9 @@ pub async fn start_queue_worker(
         });
     }
 
-    futures::future::pending::<()>().await;
+    // 优雅退出：注销后网关不再派发新任务，仍在执行的任务退回队列交给其他 worker
+    tokio::signal::ctrl_c().await?;
+    liveness.abort();
+    registration.deregister().await;
     Ok(())
 }
\ No newline at end of file
@@ -2,10 +2,10 @@ use anyhow::Result;
 use std::sync::Arc;
 use reqwest::Client;
 
-use stepflow_common::config::{StepflowConfig, StepflowExecMode};
+use stepflow_common::config::{StepflowConfig, StepflowExecMode, WorkerTransport};
 use stepflow_tool::registry::globals::GLOBAL_TOOL_REGISTRY;
 use stepflow_eventbus::global::get_global_event_bus;
-use crate::{start_queue_worker, start_event_worker};
+use crate::{start_queue_worker, start_stream_worker, start_event_worker};
 
 pub async fn launch_worker() -> Result<()> {
     // 加载配置
@@ -19,7 +19,10 @@ pub async fn launch_worker() -> Result<()> {
     match config.exec_mode {
         StepflowExecMode::Polling => {
             tracing::info!("🚀 Starting in polling mode... {}", config.summary());
-            start_queue_worker(config, client, registry, concurrency).await?;
+            match config.worker_transport {
+                WorkerTransport::Poll => start_queue_worker(config, client, registry, concurrency).await?,
+                WorkerTransport::Stream => start_stream_worker(config, client, registry, concurrency).await?,
+            }
         }
         StepflowExecMode::EventDriven => {
             tracing::info!("🚀 Starting in event-driven mode... {}", config.summary());